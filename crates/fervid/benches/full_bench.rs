@@ -9,7 +9,35 @@ fn full_compile_benchmark(c: &mut Criterion) {
             b.iter_batched(
                 || (),
                 |_| {
-                    let _ = fervid::compile_sync_naive(component, true);
+                    let _ = fervid::compile_sync_naive(
+                        component,
+                        fervid::CompileOptions {
+                            is_prod: true,
+                            filename: None,
+                            scope_id: "",
+                            source_map: false,
+                            cancellation_token: Default::default(),
+                            cache_handlers: false,
+                            hoist_static: false,
+                            keep_style_string: false,
+                            ssr: false,
+                            hmr: None,
+                            module_format: Default::default(),
+                            runtime_module: None,
+                            comments: None,
+                            custom_elements: Vec::new(),
+                            declaration: false,
+                            template_generation_mode: None,
+                            emit_options: Default::default(),
+                            ident_prefixes: Default::default(),
+                            style_preprocessor: None,
+                            compile_styles: false,
+                            style_injector: None,
+                            merge_styles: false,
+                            minify_styles: false,
+                            preserve_jsx: false,
+                        },
+                    );
                 },
                 criterion::BatchSize::SmallInput,
             );
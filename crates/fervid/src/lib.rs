@@ -7,7 +7,7 @@
 //! </p>
 //!
 //! ```
-//! use swc_core::ecma::ast::Expr;
+//! use fervid::swc::Expr;
 //!
 //! let input = r#"
 //!   <template><div>hello world</div></template>
@@ -17,8 +17,19 @@
 //! let (remaining_input, sfc) = fervid::parser::core::parse_sfc(input).unwrap();
 //! 
 //! // Do the necessary transformations
-//! let is_prod = true;
-//! let transform_result = fervid_transform::transform_sfc(sfc, is_prod);
+//! let transform_options = fervid_transform::structs::TransformSfcOptions {
+//!     is_prod: true,
+//!     filename: Some("src/App.vue"),
+//!     scope_id: "abcd1234",
+//!     comments: false,
+//!     declaration: false,
+//!     template_generation_mode: None,
+//!     custom_elements: Vec::new(),
+//!     ident_prefixes: Default::default(),
+//!     cancellation_token: Default::default(),
+//!     css_modules: Vec::new(),
+//! };
+//! let transform_result = fervid_transform::transform_sfc(sfc, transform_options);
 //!
 //! // Create the context and generate the template block
 //! let mut ctx = fervid_codegen::CodegenContext::with_bindings_helper(transform_result.bindings_helper);
@@ -43,10 +54,181 @@ extern crate lazy_static;
 
 pub mod parser;
 
-use fervid_codegen::CodegenContext;
+use fervid_codegen::{CodegenContext, HmrOptions, StyleInjectionOptions};
+pub use fervid_codegen::{parse_es_version, EmitOptions, HmrTarget, ModuleFormat, StyleInjector};
 pub use fervid_core::*;
-use fervid_transform::transform_sfc;
-use swc_core::ecma::ast::Expr;
+use fervid_css::{
+    find_child_root_selectors, merge_styles, needs_preprocessor, CompileStyleOptions,
+    StyleSourceMapOptions,
+};
+pub use fervid_css::{
+    MergedStyle, MergedStyleOffset, PreprocessedStyle, StyleError, StylePreprocessor,
+};
+pub use fervid_transform::structs::TemplateAnalysis;
+use fervid_transform::{
+    script::utils::contains_jsx, structs::TransformSfcOptions, template::collect_template_classes,
+    transform_sfc, TransformSfcResult,
+};
+use swc_core::{
+    common::{comments::SingleThreadedComments, BytePos},
+    ecma::ast::{Expr, Module},
+};
+
+// Compiler structs hold no `Rc`s or thread-local caches,
+// which makes them usable from multi-threaded runtimes (e.g. async web servers).
+// This is checked at compile time so that a regression becomes a build error.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<SfcDescriptor>();
+    assert_send_sync::<BindingsHelper>();
+    assert_send_sync::<TransformSfcResult>();
+    assert_send_sync::<CodegenContext>();
+    assert_send_sync::<fervid_parser::ParseError>();
+};
+
+/// Options for [`compile_sync_naive`]
+#[derive(Debug, Default)]
+pub struct CompileOptions<'o> {
+    /// Are we compiling for DEV or PROD
+    pub is_prod: bool,
+    /// Path to the SFC, usually relative to the project root.
+    /// In DEV it is used for the `__file` and `__name` component properties
+    pub filename: Option<&'o str>,
+    /// Scope ID of the component without `data-v-`, e.g. `abcd1234`.
+    /// It is used to name CSS variables produced by `v-bind()` in `<style>`
+    /// and for the scoped attributes in the SSR output
+    pub scope_id: &'o str,
+    /// Whether to generate a source map for the compiled code
+    pub source_map: bool,
+    /// Aborts a stale compilation, e.g. when an editor already sent a newer version of the file.
+    /// Cancelled compilation returns an error
+    pub cancellation_token: CancellationToken,
+    /// Cache the event handlers in the render function, so that the child components
+    /// do not re-render because of new handler functions
+    pub cache_handlers: bool,
+    /// Hoist the static element subtrees and constant props objects out of the render function,
+    /// so that they are created only once
+    pub hoist_static: bool,
+    /// Keep a static `style` attribute as a string instead of parsing it into an object,
+    /// e.g. `style: "color: red"` instead of `style: { color: "red" }`
+    pub keep_style_string: bool,
+    /// Generate the `ssrRender` function, which renders the template to a string on the server,
+    /// instead of the VDOM `render` function
+    pub ssr: bool,
+    /// Emit the hot module replacement code for the given bundler.
+    /// The `__hmrId` is derived from [`CompileOptions::filename`]. Ignored in PROD
+    pub hmr: Option<HmrTarget>,
+    /// Generate an ES module or a CommonJS module, e.g. for the tools which cannot load ESM.
+    /// Only the Vue imports and the default export are affected
+    pub module_format: ModuleFormat,
+    /// Module to import the Vue helpers from, defaults to `vue`.
+    /// E.g. `@vue/runtime-dom` or `vue/dist/vue.runtime.esm-bundler.js`
+    pub runtime_module: Option<&'o str>,
+    /// Keep the `<template>` comments and render them as comment vnodes.
+    /// Defaults to `true` in DEV and `false` in PROD
+    pub comments: Option<bool>,
+    /// Tag names of the native custom elements, e.g. `my-button`.
+    /// A name ending with `*` matches by prefix, e.g. `ion-*`.
+    /// Such tags are compiled as plain elements and keep their attributes as is
+    pub custom_elements: Vec<FervidAtom>,
+    /// Generate the TypeScript declaration of the component, see [`CompileResult::declaration`]
+    pub declaration: bool,
+    /// Render the template inline in `setup` or by a separate `render` function.
+    /// Defaults to inline in PROD when `<script setup>` is present, see [`TemplateGenerationMode`]
+    pub template_generation_mode: Option<TemplateGenerationMode>,
+    /// How the compiled module is printed, e.g. minified or ASCII-only.
+    /// Defaults to the pretty-printed output
+    pub emit_options: EmitOptions,
+    /// Names and prefixes of the generated identifiers, e.g. `_ctx`, `_cache` or `_hoisted_`.
+    /// Change them when they collide with the user code
+    pub ident_prefixes: IdentPrefixes,
+    /// Compiles `<style lang="scss">` and other languages to CSS, see [`StylePreprocessor`].
+    /// Without it, compiling a style block in a language other than CSS is an error
+    pub style_preprocessor: Option<&'o dyn StylePreprocessor>,
+    /// Compile the `<style>` blocks and return them in [`CompileResult::styles`],
+    /// e.g. for a bundler plugin to emit them as `.css` assets.
+    /// The styles are minified with [`CompileOptions::minify_styles`]
+    /// and get a source map with [`CompileOptions::source_map`].
+    /// In DEV, the scoped selectors which can only match the root of a child component
+    /// are reported in [`CompiledStyleBlock::diagnostics`]
+    pub compile_styles: bool,
+    /// Embed the compiled styles in the module and inject them at runtime with the injector,
+    /// e.g. for the widgets or custom elements without a CSS pipeline, see [`StyleInjector`]
+    pub style_injector: Option<StyleInjector>,
+    /// Merge the compiled `<style>` blocks into [`CompileResult::merged_style`]
+    /// and deduplicate the identical rules. The injected styles are merged as well
+    pub merge_styles: bool,
+    /// Minify the compiled styles, i.e. strip the whitespace and comments
+    /// and merge the longhand properties and rules where it is safe.
+    /// It is independent from [`EmitOptions::minify`], which only applies to the module code
+    pub minify_styles: bool,
+    /// Keep the JSX of `<script lang="jsx">` and `<script lang="tsx">` in the output,
+    /// e.g. for `@vue/babel-plugin-jsx` to compile it later.
+    /// Without it, JSX in a script is an error, because fervid does not compile it
+    pub preserve_jsx: bool,
+}
+
+/// Result of [`compile_sync_naive`]
+#[derive(Debug)]
+pub struct CompileResult {
+    /// The compiled module code
+    pub code: String,
+    /// Source map in JSON format, present when [`CompileOptions::source_map`] was set
+    pub source_map: Option<String>,
+    /// Template information, e.g. whether it is fully static and thus can be cached
+    pub template_analysis: TemplateAnalysis,
+    /// The `.d.ts` code describing the props, emits, slots and exposed bindings of the component,
+    /// present when [`CompileOptions::declaration`] was set
+    pub declaration: Option<String>,
+    /// The compiled `<style>` blocks in order of appearance,
+    /// empty unless [`CompileOptions::compile_styles`] was set
+    pub styles: Vec<CompiledStyleBlock>,
+    /// All the `<style>` blocks merged into one stylesheet with a combined source map,
+    /// present when [`CompileOptions::merge_styles`] was set
+    pub merged_style: Option<MergedStyle>,
+    /// Warnings and errors in the template and the styles which did not fail the compilation.
+    /// The diagnostics of the compiled styles are in [`CompiledStyleBlock::diagnostics`]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Result of [`compile_module_sync_naive`]
+#[derive(Debug)]
+pub struct CompileModuleResult {
+    /// The compiled module before it is emitted
+    pub module: Module,
+    /// The comments attached to the module, e.g. the `/*#__PURE__*/` annotations.
+    /// Pass them to the emitter to keep the generated code tree-shakeable
+    pub comments: SingleThreadedComments,
+    /// Template information, e.g. whether it is fully static and thus can be cached
+    pub template_analysis: TemplateAnalysis,
+    /// The declaration module, present when [`CompileOptions::declaration`] was set
+    pub declaration: Option<Module>,
+    /// The compiled `<style>` blocks, see [`CompileResult::styles`]
+    pub styles: Vec<CompiledStyleBlock>,
+    /// The merged `<style>` blocks, see [`CompileResult::merged_style`]
+    pub merged_style: Option<MergedStyle>,
+    /// See [`CompileResult::diagnostics`]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A compiled `<style>` block, see [`CompileResult::styles`]
+#[derive(Debug, Clone)]
+pub struct CompiledStyleBlock {
+    /// The compiled CSS
+    pub code: String,
+    /// Source map in JSON format, present when [`CompileOptions::source_map`] was set
+    pub map: Option<String>,
+    /// Whether the block is `<style scoped>`.
+    /// Its selectors then have the `[data-v-{scope_id}]` attribute
+    pub scoped: bool,
+    /// Name of the CSS module binding, e.g. `$style` for `<style module>`
+    pub module: Option<FervidAtom>,
+    /// Original class names and their hashed counterparts, present for a `<style module>`
+    pub module_mapping: Option<Vec<(String, String)>>,
+    /// Warnings found during the style compilation, e.g. the deprecated deep selectors
+    pub diagnostics: Vec<Diagnostic>,
+}
 
 /// Naive implementation of the SFC compilation, meaning that:
 /// - it handles the standard flow without plugins;
@@ -54,7 +236,73 @@ use swc_core::ecma::ast::Expr;
 /// - it does not report errors.
 /// This implementation is mostly meant for the WASM and NAPI beta.
 /// Later on, it will be replaced with a stable API.
-pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String> {
+///
+/// The [`CompileOptions::cancellation_token`] is checked between the compilation phases.
+pub fn compile_sync_naive(source: &str, options: CompileOptions) -> Result<CompileResult, String> {
+    let CompileModuleResult {
+        module: sfc_module,
+        comments,
+        template_analysis,
+        declaration,
+        styles,
+        merged_style,
+        diagnostics,
+    } = compile_module_sync_naive(source, &options)?;
+
+    let sfc_module = options.emit_options.downlevel(sfc_module);
+
+    let declaration = declaration.map(|declaration| {
+        CodegenContext::stringify_with_options(&source, &declaration, &options.emit_options, None)
+    });
+
+    if options.source_map {
+        let filename = options.filename.unwrap_or("anonymous.vue");
+        let (code, source_map) = CodegenContext::stringify_with_source_map(
+            &source,
+            filename,
+            &sfc_module,
+            &options.emit_options,
+            Some(&comments),
+        );
+
+        return Ok(CompileResult {
+            code,
+            source_map: Some(source_map),
+            template_analysis,
+            declaration,
+            styles,
+            merged_style,
+            diagnostics,
+        });
+    }
+
+    let compiled_code = CodegenContext::stringify_with_options(
+        &source,
+        &sfc_module,
+        &options.emit_options,
+        Some(&comments),
+    );
+
+    Ok(CompileResult {
+        code: compiled_code,
+        source_map: None,
+        template_analysis,
+        declaration,
+        styles,
+        merged_style,
+        diagnostics,
+    })
+}
+
+/// Same as [`compile_sync_naive`], but returns the SWC module instead of the code,
+/// e.g. to pass it to an SWC-based bundler without re-parsing the emitted string.
+///
+/// The emitter options of [`CompileOptions`] are not used, and
+/// the original spans are kept, so that the source map can be generated later.
+pub fn compile_module_sync_naive(
+    source: &str,
+    options: &CompileOptions,
+) -> Result<CompileModuleResult, String> {
     // let (_, mut sfc) = parse_sfc(&source).map_err(|err| {
     //     return err.to_string();
     // })?;
@@ -64,24 +312,404 @@ pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String>
         return err.to_string();
     })?;
 
+    macro_rules! check_cancelled {
+        () => {
+            if options.cancellation_token.is_cancelled() {
+                return Err(String::from("Compilation was cancelled"));
+            }
+        };
+    }
+
+    check_cancelled!();
+
+    // The Vue runtime cannot render the JSX left in the output
+    let has_jsx = [&sfc.script_setup, &sfc.script_legacy]
+        .into_iter()
+        .flatten()
+        .any(|script| script.lang.is_jsx() && contains_jsx(&script.content));
+    if has_jsx && !options.preserve_jsx {
+        return Err(String::from(
+            "JSX in <script lang=\"jsx\"> or <script lang=\"tsx\"> is not compiled. \
+            Set `preserve_jsx` to leave it for a downstream transform",
+        ));
+    }
+
+    // Slot content does not get the scoped styles unless `:slotted()` is used
+    let has_scoped_styles = sfc.styles.iter().any(|style| style.is_scoped);
+    let has_slotted_styles = sfc.styles.iter().any(|style| {
+        style.is_scoped
+            && (style.content.contains(":slotted(") || style.content.contains("::v-slotted("))
+    });
+
+    // In DEV, the scoped selectors are checked against the classes used in the template
+    let template_classes = match sfc.template {
+        Some(ref template) if options.compile_styles && !options.is_prod && has_scoped_styles => {
+            collect_template_classes(&template.roots)
+        }
+        _ => None,
+    };
+
+    // `<style module>` class names are needed to resolve `$style` in the template
+    let mut diagnostics = Vec::new();
+    let styles = compile_styles(
+        source,
+        &sfc.styles,
+        template_classes.as_deref(),
+        options,
+        &mut diagnostics,
+    )?;
+    let css_modules = to_css_modules(&styles);
+    let merged_style = if options.merge_styles {
+        let merged_style = merge_styles(
+            styles
+                .iter()
+                .map(|style| (style.code.as_str(), style.map.as_deref())),
+            options.minify_styles,
+        )
+        .map_err(|err| err.message)?;
+        Some(merged_style)
+    } else {
+        None
+    };
+    let style_injection = options.style_injector.as_ref().map(|injector| {
+        let css = match merged_style {
+            Some(ref merged_style) => FervidAtom::from(merged_style.code.as_str()),
+            None => {
+                let css: Vec<&str> = styles.iter().map(|style| style.code.as_str()).collect();
+                FervidAtom::from(css.join("\n"))
+            }
+        };
+
+        StyleInjectionOptions {
+            css,
+            injector: injector.to_owned(),
+        }
+    });
+    let styles = if options.compile_styles {
+        styles
+    } else {
+        Vec::new()
+    };
+    check_cancelled!();
+
     // TODO Return template used variables as a part of transformation result.
     // Also `used_imports`? `vue_imports`? User imports?
-    let transform_result = transform_sfc(sfc, is_prod);
+    let transform_result = transform_sfc(
+        sfc,
+        TransformSfcOptions {
+            is_prod: options.is_prod,
+            filename: options.filename,
+            scope_id: options.scope_id,
+            comments: options.comments.unwrap_or(!options.is_prod),
+            declaration: options.declaration,
+            template_generation_mode: options.template_generation_mode,
+            custom_elements: options.custom_elements.to_owned(),
+            ident_prefixes: options.ident_prefixes.to_owned(),
+            cancellation_token: options.cancellation_token.clone(),
+            css_modules,
+        },
+    );
+    check_cancelled!();
 
+    let template_analysis = transform_result.template_analysis;
+    let declaration = transform_result.declaration;
     let mut ctx = CodegenContext::with_bindings_helper(transform_result.bindings_helper);
+    ctx.cache_handlers = options.cache_handlers;
+    ctx.hoist_static = options.hoist_static;
+    ctx.keep_style_string = options.keep_style_string;
+    ctx.no_slotted = has_scoped_styles && !has_slotted_styles;
+    ctx.module_format = options.module_format;
+    ctx.runtime_module = options.runtime_module.map(FervidAtom::from);
+    ctx.style_injection = style_injection;
+    if let (false, Some(hmr_target)) = (options.is_prod, options.hmr) {
+        ctx.hmr = Some(HmrOptions::new(options.filename, source, hmr_target));
+    }
+    if has_scoped_styles && !options.scope_id.is_empty() {
+        ctx.scope_id = Some(FervidAtom::from(format!("data-v-{}", options.scope_id)));
+    }
 
-    let template_expr: Option<Expr> = transform_result.template_block.map(|template_block| {
-        ctx.generate_sfc_template(&template_block)
-    });
+    let sfc_module = if options.ssr {
+        let ssr_body = transform_result
+            .template_block
+            .map(|template_block| ctx.generate_ssr_template(&template_block));
 
-    let sfc_module = ctx.generate_module(
-        template_expr,
-        transform_result.module,
-        transform_result.exported_obj,
-        transform_result.setup_fn,
-    );
+        ctx.generate_ssr_module(
+            ssr_body,
+            transform_result.module,
+            transform_result.exported_obj,
+            transform_result.setup_fn,
+        )
+    } else {
+        let template_expr: Option<Expr> = transform_result
+            .template_block
+            .map(|template_block| ctx.generate_sfc_template(&template_block));
+
+        ctx.generate_module(
+            template_expr,
+            transform_result.module,
+            transform_result.exported_obj,
+            transform_result.setup_fn,
+        )
+    };
+    check_cancelled!();
+
+    diagnostics.append(&mut ctx.bindings_helper.diagnostics);
+
+    Ok(CompileModuleResult {
+        module: sfc_module,
+        comments: ctx.comments(),
+        template_analysis,
+        declaration,
+        styles,
+        merged_style,
+        diagnostics,
+    })
+}
+
+/// Compiles the `<style>` blocks in order of appearance.
+/// Without [`CompileOptions::compile_styles`], [`CompileOptions::style_injector`]
+/// and [`CompileOptions::merge_styles`] only the `<style module>` blocks are compiled,
+/// because their class names are needed anyway.
+/// Such a block in a language other than CSS is skipped with a warning in `diagnostics`
+/// when there is no [`CompileOptions::style_preprocessor`].
+///
+/// With `template_classes`, the scoped styles are also checked for the selectors which
+/// can only match the root elements of the child components, see [`find_child_root_selectors`].
+fn compile_styles(
+    source: &str,
+    styles: &[SfcStyleBlock],
+    template_classes: Option<&[FervidAtom]>,
+    options: &CompileOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<CompiledStyleBlock>, String> {
+    let is_cancelled = || options.cancellation_token.is_cancelled();
+    let compiles_all =
+        options.compile_styles || options.merge_styles || options.style_injector.is_some();
+
+    styles
+        .iter()
+        .filter(|style| {
+            if compiles_all {
+                return true;
+            }
+            if style.module.is_none() {
+                return false;
+            }
+
+            // Only the class names are needed, so a missing preprocessor is not fatal
+            if needs_preprocessor(&style.lang) && options.style_preprocessor.is_none() {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::StyleModuleNotCompiled(style.lang.to_owned()),
+                    span: style.span,
+                });
+                return false;
+            }
+
+            true
+        })
+        .map(|style| {
+            let source_map = options.source_map.then(|| {
+                let (line, column) = line_column(source, style.span.lo);
+                StyleSourceMapOptions {
+                    filename: options.filename.unwrap_or("anonymous.vue"),
+                    line,
+                    column,
+                }
+            });
+
+            let compiled = fervid_css::compile_style(
+                &style.content,
+                CompileStyleOptions {
+                    scope_id: options.scope_id,
+                    lang: &style.lang,
+                    preprocessor: options.style_preprocessor,
+                    is_scoped: style.is_scoped,
+                    is_module: style.module.is_some(),
+                    is_prod: options.is_prod,
+                    minify: options.minify_styles,
+                    is_cancelled: Some(&is_cancelled),
+                    source_map,
+                    span: style.span,
+                },
+            )
+            .map_err(|err| err.message)?;
+
+            let mut diagnostics = compiled.diagnostics;
+            if let (true, Some(template_classes)) = (
+                style.is_scoped && !needs_preprocessor(&style.lang),
+                template_classes,
+            ) {
+                diagnostics.extend(find_child_root_selectors(
+                    &style.content,
+                    style.span,
+                    template_classes,
+                ));
+            }
+
+            Ok(CompiledStyleBlock {
+                code: compiled.code,
+                map: compiled.map,
+                scoped: style.is_scoped,
+                module: style.module.to_owned(),
+                module_mapping: compiled.module_mapping,
+                diagnostics,
+            })
+        })
+        .collect()
+}
+
+/// Collects the hashed class names of the `<style module>` blocks.
+/// The class names are hashed using the [`CompileOptions::scope_id`].
+fn to_css_modules(styles: &[CompiledStyleBlock]) -> Vec<SfcCssModule> {
+    styles
+        .iter()
+        .filter_map(|style| {
+            let name = style.module.as_ref()?;
+
+            Some(SfcCssModule {
+                name: name.to_owned(),
+                classes: style
+                    .module_mapping
+                    .iter()
+                    .flatten()
+                    .map(|(local_name, hashed_name)| {
+                        (
+                            FervidAtom::from(local_name.as_str()),
+                            FervidAtom::from(hashed_name.as_str()),
+                        )
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Zero-based line and column of a position in the SFC.
+/// The parser spans start at `BytePos(1)`, which is the first character of the source
+fn line_column(source: &str, pos: BytePos) -> (u32, u32) {
+    let offset = (pos.0 as usize).saturating_sub(1).min(source.len());
+    let before = &source[..offset];
+
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let column = before[line_start..].chars().count();
+
+    (line as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_errors_on_jsx_unless_preserved() {
+        let source =
+            r#"<script setup lang="tsx">const Foo = () => <div class="foo">{42}</div></script>"#;
+
+        let err = compile_sync_naive(source, CompileOptions::default())
+            .expect_err("JSX should not be compiled silently");
+        assert!(err.contains("preserve_jsx"));
+
+        let result = compile_sync_naive(
+            source,
+            CompileOptions {
+                preserve_jsx: true,
+                emit_options: EmitOptions {
+                    minify: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .expect("Preserved JSX should compile");
+        assert!(result
+            .code
+            .contains(r#"const Foo=()=><div class="foo">{42}</div>"#));
+
+        // `lang="tsx"` without JSX is a regular TypeScript script
+        let source = r#"<script setup lang="tsx">const foo: number = 42</script>"#;
+        assert!(compile_sync_naive(source, CompileOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn it_returns_script_imports_used_in_template() {
+        let source = r#"<script>
+import { formatDate } from './utils'
+</script>
+<script setup>
+let msg = 'hello'
+</script>
+<template><div>{{ formatDate(msg) }}</div></template>"#;
+
+        let result = compile_sync_naive(
+            source,
+            CompileOptions {
+                template_generation_mode: Some(TemplateGenerationMode::RenderFn),
+                emit_options: EmitOptions {
+                    minify: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .expect("SFC should compile");
+
+        // `<script>` imports are read from `$setup` in the render function
+        assert!(result.code.contains("return{msg,formatDate}"));
+    }
+
+    #[test]
+    fn it_minifies_styles_independently() {
+        let source = r#"<template><div class="foo"></div></template>
+<style>.foo { color: red; }</style>"#;
+
+        let compile = |minify_styles, minify| {
+            compile_sync_naive(
+                source,
+                CompileOptions {
+                    compile_styles: true,
+                    minify_styles,
+                    emit_options: EmitOptions {
+                        minify,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .expect("Styles should compile")
+            .styles
+            .remove(0)
+            .code
+        };
+
+        assert_eq!(compile(true, false), ".foo{color:red}");
+        assert!(compile(false, true).contains('\n'));
+    }
+
+    #[test]
+    fn it_warns_on_style_module_without_preprocessor() {
+        let source = r#"<template><div :class="$style.foo"></div></template>
+<style module lang="scss">.foo { .bar { color: red } }</style>"#;
 
-    let compiled_code = CodegenContext::stringify(&source, &sfc_module, false);
+        let result = compile_sync_naive(source, CompileOptions::default())
+            .expect("<style module lang=\"scss\"> should compile without a preprocessor");
+        assert!(result.styles.is_empty());
+        assert!(matches!(
+            result.diagnostics[..],
+            [Diagnostic {
+                kind: DiagnosticKind::StyleModuleNotCompiled(ref lang),
+                ..
+            }] if lang == "scss"
+        ));
 
-    Ok(compiled_code)
+        // Compiling the styles themselves still needs the preprocessor
+        let err = compile_sync_naive(
+            source,
+            CompileOptions {
+                compile_styles: true,
+                ..Default::default()
+            },
+        );
+        assert!(err.is_err());
+    }
 }
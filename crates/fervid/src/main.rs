@@ -3,7 +3,7 @@ extern crate swc_ecma_codegen;
 extern crate swc_ecma_parser;
 use std::time::Instant;
 
-use fervid::compile_sync_naive;
+use fervid::{compile_sync_naive, CompileOptions};
 
 fn main() {
     let n = Instant::now();
@@ -14,8 +14,36 @@ fn main() {
 fn test_real_compilation() {
     let test = include_str!("../benches/fixtures/input.vue");
 
-    let compiled_code = match compile_sync_naive(test, true) {
-        Ok(result) => result,
+    let compiled_code = match compile_sync_naive(
+        test,
+        CompileOptions {
+            is_prod: true,
+            filename: Some("input.vue"),
+            scope_id: "",
+            source_map: false,
+            cancellation_token: Default::default(),
+            cache_handlers: false,
+            hoist_static: false,
+            keep_style_string: false,
+            ssr: false,
+            hmr: None,
+            module_format: Default::default(),
+            runtime_module: None,
+            comments: None,
+            custom_elements: Vec::new(),
+            declaration: false,
+            template_generation_mode: None,
+            emit_options: Default::default(),
+            ident_prefixes: Default::default(),
+            style_preprocessor: None,
+            compile_styles: false,
+            style_injector: None,
+            merge_styles: false,
+            minify_styles: false,
+            preserve_jsx: false,
+        },
+    ) {
+        Ok(result) => result.code,
         Err(e) => std::panic::panic_any(e)
     };
 
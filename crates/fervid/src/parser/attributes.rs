@@ -87,6 +87,7 @@ fn parse_vanilla_attr<'i>(
             out.push(AttributeOrBinding::RegularAttribute {
                 name: attr_name.into(),
                 value: attr_name.into(),
+                span: Default::default(),
             });
             Ok((input, ()))
         }
@@ -100,6 +101,7 @@ fn parse_vanilla_attr<'i>(
             out.push(AttributeOrBinding::RegularAttribute {
                 name: attr_name.into(),
                 value: attr_value.into(),
+                span: Default::default(),
             });
 
             Ok((input, ()))
@@ -285,6 +287,7 @@ fn parse_directive<'i>(
                 is_camel,
                 is_prop,
                 is_attr,
+                span: Default::default(),
             }));
         }
         "on" => {
@@ -301,6 +304,8 @@ fn parse_directive<'i>(
                     }
                 }),
                 modifiers,
+                is_cacheable: false,
+                span: Default::default(),
             }));
         }
         "if" => {
@@ -365,6 +370,7 @@ fn parse_directive<'i>(
                         argument,
                         value: *model_binding,
                         modifiers,
+                        update_handler: None,
                         span: DUMMY_SP, // TODO
                     });
                 }
@@ -12,7 +12,7 @@ use swc_core::ecma::ast::Module;
 
 use super::attributes::parse_attributes;
 use super::ecma::{parse_js, parse_js_module};
-use super::html_utils::{classify_element_kind, html_name, space0, TagKind};
+use super::html_utils::{classify_element_kind, html_tag_name, space0, TagKind};
 use fervid_core::{
     fervid_atom, AttributeOrBinding, ElementKind, ElementNode, FervidAtom, Interpolation, Node,
     SfcCustomBlock, SfcDescriptor, SfcScriptBlock, SfcScriptLang, SfcStyleBlock, SfcTemplateBlock,
@@ -106,7 +106,7 @@ fn parse_root_block<'a>(
 
     // Get `lang` attribute, which is common for all the Vue root blocks
     let lang = starting_tag.attributes.iter().find_map(|attr| match attr {
-        AttributeOrBinding::RegularAttribute { name, value } if name == "lang" => {
+        AttributeOrBinding::RegularAttribute { name, value, .. } if name == "lang" => {
             Some(value.to_owned())
         }
         _ => None,
@@ -218,6 +218,18 @@ fn parse_root_block<'a>(
         )
     });
 
+    // `<style module>` is bound as `$style`
+    let module = starting_tag.attributes.iter().find_map(|attr| match attr {
+        AttributeOrBinding::RegularAttribute { name, value, .. } if name == "module" => {
+            if value.is_empty() {
+                Some(FervidAtom::from("$style"))
+            } else {
+                Some(value.to_owned())
+            }
+        }
+        _ => None,
+    });
+
     // Check self-closing, ignore such styles
     if is_self_closing {
         return Ok(input);
@@ -231,6 +243,9 @@ fn parse_root_block<'a>(
         lang: lang.into(),
         content: content.into(),
         is_scoped,
+        module,
+        css_vars: vec![],
+        span: DUMMY_SP, // TODO
     });
 
     Ok(input)
@@ -239,7 +254,7 @@ fn parse_root_block<'a>(
 fn parse_element_starting_tag(input: &str) -> IResult<&str, (StartingTag, bool)> {
     let (input, (_, tag_name, (attributes, directives), _, ending_bracket)) = tuple((
         tag("<"),
-        html_name,
+        html_tag_name,
         parse_attributes,
         space0,
         alt((tag(">"), tag("/>"))),
@@ -267,7 +282,7 @@ fn parse_element_starting_tag(input: &str) -> IResult<&str, (StartingTag, bool)>
 
 fn parse_element_end_tag(input: &str) -> IResult<&str, &str> {
     // eat any tag, because it may not match the start tag according to spec
-    delimited(tag("</"), html_name, preceded(space0, tag(">")))(input)
+    delimited(tag("</"), html_tag_name, preceded(space0, tag(">")))(input)
 }
 
 fn parse_interpolation_node(input: &str) -> IResult<&str, Node> {
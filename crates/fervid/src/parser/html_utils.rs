@@ -49,6 +49,11 @@ pub fn html_name(input: &str) -> IResult<&str, &str> {
   take_while1(|x: char| is_valid_name_char(x) || x == '-')(input)
 }
 
+/// Tag names may also contain dots for the namespaced components, e.g. `<Form.Input>`
+pub fn html_tag_name(input: &str) -> IResult<&str, &str> {
+  take_while1(|x: char| is_valid_name_char(x) || x == '-' || x == '.')(input)
+}
+
 pub fn space1(input: &str) -> IResult<&str, &str> {
   take_while1(is_space_char)(input)
 }
@@ -1,25 +1,25 @@
 use fervid_core::{
-    fervid_atom, AttributeOrBinding, FervidAtom, StrOrExpr, VBindDirective, VOnDirective,
-    VueImports,
+    fervid_atom, AttributeOrBinding, AttributeSpan, BindingTypes, FervidAtom, SetupBinding,
+    StrOrExpr, TemplateGenerationMode, VBindDirective, VOnDirective, VueImports,
 };
 use regex::Regex;
 use swc_core::{
-    common::{Span, Spanned, DUMMY_SP},
+    common::{Span, Spanned},
     ecma::ast::{
-        ArrayLit, ArrowExpr, BinExpr, BinaryOp, BlockStmt, BlockStmtOrExpr, CallExpr, Callee,
-        ComputedPropName, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, ObjectLit, Prop, PropName,
-        PropOrSpread, Str,
+        ArrayLit, ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, BindingIdent, BlockStmt,
+        BlockStmtOrExpr, Bool, CallExpr, Callee, ComputedPropName, Expr, ExprOrSpread, Ident,
+        KeyValueProp, Lit, ObjectLit, ParenExpr, Pat, PatOrExpr, Prop, PropName, PropOrSpread,
+        RestPat, SpreadElement, Str,
     },
 };
 
 use crate::{
     context::CodegenContext,
-    utils::{str_to_propname, to_pascalcase},
+    utils::{cache_access, str_to_propname, to_camelcase, to_pascalcase},
 };
 
 lazy_static! {
-    static ref CSS_RE: Regex =
-        Regex::new(r"(?U)([a-zA-Z_-][a-zA-Z_0-9-]*):\s*(.+)(?:;|$)").unwrap();
+    static ref CSS_COMMENT_RE: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
 }
 
 /// Type alias for all the directives not handled as attributes.
@@ -67,30 +67,50 @@ impl CodegenContext {
         // Hints on what was processed and what to do next
         let mut result_hints = GenerateAttributesResultHints::default();
 
+        // `class` and `style` are generated before `v-bind="obj"` and `v-on="obj"`
+        // to keep the order in which the props are merged
+        macro_rules! flush_class_and_style {
+            () => {
+                result_hints.class_patch_flag |= self.generate_class_bindings(
+                    class_regular_attr.take(),
+                    class_bound.take(),
+                    out,
+                );
+                result_hints.style_patch_flag |= self.generate_style_bindings(
+                    style_regular_attr.take(),
+                    style_bound.take(),
+                    out,
+                );
+            };
+        }
+
         for attribute in attributes {
-            // TODO Spans
-            let span = DUMMY_SP;
+            // Generated keys point at the attribute name, generated values point at its value
+            let AttributeSpan {
+                name: name_span,
+                value: span,
+            } = attribute.span();
 
             match attribute {
                 // First, we check the special case: `class` and `style` attributes
                 // class
-                AttributeOrBinding::RegularAttribute { name, value } if name == "class" => {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "class" => {
                     class_regular_attr = Some((value, span));
                 }
 
                 // style
-                AttributeOrBinding::RegularAttribute { name, value } if name == "style" => {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "style" => {
                     style_regular_attr = Some((value, span));
                 }
 
                 // Any regular attribute will be added as an object entry,
                 // where key is attribute name and value is attribute value as string literal
-                AttributeOrBinding::RegularAttribute { name, value } => {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
                     // let raw = Some(Atom::from(value.as_ref()));
 
                     out.push(PropOrSpread::Prop(Box::from(Prop::KeyValue(
                         KeyValueProp {
-                            key: str_to_propname(&name, span),
+                            key: str_to_propname(&name, name_span),
                             value: Box::from(Expr::Lit(Lit::Str(Str {
                                 span,
                                 value: value.to_owned(),
@@ -108,6 +128,8 @@ impl CodegenContext {
                 AttributeOrBinding::VBind(VBindDirective {
                     argument: Some(StrOrExpr::Str(argument)),
                     value,
+                    is_prop: false,
+                    is_attr: false,
                     ..
                 }) if argument == "class" => {
                     class_bound = Some((value.to_owned(), span));
@@ -117,12 +139,16 @@ impl CodegenContext {
                 AttributeOrBinding::VBind(VBindDirective {
                     argument: Some(StrOrExpr::Str(argument)),
                     value,
+                    is_prop: false,
+                    is_attr: false,
                     ..
                 }) if argument == "style" => {
                     style_bound = Some((value.to_owned(), span));
                 }
 
-                // `v-bind` directive without argument needs its own processing
+                // `v-bind` directive without argument needs its own processing.
+                // It is kept as a spread, and the props are then merged in order,
+                // see [`CodegenContext::generate_props_expr`]
                 AttributeOrBinding::VBind(v_bind) if v_bind.argument.is_none() => {
                     // IN:
                     // v-on="ons" v-bind="bounds" @click=""
@@ -131,11 +157,36 @@ impl CodegenContext {
                     // _mergeProps(_toHandlers(_ctx.ons), _ctx.bounds, {
                     //   onClick: _cache[1] || (_cache[1] = () => {})
                     // })
+                    flush_class_and_style!();
+                    out.push(PropOrSpread::Spread(SpreadElement {
+                        dot3_token: span,
+                        expr: v_bind.value.to_owned(),
+                    }));
                     result_hints.v_bind_no_arg = Some(v_bind);
                 }
 
-                // `v-on` directive without event name also needs its own processing
+                // `v-on` directive without event name also needs its own processing,
+                // it is converted to `_toHandlers(ons)`
                 AttributeOrBinding::VOn(v_on) if v_on.event.is_none() => {
+                    if let Some(ref handler) = v_on.handler {
+                        flush_class_and_style!();
+                        out.push(PropOrSpread::Spread(SpreadElement {
+                            dot3_token: span,
+                            expr: Box::new(Expr::Call(CallExpr {
+                                span,
+                                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                                    span,
+                                    sym: self.get_and_add_import_ident(VueImports::ToHandlers),
+                                    optional: false,
+                                }))),
+                                args: vec![ExprOrSpread {
+                                    spread: None,
+                                    expr: handler.to_owned(),
+                                }],
+                                type_args: None,
+                            })),
+                        }));
+                    }
                     result_hints.v_on_no_event = Some(v_on);
                 }
 
@@ -143,6 +194,9 @@ impl CodegenContext {
                 AttributeOrBinding::VBind(VBindDirective {
                     argument: Some(argument),
                     value,
+                    is_camel,
+                    is_prop,
+                    is_attr,
                     ..
                 }) => {
                     // Transform the raw expression
@@ -154,26 +208,85 @@ impl CodegenContext {
                     result_hints.props_patch_flag =
                         result_hints.props_patch_flag || was_transformed;
 
+                    // `.prop` forces a DOM property binding and `.attr` an attribute binding,
+                    // the runtime distinguishes them by the `.` and `^` key prefixes
+                    let prefix = if *is_prop {
+                        Some(".")
+                    } else if *is_attr {
+                        Some("^")
+                    } else {
+                        None
+                    };
+
                     let key = match argument {
-                        StrOrExpr::Str(s) => str_to_propname(s, span),
+                        StrOrExpr::Str(s) => {
+                            let mut key = String::with_capacity(s.len() + 1);
+                            if let Some(prefix) = prefix {
+                                key.push_str(prefix);
+                            }
+                            if *is_camel {
+                                let _ = to_camelcase(s, &mut key); // ignore fault
+                            } else {
+                                key.push_str(s);
+                            }
+
+                            str_to_propname(&key, name_span)
+                        }
                         StrOrExpr::Expr(expr) => {
                             // Dynamic prop needs a `_normalizeProps` call
                             // TODO Take from patch flags?
                             result_hints.needs_normalize_props = true;
 
-                            // `[key_transformed || ""]`
-                            PropName::Computed(ComputedPropName {
+                            // `key_transformed || ""`
+                            let mut key_expr = Box::from(Expr::Bin(BinExpr {
                                 span,
-                                expr: Box::from(Expr::Bin(BinExpr {
+                                op: BinaryOp::LogicalOr,
+                                left: expr.to_owned(), // ?
+                                right: Box::from(Expr::Lit(Lit::Str(Str {
+                                    span,
+                                    value: FervidAtom::from(""),
+                                    raw: None,
+                                }))),
+                            }));
+
+                            // `_camelize(key_transformed || "")`
+                            if *is_camel {
+                                key_expr = Box::from(Expr::Call(CallExpr {
                                     span,
-                                    op: BinaryOp::LogicalOr,
-                                    left: expr.to_owned(), // ?
-                                    right: Box::from(Expr::Lit(Lit::Str(Str {
+                                    callee: Callee::Expr(Box::new(Expr::Ident(Ident {
                                         span,
-                                        value: FervidAtom::from(""),
+                                        sym: self.get_and_add_import_ident(VueImports::Camelize),
+                                        optional: false,
+                                    }))),
+                                    args: vec![ExprOrSpread {
+                                        spread: None,
+                                        expr: key_expr,
+                                    }],
+                                    type_args: None,
+                                }));
+                            }
+
+                            // `"." + (key_transformed || "")`
+                            if let Some(prefix) = prefix {
+                                key_expr = Box::from(Expr::Bin(BinExpr {
+                                    span,
+                                    op: BinaryOp::Add,
+                                    left: Box::from(Expr::Lit(Lit::Str(Str {
+                                        span,
+                                        value: FervidAtom::from(prefix),
                                         raw: None,
                                     }))),
-                                })),
+                                    right: Box::from(Expr::Paren(ParenExpr {
+                                        span,
+                                        expr: key_expr,
+                                    })),
+                                }));
+                            }
+
+                            // `[key_transformed || ""]`
+                            PropName::Computed(ComputedPropName {
+                                span: name_span,
+                                expr: key_expr,
                             })
                         }
                     };
@@ -191,9 +304,9 @@ impl CodegenContext {
                     event: Some(event),
                     handler,
                     modifiers,
+                    is_cacheable,
+                    ..
                 }) => {
-                    // TODO Use _cache
-
                     // Transform or default to () => {}
                     // The patch flag does not apply to v-on
                     let (transformed, _was_transformed) = handler
@@ -209,77 +322,120 @@ impl CodegenContext {
                         })
                         .unwrap_or_else(|| (Box::new(empty_arrow_expr(span)), false));
 
-                    // To generate as an array of `["modifier1", "modifier2"]`
-                    let modifiers: Vec<Option<ExprOrSpread>> = modifiers
-                        .iter()
-                        .map(|modifier| {
-                            Some(ExprOrSpread {
-                                spread: None,
-                                expr: Box::from(Expr::Lit(Lit::Str(Str {
-                                    span,
-                                    value: modifier.to_owned(),
-                                    raw: None,
-                                }))),
-                            })
-                        })
-                        .collect();
-
-                    let handler_expr = if modifiers.len() != 0 {
-                        let with_modifiers_import =
-                            self.get_and_add_import_ident(VueImports::WithModifiers);
-
-                        // `_withModifiers(transformed, ["modifier"]))`
-                        Box::new(Expr::Call(CallExpr {
-                            span,
-                            callee: Callee::Expr(Box::from(Expr::Ident(Ident {
-                                span,
-                                sym: with_modifiers_import,
-                                optional: false,
-                            }))),
-                            args: vec![
-                                ExprOrSpread {
-                                    expr: transformed,
-                                    spread: None,
-                                },
-                                ExprOrSpread {
-                                    expr: Box::from(Expr::Array(ArrayLit {
-                                        span,
-                                        elems: modifiers,
-                                    })),
-                                    spread: None,
-                                },
-                            ],
-                            type_args: None,
-                        }))
+                    // Modifiers are split into runtime (`withModifiers`), key (`withKeys`)
+                    // and event option (`capture`, `once`, `passive`) ones
+                    let static_event = match event {
+                        StrOrExpr::Str(event) => Some(&**event),
+                        StrOrExpr::Expr(_) => None,
+                    };
+                    let modifiers = VOnModifiers::classify(static_event, modifiers);
+
+                    // `_cache[0] || (_cache[0] = handler)`.
+                    // Runtime constants never change and are not cached
+                    let should_cache = *is_cacheable
+                        && self.cache_handlers
+                        && !self.is_setup_const_handler(&transformed);
+
+                    // Cached method handlers must call the latest method, so they are wrapped:
+                    // `(...args) => (_ctx.handleClick && _ctx.handleClick(...args))`
+                    let is_method = matches!(*transformed, Expr::Ident(_) | Expr::Member(_));
+                    let mut handler_expr = if should_cache && is_method {
+                        Box::new(wrap_method_handler(transformed, span))
                     } else {
-                        // No modifiers, leave expression the same
                         transformed
                     };
 
-                    // TODO Cache
+                    // `_withModifiers(transformed, ["modifier"])`
+                    if !modifiers.runtime.is_empty() {
+                        handler_expr = self.wrap_in_modifiers_helper(
+                            VueImports::WithModifiers,
+                            handler_expr,
+                            &modifiers.runtime,
+                            span,
+                        );
+                    }
+
+                    // `_withKeys(transformed, ["key"])`, only applies to keyboard events
+                    let applies_keys = static_event.map_or(true, is_keyboard_event);
+                    if !modifiers.keys.is_empty() && applies_keys {
+                        handler_expr = self.wrap_in_modifiers_helper(
+                            VueImports::WithKeys,
+                            handler_expr,
+                            &modifiers.keys,
+                            span,
+                        );
+                    }
+
+                    if should_cache {
+                        handler_expr = self.wrap_in_cache(handler_expr, span);
+                    }
+
+                    // Mouse buttons use other events, e.g. `@click.right` -> `onContextmenu`
+                    let is_click = static_event.map_or(false, |e| e.eq_ignore_ascii_case("click"));
+                    let mouse_event = if !is_click {
+                        None
+                    } else if modifiers.runtime.iter().any(|m| *m == "right") {
+                        Some("contextmenu")
+                    } else if modifiers.runtime.iter().any(|m| *m == "middle") {
+                        Some("mouseup")
+                    } else {
+                        None
+                    };
 
-                    // TODO Dynamic events are hard, but similar to `v-on`
-                    // IN:
-                    // foo="bar" :[dynamic]="hi" @[dynamic]="" @[dynamic2]="" v-on="whatever"
-                    //
-                    // OUT:
-                    // _mergeProps({
-                    //     foo: "bar",
-                    //     [_ctx.dynamic || ""]: _ctx.hi
-                    // }, {
-                    //     [_toHandlerKey(_ctx.dynamic)]: _cache[4] || (_cache[4] = () => {})
-                    // }, {
-                    //     [_toHandlerKey(_ctx.dynamic2)]: _cache[5] || (_cache[5] = () => {})
-                    // }, _toHandlers(whatever, true))
+                    // `@click.once.capture` -> `onClickOnceCapture`
+                    let mut event_options = String::new();
+                    for option in modifiers.event_options.iter() {
+                        let _ = to_pascalcase(option, &mut event_options);
+                    }
+
+                    let key = match event {
+                        StrOrExpr::Str(event) => {
+                            let mut event_name =
+                                event_name_to_handler(mouse_event.unwrap_or(event));
+                            event_name.push_str(&event_options);
+                            str_to_propname(&event_name, name_span)
+                        }
 
-                    // IDEA: Do the generation here, and put resulting `Expr`s in the return struct
+                        // `@[event].once` -> `[_toHandlerKey(event) + "Once"]`
+                        StrOrExpr::Expr(event) => {
+                            let mut key_expr = Box::new(Expr::Call(CallExpr {
+                                span: name_span,
+                                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                                    span: name_span,
+                                    sym: self.get_and_add_import_ident(VueImports::ToHandlerKey),
+                                    optional: false,
+                                }))),
+                                args: vec![ExprOrSpread {
+                                    spread: None,
+                                    expr: event.to_owned(),
+                                }],
+                                type_args: None,
+                            }));
+
+                            if !event_options.is_empty() {
+                                key_expr = Box::new(Expr::Bin(BinExpr {
+                                    span: name_span,
+                                    op: BinaryOp::Add,
+                                    left: key_expr,
+                                    right: Box::new(Expr::Lit(Lit::Str(Str {
+                                        span: name_span,
+                                        value: FervidAtom::from(event_options),
+                                        raw: None,
+                                    }))),
+                                }));
+                            }
 
-                    let event_name = event_name_to_handler(event);
+                            PropName::Computed(ComputedPropName {
+                                span: name_span,
+                                expr: key_expr,
+                            })
+                        }
+                    };
 
                     // e.g. `onClick: _ctx.handleClick` or `onClick: _withModifiers(() => {}, ["stop"])
                     out.push(PropOrSpread::Prop(Box::from(Prop::KeyValue(
                         KeyValueProp {
-                            key: str_to_propname(&event_name, span),
+                            key,
                             value: handler_expr,
                         },
                     ))));
@@ -289,14 +445,150 @@ impl CodegenContext {
             }
         }
 
-        result_hints.class_patch_flag =
-            self.generate_class_bindings(class_regular_attr, class_bound, out);
-        result_hints.style_patch_flag =
-            self.generate_style_bindings(style_regular_attr, style_bound, out);
+        flush_class_and_style!();
 
         result_hints
     }
 
+    /// Generates the props object of an element or a component.
+    ///
+    /// When the props contain `v-bind="obj"` or `v-on="obj"`, they are merged in the original order:
+    /// `<div a="1" v-bind="obj" :b="b">` becomes `_mergeProps({ a: "1" }, obj, { b: b })`.
+    /// Elements preserve the case of the `v-on="obj"` events, i.e. `_toHandlers(obj, true)`.
+    pub fn generate_props_expr(
+        &mut self,
+        props: Vec<PropOrSpread>,
+        is_component: bool,
+        span: Span,
+    ) -> Expr {
+        let has_spreads = props
+            .iter()
+            .any(|prop| matches!(prop, PropOrSpread::Spread(_)));
+        if !has_spreads {
+            return Expr::Object(ObjectLit { span, props });
+        }
+
+        let to_handlers_ident = VueImports::ToHandlers.as_atom();
+        let mut merge_args: Vec<ExprOrSpread> = Vec::new();
+        let mut current_obj: Vec<PropOrSpread> = Vec::new();
+
+        macro_rules! flush_current_obj {
+            () => {
+                if !current_obj.is_empty() {
+                    merge_args.push(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(Expr::Object(ObjectLit {
+                            span,
+                            props: std::mem::take(&mut current_obj),
+                        })),
+                    });
+                }
+            };
+        }
+
+        for prop in props {
+            match prop {
+                PropOrSpread::Prop(_) => current_obj.push(prop),
+                PropOrSpread::Spread(SpreadElement { mut expr, .. }) => {
+                    flush_current_obj!();
+
+                    // `_toHandlers(obj)` -> `_toHandlers(obj, true)`
+                    if let Expr::Call(ref mut call_expr) = *expr {
+                        let is_to_handlers = match call_expr.callee {
+                            Callee::Expr(ref callee) => matches!(
+                                **callee,
+                                Expr::Ident(ref ident) if ident.sym == to_handlers_ident
+                            ),
+                            _ => false,
+                        };
+                        if is_to_handlers && !is_component {
+                            call_expr.args.push(ExprOrSpread {
+                                spread: None,
+                                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
+                            });
+                        }
+                    }
+
+                    merge_args.push(ExprOrSpread { spread: None, expr });
+                }
+            }
+        }
+        flush_current_obj!();
+
+        // A single `v-bind="obj"` or `v-on="obj"` is not merged with anything
+        if merge_args.len() == 1 {
+            return *merge_args.remove(0).expr;
+        }
+
+        // `_mergeProps(...)`
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::MergeProps),
+                optional: false,
+            }))),
+            args: merge_args,
+            type_args: None,
+        })
+    }
+
+    /// Wraps the props of a vnode for the runtime to normalize them:
+    /// - the object with dynamic keys, e.g. `:[foo]="bar"`,
+    ///   becomes `_normalizeProps({ [foo || ""]: bar })`;
+    /// - a single `v-bind="obj"` becomes `_normalizeProps(_guardReactiveProps(obj))`,
+    ///   so that the reactive `obj` is not mutated by the renderer;
+    /// - merged props, e.g. `_mergeProps(...)` or `_toHandlers(...)`, are already normalized.
+    pub fn normalize_props_expr(&mut self, props_expr: Expr) -> Expr {
+        let needs_guard = match props_expr {
+            Expr::Object(ref obj) => {
+                let has_dynamic_keys = obj.props.iter().any(|prop| {
+                    matches!(
+                        prop,
+                        PropOrSpread::Prop(prop) if matches!(
+                            **prop,
+                            Prop::KeyValue(KeyValueProp {
+                                key: PropName::Computed(_),
+                                ..
+                            })
+                        )
+                    )
+                });
+                if !has_dynamic_keys {
+                    return props_expr;
+                }
+                false
+            }
+            Expr::Call(_) => return props_expr,
+            _ => true,
+        };
+
+        let span = props_expr.span();
+        let mut call = |import: VueImports, arg: Expr| {
+            Expr::Call(CallExpr {
+                span,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: self.get_and_add_import_ident(import),
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(arg),
+                }],
+                type_args: None,
+            })
+        };
+
+        // `_normalizeProps(_guardReactiveProps(obj))`
+        let props_expr = if needs_guard {
+            call(VueImports::GuardReactiveProps, props_expr)
+        } else {
+            props_expr
+        };
+        call(VueImports::NormalizeProps, props_expr)
+    }
+
     /// Process `class` attribute. We may have a regular one, a bound one, both or neither.
     /// Returns `true` when there were JavaScript bindings
     fn generate_class_bindings(
@@ -471,7 +763,15 @@ impl CodegenContext {
 
             // `style`
             (Some((regular_value, span)), None) => {
-                expr = Some(Expr::Object(generate_regular_style(regular_value, span)));
+                expr = Some(if self.keep_style_string {
+                    Expr::Lit(Lit::Str(Str {
+                        span,
+                        value: regular_value.to_owned(),
+                        raw: None,
+                    }))
+                } else {
+                    Expr::Object(generate_regular_style(regular_value, span))
+                });
             }
 
             // `:style`
@@ -515,21 +815,25 @@ impl CodegenContext {
     }
 }
 
-fn generate_regular_style(style: &str, span: Span) -> ObjectLit {
+/// Parses the static `style` into an object, e.g. `color: red; margin: 0` -> `{ color: "red", margin: "0" }`.
+/// Same as `parseStringStyle` of Vue, comments are removed and `;` inside parens is not a delimiter,
+/// e.g. `background: url(data:image/png;base64,...)`
+pub(crate) fn generate_regular_style(style: &str, span: Span) -> ObjectLit {
     let mut result = ObjectLit {
         span,
         props: Vec::with_capacity(4), // pre-allocate more just in case
     };
 
-    for mat in CSS_RE.captures_iter(style) {
-        let Some(style_name) = mat.get(1).map(|v| v.as_str().trim()) else {
-            continue;
-        };
-        let Some(style_value) = mat.get(2).map(|v| v.as_str().trim()) else {
+    let style = CSS_COMMENT_RE.replace_all(style, "");
+
+    for declaration in split_style_declarations(&style) {
+        let Some((style_name, style_value)) = declaration.split_once(':') else {
             continue;
         };
 
-        if style_name.len() == 0 || style_value.len() == 0 {
+        let style_name = style_name.trim();
+        let style_value = style_value.trim();
+        if style_name.is_empty() || style_value.is_empty() {
             continue;
         }
 
@@ -550,20 +854,316 @@ fn generate_regular_style(style: &str, span: Span) -> ObjectLit {
     result
 }
 
+/// Splits the style by `;` which are not inside the parens
+fn split_style_declarations(style: &str) -> Vec<&str> {
+    let mut declarations = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, c) in style.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                declarations.push(&style[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.push(&style[start..]);
+
+    declarations
+}
+
+/// Coerces the constant values of the DOM attributes the same way the browser would see them:
+/// - boolean attributes, e.g. `:disabled="true"` becomes `disabled: ""`,
+///   and `:disabled="false"` is removed altogether;
+/// - enumerated attributes, e.g. `:draggable="true"` becomes `draggable: "true"`.
+///
+/// Only applies to the plain elements, because components may expect the original values
+pub(crate) fn coerce_dom_attributes(props: &mut Vec<PropOrSpread>) {
+    props.retain_mut(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return true;
+        };
+        let Prop::KeyValue(KeyValueProp { key, value }) = prop.as_mut() else {
+            return true;
+        };
+        let name = match key {
+            PropName::Ident(ident) => &ident.sym,
+            PropName::Str(s) => &s.value,
+            _ => return true,
+        };
+
+        let is_boolean = is_boolean_attr(name);
+        if !is_boolean && !is_enumerated_attr(name) {
+            return true;
+        }
+
+        // `null` and `undefined` remove the attribute in both cases
+        let coerced = match value.as_ref() {
+            Expr::Lit(Lit::Null(_)) => None,
+            Expr::Ident(ident) if ident.sym == "undefined" => None,
+            Expr::Lit(Lit::Bool(Bool { value: b, .. })) if is_boolean => b.then(String::new),
+            Expr::Lit(Lit::Num(n)) if is_boolean => {
+                (n.value != 0.0 && !n.value.is_nan()).then(String::new)
+            }
+            Expr::Lit(Lit::Str(_)) if is_boolean => Some(String::new()),
+            Expr::Lit(Lit::Bool(Bool { value: b, .. })) => Some(b.to_string()),
+            _ => return true,
+        };
+
+        let Some(coerced) = coerced else {
+            return false;
+        };
+
+        *value = Box::new(Expr::Lit(Lit::Str(Str {
+            span: value.span(),
+            value: coerced.into(),
+            raw: None,
+        })));
+        true
+    });
+}
+
+/// Boolean attributes are present when `true` and absent when `false`, e.g. `disabled`.
+/// Same as `isBooleanAttr` of Vue
+pub(crate) fn is_boolean_attr(name: &str) -> bool {
+    matches!(
+        name,
+        "itemscope"
+            | "allowfullscreen"
+            | "formnovalidate"
+            | "ismap"
+            | "nomodule"
+            | "novalidate"
+            | "readonly"
+            | "async"
+            | "autofocus"
+            | "autoplay"
+            | "controls"
+            | "default"
+            | "defer"
+            | "disabled"
+            | "hidden"
+            | "inert"
+            | "loop"
+            | "open"
+            | "required"
+            | "reversed"
+            | "scoped"
+            | "seamless"
+            | "checked"
+            | "muted"
+            | "multiple"
+            | "selected"
+    )
+}
+
+/// Enumerated attributes need the explicit `"true"` or `"false"` string, e.g. `draggable`
+fn is_enumerated_attr(name: &str) -> bool {
+    matches!(name, "contenteditable" | "draggable" | "spellcheck")
+}
+
+impl CodegenContext {
+    /// Whether the handler is a `const` from `<script setup>` used directly in the inline mode,
+    /// e.g. `@click="handleClick"` with `function handleClick() {}`
+    fn is_setup_const_handler(&self, handler: &Expr) -> bool {
+        let is_inline = matches!(
+            self.bindings_helper.template_generation_mode,
+            TemplateGenerationMode::Inline
+        );
+        let Expr::Ident(ident) = handler else {
+            return false;
+        };
+
+        is_inline
+            && self
+                .bindings_helper
+                .setup_bindings
+                .iter()
+                .any(|SetupBinding(name, binding_type)| {
+                    *name == ident.sym && matches!(binding_type, BindingTypes::SetupConst)
+                })
+    }
+
+    /// Generates `_cache[0] || (_cache[0] = expr)` with a newly allocated cache index
+    pub(crate) fn wrap_in_cache(&mut self, expr: Box<Expr>, span: Span) -> Box<Expr> {
+        let cache_index = self.allocate_cache_index();
+        let cache_member = cache_access(
+            &self.bindings_helper.ident_prefixes.cache,
+            cache_index,
+            span,
+        );
+
+        Box::new(Expr::Bin(BinExpr {
+            span,
+            op: BinaryOp::LogicalOr,
+            left: Box::new(cache_member.to_owned()),
+            right: Box::new(Expr::Paren(ParenExpr {
+                span,
+                expr: Box::new(Expr::Assign(AssignExpr {
+                    span,
+                    op: AssignOp::Assign,
+                    left: PatOrExpr::Expr(Box::new(cache_member)),
+                    right: expr,
+                })),
+            })),
+        }))
+    }
+
+    /// Generates `_withModifiers(handler, ["stop", "prevent"])` or `_withKeys(handler, ["enter"])`
+    fn wrap_in_modifiers_helper(
+        &mut self,
+        helper: VueImports,
+        handler: Box<Expr>,
+        modifiers: &[&FervidAtom],
+        span: Span,
+    ) -> Box<Expr> {
+        // To generate as an array of `["modifier1", "modifier2"]`
+        let modifiers: Vec<Option<ExprOrSpread>> = modifiers
+            .iter()
+            .map(|modifier| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::from(Expr::Lit(Lit::Str(Str {
+                        span,
+                        value: (*modifier).to_owned(),
+                        raw: None,
+                    }))),
+                })
+            })
+            .collect();
+
+        Box::new(Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::from(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(helper),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    expr: handler,
+                    spread: None,
+                },
+                ExprOrSpread {
+                    expr: Box::from(Expr::Array(ArrayLit {
+                        span,
+                        elems: modifiers,
+                    })),
+                    spread: None,
+                },
+            ],
+            type_args: None,
+        }))
+    }
+}
+
 /// Converts event names with dashes to camelcase identifiers,
 /// e.g. `click` -> `onClick`, `state-changed` -> `onStateChanged`
-fn event_name_to_handler(event_name: &StrOrExpr) -> FervidAtom {
-    let StrOrExpr::Str(event_name) = event_name else {
-        todo!("event_name_to_handler is not yet implemented for dynamic events")
-    };
-
+fn event_name_to_handler(event_name: &str) -> String {
     let mut result = String::with_capacity(event_name.len() + 2);
     result.push_str("on");
 
     // ignore error, idk what to do if writing to String fails
     let _ = to_pascalcase(event_name, &mut result);
 
-    FervidAtom::from(result)
+    result
+}
+
+/// `v-on` modifiers split by the way they are compiled
+#[derive(Default)]
+struct VOnModifiers<'m> {
+    /// Checked by `withModifiers`, e.g. `stop`, `prevent`, `self`, `exact`
+    runtime: Vec<&'m FervidAtom>,
+    /// Checked by `withKeys`, e.g. `enter`, `esc`, `delete`
+    keys: Vec<&'m FervidAtom>,
+    /// Become a part of the event name, i.e. `capture`, `once` and `passive`
+    event_options: Vec<&'m FervidAtom>,
+}
+
+impl<'m> VOnModifiers<'m> {
+    /// Splits the modifiers the same way the official compiler does.
+    /// `static_event` is `None` for dynamic events, e.g. `@[event].left`.
+    fn classify(static_event: Option<&str>, modifiers: &'m [FervidAtom]) -> Self {
+        let mut result = VOnModifiers::default();
+
+        for modifier in modifiers.iter() {
+            match &**modifier {
+                "capture" | "once" | "passive" => result.event_options.push(modifier),
+
+                // `left` and `right` are keys for keyboard events and mouse buttons otherwise
+                "left" | "right" => match static_event {
+                    Some(event) if is_keyboard_event(event) => result.keys.push(modifier),
+                    Some(_) => result.runtime.push(modifier),
+                    None => {
+                        result.runtime.push(modifier);
+                        result.keys.push(modifier);
+                    }
+                },
+
+                "stop" | "prevent" | "self" | "ctrl" | "shift" | "alt" | "meta" | "exact"
+                | "middle" => result.runtime.push(modifier),
+
+                _ => result.keys.push(modifier),
+            }
+        }
+
+        result
+    }
+}
+
+fn is_keyboard_event(event: &str) -> bool {
+    event.eq_ignore_ascii_case("keyup")
+        || event.eq_ignore_ascii_case("keydown")
+        || event.eq_ignore_ascii_case("keypress")
+}
+
+/// Generates `(...args) => (handler && handler(...args))`
+fn wrap_method_handler(handler: Box<Expr>, span: Span) -> Expr {
+    let args_ident = Ident {
+        span,
+        sym: fervid_atom!("args"),
+        optional: false,
+    };
+
+    let call = Expr::Call(CallExpr {
+        span,
+        callee: Callee::Expr(handler.to_owned()),
+        args: vec![ExprOrSpread {
+            spread: Some(span),
+            expr: Box::new(Expr::Ident(args_ident.to_owned())),
+        }],
+        type_args: None,
+    });
+
+    Expr::Arrow(ArrowExpr {
+        span,
+        params: vec![Pat::Rest(RestPat {
+            span,
+            dot3_token: span,
+            arg: Box::new(Pat::Ident(BindingIdent {
+                id: args_ident,
+                type_ann: None,
+            })),
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span,
+            expr: Box::new(Expr::Bin(BinExpr {
+                span,
+                op: BinaryOp::LogicalAnd,
+                left: handler,
+                right: Box::new(call),
+            })),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
 }
 
 /// Generates () => {}
@@ -584,7 +1184,10 @@ fn empty_arrow_expr(span: Span) -> Expr {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{AttributeOrBinding, VBindDirective, VOnDirective};
+    use fervid_core::{
+        AttributeOrBinding, BindingTypes, FervidAtom, SetupBinding, StrOrExpr,
+        TemplateGenerationMode, VBindDirective, VOnDirective,
+    };
     use swc_core::{common::DUMMY_SP, ecma::ast::ObjectLit};
 
     use crate::{context::CodegenContext, test_utils::js};
@@ -600,6 +1203,7 @@ mod tests {
                 is_camel: Default::default(),
                 is_prop: Default::default(),
                 is_attr: Default::default(),
+                span: Default::default(),
             }
         };
     }
@@ -610,6 +1214,7 @@ mod tests {
             vec![AttributeOrBinding::RegularAttribute {
                 name: "class".into(),
                 value: "both regular and bound".into(),
+                span: Default::default(),
             }],
             r#"{class:"both regular and bound"}"#,
         );
@@ -633,6 +1238,7 @@ mod tests {
                 AttributeOrBinding::RegularAttribute {
                     name: "class".into(),
                     value: "both regular and bound".into(),
+                    span: Default::default(),
                 },
                 AttributeOrBinding::VBind(v_bind! {
                     argument: Some("class".into()),
@@ -649,11 +1255,109 @@ mod tests {
             vec![AttributeOrBinding::RegularAttribute {
                 name: "style".into(),
                 value: "margin: 0px; background-color: magenta".into(),
+                span: Default::default(),
             }],
             r#"{style:{margin:"0px","background-color":"magenta"}}"#,
         );
     }
 
+    #[test]
+    fn it_parses_style_regular() {
+        test_out(
+            vec![AttributeOrBinding::RegularAttribute {
+                name: "style".into(),
+                value: "color: red; /* comment; */ background: url(data:image/png;base64,abc);;"
+                    .into(),
+                span: Default::default(),
+            }],
+            r#"{style:{color:"red",background:"url(data:image/png;base64,abc)"}}"#,
+        );
+    }
+
+    #[test]
+    fn it_keeps_style_string() {
+        let mut ctx = CodegenContext::default();
+        ctx.keep_style_string = true;
+        let regular_style = AttributeOrBinding::RegularAttribute {
+            name: "style".into(),
+            value: "color: red; margin: 0".into(),
+            span: Default::default(),
+        };
+
+        // `style="color: red; margin: 0"`
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(&[regular_style.to_owned()], &mut out.props);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{style:"color: red; margin: 0"}"#
+        );
+
+        // `style="color: red; margin: 0" :style="foo"` is always merged as an object
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(
+            &[
+                regular_style,
+                AttributeOrBinding::VBind(v_bind! {
+                    argument: Some("style".into()),
+                    value: js("foo")
+                }),
+            ],
+            &mut out.props,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{style:_normalizeStyle([{color:"red",margin:"0"},foo])}"#
+        );
+    }
+
+    #[test]
+    fn it_coerces_dom_attributes() {
+        macro_rules! bound {
+            ($name: expr, $value: expr) => {
+                AttributeOrBinding::VBind(v_bind! {
+                    argument: Some($name.into()),
+                    value: js($value)
+                })
+            };
+        }
+
+        let mut ctx = CodegenContext::default();
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(
+            &[
+                AttributeOrBinding::RegularAttribute {
+                    name: "checked".into(),
+                    value: "".into(),
+                    span: Default::default(),
+                },
+                bound!("disabled", "true"),
+                bound!("hidden", "false"),
+                bound!("readonly", "null"),
+                bound!("multiple", "0"),
+                bound!("selected", "foo"),
+                bound!("draggable", "true"),
+                bound!("spellcheck", "false"),
+                bound!("contenteditable", "undefined"),
+                bound!("title", "false"),
+            ],
+            &mut out.props,
+        );
+        super::coerce_dom_attributes(&mut out.props);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{checked:"",disabled:"",selected:foo,draggable:"true",spellcheck:"false",title:false}"#
+        );
+    }
+
     #[test]
     fn it_generates_style_bound() {
         // `:style="{ backgroundColor: v ? 'yellow' : undefined }"`
@@ -673,6 +1377,7 @@ mod tests {
                 AttributeOrBinding::RegularAttribute {
                     name: "style".into(),
                     value: "margin: 0px; background-color: magenta".into(),
+                    span: Default::default(),
                 },
                 AttributeOrBinding::VBind(v_bind! {
                     argument: Some("style".into()),
@@ -721,6 +1426,8 @@ mod tests {
                 event: Some("click".into()),
                 handler: None,
                 modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r"{onClick:()=>{}}",
         );
@@ -731,6 +1438,8 @@ mod tests {
                 event: Some("multi-word-event".into()),
                 handler: None,
                 modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r"{onMultiWordEvent:()=>{}}",
         );
@@ -741,6 +1450,8 @@ mod tests {
                 event: Some("click".into()),
                 handler: Some(js("handleClick")),
                 modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r"{onClick:handleClick}",
         );
@@ -752,6 +1463,8 @@ mod tests {
         //         event: Some("click".into()),
         //         handler: Some(js("() => console.log('hello')")),
         //         modifiers: vec![],
+        //         is_cacheable: false,
+        //         span: Default::default(),
         //     })],
         //     r"{onClick:()=>console.log('hello')}"
         // );
@@ -762,6 +1475,8 @@ mod tests {
                 event: Some("click".into()),
                 handler: Some(js("() => console.log('hello')")),
                 modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r#"{onClick:()=>console.log("hello")}"#,
         );
@@ -772,6 +1487,8 @@ mod tests {
                 event: Some("click".into()),
                 handler: Some(js("$event => handleClick($event, foo, bar)")),
                 modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r"{onClick:$event=>handleClick($event,foo,bar)}",
         );
@@ -782,6 +1499,8 @@ mod tests {
                 event: Some("click".into()),
                 handler: None,
                 modifiers: vec!["stop".into(), "prevent".into(), "self".into()],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r#"{onClick:_withModifiers(()=>{},["stop","prevent","self"])}"#,
         );
@@ -792,11 +1511,353 @@ mod tests {
                 event: Some("click".into()),
                 handler: Some(js("$event => handleClick($event, foo, bar)")),
                 modifiers: vec!["stop".into()],
+                is_cacheable: false,
+                span: Default::default(),
             })],
             r#"{onClick:_withModifiers($event=>handleClick($event,foo,bar),["stop"])}"#,
         );
     }
 
+    #[test]
+    fn it_generates_v_on_key_and_option_modifiers() {
+        macro_rules! v_on {
+            ($event: literal, $handler: expr, [$($modifier: literal),*]) => {
+                AttributeOrBinding::VOn(VOnDirective {
+                    event: Some($event.into()),
+                    handler: $handler,
+                    modifiers: vec![$($modifier.into()),*],
+                    is_cacheable: false,
+                    span: Default::default(),
+                })
+            };
+        }
+
+        // @keyup.enter.stop="submit"
+        test_out(
+            vec![v_on!("keyup", Some(js("submit")), ["enter", "stop"])],
+            r#"{onKeyup:_withKeys(_withModifiers(submit,["stop"]),["enter"])}"#,
+        );
+
+        // @keydown.left="prev"
+        test_out(
+            vec![v_on!("keydown", Some(js("prev")), ["left"])],
+            r#"{onKeydown:_withKeys(prev,["left"])}"#,
+        );
+
+        // @click.right="openMenu"
+        test_out(
+            vec![v_on!("click", Some(js("openMenu")), ["right"])],
+            r#"{onContextmenu:_withModifiers(openMenu,["right"])}"#,
+        );
+
+        // @click.middle.exact="paste"
+        test_out(
+            vec![v_on!("click", Some(js("paste")), ["middle", "exact"])],
+            r#"{onMouseup:_withModifiers(paste,["middle","exact"])}"#,
+        );
+
+        // @click.once.capture="handleClick"
+        test_out(
+            vec![v_on!("click", Some(js("handleClick")), ["once", "capture"])],
+            r#"{onClickOnceCapture:handleClick}"#,
+        );
+
+        // @scroll.passive.prevent="onScroll"
+        test_out(
+            vec![v_on!(
+                "scroll",
+                Some(js("onScroll")),
+                ["passive", "prevent"]
+            )],
+            r#"{onScrollPassive:_withModifiers(onScroll,["prevent"])}"#,
+        );
+
+        // Key modifiers do not apply to non-keyboard events, e.g. @click.enter
+        test_out(
+            vec![v_on!("click", Some(js("handleClick")), ["enter"])],
+            r#"{onClick:handleClick}"#,
+        );
+    }
+
+    #[test]
+    fn it_merges_v_on_object_with_listeners() {
+        // foo="bar" @click="onClick" @[event].once="onEvent" v-on="handlers"
+        let input = vec![
+            AttributeOrBinding::RegularAttribute {
+                name: "foo".into(),
+                value: "bar".into(),
+                span: Default::default(),
+            },
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("click".into()),
+                handler: Some(js("onClick")),
+                modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
+            }),
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some(StrOrExpr::Expr(js("event"))),
+                handler: Some(js("onEvent")),
+                modifiers: vec!["once".into()],
+                is_cacheable: false,
+                span: Default::default(),
+            }),
+            AttributeOrBinding::VOn(VOnDirective {
+                event: None,
+                handler: Some(js("handlers")),
+                modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
+            }),
+        ];
+
+        let mut ctx = CodegenContext::default();
+        let mut props = vec![];
+        ctx.generate_attributes(&input, &mut props);
+        let props_expr = ctx.generate_props_expr(props, false, DUMMY_SP);
+        assert_eq!(
+            crate::test_utils::to_str(ctx.normalize_props_expr(props_expr)),
+            r#"_mergeProps({foo:"bar",onClick:onClick,[_toHandlerKey(event)+"Once"]:onEvent},_toHandlers(handlers,true))"#
+        );
+
+        // Without `v-on="handlers"`, the dynamic event name needs normalization
+        let mut props = vec![];
+        ctx.generate_attributes(&input[2..3], &mut props);
+        let props_expr = ctx.generate_props_expr(props, false, DUMMY_SP);
+        assert_eq!(
+            crate::test_utils::to_str(ctx.normalize_props_expr(props_expr)),
+            r#"_normalizeProps({[_toHandlerKey(event)+"Once"]:onEvent})"#
+        );
+    }
+
+    #[test]
+    fn it_caches_handlers() {
+        let mut ctx = CodegenContext::default();
+        ctx.cache_handlers = true;
+
+        let input = vec![
+            // @click="count++"
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("click".into()),
+                handler: Some(js("$event => (_ctx.count++)")),
+                modifiers: vec![],
+                is_cacheable: true,
+                span: Default::default(),
+            }),
+            // @keyup.stop="handleKeyup"
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("keyup".into()),
+                handler: Some(js("_ctx.handleKeyup")),
+                modifiers: vec!["stop".into()],
+                is_cacheable: true,
+                span: Default::default(),
+            }),
+            // @input="select(item)" inside `v-for="item in items"`
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("input".into()),
+                handler: Some(js("$event => (_ctx.select(item))")),
+                modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
+            }),
+        ];
+
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(&input, &mut out.props);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{onClick:_cache[0]||(_cache[0]=$event=>(_ctx.count++)),onKeyup:_cache[1]||(_cache[1]=_withModifiers((...args)=>(_ctx.handleKeyup&&_ctx.handleKeyup(...args)),["stop"])),onInput:$event=>(_ctx.select(item))}"#
+        );
+
+        // Inline mode shares the `_cache` with the render function,
+        // but the `const` functions from `<script setup>` are not cached
+        let mut ctx = CodegenContext::default();
+        ctx.cache_handlers = true;
+        ctx.bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        ctx.bindings_helper.setup_bindings = vec![SetupBinding(
+            FervidAtom::from("handleClick"),
+            BindingTypes::SetupConst,
+        )];
+        let input = vec![
+            // @click="count++"
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("click".into()),
+                handler: Some(js("$event => (count.value++)")),
+                modifiers: vec![],
+                is_cacheable: true,
+                span: Default::default(),
+            }),
+            // @dblclick="handleClick"
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("dblclick".into()),
+                handler: Some(js("handleClick")),
+                modifiers: vec![],
+                is_cacheable: true,
+                span: Default::default(),
+            }),
+        ];
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(&input, &mut out.props);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{onClick:_cache[0]||(_cache[0]=$event=>(count.value++)),onDblclick:handleClick}"#
+        );
+    }
+
+    #[test]
+    fn it_merges_object_props() {
+        // class="foo" v-bind="attrs" :id="id" v-on="handlers"
+        let input = vec![
+            AttributeOrBinding::RegularAttribute {
+                name: "class".into(),
+                value: "foo".into(),
+                span: Default::default(),
+            },
+            AttributeOrBinding::VBind(v_bind! {
+                argument: None,
+                value: js("attrs")
+            }),
+            AttributeOrBinding::VBind(v_bind! {
+                argument: Some("id".into()),
+                value: js("id")
+            }),
+            AttributeOrBinding::VOn(VOnDirective {
+                event: None,
+                handler: Some(js("handlers")),
+                modifiers: vec![],
+                is_cacheable: false,
+                span: Default::default(),
+            }),
+        ];
+
+        let mut ctx = CodegenContext::default();
+        let mut props = vec![];
+        ctx.generate_attributes(&input, &mut props);
+        assert_eq!(
+            crate::test_utils::to_str(ctx.generate_props_expr(props, false, DUMMY_SP)),
+            r#"_mergeProps({class:"foo"},attrs,{id:id},_toHandlers(handlers,true))"#
+        );
+
+        // Components do not preserve the case of the events
+        let mut props = vec![];
+        ctx.generate_attributes(&input[3..], &mut props);
+        assert_eq!(
+            crate::test_utils::to_str(ctx.generate_props_expr(props, true, DUMMY_SP)),
+            r#"_toHandlers(handlers)"#
+        );
+
+        // No merging without object props
+        let mut props = vec![];
+        ctx.generate_attributes(&input[2..3], &mut props);
+        assert_eq!(
+            crate::test_utils::to_str(ctx.generate_props_expr(props, false, DUMMY_SP)),
+            r#"{id:id}"#
+        );
+    }
+
+    #[test]
+    fn it_normalizes_props() {
+        let normalized = |input: Vec<AttributeOrBinding>| {
+            let mut ctx = CodegenContext::default();
+            let mut props = vec![];
+            ctx.generate_attributes(&input, &mut props);
+            let props_expr = ctx.generate_props_expr(props, false, DUMMY_SP);
+            crate::test_utils::to_str(ctx.normalize_props_expr(props_expr))
+        };
+
+        // v-bind="attrs"
+        assert_eq!(
+            normalized(vec![AttributeOrBinding::VBind(v_bind! {
+                argument: None,
+                value: js("attrs")
+            })]),
+            r#"_normalizeProps(_guardReactiveProps(attrs))"#
+        );
+
+        // :[key]="value"
+        assert_eq!(
+            normalized(vec![AttributeOrBinding::VBind(v_bind! {
+                argument: Some(StrOrExpr::Expr(js("key"))),
+                value: js("value")
+            })]),
+            r#"_normalizeProps({[key||""]:value})"#
+        );
+
+        // :id="id" v-bind="attrs"
+        assert_eq!(
+            normalized(vec![
+                AttributeOrBinding::VBind(v_bind! {
+                    argument: Some("id".into()),
+                    value: js("id")
+                }),
+                AttributeOrBinding::VBind(v_bind! {
+                    argument: None,
+                    value: js("attrs")
+                })
+            ]),
+            r#"_mergeProps({id:id},attrs)"#
+        );
+
+        // :id="id"
+        assert_eq!(
+            normalized(vec![AttributeOrBinding::VBind(v_bind! {
+                argument: Some("id".into()),
+                value: js("id")
+            })]),
+            r#"{id:id}"#
+        );
+    }
+
+    #[test]
+    fn it_generates_v_bind_modifiers() {
+        // :foo-bar.camel="baz" :inner-html.prop="html" :class.attr="cls"
+        test_out(
+            vec![
+                AttributeOrBinding::VBind(VBindDirective {
+                    is_camel: true,
+                    ..v_bind! {
+                        argument: Some("foo-bar".into()),
+                        value: js("baz")
+                    }
+                }),
+                AttributeOrBinding::VBind(VBindDirective {
+                    is_prop: true,
+                    ..v_bind! {
+                        argument: Some("inner-html".into()),
+                        value: js("html")
+                    }
+                }),
+                AttributeOrBinding::VBind(VBindDirective {
+                    is_attr: true,
+                    ..v_bind! {
+                        argument: Some("class".into()),
+                        value: js("cls")
+                    }
+                }),
+            ],
+            r#"{fooBar:baz,".inner-html":html,"^class":cls}"#,
+        );
+
+        // :[key].camel.prop="value"
+        test_out(
+            vec![AttributeOrBinding::VBind(VBindDirective {
+                is_camel: true,
+                is_prop: true,
+                ..v_bind! {
+                    argument: Some(StrOrExpr::Expr(js("key"))),
+                    value: js("value")
+                }
+            })],
+            r#"{["."+(_camelize(key||""))]:value}"#,
+        );
+    }
+
     fn test_out(input: Vec<AttributeOrBinding>, expected: &str) {
         let mut ctx = CodegenContext::default();
         let mut out = ObjectLit {
@@ -1,7 +1,7 @@
-use fervid_core::{fervid_atom, AttributeOrBinding, ElementNode};
+use fervid_core::{AttributeOrBinding, Diagnostic, DiagnosticKind, ElementNode};
 use swc_core::{
-    common::{Span, DUMMY_SP},
-    ecma::ast::{Expr, Ident, KeyValueProp, Lit, Number, ObjectLit, Prop, PropName, PropOrSpread},
+    common::Span,
+    ecma::ast::{Expr, PropOrSpread},
 };
 
 use crate::CodegenContext;
@@ -16,33 +16,67 @@ impl CodegenContext {
         if attributes.len() != 0 {
             let mut attrs = Vec::with_capacity(attributes.len());
             self.generate_attributes(&attributes, &mut attrs);
-            Some(Expr::Object(ObjectLit { span, props: attrs }))
+            let props_expr = self.generate_props_expr(attrs, true, span);
+            Some(self.normalize_props_expr(props_expr))
         } else {
             None
         }
     }
 
-    /// Generates the slots expression for builtins.
-    ///
-    /// Additionally adds `_: 1` to the slots object.
-    pub(crate) fn generate_builtin_slots(&mut self, element_node: &ElementNode) -> Option<Expr> {
-        let mut slots = self.generate_component_children(element_node);
-        if let Some(Expr::Object(ref mut obj)) = slots {
-            obj.props
-                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                    key: PropName::Ident(Ident {
-                        span: DUMMY_SP,
-                        sym: fervid_atom!("_"),
-                        optional: false,
-                    }),
-                    value: Box::new(Expr::Lit(Lit::Num(Number {
-                        span: DUMMY_SP,
-                        value: 1.0,
-                        raw: None,
-                    }))),
-                }))));
+    /// Generates attributes and `v-model`s for the wrapper built-ins,
+    /// i.e. `<Transition>` and `<TransitionGroup>`.
+    /// `v-model`s are generated the same way as for components.
+    pub(crate) fn generate_builtin_wrapper_attrs(
+        &mut self,
+        element_node: &ElementNode,
+    ) -> Option<Expr> {
+        let attrs = self.generate_builtin_wrapper_props(element_node);
+        if attrs.is_empty() {
+            return None;
+        }
+
+        let props_expr = self.generate_props_expr(attrs, true, element_node.span);
+        Some(self.normalize_props_expr(props_expr))
+    }
+
+    /// Same as [`CodegenContext::generate_builtin_wrapper_attrs`],
+    /// but the props are not yet combined into an expression
+    pub(crate) fn generate_builtin_wrapper_props(
+        &mut self,
+        element_node: &ElementNode,
+    ) -> Vec<PropOrSpread> {
+        let attributes = &element_node.starting_tag.attributes;
+        let v_models = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(&[][..], |directives| &directives.v_model);
+
+        let mut attrs = Vec::with_capacity(attributes.len() + 2 * v_models.len());
+        self.generate_attributes(attributes, &mut attrs);
+
+        for v_model in v_models.iter() {
+            self.generate_v_model_for_component(v_model, &mut attrs, element_node.template_scope);
         }
 
-        slots
+        attrs
+    }
+
+    /// Reports the directives on `<Teleport>`, as they cannot be applied to it
+    pub(crate) fn report_teleport_directives(&mut self, element_node: &ElementNode) {
+        let Some(ref directives) = element_node.starting_tag.directives else {
+            return;
+        };
+
+        let has_directives = directives.v_show.is_some()
+            || !directives.custom.is_empty()
+            || !directives.v_model.is_empty();
+
+        if has_directives {
+            self.bindings_helper.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::DirectiveOnTeleport,
+                span: element_node.span,
+            });
+        }
     }
 }
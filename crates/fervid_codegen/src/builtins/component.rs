@@ -4,9 +4,7 @@
 use fervid_core::{
     check_attribute_name, AttributeOrBinding, ElementNode, StrOrExpr, VBindDirective, VueImports,
 };
-use swc_core::ecma::ast::{
-    CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, ObjectLit, PropOrSpread, Str,
-};
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, PropOrSpread, Str};
 
 use crate::CodegenContext;
 
@@ -28,7 +26,7 @@ impl CodegenContext {
 
         // Expression to put as the first argument to `resolveDynamicComponent()`
         let is_attribute_expr = match component_is_attribute {
-            AttributeOrBinding::RegularAttribute { name, value } if name == "is" => {
+            AttributeOrBinding::RegularAttribute { name, value, .. } if name == "is" => {
                 Expr::Lit(Lit::Str(Str {
                     span,
                     value: value.to_owned(),
@@ -72,25 +70,27 @@ impl CodegenContext {
             self.generate_attributes(attrs_first_half, &mut attrs);
             self.generate_attributes(attrs_second_half, &mut attrs);
 
-            Some(Expr::Object(ObjectLit { span, props: attrs }))
+            let props_expr = self.generate_props_expr(attrs, true, span);
+            Some(self.normalize_props_expr(props_expr))
         } else {
             None
         };
 
-        // TODO
-        // 2. Do not identify the node as a builtin if it does not have `is` attribute;
-        // 7. Update the README and the progress.
+        // TODO Update the README and the progress.
 
-        let component_builtin_slots = self.generate_builtin_slots(element_node);
+        let component_builtin_slots = self.generate_component_children(element_node);
 
-        self.generate_componentlike(
+        let create_component_builtin_expr = self.generate_componentlike(
             identifier,
             component_builtin_attrs,
             component_builtin_slots,
             &element_node.patch_hints,
             true,
             span,
-        )
+        );
+
+        // `v-show` and custom directives
+        self.generate_component_directives(create_component_builtin_expr, element_node)
     }
 }
 
@@ -99,8 +99,8 @@ mod tests {
     use std::fmt::Debug;
 
     use fervid_core::{
-        AttributeOrBinding, BuiltinType, ElementKind, Node, StartingTag, VSlotDirective,
-        VueDirectives,
+        AttributeOrBinding, BuiltinType, ElementKind, Node, StartingTag, VCustomDirective,
+        VSlotDirective, VueDirectives,
     };
     use swc_core::common::DUMMY_SP;
 
@@ -144,6 +144,7 @@ mod tests {
                     attributes: vec![AttributeOrBinding::RegularAttribute {
                         name: "is".into(),
                         value: "div".into(),
+                        span: Default::default(),
                     }],
                     directives: None,
                 },
@@ -170,6 +171,7 @@ mod tests {
                         is_camel: false,
                         is_prop: false,
                         is_attr: false,
+                        span: Default::default(),
                     })],
                     directives: None,
                 },
@@ -194,10 +196,12 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "is".into(),
                             value: "div".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -205,6 +209,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -229,6 +234,7 @@ mod tests {
                     attributes: vec![AttributeOrBinding::RegularAttribute {
                         name: "is".into(),
                         value: "div".into(),
+                        span: Default::default(),
                     }],
                     directives: None,
                 },
@@ -254,6 +260,7 @@ mod tests {
                     attributes: vec![AttributeOrBinding::RegularAttribute {
                         name: "is".into(),
                         value: "div".into(),
+                        span: Default::default(),
                     }],
                     directives: None,
                 },
@@ -300,10 +307,12 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "is".into(),
                             value: "div".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -311,6 +320,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -344,6 +354,42 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_component_builtin_directives() {
+        // <component :is="foo" v-show="bar" v-focus></component>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Component),
+                starting_tag: StartingTag {
+                    tag_name: "component".into(),
+                    attributes: vec![AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str("is".into())),
+                        value: js("foo"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: Default::default(),
+                    })],
+                    directives: Some(Box::new(VueDirectives {
+                        v_show: Some(js("bar")),
+                        custom: vec![VCustomDirective {
+                            name: "focus".into(),
+                            argument: None,
+                            modifiers: vec![],
+                            value: None,
+                        }],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives((_openBlock(),_createBlock(_resolveDynamicComponent(foo))),[[_vShow,bar],[_directive_focus]])"#,
+        );
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_component_builtin(&input);
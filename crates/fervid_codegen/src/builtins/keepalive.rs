@@ -1,4 +1,4 @@
-use fervid_core::{ElementNode, PatchFlags, PatchFlagsSet, PatchHints, VueImports};
+use fervid_core::{ElementNode, PatchFlags, VueImports};
 use swc_core::ecma::ast::{ArrayLit, Expr, ExprOrSpread, Ident};
 
 use crate::CodegenContext;
@@ -39,29 +39,32 @@ impl CodegenContext {
 
         let should_wrap_in_block = keepalive_children.is_some();
 
-        let patch_hints = PatchHints {
-            flags: if should_wrap_in_block {
-                PatchFlagsSet::from(PatchFlags::DynamicSlots)
-            } else {
-                PatchFlagsSet::default()
-            },
-            props: vec![],
-        };
+        // The children are always dynamic, the props come from the transform,
+        // e.g. `:include="cached"` is `PROPS` with `["include"]`
+        let mut patch_hints = element_node.patch_hints.to_owned();
+        if should_wrap_in_block {
+            patch_hints.flags |= PatchFlags::DynamicSlots;
+        }
 
-        self.generate_componentlike(
+        let create_keepalive_expr = self.generate_componentlike(
             keepalive_identifier,
             keepalive_attrs,
             keepalive_children,
             &patch_hints,
             should_wrap_in_block,
             span,
-        )
+        );
+
+        // `v-show` and custom directives
+        self.generate_component_directives(create_keepalive_expr, element_node)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{AttributeOrBinding, BuiltinType, ElementKind, Node, StartingTag};
+    use fervid_core::{
+        AttributeOrBinding, BuiltinType, ElementKind, Node, PatchHints, StartingTag, VueDirectives,
+    };
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::js;
@@ -100,6 +103,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -107,6 +111,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -152,6 +157,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -159,6 +165,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -172,6 +179,60 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_keepalive_dynamic_props() {
+        // <keep-alive :include="foo">foobar</keep-alive>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::KeepAlive),
+                starting_tag: StartingTag {
+                    tag_name: "keep-alive".into(),
+                    attributes: vec![AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                        argument: Some("include".into()),
+                        value: js("foo"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: Default::default(),
+                    })],
+                    directives: None,
+                },
+                children: vec![Node::Text("foobar".into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: PatchHints {
+                    flags: PatchFlags::Props.into(),
+                    props: vec!["include".into()],
+                    ..Default::default()
+                },
+                span: DUMMY_SP,
+            },
+            r#"(_openBlock(),_createBlock(_KeepAlive,{include:foo},[_createTextVNode("foobar")],1032,["include"]))"#,
+        )
+    }
+
+    #[test]
+    fn it_generates_keepalive_directives() {
+        // <keep-alive v-show="foo">foobar</keep-alive>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::KeepAlive),
+                starting_tag: StartingTag {
+                    tag_name: "keep-alive".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_show: Some(js("foo")),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text("foobar".into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives((_openBlock(),_createBlock(_KeepAlive,null,[_createTextVNode("foobar")],1024)),[[_vShow,foo]])"#,
+        )
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_keepalive(&input);
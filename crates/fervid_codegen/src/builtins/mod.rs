@@ -12,6 +12,8 @@ mod teleport;
 mod transition;
 mod transition_group;
 
+pub(crate) use slot::camelize_slot_prop;
+
 impl CodegenContext {
     pub fn generate_builtin(&mut self, element_node: &ElementNode, builtin_type: BuiltinType) -> Expr {
         match builtin_type {
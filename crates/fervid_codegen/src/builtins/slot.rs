@@ -1,38 +1,47 @@
-use fervid_core::{check_attribute_name, fervid_atom, AttributeOrBinding, ElementNode, VueImports};
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, ElementNode, FervidAtom, StrOrExpr,
+    VBindDirective, VueImports,
+};
 use swc_core::ecma::ast::{
-    ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp, ObjectLit,
-    Str,
+    ArrayLit, ArrowExpr, BlockStmtOrExpr, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit,
+    MemberExpr, MemberProp, ObjectLit, Str,
 };
 
-use crate::CodegenContext;
+use crate::{utils::to_camelcase, CodegenContext};
 
 impl CodegenContext {
     /// Generates the code for `<slot>` element.
     ///
     /// A typical code (stringified) has the following form:
     /// ```js
-    /// renderSlot(_ctx.$slots, "slot-name", /*optional*/ { slot: attributes }, /*optional*/ [slot, children])
+    /// renderSlot(_ctx.$slots, "slot-name", /*optional*/ { slot: attributes }, /*optional*/ () => [fallback, children])
     /// ```
+    /// When [`CodegenContext::no_slotted`] is set, `true` is passed as the last argument.
     pub fn generate_slot(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
         // The `name` attribute should NOT be generated,
-        // therefore we split attributes generation to two slices, like so:
-        // ---1--- "name" ---2---
-        // This way we preserve the original order of attributes
-        // and avoid sorting or allocating extra.
-        let idx_of_name = element_node
-            .starting_tag
-            .attributes
+        // the other attributes are slot props with camelized names
+        let attributes = &element_node.starting_tag.attributes;
+        let idx_of_name = attributes
             .iter()
             .position(|attr| check_attribute_name(attr, "name"));
 
-        // Determine the args length (remember, we exclude `name` from attrs length)
-        let has_children = element_node.children.len() > 0;
-        let has_attributes =
-            element_node.starting_tag.attributes.len() > idx_of_name.map_or(0, |_| 1);
-
-        let render_slot_args_len = if has_children {
+        let slot_props: Vec<AttributeOrBinding> = attributes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| Some(*idx) != idx_of_name)
+            .map(|(_, attr)| camelize_slot_prop(attr))
+            .collect();
+
+        // Determine the args length
+        let has_children = !element_node.children.is_empty();
+        let has_attributes = !slot_props.is_empty();
+        let no_slotted = self.no_slotted;
+
+        let render_slot_args_len = if no_slotted {
+            5
+        } else if has_children {
             4
         } else if has_attributes {
             3
@@ -49,7 +58,7 @@ impl CodegenContext {
                 span,
                 obj: Box::new(Expr::Ident(Ident {
                     span,
-                    sym: fervid_atom!("_ctx"),
+                    sym: self.bindings_helper.ident_prefixes.ctx.to_owned(),
                     optional: false,
                 })),
                 prop: MemberProp::Ident(Ident {
@@ -62,9 +71,7 @@ impl CodegenContext {
 
         // Second arg: slot name (`name="foo"`), slot expression (`:name="foo"`) or "default"
         let name_expr = if let Some(idx) = idx_of_name {
-            let name_attr = &element_node.starting_tag.attributes[idx];
-
-            match name_attr {
+            match &attributes[idx] {
                 AttributeOrBinding::RegularAttribute { value, .. } => Expr::Lit(Lit::Str(Str {
                     span,
                     value: value.to_owned(),
@@ -87,38 +94,18 @@ impl CodegenContext {
             expr: Box::new(name_expr),
         });
 
-        // Third arg (optional): attributes
+        // Third arg (optional): slot props
         if has_attributes {
-            let mut attrs_obj = ObjectLit {
-                span,
-                props: Vec::with_capacity(element_node.starting_tag.attributes.len()),
-            };
-
-            match idx_of_name {
-                // Split attributes to two slices if we have a `name`
-                Some(idx) => {
-                    let attrs_slice1 = &element_node.starting_tag.attributes[..idx];
-                    let attrs_slice2 = &element_node.starting_tag.attributes[(idx + 1)..];
-
-                    // TODO Consider attr hints?
-                    self.generate_attributes(attrs_slice1, &mut attrs_obj.props);
-                    self.generate_attributes(attrs_slice2, &mut attrs_obj.props);
-                }
-
-                // TODO Consider attr hints?
-                None => {
-                    self.generate_attributes(
-                        &element_node.starting_tag.attributes,
-                        &mut attrs_obj.props,
-                    );
-                }
-            }
+            let mut props = Vec::with_capacity(slot_props.len());
+
+            // TODO Consider attr hints?
+            self.generate_attributes(&slot_props, &mut props);
 
             render_slot_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Object(attrs_obj)),
+                expr: Box::new(self.generate_props_expr(props, false, span)),
             });
-        } else if has_children {
+        } else if has_children || no_slotted {
             // Pushes `{}` as third argument
             render_slot_args.push(ExprOrSpread {
                 spread: None,
@@ -129,7 +116,7 @@ impl CodegenContext {
             })
         }
 
-        // Fourth arg (optional): children
+        // Fourth arg (optional): fallback children as `() => [children]`
         if has_children {
             let slot_children = self
                 .generate_element_children(element_node, false)
@@ -145,14 +132,41 @@ impl CodegenContext {
 
             render_slot_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Array(ArrayLit {
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    span,
+                    params: vec![],
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Array(ArrayLit {
+                        span,
+                        elems: slot_children,
+                    })))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            });
+        } else if no_slotted {
+            // Pushes `undefined` as fourth argument
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(Ident {
                     span,
-                    elems: slot_children,
+                    sym: fervid_atom!("undefined"),
+                    optional: false,
                 })),
+            })
+        }
+
+        // Fifth arg (optional): `noSlotted`, so that the scoped styles of this component
+        // are not inherited by the slot content
+        if no_slotted {
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
             });
         }
 
-        // `renderSlot(_ctx.$slots, "slot-name", { slot: attributes }, [slot, children])`
+        // `renderSlot(_ctx.$slots, "slot-name", { slot: attributes }, () => [slot, children])`
         Expr::Call(CallExpr {
             span,
             callee: Callee::Expr(Box::new(Expr::Ident(Ident {
@@ -166,9 +180,30 @@ impl CodegenContext {
     }
 }
 
+/// Slot props are camelized, e.g. `<slot foo-bar="baz">` passes `{ fooBar: "baz" }`
+pub(crate) fn camelize_slot_prop(attr: &AttributeOrBinding) -> AttributeOrBinding {
+    let mut attr = attr.to_owned();
+
+    match attr {
+        AttributeOrBinding::RegularAttribute { ref mut name, .. }
+        | AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(ref mut name)),
+            ..
+        }) if name.contains('-') => {
+            let mut camelized = String::with_capacity(name.len());
+            let _ = to_camelcase(name, &mut camelized); // ignore fault
+            *name = FervidAtom::from(camelized);
+        }
+
+        _ => {}
+    }
+
+    attr
+}
+
 #[cfg(test)]
 mod tests {
-    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag, VBindDirective, StrOrExpr};
+    use fervid_core::{BuiltinType, ElementKind, Node, StartingTag};
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::js;
@@ -205,7 +240,8 @@ mod tests {
             slot!(
                 vec![AttributeOrBinding::RegularAttribute {
                     name: "name".into(),
-                    value: "default".into()
+                    value: "default".into(),
+                    span: Default::default(),
                 }],
                 vec![]
             ),
@@ -220,7 +256,8 @@ mod tests {
             slot!(
                 vec![AttributeOrBinding::RegularAttribute {
                     name: "name".into(),
-                    value: "test-slot".into()
+                    value: "test-slot".into(),
+                    span: Default::default(),
                 }],
                 vec![]
             ),
@@ -238,7 +275,8 @@ mod tests {
                     value: js("slot + name"),
                     is_camel: false,
                     is_prop: false,
-                    is_attr: false
+                    is_attr: false,
+                    span: Default::default(),
                 })],
                 vec![]
             ),
@@ -254,14 +292,16 @@ mod tests {
                 vec![
                     AttributeOrBinding::RegularAttribute {
                         name: "foo".into(),
-                        value: "bar".into()
+                        value: "bar".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(VBindDirective {
                         argument: Some(StrOrExpr::Str("baz".into())),
                         value: js("qux"),
                         is_camel: false,
                         is_prop: false,
-                        is_attr: false
+                        is_attr: false,
+                        span: Default::default(),
                     })
                 ],
                 vec![]
@@ -275,18 +315,21 @@ mod tests {
                 vec![
                     AttributeOrBinding::RegularAttribute {
                         name: "name".into(),
-                        value: "default".into()
+                        value: "default".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::RegularAttribute {
                         name: "foo".into(),
-                        value: "bar".into()
+                        value: "bar".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(VBindDirective {
                         argument: Some(StrOrExpr::Str("baz".into())),
                         value: js("qux"),
                         is_camel: false,
                         is_prop: false,
-                        is_attr: false
+                        is_attr: false,
+                        span: Default::default(),
                     })
                 ],
                 vec![]
@@ -300,18 +343,21 @@ mod tests {
                 vec![
                     AttributeOrBinding::RegularAttribute {
                         name: "foo".into(),
-                        value: "bar".into()
+                        value: "bar".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::RegularAttribute {
                         name: "name".into(),
-                        value: "default".into()
+                        value: "default".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(VBindDirective {
                         argument: Some(StrOrExpr::Str("baz".into())),
                         value: js("qux"),
                         is_camel: false,
                         is_prop: false,
-                        is_attr: false
+                        is_attr: false,
+                        span: Default::default(),
                     })
                 ],
                 vec![]
@@ -325,18 +371,21 @@ mod tests {
                 vec![
                     AttributeOrBinding::RegularAttribute {
                         name: "foo".into(),
-                        value: "bar".into()
+                        value: "bar".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(VBindDirective {
                         argument: Some(StrOrExpr::Str("baz".into())),
                         value: js("qux"),
                         is_camel: false,
                         is_prop: false,
-                        is_attr: false
+                        is_attr: false,
+                        span: Default::default(),
                     }),
                     AttributeOrBinding::RegularAttribute {
                         name: "name".into(),
-                        value: "default".into()
+                        value: "default".into(),
+                        span: Default::default(),
                     }
                 ],
                 vec![]
@@ -381,7 +430,7 @@ mod tests {
                     })
                 ]
             ),
-            r#"_renderSlot(_ctx.$slots,"default",{},[_createElementVNode("div",null,"Placeholder"),_createVNode(_component_foo_component)])"#,
+            r#"_renderSlot(_ctx.$slots,"default",{},()=>[_createElementVNode("div",null,"Placeholder"),_createVNode(_component_foo_component)])"#,
         );
     }
 
@@ -396,18 +445,21 @@ mod tests {
                 vec![
                     AttributeOrBinding::RegularAttribute {
                         name: "name".into(),
-                        value: "test-slot".into()
+                        value: "test-slot".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::RegularAttribute {
                         name: "foo".into(),
-                        value: "bar".into()
+                        value: "bar".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(VBindDirective {
                         argument: Some(StrOrExpr::Str("baz".into())),
                         value: js("qux"),
                         is_camel: false,
                         is_prop: false,
-                        is_attr: false
+                        is_attr: false,
+                        span: Default::default(),
                     })
                 ],
                 vec![
@@ -437,7 +489,45 @@ mod tests {
                     })
                 ]
             ),
-            r#"_renderSlot(_ctx.$slots,"test-slot",{foo:"bar",baz:qux},[_createElementVNode("div",null,"Placeholder"),_createVNode(_component_foo_component)])"#,
+            r#"_renderSlot(_ctx.$slots,"test-slot",{foo:"bar",baz:qux},()=>[_createElementVNode("div",null,"Placeholder"),_createVNode(_component_foo_component)])"#,
+        );
+    }
+
+    #[test]
+    fn it_camelizes_slot_props() {
+        // <slot foo-bar="baz" :qux-quux="corge" />
+        test_out(
+            slot!(
+                vec![
+                    AttributeOrBinding::RegularAttribute {
+                        name: "foo-bar".into(),
+                        value: "baz".into(),
+                        span: Default::default(),
+                    },
+                    AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str("qux-quux".into())),
+                        value: js("corge"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: Default::default(),
+                    })
+                ],
+                vec![]
+            ),
+            r#"_renderSlot(_ctx.$slots,"default",{fooBar:"baz",quxQuux:corge})"#,
+        );
+    }
+
+    #[test]
+    fn it_generates_no_slotted() {
+        // <slot /> with scoped styles without `:slotted()`
+        let mut ctx = CodegenContext::default();
+        ctx.no_slotted = true;
+        let out = ctx.generate_slot(&slot!(vec![], vec![]));
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_renderSlot(_ctx.$slots,"default",{},undefined,true)"#
         );
     }
 
@@ -18,16 +18,19 @@ impl CodegenContext {
         let suspense_attrs =
             self.generate_builtin_attrs(&element_node.starting_tag.attributes, span);
 
-        let suspense_slots = self.generate_builtin_slots(element_node);
+        let suspense_slots = self.generate_component_children(element_node);
 
-        self.generate_componentlike(
+        let create_suspense_expr = self.generate_componentlike(
             suspense_identifier,
             suspense_attrs,
             suspense_slots,
             &element_node.patch_hints,
             true,
             span,
-        )
+        );
+
+        // `v-show` and custom directives
+        self.generate_component_directives(create_suspense_expr, element_node)
     }
 }
 
@@ -72,6 +75,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -79,6 +83,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -124,6 +129,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -131,6 +137,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -18,6 +18,9 @@ impl CodegenContext {
         let teleport_attrs =
             self.generate_builtin_attrs(&element_node.starting_tag.attributes, span);
 
+        // Teleport does not render an element, so directives are not applicable
+        self.report_teleport_directives(element_node);
+
         let generated_children = self.generate_element_children(element_node, false);
         let teleport_children = if generated_children.0.len() != 0 {
             Some(Expr::Array(ArrayLit {
@@ -50,7 +53,10 @@ impl CodegenContext {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{AttributeOrBinding, BuiltinType, ElementKind, Node, StartingTag};
+    use fervid_core::{
+        AttributeOrBinding, BuiltinType, Diagnostic, DiagnosticKind, ElementKind, Node,
+        StartingTag, VueDirectives,
+    };
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::js;
@@ -89,6 +95,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -96,6 +103,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -141,6 +149,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -148,6 +157,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -161,6 +171,40 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_reports_teleport_directives() {
+        // <teleport v-show="foo">foobar</teleport>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_teleport(&ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Teleport),
+            starting_tag: StartingTag {
+                tag_name: "teleport".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_show: Some(js("foo")),
+                    ..Default::default()
+                })),
+            },
+            children: vec![Node::Text("foobar".into(), DUMMY_SP)],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        });
+
+        // Directive is not applied
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(),_createBlock(_Teleport,null,[_createTextVNode("foobar")]))"#
+        );
+        assert_eq!(
+            ctx.bindings_helper.diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::DirectiveOnTeleport,
+                span: DUMMY_SP
+            }]
+        );
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_teleport(&input);
@@ -1,5 +1,8 @@
-use fervid_core::{ElementNode, VueImports};
-use swc_core::ecma::ast::{Expr, Ident};
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, Diagnostic, DiagnosticKind, ElementKind,
+    ElementNode, Node, VueImports,
+};
+use swc_core::ecma::ast::{Expr, Ident, KeyValueProp, Lit, Prop, PropName, PropOrSpread, Str};
 
 use crate::CodegenContext;
 
@@ -7,6 +10,8 @@ impl CodegenContext {
     pub fn generate_transition(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
+        self.report_transition_misuse(element_node);
+
         // _Transition
         let transition_identifier = Expr::Ident(Ident {
             span,
@@ -14,25 +19,134 @@ impl CodegenContext {
             optional: false,
         });
 
-        let transition_attrs =
-            self.generate_builtin_attrs(&element_node.starting_tag.attributes, span);
+        let mut transition_props = self.generate_builtin_wrapper_props(element_node);
+
+        // A single child with `v-show` is never removed, only hidden,
+        // so the transition is `persisted: ""`, same as in the official compiler
+        let has_v_show_child = matches!(
+            significant_children(&element_node.children).as_slice(),
+            [Node::Element(child)] if child
+                .starting_tag
+                .directives
+                .as_ref()
+                .map_or(false, |directives| directives.v_show.is_some())
+        );
+        if has_v_show_child {
+            transition_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(Ident {
+                    span,
+                    sym: fervid_atom!("persisted"),
+                    optional: false,
+                }),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span,
+                    value: fervid_atom!(""),
+                    raw: None,
+                }))),
+            }))));
+        }
 
-        let transition_slots = self.generate_builtin_slots(element_node);
+        let transition_attrs = if !transition_props.is_empty() {
+            let props_expr = self.generate_props_expr(transition_props, true, span);
+            Some(self.normalize_props_expr(props_expr))
+        } else {
+            None
+        };
 
-        self.generate_componentlike(
+        let transition_slots = self.generate_component_children(element_node);
+
+        let create_transition_expr = self.generate_componentlike(
             transition_identifier,
             transition_attrs,
             transition_slots,
             &element_node.patch_hints,
             false,
             span,
-        )
+        );
+
+        // `v-show` and custom directives
+        self.generate_component_directives(create_transition_expr, element_node)
+    }
+
+    /// Warns about the `<Transition>` usages which do not work in runtime:
+    /// - multiple children, including a `v-for` child or a `v-if` branch with multiple children;
+    /// - a static `mode` other than `in-out`, `out-in` or `default`.
+    fn report_transition_misuse(&mut self, element_node: &ElementNode) {
+        let children = significant_children(&element_node.children);
+        if !children.is_empty() && has_multiple_children(&children) {
+            self.bindings_helper.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::TransitionInvalidChildren,
+                span: element_node.span,
+            });
+        }
+
+        let mode = element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .find(|attr| check_attribute_name(attr, "mode"));
+        if let Some(AttributeOrBinding::RegularAttribute { value, .. }) = mode {
+            if !matches!(value.as_ref(), "in-out" | "out-in" | "default") {
+                self.bindings_helper.diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::TransitionInvalidMode(value.to_owned()),
+                    span: element_node.span,
+                });
+            }
+        }
+    }
+}
+
+/// Children except for the comments and whitespace
+fn significant_children(children: &[Node]) -> Vec<&Node> {
+    children
+        .iter()
+        .filter(|child| match child {
+            Node::Comment(_, _) => false,
+            Node::Text(text, _) => !text.trim().is_empty(),
+            _ => true,
+        })
+        .collect()
+}
+
+/// `v-for` renders a list, and `<template>` renders all of its children
+fn has_multiple_children(children: &[&Node]) -> bool {
+    fn is_multiple(element_node: &ElementNode) -> bool {
+        let has_v_for = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_for.is_some());
+        let is_template = matches!(element_node.kind, ElementKind::Element)
+            && element_node.starting_tag.tag_name == "template";
+
+        has_v_for
+            || (is_template && has_multiple_children(&significant_children(&element_node.children)))
+    }
+
+    match children {
+        [Node::Element(element_node)] => is_multiple(element_node),
+        [Node::ConditionalSeq(conditional_seq)] => {
+            is_multiple(&conditional_seq.if_node.node)
+                || conditional_seq
+                    .else_if_nodes
+                    .iter()
+                    .any(|else_if| is_multiple(&else_if.node))
+                || conditional_seq
+                    .else_node
+                    .as_ref()
+                    .map_or(false, |else_node| is_multiple(else_node))
+        }
+        [_] => false,
+        _ => true,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{BuiltinType, ElementKind, StartingTag, Node, AttributeOrBinding};
+    use fervid_core::{
+        BuiltinType, PatchFlags, PatchFlagsSet, PatchHints, StartingTag, VModelDirective,
+        VueDirectives,
+    };
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::js;
@@ -71,6 +185,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -78,6 +193,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -123,6 +239,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -130,6 +247,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -143,6 +261,142 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_transition_directives() {
+        // <transition v-model="foo" v-show="bar">foobar</transition>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Transition),
+                starting_tag: StartingTag {
+                    tag_name: "transition".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_model: vec![VModelDirective {
+                            argument: None,
+                            value: *js("foo"),
+                            modifiers: vec![],
+                            update_handler: None,
+                            span: DUMMY_SP,
+                        }],
+                        v_show: Some(js("bar")),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text("foobar".into(), DUMMY_SP)],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP
+            },
+            r#"_withDirectives(_createVNode(_Transition,{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event)},{"default":_withCtx(()=>[_createTextVNode("foobar")]),_:1}),[[_vShow,bar]])"#,
+        )
+    }
+
+    #[test]
+    fn it_persists_transition_with_v_show() {
+        // <transition><div v-show="foo"></div></transition>
+        test_out(
+            ElementNode {
+                kind: ElementKind::Builtin(BuiltinType::Transition),
+                starting_tag: StartingTag {
+                    tag_name: "transition".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(div(
+                    Some(Box::new(VueDirectives {
+                        v_show: Some(js("foo")),
+                        ..Default::default()
+                    })),
+                    PatchFlags::NeedPatch.into(),
+                ))],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_Transition,{persisted:""},{"default":_withCtx(()=>[_withDirectives(_createElementVNode("div",null,null,512),[[_vShow,foo]])]),_:1})"#,
+        )
+    }
+
+    #[test]
+    fn it_reports_transition_misuse() {
+        // <transition mode="in-and-out"><div></div><div></div></transition>
+        let mut ctx = CodegenContext::default();
+        ctx.generate_transition(&ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Transition),
+            starting_tag: StartingTag {
+                tag_name: "transition".into(),
+                attributes: vec![AttributeOrBinding::RegularAttribute {
+                    name: "mode".into(),
+                    value: "in-and-out".into(),
+                    span: Default::default(),
+                }],
+                directives: None,
+            },
+            children: vec![
+                Node::Element(div(None, Default::default())),
+                Node::Element(div(None, Default::default())),
+            ],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        });
+
+        assert_eq!(
+            ctx.bindings_helper.diagnostics,
+            vec![
+                Diagnostic {
+                    kind: DiagnosticKind::TransitionInvalidChildren,
+                    span: DUMMY_SP
+                },
+                Diagnostic {
+                    kind: DiagnosticKind::TransitionInvalidMode("in-and-out".into()),
+                    span: DUMMY_SP
+                }
+            ]
+        );
+
+        // A single child with `mode="out-in"` is fine
+        let mut ctx = CodegenContext::default();
+        ctx.generate_transition(&ElementNode {
+            kind: ElementKind::Builtin(BuiltinType::Transition),
+            starting_tag: StartingTag {
+                tag_name: "transition".into(),
+                attributes: vec![AttributeOrBinding::RegularAttribute {
+                    name: "mode".into(),
+                    value: "out-in".into(),
+                    span: Default::default(),
+                }],
+                directives: None,
+            },
+            children: vec![
+                Node::Comment("comment".into(), DUMMY_SP),
+                Node::Element(div(None, Default::default())),
+            ],
+            template_scope: 0,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        });
+        assert!(ctx.bindings_helper.diagnostics.is_empty());
+    }
+
+    fn div(directives: Option<Box<VueDirectives>>, flags: PatchFlagsSet) -> ElementNode {
+        ElementNode {
+            kind: ElementKind::Element,
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives,
+            },
+            children: vec![],
+            template_scope: 0,
+            patch_hints: PatchHints {
+                flags,
+                ..Default::default()
+            },
+            span: DUMMY_SP,
+        }
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_transition(&input);
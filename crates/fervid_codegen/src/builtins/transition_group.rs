@@ -14,19 +14,21 @@ impl CodegenContext {
             optional: false,
         });
 
-        let transition_group_attrs =
-            self.generate_builtin_attrs(&element_node.starting_tag.attributes, span);
+        let transition_group_attrs = self.generate_builtin_wrapper_attrs(element_node);
 
-        let transition_group_slots = self.generate_builtin_slots(element_node);
+        let transition_group_slots = self.generate_component_children(element_node);
 
-        self.generate_componentlike(
+        let create_transition_group_expr = self.generate_componentlike(
             transition_group_identifier,
             transition_group_attrs,
             transition_group_slots,
             &element_node.patch_hints,
             false,
             span,
-        )
+        );
+
+        // `v-show` and custom directives
+        self.generate_component_directives(create_transition_group_expr, element_node)
     }
 }
 
@@ -71,6 +73,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -78,6 +81,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -123,6 +127,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(fervid_core::VBindDirective {
                             argument: Some("baz".into()),
@@ -130,6 +135,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -1,18 +1,23 @@
 use fervid_core::{
-    fervid_atom, ComponentBinding, ElementNode, FervidAtom, Node, PatchHints, StartingTag,
-    StrOrExpr, VSlotDirective, VueDirectives, VueImports,
+    fervid_atom, is_from_default_slot, ComponentBinding, ConditionalNodeSequence, ElementNode,
+    FervidAtom, Node, PatchFlags, PatchHints, StrOrExpr, VForDirective, VSlotDirective,
+    VueDirectives, VueImports,
 };
 use swc_core::{
     common::{Span, DUMMY_SP},
     ecma::ast::{
-        ArrayLit, ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread,
-        Ident, KeyValueProp, Lit, Null, Number, ObjectLit, Pat, Prop, PropOrSpread, Str,
-        VarDeclarator,
+        ArrayLit, ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, Bool, CallExpr, Callee,
+        CondExpr, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, MemberExpr, MemberProp, Null,
+        Number, ObjectLit, Pat, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Str, VarDeclarator,
     },
 };
 
 use crate::{
-    context::CodegenContext, control_flow::SlottedIterator, utils::str_or_expr_to_propname,
+    context::CodegenContext,
+    control_flow::SlottedIterator,
+    directives::generate_v_for_params,
+    elements::generate_dynamic_props,
+    utils::{str_or_expr_to_propname, to_pascalcase, to_valid_asset_id},
 };
 
 impl CodegenContext {
@@ -29,7 +34,9 @@ impl CodegenContext {
         let attributes_obj = self.generate_component_attributes(component_node);
         // TODO Apply all the directives and modifications
         let attributes_expr = if attributes_obj.props.len() != 0 {
-            Some(Expr::Object(attributes_obj))
+            let props_expr =
+                self.generate_props_expr(attributes_obj.props, true, attributes_obj.span);
+            Some(self.normalize_props_expr(props_expr))
         } else {
             None
         };
@@ -117,36 +124,14 @@ impl CodegenContext {
         if expected_component_args_count >= 4 {
             create_component_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: patch_hints.flags.bits().into(),
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(patch_hints.flags, span)),
             });
 
             // Props array
             if !patch_hints.props.is_empty() {
-                let props_array = patch_hints
-                    .props
-                    .iter()
-                    .map(|prop| {
-                        Some(ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(Expr::Lit(Lit::Str(Str {
-                                span: DUMMY_SP,
-                                value: prop.to_owned(),
-                                raw: None,
-                            }))),
-                        })
-                    })
-                    .collect();
-
                 create_component_args.push(ExprOrSpread {
                     spread: None,
-                    expr: Box::new(Expr::Array(ArrayLit {
-                        span: DUMMY_SP,
-                        elems: props_array,
-                    })),
+                    expr: Box::new(generate_dynamic_props(&patch_hints.props)),
                 });
             }
         }
@@ -173,7 +158,7 @@ impl CodegenContext {
         // When wrapping in block, we also need `openBlock()`
         let create_component_expr = if wrap_in_block {
             // (openBlock(), createBlock(_component_name, {component:attrs}, {component:slots}, PATCH_FLAGS))
-            self.wrap_in_open_block(create_component_fn_call, span)
+            self.wrap_in_open_block(create_component_fn_call, false, span)
         } else {
             // Just `createVNode` call
             create_component_fn_call
@@ -185,7 +170,13 @@ impl CodegenContext {
     pub fn generate_component_resolves(&mut self) -> Vec<VarDeclarator> {
         let mut result = Vec::new();
 
-        if self.bindings_helper.components.is_empty() {
+        // Components resolved at compile time, e.g. `<script setup>` bindings, do not need `resolveComponent`
+        let has_runtime_resolved = self
+            .bindings_helper
+            .components
+            .values()
+            .any(|component| matches!(component, ComponentBinding::RuntimeResolved(_)));
+        if !has_runtime_resolved {
             return result;
         }
 
@@ -211,6 +202,36 @@ impl CodegenContext {
 
         // Key is a component as used in template, value is the assigned Js identifier
         for (component_name, component_identifier) in sorted_components.iter() {
+            let mut args = vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: (*component_name).to_owned(),
+                    raw: None,
+                }))),
+            }];
+
+            // A component using its own tag, e.g. `<TreeItem>` inside `TreeItem.vue`,
+            // is resolved as `resolveComponent("TreeItem", true)` (`maybeSelfReference`)
+            let is_self_reference = self
+                .bindings_helper
+                .self_name
+                .as_ref()
+                .map_or(false, |name| {
+                    let mut pascalcased = String::with_capacity(component_name.len());
+                    let _ = to_pascalcase(component_name, &mut pascalcased); // ignore fault
+                    pascalcased == **name
+                });
+            if is_self_reference {
+                args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    }))),
+                });
+            }
+
             // _component_ident_name = resolveComponent("component-name")
             result.push(VarDeclarator {
                 span: DUMMY_SP,
@@ -225,14 +246,7 @@ impl CodegenContext {
                         sym: resolve_component_ident.to_owned(),
                         optional: false,
                     }))),
-                    args: vec![ExprOrSpread {
-                        spread: None,
-                        expr: Box::new(Expr::Lit(Lit::Str(Str {
-                            span: DUMMY_SP,
-                            value: (*component_name).to_owned(),
-                            raw: None,
-                        }))),
-                    }],
+                    args,
                     type_args: None,
                 }))),
                 definite: false,
@@ -242,7 +256,10 @@ impl CodegenContext {
         result
     }
 
-    fn generate_component_attributes<'e>(&mut self, component_node: &'e ElementNode) -> ObjectLit {
+    pub(crate) fn generate_component_attributes<'e>(
+        &mut self,
+        component_node: &'e ElementNode,
+    ) -> ObjectLit {
         let mut result_props = Vec::new();
 
         self.generate_attributes(&component_node.starting_tag.attributes, &mut result_props);
@@ -296,6 +313,13 @@ impl CodegenContext {
         let component_span = DUMMY_SP; // todo
         let mut default_slot_children: Vec<Expr> = Vec::new();
 
+        // Comments do not make a default slot on their own, e.g. `<comp><!-- hi --></comp>`.
+        // When there is other content, they are rendered inside the default slot
+        let has_default_slot_content = component_node
+            .children
+            .iter()
+            .any(|child| !matches!(child, Node::Comment(_, _)) && is_from_default_slot(child));
+
         // `SlottedIterator` will iterate over sequences of default or named slots,
         // and it will stop yielding elements unless [`SlottedIterator::toggle_mode`] is called.
         let mut slotted_iterator = SlottedIterator::new(&component_node.children);
@@ -321,6 +345,10 @@ impl CodegenContext {
         let mut has_encountered_default_slot = false;
         // let mut default_slot_is_not_template = false;
 
+        // Conditional and looped slots, e.g. `<template v-if="foo" #foo>`, go to `createSlots`
+        let mut dynamic_slots: Vec<Option<ExprOrSpread>> = Vec::new();
+        let mut conditional_branch_index = 0;
+
         // Generate the default slot items into the `default_slot_children` vec,
         // and named slots into the `result` vec.
         while slotted_iterator.has_more() {
@@ -358,21 +386,14 @@ impl CodegenContext {
                 }
 
                 // Check if this is a `<template>` or not
-                let Node::Element(ElementNode {
-                    starting_tag:
-                        StartingTag {
-                            tag_name,
-                            directives: Some(directives),
-                            ..
-                        },
-                    children,
-                    ..
-                }) = node
-                else {
+                let Node::Element(template_node) = node else {
+                    not_in_a_template_v_slot!();
+                };
+                let Some(ref directives) = template_node.starting_tag.directives else {
                     not_in_a_template_v_slot!();
                 };
 
-                if tag_name != "template" {
+                if template_node.starting_tag.tag_name != "template" {
                     not_in_a_template_v_slot!();
                 }
 
@@ -385,9 +406,10 @@ impl CodegenContext {
                 // We need to generate it as if it was a named slot
                 self.generate_named_slot(
                     v_slot_directive,
-                    &children,
-                    &directives,
+                    template_node,
+                    directives,
                     &mut result_static_slots,
+                    &mut dynamic_slots,
                 );
 
                 // Advance the iterator forward
@@ -399,8 +421,22 @@ impl CodegenContext {
                     continue;
                 };
 
-                let Node::Element(slotted_node) = slotted_node else {
-                    unreachable!("Only element node can be slotted")
+                let slotted_node = match slotted_node {
+                    Node::Element(slotted_node) => slotted_node,
+                    Node::ConditionalSeq(conditional_seq) => {
+                        let conditional_slot = self.generate_conditional_slot(
+                            conditional_seq,
+                            &mut conditional_branch_index,
+                            false,
+                        );
+                        dynamic_slots.push(Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(conditional_slot),
+                        }));
+                        slotted_iterator.toggle_mode();
+                        continue;
+                    }
+                    _ => unreachable!("Only element node can be slotted"),
                 };
 
                 // Get `v-slot`
@@ -415,23 +451,24 @@ impl CodegenContext {
 
                 self.generate_named_slot(
                     v_slot_directive,
-                    &slotted_node.children,
-                    &directives,
+                    slotted_node,
+                    directives,
                     &mut result_static_slots,
+                    &mut dynamic_slots,
                 );
             }
 
             slotted_iterator.toggle_mode();
         }
 
-        // Add default slot children when needed
-        // TODO Error on cases when both `<template v-slot:default>`
-        // and non-slotted children are present (analyzer), e.g.:
+        // Add default slot children when needed.
+        // Cases when both `<template v-slot:default>`
+        // and non-slotted children are present are reported by the transform, e.g.:
         // <some-component>
         //   <template v-slot:default>hi</template>
         //   not hi
         // </some-component>
-        if default_slot_children.len() != 0 {
+        if has_default_slot_content && !default_slot_children.is_empty() {
             // withCtx(() => [child1, child2, child3])
             result_static_slots.push(self.generate_slot_shell(
                 StrOrExpr::Str(fervid_atom!("default")),
@@ -441,78 +478,258 @@ impl CodegenContext {
             ));
         }
 
-        // TODO Add `createSlots` if needed
-        Some(Expr::Object(ObjectLit {
+        // Nothing to render, e.g. only the comments were inside
+        if result_static_slots.is_empty() && dynamic_slots.is_empty() {
+            return None;
+        }
+
+        result_static_slots.push(generate_slots_flag(component_node, component_span));
+
+        let static_slots = Expr::Object(ObjectLit {
             span: component_span,
             props: result_static_slots,
-        }))
+        });
+
+        if dynamic_slots.is_empty() {
+            return Some(static_slots);
+        }
+
+        Some(self.generate_create_slots(static_slots, dynamic_slots, component_span))
+    }
+
+    /// Generates `_createSlots({ static: _withCtx(() => []), _: 2 }, [dynamic slots])`
+    pub(crate) fn generate_create_slots(
+        &mut self,
+        static_slots: Expr,
+        dynamic_slots: Vec<Option<ExprOrSpread>>,
+        span: Span,
+    ) -> Expr {
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::CreateSlots),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(static_slots),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Array(ArrayLit {
+                        span,
+                        elems: dynamic_slots,
+                    })),
+                },
+            ],
+            type_args: None,
+        })
+    }
+
+    /// Generates a conditional slot entry for `createSlots`, e.g.
+    /// `foo ? { name: "foo", fn: _withCtx(() => []), key: "0" } : undefined`.
+    /// Every branch gets its own key, so that the runtime can tell them apart.
+    /// With `ssr`, the slot functions are generated for the SSR render
+    pub(crate) fn generate_conditional_slot(
+        &mut self,
+        conditional_seq: &ConditionalNodeSequence,
+        branch_index: &mut usize,
+        ssr: bool,
+    ) -> Expr {
+        let mut branches = Vec::with_capacity(1 + conditional_seq.else_if_nodes.len());
+        for conditional in std::iter::once(conditional_seq.if_node.as_ref())
+            .chain(conditional_seq.else_if_nodes.iter())
+        {
+            let slot_object =
+                self.generate_dynamic_slot_object(&conditional.node, Some(*branch_index), ssr);
+            *branch_index += 1;
+            branches.push((&conditional.condition, slot_object));
+        }
+
+        // Without `v-else` the last alternate is `undefined`
+        let mut result = match conditional_seq.else_node {
+            Some(ref else_node) => {
+                let slot_object =
+                    self.generate_dynamic_slot_object(else_node, Some(*branch_index), ssr);
+                *branch_index += 1;
+                slot_object
+            }
+            None => Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: fervid_atom!("undefined"),
+                optional: false,
+            }),
+        };
+
+        // Fold from the last branch to the first into `cond1 ? slot1 : cond2 ? slot2 : else`
+        for (condition, slot_object) in branches.into_iter().rev() {
+            result = Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test: Box::new(condition.to_owned()),
+                cons: Box::new(slot_object),
+                alt: Box::new(result),
+            });
+        }
+
+        result
+    }
+
+    /// Generates `{ name: "foo", fn: _withCtx(() => []), key: "0" }` from a `<template v-slot>`.
+    /// The `key` is only needed to tell apart the branches of a conditional slot
+    fn generate_dynamic_slot_object(
+        &mut self,
+        slot_node: &ElementNode,
+        key: Option<usize>,
+        ssr: bool,
+    ) -> Expr {
+        let span = slot_node.span;
+        let v_slot = slot_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_slot.as_ref());
+
+        let slot_name = match v_slot.and_then(|v_slot| v_slot.slot_name.as_ref()) {
+            Some(StrOrExpr::Str(name)) => Expr::Lit(Lit::Str(Str {
+                span,
+                value: name.to_owned(),
+                raw: None,
+            })),
+            Some(StrOrExpr::Expr(name_expr)) => (**name_expr).to_owned(),
+            None => Expr::Lit(Lit::Str(Str {
+                span,
+                value: fervid_atom!("default"),
+                raw: None,
+            })),
+        };
+
+        let slot_binding = v_slot.and_then(|v_slot| v_slot.value.as_deref());
+        let slot_fn = if ssr {
+            self.generate_ssr_slot_fn(&slot_node.children, slot_binding)
+        } else {
+            let slot_children = self.generate_slot_children(&slot_node.children);
+            self.generate_slot_fn(slot_children, slot_binding, span)
+        };
+
+        macro_rules! prop {
+            ($name: literal, $value: expr) => {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Ident {
+                        span,
+                        sym: fervid_atom!($name),
+                        optional: false,
+                    }),
+                    value: Box::new($value),
+                })))
+            };
+        }
+
+        let mut props = vec![prop!("name", slot_name), prop!("fn", slot_fn)];
+        if let Some(key) = key {
+            props.push(prop!(
+                "key",
+                Expr::Lit(Lit::Str(Str {
+                    span,
+                    value: FervidAtom::from(key.to_string()),
+                    raw: None,
+                }))
+            ));
+        }
+
+        Expr::Object(ObjectLit { span, props })
+    }
+
+    /// Generates a looped slot entry for `createSlots`, e.g. for `<template v-for="i in list" #[i]>`
+    /// it is `_renderList(list, (i) => { return { name: i, fn: _withCtx(() => []) } })`
+    pub(crate) fn generate_looped_slot(
+        &mut self,
+        v_for: &VForDirective,
+        slot_node: &ElementNode,
+        ssr: bool,
+    ) -> Expr {
+        let span = slot_node.span;
+        let slot_object = self.generate_dynamic_slot_object(slot_node, None, ssr);
+
+        let render_list_arrow = Expr::Arrow(ArrowExpr {
+            span,
+            params: generate_v_for_params(&v_for.itervar),
+            body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span,
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span,
+                    arg: Some(Box::new(slot_object)),
+                })],
+            })),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::RenderList),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: v_for.iterable.to_owned(),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(render_list_arrow),
+                },
+            ],
+            type_args: None,
+        })
     }
 
     /// Generates a named slot using a vector of slot children.
-    /// Primarily for `<template v-slot:named>` or `<template v-slot:default>`
+    /// Primarily for `<template v-slot:named>` or `<template v-slot:default>`.
+    /// Looped slots, i.e. `<template v-for="item in list" #[item.name]>`,
+    /// are generated into `out_dynamic_slots` for `createSlots`
     fn generate_named_slot(
         &mut self,
         v_slot: &VSlotDirective,
-        slot_children: &[Node],
+        slot_node: &ElementNode,
         directives: &VueDirectives,
         out_static_slots: &mut Vec<PropOrSpread>,
+        out_dynamic_slots: &mut Vec<Option<ExprOrSpread>>,
     ) {
-        // Extra logic is needed if this is more than just `<template v-slot>`
-        let is_complex = directives.v_if.is_some()
-            || directives.v_else_if.is_some()
-            || directives.v_else.is_some()
-            || directives.v_for.is_some();
-
-        if is_complex {
-            todo!("createSlots is not supported yet");
-            // https://play.vuejs.org/#eNqVVNtuozAQ/ZUpWolGKo2y+xaRqFVfdr9gH0qlOngIVo2NbEMbRfz7js0l0G33okTgmfGcMzfmHN3X9W3bYLSNUpsbUTuw6JoaJFPHXRY5m0X7TImq1sbBGQwW0EFhdAUxucWZylSulXVQ2SPsvP06/o5SavipjeRX8SpT6bqHJiASBpolQY/xWjKHLRoCiktB4PgWeDkWrJHEnykAzhy7XvVnIELXGDVK4MOwW+hDIACv6vyLHvRfRuKwqiUxkgSQctFCm1RYoQ0KyFxabvbnc8CErgM6ThF2Xboma8+QClU3zntrjpKyIo+QVjDypqpOSU5mrchGmWYRCQehOIkH3SjuNXe5FPkLab4QgXKw20PJFJf44PXXvXYVYDPnf+lVksC9lOBKBE0PA1wYzJ1o0QIzCOKotEEOooDnNhHFMwgLhSe8hR/8xeuFiy3k2ng/SJIhZop6LA5FaqV226EH0wWAUkyX14tSfuj/yE+KVSJ/miH4aoLSr3/A+RSPGmXpLAo/RKZBKsy/hBH6SwJKi72zAKFgM7XL5/UbULoemjj1NAzLjLwfHU3y49MoeVKt8v7lp8JzB6PDN0cKqmAE6zCMAbXcUHc8xoEZslxqNt69m2vm8tdBMTCNY7qEr2HLhWUHiX7wiGmWdGprpvalODH6RvzxUg0/XwZHFOhHpiLFskM+5qFY9eA98bZJoc1Y628hznnjLlxLBCq7aOk060d0Ez3o6v3OWi4TCnHcVzS1QqH30Mp/VO9310cLhgqKtELKsEJu+rz/unTI4f/WzbRa5qvk00Uyr0D3Cy9D1W8=
-            // idk if that should be wrapped in block or not
-            // _createSlots({
-            //     default: _withCtx(() => [
-            //       _createTextVNode(" hi ")
-            //     ]),
-            //     _: 2 /* DYNAMIC */
-            //   }, [
-            //     _renderList(1, (i) => {
-            //       return {
-            //         name: "memes",
-            //         fn: _withCtx(() => [
-            //           _createTextVNode(" hi")
-            //         ])
-            //       }
-            //     })
-            //   ]), 1040 /* FULL_PROPS, DYNAMIC_SLOTS */)
-            // let generated = self.generate_node(slotted_node, false);
-        } else {
-            // Generate the children of the `<template v-slot>`
-            let total_children = slot_children.len();
-            let mut slotted_children_results = Vec::with_capacity(total_children);
-            let mut slotted_children_iter = slot_children.iter();
-
-            self.generate_node_sequence(
-                &mut slotted_children_iter,
-                &mut slotted_children_results,
-                total_children,
-                false,
-            );
+        // Conditional slots are already folded and generated by `generate_conditional_slot`
+        if let Some(ref v_for) = directives.v_for {
+            let looped_slot = self.generate_looped_slot(v_for, slot_node, false);
+            out_dynamic_slots.push(Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(looped_slot),
+            }));
+            return;
+        }
 
-            let slot_name = v_slot
-                .slot_name
-                .to_owned()
-                .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
-            let span = DUMMY_SP; // todo?
+        // Generate the children of the `<template v-slot>`
+        let slotted_children_results = self.generate_slot_children(&slot_node.children);
 
-            out_static_slots.push(self.generate_slot_shell(
-                slot_name,
-                slotted_children_results,
-                v_slot.value.as_deref(),
-                span,
-            ));
-        }
+        let slot_name = v_slot
+            .slot_name
+            .to_owned()
+            .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+        let span = DUMMY_SP; // todo?
+
+        out_static_slots.push(self.generate_slot_shell(
+            slot_name,
+            slotted_children_results,
+            v_slot.value.as_deref(),
+            span,
+        ));
     }
 
     /// Creates the SWC identifier from a tag name. Will fetch from cache if present
-    fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
+    pub(crate) fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
         // Cached
         let existing_component_binding = self.bindings_helper.components.get(tag_name);
         match existing_component_binding {
@@ -525,12 +742,33 @@ impl CodegenContext {
             _ => {}
         }
 
-        // _component_ prefix plus tag name
-        let mut component_name = tag_name.replace('-', "_");
-        component_name.insert_str(0, "_component_");
+        // `<Foo.Bar.Baz>` is a member expression `_component_Foo.Bar.Baz` over the `Foo` component
+        if let Some((namespace, member)) = tag_name.rsplit_once('.') {
+            let namespace = FervidAtom::from(namespace);
+            let component_expr = Expr::Member(MemberExpr {
+                span,
+                obj: Box::new(self.get_component_identifier(&namespace, span)),
+                prop: MemberProp::Ident(Ident {
+                    span,
+                    sym: FervidAtom::from(member),
+                    optional: false,
+                }),
+            });
 
-        // To create an identifier, we need to convert it to an SWC JsWord
-        let component_name = FervidAtom::from(component_name);
+            self.bindings_helper.components.insert(
+                tag_name.to_owned(),
+                ComponentBinding::Resolved(Box::new(component_expr.to_owned())),
+            );
+
+            return component_expr;
+        }
+
+        // _component_ prefix plus tag name.
+        // The name may contain characters which are not valid in an identifier,
+        // but the component is still resolved by its verbatim name,
+        // so that the runtime can warn about it in DEV
+        let component_name =
+            to_valid_asset_id(tag_name, &self.bindings_helper.ident_prefixes.component);
 
         // Directive will be resolved during runtime, this provides a variable name,
         // e.g. `const _component_custom = resolveComponent('custom')`
@@ -550,7 +788,7 @@ impl CodegenContext {
     }
 
     // Generates `withDirectives(expr, [directives])`
-    fn generate_component_directives(
+    pub(crate) fn generate_component_directives(
         &mut self,
         create_component_expr: Expr,
         component_node: &ElementNode,
@@ -576,6 +814,19 @@ impl CodegenContext {
         slot_binding: Option<&Pat>,
         span: Span,
     ) -> PropOrSpread {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(slot_name, span),
+            value: Box::new(self.generate_slot_fn(slot_children, slot_binding, span)),
+        })))
+    }
+
+    /// Generates `withCtx((_maybeCtx_) => [slot, children])`
+    fn generate_slot_fn(
+        &mut self,
+        slot_children: Vec<Expr>,
+        slot_binding: Option<&Pat>,
+        span: Span,
+    ) -> Expr {
         // e.g. child1, child2, child3
         let children_elems = slot_children
             .into_iter()
@@ -601,35 +852,76 @@ impl CodegenContext {
             Vec::new()
         };
 
-        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-            key: str_or_expr_to_propname(slot_name, span),
-            value: Box::new(Expr::Call(CallExpr {
+        Expr::Call(CallExpr {
+            span,
+            // withCtx
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
                 span,
-                // withCtx
-                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                sym: self.get_and_add_import_ident(VueImports::WithCtx),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                // () => [child1, child2, child3]
+                expr: Box::new(Expr::Arrow(ArrowExpr {
                     span,
-                    sym: self.get_and_add_import_ident(VueImports::WithCtx),
-                    optional: false,
-                }))),
-                args: vec![ExprOrSpread {
-                    spread: None,
-                    // () => [child1, child2, child3]
-                    expr: Box::new(Expr::Arrow(ArrowExpr {
-                        span,
-                        params,
-                        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Array(children_arr)))),
-                        is_async: false,
-                        is_generator: false,
-                        type_params: None,
-                        return_type: None,
-                    })),
-                }],
-                type_args: None,
-            })),
-        })))
+                    params,
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Array(children_arr)))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }],
+            type_args: None,
+        })
+    }
+
+    /// Generates the children of a `<template v-slot>`
+    pub(crate) fn generate_slot_children(&mut self, slot_children: &[Node]) -> Vec<Expr> {
+        let total_children = slot_children.len();
+        let mut slotted_children_results = Vec::with_capacity(total_children);
+        let mut slotted_children_iter = slot_children.iter();
+
+        self.generate_node_sequence(
+            &mut slotted_children_iter,
+            &mut slotted_children_results,
+            total_children,
+            false,
+        );
+
+        slotted_children_results
     }
 }
 
+/// `_: 1` for the stable slots, `_: 2` for the dynamic and `_: 3` for the forwarded ones
+pub(crate) fn generate_slots_flag(component_node: &ElementNode, span: Span) -> PropOrSpread {
+    let slots_flag = if component_node
+        .patch_hints
+        .flags
+        .contains(PatchFlags::DynamicSlots)
+    {
+        2.0
+    } else if component_node.patch_hints.has_forwarded_slots {
+        3.0
+    } else {
+        1.0
+    };
+
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(Ident {
+            span,
+            sym: fervid_atom!("_"),
+            optional: false,
+        }),
+        value: Box::new(Expr::Lit(Lit::Num(Number {
+            span,
+            value: slots_flag,
+            raw: None,
+        }))),
+    })))
+}
+
 #[inline]
 fn null(span: Span) -> Box<Expr> {
     Box::new(Expr::Lit(Lit::Null(Null { span })))
@@ -638,7 +930,8 @@ fn null(span: Span) -> Box<Expr> {
 #[cfg(test)]
 mod tests {
     use fervid_core::{
-        AttributeOrBinding, ElementKind, Interpolation, Node, StartingTag, VBindDirective,
+        AttributeOrBinding, BuiltinType, Conditional, ElementKind, Interpolation, Node,
+        StartingTag, VBindDirective,
     };
 
     use crate::test_utils::js;
@@ -684,6 +977,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_uses_custom_component_prefix() {
+        // <test-component></test-component>
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.ident_prefixes.component = FervidAtom::from("$c_");
+        let out = ctx.generate_component_vnode(
+            &ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            false,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r"_createVNode($c_test_component)"
+        );
+    }
+
     #[test]
     fn it_generates_attributes() {
         // <test-component foo="bar" :baz="qux"></test-component>
@@ -695,6 +1014,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(VBindDirective {
                             argument: Some("some-baz".into()),
@@ -702,6 +1022,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -747,7 +1068,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
 
@@ -798,7 +1119,264 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_default_slot_comments() {
+        let component = |children: Vec<Node>| ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "test-component".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Component,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        // <test-component><!-- only a comment --></test-component>
+        test_out(
+            component(vec![Node::Comment(" only a comment ".into(), DUMMY_SP)]),
+            r#"_createVNode(_component_test_component)"#,
+            false,
+        );
+
+        // <test-component><!-- comment -->hello</test-component>
+        test_out(
+            component(vec![
+                Node::Comment(" comment ".into(), DUMMY_SP),
+                Node::Text("hello".into(), DUMMY_SP),
+            ]),
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createCommentVNode(" comment "),_createTextVNode("hello")]),_:1})"#,
+            false,
+        );
+
+        // <test-component>
+        //   <!-- comment -->
+        //   <template v-slot:named>hello</template>
+        // </test-component>
+        test_out(
+            component(vec![
+                Node::Comment(" comment ".into(), DUMMY_SP),
+                Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "template".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some("named".into()),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                }),
+            ]),
+            r#"_createVNode(_component_test_component,null,{named:_withCtx(()=>[_createTextVNode("hello")]),_:1})"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_dynamic_slot_name() {
+        // <test-component>
+        //   <template v-slot:[name]>hi</template>
+        // </test-component>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::DynamicSlots;
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "template".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some(StrOrExpr::Expr(js("name"))),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![Node::Text("hi".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,{[name]:_withCtx(()=>[_createTextVNode("hi")]),_:2},1024)"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_conditional_slots() {
+        // <test-component>
+        //   <template v-if="foo" #foo>foo</template>
+        //   <template v-else #bar>bar</template>
+        //   hi
+        // </test-component>
+        fn slot_template(slot_name: &str) -> ElementNode {
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "template".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: Some(StrOrExpr::Str(slot_name.into())),
+                            value: None,
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Text(slot_name.into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            }
+        }
+
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::DynamicSlots;
+        let mut component = ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "test-component".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![
+                Node::ConditionalSeq(ConditionalNodeSequence {
+                    if_node: Box::new(Conditional {
+                        condition: *js("foo"),
+                        node: slot_template("foo"),
+                    }),
+                    else_if_nodes: vec![],
+                    else_node: Some(Box::new(slot_template("bar"))),
+                }),
+                Node::Text("hi".into(), DUMMY_SP),
+            ],
+            template_scope: 0,
+            kind: ElementKind::Component,
+            patch_hints,
+            span: DUMMY_SP,
+        };
+
+        test_out(
+            component.clone(),
+            r#"_createVNode(_component_test_component,null,_createSlots({"default":_withCtx(()=>[_createTextVNode("hi")]),_:2},[foo?{name:"foo",fn:_withCtx(()=>[_createTextVNode("foo")]),key:"0"}:{name:"bar",fn:_withCtx(()=>[_createTextVNode("bar")]),key:"1"}]),1024)"#,
+            false,
+        );
+
+        // Without `v-else`
+        let Node::ConditionalSeq(ref mut conditional_seq) = component.children[0] else {
+            unreachable!()
+        };
+        conditional_seq.else_node = None;
+        test_out(
+            component,
+            r#"_createVNode(_component_test_component,null,_createSlots({"default":_withCtx(()=>[_createTextVNode("hi")]),_:2},[foo?{name:"foo",fn:_withCtx(()=>[_createTextVNode("foo")]),key:"0"}:undefined]),1024)"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_looped_slots() {
+        // <test-component>
+        //   <template v-for="item in list" #[item.name]>hi</template>
+        // </test-component>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.flags |= PatchFlags::DynamicSlots;
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "template".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_for: Some(VForDirective {
+                                iterable: js("list"),
+                                itervar: js("item"),
+                                patch_flags: Default::default(),
+                            }),
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some(StrOrExpr::Expr(js("item.name"))),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![Node::Text("hi".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,_createSlots({_:2},[_renderList(list,item=>{return{name:item.name,fn:_withCtx(()=>[_createTextVNode("hi")])}})]),1024)"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_forwarded_slots() {
+        // <test-component><slot /></test-component>
+        let mut patch_hints = PatchHints::default();
+        patch_hints.has_forwarded_slots = true;
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "slot".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    kind: ElementKind::Builtin(BuiltinType::Slot),
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints,
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_renderSlot(_ctx.$slots,"default")]),_:3})"#,
             false,
         );
     }
@@ -852,7 +1430,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
     }
@@ -934,7 +1512,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot "+_toDisplayString(one),1)]),baz:_withCtx(()=>[_createTextVNode("hello from slot "),_createElementVNode("b",null,"two")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot "+_toDisplayString(one),1)]),baz:_withCtx(()=>[_createTextVNode("hello from slot "),_createElementVNode("b",null,"two")]),_:1})"#,
             false,
         );
     }
@@ -990,7 +1568,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
 
@@ -1062,7 +1640,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),_:1})"#,
             false,
         );
 
@@ -1115,7 +1693,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
     }
@@ -1209,9 +1787,106 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),baz:_withCtx(()=>[_createTextVNode("hello from baz")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),baz:_withCtx(()=>[_createTextVNode("hello from baz")]),_:1})"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_namespaced_component() {
+        // <Foo.Bar.Baz />
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_component_vnode(
+            &ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "Foo.Bar.Baz".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
             false,
         );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            "_createVNode(_component_Foo.Bar.Baz)"
+        );
+
+        // Only the namespace is resolved in runtime
+        let resolves = ctx.generate_component_resolves();
+        assert_eq!(resolves.len(), 1);
+        assert_eq!(
+            crate::test_utils::to_str(resolves[0].to_owned()),
+            r#"_component_Foo=_resolveComponent("Foo")"#
+        );
+    }
+
+    #[test]
+    fn it_resolves_self_reference() {
+        // <tree-item /> inside `TreeItem.vue`
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.self_name = Some("TreeItem".into());
+        ctx.generate_component_vnode(
+            &ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "tree-item".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            false,
+        );
+
+        let resolves = ctx.generate_component_resolves();
+        assert_eq!(
+            crate::test_utils::to_str(resolves[0].to_owned()),
+            r#"_component_tree_item=_resolveComponent("tree-item",true)"#
+        );
+    }
+
+    #[test]
+    fn it_resolves_components_by_verbatim_name() {
+        // <my:comp /><my-comp />
+        let mut ctx = CodegenContext::default();
+        for tag_name in ["my:comp", "my-comp"] {
+            ctx.generate_component_vnode(
+                &ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: tag_name.into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    kind: ElementKind::Component,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                },
+                false,
+            );
+        }
+
+        let resolves: Vec<String> = ctx
+            .generate_component_resolves()
+            .into_iter()
+            .map(crate::test_utils::to_str)
+            .collect();
+        assert_eq!(
+            resolves,
+            vec![
+                r#"_component_my_comp=_resolveComponent("my-comp")"#,
+                r#"_component_my58comp=_resolveComponent("my:comp")"#,
+            ]
+        );
     }
 
     fn test_out(input: ElementNode, expected: &str, wrap_in_block: bool) {
@@ -1,12 +1,107 @@
-use fervid_core::BindingsHelper;
+use fervid_core::{BindingsHelper, FervidAtom};
+use swc_core::{
+    common::{
+        comments::{Comments, SingleThreadedComments},
+        BytePos, Span, SyntaxContext,
+    },
+    ecma::ast::{CallExpr, Expr},
+};
 
+use crate::{hmr::HmrOptions, imports::ModuleFormat, style_injection::StyleInjectionOptions};
+
+/// Context for generating the code of a single SFC.
+///
+/// The context owns all of its state and is `Send + Sync`,
+/// so a separate context can be created per SFC on any thread.
 #[derive(Debug, Default)]
 pub struct CodegenContext {
     pub bindings_helper: BindingsHelper,
+    /// Whether to cache the event handlers in the render function,
+    /// e.g. `onClick: _cache[0] || (_cache[0] = $event => (_ctx.count++))`.
+    /// The `_cache` slots are shared with `v-once` and `v-memo`, see [`CodegenContext::allocate_cache_index`]
+    pub cache_handlers: bool,
+    /// Whether to hoist the static element subtrees and constant props objects
+    /// out of the render function, e.g. `const _hoisted_1 = { class: "foo" }`
+    pub hoist_static: bool,
+    /// Whether to keep a static `style` attribute as a string instead of parsing it into an object.
+    /// The static style is still parsed when it is merged with a `:style` binding
+    pub keep_style_string: bool,
+    /// Whether the `<slot>` outlets opt out of the scoped styles of this component,
+    /// i.e. there are `scoped` styles but none of them use `:slotted()`
+    pub no_slotted: bool,
+    /// Scope ID attribute of the `scoped` styles, e.g. `data-v-abcd1234`.
+    /// Only set when the SFC has `scoped` styles
+    pub scope_id: Option<FervidAtom>,
+    /// Hot module replacement code to add to the module, only used in DEV.
+    /// See [`CodegenContext::generate_hmr`]
+    pub hmr: Option<HmrOptions>,
+    /// Whether to generate ES module `import`/`export` or CommonJS `require`/`module.exports`
+    pub module_format: ModuleFormat,
+    /// Module to import the Vue helpers from instead of `vue`,
+    /// e.g. `@vue/runtime-dom` or a custom shim
+    pub runtime_module: Option<FervidAtom>,
+    /// Compiled styles to embed in the module and inject at runtime.
+    /// See [`CodegenContext::generate_style_injection`]
+    pub style_injection: Option<StyleInjectionOptions>,
+    /// Expressions hoisted to the top level as `_hoisted_N`, see [`CodegenContext::hoist`]
+    pub(crate) hoists: Vec<Expr>,
+    /// Whether the SSR generation is inside a slot function,
+    /// where the elements get the `_scopeId` of the slot
+    pub(crate) ssr_in_slot: bool,
+    /// Next free index in the `_cache` array
+    cache_index: u32,
+    /// Next free `key` of the conditional branches, see [`CodegenContext::allocate_branch_key`]
+    branch_key: u32,
+    /// Positions of the calls annotated with `/*#__PURE__*/`, see [`CodegenContext::mark_pure`]
+    pure_calls: Vec<BytePos>,
 }
 
+/// Generated calls have no position in the source, so the pure calls get their positions
+/// from the end of the `BytePos` range, which SWC reserves for the comments of synthesized code
+const PURE_CALL_POS_START: u32 = u32::MAX - (1 << 16);
+
 impl CodegenContext {
     pub fn with_bindings_helper(bindings_helper: BindingsHelper) -> CodegenContext {
-        CodegenContext { bindings_helper }
+        CodegenContext {
+            bindings_helper,
+            ..Default::default()
+        }
+    }
+
+    /// Allocates a slot in the `_cache` array of the render function
+    pub fn allocate_cache_index(&mut self) -> u32 {
+        let index = self.cache_index;
+        self.cache_index += 1;
+        index
+    }
+
+    /// Allocates a `key` for a branch of `v-if`/`v-else-if`/`v-else`.
+    /// The keys only need to differ between the sibling branches,
+    /// so a single counter is shared by the whole template
+    pub fn allocate_branch_key(&mut self) -> u32 {
+        let key = self.branch_key;
+        self.branch_key += 1;
+        key
+    }
+
+    /// Annotates a generated call with a leading `/*#__PURE__*/` comment,
+    /// so that bundlers can tree-shake it, e.g. `/*#__PURE__*/_createElementVNode(/*...*/)`.
+    ///
+    /// The comment is only printed when [`CodegenContext::comments`] are passed to the emitter
+    pub fn mark_pure(&mut self, call_expr: &mut CallExpr) {
+        let pos = BytePos(PURE_CALL_POS_START + self.pure_calls.len() as u32);
+        call_expr.span = Span::new(pos, pos, SyntaxContext::empty());
+        self.pure_calls.push(pos);
+    }
+
+    /// Comments of the generated code, i.e. the `/*#__PURE__*/` annotations.
+    /// They are needed to print the module and to keep the annotations in other SWC passes
+    pub fn comments(&self) -> SingleThreadedComments {
+        let comments = SingleThreadedComments::default();
+        for pos in self.pure_calls.iter() {
+            comments.add_pure_comment(*pos);
+        }
+
+        comments
     }
 }
@@ -1,14 +1,14 @@
-use fervid_core::{ElementKind, ElementNode, Node, VueImports};
+use fervid_core::{ElementKind, ElementNode, Node, PatchFlags, VueImports};
 use smallvec::SmallVec;
 use swc_core::{
     common::{BytePos, Span, SyntaxContext},
     ecma::ast::{
-        BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Number, ParenExpr,
+        BinExpr, BinaryOp, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, ParenExpr,
         SeqExpr,
     },
 };
 
-use crate::context::CodegenContext;
+use crate::{context::CodegenContext, hoisting::is_static_element};
 
 type TextNodesConcatenationVec = SmallVec<[Expr; 3]>;
 
@@ -34,19 +34,48 @@ impl CodegenContext {
         element_node: &ElementNode,
         wrap_in_block: bool,
     ) -> Expr {
+        // Blocks are never hoisted, because they need to track their dynamic children
+        if self.hoist_static && !wrap_in_block && is_static_element(element_node) {
+            return self.generate_hoisted_element(element_node);
+        }
+
+        let directives = element_node.starting_tag.directives.as_deref();
+        let v_for = directives.and_then(|directives| directives.v_for.as_ref());
+        let v_memo = directives.and_then(|directives| directives.v_memo.as_deref());
+
+        // `v-once` vnode is cached and does not take part in block tracking.
+        // `v-memo` vnode is always a block.
+        // `v-for` items are blocks on their own, because the fragment does not track them,
+        // unless the fragment is stable, e.g. `v-for="i in 3"`
+        let is_v_once = directives.map_or(false, |directives| directives.v_once.is_some());
+        let wrap_in_block = match v_for {
+            Some(v_for) => !v_for.patch_flags.contains(PatchFlags::StableFragment),
+            None => wrap_in_block,
+        };
+        let wrap_in_block = (wrap_in_block || v_memo.is_some()) && !is_v_once;
+
         let mut result = match element_node.kind {
             ElementKind::Builtin(builtin_type) => self.generate_builtin(element_node, builtin_type),
 
+            // `<template v-for>` with a single element renders the element itself
+            ElementKind::Element if v_for.is_some() => self
+                .generate_v_for_template_child(element_node, wrap_in_block)
+                .unwrap_or_else(|| self.generate_element_vnode(element_node, wrap_in_block)),
+
             ElementKind::Element => self.generate_element_vnode(element_node, wrap_in_block),
 
             ElementKind::Component => self.generate_component_vnode(element_node, wrap_in_block),
         };
 
-        // Generate `v-for` if it is present
-        if let Some(ref directives) = element_node.starting_tag.directives {
-            if let Some(ref v_for) = directives.v_for {
-                result = self.generate_v_for(v_for, result);
-            }
+        // Generate `v-for` if it is present, `v-memo` is then checked for each item
+        if let Some(v_for) = v_for {
+            result = self.generate_v_for(v_for, result, v_memo);
+        } else if let Some(v_memo) = v_memo {
+            result = self.generate_v_memo(v_memo, result);
+        }
+
+        if is_v_once {
+            result = self.generate_v_once(result);
         }
 
         result
@@ -102,7 +131,29 @@ impl CodegenContext {
             };
         }
 
+        // Consecutive static elements are generated together, so that they can be stringified
+        let mut static_elements: Vec<&ElementNode> = Vec::new();
+
         while let Some(node) = iter.next() {
+            if self.hoist_static {
+                match node {
+                    Node::Element(element_node) if is_static_element(element_node) => {
+                        maybe_concatenate_text_nodes!();
+                        patch_flag_text = false;
+
+                        static_elements.push(element_node);
+                        continue;
+                    }
+
+                    _ if !static_elements.is_empty() => {
+                        self.generate_static_elements(&static_elements, out);
+                        static_elements.clear();
+                    }
+
+                    _ => {}
+                }
+            }
+
             let generated = self.generate_node(node, false);
             let is_text_node = matches!(node, Node::Text(_, _) | Node::Interpolation { .. });
 
@@ -128,13 +179,30 @@ impl CodegenContext {
             }
         }
 
-        // Process the remaining text nodes.
+        // Process the remaining static elements and text nodes.
+        // Only one of them can be non-empty at this point
+        if !static_elements.is_empty() {
+            self.generate_static_elements(&static_elements, out);
+        }
         maybe_concatenate_text_nodes!()
     }
 
     /// Wraps the expression in openBlock construction,
-    /// e.g. `(openBlock(), expr)`
-    pub fn wrap_in_open_block(&mut self, expr: Expr, span: Span) -> Expr {
+    /// e.g. `(openBlock(), expr)`.
+    ///
+    /// With `disable_tracking`, the block does not collect its dynamic children,
+    /// e.g. `(openBlock(true), expr)` for the `v-for` fragments, which are fully diffed instead
+    pub fn wrap_in_open_block(&mut self, expr: Expr, disable_tracking: bool, span: Span) -> Expr {
+        // `openBlock(true)`
+        let open_block_args = if disable_tracking {
+            vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
+            }]
+        } else {
+            Vec::new()
+        };
+
         Expr::Paren(ParenExpr {
             span,
             expr: Box::new(Expr::Seq(SeqExpr {
@@ -148,7 +216,7 @@ impl CodegenContext {
                             sym: self.get_and_add_import_ident(VueImports::OpenBlock),
                             optional: false,
                         }))),
-                        args: Vec::new(),
+                        args: open_block_args,
                         type_args: None,
                     })),
                     Box::new(expr),
@@ -195,15 +263,11 @@ impl CodegenContext {
         });
 
         // Add patch flag
-        // `concatenation, 1`
+        // `concatenation, 1 /* TEXT */`
         if patch_flag_text {
             create_text_vnode_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: 1.0,
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(PatchFlags::Text.into(), span)),
             })
         }
 
@@ -1,13 +1,35 @@
-use fervid_core::ConditionalNodeSequence;
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, ConditionalNodeSequence, ElementNode,
+    StrOrExpr, VBindDirective,
+};
 use swc_core::{
     common::{Spanned, DUMMY_SP},
-    ecma::ast::{CondExpr, Expr},
+    ecma::ast::{CondExpr, Expr, Lit, Number},
 };
 
 use crate::context::CodegenContext;
 
 impl CodegenContext {
     pub fn generate_conditional_seq(&mut self, conditional_seq: &ConditionalNodeSequence) -> Expr {
+        // `v-once` on the `v-if` node caches the whole conditional, not only the branch,
+        // so that the conditions are not evaluated again
+        let is_v_once = conditional_seq
+            .if_node
+            .node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_once.is_some());
+        if is_v_once {
+            let mut uncached_seq = conditional_seq.to_owned();
+            if let Some(ref mut directives) = uncached_seq.if_node.node.starting_tag.directives {
+                directives.v_once = None;
+            }
+
+            let conditional_expr = self.generate_conditional_seq(&uncached_seq);
+            return self.generate_v_once(conditional_expr);
+        }
+
         let mut conditional_exprs = Vec::new();
 
         // First, push the `if` node
@@ -16,9 +38,7 @@ impl CodegenContext {
         let if_element_node = &if_conditional.node;
         // let _has_js = transform_scoped(&mut if_expr, &self.scope_helper, if_element_node.template_scope);
         conditional_exprs.push(Box::new(if_expr.to_owned()));
-        conditional_exprs.push(Box::new(
-            self.generate_element_or_component(if_element_node, true),
-        ));
+        conditional_exprs.push(Box::new(self.generate_conditional_branch(if_element_node)));
 
         // Then, push all the `else-if` nodes
         for else_if_conditional in conditional_seq.else_if_nodes.iter() {
@@ -27,14 +47,12 @@ impl CodegenContext {
 
             // let _has_js = transform_scoped(&mut else_if_expr, &self.scope_helper, else_if_node.template_scope);
             conditional_exprs.push(Box::new(else_if_expr.to_owned()));
-            conditional_exprs.push(Box::new(
-                self.generate_element_or_component(else_if_node, true),
-            ));
+            conditional_exprs.push(Box::new(self.generate_conditional_branch(else_if_node)));
         }
 
         // Push either `else` or a comment node
         let else_expr = if let Some(ref else_node) = conditional_seq.else_node {
-            self.generate_element_or_component(else_node, true)
+            self.generate_conditional_branch(else_node)
         } else {
             self.generate_comment_vnode("v-if", DUMMY_SP)
         };
@@ -68,11 +86,44 @@ impl CodegenContext {
         // I don't like the idea of dereferencing a Box, but the signature requires it
         *resulting_expr
     }
+
+    /// Generates a branch with `key: N` added to its props, unless the key is provided.
+    /// This way switching between the branches of the same tag replaces the element
+    fn generate_conditional_branch(&mut self, branch_node: &ElementNode) -> Expr {
+        let key = self.allocate_branch_key();
+
+        let has_key = branch_node
+            .starting_tag
+            .attributes
+            .iter()
+            .any(|attr| check_attribute_name(attr, "key"));
+        if has_key {
+            return self.generate_element_or_component(branch_node, true);
+        }
+
+        let mut keyed_node = branch_node.to_owned();
+        keyed_node.starting_tag.attributes.insert(
+            0,
+            AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(fervid_atom!("key"))),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: key as f64,
+                    raw: None,
+                }))),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            }),
+        );
+        self.generate_element_or_component(&keyed_node, true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementNode, Node, StartingTag, Conditional, ElementKind};
+    use fervid_core::{ElementNode, Node, StartingTag, Conditional, ElementKind, VueDirectives};
 
     use crate::test_utils::js;
 
@@ -101,7 +152,7 @@ mod tests {
                 else_if_nodes: vec![],
                 else_node: None,
             },
-            r#"foo||true?(_openBlock(),_createElementBlock("h1",null,"hello")):_createCommentVNode("v-if")"#,
+            r#"foo||true?(_openBlock(),_createElementBlock("h1",{key:0},"hello")):_createCommentVNode("v-if")"#,
         )
     }
 
@@ -140,7 +191,7 @@ mod tests {
                     span: DUMMY_SP,
                 })),
             },
-            r#"foo||true?(_openBlock(),_createElementBlock("h1",null,"hello")):(_openBlock(),_createElementBlock("h2",null,"bye"))"#,
+            r#"foo||true?(_openBlock(),_createElementBlock("h1",{key:0},"hello")):(_openBlock(),_createElementBlock("h2",{key:1},"bye"))"#,
         )
     }
 
@@ -200,7 +251,7 @@ mod tests {
                 ],
                 else_node: None,
             },
-            r#"foo?(_openBlock(),_createElementBlock("h1",null,"hello")):true?(_openBlock(),_createElementBlock("h2",null,"hi")):undefined?(_openBlock(),_createElementBlock("h3",null,"bye")):_createCommentVNode("v-if")"#,
+            r#"foo?(_openBlock(),_createElementBlock("h1",{key:0},"hello")):true?(_openBlock(),_createElementBlock("h2",{key:1},"hi")):undefined?(_openBlock(),_createElementBlock("h3",{key:2},"bye")):_createCommentVNode("v-if")"#,
         )
     }
 
@@ -272,7 +323,95 @@ mod tests {
                     span: DUMMY_SP,
                 })),
             },
-            r#"foo?(_openBlock(),_createElementBlock("h1",null,"hello")):true?(_openBlock(),_createElementBlock("h2",null,"hi")):undefined?(_openBlock(),_createElementBlock("h3",null,"good morning")):(_openBlock(),_createElementBlock("h4",null,"bye"))"#,
+            r#"foo?(_openBlock(),_createElementBlock("h1",{key:0},"hello")):true?(_openBlock(),_createElementBlock("h2",{key:1},"hi")):undefined?(_openBlock(),_createElementBlock("h3",{key:2},"good morning")):(_openBlock(),_createElementBlock("h4",{key:3},"bye"))"#,
+        )
+    }
+
+    #[test]
+    fn it_keeps_provided_branch_key() {
+        // <h1 v-if="foo" :key="id">hello</h1>
+        // <h2 v-else>bye</h2>
+        test_out(
+            ConditionalNodeSequence {
+                if_node: Box::new(Conditional {
+                    condition: *js("foo"),
+                    node: ElementNode {
+                        starting_tag: StartingTag {
+                            tag_name: "h1".into(),
+                            attributes: vec![AttributeOrBinding::VBind(VBindDirective {
+                                argument: Some("key".into()),
+                                value: js("id"),
+                                is_camel: false,
+                                is_prop: false,
+                                is_attr: false,
+                                span: DUMMY_SP,
+                            })],
+                            directives: None,
+                        },
+                        children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                        template_scope: 0,
+                        kind: ElementKind::Element,
+                        patch_hints: Default::default(),
+                        span: DUMMY_SP,
+                    },
+                }),
+                else_if_nodes: vec![],
+                else_node: Some(Box::new(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "h2".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![Node::Text("bye".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })),
+            },
+            r#"foo?(_openBlock(),_createElementBlock("h1",{key:id},"hello")):(_openBlock(),_createElementBlock("h2",{key:1},"bye"))"#,
+        )
+    }
+
+    #[test]
+    fn it_caches_v_once_conditional() {
+        // <h1 v-if="foo" v-once>hello</h1>
+        // <h2 v-else>bye</h2>
+        test_out(
+            ConditionalNodeSequence {
+                if_node: Box::new(Conditional {
+                    condition: *js("foo"),
+                    node: ElementNode {
+                        starting_tag: StartingTag {
+                            tag_name: "h1".into(),
+                            attributes: vec![],
+                            directives: Some(Box::new(VueDirectives {
+                                v_once: Some(()),
+                                ..Default::default()
+                            })),
+                        },
+                        children: vec![Node::Text("hello".into(), DUMMY_SP)],
+                        template_scope: 0,
+                        kind: ElementKind::Element,
+                        patch_hints: Default::default(),
+                        span: DUMMY_SP,
+                    },
+                }),
+                else_if_nodes: vec![],
+                else_node: Some(Box::new(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "h2".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![Node::Text("bye".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })),
+            },
+            r#"_cache[0]||(_setBlockTracking(-1),_cache[0]=foo?(_openBlock(),_createElementBlock("h1",{key:0},"hello")):(_openBlock(),_createElementBlock("h2",{key:1},"bye")),_setBlockTracking(1),_cache[0])"#,
         )
     }
 
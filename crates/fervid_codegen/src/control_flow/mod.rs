@@ -3,4 +3,6 @@ mod conditional_seq;
 mod slotted_iterator;
 mod sfc;
 
+pub(crate) use sfc::{add_method, return_from_setup};
+pub use sfc::{parse_es_version, EmitOptions};
 pub use slotted_iterator::{SlottedIterator, SlottedIteratorMode};
@@ -1,15 +1,36 @@
-use fervid_core::{FervidAtom, SfcTemplateBlock, TemplateGenerationMode};
+use fervid_core::{BindingTypes, FervidAtom, SfcTemplateBlock, TemplateGenerationMode, VueImports};
 use swc_core::{
-    common::{FileName, SourceMap, DUMMY_SP},
-    ecma::ast::{
-        ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, Decl, ExportDefaultExpr, Expr,
-        Function, Ident, ImportDecl, MethodProp, Module, ModuleDecl, ModuleItem, ObjectLit, Param,
-        Pat, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Str, VarDecl, VarDeclKind,
+    common::{
+        chain,
+        comments::{Comments, NoopComments},
+        FileName, Globals, Mark, SourceMap, DUMMY_SP, GLOBALS,
+    },
+    ecma::{
+        ast::{
+            ArrowExpr, BinExpr, BinaryOp, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr,
+            Callee, ClassDecl, Decl, DefaultDecl, EsVersion, ExportDefaultDecl, ExportDefaultExpr,
+            Expr, ExprOrSpread, ExprStmt, FnDecl, Function, Ident, KeyValueProp, Lit, MemberExpr,
+            MemberProp, MethodProp, Module, ModuleDecl, ModuleItem, ObjectLit, Param, Pat, Prop,
+            PropName, PropOrSpread, ReturnStmt, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+        },
+        transforms::{
+            base::{
+                fixer::fixer,
+                helpers::{inject_helpers, Helpers, HELPERS},
+                hygiene::hygiene,
+                resolver,
+            },
+            compat::{es2015, es2016, es2017, es2018, es2019, es2020, es2021, es2022},
+        },
+        visit::{FoldWith, Optional},
     },
 };
 use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
 
-use crate::context::CodegenContext;
+use crate::{
+    context::CodegenContext,
+    imports::{convert_to_cjs, ModuleFormat},
+};
 
 impl CodegenContext {
     // TODO Generation mode? Is it relevant?
@@ -36,70 +57,59 @@ impl CodegenContext {
                 // Generates the render expression and appends it to the end of the `setup` function.
                 TemplateGenerationMode::Inline => {
                     let render_arrow = self.generate_render_arrow(template_expr);
-
-                    let setup_function = synthetic_setup_fn.get_or_insert_with(|| {
-                        Box::new(Function {
-                            params: vec![],
-                            decorators: vec![],
-                            span: DUMMY_SP,
-                            body: None,
-                            is_generator: false,
-                            is_async: false,
-                            type_params: None,
-                            return_type: None,
-                        })
-                    });
-
-                    let setup_body = setup_function.body.get_or_insert_with(|| BlockStmt {
-                        span: DUMMY_SP,
-                        stmts: Vec::with_capacity(1),
-                    });
-
-                    setup_body.stmts.push(Stmt::Return(ReturnStmt {
-                        span: DUMMY_SP,
-                        arg: Some(Box::new(Expr::Arrow(render_arrow))),
-                    }));
+                    return_from_setup(&mut synthetic_setup_fn, Expr::Arrow(render_arrow));
                 }
 
-                // Generates the render Function to be used as a property in exported object.
-                // `render(_ctx, _cache, $props, $setup, $data, $options) { /*...*/ }`
+                // Generates the standalone render function and attaches it to the exported object.
+                // `function _sfc_render(_ctx, _cache, $props, $setup, $data, $options) { /*...*/ }`
                 TemplateGenerationMode::RenderFn => {
                     let render_fn = self.generate_render_fn(template_expr);
+                    self.return_bindings_from_setup(&mut synthetic_setup_fn);
+                    add_fn_decl(
+                        &mut script,
+                        &mut sfc_export_obj,
+                        "render",
+                        "_sfc_render",
+                        render_fn,
+                    );
+                }
+            }
+        }
 
-                    // When a synthetic setup function is present,
-                    // we need to return bindings as its last statement
-                    'return_bindings: {
-                        let Some(ref mut setup_fn) = synthetic_setup_fn else {
-                            break 'return_bindings;
-                        };
+        self.finish_module(script, sfc_export_obj, synthetic_setup_fn)
+    }
 
-                        let Some(ref mut setup_body) = setup_fn.body else {
-                            break 'return_bindings;
-                        };
+    /// When a synthetic setup function is present,
+    /// we need to return bindings as its last statement
+    pub(crate) fn return_bindings_from_setup(
+        &self,
+        synthetic_setup_fn: &mut Option<Box<Function>>,
+    ) {
+        let Some(ref mut setup_fn) = synthetic_setup_fn else {
+            return;
+        };
 
-                        let return_bindings = self.generate_return_bindings();
-                        if !return_bindings.props.is_empty() {
-                            setup_body.stmts.push(Stmt::Return(ReturnStmt {
-                                span: DUMMY_SP,
-                                arg: Some(Box::new(Expr::Object(return_bindings))),
-                            }));
-                        }
-                    }
+        let Some(ref mut setup_body) = setup_fn.body else {
+            return;
+        };
 
-                    sfc_export_obj
-                        .props
-                        .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
-                            key: PropName::Ident(Ident {
-                                span: DUMMY_SP,
-                                sym: FervidAtom::from("render"),
-                                optional: false,
-                            }),
-                            function: Box::new(render_fn),
-                        }))));
-                }
-            }
+        let return_bindings = self.generate_return_bindings();
+        if !return_bindings.props.is_empty() {
+            setup_body.stmts.push(Stmt::Return(ReturnStmt {
+                span: DUMMY_SP,
+                arg: Some(Box::new(Expr::Object(return_bindings))),
+            }));
         }
+    }
 
+    /// Adds the `setup` function, the imports and the hoists, and exports the component.
+    /// The render function is expected to be already attached
+    pub(crate) fn finish_module(
+        &mut self,
+        mut script: Module,
+        mut sfc_export_obj: ObjectLit,
+        synthetic_setup_fn: Option<Box<Function>>,
+    ) -> Module {
         // Add the `setup` function to the exported object
         if let Some(setup_fn) = synthetic_setup_fn {
             match setup_fn.body {
@@ -121,34 +131,143 @@ impl CodegenContext {
             }
         }
 
-        // Append the Vue imports
+        // `__scopeId: "data-v-abcd1234"` makes the runtime add the attribute
+        // to the elements and to the slot content rendered by this component
+        if let Some(ref scope_id) = self.scope_id {
+            sfc_export_obj
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: FervidAtom::from("__scopeId"),
+                        optional: false,
+                    }),
+                    value: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: scope_id.to_owned(),
+                        raw: None,
+                    }))),
+                }))));
+        }
+
+        // `props: /*#__PURE__*/_mergeModels(/*...*/)`
+        self.mark_pure_helper_calls(&mut sfc_export_obj);
+
+        // Class components (e.g. `vue-facing-decorator`) are left untouched,
+        // and the generated fields are attached to the class instead
+        let class_component_ident = take_default_export_class(&mut script);
+
+        // TypeScript components are wrapped for the type inference,
+        // i.e. `/*#__PURE__*/_defineComponent({ /*...*/ })`
+        let define_component = match class_component_ident {
+            None if self.bindings_helper.is_ts => {
+                Some(self.get_and_add_import_ident(VueImports::DefineComponent))
+            }
+            _ => None,
+        };
+
+        // Hoists may add imports, e.g. `_pushScopeId`, so they are generated first
+        let hoists = self.generate_hoists();
+
+        // Add the Vue imports after the user imports.
+        // User imports are never reordered, because side-effect imports
+        // (e.g. `import './polyfill'`) must be evaluated before anything else.
         // TODO Smart merging with user imports?
         let used_imports = self.generate_imports();
-        if !used_imports.is_empty() {
+        let mut insert_at = script
+            .body
+            .iter()
+            .rposition(|item| matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(_))))
+            .map_or(0, |idx| idx + 1);
+        let used_ssr_imports = self.generate_ssr_imports();
+        let runtime_module = self.runtime_module.as_deref().unwrap_or("vue");
+        for (specifiers, src) in [
+            (used_imports, runtime_module),
+            (used_ssr_imports, "vue/server-renderer"),
+        ] {
+            if specifiers.is_empty() {
+                continue;
+            }
+
             script
                 .body
-                .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                .insert(insert_at, self.generate_import_item(specifiers, src));
+            insert_at += 1;
+        }
+
+        // Styles are injected as soon as the module is evaluated
+        if let Some(ref style_injection) = self.style_injection {
+            let injection = self.generate_style_injection(style_injection);
+            let injection_len = injection.len();
+            script.body.splice(insert_at..insert_at, injection);
+            insert_at += injection_len;
+        }
+
+        // Hoisted static nodes and props go right after the imports
+        script.body.splice(insert_at..insert_at, hoists);
+
+        let component_ident = match class_component_ident {
+            Some(component_ident) => {
+                attach_to_class_component(&mut script, &component_ident, sfc_export_obj);
+                Some(component_ident)
+            }
+
+            // HMR needs to reference the component, i.e. `const _sfc_main = { /*...*/ }`
+            None if self.hmr.is_some() => {
+                let component_ident = Ident {
                     span: DUMMY_SP,
-                    specifiers: used_imports,
-                    src: Box::new(Str {
+                    sym: FervidAtom::from("_sfc_main"),
+                    optional: false,
+                };
+
+                script
+                    .body
+                    .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
                         span: DUMMY_SP,
-                        value: FervidAtom::from("vue"),
-                        raw: None,
-                    }),
-                    type_only: false,
-                    with: None,
-                })));
+                        kind: VarDeclKind::Const,
+                        declare: false,
+                        decls: vec![VarDeclarator {
+                            span: DUMMY_SP,
+                            name: Pat::Ident(BindingIdent {
+                                id: component_ident.to_owned(),
+                                type_ann: None,
+                            }),
+                            init: Some(Box::new(
+                                self.generate_component_expr(define_component, sfc_export_obj),
+                            )),
+                            definite: false,
+                        }],
+                    })))));
+
+                Some(component_ident)
+            }
+
+            // Append the default export
+            None => {
+                let component_expr = self.generate_component_expr(define_component, sfc_export_obj);
+                let default_export = self.generate_default_export(component_expr);
+                script.body.push(default_export);
+                None
+            }
+        };
+
+        if let Some(component_ident) = component_ident {
+            if let Some(ref hmr_options) = self.hmr {
+                let hmr_stmts = self.generate_hmr(hmr_options, &component_ident);
+                script
+                    .body
+                    .extend(hmr_stmts.into_iter().map(ModuleItem::Stmt));
+            }
+
+            // `export default Foo`
+            let default_export = self.generate_default_export(Expr::Ident(component_ident));
+            script.body.push(default_export);
         }
 
-        // Append the default export
-        script
-            .body
-            .push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
-                ExportDefaultExpr {
-                    span: DUMMY_SP,
-                    expr: Box::new(Expr::Object(sfc_export_obj)),
-                },
-            )));
+        // Only the user imports and exports are left, the generated ones are already in CommonJS
+        if let ModuleFormat::Cjs = self.module_format {
+            convert_to_cjs(&mut script);
+        }
 
         script
     }
@@ -202,9 +321,10 @@ impl CodegenContext {
             };
         }
 
+        let prefixes = &self.bindings_helper.ident_prefixes;
         ArrowExpr {
             span: DUMMY_SP,
-            params: vec![param!("_ctx"), param!("_cache")],
+            params: vec![param!(&*prefixes.ctx), param!(&*prefixes.cache)],
             body,
             is_async: false,
             is_generator: false,
@@ -259,11 +379,12 @@ impl CodegenContext {
             };
         }
 
+        let prefixes = &self.bindings_helper.ident_prefixes;
         Function {
             // Render function params
             params: vec![
-                param!("_ctx"),
-                param!("_cache"),
+                param!(&*prefixes.ctx),
+                param!(&*prefixes.cache),
                 param!("$props"),
                 param!("$setup"),
                 param!("$data"),
@@ -284,37 +405,152 @@ impl CodegenContext {
 
     /// Generates bindings for a synthetic setup function when used in combination
     /// with `TemplateGenerationMode::RenderFn`.
+    ///
+    /// Only the bindings which are referenced in the template
+    /// (see `used_bindings` of [`fervid_core::BindingsHelper`]) or exposed using `defineExpose`
+    /// are returned. `<script setup>` bindings go first in the order they were declared.
     pub fn generate_return_bindings(&self) -> ObjectLit {
-        let mut props =
-            Vec::<PropOrSpread>::with_capacity(self.bindings_helper.used_bindings.len());
+        let bindings_helper = &self.bindings_helper;
 
-        for used_binding in self.bindings_helper.used_bindings.keys() {
-            props.push(PropOrSpread::Prop(Box::new(Prop::Shorthand(Ident {
-                span: DUMMY_SP,
-                sym: used_binding.to_owned(),
-                optional: false,
-            }))));
+        // Props are accessed through `$props`, Options API bindings through `_ctx`
+        let mut bindings: Vec<&FervidAtom> = bindings_helper
+            .used_bindings
+            .iter()
+            .filter(|(_, binding_type)| {
+                !matches!(
+                    binding_type,
+                    BindingTypes::Props
+                        | BindingTypes::PropsAliased
+                        | BindingTypes::Data
+                        | BindingTypes::Options
+                )
+            })
+            .map(|(binding, _)| binding)
+            .collect();
+
+        for exposed in bindings_helper.exposed_bindings.iter() {
+            let is_setup_binding = bindings_helper
+                .setup_bindings
+                .iter()
+                .any(|binding| binding.0 == *exposed);
+            if is_setup_binding && !bindings.contains(&exposed) {
+                bindings.push(exposed);
+            }
         }
 
+        // `used_bindings` is a hash map, the order is restored for a stable output
+        let declaration_index = |sym: &FervidAtom| {
+            bindings_helper
+                .setup_bindings
+                .iter()
+                .position(|binding| binding.0 == *sym)
+                .unwrap_or(usize::MAX)
+        };
+        bindings.sort_by(|a, b| {
+            declaration_index(a)
+                .cmp(&declaration_index(b))
+                .then_with(|| str::cmp(a, b))
+        });
+
+        let props: Vec<PropOrSpread> = bindings
+            .into_iter()
+            .map(|binding| {
+                PropOrSpread::Prop(Box::new(Prop::Shorthand(Ident {
+                    span: DUMMY_SP,
+                    sym: binding.to_owned(),
+                    optional: false,
+                })))
+            })
+            .collect();
+
         ObjectLit {
             span: DUMMY_SP,
             props,
         }
     }
 
+    /// `{ /*...*/ }` or `/*#__PURE__*/_defineComponent({ /*...*/ })`
+    fn generate_component_expr(
+        &mut self,
+        define_component: Option<FervidAtom>,
+        sfc_export_obj: ObjectLit,
+    ) -> Expr {
+        let Some(define_component) = define_component else {
+            return Expr::Object(sfc_export_obj);
+        };
+
+        let mut call_expr = CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: define_component,
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Object(sfc_export_obj)),
+            }],
+            type_args: None,
+        };
+        self.mark_pure(&mut call_expr);
+
+        Expr::Call(call_expr)
+    }
+
+    /// Marks the calls of the Vue helpers in the component object as pure,
+    /// e.g. `props: /*#__PURE__*/_mergeModels(/*...*/)`
+    fn mark_pure_helper_calls(&mut self, sfc_export_obj: &mut ObjectLit) {
+        let merge_models = VueImports::MergeModels.as_str();
+
+        for prop in sfc_export_obj.props.iter_mut() {
+            let PropOrSpread::Prop(ref mut prop) = prop else {
+                continue;
+            };
+            let Prop::KeyValue(ref mut key_value) = **prop else {
+                continue;
+            };
+            let Expr::Call(ref mut call_expr) = *key_value.value else {
+                continue;
+            };
+
+            let is_helper_call = match call_expr.callee {
+                Callee::Expr(ref callee_expr) => {
+                    matches!(**callee_expr, Expr::Ident(ref ident) if ident.sym == merge_models)
+                }
+                _ => false,
+            };
+            if is_helper_call {
+                self.mark_pure(call_expr);
+            }
+        }
+    }
+
     pub fn stringify(source: &str, item: &impl Node, minify: bool) -> String {
+        let emit_options = EmitOptions {
+            minify,
+            ..Default::default()
+        };
+
+        Self::stringify_with_options(source, item, &emit_options, None)
+    }
+
+    /// Same as [`CodegenContext::stringify`], but with the full control over the emitter.
+    /// Pass the [`CodegenContext::comments`] to print the `/*#__PURE__*/` annotations
+    pub fn stringify_with_options(
+        source: &str,
+        item: &impl Node,
+        emit_options: &EmitOptions,
+        comments: Option<&dyn Comments>,
+    ) -> String {
         // Emitting the result requires some setup with SWC
         let cm: swc_core::common::sync::Lrc<SourceMap> = Default::default();
         cm.new_source_file(FileName::Custom("test.ts".to_owned()), source.to_owned());
         let mut buff: Vec<u8> = Vec::new();
         let writer: JsWriter<&mut Vec<u8>> = JsWriter::new(cm.clone(), "\n", &mut buff, None);
 
-        let mut emitter_cfg = swc_ecma_codegen::Config::default();
-        emitter_cfg.minify = minify;
-
         let mut emitter = Emitter {
-            cfg: emitter_cfg,
-            comments: None,
+            cfg: emit_options.to_emitter_config(),
+            comments,
             wr: writer,
             cm,
         };
@@ -323,4 +559,768 @@ impl CodegenContext {
 
         String::from_utf8(buff).unwrap()
     }
+
+    /// Same as [`CodegenContext::stringify_with_options`], but also generates a source map in JSON format.
+    ///
+    /// Only the nodes which kept their original spans are mapped back to `filename`,
+    /// e.g. user statements moved from `<script setup>` into the `setup` function.
+    /// Synthesized code uses dummy spans and is not mapped.
+    pub fn stringify_with_source_map(
+        source: &str,
+        filename: &str,
+        item: &impl Node,
+        emit_options: &EmitOptions,
+        comments: Option<&dyn Comments>,
+    ) -> (String, String) {
+        let cm: swc_core::common::sync::Lrc<SourceMap> = Default::default();
+        cm.new_source_file(FileName::Custom(filename.to_owned()), source.to_owned());
+        let mut buff: Vec<u8> = Vec::new();
+        let mut source_map_entries = Vec::new();
+        let writer: JsWriter<&mut Vec<u8>> = JsWriter::new(
+            cm.clone(),
+            "\n",
+            &mut buff,
+            Some(&mut source_map_entries),
+        );
+
+        let mut emitter = Emitter {
+            cfg: emit_options.to_emitter_config(),
+            comments,
+            wr: writer,
+            cm: cm.clone(),
+        };
+
+        let _ = item.emit_with(&mut emitter);
+
+        let mut source_map: Vec<u8> = Vec::new();
+        let _ = cm
+            .build_source_map(&source_map_entries)
+            .to_writer(&mut source_map);
+
+        (
+            String::from_utf8(buff).unwrap(),
+            String::from_utf8(source_map).unwrap(),
+        )
+    }
+}
+
+/// How the generated module is printed, see [`CodegenContext::stringify_with_options`].
+/// The defaults are the same as the SWC emitter defaults, except for the `target`,
+/// which is the latest ES version, so that the code is not downleveled unless asked
+#[derive(Debug, Clone, Copy)]
+pub struct EmitOptions {
+    /// Minified output instead of the pretty-printed one
+    pub minify: bool,
+    /// Escape the non-ASCII characters in strings, template literals and regular expressions
+    pub ascii_only: bool,
+    /// ES version of the output. The newer syntax is downleveled by [`EmitOptions::downlevel`],
+    /// e.g. `a?.b` for the targets below ES2020
+    pub target: EsVersion,
+    /// Omit the semicolon after the last statement of a block, e.g. `{foo();bar()}`
+    pub omit_last_semi: bool,
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self {
+            minify: false,
+            ascii_only: false,
+            target: EsVersion::EsNext,
+            omit_last_semi: false,
+        }
+    }
+}
+
+impl EmitOptions {
+    fn to_emitter_config(&self) -> swc_ecma_codegen::Config {
+        let mut emitter_cfg = swc_ecma_codegen::Config::default();
+        emitter_cfg.minify = self.minify;
+        emitter_cfg.ascii_only = self.ascii_only;
+        emitter_cfg.target = self.target;
+        emitter_cfg.omit_last_semi = self.omit_last_semi;
+        emitter_cfg
+    }
+
+    /// Transforms the syntax newer than the [`EmitOptions::target`] using the SWC compat passes.
+    /// The helpers they need, e.g. `_object_spread`, are inlined into the module
+    pub fn downlevel(&self, module: Module) -> Module {
+        let target = self.target;
+        if target >= EsVersion::Es2022 {
+            return module;
+        }
+
+        GLOBALS.set(&Globals::new(), || {
+            HELPERS.set(&Helpers::new(false), || {
+                let unresolved_mark = Mark::new();
+                let top_level_mark = Mark::new();
+
+                module
+                    .fold_with(&mut resolver(unresolved_mark, top_level_mark, false))
+                    .fold_with(&mut chain!(
+                        Optional::new(
+                            es2022::es2022(
+                                None::<NoopComments>,
+                                Default::default(),
+                                unresolved_mark
+                            ),
+                            target < EsVersion::Es2022
+                        ),
+                        Optional::new(es2021::es2021(), target < EsVersion::Es2021),
+                        Optional::new(
+                            es2020::es2020(Default::default(), unresolved_mark),
+                            target < EsVersion::Es2020
+                        ),
+                        Optional::new(es2019::es2019(), target < EsVersion::Es2019),
+                        Optional::new(
+                            es2018::es2018(Default::default()),
+                            target < EsVersion::Es2018
+                        ),
+                        Optional::new(
+                            es2017::es2017(
+                                Default::default(),
+                                None::<NoopComments>,
+                                unresolved_mark
+                            ),
+                            target < EsVersion::Es2017
+                        ),
+                        Optional::new(es2016::es2016(), target < EsVersion::Es2016),
+                        Optional::new(
+                            es2015::es2015(
+                                unresolved_mark,
+                                None::<NoopComments>,
+                                Default::default()
+                            ),
+                            target < EsVersion::Es2015
+                        ),
+                        inject_helpers(unresolved_mark),
+                        hygiene(),
+                        fixer(None)
+                    ))
+            })
+        })
+    }
+}
+
+/// Parses the ES version of the output, e.g. `"es2015"` or `"esnext"`
+pub fn parse_es_version(target: &str) -> Option<EsVersion> {
+    let es_version = match target.to_ascii_lowercase().as_str() {
+        "es3" => EsVersion::Es3,
+        "es5" => EsVersion::Es5,
+        "es2015" | "es6" => EsVersion::Es2015,
+        "es2016" => EsVersion::Es2016,
+        "es2017" => EsVersion::Es2017,
+        "es2018" => EsVersion::Es2018,
+        "es2019" => EsVersion::Es2019,
+        "es2020" => EsVersion::Es2020,
+        "es2021" => EsVersion::Es2021,
+        "es2022" => EsVersion::Es2022,
+        "esnext" => EsVersion::EsNext,
+        _ => return None,
+    };
+
+    Some(es_version)
+}
+
+/// Appends `return <expr>` to the end of the `setup` function, creating the function if needed
+pub(crate) fn return_from_setup(synthetic_setup_fn: &mut Option<Box<Function>>, expr: Expr) {
+    let setup_function = synthetic_setup_fn.get_or_insert_with(|| {
+        Box::new(Function {
+            params: vec![],
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: None,
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        })
+    });
+
+    let setup_body = setup_function.body.get_or_insert_with(|| BlockStmt {
+        span: DUMMY_SP,
+        stmts: Vec::with_capacity(1),
+    });
+
+    setup_body.stmts.push(Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(expr)),
+    }));
+}
+
+/// Adds a method to the exported object, e.g. `render() {}`
+pub(crate) fn add_method(sfc_export_obj: &mut ObjectLit, name: &str, function: Function) {
+    sfc_export_obj
+        .props
+        .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+            key: PropName::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from(name),
+                optional: false,
+            }),
+            function: Box::new(function),
+        }))));
+}
+
+/// Declares the function in the module and references it from the exported object,
+/// e.g. `function _sfc_render() {}` and `render: _sfc_render`
+fn add_fn_decl(
+    script: &mut Module,
+    sfc_export_obj: &mut ObjectLit,
+    name: &str,
+    fn_ident: &str,
+    function: Function,
+) {
+    let fn_ident = Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(fn_ident),
+        optional: false,
+    };
+
+    script
+        .body
+        .push(ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl {
+            ident: fn_ident.to_owned(),
+            declare: false,
+            function: Box::new(function),
+        }))));
+
+    sfc_export_obj
+        .props
+        .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from(name),
+                optional: false,
+            }),
+            value: Box::new(Expr::Ident(fn_ident)),
+        }))));
+}
+
+/// Finds `export default class Foo {}` and replaces it with `class Foo {}`.
+/// Anonymous classes are named `__default__`.
+///
+/// `export default Foo` is also supported, in which case the export is removed.
+/// Returns the identifier of the exported class.
+fn take_default_export_class(script: &mut Module) -> Option<Ident> {
+    let idx = script
+        .body
+        .iter()
+        .position(|module_item| match module_item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export_default_decl)) => {
+                matches!(export_default_decl.decl, DefaultDecl::Class(_))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export_default_expr)) => {
+                export_default_expr.expr.is_ident()
+            }
+            _ => false,
+        })?;
+
+    match script.body.remove(idx) {
+        // `export default class Foo {}` -> `class Foo {}`
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+            decl: DefaultDecl::Class(class_expr),
+            ..
+        })) => {
+            let ident = class_expr.ident.unwrap_or_else(|| Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("__default__"),
+                optional: false,
+            });
+
+            script.body.insert(
+                idx,
+                ModuleItem::Stmt(Stmt::Decl(Decl::Class(ClassDecl {
+                    ident: ident.to_owned(),
+                    declare: false,
+                    class: class_expr.class,
+                }))),
+            );
+
+            Some(ident)
+        }
+
+        // `export default Foo`
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export_default_expr)) => {
+            let Expr::Ident(ident) = *export_default_expr.expr else {
+                unreachable!()
+            };
+
+            Some(ident)
+        }
+
+        _ => unreachable!(),
+    }
+}
+
+/// Generates `Object.assign(Foo.__vccOpts || Foo, { /* fields */ })`.
+///
+/// `__vccOpts` is where class component libraries keep the component options,
+/// this is the same convention as in the official Vue tooling.
+fn attach_to_class_component(
+    script: &mut Module,
+    component_ident: &Ident,
+    sfc_export_obj: ObjectLit,
+) {
+    if sfc_export_obj.props.is_empty() {
+        return;
+    }
+
+    let component_ident_expr = Box::new(Expr::Ident(component_ident.to_owned()));
+
+    let options_expr = Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::LogicalOr,
+        left: Box::new(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: component_ident_expr.to_owned(),
+            prop: MemberProp::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("__vccOpts"),
+                optional: false,
+            }),
+        })),
+        right: component_ident_expr,
+    });
+
+    let object_assign = Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("Object"),
+            optional: false,
+        })),
+        prop: MemberProp::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("assign"),
+            optional: false,
+        }),
+    });
+
+    script.body.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(object_assign)),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(options_expr),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Object(sfc_export_obj)),
+                },
+            ],
+            type_args: None,
+        })),
+    })));
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        BindingTypes, BindingsHelper, FervidAtom, SetupBinding, TemplateGenerationMode, VueImports,
+    };
+
+    use crate::{
+        parse_es_version,
+        test_utils::{js, js_module, to_str},
+        CodegenContext, EmitOptions, HmrOptions, HmrTarget, ModuleFormat,
+    };
+    use swc_core::{
+        common::{BytePos, DUMMY_SP},
+        ecma::ast::{
+            EsVersion, Ident, KeyValueProp, Module, ObjectLit, Prop, PropName, PropOrSpread,
+        },
+    };
+    use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
+
+    /// Minified code with the `/*#__PURE__*/` annotations
+    fn stringify_with_comments(ctx: &CodegenContext, module: &Module) -> String {
+        let emit_options = EmitOptions {
+            minify: true,
+            ..Default::default()
+        };
+
+        CodegenContext::stringify_with_options("", module, &emit_options, Some(&ctx.comments()))
+    }
+
+    #[test]
+    fn it_returns_only_used_bindings() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("foo"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("bar"), BindingTypes::SetupConst),
+            SetupBinding(FervidAtom::from("baz"), BindingTypes::SetupMaybeRef),
+            SetupBinding(FervidAtom::from("qux"), BindingTypes::SetupLet),
+            SetupBinding(FervidAtom::from("msg"), BindingTypes::Props),
+        ];
+        bindings_helper
+            .used_bindings
+            .insert(FervidAtom::from("baz"), BindingTypes::SetupMaybeRef);
+        // Props are accessed through `$props`
+        bindings_helper
+            .used_bindings
+            .insert(FervidAtom::from("msg"), BindingTypes::Props);
+        bindings_helper
+            .used_bindings
+            .insert(FervidAtom::from("foo"), BindingTypes::SetupRef);
+        // Options API bindings are not a part of `setup`
+        bindings_helper
+            .used_bindings
+            .insert(FervidAtom::from("data"), BindingTypes::Data);
+
+        let ctx = CodegenContext::with_bindings_helper(bindings_helper);
+        assert_eq!(to_str(ctx.generate_return_bindings()), "{foo,baz}");
+    }
+
+    #[test]
+    fn it_returns_exposed_bindings() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("foo"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("bar"), BindingTypes::SetupConst),
+        ];
+        // `defineExpose({ bar, qux: foo })` also references `foo`, `qux` is not a binding
+        bindings_helper.exposed_bindings = vec![FervidAtom::from("bar"), FervidAtom::from("qux")];
+
+        let ctx = CodegenContext::with_bindings_helper(bindings_helper);
+        assert_eq!(to_str(ctx.generate_return_bindings()), "{bar}");
+    }
+
+    #[test]
+    fn it_attaches_render_to_class_component() {
+        let mut ctx = CodegenContext::default();
+        let module = ctx.generate_module(
+            Some(*js("42")),
+            js_module("export default class Foo extends Vue {}"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with(
+            "class Foo extends Vue{}function _sfc_render(_ctx,_cache,$props,$setup,$data,$options){return 42}Object.assign(Foo.__vccOpts||Foo,{render:_sfc_render})"
+        ));
+        assert!(code.contains("export default Foo"));
+        assert!(!code.contains("export default{"));
+    }
+
+    #[test]
+    fn it_generates_inline_render() {
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+
+        let module = ctx.generate_module(
+            Some(*js("42")),
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert_eq!(code, "export default{setup(){return(_ctx,_cache)=>42}};");
+    }
+
+    #[test]
+    fn it_passes_through_default_exported_ident() {
+        let mut ctx = CodegenContext::default();
+        let module = ctx.generate_module(
+            None,
+            js_module("class Foo extends Vue {}\nexport default Foo"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with("class Foo extends Vue{}export default Foo"));
+        assert!(!code.contains("Object.assign"));
+    }
+
+    #[test]
+    fn it_generates_hmr() {
+        let mut ctx = CodegenContext::default();
+        ctx.hmr = Some(HmrOptions {
+            id: FervidAtom::from("abcd1234"),
+            target: HmrTarget::Webpack,
+        });
+
+        let module = ctx.generate_module(
+            Some(*js("42")),
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with(
+            r#"function _sfc_render(_ctx,_cache,$props,$setup,$data,$options){return 42}const _sfc_main={render:_sfc_render};_sfc_main.__hmrId="abcd1234";"#
+        ));
+        assert!(code.contains("export default _sfc_main"));
+
+        // Class components are referenced directly
+        let mut ctx = CodegenContext::default();
+        ctx.hmr = Some(HmrOptions {
+            id: FervidAtom::from("abcd1234"),
+            target: HmrTarget::Webpack,
+        });
+
+        let module = ctx.generate_module(
+            None,
+            js_module("export default class Foo extends Vue {}"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with(r#"class Foo extends Vue{}Foo.__hmrId="abcd1234";"#));
+        assert!(code.contains("export default Foo"));
+    }
+
+    #[test]
+    fn it_generates_cjs_module() {
+        let mut ctx = CodegenContext::default();
+        ctx.module_format = ModuleFormat::Cjs;
+        ctx.add_to_imports(VueImports::OpenBlock);
+
+        let module = ctx.generate_module(
+            Some(*js("42")),
+            js_module("import Foo from './Foo.vue'"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert_eq!(
+            code,
+            r#"function _interopDefault(m){return m&&m.__esModule?m.default:m}const Foo=_interopDefault(require("./Foo.vue"));const{openBlock:_openBlock}=require("vue");function _sfc_render(_ctx,_cache,$props,$setup,$data,$options){return 42}module.exports={render:_sfc_render};"#
+        );
+    }
+
+    #[test]
+    fn it_imports_from_runtime_module() {
+        let mut ctx = CodegenContext::default();
+        ctx.runtime_module = Some(FervidAtom::from("@vue/runtime-dom"));
+        ctx.add_to_imports(VueImports::OpenBlock);
+
+        let module = ctx.generate_module(
+            None,
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with(
+            r#"import{openBlock as _openBlock}from"@vue/runtime-dom";export default{}"#
+        ));
+    }
+
+    #[test]
+    fn it_adds_scope_id() {
+        let mut ctx = CodegenContext::default();
+        ctx.scope_id = Some(FervidAtom::from("data-v-abcd1234"));
+
+        let module = ctx.generate_module(
+            None,
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert_eq!(code, r#"export default{__scopeId:"data-v-abcd1234"};"#);
+    }
+
+    #[test]
+    fn it_wraps_ts_component_in_define_component() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.is_ts = true;
+        let mut ctx = CodegenContext::with_bindings_helper(bindings_helper);
+
+        let module = ctx.generate_module(
+            None,
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = stringify_with_comments(&ctx, &module);
+        assert!(code
+            .starts_with(r#"import{defineComponent as _defineComponent}from"vue";export default"#));
+        assert!(code.contains("/*#__PURE__*/_defineComponent({})"));
+
+        // Class components are not wrapped
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.is_ts = true;
+        let mut ctx = CodegenContext::with_bindings_helper(bindings_helper);
+
+        let module = ctx.generate_module(
+            None,
+            js_module("export default class Foo extends Vue {}"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(!code.contains("_defineComponent"));
+    }
+
+    #[test]
+    fn it_marks_merge_models_as_pure() {
+        let mut ctx = CodegenContext::default();
+
+        let module = ctx.generate_module(
+            None,
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: FervidAtom::from("props"),
+                        optional: false,
+                    }),
+                    value: js("_mergeModels({foo:null},{modelValue:{}})"),
+                })))],
+            },
+            None,
+        );
+
+        let code = stringify_with_comments(&ctx, &module);
+        assert!(code.contains("props:/*#__PURE__*/_mergeModels({foo:null},{modelValue:{}})"));
+    }
+
+    #[test]
+    fn it_preserves_side_effect_imports() {
+        let mut ctx = CodegenContext::default();
+        ctx.add_to_imports(fervid_core::VueImports::OpenBlock);
+
+        let module = ctx.generate_module(
+            None,
+            js_module("import './polyfill'\nimport 'uno.css'\nimport Foo from './Foo.vue'\nconst bar = 42"),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.starts_with(
+            r#"import"./polyfill";import"uno.css";import Foo from"./Foo.vue";import{openBlock as _openBlock}from"vue";const bar=42"#
+        ));
+    }
+
+    #[test]
+    fn it_emits_with_options() {
+        let module = js_module("const foo = \"中\"\nfunction bar() {\n  baz()\n}");
+
+        // Pretty output is the default
+        assert_eq!(
+            CodegenContext::stringify_with_options("", &module, &EmitOptions::default(), None),
+            "const foo = \"中\";\nfunction bar() {\n    baz();\n}\n"
+        );
+
+        let code = CodegenContext::stringify_with_options(
+            "",
+            &module,
+            &EmitOptions {
+                minify: true,
+                ascii_only: true,
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(code.starts_with(r#"const foo="\u"#));
+        assert!(!code.contains('中'));
+        assert!(code.ends_with("function bar(){baz()}"));
+    }
+
+    #[test]
+    fn it_downlevels_to_target() {
+        let emit_options = EmitOptions {
+            minify: true,
+            target: EsVersion::Es2019,
+            ..Default::default()
+        };
+
+        // Optional chaining is ES2020
+        let module = emit_options.downlevel(js_module("const c = a?.b"));
+        let code = CodegenContext::stringify_with_options("", &module, &emit_options, None);
+        assert!(!code.contains("?."));
+        assert!(code.contains("void 0"));
+
+        // The newer targets keep the syntax
+        let emit_options = EmitOptions {
+            minify: true,
+            ..Default::default()
+        };
+        let module = emit_options.downlevel(js_module("const c = a?.b"));
+        let code = CodegenContext::stringify_with_options("", &module, &emit_options, None);
+        assert_eq!(code, "const c=a?.b;");
+
+        assert_eq!(parse_es_version("es2019"), Some(EsVersion::Es2019));
+        assert_eq!(parse_es_version("es6"), Some(EsVersion::Es2015));
+        assert_eq!(parse_es_version("es1"), None);
+    }
+
+    #[test]
+    fn it_generates_source_map() {
+        let source = "<script setup>\nconst foo = 42\n</script>";
+
+        // Parse the statement at its position in the source (spans are 1-based)
+        let script = "const foo = 42";
+        let lo = BytePos(source.find(script).unwrap() as u32 + 1);
+        let lexer = Lexer::new(
+            Syntax::Es(Default::default()),
+            Default::default(),
+            StringInput::new(script, lo, lo + BytePos(script.len() as u32)),
+            None,
+        );
+        let module = Parser::new_from(lexer).parse_module().unwrap();
+
+        let (code, source_map) = CodegenContext::stringify_with_source_map(
+            source,
+            "App.vue",
+            &module,
+            &Default::default(),
+            None,
+        );
+
+        assert_eq!(code, "const foo = 42;\n");
+        assert!(source_map.contains(r#""sources":["App.vue"]"#));
+        // The statement is on the 2nd line of the source, which is `AACA` in VLQ
+        assert!(source_map.contains(r#""mappings":"AACA"#));
+    }
 }
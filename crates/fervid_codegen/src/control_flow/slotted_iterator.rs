@@ -225,6 +225,7 @@ mod tests {
                     AttributeOrBinding::RegularAttribute {
                         name: "class".into(),
                         value: "regular".into(),
+                        span: Default::default(),
                     },
                     AttributeOrBinding::VBind(fervid_core::VBindDirective {
                         argument: Some("disabled".into()),
@@ -232,6 +233,7 @@ mod tests {
                         is_camel: false,
                         is_prop: false,
                         is_attr: false,
+                        span: Default::default(),
                     }),
                 ],
                 directives: None,
@@ -256,11 +258,14 @@ mod tests {
                         is_camel: false,
                         is_prop: false,
                         is_attr: false,
+                        span: Default::default(),
                     }),
                     AttributeOrBinding::VOn(VOnDirective {
                         event: Some("event".into()),
                         handler: Some(js("baz")),
                         modifiers: vec![],
+                        is_cacheable: false,
+                        span: Default::default(),
                     }),
                 ],
                 directives: None,
@@ -7,13 +7,20 @@ use swc_core::{
     },
 };
 
-use crate::{utils::str_to_propname, CodegenContext};
+use crate::{
+    utils::{str_to_propname, to_valid_asset_id},
+    CodegenContext,
+};
 
 mod v_for;
 mod v_html;
+mod v_memo;
 mod v_model;
+mod v_once;
 mod v_text;
 
+pub(crate) use v_for::generate_v_for_params;
+
 impl CodegenContext {
     pub fn generate_directives_to_array(
         &mut self,
@@ -26,7 +33,7 @@ impl CodegenContext {
                 directives.$key.is_some() as usize
             };
         }
-        let total_work = directives.custom.len() + has!(v_show) + has!(v_memo);
+        let total_work = directives.custom.len() + has!(v_show);
         if total_work == 0 {
             return;
         }
@@ -227,9 +234,10 @@ impl CodegenContext {
         }
 
         // _directive_ prefix plus directive name
-        let mut directive_ident_raw = directive_name.replace('-', "_");
-        directive_ident_raw.insert_str(0, "_directive_");
-        let directive_ident_atom = FervidAtom::from(directive_ident_raw);
+        let directive_ident_atom = to_valid_asset_id(
+            directive_name,
+            &self.bindings_helper.ident_prefixes.directive,
+        );
 
         // Directive will be resolved during runtime, this provides a variable name,
         // e.g. `const _directive_custom = resolveDirective('custom')`
@@ -252,7 +260,13 @@ impl CodegenContext {
     pub fn generate_directive_resolves(&mut self) -> Vec<VarDeclarator> {
         let mut result = Vec::new();
 
-        if self.bindings_helper.custom_directives.len() == 0 {
+        // Directives resolved at compile time, e.g. `vFocus` from `<script setup>`, do not need `resolveDirective`
+        let has_runtime_resolved = self
+            .bindings_helper
+            .custom_directives
+            .values()
+            .any(|directive| matches!(directive, CustomDirectiveBinding::RuntimeResolved(_)));
+        if !has_runtime_resolved {
             return result;
         }
 
@@ -276,7 +290,7 @@ impl CodegenContext {
 
         sorted_directives.sort_by(|a, b| a.0.cmp(b.0));
 
-        // Key is a component as used in template, value is the assigned Js identifier
+        // Key is a directive as used in template, value is the assigned Js identifier
         for (directive_name, directive_identifier) in sorted_directives.iter() {
             // _directive_ident_name = resolveDirective("directive-template-name")
             result.push(VarDeclarator {
@@ -322,3 +336,56 @@ fn void0() -> Expr {
         }))),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{js, to_str};
+
+    use super::*;
+
+    #[test]
+    fn it_resolves_each_directive_once() {
+        // <div v-focus v-my-dir="foo"><span v-focus.lazy></span></div>
+        let mut ctx = CodegenContext::default();
+        for directive_name in ["focus", "my-dir", "focus"] {
+            ctx.get_custom_directive_ident(&directive_name.into(), DUMMY_SP);
+        }
+
+        let resolves: Vec<String> = ctx
+            .generate_directive_resolves()
+            .into_iter()
+            .map(to_str)
+            .collect();
+        assert_eq!(
+            resolves,
+            vec![
+                r#"_directive_focus=_resolveDirective("focus")"#,
+                r#"_directive_my_dir=_resolveDirective("my-dir")"#,
+            ]
+        );
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::ResolveDirective));
+    }
+
+    #[test]
+    fn it_skips_compile_time_resolved_directives() {
+        // `const vFocus = {}` in `<script setup>`
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.custom_directives.insert(
+            "focus".into(),
+            CustomDirectiveBinding::Resolved(js("vFocus")),
+        );
+
+        assert_eq!(
+            to_str(ctx.get_custom_directive_ident(&"focus".into(), DUMMY_SP)),
+            "vFocus"
+        );
+        assert!(ctx.generate_directive_resolves().is_empty());
+        assert!(!ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::ResolveDirective));
+    }
+}
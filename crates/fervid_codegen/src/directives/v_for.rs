@@ -1,23 +1,54 @@
-use fervid_core::{VForDirective, VueImports};
+use fervid_core::{check_attribute_name, ElementNode, Node, PatchFlags, VForDirective, VueImports};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{ArrowExpr, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Null, Number, Pat},
+    ecma::ast::{
+        ArrayLit, ArrayPat, ArrowExpr, AssignExpr, AssignOp, AssignPat, AssignPatProp, AssignProp,
+        BinExpr, BinaryOp, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr,
+        ExprOrSpread, ExprStmt, Ident, IfStmt, KeyValuePatProp, KeyValueProp, Lit, MemberExpr,
+        MemberProp, Null, Number, ObjectLit, ObjectPat, ObjectPatProp, ParenExpr, Pat, PatOrExpr,
+        Prop, PropOrSpread, RestPat, ReturnStmt, SeqExpr, SpreadElement, Stmt, VarDecl,
+        VarDeclKind, VarDeclarator,
+    },
 };
 
 use crate::CodegenContext;
 
 impl CodegenContext {
-    /// Generates `(openBlock(true), createElementBlock(Fragment, null, renderList(<list>, (<item>) => (<expr>)), <patch flag>))`
-    pub fn generate_v_for(&mut self, v_for: &VForDirective, item_render_expr: Expr) -> Expr {
+    /// Generates `(openBlock(true), createElementBlock(Fragment, null, renderList(<list>, (<item>) => (<expr>)), <patch flag>))`.
+    /// Stable fragments, e.g. `v-for="i in 3"`, use `openBlock()` instead.
+    /// When `v_memo` is present, each item is memoized, see [`CodegenContext::generate_v_for_memo_item`].
+    /// The `_cache` slot of the list is allocated after the slots used inside `item_render_expr`.
+    pub fn generate_v_for(
+        &mut self,
+        v_for: &VForDirective,
+        item_render_expr: Expr,
+        v_memo: Option<&Expr>,
+    ) -> Expr {
         let span = DUMMY_SP; // TODO
 
+        // Memoized items need the `_cached` item and the `_cache` slot
+        let mut memo_cache_index = None;
+        let (params, body) = match v_memo {
+            Some(v_memo) => {
+                memo_cache_index = Some(self.allocate_cache_index());
+                (
+                    generate_v_for_memo_params(&v_for.itervar),
+                    BlockStmtOrExpr::BlockStmt(
+                        self.generate_v_for_memo_item(v_memo, item_render_expr),
+                    ),
+                )
+            }
+            None => (
+                generate_v_for_params(&v_for.itervar),
+                BlockStmtOrExpr::Expr(Box::new(item_render_expr)),
+            ),
+        };
+
         // Arrow function which renders each individual item
         let render_list_arrow = Expr::Arrow(ArrowExpr {
             span,
-            params: vec![Pat::Expr(v_for.itervar.to_owned())],
-            body: Box::new(swc_core::ecma::ast::BlockStmtOrExpr::Expr(Box::new(
-                item_render_expr,
-            ))),
+            params,
+            body: Box::new(body),
             is_async: false,
             is_generator: false,
             type_params: None,
@@ -38,6 +69,26 @@ impl CodegenContext {
             expr: Box::new(render_list_arrow),
         });
 
+        // 3. `_cache` and the slot index for `v-memo`
+        if let Some(memo_cache_index) = memo_cache_index {
+            render_list_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: self.bindings_helper.ident_prefixes.cache.to_owned(),
+                    optional: false,
+                })),
+            });
+            render_list_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Num(Number {
+                    span,
+                    value: memo_cache_index.into(),
+                    raw: None,
+                }))),
+            });
+        }
+
         let render_list_call_expr = Expr::Call(CallExpr {
             span,
             callee: Callee::Expr(Box::new(Expr::Ident(Ident {
@@ -73,11 +124,7 @@ impl CodegenContext {
         });
         create_element_block_args.push(ExprOrSpread {
             spread: None,
-            expr: Box::new(Expr::Lit(Lit::Num(Number {
-                span,
-                value: v_for.patch_flags.bits().into(),
-                raw: None,
-            }))),
+            expr: Box::new(self.generate_patch_flag(v_for.patch_flags, span)),
         });
 
         let create_element_block = Expr::Call(CallExpr {
@@ -91,6 +138,484 @@ impl CodegenContext {
             type_args: None,
         });
 
-        self.wrap_in_open_block(create_element_block, span)
+        // Only a stable fragment tracks its children, otherwise they are diffed as a whole
+        let is_stable = v_for.patch_flags.contains(PatchFlags::StableFragment);
+        self.wrap_in_open_block(create_element_block, !is_stable, span)
+    }
+
+    /// Generates the item of `<template v-for>` which has a single element child.
+    /// The child is rendered directly, and the `key` of the `<template>` is moved onto it:
+    /// `<template v-for="item in list" :key="item.id"><div /></template>`
+    /// renders `(_openBlock(), _createElementBlock("div", { key: item.id }))` for each item.
+    ///
+    /// Returns `None` when the `<template>` needs a Fragment, e.g. it has several children
+    pub(crate) fn generate_v_for_template_child(
+        &mut self,
+        template_node: &ElementNode,
+        wrap_in_block: bool,
+    ) -> Option<Expr> {
+        if template_node.starting_tag.tag_name != "template" {
+            return None;
+        }
+
+        // A child with `v-for` is a list on its own and needs the Fragment
+        let [Node::Element(child)] = template_node.children.as_slice() else {
+            return None;
+        };
+        let child_has_v_for = child
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_for.is_some());
+        if child_has_v_for {
+            return None;
+        }
+
+        let key_attr = template_node
+            .starting_tag
+            .attributes
+            .iter()
+            .find(|attr| check_attribute_name(attr, "key"));
+
+        let Some(key_attr) = key_attr else {
+            return Some(self.generate_element_or_component(child, wrap_in_block));
+        };
+
+        let mut keyed_child = child.to_owned();
+        keyed_child
+            .starting_tag
+            .attributes
+            .insert(0, key_attr.to_owned());
+        Some(self.generate_element_or_component(&keyed_child, wrap_in_block))
+    }
+
+    /// Generates the body of a memoized `v-for` item:
+    /// ```js
+    /// const _memo = (<v-memo>);
+    /// if (_cached && _isMemoSame(_cached, _memo)) return _cached;
+    /// const _item = <item render expr>;
+    /// _item.memo = _memo;
+    /// return _item;
+    /// ```
+    pub fn generate_v_for_memo_item(&mut self, v_memo: &Expr, item_render_expr: Expr) -> BlockStmt {
+        let span = DUMMY_SP;
+
+        macro_rules! ident {
+            ($sym: expr) => {
+                Ident {
+                    span,
+                    sym: $sym.into(),
+                    optional: false,
+                }
+            };
+        }
+
+        macro_rules! const_decl {
+            ($name: expr, $init: expr) => {
+                Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span,
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span,
+                        name: Pat::Ident(BindingIdent {
+                            id: ident!($name),
+                            type_ann: None,
+                        }),
+                        init: Some(Box::new($init)),
+                        definite: false,
+                    }],
+                })))
+            };
+        }
+
+        // `_isMemoSame(_cached, _memo)`
+        let is_memo_same = Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::IsMemoSame),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Ident(ident!("_cached"))),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Ident(ident!("_memo"))),
+                },
+            ],
+            type_args: None,
+        });
+
+        // `if (_cached && _isMemoSame(_cached, _memo)) return _cached`
+        let return_cached = Stmt::If(IfStmt {
+            span,
+            test: Box::new(Expr::Bin(BinExpr {
+                span,
+                op: BinaryOp::LogicalAnd,
+                left: Box::new(Expr::Ident(ident!("_cached"))),
+                right: Box::new(is_memo_same),
+            })),
+            cons: Box::new(Stmt::Return(ReturnStmt {
+                span,
+                arg: Some(Box::new(Expr::Ident(ident!("_cached")))),
+            })),
+            alt: None,
+        });
+
+        // `_item.memo = _memo`
+        let assign_memo = Stmt::Expr(ExprStmt {
+            span,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span,
+                op: AssignOp::Assign,
+                left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+                    span,
+                    obj: Box::new(Expr::Ident(ident!("_item"))),
+                    prop: MemberProp::Ident(ident!("memo")),
+                }))),
+                right: Box::new(Expr::Ident(ident!("_memo"))),
+            })),
+        });
+
+        BlockStmt {
+            span,
+            stmts: vec![
+                const_decl!("_memo", v_memo.to_owned()),
+                return_cached,
+                const_decl!("_item", item_render_expr),
+                assign_memo,
+                Stmt::Return(ReturnStmt {
+                    span,
+                    arg: Some(Box::new(Expr::Ident(ident!("_item")))),
+                }),
+            ],
+        }
+    }
+}
+
+/// `item` -> `(item)`, `({ id, name }, index)` -> `({ id, name }, index)`.
+/// Each alias of the `v-for` is converted to a binding pattern
+pub(crate) fn generate_v_for_params(itervar: &Expr) -> Vec<Pat> {
+    match itervar {
+        Expr::Paren(ParenExpr { expr, .. }) => match **expr {
+            Expr::Seq(SeqExpr { ref exprs, .. }) => {
+                exprs.iter().map(|expr| expr_to_pat(expr)).collect()
+            }
+            _ => vec![expr_to_pat(expr)],
+        },
+        _ => vec![expr_to_pat(itervar)],
+    }
+}
+
+/// `item` -> `(item, __, ___, _cached)`, `(item, index)` -> `(item, index, ___, _cached)`.
+/// `renderList` passes the cached item as the 4th argument
+fn generate_v_for_memo_params(itervar: &Expr) -> Vec<Pat> {
+    let mut params = generate_v_for_params(itervar);
+
+    for placeholder in ["_", "__", "___"].iter().skip(params.len()) {
+        params.push(Pat::Ident(BindingIdent {
+            id: Ident {
+                span: DUMMY_SP,
+                sym: (*placeholder).into(),
+                optional: false,
+            },
+            type_ann: None,
+        }));
+    }
+    params.truncate(3);
+
+    params.push(Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym: "_cached".into(),
+            optional: false,
+        },
+        type_ann: None,
+    }));
+
+    params
+}
+
+/// Converts a `v-for` alias parsed as an expression to the equivalent pattern, e.g.
+/// `{ id, name: alias = 'x', ...rest }` or `[first, [second]]`.
+/// Expressions which cannot be a pattern are kept as-is
+fn expr_to_pat(expr: &Expr) -> Pat {
+    match expr {
+        Expr::Ident(ident) => Pat::Ident(BindingIdent {
+            id: ident.to_owned(),
+            type_ann: None,
+        }),
+
+        Expr::Paren(ParenExpr { expr, .. }) => expr_to_pat(expr),
+
+        // `[a, , b = 1, ...rest]`
+        Expr::Array(ArrayLit { span, elems }) => Pat::Array(ArrayPat {
+            span: *span,
+            elems: elems
+                .iter()
+                .map(|elem| {
+                    elem.as_ref().map(|elem| {
+                        let pat = expr_to_pat(&elem.expr);
+                        match elem.spread {
+                            Some(dot3_token) => Pat::Rest(RestPat {
+                                span: dot3_token,
+                                dot3_token,
+                                arg: Box::new(pat),
+                                type_ann: None,
+                            }),
+                            None => pat,
+                        }
+                    })
+                })
+                .collect(),
+            optional: false,
+            type_ann: None,
+        }),
+
+        // `{ a, b: c, d = 1, ...rest }`
+        Expr::Object(ObjectLit { span, props }) => Pat::Object(ObjectPat {
+            span: *span,
+            props: props
+                .iter()
+                .filter_map(|prop| match prop {
+                    PropOrSpread::Spread(SpreadElement { dot3_token, expr }) => {
+                        Some(ObjectPatProp::Rest(RestPat {
+                            span: *dot3_token,
+                            dot3_token: *dot3_token,
+                            arg: Box::new(expr_to_pat(expr)),
+                            type_ann: None,
+                        }))
+                    }
+                    PropOrSpread::Prop(prop) => match **prop {
+                        Prop::Shorthand(ref ident) => Some(ObjectPatProp::Assign(AssignPatProp {
+                            span: ident.span,
+                            key: ident.to_owned(),
+                            value: None,
+                        })),
+                        Prop::Assign(AssignProp { ref key, ref value }) => {
+                            Some(ObjectPatProp::Assign(AssignPatProp {
+                                span: key.span,
+                                key: key.to_owned(),
+                                value: Some(value.to_owned()),
+                            }))
+                        }
+                        Prop::KeyValue(KeyValueProp { ref key, ref value }) => {
+                            Some(ObjectPatProp::KeyValue(KeyValuePatProp {
+                                key: key.to_owned(),
+                                value: Box::new(expr_to_pat(value)),
+                            }))
+                        }
+                        _ => None,
+                    },
+                })
+                .collect(),
+            optional: false,
+            type_ann: None,
+        }),
+
+        // `a = 1`
+        Expr::Assign(AssignExpr {
+            span,
+            op: AssignOp::Assign,
+            left,
+            right,
+        }) => Pat::Assign(AssignPat {
+            span: *span,
+            left: match left {
+                PatOrExpr::Pat(pat) => pat.to_owned(),
+                PatOrExpr::Expr(expr) => Box::new(expr_to_pat(expr)),
+            },
+            right: right.to_owned(),
+        }),
+
+        _ => Pat::Expr(Box::new(expr.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        AttributeOrBinding, ElementKind, PatchFlagsSet, StartingTag, VBindDirective, VueDirectives,
+    };
+
+    use super::*;
+    use crate::test_utils::js;
+
+    fn v_for_node(itervar: &str, iterable: &str, patch_flags: PatchFlagsSet) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_for: Some(VForDirective {
+                        iterable: js(iterable),
+                        itervar: js(itervar),
+                        patch_flags,
+                    }),
+                    ..Default::default()
+                })),
+            },
+            children: vec![],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    #[test]
+    fn it_generates_untracked_v_for() {
+        // <div v-for="item in list"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node("item", "list", PatchFlags::UnkeyedFragment.into()),
+            false,
+        );
+        let out = crate::test_utils::to_str(out);
+        assert!(out
+            .starts_with("(_openBlock(true),_createElementBlock(_Fragment,null,_renderList(list,"));
+        assert!(out.ends_with(r#"=>(_openBlock(),_createElementBlock("div"))),256))"#));
+    }
+
+    #[test]
+    fn it_generates_stable_v_for() {
+        // <div v-for="item in 3"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node("item", "3", PatchFlags::StableFragment.into()),
+            false,
+        );
+        let out = crate::test_utils::to_str(out);
+        assert!(out.starts_with("(_openBlock(),_createElementBlock(_Fragment,null,_renderList(3,"));
+        assert!(out.ends_with(r#"=>_createElementVNode("div")),64))"#));
+    }
+
+    #[test]
+    fn it_generates_v_for_params_by_arity() {
+        // <div v-for="(value, key, index) in object"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node(
+                "(value, key, index)",
+                "object",
+                PatchFlags::UnkeyedFragment.into(),
+            ),
+            false,
+        );
+        assert!(crate::test_utils::to_str(out).contains("_renderList(object,(value,key,index)=>"));
+
+        // <div v-for="(, key) in object"></div>, the skipped alias is filled by the parser
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node("(_, key)", "object", PatchFlags::UnkeyedFragment.into()),
+            false,
+        );
+        assert!(crate::test_utils::to_str(out).contains("_renderList(object,(_,key)=>"));
+
+        // <div v-for="x of iterable"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node("(x)", "iterable", PatchFlags::UnkeyedFragment.into()),
+            false,
+        );
+        assert!(crate::test_utils::to_str(out).contains("_renderList(iterable,x=>"));
+    }
+
+    #[test]
+    fn it_generates_destructured_v_for() {
+        // <div v-for="({ id, name: alias = 1, ...rest }, [first, ...others]) in list"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &v_for_node(
+                "({ id, name: alias = 1, ...rest }, [first, ...others])",
+                "list",
+                PatchFlags::UnkeyedFragment.into(),
+            ),
+            false,
+        );
+        assert!(crate::test_utils::to_str(out)
+            .contains("_renderList(list,({id,name:alias=1,...rest},[first,...others])=>"));
+
+        // <div v-for="{ a: [b, { c }] } in list" v-memo="[b]"></div>
+        let mut ctx = CodegenContext::default();
+        let mut node = v_for_node(
+            "{ a: [b, { c }] }",
+            "list",
+            PatchFlags::UnkeyedFragment.into(),
+        );
+        if let Some(ref mut directives) = node.starting_tag.directives {
+            directives.v_memo = Some(js("[b]"));
+        }
+        let out = ctx.generate_element_or_component(&node, false);
+        assert!(crate::test_utils::to_str(out)
+            .contains("_renderList(list,({a:[b,{c}]},__,___,_cached)=>{"));
+    }
+
+    #[test]
+    fn it_generates_keyed_template_v_for() {
+        // <template v-for="item in list" :key="item.id">...</template>
+        let template_node = |children: Vec<Node>| ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "template".into(),
+                attributes: vec![AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some("key".into()),
+                    value: js("item.id"),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: DUMMY_SP,
+                })],
+                directives: Some(Box::new(VueDirectives {
+                    v_for: Some(VForDirective {
+                        iterable: js("list"),
+                        itervar: js("item"),
+                        patch_flags: PatchFlags::KeyedFragment.into(),
+                    }),
+                    ..Default::default()
+                })),
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+        let child = |tag_name: &str| {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+
+        // The key is moved onto the only child
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(&template_node(vec![child("div")]), false);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(true),_createElementBlock(_Fragment,null,_renderList(list,item=>(_openBlock(),_createElementBlock("div",{key:item.id}))),128))"#
+        );
+
+        // Several children are a keyed stable Fragment
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &template_node(vec![child("div"), child("span")]),
+            false,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(true),_createElementBlock(_Fragment,null,_renderList(list,item=>(_openBlock(),_createElementBlock(_Fragment,{key:item.id},[_createElementVNode("div"),_createElementVNode("span")],64))),128))"#
+        );
     }
 }
@@ -0,0 +1,157 @@
+use fervid_core::VueImports;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Number,
+    },
+};
+
+use crate::CodegenContext;
+
+impl CodegenContext {
+    /// Generates the `v-memo` directive for a node without `v-for`.
+    /// For `v-memo` inside `v-for` see [`CodegenContext::generate_v_for`].
+    ///
+    /// # Example
+    /// `<div v-memo="[foo]"></div>` will generate
+    /// `_withMemo([_ctx.foo], () => (_openBlock(), _createElementBlock("div")), _cache, 0)`
+    pub fn generate_v_memo(&mut self, v_memo: &Expr, vnode_expr: Expr) -> Expr {
+        let span = DUMMY_SP;
+        let cache_index = self.allocate_cache_index();
+
+        // `() => vnode`
+        let render_fn = Expr::Arrow(ArrowExpr {
+            span,
+            params: vec![],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(vnode_expr))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::WithMemo),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(v_memo.to_owned()),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(render_fn),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Ident(Ident {
+                        span,
+                        sym: self.bindings_helper.ident_prefixes.cache.to_owned(),
+                        optional: false,
+                    })),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Num(Number {
+                        span,
+                        value: cache_index.into(),
+                        raw: None,
+                    }))),
+                },
+            ],
+            type_args: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, ElementNode, Node, StartingTag, VForDirective, VueDirectives};
+
+    use super::*;
+    use crate::test_utils::js;
+
+    fn memo_node(v_for: Option<VForDirective>) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_for,
+                    v_memo: Some(js("[foo]")),
+                    ..Default::default()
+                })),
+            },
+            children: vec![],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    #[test]
+    fn it_generates_v_memo() {
+        // <div v-memo="[foo]"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(&memo_node(None), false);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_withMemo([foo],()=>(_openBlock(),_createElementBlock("div")),_cache,0)"#
+        );
+    }
+
+    #[test]
+    fn it_generates_v_memo_in_v_for() {
+        // <div v-for="item in list" v-memo="[foo]"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &memo_node(Some(VForDirective {
+                iterable: js("list"),
+                itervar: js("item"),
+                patch_flags: Default::default(),
+            })),
+            false,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(true),_createElementBlock(_Fragment,null,_renderList(list,(item,__,___,_cached)=>{const _memo=[foo];if(_cached&&_isMemoSame(_cached,_memo))return _cached;const _item=(_openBlock(),_createElementBlock("div"));_item.memo=_memo;return _item},_cache,0),0))"#
+        );
+
+        // <div v-for="(item, index) in list" v-memo="[foo]"></div>
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(
+            &memo_node(Some(VForDirective {
+                iterable: js("list"),
+                itervar: js("(item, index)"),
+                patch_flags: Default::default(),
+            })),
+            false,
+        );
+        assert!(
+            crate::test_utils::to_str(out).contains("_renderList(list,(item,index,___,_cached)=>{")
+        );
+    }
+
+    #[test]
+    fn it_offsets_cache_index_of_v_memo_in_v_for() {
+        // <div v-for="item in list" v-memo="[foo]"><div v-memo="[foo]"></div></div>
+        let mut parent = memo_node(Some(VForDirective {
+            iterable: js("list"),
+            itervar: js("item"),
+            patch_flags: Default::default(),
+        }));
+        parent.children.push(Node::Element(memo_node(None)));
+
+        // Inner slots are allocated first, so the list takes the next one
+        let mut ctx = CodegenContext::default();
+        let out = crate::test_utils::to_str(ctx.generate_element_or_component(&parent, false));
+        assert!(out
+            .contains("_withMemo([foo],()=>(_openBlock(),_createElementBlock(\"div\")),_cache,0)"));
+        assert!(out.ends_with("},_cache,1),0))"));
+    }
+}
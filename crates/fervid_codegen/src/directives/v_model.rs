@@ -76,7 +76,10 @@ impl CodegenContext {
         // TODO Cache like so `_cache[1] || (_cache[1] = `
         out.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
             key: event_listener_propname,
-            value: self.generate_v_model_update_fn(&v_model.value, scope_to_use, span),
+            value: match v_model.update_handler {
+                Some(ref update_handler) => update_handler.to_owned(),
+                None => self.generate_v_model_update_fn(&v_model.value, scope_to_use, span),
+            },
         }))));
 
         // 5. Optionally generate modifiers
@@ -135,6 +138,26 @@ impl CodegenContext {
         has_js_bindings
     }
 
+    /// Generates the `"onUpdate:modelValue"` listener for the element `v-model`.
+    /// The value itself is bound by the runtime directive, e.g. `vModelText`.
+    pub fn generate_v_model_for_element(
+        &self,
+        v_model: &VModelDirective,
+        out: &mut Vec<PropOrSpread>,
+        scope_to_use: u32,
+    ) {
+        let span = v_model.span;
+
+        // `"onUpdate:modelValue": $event => ((_ctx.smth) = $event)`
+        out.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_to_propname("onUpdate:modelValue", span),
+            value: match v_model.update_handler {
+                Some(ref update_handler) => update_handler.to_owned(),
+                None => self.generate_v_model_update_fn(&v_model.value, scope_to_use, span),
+            },
+        }))));
+    }
+
     /// Transforms the binding of `v-model`.
     /// Because the rules of transformation differ a lot depending on the `BindingType`,
     /// transformed expression may also differ a lot.
@@ -232,6 +255,7 @@ mod tests {
                 argument: None,
                 value: *js("foo"),
                 modifiers: Vec::new(),
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event)}"#,
@@ -246,6 +270,7 @@ mod tests {
                 argument: Some("simple".into()),
                 value: *js("foo"),
                 modifiers: Vec::new(),
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{simple:foo,"onUpdate:simple":$event=>((foo)=$event)}"#,
@@ -257,6 +282,7 @@ mod tests {
                 argument: Some("modelValue".into()),
                 value: *js("bar"),
                 modifiers: Vec::new(),
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:bar,"onUpdate:modelValue":$event=>((bar)=$event)}"#,
@@ -268,6 +294,7 @@ mod tests {
                 argument: Some("model-value".into()),
                 value: *js("baz"),
                 modifiers: Vec::new(),
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{"model-value":baz,"onUpdate:modelValue":$event=>((baz)=$event)}"#,
@@ -282,6 +309,7 @@ mod tests {
                 argument: None,
                 value: *js("foo"),
                 modifiers: vec!["lazy".into(), "trim".into()],
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event),modelModifiers:{lazy:true,trim:true}}"#,
@@ -293,6 +321,7 @@ mod tests {
                 argument: None,
                 value: *js("foo"),
                 modifiers: vec!["custom-modifier".into()],
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event),modelModifiers:{"custom-modifier":true}}"#,
@@ -304,6 +333,7 @@ mod tests {
                 argument: Some("foo-bar".into()),
                 value: *js("bazQux"),
                 modifiers: vec!["custom-modifier".into()],
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{"foo-bar":bazQux,"onUpdate:fooBar":$event=>((bazQux)=$event),"foo-barModifiers":{"custom-modifier":true}}"#,
@@ -318,6 +348,7 @@ mod tests {
                 argument: Some(StrOrExpr::Expr(js("foo"))),
                 value: *js("bar"),
                 modifiers: Vec::new(),
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{[foo]:bar,["onUpdate:"+foo]:$event=>((bar)=$event)}"#,
@@ -329,6 +360,7 @@ mod tests {
                 argument: Some(StrOrExpr::Expr(js("foo"))),
                 value: *js("bar"),
                 modifiers: vec!["baz".into()],
+                update_handler: None,
                 span: DUMMY_SP,
             }],
             r#"{[foo]:bar,["onUpdate:"+foo]:$event=>((bar)=$event),[foo+"Modifiers"]:{baz:true}}"#,
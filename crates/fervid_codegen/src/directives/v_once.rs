@@ -0,0 +1,130 @@
+use fervid_core::VueImports;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        AssignExpr, AssignOp, BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit,
+        Number, ParenExpr, PatOrExpr, SeqExpr, UnaryExpr, UnaryOp,
+    },
+};
+
+use crate::{utils::cache_access, CodegenContext};
+
+impl CodegenContext {
+    /// Generates the `v-once` directive by caching the vnode.
+    ///
+    /// # Example
+    /// `<div v-once>{{ msg }}</div>` will generate
+    /// ```js
+    /// _cache[0] || (
+    ///   _setBlockTracking(-1),
+    ///   _cache[0] = _createElementVNode("div", null, _toDisplayString(_ctx.msg), 1),
+    ///   _setBlockTracking(1),
+    ///   _cache[0]
+    /// )
+    /// ```
+    pub fn generate_v_once(&mut self, vnode_expr: Expr) -> Expr {
+        let span = DUMMY_SP;
+        let cache_index = self.allocate_cache_index();
+
+        let cache_member = cache_access(
+            &self.bindings_helper.ident_prefixes.cache,
+            cache_index,
+            span,
+        );
+
+        let set_block_tracking_ident = self.get_and_add_import_ident(VueImports::SetBlockTracking);
+
+        // `_setBlockTracking(-1)` or `_setBlockTracking(1)`
+        let set_block_tracking = |arg: Expr| {
+            Box::new(Expr::Call(CallExpr {
+                span,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: set_block_tracking_ident.to_owned(),
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(arg),
+                }],
+                type_args: None,
+            }))
+        };
+        let one = || {
+            Expr::Lit(Lit::Num(Number {
+                span,
+                value: 1.0,
+                raw: None,
+            }))
+        };
+        let minus_one = Expr::Unary(UnaryExpr {
+            span,
+            op: UnaryOp::Minus,
+            arg: Box::new(one()),
+        });
+
+        // `_cache[0] = vnode`
+        let assign_cache = Box::new(Expr::Assign(AssignExpr {
+            span,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Expr(Box::new(cache_member.to_owned())),
+            right: Box::new(vnode_expr),
+        }));
+
+        Expr::Bin(BinExpr {
+            span,
+            op: BinaryOp::LogicalOr,
+            left: Box::new(cache_member.to_owned()),
+            right: Box::new(Expr::Paren(ParenExpr {
+                span,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span,
+                    exprs: vec![
+                        set_block_tracking(minus_one),
+                        assign_cache,
+                        set_block_tracking(one()),
+                        Box::new(cache_member),
+                    ],
+                })),
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, ElementNode, Node, StartingTag, VueDirectives};
+
+    use super::*;
+
+    #[test]
+    fn it_generates_v_once() {
+        // <div v-once>hello</div>
+        let element_node = ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_once: Some(()),
+                    ..Default::default()
+                })),
+            },
+            children: vec![Node::Text("hello".into(), DUMMY_SP)],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(&element_node, true);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_cache[0]||(_setBlockTracking(-1),_cache[0]=_createElementVNode("div",null,"hello"),_setBlockTracking(1),_cache[0])"#
+        );
+
+        // Next `v-once` uses the next cache slot
+        let out = ctx.generate_element_or_component(&element_node, false);
+        assert!(crate::test_utils::to_str(out).starts_with("_cache[1]||"));
+    }
+}
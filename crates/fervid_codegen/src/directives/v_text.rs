@@ -1,7 +1,9 @@
-use fervid_core::fervid_atom;
+use fervid_core::{fervid_atom, VueImports};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Expr, Ident, KeyValueProp, Prop, PropName, PropOrSpread},
+    ecma::ast::{
+        CallExpr, Callee, Expr, ExprOrSpread, Ident, KeyValueProp, Prop, PropName, PropOrSpread,
+    },
 };
 
 use crate::CodegenContext;
@@ -10,15 +12,35 @@ impl CodegenContext {
     /// Generates the `v-text` directive
     ///
     /// # Example
-    /// `v-text="foo + bar"` will generate `textContent: foo + bar` (without transforms)
-    pub fn generate_v_text(&self, expr: &Expr) -> PropOrSpread {
+    /// `v-text="foo + bar"` will generate `textContent: _toDisplayString(foo + bar)`
+    /// (without transforms).
+    /// Literals are used as-is, e.g. `v-text="'hi'"` is `textContent: "hi"`
+    pub fn generate_v_text(&mut self, expr: &Expr) -> PropOrSpread {
+        let value = if let Expr::Lit(_) = expr {
+            expr.to_owned()
+        } else {
+            Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: self.get_and_add_import_ident(VueImports::ToDisplayString),
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr.to_owned()),
+                }],
+                type_args: None,
+            })
+        };
+
         PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
             key: PropName::Ident(Ident {
                 span: DUMMY_SP, // TODO
                 sym: fervid_atom!("textContent"),
                 optional: false,
             }),
-            value: Box::new(expr.to_owned()),
+            value: Box::new(value),
         })))
     }
 }
@@ -64,7 +86,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,{textContent:foo+bar})"#,
+            r#"_createVNode(_component_test_component,{textContent:_toDisplayString(foo+bar)})"#,
             false,
         )
     }
@@ -88,7 +110,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createElementVNode("h1",{textContent:foo+bar})"#,
+            r#"_createElementVNode("h1",{textContent:_toDisplayString(foo+bar)})"#,
             false,
         )
     }
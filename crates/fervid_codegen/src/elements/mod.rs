@@ -1,18 +1,22 @@
 use fervid_core::{
-    AttributeOrBinding, ElementNode, StartingTag, StrOrExpr, VBindDirective, VueImports,
+    AttributeOrBinding, ElementNode, PatchFlags, PatchFlagsSet, StartingTag, StrOrExpr,
+    VBindDirective, VueImports,
 };
 use swc_core::{
-    common::DUMMY_SP,
+    common::{Span, DUMMY_SP},
     ecma::{
         ast::{
-            ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Null, Number, ObjectLit,
-            PropOrSpread, Str,
+            ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Null, Number, PropOrSpread,
+            Str,
         },
-        atoms::JsWord,
+        atoms::{Atom, JsWord},
     },
 };
 
-use crate::{context::CodegenContext, control_flow::SlottedIterator};
+use crate::{
+    attributes::coerce_dom_attributes, context::CodegenContext, control_flow::SlottedIterator,
+    hoisting::has_hoistable_props,
+};
 
 impl CodegenContext {
     pub fn generate_element_vnode(
@@ -26,10 +30,15 @@ impl CodegenContext {
         // Generate attributes
         let attributes = self.generate_element_attributes(element_node);
         let attributes_expr = if attributes.len() != 0 {
-            Some(Expr::Object(ObjectLit {
-                span,
-                props: attributes,
-            }))
+            let attributes_obj = self.generate_props_expr(attributes, false, span);
+            let attributes_obj = self.normalize_props_expr(attributes_obj);
+
+            // Constant props are created once, even if the element itself is dynamic
+            if self.hoist_static && has_hoistable_props(element_node) {
+                Some(self.hoist(attributes_obj))
+            } else {
+                Some(attributes_obj)
+            }
         } else {
             None
         };
@@ -44,6 +53,12 @@ impl CodegenContext {
         let (mut children, was_inlined) =
             self.generate_element_children(element_node, !should_generate_fragment_instead);
 
+        // `<template>` children never change their order, e.g. `<template v-for>` items
+        let mut patch_flags = element_node.patch_hints.flags;
+        if should_generate_fragment_instead {
+            patch_flags |= PatchFlags::StableFragment;
+        }
+
         // Wire the things together. `createElementVNode` args:
         // 1st - element name or Fragment;
         // 2nd (optional) - element attributes & directives object;
@@ -52,7 +67,7 @@ impl CodegenContext {
         // 5th (optional) - props array (for PROPS patch flag).
         let expected_element_args_count = if !element_node.patch_hints.props.is_empty() {
             5
-        } else if !element_node.patch_hints.flags.is_empty() {
+        } else if !patch_flags.is_empty() {
             4
         } else if children.len() != 0 {
             3
@@ -142,40 +157,15 @@ impl CodegenContext {
 
         // Arg 4 (optional): patch flags (default to nothing)
         if expected_element_args_count >= 4 {
-            let patch_flag_value = element_node.patch_hints.flags.bits();
-
             create_element_args.push(ExprOrSpread {
                 spread: None,
-                expr: Box::new(Expr::Lit(Lit::Num(Number {
-                    span,
-                    value: patch_flag_value.into(),
-                    raw: None,
-                }))),
+                expr: Box::new(self.generate_patch_flag(patch_flags, span)),
             });
 
             if !element_node.patch_hints.props.is_empty() {
-                let props_array = element_node
-                    .patch_hints
-                    .props
-                    .iter()
-                    .map(|prop| {
-                        Some(ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(Expr::Lit(Lit::Str(Str {
-                                span: DUMMY_SP,
-                                value: prop.to_owned(),
-                                raw: None,
-                            }))),
-                        })
-                    })
-                    .collect();
-
                 create_element_args.push(ExprOrSpread {
                     spread: None,
-                    expr: Box::new(Expr::Array(ArrayLit {
-                        span: DUMMY_SP,
-                        elems: props_array,
-                    })),
+                    expr: Box::new(generate_dynamic_props(&element_node.patch_hints.props)),
                 });
             }
         }
@@ -202,7 +192,7 @@ impl CodegenContext {
         // When wrapping in block, we also need `openBlock()`
         let mut create_element_expr = if wrap_in_block {
             // (openBlock(), createElementBlock("element-name", {element:attrs}, [element, children], PATCH_FLAGS))
-            self.wrap_in_open_block(create_element_fn_call, span)
+            self.wrap_in_open_block(create_element_fn_call, false, span)
         } else {
             // Just `createElementVNode` call
             create_element_fn_call
@@ -214,6 +204,36 @@ impl CodegenContext {
         create_element_expr
     }
 
+    /// Generates the patch flag argument of a vnode.
+    /// In DEV the names of the flags are added as a comment, e.g. `9 /* TEXT, PROPS */`
+    pub(crate) fn generate_patch_flag(&self, flags: PatchFlagsSet, span: Span) -> Expr {
+        let value = flags.bits();
+
+        // The comment is a part of the raw literal, because the generated nodes have no spans
+        // to attach the comments to. Minified output ignores it
+        let raw = if self.bindings_helper.is_prod {
+            None
+        } else {
+            // Special flags are negative and never combined
+            let names = match value {
+                -1 => String::from("HOISTED"),
+                -2 => String::from("BAIL"),
+                _ => flags
+                    .into_iter()
+                    .map(patch_flag_name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            Some(Atom::from(format!("{value} /* {names} */")))
+        };
+
+        Expr::Lit(Lit::Num(Number {
+            span,
+            value: value.into(),
+            raw,
+        }))
+    }
+
     fn generate_element_attributes<'e>(
         &mut self,
         element_node: &'e ElementNode,
@@ -221,6 +241,7 @@ impl CodegenContext {
         let mut result_props = Vec::new();
 
         self.generate_attributes(&element_node.starting_tag.attributes, &mut result_props);
+        coerce_dom_attributes(&mut result_props);
 
         // Directives
         if let Some(ref directives) = element_node.starting_tag.directives {
@@ -231,6 +252,14 @@ impl CodegenContext {
             if let Some(ref v_html) = directives.v_html {
                 result_props.push(self.generate_v_html(&v_html));
             }
+
+            for v_model in directives.v_model.iter() {
+                self.generate_v_model_for_element(
+                    v_model,
+                    &mut result_props,
+                    element_node.template_scope,
+                );
+            }
         }
 
         result_props
@@ -264,6 +293,7 @@ impl CodegenContext {
             } else {
                 // Ignore named slots in the elements.
                 // These should be reported in the analyzer.
+                slotted_iterator.next();
                 was_inlined = false;
             }
 
@@ -328,7 +358,7 @@ impl CodegenContext {
                 for attr in starting_tag.attributes.iter() {
                     match attr {
                         // type="smth"
-                        AttributeOrBinding::RegularAttribute { name, value } if name == "type" => {
+                        AttributeOrBinding::RegularAttribute { name, value, .. } if name == "type" => {
                             match value.as_ref() {
                                 "checkbox" => {
                                     return self
@@ -349,6 +379,12 @@ impl CodegenContext {
                             return self.get_and_add_import_ident(VueImports::VModelDynamic)
                         }
 
+                        // v-bind="obj" and :[attr]="smth" may also contain `type`
+                        AttributeOrBinding::VBind(VBindDirective {
+                            argument: None | Some(StrOrExpr::Expr(_)),
+                            ..
+                        }) => return self.get_and_add_import_ident(VueImports::VModelDynamic),
+
                         _ => continue,
                     }
                 }
@@ -364,11 +400,52 @@ impl CodegenContext {
     }
 }
 
+/// Generates the dynamic props argument of a vnode, e.g. `["foo", "onUpdate:modelValue"]`.
+/// The runtime only diffs these props when the `PROPS` patch flag is set
+pub(crate) fn generate_dynamic_props(props: &[JsWord]) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: props
+            .iter()
+            .map(|prop| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: prop.to_owned(),
+                        raw: None,
+                    }))),
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Names of the patch flags as in the official compiler
+fn patch_flag_name(flag: PatchFlags) -> &'static str {
+    match flag {
+        PatchFlags::Text => "TEXT",
+        PatchFlags::Class => "CLASS",
+        PatchFlags::Style => "STYLE",
+        PatchFlags::Props => "PROPS",
+        PatchFlags::FullProps => "FULL_PROPS",
+        PatchFlags::HydrateEvents => "HYDRATE_EVENTS",
+        PatchFlags::StableFragment => "STABLE_FRAGMENT",
+        PatchFlags::KeyedFragment => "KEYED_FRAGMENT",
+        PatchFlags::UnkeyedFragment => "UNKEYED_FRAGMENT",
+        PatchFlags::NeedPatch => "NEED_PATCH",
+        PatchFlags::DynamicSlots => "DYNAMIC_SLOTS",
+        PatchFlags::DevRootFragment => "DEV_ROOT_FRAGMENT",
+        PatchFlags::Hoisted => "HOISTED",
+        PatchFlags::Bail => "BAIL",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fervid_core::{
-        AttributeOrBinding, ElementKind, Interpolation, Node, StartingTag, VBindDirective,
-        VOnDirective,
+        AttributeOrBinding, ElementKind, Interpolation, Node, PatchFlags, PatchHints, StartingTag,
+        StrOrExpr, VBindDirective, VCustomDirective, VModelDirective, VOnDirective, VueDirectives,
     };
 
     use super::*;
@@ -390,6 +467,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(VBindDirective {
                             argument: Some("baz".into()),
@@ -397,6 +475,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                         AttributeOrBinding::VBind(VBindDirective {
                             argument: Some("readonly".into()),
@@ -404,11 +483,14 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                         AttributeOrBinding::VOn(VOnDirective {
                             event: Some("click".into()),
                             handler: Some(js("handleClick")),
                             modifiers: vec![],
+                            is_cacheable: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -419,7 +501,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createElementVNode("div",{foo:"bar",baz:qux,readonly:true,onClick:handleClick},"hello from div")"#,
+            r#"_createElementVNode("div",{foo:"bar",baz:qux,readonly:"",onClick:handleClick},"hello from div")"#,
             false,
         )
     }
@@ -456,6 +538,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(VBindDirective {
                             argument: Some("some-baz".into()),
@@ -463,6 +546,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -486,6 +570,7 @@ mod tests {
                         AttributeOrBinding::RegularAttribute {
                             name: "foo".into(),
                             value: "bar".into(),
+                            span: Default::default(),
                         },
                         AttributeOrBinding::VBind(VBindDirective {
                             argument: Some("some-baz".into()),
@@ -493,6 +578,7 @@ mod tests {
                             is_camel: false,
                             is_prop: false,
                             is_attr: false,
+                            span: Default::default(),
                         }),
                     ],
                     directives: None,
@@ -577,6 +663,229 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_generates_v_model() {
+        macro_rules! v_model_input {
+            ($attributes: expr, $modifiers: expr) => {
+                ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "input".into(),
+                        attributes: $attributes,
+                        directives: Some(Box::new(VueDirectives {
+                            v_model: vec![VModelDirective {
+                                argument: None,
+                                value: *js("foo"),
+                                modifiers: $modifiers,
+                                update_handler: None,
+                                span: DUMMY_SP,
+                            }],
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: PatchHints {
+                        flags: PatchFlags::NeedPatch | PatchFlags::Props,
+                        props: vec!["onUpdate:modelValue".into()],
+                        ..Default::default()
+                    },
+                    span: DUMMY_SP,
+                }
+            };
+        }
+
+        // <input v-model.trim="foo">
+        test_out(
+            v_model_input!(vec![], vec!["trim".into()]),
+            r#"_withDirectives(_createElementVNode("input",{"onUpdate:modelValue":$event=>((foo)=$event)},null,520,["onUpdate:modelValue"]),[[_vModelText,foo,void 0,{trim:true}]])"#,
+            false,
+        );
+
+        // <input type="checkbox" v-model="foo">
+        test_out(
+            v_model_input!(
+                vec![AttributeOrBinding::RegularAttribute {
+                    name: "type".into(),
+                    value: "checkbox".into(),
+                    span: DUMMY_SP,
+                }],
+                vec![]
+            ),
+            r#"_withDirectives(_createElementVNode("input",{type:"checkbox","onUpdate:modelValue":$event=>((foo)=$event)},null,520,["onUpdate:modelValue"]),[[_vModelCheckbox,foo]])"#,
+            false,
+        );
+
+        // <input :type="inputType" v-model="foo">
+        test_out(
+            v_model_input!(
+                vec![AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some("type".into()),
+                    value: js("inputType"),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: DUMMY_SP,
+                })],
+                vec![]
+            ),
+            r#"_withDirectives(_createElementVNode("input",{type:inputType,"onUpdate:modelValue":$event=>((foo)=$event)},null,520,["onUpdate:modelValue"]),[[_vModelDynamic,foo]])"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_merges_text_nodes() {
+        // <div>{{ foo }} {{ bar }}<span></span>baz</div>
+        let interpolation = |raw: &str| {
+            Node::Interpolation(Interpolation {
+                value: js(raw),
+                template_scope: 0,
+                patch_flag: true,
+            })
+        };
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![
+                    interpolation("foo"),
+                    Node::Text(" ".into(), DUMMY_SP),
+                    interpolation("bar"),
+                    Node::Element(ElementNode {
+                        starting_tag: StartingTag {
+                            tag_name: "span".into(),
+                            attributes: vec![],
+                            directives: None,
+                        },
+                        children: vec![],
+                        template_scope: 0,
+                        kind: ElementKind::Element,
+                        patch_hints: Default::default(),
+                        span: DUMMY_SP,
+                    }),
+                    Node::Text("baz".into(), DUMMY_SP),
+                ],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createElementVNode("div",null,[_createTextVNode(_toDisplayString(foo)+" "+_toDisplayString(bar),1),_createElementVNode("span"),_createTextVNode("baz")])"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_custom_directives() {
+        // <div v-my-dir:arg.mod.other="value" v-focus:[name]></div>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        custom: vec![
+                            VCustomDirective {
+                                name: "my-dir".into(),
+                                argument: Some(StrOrExpr::Str("arg".into())),
+                                modifiers: vec!["mod".into(), "other".into()],
+                                value: Some(js("value")),
+                            },
+                            VCustomDirective {
+                                name: "focus".into(),
+                                argument: Some(StrOrExpr::Expr(js("name"))),
+                                modifiers: vec![],
+                                value: None,
+                            },
+                        ],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: PatchHints {
+                    flags: PatchFlags::NeedPatch.into(),
+                    ..Default::default()
+                },
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives(_createElementVNode("div",null,null,512),[[_directive_my_dir,value,"arg",{mod:true,other:true}],[_directive_focus,void 0,name]])"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_combines_directives_into_one_array() {
+        // <input v-model="foo" v-show="bar" v-focus>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "input".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_model: vec![VModelDirective {
+                            argument: None,
+                            value: *js("foo"),
+                            modifiers: vec![],
+                            update_handler: None,
+                            span: DUMMY_SP,
+                        }],
+                        v_show: Some(js("bar")),
+                        custom: vec![VCustomDirective {
+                            name: "focus".into(),
+                            argument: None,
+                            modifiers: vec![],
+                            value: None,
+                        }],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: PatchHints {
+                    flags: PatchFlags::NeedPatch | PatchFlags::Props,
+                    props: vec!["onUpdate:modelValue".into()],
+                    ..Default::default()
+                },
+                span: DUMMY_SP,
+            },
+            r#"_withDirectives(_createElementVNode("input",{"onUpdate:modelValue":$event=>((foo)=$event)},null,520,["onUpdate:modelValue"]),[[_vModelText,foo],[_vShow,bar],[_directive_focus]])"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_patch_flag_names_in_dev() {
+        let patch_flag_raw = |is_prod: bool, flags: PatchFlagsSet| {
+            let mut ctx = CodegenContext::default();
+            ctx.bindings_helper.is_prod = is_prod;
+            match ctx.generate_patch_flag(flags, DUMMY_SP) {
+                Expr::Lit(Lit::Num(Number { raw, .. })) => raw.map(|raw| raw.to_string()),
+                _ => panic!("Expected a number literal"),
+            }
+        };
+
+        assert_eq!(
+            patch_flag_raw(false, PatchFlags::Text.into()),
+            Some(String::from("1 /* TEXT */"))
+        );
+        assert_eq!(
+            patch_flag_raw(false, PatchFlags::Class | PatchFlags::Props),
+            Some(String::from("10 /* CLASS, PROPS */"))
+        );
+        assert_eq!(
+            patch_flag_raw(false, PatchFlags::Hoisted.into()),
+            Some(String::from("-1 /* HOISTED */"))
+        );
+        assert_eq!(patch_flag_raw(true, PatchFlags::Text.into()), None);
+    }
+
     fn test_out(input: ElementNode, expected: &str, wrap_in_block: bool) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_element_vnode(&input, wrap_in_block);
@@ -0,0 +1,314 @@
+//! Generates the hot module replacement (HMR) code of a component in DEV.
+//!
+//! The component is registered in `__VUE_HMR_RUNTIME__` under a stable `__hmrId`,
+//! and the bundler is told to accept the updates of the module:
+//!
+//! ```js
+//! _sfc_main.__hmrId = "abcd1234";
+//! typeof __VUE_HMR_RUNTIME__ !== "undefined" && __VUE_HMR_RUNTIME__.createRecord(_sfc_main.__hmrId, _sfc_main);
+//! if (import.meta.hot) {
+//!   import.meta.hot.accept((mod) => {
+//!     if (!mod) return;
+//!     __VUE_HMR_RUNTIME__.reload(mod.default.__hmrId, mod.default);
+//!   });
+//! }
+//! ```
+
+use fervid_core::FervidAtom;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, BindingIdent, BlockStmt,
+        BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread, ExprStmt, Ident, IfStmt, Lit,
+        MemberExpr, MemberProp, MetaPropExpr, MetaPropKind, Pat, PatOrExpr, ReturnStmt, Stmt, Str,
+        UnaryExpr, UnaryOp,
+    },
+};
+
+use crate::context::CodegenContext;
+
+/// Which bundler API is used to accept the hot updates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmrTarget {
+    /// `import.meta.hot.accept()`
+    Vite,
+    /// `module.hot.accept()`
+    Webpack,
+}
+
+/// Options of the HMR code, see [`CodegenContext::generate_hmr`]
+#[derive(Debug, Clone)]
+pub struct HmrOptions {
+    /// Stable ID of the component, which does not change between the updates of the file
+    pub id: FervidAtom,
+    pub target: HmrTarget,
+}
+
+impl HmrOptions {
+    /// Derives the `__hmrId` from the path of the SFC, same as the official tooling.
+    /// The content is hashed instead when the path is unknown
+    pub fn new(filename: Option<&str>, source: &str, target: HmrTarget) -> HmrOptions {
+        let hash = fxhash::hash32(filename.unwrap_or(source));
+
+        HmrOptions {
+            id: FervidAtom::from(format!("{hash:08x}")),
+            target,
+        }
+    }
+}
+
+impl CodegenContext {
+    /// Generates the statements which register the component `component_ident`
+    /// and accept the hot updates, see [`HmrTarget`]
+    pub fn generate_hmr(&self, hmr_options: &HmrOptions, component_ident: &Ident) -> Vec<Stmt> {
+        let component = || Expr::Ident(component_ident.to_owned());
+        let hmr_id = || member(component(), "__hmrId");
+
+        let mut stmts = Vec::with_capacity(3);
+
+        // `_sfc_main.__hmrId = "abcd1234"`
+        stmts.push(expr_stmt(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Expr(Box::new(hmr_id())),
+            right: Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: hmr_options.id.to_owned(),
+                raw: None,
+            }))),
+        })));
+
+        let create_record = call(
+            member(ident("__VUE_HMR_RUNTIME__"), "createRecord"),
+            vec![hmr_id(), component()],
+        );
+
+        match hmr_options.target {
+            HmrTarget::Vite => {
+                // `typeof __VUE_HMR_RUNTIME__ !== "undefined" && __VUE_HMR_RUNTIME__.createRecord(/*...*/)`
+                let has_runtime = Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::NotEqEq,
+                    left: Box::new(Expr::Unary(UnaryExpr {
+                        span: DUMMY_SP,
+                        op: UnaryOp::TypeOf,
+                        arg: Box::new(ident("__VUE_HMR_RUNTIME__")),
+                    })),
+                    right: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: FervidAtom::from("undefined"),
+                        raw: None,
+                    }))),
+                });
+                stmts.push(expr_stmt(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::LogicalAnd,
+                    left: Box::new(has_runtime),
+                    right: Box::new(create_record),
+                })));
+
+                // `if (!mod) return; __VUE_HMR_RUNTIME__.reload(mod.default.__hmrId, mod.default)`
+                let updated = || member(ident("mod"), "default");
+                let accept_body = vec![
+                    Stmt::If(IfStmt {
+                        span: DUMMY_SP,
+                        test: Box::new(not(ident("mod"))),
+                        cons: Box::new(Stmt::Return(ReturnStmt {
+                            span: DUMMY_SP,
+                            arg: None,
+                        })),
+                        alt: None,
+                    }),
+                    expr_stmt(call(
+                        member(ident("__VUE_HMR_RUNTIME__"), "reload"),
+                        vec![member(updated(), "__hmrId"), updated()],
+                    )),
+                ];
+
+                let accept_arrow = Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    params: vec![Pat::Ident(BindingIdent {
+                        id: Ident {
+                            span: DUMMY_SP,
+                            sym: FervidAtom::from("mod"),
+                            optional: false,
+                        },
+                        type_ann: None,
+                    })],
+                    body: Box::new(BlockStmtOrExpr::BlockStmt(block(accept_body))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                });
+
+                // `if (import.meta.hot) { import.meta.hot.accept((mod) => { /*...*/ }) }`
+                let import_meta_hot = || {
+                    member(
+                        Expr::MetaProp(MetaPropExpr {
+                            span: DUMMY_SP,
+                            kind: MetaPropKind::ImportMeta,
+                        }),
+                        "hot",
+                    )
+                };
+                stmts.push(Stmt::If(IfStmt {
+                    span: DUMMY_SP,
+                    test: Box::new(import_meta_hot()),
+                    cons: Box::new(Stmt::Block(block(vec![expr_stmt(call(
+                        member(import_meta_hot(), "accept"),
+                        vec![accept_arrow],
+                    ))]))),
+                    alt: None,
+                }));
+            }
+
+            HmrTarget::Webpack => {
+                // `if (!__VUE_HMR_RUNTIME__.createRecord(/*...*/)) __VUE_HMR_RUNTIME__.reload(/*...*/)`
+                let reload_if_exists = Stmt::If(IfStmt {
+                    span: DUMMY_SP,
+                    test: Box::new(not(create_record)),
+                    cons: Box::new(Stmt::Block(block(vec![expr_stmt(call(
+                        member(ident("__VUE_HMR_RUNTIME__"), "reload"),
+                        vec![hmr_id(), component()],
+                    ))]))),
+                    alt: None,
+                });
+
+                // `if (module.hot) { module.hot.accept(); /*...*/ }`
+                let module_hot = || member(ident("module"), "hot");
+                stmts.push(Stmt::If(IfStmt {
+                    span: DUMMY_SP,
+                    test: Box::new(module_hot()),
+                    cons: Box::new(Stmt::Block(block(vec![
+                        expr_stmt(call(member(module_hot(), "accept"), vec![])),
+                        reload_if_exists,
+                    ]))),
+                    alt: None,
+                }));
+            }
+        }
+
+        stmts
+    }
+}
+
+fn ident(sym: &str) -> Expr {
+    Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(sym),
+        optional: false,
+    })
+}
+
+fn member(obj: Expr, prop: &str) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(obj),
+        prop: MemberProp::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from(prop),
+            optional: false,
+        }),
+    })
+}
+
+fn call(callee: Expr, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(callee)),
+        args: args
+            .into_iter()
+            .map(|expr| ExprOrSpread {
+                spread: None,
+                expr: Box::new(expr),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+fn not(expr: Expr) -> Expr {
+    Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: UnaryOp::Bang,
+        arg: Box::new(expr),
+    })
+}
+
+fn expr_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
+
+fn block(stmts: Vec<Stmt>) -> BlockStmt {
+    BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::to_str;
+
+    fn test_out(target: HmrTarget) -> String {
+        let ctx = CodegenContext::default();
+        let hmr_options = HmrOptions {
+            id: FervidAtom::from("abcd1234"),
+            target,
+        };
+        let component_ident = Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("_sfc_main"),
+            optional: false,
+        };
+
+        ctx.generate_hmr(&hmr_options, &component_ident)
+            .into_iter()
+            .map(to_str)
+            .collect()
+    }
+
+    #[test]
+    fn it_generates_vite_hmr() {
+        assert_eq!(
+            test_out(HmrTarget::Vite),
+            r#"_sfc_main.__hmrId="abcd1234";typeof __VUE_HMR_RUNTIME__!=="undefined"&&__VUE_HMR_RUNTIME__.createRecord(_sfc_main.__hmrId,_sfc_main);if(import.meta.hot){import.meta.hot.accept(mod=>{if(!mod)return;__VUE_HMR_RUNTIME__.reload(mod.default.__hmrId,mod.default)})}"#
+        );
+    }
+
+    #[test]
+    fn it_generates_webpack_hmr() {
+        assert_eq!(
+            test_out(HmrTarget::Webpack),
+            r#"_sfc_main.__hmrId="abcd1234";if(module.hot){module.hot.accept();if(!__VUE_HMR_RUNTIME__.createRecord(_sfc_main.__hmrId,_sfc_main)){__VUE_HMR_RUNTIME__.reload(_sfc_main.__hmrId,_sfc_main)}}"#
+        );
+    }
+
+    #[test]
+    fn it_derives_stable_hmr_id() {
+        let from_path = HmrOptions::new(
+            Some("src/App.vue"),
+            "<template></template>",
+            HmrTarget::Vite,
+        );
+        let same_path = HmrOptions::new(
+            Some("src/App.vue"),
+            "<template>changed</template>",
+            HmrTarget::Vite,
+        );
+        assert_eq!(from_path.id, same_path.id);
+        assert_eq!(from_path.id.len(), 8);
+
+        let other_path = HmrOptions::new(
+            Some("src/Other.vue"),
+            "<template></template>",
+            HmrTarget::Vite,
+        );
+        assert_ne!(from_path.id, other_path.id);
+    }
+}
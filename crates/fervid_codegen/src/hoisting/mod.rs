@@ -0,0 +1,557 @@
+use fervid_core::{
+    AttributeOrBinding, ElementKind, ElementNode, FervidAtom, Node, PatchFlags, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, AssignExpr, AssignOp, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Decl,
+        Expr, ExprOrSpread, Ident, Lit, ModuleItem, Null, ParenExpr, Pat, PatOrExpr, SeqExpr, Stmt,
+        Str, VarDecl, VarDeclKind, VarDeclarator,
+    },
+};
+
+use crate::context::CodegenContext;
+
+mod stringify;
+
+impl CodegenContext {
+    /// Moves the expression to a top-level `const _hoisted_N = <expr>`
+    /// and returns the `_hoisted_N` identifier to be used instead
+    pub fn hoist(&mut self, expr: Expr) -> Expr {
+        let index = self.allocate_hoist(expr);
+        self.hoisted_ident(index)
+    }
+
+    /// Moves the expression to the hoists and returns its index,
+    /// so that the same hoisted value can be referenced from several places
+    /// using [`CodegenContext::hoisted_ident`]
+    pub fn allocate_hoist(&mut self, expr: Expr) -> usize {
+        self.hoists.push(expr);
+        self.hoists.len() - 1
+    }
+
+    /// `_hoisted_N` identifier of the hoist at `index`, see [`CodegenContext::allocate_hoist`]
+    pub fn hoisted_ident(&self, index: usize) -> Expr {
+        Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: hoisted_name(&self.bindings_helper.ident_prefixes.hoisted, index),
+            optional: false,
+        })
+    }
+
+    /// Generates and hoists a fully static element subtree, see [`is_static_element`].
+    ///
+    /// ## Example
+    /// `<span class="foo">Hello</span>` is hoisted as
+    /// `const _hoisted_1 = /*#__PURE__*/_createElementVNode("span", { class: "foo" }, "Hello", -1 /* HOISTED */)`
+    pub fn generate_hoisted_element(&mut self, element_node: &ElementNode) -> Expr {
+        // Nested static elements are a part of this hoisted subtree
+        let old_hoist_static = std::mem::replace(&mut self.hoist_static, false);
+        let mut vnode = self.generate_element_vnode(element_node, false);
+        self.hoist_static = old_hoist_static;
+
+        if let Expr::Call(ref mut create_element_vnode) = vnode {
+            // Static element does not have a patch flag, thus there may only be 3 arguments
+            while create_element_vnode.args.len() < 3 {
+                create_element_vnode.args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+                });
+            }
+
+            // `-1 /* HOISTED */`
+            let hoisted_flag = self.generate_patch_flag(PatchFlags::Hoisted.into(), DUMMY_SP);
+            create_element_vnode.args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(hoisted_flag),
+            });
+
+            self.mark_pure(create_element_vnode);
+        }
+
+        self.hoist(vnode)
+    }
+
+    /// Generates the top-level `const _hoisted_N = <expr>` declarations
+    /// for everything hoisted during the template generation.
+    ///
+    /// The hoisted vnodes are created outside of the render function,
+    /// so for the `scoped` styles their creation is wrapped to get the scope ID:
+    /// `const _hoisted_1 = /*#__PURE__*/_withScopeId(() => /*#__PURE__*/_createElementVNode(/*...*/))`
+    pub fn generate_hoists(&mut self) -> Vec<ModuleItem> {
+        let hoists = std::mem::take(&mut self.hoists);
+
+        let has_hoisted_vnodes = hoists
+            .iter()
+            .any(|hoisted| matches!(hoisted, Expr::Call(_)));
+        let scope_id = self.scope_id.to_owned().filter(|_| has_hoisted_vnodes);
+
+        let mut result = Vec::with_capacity(hoists.len() + 1);
+        if let Some(ref scope_id) = scope_id {
+            let with_scope_id = self.generate_with_scope_id(scope_id);
+            result.push(const_decl(FervidAtom::from("_withScopeId"), with_scope_id));
+        }
+
+        for (idx, hoisted) in hoists.into_iter().enumerate() {
+            let init = match hoisted {
+                Expr::Call(_) if scope_id.is_some() => self.wrap_with_scope_id(hoisted),
+                _ => hoisted,
+            };
+
+            let name = hoisted_name(&self.bindings_helper.ident_prefixes.hoisted, idx);
+            result.push(const_decl(name, init));
+        }
+
+        result
+    }
+
+    /// `/*#__PURE__*/_withScopeId(() => hoisted)`
+    fn wrap_with_scope_id(&mut self, hoisted: Expr) -> Expr {
+        let mut with_scope_id = CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("_withScopeId"),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    params: vec![],
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(hoisted))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }],
+            type_args: None,
+        };
+        self.mark_pure(&mut with_scope_id);
+
+        Expr::Call(with_scope_id)
+    }
+
+    /// Generates `n => (_pushScopeId("data-v-abcd1234"), n = n(), _popScopeId(), n)`
+    fn generate_with_scope_id(&mut self, scope_id: &FervidAtom) -> Expr {
+        let n = || {
+            Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("n"),
+                optional: false,
+            })
+        };
+        let call = |callee: Expr, args: Vec<Expr>| {
+            Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(callee)),
+                args: args
+                    .into_iter()
+                    .map(|arg| ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(arg),
+                    })
+                    .collect(),
+                type_args: None,
+            })
+        };
+        let import = |sym: FervidAtom| {
+            Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym,
+                optional: false,
+            })
+        };
+
+        let push_scope_id = call(
+            import(self.get_and_add_import_ident(VueImports::PushScopeId)),
+            vec![Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: scope_id.to_owned(),
+                raw: None,
+            }))],
+        );
+        let assign_n = Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Pat(Box::new(Pat::Ident(BindingIdent {
+                id: Ident {
+                    span: DUMMY_SP,
+                    sym: FervidAtom::from("n"),
+                    optional: false,
+                },
+                type_ann: None,
+            }))),
+            right: Box::new(call(n(), vec![])),
+        });
+        let pop_scope_id = call(
+            import(self.get_and_add_import_ident(VueImports::PopScopeId)),
+            vec![],
+        );
+
+        Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![Pat::Ident(BindingIdent {
+                id: Ident {
+                    span: DUMMY_SP,
+                    sym: FervidAtom::from("n"),
+                    optional: false,
+                },
+                type_ann: None,
+            })],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs: vec![
+                        Box::new(push_scope_id),
+                        Box::new(assign_n),
+                        Box::new(pop_scope_id),
+                        Box::new(n()),
+                    ],
+                })),
+            })))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        })
+    }
+}
+
+/// `const name = init`
+fn const_decl(name: FervidAtom, init: Expr) -> ModuleItem {
+    ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: Ident {
+                    span: DUMMY_SP,
+                    sym: name,
+                    optional: false,
+                },
+                type_ann: None,
+            }),
+            init: Some(Box::new(init)),
+            definite: false,
+        }],
+    }))))
+}
+
+/// Hoists are numbered from 1, same as in the official compiler
+fn hoisted_name(prefix: &str, index: usize) -> FervidAtom {
+    FervidAtom::from(format!("{}{}", prefix, index + 1))
+}
+
+/// Whether the element and its whole subtree never change between renders,
+/// i.e. it is a plain HTML element with only static attributes, no directives and static children
+pub fn is_static_element(element_node: &ElementNode) -> bool {
+    matches!(element_node.kind, ElementKind::Element)
+        && element_node.starting_tag.tag_name != "template"
+        && element_node.patch_hints.flags.is_empty()
+        && element_node.patch_hints.props.is_empty()
+        && has_static_props(element_node)
+        && element_node.children.iter().all(|child| match child {
+            Node::Text(_, _) | Node::Comment(_, _) => true,
+            Node::Element(child_element) => is_static_element(child_element),
+            Node::Interpolation(_) | Node::ConditionalSeq(_) => false,
+        })
+}
+
+/// Whether the generated props object of an element is constant and can be hoisted
+/// even when the element itself cannot, e.g. `<div class="foo">{{ bar }}</div>`.
+pub fn has_hoistable_props(element_node: &ElementNode) -> bool {
+    matches!(element_node.kind, ElementKind::Element)
+        && !element_node.starting_tag.attributes.is_empty()
+        && element_node.patch_hints.props.is_empty()
+        && (element_node.patch_hints.flags - (PatchFlags::Text | PatchFlags::NeedPatch)).is_empty()
+        && has_static_props(element_node)
+}
+
+/// Only the regular attributes are allowed, and the directives must not produce props.
+/// Template `ref`s need to be patched and are never static
+fn has_static_props(element_node: &ElementNode) -> bool {
+    let starting_tag = &element_node.starting_tag;
+
+    let are_attributes_static = starting_tag.attributes.iter().all(
+        |attr| matches!(attr, AttributeOrBinding::RegularAttribute { name, .. } if name != "ref"),
+    );
+
+    let are_directives_static = starting_tag.directives.as_ref().map_or(true, |directives| {
+        directives.custom.is_empty()
+            && directives.v_else.is_none()
+            && directives.v_else_if.is_none()
+            && directives.v_for.is_none()
+            && directives.v_html.is_none()
+            && directives.v_if.is_none()
+            && directives.v_memo.is_none()
+            && directives.v_model.is_empty()
+            && directives.v_once.is_none()
+            && directives.v_show.is_none()
+            && directives.v_slot.is_none()
+            && directives.v_text.is_none()
+    });
+
+    are_attributes_static && are_directives_static
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        AttributeOrBinding, ElementNode, Interpolation, Node, StartingTag, VueDirectives,
+    };
+    use swc_core::common::{comments::Comments, DUMMY_SP};
+
+    use super::*;
+    use crate::test_utils::{js, to_str_with_comments};
+
+    fn element(
+        tag_name: &str,
+        attributes: Vec<AttributeOrBinding>,
+        children: Vec<Node>,
+    ) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn class_attr(value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: "class".into(),
+            value: value.into(),
+            span: Default::default(),
+        }
+    }
+
+    fn interpolation(raw: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: js(raw),
+            template_scope: 0,
+            patch_flag: true,
+        })
+    }
+
+    fn hoists_to_str(ctx: &mut CodegenContext) -> Vec<String> {
+        let hoists = ctx.generate_hoists();
+        let comments = ctx.comments();
+
+        hoists
+            .into_iter()
+            .map(|hoist| to_str_with_comments(hoist, &comments))
+            .collect()
+    }
+
+    #[test]
+    fn it_detects_static_elements() {
+        // <div class="foo"><span>Hello</span><!-- comment --></div>
+        assert!(is_static_element(&element(
+            "div",
+            vec![class_attr("foo")],
+            vec![
+                Node::Element(element(
+                    "span",
+                    vec![],
+                    vec![Node::Text("Hello".into(), DUMMY_SP)]
+                )),
+                Node::Comment("comment".into(), DUMMY_SP),
+            ],
+        )));
+
+        // <div><span>{{ foo }}</span></div>
+        assert!(!is_static_element(&element(
+            "div",
+            vec![],
+            vec![Node::Element(element(
+                "span",
+                vec![],
+                vec![interpolation("foo")]
+            ))],
+        )));
+
+        // <div ref="foo"></div>
+        assert!(!is_static_element(&element(
+            "div",
+            vec![AttributeOrBinding::RegularAttribute {
+                name: "ref".into(),
+                value: "foo".into(),
+                span: Default::default(),
+            }],
+            vec![],
+        )));
+
+        // <div v-show="foo"></div>
+        let mut v_show_element = element("div", vec![], vec![]);
+        v_show_element.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_show: Some(js("foo")),
+            ..Default::default()
+        }));
+        assert!(!is_static_element(&v_show_element));
+
+        // <div class="foo">{{ bar }}</div>
+        let mut text_element = element("div", vec![class_attr("foo")], vec![interpolation("bar")]);
+        text_element.patch_hints.flags |= PatchFlags::Text;
+        assert!(!is_static_element(&text_element));
+        assert!(has_hoistable_props(&text_element));
+    }
+
+    #[test]
+    fn it_hoists_static_elements() {
+        // <div><span class="foo">Hello</span>{{ bar }}</div>
+        let mut ctx = CodegenContext::default();
+        ctx.hoist_static = true;
+        let root = element(
+            "div",
+            vec![],
+            vec![
+                Node::Element(element(
+                    "span",
+                    vec![class_attr("foo")],
+                    vec![Node::Element(element(
+                        "b",
+                        vec![],
+                        vec![Node::Text("Hello".into(), DUMMY_SP)],
+                    ))],
+                )),
+                interpolation("bar"),
+            ],
+        );
+
+        let out = ctx.generate_element_or_component(&root, true);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(),_createElementBlock("div",null,[_hoisted_1,_createTextVNode(_toDisplayString(bar),1)]))"#
+        );
+        assert_eq!(
+            hoists_to_str(&mut ctx),
+            vec![
+                r#"const _hoisted_1=/*#__PURE__*/_createElementVNode("span",{class:"foo"},[_createElementVNode("b",null,"Hello")],-1);"#
+            ]
+        );
+
+        // Disabled hoisting
+        let mut ctx = CodegenContext::default();
+        let out = ctx.generate_element_or_component(&root, true);
+        assert!(
+            crate::test_utils::to_str(out).contains(r#"_createElementVNode("span",{class:"foo"}"#)
+        );
+        assert!(ctx.generate_hoists().is_empty());
+    }
+
+    #[test]
+    fn it_hoists_constant_props() {
+        // <div><span class="foo">{{ bar }}</span></div>
+        let mut ctx = CodegenContext::default();
+        ctx.hoist_static = true;
+        let mut span = element("span", vec![class_attr("foo")], vec![interpolation("bar")]);
+        span.patch_hints.flags |= PatchFlags::Text;
+        let root = element("div", vec![], vec![Node::Element(span)]);
+
+        let out = ctx.generate_element_or_component(&root, true);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"(_openBlock(),_createElementBlock("div",null,[_createElementVNode("span",_hoisted_1,_toDisplayString(bar),1)]))"#
+        );
+        assert_eq!(
+            hoists_to_str(&mut ctx),
+            vec![r#"const _hoisted_1={class:"foo"};"#]
+        );
+    }
+
+    #[test]
+    fn it_references_hoists_by_index() {
+        let mut ctx = CodegenContext::default();
+        let first = ctx.allocate_hoist(*js("{ foo: 1 }"));
+        let second = ctx.allocate_hoist(*js("[bar]"));
+
+        assert_eq!(
+            crate::test_utils::to_str(ctx.hoisted_ident(second)),
+            "_hoisted_2"
+        );
+        assert_eq!(
+            crate::test_utils::to_str(ctx.hoisted_ident(first)),
+            "_hoisted_1"
+        );
+        assert_eq!(
+            hoists_to_str(&mut ctx),
+            vec!["const _hoisted_1={foo:1};", "const _hoisted_2=[bar];"]
+        );
+    }
+
+    #[test]
+    fn it_uses_custom_hoisted_prefix() {
+        let mut ctx = CodegenContext::default();
+        ctx.bindings_helper.ident_prefixes.hoisted = FervidAtom::from("$h");
+        let index = ctx.allocate_hoist(*js("{ foo: 1 }"));
+
+        assert_eq!(crate::test_utils::to_str(ctx.hoisted_ident(index)), "$h1");
+        assert_eq!(hoists_to_str(&mut ctx), vec!["const $h1={foo:1};"]);
+    }
+
+    #[test]
+    fn it_hoists_with_scope_id() {
+        // <div><span class="foo">Hello</span><span class="bar">{{ baz }}</span></div>
+        let mut ctx = CodegenContext::default();
+        ctx.hoist_static = true;
+        ctx.scope_id = Some(FervidAtom::from("data-v-abcd1234"));
+        let mut dynamic_span = element("span", vec![class_attr("bar")], vec![interpolation("baz")]);
+        dynamic_span.patch_hints.flags |= PatchFlags::Text;
+        let root = element(
+            "div",
+            vec![],
+            vec![
+                Node::Element(element(
+                    "span",
+                    vec![class_attr("foo")],
+                    vec![Node::Text("Hello".into(), DUMMY_SP)],
+                )),
+                Node::Element(dynamic_span),
+            ],
+        );
+
+        ctx.generate_element_or_component(&root, true);
+
+        // The annotation is a comment, the callee is left intact for the other SWC passes
+        let hoists = ctx.generate_hoists();
+        let ModuleItem::Stmt(Stmt::Decl(Decl::Var(ref hoisted_decl))) = hoists[1] else {
+            panic!("Expected a hoisted declaration");
+        };
+        let Some(Expr::Call(ref with_scope_id)) = hoisted_decl.decls[0].init.as_deref() else {
+            panic!("Expected a _withScopeId call");
+        };
+        let Callee::Expr(ref callee) = with_scope_id.callee else {
+            panic!("Expected an identifier callee");
+        };
+        assert!(matches!(**callee, Expr::Ident(ref ident) if ident.sym == "_withScopeId"));
+        assert!(ctx.comments().has_flag(with_scope_id.span.lo, "PURE"));
+
+        let mut ctx = CodegenContext::default();
+        ctx.hoist_static = true;
+        ctx.scope_id = Some(FervidAtom::from("data-v-abcd1234"));
+        ctx.generate_element_or_component(&root, true);
+        assert_eq!(
+            hoists_to_str(&mut ctx),
+            vec![
+                r#"const _withScopeId=n=>(_pushScopeId("data-v-abcd1234"),n=n(),_popScopeId(),n);"#,
+                r#"const _hoisted_1=/*#__PURE__*/_withScopeId(()=>/*#__PURE__*/_createElementVNode("span",{class:"foo"},"Hello",-1));"#,
+                r#"const _hoisted_2={class:"bar"};"#,
+            ]
+        );
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::PushScopeId | VueImports::PopScopeId));
+    }
+}
@@ -0,0 +1,561 @@
+use fervid_core::{AttributeOrBinding, ElementNode, FervidAtom, Node, VueImports};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Number, Str},
+};
+
+use crate::{context::CodegenContext, ssr::escape_html, text::shorten_whitespace};
+
+/// A chunk is stringified when it has at least this many nodes...
+const NODE_COUNT_THRESHOLD: usize = 20;
+/// ...or at least this many elements with attributes
+const ELEMENT_WITH_PROPS_THRESHOLD: usize = 5;
+
+impl CodegenContext {
+    /// Generates consecutive static sibling elements.
+    ///
+    /// The stringifiable elements (see [`analyze_stringifiable`]) are grouped into chunks.
+    /// When a chunk is big enough, it is stringified into a single hoisted
+    /// `_createStaticVNode("<div>...</div><div>...</div>", 2)` call,
+    /// which is much cheaper for the runtime than creating each vnode.
+    /// Otherwise, each element is hoisted on its own.
+    pub fn generate_static_elements(
+        &mut self,
+        static_elements: &[&ElementNode],
+        out: &mut Vec<Expr>,
+    ) {
+        let mut chunk_start = 0;
+        let mut chunk_counts = StaticCounts::default();
+
+        for (idx, element_node) in static_elements.iter().enumerate() {
+            match analyze_stringifiable(element_node) {
+                Some(counts) => {
+                    chunk_counts.nodes += counts.nodes;
+                    chunk_counts.elements_with_props += counts.elements_with_props;
+                }
+
+                // Elements which are not stringifiable break the chunk
+                None => {
+                    self.generate_static_chunk(
+                        &static_elements[chunk_start..idx],
+                        &chunk_counts,
+                        out,
+                    );
+                    out.push(self.generate_hoisted_element(element_node));
+
+                    chunk_start = idx + 1;
+                    chunk_counts = StaticCounts::default();
+                }
+            }
+        }
+
+        self.generate_static_chunk(&static_elements[chunk_start..], &chunk_counts, out);
+    }
+
+    fn generate_static_chunk(
+        &mut self,
+        chunk: &[&ElementNode],
+        chunk_counts: &StaticCounts,
+        out: &mut Vec<Expr>,
+    ) {
+        if chunk_counts.nodes < NODE_COUNT_THRESHOLD
+            && chunk_counts.elements_with_props < ELEMENT_WITH_PROPS_THRESHOLD
+        {
+            for element_node in chunk {
+                out.push(self.generate_hoisted_element(element_node));
+            }
+            return;
+        }
+
+        let mut html = String::new();
+        for element_node in chunk {
+            stringify_element(element_node, &mut html);
+        }
+
+        // `_createStaticVNode("<html>", <number of nodes>)`
+        let mut create_static_vnode = CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: self.get_and_add_import_ident(VueImports::CreateStaticVNode),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: FervidAtom::from(html),
+                        raw: None,
+                    }))),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Num(Number {
+                        span: DUMMY_SP,
+                        value: chunk.len() as f64,
+                        raw: None,
+                    }))),
+                },
+            ],
+            type_args: None,
+        };
+        self.mark_pure(&mut create_static_vnode);
+
+        out.push(self.hoist(Expr::Call(create_static_vnode)));
+    }
+}
+
+#[derive(Debug, Default)]
+struct StaticCounts {
+    nodes: usize,
+    elements_with_props: usize,
+}
+
+/// Counts the nodes and the elements with attributes in a static subtree.
+/// Returns `None` when the subtree cannot be rendered from an HTML string,
+/// e.g. table parts outside of a `<table>` or attributes which are not set as HTML attributes.
+fn analyze_stringifiable(element_node: &ElementNode) -> Option<StaticCounts> {
+    let mut counts = StaticCounts {
+        nodes: 1,
+        elements_with_props: 0,
+    };
+
+    fn walk(element_node: &ElementNode, counts: &mut StaticCounts) -> bool {
+        if is_non_stringifiable_tag(&element_node.starting_tag.tag_name) {
+            return false;
+        }
+
+        let starting_tag = &element_node.starting_tag;
+        if !starting_tag.attributes.is_empty() {
+            counts.elements_with_props += 1;
+        }
+
+        let has_only_stringifiable_attrs = starting_tag.attributes.iter().all(|attr| match attr {
+            AttributeOrBinding::RegularAttribute { name, .. } => is_stringifiable_attr(name),
+            _ => false,
+        });
+        if !has_only_stringifiable_attrs {
+            return false;
+        }
+
+        for child in element_node.children.iter() {
+            counts.nodes += 1;
+            if let Node::Element(child_element) = child {
+                if !walk(child_element, counts) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    if walk(element_node, &mut counts) {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+/// Stringifies a static element to HTML, e.g. `<div class="foo">bar</div>`
+fn stringify_element(element_node: &ElementNode, out: &mut String) {
+    let tag_name = &element_node.starting_tag.tag_name;
+
+    out.push('<');
+    out.push_str(tag_name);
+    for attr in element_node.starting_tag.attributes.iter() {
+        let AttributeOrBinding::RegularAttribute { name, value, .. } = attr else {
+            continue;
+        };
+
+        out.push(' ');
+        out.push_str(name);
+        if !value.is_empty() {
+            out.push_str("=\"");
+            out.push_str(&escape_html(value));
+            out.push('"');
+        }
+    }
+    out.push('>');
+
+    if is_void_tag(tag_name) {
+        return;
+    }
+
+    for child in element_node.children.iter() {
+        match child {
+            Node::Element(child_element) => stringify_element(child_element, out),
+            Node::Text(contents, _) => out.push_str(&escape_html(&shorten_whitespace(contents))),
+            Node::Comment(comment, _) => {
+                out.push_str("<!--");
+                out.push_str(comment);
+                out.push_str("-->");
+            }
+            // Static elements do not have these
+            Node::Interpolation(_) | Node::ConditionalSeq(_) => {}
+        }
+    }
+
+    out.push_str("</");
+    out.push_str(tag_name);
+    out.push('>');
+}
+
+/// These elements are dropped by the HTML parser when not inside a `<table>`
+#[inline]
+fn is_non_stringifiable_tag(tag_name: &str) -> bool {
+    matches!(
+        tag_name,
+        "caption" | "thead" | "tr" | "th" | "tbody" | "td" | "tfoot" | "colgroup" | "col"
+    )
+}
+
+#[inline]
+fn is_void_tag(tag_name: &str) -> bool {
+    matches!(
+        tag_name,
+        "area"
+            | "base"
+            | "br"
+            | "col"
+            | "embed"
+            | "hr"
+            | "img"
+            | "input"
+            | "link"
+            | "meta"
+            | "param"
+            | "source"
+            | "track"
+            | "wbr"
+    )
+}
+
+/// Only the known HTML and SVG attributes are safe to be set from an HTML string,
+/// because the others may be DOM properties.
+/// Adapted from `isKnownHtmlAttr` and `isKnownSvgAttr` of `@vue/shared`
+fn is_stringifiable_attr(name: &str) -> bool {
+    if name.starts_with("data-") || name.starts_with("aria-") {
+        return true;
+    }
+
+    matches!(
+        name,
+        // HTML
+        "accept"
+            | "accept-charset"
+            | "accesskey"
+            | "action"
+            | "align"
+            | "allow"
+            | "alt"
+            | "async"
+            | "autocapitalize"
+            | "autocomplete"
+            | "autofocus"
+            | "autoplay"
+            | "background"
+            | "bgcolor"
+            | "border"
+            | "buffered"
+            | "capture"
+            | "challenge"
+            | "charset"
+            | "checked"
+            | "cite"
+            | "class"
+            | "code"
+            | "codebase"
+            | "color"
+            | "cols"
+            | "colspan"
+            | "content"
+            | "contenteditable"
+            | "contextmenu"
+            | "controls"
+            | "coords"
+            | "crossorigin"
+            | "csp"
+            | "data"
+            | "datetime"
+            | "decoding"
+            | "default"
+            | "defer"
+            | "dir"
+            | "dirname"
+            | "disabled"
+            | "download"
+            | "draggable"
+            | "dropzone"
+            | "enctype"
+            | "enterkeyhint"
+            | "for"
+            | "form"
+            | "formaction"
+            | "formenctype"
+            | "formmethod"
+            | "formnovalidate"
+            | "formtarget"
+            | "headers"
+            | "height"
+            | "hidden"
+            | "high"
+            | "href"
+            | "hreflang"
+            | "http-equiv"
+            | "icon"
+            | "id"
+            | "importance"
+            | "inert"
+            | "integrity"
+            | "ismap"
+            | "itemprop"
+            | "keytype"
+            | "kind"
+            | "label"
+            | "lang"
+            | "language"
+            | "loading"
+            | "list"
+            | "loop"
+            | "low"
+            | "manifest"
+            | "max"
+            | "maxlength"
+            | "minlength"
+            | "media"
+            | "min"
+            | "multiple"
+            | "muted"
+            | "name"
+            | "novalidate"
+            | "open"
+            | "optimum"
+            | "pattern"
+            | "ping"
+            | "placeholder"
+            | "poster"
+            | "preload"
+            | "radiogroup"
+            | "readonly"
+            | "referrerpolicy"
+            | "rel"
+            | "required"
+            | "reversed"
+            | "rows"
+            | "rowspan"
+            | "sandbox"
+            | "scope"
+            | "scoped"
+            | "selected"
+            | "shape"
+            | "size"
+            | "sizes"
+            | "slot"
+            | "span"
+            | "spellcheck"
+            | "src"
+            | "srcdoc"
+            | "srclang"
+            | "srcset"
+            | "start"
+            | "step"
+            | "style"
+            | "summary"
+            | "tabindex"
+            | "target"
+            | "title"
+            | "translate"
+            | "type"
+            | "usemap"
+            | "value"
+            | "width"
+            | "wrap"
+            // SVG
+            | "clip-path"
+            | "clip-rule"
+            | "cx"
+            | "cy"
+            | "d"
+            | "dx"
+            | "dy"
+            | "fill"
+            | "fill-opacity"
+            | "fill-rule"
+            | "fx"
+            | "fy"
+            | "gradientTransform"
+            | "gradientUnits"
+            | "mask"
+            | "offset"
+            | "opacity"
+            | "points"
+            | "preserveAspectRatio"
+            | "r"
+            | "rx"
+            | "ry"
+            | "stop-color"
+            | "stop-opacity"
+            | "stroke"
+            | "stroke-dasharray"
+            | "stroke-dashoffset"
+            | "stroke-linecap"
+            | "stroke-linejoin"
+            | "stroke-miterlimit"
+            | "stroke-opacity"
+            | "stroke-width"
+            | "transform"
+            | "version"
+            | "viewBox"
+            | "x"
+            | "x1"
+            | "x2"
+            | "xlink:href"
+            | "xmlns"
+            | "xmlns:xlink"
+            | "y"
+            | "y1"
+            | "y2"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, StartingTag};
+
+    use super::*;
+
+    fn element(tag_name: &str, attributes: &[(&str, &str)], children: Vec<Node>) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: attributes
+                    .iter()
+                    .map(|(name, value)| AttributeOrBinding::RegularAttribute {
+                        name: (*name).into(),
+                        value: (*value).into(),
+                        span: Default::default(),
+                    })
+                    .collect(),
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn text(contents: &str) -> Node {
+        Node::Text(contents.into(), DUMMY_SP)
+    }
+
+    #[test]
+    fn it_stringifies_big_chunks() {
+        // 5 x <p class="item">Item</p>
+        let items: Vec<ElementNode> = (0..5)
+            .map(|_| element("p", &[("class", "item")], vec![text("Item")]))
+            .collect();
+        let items: Vec<&ElementNode> = items.iter().collect();
+
+        let mut ctx = CodegenContext::default();
+        let mut out = Vec::new();
+        ctx.generate_static_elements(&items, &mut out);
+
+        assert_eq!(out.len(), 1);
+        assert_eq!(crate::test_utils::to_str(out.remove(0)), "_hoisted_1");
+        assert_eq!(
+            crate::test_utils::to_str_with_comments(
+                ctx.generate_hoists().remove(0),
+                &ctx.comments()
+            ),
+            r#"const _hoisted_1=/*#__PURE__*/_createStaticVNode("<p class=\"item\">Item</p><p class=\"item\">Item</p><p class=\"item\">Item</p><p class=\"item\">Item</p><p class=\"item\">Item</p>",5);"#
+        );
+    }
+
+    #[test]
+    fn it_escapes_text_and_attributes() {
+        // <p title="a < b">a & b</p>
+        let mut out = String::new();
+        stringify_element(
+            &element("p", &[("title", "a < b")], vec![text("a & b")]),
+            &mut out,
+        );
+
+        assert_eq!(out, r#"<p title="a &lt; b">a &amp; b</p>"#);
+    }
+
+    #[test]
+    fn it_hoists_small_chunks_separately() {
+        // <p class="item">Item</p><p>Item</p>
+        let items = vec![
+            element("p", &[("class", "item")], vec![text("Item")]),
+            element("p", &[], vec![text("Item")]),
+        ];
+        let items: Vec<&ElementNode> = items.iter().collect();
+
+        let mut ctx = CodegenContext::default();
+        let mut out = Vec::new();
+        ctx.generate_static_elements(&items, &mut out);
+
+        let out: Vec<String> = out.into_iter().map(crate::test_utils::to_str).collect();
+        assert_eq!(out, vec!["_hoisted_1", "_hoisted_2"]);
+        assert!(!ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::CreateStaticVNode));
+    }
+
+    #[test]
+    fn it_breaks_chunks_on_non_stringifiable_elements() {
+        // 5 x <p class="item">Item</p>, but the 3rd has an unknown attribute
+        let items: Vec<ElementNode> = (0..5)
+            .map(|idx| {
+                let attr_name = if idx == 2 { "foo" } else { "class" };
+                element("p", &[(attr_name, "item")], vec![text("Item")])
+            })
+            .collect();
+        let items: Vec<&ElementNode> = items.iter().collect();
+
+        let mut ctx = CodegenContext::default();
+        let mut out = Vec::new();
+        ctx.generate_static_elements(&items, &mut out);
+        assert_eq!(out.len(), 5);
+        assert!(!ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::CreateStaticVNode));
+    }
+
+    #[test]
+    fn it_stringifies_elements() {
+        let mut out = String::new();
+        stringify_element(
+            &element(
+                "div",
+                &[("title", "say \"hi\""), ("hidden", "")],
+                vec![
+                    Node::Element(element("img", &[("src", "foo.png")], vec![])),
+                    text("\n  Hello  \n"),
+                    Node::Comment(" comment ".into(), DUMMY_SP),
+                ],
+            ),
+            &mut out,
+        );
+        assert_eq!(
+            out,
+            r#"<div title="say &quot;hi&quot;" hidden><img src="foo.png"> Hello <!-- comment --></div>"#
+        );
+
+        // Table parts cannot be stringified
+        assert!(analyze_stringifiable(&element("tr", &[], vec![])).is_none());
+        assert!(analyze_stringifiable(&element(
+            "div",
+            &[],
+            vec![Node::Element(element("td", &[], vec![]))]
+        ))
+        .is_none());
+    }
+}
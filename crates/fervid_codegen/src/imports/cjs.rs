@@ -0,0 +1,666 @@
+use fervid_core::FervidAtom;
+use fxhash::FxHashMap;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::{
+        ast::{
+            ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, BindingIdent, BlockStmt, CallExpr,
+            Callee, CatchClause, ComputedPropName, CondExpr, Decl, ExportAll, ExportSpecifier,
+            Expr, ExprOrSpread, ExprStmt, FnDecl, Function, Ident, ImportDecl, ImportSpecifier,
+            KeyValueProp, Lit, MemberExpr, MemberProp, Module, ModuleDecl, ModuleExportName,
+            ModuleItem, NamedExport, ObjectPatProp, Param, Pat, PatOrExpr, Prop, PropName,
+            ReturnStmt, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+/// Converts the `import` and `export` declarations written by the user to CommonJS:
+/// - `import Foo, { bar as baz } from "./foo"` -> `const _foo = require("./foo"), Foo = _interopDefault(_foo)`,
+///   and `baz` is accessed as `_foo.bar` to keep the binding live;
+/// - `import "./polyfill"` -> `require("./polyfill")`;
+/// - `export const foo = 1` -> `const foo = 1` and `module.exports.foo = foo`.
+///
+/// The `require`s are hoisted to the top of the module, same as the imports they replace.
+/// The default import supports the transpiled ES modules, i.e. it is `module.exports.default`
+/// when `module.exports.__esModule` is set, and the whole `module.exports` otherwise.
+///
+/// The component is `module.exports` itself, so the named exports are attached to it
+/// at the end of the module. Type-only imports and exports are removed.
+pub(crate) fn convert_to_cjs(module: &mut Module) {
+    let mut imports = CjsImports::default();
+    let mut exports = Vec::new();
+
+    for item in std::mem::take(&mut module.body) {
+        let ModuleItem::ModuleDecl(module_decl) = item else {
+            module.body.push(item);
+            continue;
+        };
+
+        match module_decl {
+            ModuleDecl::Import(import_decl) => convert_import(import_decl, &mut imports),
+
+            ModuleDecl::ExportDecl(export_decl) => {
+                let mut idents = Vec::new();
+                collect_decl_idents(&export_decl.decl, &mut idents);
+                exports.extend(idents.into_iter().map(|ident| {
+                    generate_export_assignment(
+                        ModuleExportName::Ident(ident.to_owned()),
+                        Expr::Ident(ident),
+                    )
+                }));
+
+                module
+                    .body
+                    .push(ModuleItem::Stmt(Stmt::Decl(export_decl.decl)));
+            }
+
+            ModuleDecl::ExportNamed(named_export) => {
+                convert_named_export(named_export, &mut exports);
+            }
+
+            // `export * from "./foo"` -> `Object.assign(module.exports, require("./foo"))`
+            ModuleDecl::ExportAll(ExportAll { src, type_only, .. }) => {
+                if !type_only {
+                    exports.push(generate_export_all(*src));
+                }
+            }
+
+            // The default export is generated by the compiler,
+            // the rest are TypeScript-specific declarations
+            module_decl => module.body.push(ModuleItem::ModuleDecl(module_decl)),
+        }
+    }
+
+    module
+        .body
+        .extend(exports.into_iter().map(ModuleItem::Stmt));
+
+    if !imports.named_imports.is_empty() {
+        module.visit_mut_with(&mut NamedImportsVisitor {
+            named_imports: imports.named_imports,
+            local_vars: vec![],
+        });
+    }
+
+    let mut hoisted = Vec::with_capacity(imports.requires.len() + 1);
+    if imports.uses_interop_default {
+        hoisted.push(ModuleItem::Stmt(generate_interop_default_fn()));
+    }
+    hoisted.extend(imports.requires.into_iter().map(ModuleItem::Stmt));
+    module.body.splice(0..0, hoisted);
+}
+
+/// Generates `module.exports`
+pub(crate) fn generate_module_exports() -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("module"),
+            optional: false,
+        })),
+        prop: MemberProp::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("exports"),
+            optional: false,
+        }),
+    })
+}
+
+/// Generates `require("src")`
+pub(crate) fn generate_require_call(src: Str) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("require"),
+            optional: false,
+        }))),
+        args: vec![ExprOrSpread {
+            spread: None,
+            expr: Box::new(Expr::Lit(Lit::Str(src))),
+        }],
+        type_args: None,
+    })
+}
+
+/// The `require`s replacing the imports of a module
+#[derive(Default)]
+struct CjsImports {
+    /// `const _foo = require("./foo")` and the other statements, in the order of the imports
+    requires: Vec<Stmt>,
+    /// The named imports and their accesses on the module object, e.g. `baz` -> `_foo.bar`
+    named_imports: FxHashMap<FervidAtom, Expr>,
+    /// The generated module objects, e.g. `_foo`
+    module_names: Vec<FervidAtom>,
+    /// Whether `_interopDefault` is used by the default imports
+    uses_interop_default: bool,
+}
+
+/// Converts an import declaration to a `const` declaration with `require`.
+/// Nothing is generated when the import only has types
+fn convert_import(import_decl: ImportDecl, imports: &mut CjsImports) {
+    if import_decl.type_only {
+        return;
+    }
+
+    let src = import_decl.src;
+
+    // `import "./polyfill"` -> `require("./polyfill")`
+    if import_decl.specifiers.is_empty() {
+        imports.requires.push(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(generate_require_call(*src)),
+        }));
+        return;
+    }
+
+    let mut default_local = None;
+    let mut namespace_local = None;
+    let mut named = Vec::new();
+    for specifier in import_decl.specifiers {
+        match specifier {
+            ImportSpecifier::Default(default) => default_local = Some(default.local),
+            ImportSpecifier::Namespace(namespace) => namespace_local = Some(namespace.local),
+            ImportSpecifier::Named(named_specifier) if named_specifier.is_type_only => {}
+            ImportSpecifier::Named(named_specifier) => {
+                let imported = named_specifier
+                    .imported
+                    .unwrap_or_else(|| ModuleExportName::Ident(named_specifier.local.to_owned()));
+                named.push((named_specifier.local, imported));
+            }
+        }
+    }
+
+    // The module object is the namespace import, or `_foo` when only the named imports need it
+    let module_ident = match namespace_local {
+        Some(namespace_local) => Some(namespace_local),
+        None if !named.is_empty() => Some(generate_module_ident(&src.value, imports)),
+        None => None,
+    };
+
+    // `const _foo = require("./foo"), Foo = _interopDefault(_foo)`
+    let mut decls = Vec::with_capacity(2);
+    let mut default_init = generate_require_call(*src);
+    if let Some(ref module_ident) = module_ident {
+        decls.push(var_declarator(
+            binding_pat(module_ident.to_owned()),
+            default_init,
+        ));
+        default_init = Expr::Ident(module_ident.to_owned());
+    }
+    if let Some(default_local) = default_local {
+        imports.uses_interop_default = true;
+        decls.push(var_declarator(
+            binding_pat(default_local),
+            generate_call(ident("_interopDefault"), vec![default_init]),
+        ));
+    }
+
+    if decls.is_empty() {
+        return;
+    }
+
+    if let Some(module_ident) = module_ident {
+        for (local, imported) in named {
+            imports.named_imports.insert(
+                local.sym,
+                generate_member(Expr::Ident(module_ident.to_owned()), imported),
+            );
+        }
+    }
+
+    imports
+        .requires
+        .push(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls,
+        }))));
+}
+
+/// Generates the name of a module object from the import source,
+/// e.g. `_foo_bar` for `./foo-bar.js`. Repeated names get a numeric suffix, e.g. `_foo2`
+fn generate_module_ident(src: &str, imports: &mut CjsImports) -> Ident {
+    let file_name = src.rsplit('/').next().unwrap_or_default();
+    let stem = file_name.split('.').next().unwrap_or_default();
+
+    let mut name = String::with_capacity(stem.len() + 2);
+    name.push('_');
+    for c in stem.chars() {
+        name.push(if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+            c
+        } else {
+            '_'
+        });
+    }
+    if name.len() == 1 {
+        name.push_str("mod");
+    }
+
+    let mut sym = FervidAtom::from(name.as_str());
+    let mut suffix = 2;
+    while imports.module_names.contains(&sym) {
+        sym = FervidAtom::from(format!("{}{}", name, suffix));
+        suffix += 1;
+    }
+    imports.module_names.push(sym.to_owned());
+
+    ident_with_sym(sym)
+}
+
+/// `function _interopDefault(m) { return m && m.__esModule ? m.default : m }`
+fn generate_interop_default_fn() -> Stmt {
+    let m = || Expr::Ident(ident("m"));
+
+    let test = Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::LogicalAnd,
+        left: Box::new(m()),
+        right: Box::new(generate_member(
+            m(),
+            ModuleExportName::Ident(ident("__esModule")),
+        )),
+    });
+
+    let return_stmt = Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(test),
+            cons: Box::new(generate_member(
+                m(),
+                ModuleExportName::Ident(ident("default")),
+            )),
+            alt: Box::new(m()),
+        }))),
+    });
+
+    Stmt::Decl(Decl::Fn(FnDecl {
+        ident: ident("_interopDefault"),
+        declare: false,
+        function: Box::new(Function {
+            params: vec![Param {
+                span: DUMMY_SP,
+                decorators: vec![],
+                pat: binding_pat(ident("m")),
+            }],
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![return_stmt],
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }),
+    }))
+}
+
+/// Replaces the named imports with the accesses on their module object, e.g. `baz` -> `_foo.bar`.
+/// Parameters and local declarations with the same name shadow the imports
+struct NamedImportsVisitor {
+    named_imports: FxHashMap<FervidAtom, Expr>,
+    local_vars: Vec<FervidAtom>,
+}
+
+impl NamedImportsVisitor {
+    fn get_replacement(&self, ident: &Ident) -> Option<Expr> {
+        if self.local_vars.contains(&ident.sym) {
+            return None;
+        }
+
+        self.named_imports.get(&ident.sym).cloned()
+    }
+
+    fn add_pat_locals<'p>(&mut self, pats: impl Iterator<Item = &'p Pat>) {
+        let mut idents = Vec::new();
+        for pat in pats {
+            collect_pat_idents(pat, &mut idents);
+        }
+        self.local_vars
+            .extend(idents.into_iter().map(|ident| ident.sym));
+    }
+}
+
+impl VisitMut for NamedImportsVisitor {
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        let Expr::Ident(ident_expr) = n else {
+            n.visit_mut_children_with(self);
+            return;
+        };
+
+        if let Some(replacement) = self.get_replacement(ident_expr) {
+            *n = replacement;
+        }
+    }
+
+    fn visit_mut_prop(&mut self, n: &mut Prop) {
+        // `{ baz }` -> `{ baz: _foo.bar }`
+        let Prop::Shorthand(ident) = n else {
+            n.visit_mut_children_with(self);
+            return;
+        };
+
+        if let Some(replacement) = self.get_replacement(ident) {
+            *n = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(ident.to_owned()),
+                value: Box::new(replacement),
+            });
+        }
+    }
+
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let locals_len = self.local_vars.len();
+        self.add_pat_locals(n.params.iter().map(|param| &param.pat));
+        n.visit_mut_children_with(self);
+        self.local_vars.truncate(locals_len);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        let locals_len = self.local_vars.len();
+        self.add_pat_locals(n.params.iter());
+        n.visit_mut_children_with(self);
+        self.local_vars.truncate(locals_len);
+    }
+
+    fn visit_mut_catch_clause(&mut self, n: &mut CatchClause) {
+        let locals_len = self.local_vars.len();
+        self.add_pat_locals(n.param.iter());
+        n.visit_mut_children_with(self);
+        self.local_vars.truncate(locals_len);
+    }
+
+    fn visit_mut_block_stmt(&mut self, n: &mut BlockStmt) {
+        let mut idents = Vec::new();
+        for stmt in n.stmts.iter() {
+            if let Stmt::Decl(decl) = stmt {
+                collect_decl_idents(decl, &mut idents);
+            }
+        }
+
+        let locals_len = self.local_vars.len();
+        self.local_vars
+            .extend(idents.into_iter().map(|ident| ident.sym));
+        n.visit_mut_children_with(self);
+        self.local_vars.truncate(locals_len);
+    }
+}
+
+/// `export { foo as bar }` -> `module.exports.bar = foo`,
+/// `export { foo as bar } from "./foo"` -> `module.exports.bar = require("./foo").foo`
+fn convert_named_export(named_export: NamedExport, out: &mut Vec<Stmt>) {
+    if named_export.type_only {
+        return;
+    }
+
+    let src = named_export.src;
+    for specifier in named_export.specifiers {
+        let (exported, value) = match (specifier, &src) {
+            (ExportSpecifier::Named(named), _) if named.is_type_only => continue,
+
+            (ExportSpecifier::Named(named), Some(src)) => {
+                let exported = named.exported.unwrap_or_else(|| named.orig.to_owned());
+                let value = generate_member(generate_require_call((**src).to_owned()), named.orig);
+                (exported, value)
+            }
+
+            (ExportSpecifier::Named(named), None) => {
+                // String names can only be re-exported
+                let ModuleExportName::Ident(orig) = named.orig else {
+                    continue;
+                };
+                let exported = named
+                    .exported
+                    .unwrap_or_else(|| ModuleExportName::Ident(orig.to_owned()));
+                (exported, Expr::Ident(orig))
+            }
+
+            // `export * as foo from "./foo"`
+            (ExportSpecifier::Namespace(namespace), Some(src)) => {
+                (namespace.name, generate_require_call((**src).to_owned()))
+            }
+
+            // `export foo from "./foo"` is only a proposal
+            _ => continue,
+        };
+
+        out.push(generate_export_assignment(exported, value));
+    }
+}
+
+/// `module.exports.foo = value`
+fn generate_export_assignment(exported: ModuleExportName, value: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Expr(Box::new(generate_member(
+                generate_module_exports(),
+                exported,
+            ))),
+            right: Box::new(value),
+        })),
+    })
+}
+
+/// `Object.assign(module.exports, require("./foo"))`
+fn generate_export_all(src: Str) -> Stmt {
+    let object_assign = generate_member(
+        Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("Object"),
+            optional: false,
+        }),
+        ModuleExportName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("assign"),
+            optional: false,
+        }),
+    );
+
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(object_assign)),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(generate_module_exports()),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(generate_require_call(src)),
+                },
+            ],
+            type_args: None,
+        })),
+    })
+}
+
+/// `obj.foo` or `obj["foo-bar"]`
+fn generate_member(obj: Expr, name: ModuleExportName) -> Expr {
+    let prop = match name {
+        ModuleExportName::Ident(ident) => MemberProp::Ident(ident),
+        ModuleExportName::Str(name_str) => MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Lit(Lit::Str(name_str))),
+        }),
+    };
+
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(obj),
+        prop,
+    })
+}
+
+/// Collects the runtime bindings of an exported declaration.
+/// Types and ambient declarations do not exist in runtime
+fn collect_decl_idents(decl: &Decl, out: &mut Vec<Ident>) {
+    match decl {
+        Decl::Class(class_decl) if !class_decl.declare => out.push(class_decl.ident.to_owned()),
+        Decl::Fn(fn_decl) if !fn_decl.declare => out.push(fn_decl.ident.to_owned()),
+        Decl::Var(var_decl) if !var_decl.declare => {
+            for var_declarator in var_decl.decls.iter() {
+                collect_pat_idents(&var_declarator.name, out);
+            }
+        }
+        Decl::TsEnum(ts_enum) if !ts_enum.declare => out.push(ts_enum.id.to_owned()),
+        _ => {}
+    }
+}
+
+/// Collects the identifiers bound by a pattern, e.g. `foo` and `bar` in `{ foo, baz: [bar] }`
+fn collect_pat_idents(pat: &Pat, out: &mut Vec<Ident>) {
+    match pat {
+        Pat::Ident(binding_ident) => out.push(binding_ident.id.to_owned()),
+        Pat::Array(array_pat) => {
+            for elem in array_pat.elems.iter().flatten() {
+                collect_pat_idents(elem, out);
+            }
+        }
+        Pat::Rest(rest_pat) => collect_pat_idents(&rest_pat.arg, out),
+        Pat::Object(object_pat) => {
+            for prop in object_pat.props.iter() {
+                match prop {
+                    ObjectPatProp::KeyValue(key_value) => collect_pat_idents(&key_value.value, out),
+                    ObjectPatProp::Assign(assign) => out.push(Ident {
+                        span: assign.key.span,
+                        sym: assign.key.sym.to_owned(),
+                        optional: false,
+                    }),
+                    ObjectPatProp::Rest(rest_pat) => collect_pat_idents(&rest_pat.arg, out),
+                }
+            }
+        }
+        Pat::Assign(assign_pat) => collect_pat_idents(&assign_pat.left, out),
+        Pat::Invalid(_) | Pat::Expr(_) => {}
+    }
+}
+
+fn binding_pat(id: Ident) -> Pat {
+    Pat::Ident(BindingIdent { id, type_ann: None })
+}
+
+fn var_declarator(name: Pat, init: Expr) -> VarDeclarator {
+    VarDeclarator {
+        span: DUMMY_SP,
+        name,
+        init: Some(Box::new(init)),
+        definite: false,
+    }
+}
+
+fn ident(sym: &str) -> Ident {
+    ident_with_sym(FervidAtom::from(sym))
+}
+
+fn ident_with_sym(sym: FervidAtom) -> Ident {
+    Ident {
+        span: DUMMY_SP,
+        sym,
+        optional: false,
+    }
+}
+
+/// `callee(args)`
+fn generate_call(callee: Ident, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Ident(callee))),
+        args: args
+            .into_iter()
+            .map(|expr| ExprOrSpread {
+                spread: None,
+                expr: Box::new(expr),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{js_module, to_str};
+
+    #[test]
+    fn it_converts_imports() {
+        let mut module = js_module(
+            r#"
+import Foo, { bar as baz, qux } from "./foo";
+import * as ns from "./ns";
+import "./polyfill";
+console.log(Foo, baz, { qux }, ns.a);
+"#,
+        );
+        convert_to_cjs(&mut module);
+
+        assert_eq!(
+            to_str(module),
+            concat!(
+                r#"function _interopDefault(m){return m&&m.__esModule?m.default:m}"#,
+                r#"const _foo=require("./foo"),Foo=_interopDefault(_foo);const ns=require("./ns");require("./polyfill");"#,
+                r#"console.log(Foo,_foo.bar,{qux:_foo.qux},ns.a);"#
+            )
+        );
+    }
+
+    #[test]
+    fn it_hoists_imports() {
+        // The named imports stay live and are not shadowed by the locals
+        let mut module = js_module(
+            r#"
+inc();
+function log(count) {
+    console.log(count);
+}
+log(count);
+import { count, inc } from "./counter";
+import { count as other } from "./nested/counter";
+"#,
+        );
+        convert_to_cjs(&mut module);
+
+        assert_eq!(
+            to_str(module),
+            concat!(
+                r#"const _counter=require("./counter");const _counter2=require("./nested/counter");"#,
+                r#"_counter.inc();function log(count){console.log(count)}log(_counter.count);"#
+            )
+        );
+    }
+
+    #[test]
+    fn it_converts_exports() {
+        let mut module = js_module(
+            r#"
+export const a = 1, { b, c: [d] } = obj;
+export function e() {}
+export { a as f };
+export { g } from "./g";
+export * as h from "./h";
+export * from "./i";
+foo();
+"#,
+        );
+        convert_to_cjs(&mut module);
+
+        assert_eq!(
+            to_str(module),
+            concat!(
+                r#"const a=1,{b,c:[d]}=obj;function e(){}foo();"#,
+                r#"module.exports.a=a;module.exports.b=b;module.exports.d=d;module.exports.e=e;"#,
+                r#"module.exports.f=a;module.exports.g=require("./g").g;"#,
+                r#"module.exports.h=require("./h");Object.assign(module.exports,require("./i"));"#
+            )
+        );
+    }
+}
@@ -1,11 +1,33 @@
-use fervid_core::{FervidAtom, VueImports};
+use fervid_core::{FervidAtom, SsrImports, VueImports};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Ident, ImportNamedSpecifier, ImportSpecifier, ModuleExportName},
+    ecma::ast::{
+        AssignExpr, AssignOp, BindingIdent, Decl, ExportDefaultExpr, Expr, ExprStmt, Ident,
+        ImportDecl, ImportNamedSpecifier, ImportSpecifier, KeyValuePatProp, ModuleDecl,
+        ModuleExportName, ModuleItem, ObjectPat, ObjectPatProp, Pat, PatOrExpr, PropName, Stmt,
+        Str, VarDecl, VarDeclKind, VarDeclarator,
+    },
 };
 
 use super::context::CodegenContext;
 
+mod cjs;
+
+pub(crate) use cjs::convert_to_cjs;
+use cjs::{generate_module_exports, generate_require_call};
+
+/// Module system of the generated code
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleFormat {
+    /// `import { openBlock as _openBlock } from "vue"` and `export default`
+    #[default]
+    Esm,
+    /// `const { openBlock: _openBlock } = require("vue")` and `module.exports =`.
+    /// The imports and exports written by the user are converted as well,
+    /// e.g. `export const foo = 1` is attached to the component as `module.exports.foo`
+    Cjs,
+}
+
 impl CodegenContext {
     pub fn add_to_imports(&mut self, vue_import: VueImports) {
         self.bindings_helper.vue_imports |= vue_import;
@@ -21,41 +43,146 @@ impl CodegenContext {
         vue_import.as_atom()
     }
 
+    pub fn add_to_ssr_imports(&mut self, ssr_import: SsrImports) {
+        self.bindings_helper.ssr_imports |= ssr_import;
+    }
+
+    pub fn get_and_add_ssr_import_ident(&mut self, ssr_import: SsrImports) -> FervidAtom {
+        self.add_to_ssr_imports(ssr_import);
+        ssr_import.as_atom()
+    }
+
     /// Generates all the imports used by template generation.
     /// All of the imports come from 'vue'.
     pub fn generate_imports(&self) -> Vec<ImportSpecifier> {
-        let mut result = Vec::new();
-        for import in self.bindings_helper.vue_imports.into_iter() {
-            let import_raw = import.as_str();
+        self.bindings_helper
+            .vue_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
+    }
 
-            let import_local = Ident {
-                span: DUMMY_SP,
-                sym: import.as_atom(),
-                optional: false,
-            };
+    /// Generates the imports used by the SSR render function.
+    /// All of the imports come from 'vue/server-renderer'.
+    pub fn generate_ssr_imports(&self) -> Vec<ImportSpecifier> {
+        self.bindings_helper
+            .ssr_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
+    }
 
-            let import_vue = Some(ModuleExportName::Ident(Ident {
-                span: DUMMY_SP,
-                sym: FervidAtom::from(&import_raw[1..]),
-                optional: false,
-            }));
+    /// Generates the import declaration of the helpers from `src`, e.g.
+    /// `import { openBlock as _openBlock } from "vue"` for [`ModuleFormat::Esm`]
+    /// or `const { openBlock: _openBlock } = require("vue")` for [`ModuleFormat::Cjs`]
+    pub fn generate_import_item(&self, specifiers: Vec<ImportSpecifier>, src: &str) -> ModuleItem {
+        let src = Str {
+            span: DUMMY_SP,
+            value: FervidAtom::from(src),
+            raw: None,
+        };
 
-            result.push(ImportSpecifier::Named(ImportNamedSpecifier {
+        if let ModuleFormat::Esm = self.module_format {
+            return ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                 span: DUMMY_SP,
-                local: import_local,
-                imported: import_vue,
-                is_type_only: false,
+                specifiers,
+                src: Box::new(src),
+                type_only: false,
+                with: None,
             }));
         }
 
-        result
+        // `openBlock as _openBlock` -> `openBlock: _openBlock`
+        let props = specifiers
+            .into_iter()
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::Named(named) => {
+                    let key = match named.imported {
+                        Some(ModuleExportName::Ident(imported)) => imported,
+                        _ => named.local.to_owned(),
+                    };
+
+                    Some(ObjectPatProp::KeyValue(KeyValuePatProp {
+                        key: PropName::Ident(key),
+                        value: Box::new(Pat::Ident(BindingIdent {
+                            id: named.local,
+                            type_ann: None,
+                        })),
+                    }))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let require_call = generate_require_call(src);
+
+        ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Object(ObjectPat {
+                    span: DUMMY_SP,
+                    props,
+                    optional: false,
+                    type_ann: None,
+                }),
+                init: Some(Box::new(require_call)),
+                definite: false,
+            }],
+        }))))
     }
+
+    /// Generates the default export of the component,
+    /// i.e. `export default expr` or `module.exports = expr`, see [`ModuleFormat`]
+    pub fn generate_default_export(&self, expr: Expr) -> ModuleItem {
+        match self.module_format {
+            ModuleFormat::Esm => {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                    span: DUMMY_SP,
+                    expr: Box::new(expr),
+                }))
+            }
+
+            ModuleFormat::Cjs => ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Assign(AssignExpr {
+                    span: DUMMY_SP,
+                    op: AssignOp::Assign,
+                    left: PatOrExpr::Expr(Box::new(generate_module_exports())),
+                    right: Box::new(expr),
+                })),
+            })),
+        }
+    }
+}
+
+/// `_openBlock` -> `openBlock as _openBlock`
+pub(crate) fn generate_import_specifier(import_raw: &str) -> ImportSpecifier {
+    let import_local = Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(import_raw),
+        optional: false,
+    };
+
+    let import_vue = Some(ModuleExportName::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(&import_raw[1..]),
+        optional: false,
+    }));
+
+    ImportSpecifier::Named(ImportNamedSpecifier {
+        span: DUMMY_SP,
+        local: import_local,
+        imported: import_vue,
+        is_type_only: false,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use swc_core::ecma::ast::{ImportDecl, Str};
 
     #[test]
     fn it_remembers_added_imports() {
@@ -103,4 +230,28 @@ mod tests {
 
         assert_eq!(crate::test_utils::to_str(vue_import_decl), "import{createBlock as _createBlock,normalizeClass as _normalizeClass,openBlock as _openBlock,toDisplayString as _toDisplayString,withCtx as _withCtx,withDirectives as _withDirectives,withModifiers as _withModifiers}from\"vue\";");
     }
+
+    #[test]
+    fn it_generates_cjs_imports_and_export() {
+        let mut ctx = CodegenContext::default();
+        ctx.module_format = ModuleFormat::Cjs;
+        ctx.add_to_imports(VueImports::OpenBlock);
+        ctx.add_to_imports(VueImports::CreateElementBlock);
+
+        let require_item = ctx.generate_import_item(ctx.generate_imports(), "vue");
+        assert_eq!(
+            crate::test_utils::to_str(require_item),
+            r#"const{createElementBlock:_createElementBlock,openBlock:_openBlock}=require("vue");"#
+        );
+
+        let export_item = ctx.generate_default_export(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from("_sfc_main"),
+            optional: false,
+        }));
+        assert_eq!(
+            crate::test_utils::to_str(export_item),
+            "module.exports=_sfc_main;"
+        );
+    }
 }
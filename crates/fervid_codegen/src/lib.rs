@@ -15,7 +15,11 @@ mod control_flow;
 mod directives;
 mod interpolation;
 mod elements;
+mod hmr;
+mod hoisting;
 mod imports;
+mod ssr;
+mod style_injection;
 mod text;
 mod utils;
 
@@ -23,3 +27,7 @@ mod utils;
 mod test_utils;
 
 pub use context::CodegenContext;
+pub use control_flow::{parse_es_version, EmitOptions};
+pub use hmr::{HmrOptions, HmrTarget};
+pub use imports::ModuleFormat;
+pub use style_injection::{StyleInjectionOptions, StyleInjector};
@@ -0,0 +1,391 @@
+use fervid_core::{
+    AttributeOrBinding, FervidAtom, SsrImports, StrOrExpr, VBindDirective, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{CallExpr, Callee, CondExpr, Expr, ExprOrSpread},
+};
+
+use crate::{
+    attributes::{generate_regular_style, is_boolean_attr},
+    context::CodegenContext,
+    utils::to_camelcase,
+};
+
+use super::{array, call, escape_html, ident, str_lit, SsrBuffer};
+
+impl CodegenContext {
+    /// Generates the attributes of an element inside its opening tag.
+    ///
+    /// Static attributes are escaped and pushed as strings, e.g. ` class="foo"`,
+    /// while bindings use the runtime helpers, e.g. `${_ssrRenderAttr("id", _ctx.id)}`.
+    /// The root element and the elements with `v-bind="obj"` render all the attributes
+    /// using `_ssrRenderAttrs`, because their final set of attributes is only known at runtime.
+    pub(crate) fn generate_ssr_attributes(
+        &mut self,
+        attributes: &[AttributeOrBinding],
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let has_v_bind_object = attributes.iter().any(|attr| {
+            matches!(
+                attr,
+                AttributeOrBinding::VBind(VBindDirective { argument: None, .. })
+            )
+        });
+        if is_root || has_v_bind_object {
+            self.generate_ssr_merged_attributes(attributes, buffer, is_root);
+            return;
+        }
+
+        // `class` and `style` may be both static and bound, but are rendered as one attribute
+        let mut class_regular = None;
+        let mut class_bound = None;
+        let mut style_regular = None;
+        let mut style_bound = None;
+        for attr in attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "class" => {
+                    class_regular = Some(value);
+                }
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "style" => {
+                    style_regular = Some(value);
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(argument)),
+                    value,
+                    ..
+                }) if argument == "class" => {
+                    class_bound = Some(value.as_ref());
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(argument)),
+                    value,
+                    ..
+                }) if argument == "style" => {
+                    style_bound = Some(value.as_ref());
+                }
+                _ => {}
+            }
+        }
+
+        for attr in attributes.iter() {
+            match attr {
+                // `ref` and `key` are runtime-only
+                AttributeOrBinding::RegularAttribute { name, .. }
+                | AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    ..
+                }) if name == "ref" || name == "key" => {}
+
+                // `class` is rendered in place of its first occurrence
+                AttributeOrBinding::RegularAttribute { name, .. }
+                | AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    ..
+                }) if name == "class" => {
+                    self.generate_ssr_class(class_regular.take(), class_bound.take(), buffer);
+                }
+
+                // Same for `style`
+                AttributeOrBinding::RegularAttribute { name, .. }
+                | AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    ..
+                }) if name == "style" => {
+                    self.generate_ssr_style(style_regular.take(), style_bound.take(), buffer);
+                }
+
+                // `disabled` or `id="foo"`
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                    push_static_attr(buffer, name, value);
+                }
+
+                // `:id="foo"` or `:disabled="foo"`
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(argument)),
+                    value,
+                    is_camel,
+                    ..
+                }) => {
+                    let name = if *is_camel {
+                        let mut camelized = String::with_capacity(argument.len());
+                        let _ = to_camelcase(argument, &mut camelized); // ignore fault
+                        FervidAtom::from(camelized)
+                    } else {
+                        argument.to_owned()
+                    };
+
+                    self.generate_ssr_bound_attr(name, (**value).to_owned(), buffer);
+                }
+
+                // `:[foo]="bar"` -> `${_ssrRenderDynamicAttr(foo, bar)}`
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Expr(argument)),
+                    value,
+                    ..
+                }) => {
+                    let ssr_render_dynamic_attr =
+                        self.get_and_add_ssr_import_ident(SsrImports::SsrRenderDynamicAttr);
+                    buffer.push_expr(call(
+                        ssr_render_dynamic_attr,
+                        vec![(**argument).to_owned(), (**value).to_owned()],
+                    ));
+                }
+
+                // Already handled by `generate_ssr_merged_attributes`
+                AttributeOrBinding::VBind(VBindDirective { argument: None, .. }) => {}
+
+                // Event listeners are not rendered on the server
+                AttributeOrBinding::VOn(_) => {}
+            }
+        }
+    }
+
+    /// Generates `${_ssrRenderAttrs({ /* props */ })}`.
+    /// The root element also merges its props with the fallthrough `_attrs`
+    fn generate_ssr_merged_attributes(
+        &mut self,
+        attributes: &[AttributeOrBinding],
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        // Event listeners are not rendered on the server
+        let attributes: Vec<AttributeOrBinding> = attributes
+            .iter()
+            .filter(|attr| !matches!(attr, AttributeOrBinding::VOn(_)))
+            .cloned()
+            .collect();
+
+        let mut props = Vec::new();
+        self.generate_attributes(&attributes, &mut props);
+
+        let mut props_expr = if !props.is_empty() {
+            Some(self.generate_props_expr(props, false, DUMMY_SP))
+        } else {
+            None
+        };
+
+        if is_root {
+            props_expr = Some(self.merge_with_fallthrough_attrs(props_expr));
+        }
+
+        let Some(props_expr) = props_expr else {
+            return;
+        };
+
+        let ssr_render_attrs = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderAttrs);
+        buffer.push_expr(call(ssr_render_attrs, vec![props_expr]));
+    }
+
+    /// `:disabled="foo"` -> `${_ssrIncludeBooleanAttr(foo) ? " disabled" : ""}`,
+    /// `:id="foo"` -> `${_ssrRenderAttr("id", foo)}`
+    fn generate_ssr_bound_attr(&mut self, name: FervidAtom, value: Expr, buffer: &mut SsrBuffer) {
+        if is_boolean_attr(&name) {
+            let ssr_include_boolean_attr =
+                self.get_and_add_ssr_import_ident(SsrImports::SsrIncludeBooleanAttr);
+
+            buffer.push_expr(Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test: Box::new(call(ssr_include_boolean_attr, vec![value])),
+                cons: Box::new(str_lit(FervidAtom::from(format!(" {}", name)))),
+                alt: Box::new(str_lit(FervidAtom::from(""))),
+            }));
+            return;
+        }
+
+        let ssr_render_attr = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderAttr);
+        buffer.push_expr(call(ssr_render_attr, vec![str_lit(name), value]));
+    }
+
+    /// `class="foo"`, `class="${_ssrRenderClass(bar)}"`
+    /// or `class="${_ssrRenderClass(["foo", bar])}"`
+    fn generate_ssr_class(
+        &mut self,
+        class_regular: Option<&FervidAtom>,
+        class_bound: Option<&Expr>,
+        buffer: &mut SsrBuffer,
+    ) {
+        let class_expr = match (class_regular, class_bound) {
+            (Some(regular), None) => {
+                push_static_attr(buffer, "class", regular);
+                return;
+            }
+            (None, Some(bound)) => bound.to_owned(),
+            (Some(regular), Some(bound)) => {
+                array(vec![str_lit(regular.to_owned()), bound.to_owned()])
+            }
+            (None, None) => return,
+        };
+
+        let ssr_render_class = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderClass);
+        buffer.push_str(" class=\"");
+        buffer.push_expr(call(ssr_render_class, vec![class_expr]));
+        buffer.push_str("\"");
+    }
+
+    /// `style="color: red"`, `style="${_ssrRenderStyle(foo)}"`
+    /// or `style="${_ssrRenderStyle([{ color: "red" }, foo])}"`
+    fn generate_ssr_style(
+        &mut self,
+        style_regular: Option<&FervidAtom>,
+        style_bound: Option<&Expr>,
+        buffer: &mut SsrBuffer,
+    ) {
+        let style_expr = match (style_regular, style_bound) {
+            (Some(regular), None) => {
+                push_static_attr(buffer, "style", regular);
+                return;
+            }
+            (None, Some(bound)) => bound.to_owned(),
+            (Some(regular), Some(bound)) => array(vec![
+                Expr::Object(generate_regular_style(regular, DUMMY_SP)),
+                bound.to_owned(),
+            ]),
+            (None, None) => return,
+        };
+
+        let ssr_render_style = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderStyle);
+        buffer.push_str(" style=\"");
+        buffer.push_expr(call(ssr_render_style, vec![style_expr]));
+        buffer.push_str("\"");
+    }
+
+    /// `_mergeProps(props, _attrs)` or just `_attrs` when there are no props.
+    /// Already merged props, e.g. `_mergeProps({ id: "a" }, obj)`,
+    /// get `_attrs` as the last argument
+    pub(crate) fn merge_with_fallthrough_attrs(&mut self, props_expr: Option<Expr>) -> Expr {
+        let Some(props_expr) = props_expr else {
+            return ident("_attrs");
+        };
+
+        let merge_props = self.get_and_add_import_ident(VueImports::MergeProps);
+
+        match props_expr {
+            Expr::Call(mut call_expr) if is_call_to(&call_expr, &merge_props) => {
+                call_expr.args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(ident("_attrs")),
+                });
+                Expr::Call(call_expr)
+            }
+            props_expr => call(merge_props, vec![props_expr, ident("_attrs")]),
+        }
+    }
+}
+
+/// ` name="value"`, or just ` name` when the value is empty.
+/// The value is escaped at compile time
+fn push_static_attr(buffer: &mut SsrBuffer, name: &str, value: &str) {
+    buffer.push_str(" ");
+    buffer.push_str(name);
+    if !value.is_empty() {
+        buffer.push_str("=\"");
+        buffer.push_str(&escape_html(value));
+        buffer.push_str("\"");
+    }
+}
+
+fn is_call_to(call_expr: &CallExpr, callee_sym: &FervidAtom) -> bool {
+    let Callee::Expr(ref callee) = call_expr.callee else {
+        return false;
+    };
+
+    matches!(**callee, Expr::Ident(ref callee_ident) if callee_ident.sym == *callee_sym)
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::VOnDirective;
+
+    use super::*;
+    use crate::test_utils::{js, to_str};
+
+    fn regular(name: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: name.into(),
+            value: value.into(),
+            span: Default::default(),
+        }
+    }
+
+    fn v_bind(argument: Option<StrOrExpr>, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument,
+            value: js(value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: Default::default(),
+        })
+    }
+
+    fn test_out(attributes: Vec<AttributeOrBinding>, is_root: bool) -> String {
+        let mut ctx = CodegenContext::default();
+        let mut buffer = SsrBuffer::default();
+        ctx.generate_ssr_attributes(&attributes, &mut buffer, is_root);
+
+        buffer.into_stmts().into_iter().map(to_str).collect()
+    }
+
+    #[test]
+    fn it_generates_ssr_attributes() {
+        // id="a<b" :class="foo" class="bar" :style="baz" :disabled="dis" :title="t" :[key]="val"
+        // ref="r" @click="onClick"
+        assert_eq!(
+            test_out(
+                vec![
+                    regular("id", "a<b"),
+                    v_bind(Some("class".into()), "foo"),
+                    regular("class", "bar"),
+                    v_bind(Some("style".into()), "baz"),
+                    v_bind(Some("disabled".into()), "dis"),
+                    v_bind(Some("title".into()), "t"),
+                    v_bind(Some(StrOrExpr::Expr(js("key"))), "val"),
+                    regular("ref", "r"),
+                    AttributeOrBinding::VOn(VOnDirective {
+                        event: Some("click".into()),
+                        handler: Some(js("onClick")),
+                        modifiers: vec![],
+                        is_cacheable: false,
+                        span: Default::default(),
+                    }),
+                ],
+                false
+            ),
+            r#"_push(` id="a&lt;b" class="${_ssrRenderClass(["bar",foo])}" style="${_ssrRenderStyle(baz)}"${_ssrIncludeBooleanAttr(dis)?" disabled":""}${_ssrRenderAttr("title",t)}${_ssrRenderDynamicAttr(key,val)}`);"#
+        );
+
+        // style="color: red" :style="baz"
+        assert_eq!(
+            test_out(
+                vec![
+                    regular("style", "color: red"),
+                    v_bind(Some("style".into()), "baz")
+                ],
+                false
+            ),
+            r#"_push(` style="${_ssrRenderStyle([{color:"red"},baz])}"`);"#
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_merged_attributes() {
+        // id="a" v-bind="obj"
+        let attributes = vec![regular("id", "a"), v_bind(None, "obj")];
+        assert_eq!(
+            test_out(attributes.clone(), false),
+            r#"_push(`${_ssrRenderAttrs(_mergeProps({id:"a"},obj))}`);"#
+        );
+
+        // Root element also gets the fallthrough attributes
+        assert_eq!(
+            test_out(attributes, true),
+            r#"_push(`${_ssrRenderAttrs(_mergeProps({id:"a"},obj,_attrs))}`);"#
+        );
+        assert_eq!(
+            test_out(vec![], true),
+            r#"_push(`${_ssrRenderAttrs(_attrs)}`);"#
+        );
+    }
+}
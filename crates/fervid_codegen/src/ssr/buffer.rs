@@ -0,0 +1,99 @@
+use fervid_core::FervidAtom;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, ExprStmt, Ident, Stmt, Tpl, TplElement},
+};
+
+/// Collects the SSR output into the `_push` calls.
+///
+/// Adjacent static strings and expressions are merged into a single template literal,
+/// e.g. `<div>` + `foo` + `</div>` is pushed as ``_push(`<div>${foo}</div>`)``.
+#[derive(Debug, Default)]
+pub struct SsrBuffer {
+    stmts: Vec<Stmt>,
+    quasis: Vec<TplElement>,
+    exprs: Vec<Box<Expr>>,
+    current: String,
+}
+
+impl SsrBuffer {
+    /// Appends a static string. It is expected to be already escaped for HTML
+    pub fn push_str(&mut self, s: &str) {
+        self.current.push_str(s);
+    }
+
+    /// Appends `${expr}` to the current template literal
+    pub fn push_expr(&mut self, expr: Expr) {
+        let raw = std::mem::take(&mut self.current);
+        self.quasis.push(tpl_element(raw, false));
+        self.exprs.push(Box::new(expr));
+    }
+
+    /// Pushes an expression on its own, e.g. `_push(_ssrRenderComponent(/*...*/))`
+    pub fn push_call(&mut self, expr: Expr) {
+        self.flush();
+        self.stmts.push(push_stmt(expr));
+    }
+
+    /// Adds a statement as is, e.g. `if (foo) { /*...*/ }`
+    pub fn push_stmt(&mut self, stmt: Stmt) {
+        self.flush();
+        self.stmts.push(stmt);
+    }
+
+    /// Pushes the accumulated template literal, if any
+    pub fn flush(&mut self) {
+        if self.current.is_empty() && self.exprs.is_empty() {
+            return;
+        }
+
+        let mut quasis = std::mem::take(&mut self.quasis);
+        quasis.push(tpl_element(std::mem::take(&mut self.current), true));
+
+        self.stmts.push(push_stmt(Expr::Tpl(Tpl {
+            span: DUMMY_SP,
+            exprs: std::mem::take(&mut self.exprs),
+            quasis,
+        })));
+    }
+
+    pub fn into_stmts(mut self) -> Vec<Stmt> {
+        self.flush();
+        self.stmts
+    }
+}
+
+/// `_push(expr)`
+fn push_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("_push"),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(expr),
+            }],
+            type_args: None,
+        })),
+    })
+}
+
+/// Template literal part, where the backslashes, backticks and `${` are escaped
+fn tpl_element(cooked: String, tail: bool) -> TplElement {
+    let raw = cooked
+        .replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "$\\{");
+
+    TplElement {
+        span: DUMMY_SP,
+        tail,
+        cooked: Some(cooked.into()),
+        raw: raw.into(),
+    }
+}
@@ -0,0 +1,310 @@
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, Diagnostic, DiagnosticKind, ElementNode,
+    Node, SsrImports, StrOrExpr, VBindDirective, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, BlockStmt, BlockStmtOrExpr, Bool, Expr, ExprStmt, KeyValueProp, Lit, ObjectLit,
+        Pat, Prop, PropOrSpread, Stmt,
+    },
+};
+
+use crate::{
+    components::generate_slots_flag, context::CodegenContext, utils::str_or_expr_to_propname,
+};
+
+use super::{call, ident, null, param, str_lit, SsrBuffer};
+
+impl CodegenContext {
+    /// Generates `<component :is="foo">` as a VNode rendered on the server:
+    ///
+    /// ```js
+    /// _ssrRenderVNode(_push, _createVNode(_resolveDynamicComponent(_ctx.foo), null, null), _parent)
+    /// ```
+    pub(crate) fn generate_ssr_dynamic_component(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let attributes = &element_node.starting_tag.attributes;
+        let component_is_attribute_idx = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "is"))
+            .expect("<component> should always have `is` attribute");
+
+        let is_attribute_expr = match attributes[component_is_attribute_idx] {
+            AttributeOrBinding::RegularAttribute { ref value, .. } => str_lit(value.to_owned()),
+            AttributeOrBinding::VBind(VBindDirective { ref value, .. }) => (**value).to_owned(),
+            AttributeOrBinding::VOn(_) => unreachable!(),
+        };
+
+        // The rest of the attributes are the props, same as for a regular component
+        let mut component_node = element_node.to_owned();
+        component_node
+            .starting_tag
+            .attributes
+            .remove(component_is_attribute_idx);
+        let (props_expr, slots_expr) =
+            self.generate_ssr_component_props_and_slots(&component_node, is_root);
+
+        let resolve_dynamic_component =
+            self.get_and_add_import_ident(VueImports::ResolveDynamicComponent);
+        let create_vnode = self.get_and_add_import_ident(VueImports::CreateVNode);
+        let vnode = call(
+            create_vnode,
+            vec![
+                call(resolve_dynamic_component, vec![is_attribute_expr]),
+                props_expr,
+                slots_expr,
+            ],
+        );
+
+        let mut args = vec![ident("_push"), vnode, ident("_parent")];
+        if self.ssr_in_slot {
+            args.push(ident("_scopeId"));
+        }
+
+        let ssr_render_vnode = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderVNode);
+        buffer.push_stmt(expr_stmt(call(ssr_render_vnode, args)));
+    }
+
+    /// Generates `<Teleport>` with its content pushed to the target:
+    ///
+    /// ```js
+    /// _ssrRenderTeleport(_push, (_push) => {
+    ///   _push(`<div></div>`)
+    /// }, "#modal", false, _parent)
+    /// ```
+    pub(crate) fn generate_ssr_teleport(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        // Teleport does not render an element, so directives are not applicable
+        self.report_teleport_directives(element_node);
+
+        let mut target_expr = None;
+        let mut disabled_expr = Expr::Lit(Lit::Bool(Bool {
+            span: DUMMY_SP,
+            value: false,
+        }));
+
+        for attr in element_node.starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "to" => {
+                    target_expr = Some(str_lit(value.to_owned()));
+                }
+                AttributeOrBinding::RegularAttribute { name, .. } if name == "disabled" => {
+                    disabled_expr = Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    }));
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "to" => {
+                    target_expr = Some((**value).to_owned());
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "disabled" => {
+                    disabled_expr = (**value).to_owned();
+                }
+                _ => {}
+            }
+        }
+
+        // The target is required on the server, because the content is rendered into it
+        let target_expr = target_expr.unwrap_or_else(|| {
+            self.bindings_helper.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::TeleportMissingTarget,
+                span: element_node.span,
+            });
+            null()
+        });
+
+        let mut content_buffer = SsrBuffer::default();
+        self.generate_ssr_children(&element_node.children, &mut content_buffer, false);
+        let content_fn = arrow(vec![param("_push")], content_buffer.into_stmts());
+
+        let ssr_render_teleport = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderTeleport);
+        buffer.push_stmt(expr_stmt(call(
+            ssr_render_teleport,
+            vec![
+                ident("_push"),
+                content_fn,
+                target_expr,
+                disabled_expr,
+                ident("_parent"),
+            ],
+        )));
+    }
+
+    /// Generates `<Suspense>` with its slots as functions pushing the content:
+    ///
+    /// ```js
+    /// _ssrRenderSuspense(_push, {
+    ///   default: () => {
+    ///     _push(`<div></div>`)
+    ///   },
+    ///   _: 1
+    /// })
+    /// ```
+    pub(crate) fn generate_ssr_suspense(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let mut slots = Vec::new();
+        let mut default_slot_children = Vec::new();
+
+        for child in element_node.children.iter() {
+            match child {
+                // `<template #fallback>`
+                Node::Element(ElementNode {
+                    starting_tag,
+                    children,
+                    ..
+                }) if starting_tag.tag_name == "template" => {
+                    let v_slot = starting_tag
+                        .directives
+                        .as_ref()
+                        .and_then(|directives| directives.v_slot.as_ref());
+
+                    let Some(v_slot) = v_slot else {
+                        default_slot_children.push(child.to_owned());
+                        continue;
+                    };
+
+                    let slot_name = v_slot
+                        .slot_name
+                        .to_owned()
+                        .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+                    let slot_fn = self.generate_ssr_suspense_slot_fn(children);
+                    slots.push(slot_prop(slot_name, slot_fn));
+                }
+
+                _ => default_slot_children.push(child.to_owned()),
+            }
+        }
+
+        // Comments do not make a default slot on their own, same as for components
+        let has_default_slot_content = default_slot_children
+            .iter()
+            .any(|child| !matches!(child, Node::Comment(_, _)));
+        if has_default_slot_content {
+            let slot_fn = self.generate_ssr_suspense_slot_fn(&default_slot_children);
+            slots.insert(
+                0,
+                slot_prop(StrOrExpr::Str(fervid_atom!("default")), slot_fn),
+            );
+        }
+
+        slots.push(generate_slots_flag(element_node, DUMMY_SP));
+
+        let ssr_render_suspense = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderSuspense);
+        buffer.push_stmt(expr_stmt(call(
+            ssr_render_suspense,
+            vec![
+                ident("_push"),
+                Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: slots,
+                }),
+            ],
+        )));
+    }
+
+    /// `() => { _push(`...`) }`
+    fn generate_ssr_suspense_slot_fn(&mut self, children: &[Node]) -> Expr {
+        let mut slot_buffer = SsrBuffer::default();
+        self.generate_ssr_children(children, &mut slot_buffer, false);
+        arrow(vec![], slot_buffer.into_stmts())
+    }
+
+    /// Generates `<TransitionGroup>` as its wrapper element,
+    /// e.g. `<ul><li>a</li></ul>` for `<TransitionGroup tag="ul">`.
+    /// Without the `tag`, the children are rendered as a Fragment
+    pub(crate) fn generate_ssr_transition_group(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let attributes = &element_node.starting_tag.attributes;
+        let tag_attribute_idx = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "tag"));
+
+        let Some(tag_attribute_idx) = tag_attribute_idx else {
+            buffer.push_str("<!--[-->");
+            self.generate_ssr_children(&element_node.children, buffer, false);
+            buffer.push_str("<!--]-->");
+            return;
+        };
+
+        let wrapper_attributes: Vec<AttributeOrBinding> = attributes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != tag_attribute_idx)
+            .map(|(_, attr)| attr.to_owned())
+            .collect();
+
+        // `tag="ul"` is known at compile time, `:tag="foo"` is pushed as `${foo}`
+        let push_tag_name = |buffer: &mut SsrBuffer| match attributes[tag_attribute_idx] {
+            AttributeOrBinding::RegularAttribute { ref value, .. } => buffer.push_str(value),
+            AttributeOrBinding::VBind(VBindDirective { ref value, .. }) => {
+                buffer.push_expr((**value).to_owned())
+            }
+            AttributeOrBinding::VOn(_) => unreachable!(),
+        };
+
+        buffer.push_str("<");
+        push_tag_name(buffer);
+        self.generate_ssr_attributes(&wrapper_attributes, buffer, is_root);
+        self.generate_ssr_scope_id(buffer);
+        buffer.push_str(">");
+
+        self.generate_ssr_children(&element_node.children, buffer, false);
+
+        buffer.push_str("</");
+        push_tag_name(buffer);
+        buffer.push_str(">");
+    }
+}
+
+/// `name: () => { /* content */ }`
+fn slot_prop(slot_name: StrOrExpr, slot_fn: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: str_or_expr_to_propname(slot_name, DUMMY_SP),
+        value: Box::new(slot_fn),
+    })))
+}
+
+fn arrow(params: Vec<Pat>, stmts: Vec<Stmt>) -> Expr {
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params,
+        body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        })),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+fn expr_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
@@ -0,0 +1,266 @@
+use fervid_core::{ConditionalNodeSequence, ElementKind, ElementNode, SsrImports, VForDirective};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{ArrowExpr, BlockStmt, BlockStmtOrExpr, Expr, ExprStmt, IfStmt, Stmt},
+};
+
+use crate::{context::CodegenContext, directives::generate_v_for_params};
+
+use super::{call, SsrBuffer};
+
+impl CodegenContext {
+    /// Generates the `v-if`/`v-else-if`/`v-else` chain as `if` statements:
+    ///
+    /// ```js
+    /// if (_ctx.foo) {
+    ///   _push(`<div></div>`)
+    /// } else {
+    ///   _push(`<!---->`)
+    /// }
+    /// ```
+    ///
+    /// When there is no `v-else`, a comment is rendered as a placeholder, same as the VDOM path
+    pub(crate) fn generate_ssr_conditional_seq(
+        &mut self,
+        conditional_seq: &ConditionalNodeSequence,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let mut branches = Vec::with_capacity(conditional_seq.else_if_nodes.len() + 1);
+
+        let if_conditional = &conditional_seq.if_node;
+        branches.push((
+            if_conditional.condition.to_owned(),
+            self.generate_ssr_branch(&if_conditional.node, is_root),
+        ));
+
+        for else_if_conditional in conditional_seq.else_if_nodes.iter() {
+            branches.push((
+                else_if_conditional.condition.to_owned(),
+                self.generate_ssr_branch(&else_if_conditional.node, is_root),
+            ));
+        }
+
+        let else_block = match conditional_seq.else_node {
+            Some(ref else_node) => self.generate_ssr_branch(else_node, is_root),
+            None => {
+                let mut else_buffer = SsrBuffer::default();
+                else_buffer.push_str("<!---->");
+                block(else_buffer.into_stmts())
+            }
+        };
+
+        // Fold the branches from the back, the same way as ternaries are folded in the VDOM path
+        let mut alt = Stmt::Block(else_block);
+        while let Some((condition, cons)) = branches.pop() {
+            alt = Stmt::If(IfStmt {
+                span: DUMMY_SP,
+                test: Box::new(condition),
+                cons: Box::new(Stmt::Block(cons)),
+                alt: Some(Box::new(alt)),
+            });
+        }
+
+        buffer.push_stmt(alt);
+    }
+
+    /// Generates `_ssrRenderList(list, (item) => { /* item */ })` wrapped in the Fragment markers:
+    ///
+    /// ```js
+    /// _push(`<!--[-->`)
+    /// _ssrRenderList(_ctx.list, (item) => {
+    ///   _push(`<div>${_ssrInterpolate(item)}</div>`)
+    /// })
+    /// _push(`<!--]-->`)
+    /// ```
+    pub(crate) fn generate_ssr_v_for(
+        &mut self,
+        element_node: &ElementNode,
+        v_for: &VForDirective,
+        buffer: &mut SsrBuffer,
+    ) {
+        // Each item is rendered into its own buffer, which becomes the body of the arrow
+        let mut item_buffer = SsrBuffer::default();
+        self.generate_ssr_template_or_element(element_node, &mut item_buffer, false);
+
+        let render_list_arrow = Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: generate_v_for_params(&v_for.itervar),
+            body: Box::new(BlockStmtOrExpr::BlockStmt(block(item_buffer.into_stmts()))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        let ssr_render_list = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderList);
+
+        buffer.push_str("<!--[-->");
+        buffer.push_stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(call(
+                ssr_render_list,
+                vec![(*v_for.iterable).to_owned(), render_list_arrow],
+            )),
+        }));
+        buffer.push_str("<!--]-->");
+    }
+
+    /// Generates a branch of the conditional sequence into a separate block
+    fn generate_ssr_branch(&mut self, element_node: &ElementNode, is_root: bool) -> BlockStmt {
+        let mut branch_buffer = SsrBuffer::default();
+
+        match element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_for.as_ref())
+        {
+            Some(v_for) => self.generate_ssr_v_for(element_node, v_for, &mut branch_buffer),
+            None => {
+                self.generate_ssr_template_or_element(element_node, &mut branch_buffer, is_root)
+            }
+        }
+
+        block(branch_buffer.into_stmts())
+    }
+
+    /// `<template>` with `v-if` or `v-for` only renders its children.
+    /// Multiple children are a Fragment marked by `<!--[-->` and `<!--]-->`
+    pub(crate) fn generate_ssr_template_or_element(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let is_template = matches!(element_node.kind, ElementKind::Element)
+            && element_node.starting_tag.tag_name == "template";
+
+        if !is_template {
+            self.generate_ssr_element_kind(element_node, buffer, is_root);
+            return;
+        }
+
+        if element_node.children.len() == 1 {
+            self.generate_ssr_children(&element_node.children, buffer, is_root);
+            return;
+        }
+
+        buffer.push_str("<!--[-->");
+        self.generate_ssr_children(&element_node.children, buffer, false);
+        buffer.push_str("<!--]-->");
+    }
+}
+
+fn block(stmts: Vec<Stmt>) -> BlockStmt {
+    BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{Conditional, Interpolation, Node, StartingTag, VueDirectives};
+
+    use super::*;
+    use crate::test_utils::{js, to_str};
+
+    fn element(
+        tag_name: &str,
+        directives: Option<Box<VueDirectives>>,
+        children: Vec<Node>,
+    ) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn interpolation(value: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: js(value),
+            template_scope: 0,
+            patch_flag: false,
+        })
+    }
+
+    fn test_out(node: Node) -> String {
+        let mut ctx = CodegenContext::default();
+        let mut buffer = SsrBuffer::default();
+        ctx.generate_ssr_node(&node, &mut buffer, false);
+
+        buffer.into_stmts().into_iter().map(to_str).collect()
+    }
+
+    #[test]
+    fn it_generates_ssr_conditional_seq() {
+        // <div v-if="foo"></div><span v-else-if="bar"></span>
+        let conditional_seq = |else_node: Option<Box<ElementNode>>| {
+            Node::ConditionalSeq(ConditionalNodeSequence {
+                if_node: Box::new(Conditional {
+                    condition: *js("_ctx.foo"),
+                    node: element("div", None, vec![]),
+                }),
+                else_if_nodes: vec![Conditional {
+                    condition: *js("_ctx.bar"),
+                    node: element("span", None, vec![]),
+                }],
+                else_node,
+            })
+        };
+
+        assert_eq!(
+            test_out(conditional_seq(None)),
+            "if(_ctx.foo){_push(`<div></div>`)}else if(_ctx.bar){_push(`<span></span>`)}else{_push(`<!---->`)}"
+        );
+
+        // <template v-else>a{{ baz }}</template>
+        assert_eq!(
+            test_out(conditional_seq(Some(Box::new(element(
+                "template",
+                None,
+                vec![Node::Text("a".into(), DUMMY_SP), interpolation("_ctx.baz")]
+            ))))),
+            "if(_ctx.foo){_push(`<div></div>`)}else if(_ctx.bar){_push(`<span></span>`)}else{_push(`<!--[-->a${_ssrInterpolate(_ctx.baz)}<!--]-->`)}"
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_v_for() {
+        // <div v-for="({ id }, index) in list">{{ id }}</div>
+        let v_for = |tag_name: &str| {
+            Node::Element(element(
+                tag_name,
+                Some(Box::new(VueDirectives {
+                    v_for: Some(VForDirective {
+                        iterable: js("_ctx.list"),
+                        itervar: js("({ id }, index)"),
+                        patch_flags: Default::default(),
+                    }),
+                    ..Default::default()
+                })),
+                vec![interpolation("id")],
+            ))
+        };
+
+        assert_eq!(
+            test_out(v_for("div")),
+            "_push(`<!--[-->`);_ssrRenderList(_ctx.list,({id},index)=>{_push(`<div>${_ssrInterpolate(id)}</div>`)});_push(`<!--]-->`);"
+        );
+
+        // <template v-for="({ id }, index) in list">{{ id }}</template>
+        assert_eq!(
+            test_out(v_for("template")),
+            "_push(`<!--[-->`);_ssrRenderList(_ctx.list,({id},index)=>{_push(`${_ssrInterpolate(id)}`)});_push(`<!--]-->`);"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use fervid_core::{
+    fervid_atom, AttributeOrBinding, SsrImports, StartingTag, StrOrExpr, VBindDirective,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        CallExpr, Callee, CondExpr, Expr, ExprOrSpread, Ident, KeyValueProp, MemberExpr,
+        MemberProp, ObjectLit, Prop, PropName, PropOrSpread,
+    },
+};
+
+use crate::context::CodegenContext;
+
+use super::{array, call, ident, null, str_lit};
+
+impl CodegenContext {
+    /// Adds the attributes rendered by `v-show` and `v-model` to the attributes of an element:
+    ///
+    /// - `v-show="foo"` merges `foo ? null : { display: "none" }` into the `style`;
+    /// - `<input v-model="foo">` renders the `value`, or `checked` for the checkboxes and radios.
+    ///
+    /// Returns `_ssrRenderDynamicModel(type, foo, value)` for `<input :type="type" v-model="foo">`,
+    /// which should be pushed after the attributes.
+    /// `<textarea v-model>` renders its value as the content instead,
+    /// see [`CodegenContext::generate_ssr_textarea_v_model`].
+    pub(crate) fn generate_ssr_directive_attributes(
+        &mut self,
+        starting_tag: &StartingTag,
+        attributes: &mut Vec<AttributeOrBinding>,
+    ) -> Option<Expr> {
+        let directives = starting_tag.directives.as_ref()?;
+
+        if let Some(ref v_show) = directives.v_show {
+            let show_style = Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test: v_show.to_owned(),
+                cons: Box::new(null()),
+                alt: Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(Ident {
+                            span: DUMMY_SP,
+                            sym: fervid_atom!("display"),
+                            optional: false,
+                        }),
+                        value: Box::new(str_lit(fervid_atom!("none"))),
+                    })))],
+                })),
+            });
+
+            // `:style="bar"` becomes `:style="[bar, foo ? null : { display: "none" }]"`
+            let style_binding = attributes.iter_mut().find_map(|attr| match attr {
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "style" => Some(value),
+                _ => None,
+            });
+
+            match style_binding {
+                Some(value) => {
+                    let bound_style = std::mem::replace(&mut **value, null());
+                    **value = array(vec![bound_style, show_style]);
+                }
+                None => attributes.push(v_bind("style", show_style)),
+            }
+        }
+
+        if starting_tag.tag_name != "input" {
+            return None;
+        }
+
+        let v_model = directives.v_model.first()?;
+        let model = v_model.value.to_owned();
+
+        let mut static_type = None;
+        let mut dynamic_type = None;
+        let mut value_expr = None;
+        let mut true_value_expr = None;
+        for attr in attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => match name.as_ref() {
+                    "type" => static_type = Some(value.to_owned()),
+                    "value" => value_expr = Some(str_lit(value.to_owned())),
+                    "true-value" => true_value_expr = Some(str_lit(value.to_owned())),
+                    _ => {}
+                },
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) => match name.as_ref() {
+                    "type" => dynamic_type = Some((**value).to_owned()),
+                    "value" => value_expr = Some((**value).to_owned()),
+                    "true-value" => true_value_expr = Some((**value).to_owned()),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        let value_expr = value_expr.unwrap_or_else(null);
+
+        // `<input :type="type" v-model="foo">`
+        if let Some(type_expr) = dynamic_type {
+            let ssr_render_dynamic_model =
+                self.get_and_add_ssr_import_ident(SsrImports::SsrRenderDynamicModel);
+            return Some(call(
+                ssr_render_dynamic_model,
+                vec![type_expr, model, value_expr],
+            ));
+        }
+
+        let model_attribute = match static_type.as_deref() {
+            // `<input type="checkbox" v-model="foo">`
+            Some("checkbox") => v_bind(
+                "checked",
+                self.generate_ssr_checkbox_checked(model, value_expr, true_value_expr),
+            ),
+
+            // `<input type="radio" value="a" v-model="foo">`
+            Some("radio") => {
+                let ssr_loose_equal = self.get_and_add_ssr_import_ident(SsrImports::SsrLooseEqual);
+                v_bind("checked", call(ssr_loose_equal, vec![model, value_expr]))
+            }
+
+            // Text inputs
+            _ => v_bind("value", model),
+        };
+        attributes.push(model_attribute);
+
+        None
+    }
+
+    /// `<textarea v-model="foo">` renders `${_ssrInterpolate(foo)}` as its content
+    pub(crate) fn generate_ssr_textarea_v_model(
+        &mut self,
+        starting_tag: &StartingTag,
+    ) -> Option<Expr> {
+        if starting_tag.tag_name != "textarea" {
+            return None;
+        }
+
+        let v_model = starting_tag.directives.as_ref()?.v_model.first()?;
+        let ssr_interpolate = self.get_and_add_ssr_import_ident(SsrImports::SsrInterpolate);
+        Some(call(ssr_interpolate, vec![v_model.value.to_owned()]))
+    }
+
+    /// `_ssrLooseEqual(foo, trueValue)` when there is a `true-value`, otherwise
+    /// `Array.isArray(foo) ? _ssrLooseContain(foo, value) : foo`
+    fn generate_ssr_checkbox_checked(
+        &mut self,
+        model: Expr,
+        value_expr: Expr,
+        true_value_expr: Option<Expr>,
+    ) -> Expr {
+        if let Some(true_value_expr) = true_value_expr {
+            let ssr_loose_equal = self.get_and_add_ssr_import_ident(SsrImports::SsrLooseEqual);
+            return call(ssr_loose_equal, vec![model, true_value_expr]);
+        }
+
+        let is_array = Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(ident("Array")),
+                prop: MemberProp::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("isArray"),
+                    optional: false,
+                }),
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(model.to_owned()),
+            }],
+            type_args: None,
+        });
+
+        let ssr_loose_contain = self.get_and_add_ssr_import_ident(SsrImports::SsrLooseContain);
+        Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(is_array),
+            cons: Box::new(call(ssr_loose_contain, vec![model.to_owned(), value_expr])),
+            alt: Box::new(model),
+        })
+    }
+}
+
+/// A synthetic `:name="value"` binding
+fn v_bind(name: &str, value: Expr) -> AttributeOrBinding {
+    AttributeOrBinding::VBind(VBindDirective {
+        argument: Some(StrOrExpr::Str(name.into())),
+        value: Box::new(value),
+        is_camel: false,
+        is_prop: false,
+        is_attr: false,
+        span: Default::default(),
+    })
+}
@@ -0,0 +1,1056 @@
+//! Generates the `ssrRender` function, which renders the template to a string on the server.
+//!
+//! Plain elements are pushed as strings into the `_push` buffer,
+//! while components are rendered using the `vue/server-renderer` helpers:
+//!
+//! ```js
+//! function ssrRender(_ctx, _push, _parent, _attrs) {
+//!   _push(`<div${_ssrRenderAttrs(_attrs)}>`)
+//!   _push(_ssrRenderComponent(_component_foo, null, null, _parent))
+//!   _push(`</div>`)
+//! }
+//! ```
+
+use fervid_core::{
+    BuiltinType, ElementKind, ElementNode, FervidAtom, Node, SfcTemplateBlock, SsrImports,
+    TemplateGenerationMode,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl,
+        Expr, ExprOrSpread, Function, Ident, Lit, Module, Null, ObjectLit, Param, Pat, Stmt, Str,
+        VarDecl, VarDeclKind,
+    },
+};
+
+use crate::{
+    context::CodegenContext,
+    control_flow::{add_method, return_from_setup},
+};
+
+mod attributes;
+mod buffer;
+mod builtins;
+mod control_flow;
+mod directives;
+mod slots;
+
+use buffer::SsrBuffer;
+
+/// Elements without the closing tag, e.g. `<br>`
+const VOID_TAGS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+impl CodegenContext {
+    /// Generates the body of the `ssrRender` function
+    pub fn generate_ssr_template(&mut self, sfc_template: &SfcTemplateBlock) -> BlockStmt {
+        let mut buffer = SsrBuffer::default();
+
+        // Multiple roots are already merged into a `<template>` during the transform
+        let is_single_root = sfc_template.roots.len() == 1;
+        for root in sfc_template.roots.iter() {
+            self.generate_ssr_node(root, &mut buffer, is_single_root);
+        }
+
+        BlockStmt {
+            span: DUMMY_SP,
+            stmts: buffer.into_stmts(),
+        }
+    }
+
+    /// Generates a node into the buffer.
+    /// The root node also renders the fallthrough attributes (`_attrs`)
+    pub(crate) fn generate_ssr_node(&mut self, node: &Node, buffer: &mut SsrBuffer, is_root: bool) {
+        match node {
+            Node::Text(text, _) => buffer.push_str(&escape_html(text)),
+
+            Node::Comment(comment, _) => {
+                buffer.push_str("<!--");
+                buffer.push_str(comment);
+                buffer.push_str("-->");
+            }
+
+            Node::Element(element_node) => {
+                self.generate_ssr_element_or_component(element_node, buffer, is_root)
+            }
+
+            // `{{ foo }}` -> `${_ssrInterpolate(foo)}`
+            Node::Interpolation(interpolation) => {
+                let ssr_interpolate = self.get_and_add_ssr_import_ident(SsrImports::SsrInterpolate);
+                buffer.push_expr(call(
+                    ssr_interpolate,
+                    vec![(*interpolation.value).to_owned()],
+                ));
+            }
+
+            Node::ConditionalSeq(conditional_seq) => {
+                self.generate_ssr_conditional_seq(conditional_seq, buffer, is_root)
+            }
+        }
+    }
+
+    fn generate_ssr_element_or_component(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        if let Some(v_for) = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_for.as_ref())
+        {
+            self.generate_ssr_v_for(element_node, v_for, buffer);
+            return;
+        }
+
+        self.generate_ssr_element_kind(element_node, buffer, is_root);
+    }
+
+    /// Same as [`CodegenContext::generate_ssr_element_or_component`], but ignores `v-for`
+    fn generate_ssr_element_kind(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        match element_node.kind {
+            // Root `<template>` is a Fragment, which is marked by `<!--[-->` and `<!--]-->`
+            ElementKind::Element if is_root && element_node.starting_tag.tag_name == "template" => {
+                buffer.push_str("<!--[-->");
+                self.generate_ssr_children(&element_node.children, buffer, false);
+                buffer.push_str("<!--]-->");
+            }
+
+            ElementKind::Element => self.generate_ssr_element(element_node, buffer, is_root),
+
+            ElementKind::Component => self.generate_ssr_component(element_node, buffer, is_root),
+
+            // Transitions have no effect on the server, only the content is rendered
+            ElementKind::Builtin(BuiltinType::Transition | BuiltinType::KeepAlive) => {
+                self.generate_ssr_children(&element_node.children, buffer, is_root)
+            }
+
+            ElementKind::Builtin(BuiltinType::Slot) => {
+                self.generate_ssr_slot_outlet(element_node, buffer)
+            }
+
+            ElementKind::Builtin(BuiltinType::Component) => {
+                self.generate_ssr_dynamic_component(element_node, buffer, is_root)
+            }
+
+            ElementKind::Builtin(BuiltinType::Teleport) => {
+                self.generate_ssr_teleport(element_node, buffer)
+            }
+
+            ElementKind::Builtin(BuiltinType::Suspense) => {
+                self.generate_ssr_suspense(element_node, buffer)
+            }
+
+            ElementKind::Builtin(BuiltinType::TransitionGroup) => {
+                self.generate_ssr_transition_group(element_node, buffer, is_root)
+            }
+        }
+    }
+
+    /// Generates the children in order.
+    /// When `is_root` is set, the only child receives the fallthrough attributes
+    fn generate_ssr_children(&mut self, children: &[Node], buffer: &mut SsrBuffer, is_root: bool) {
+        let is_root = is_root && children.len() == 1;
+        for child in children.iter() {
+            self.generate_ssr_node(child, buffer, is_root);
+        }
+    }
+
+    /// Generates an element as a string, e.g. `<div class="foo">hi</div>`.
+    fn generate_ssr_element(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let starting_tag = &element_node.starting_tag;
+
+        buffer.push_str("<");
+        buffer.push_str(&starting_tag.tag_name);
+
+        // `v-show` and `v-model` are rendered as attributes
+        let directives = starting_tag.directives.as_ref();
+        let has_attribute_directives = directives.map_or(false, |directives| {
+            directives.v_show.is_some() || !directives.v_model.is_empty()
+        });
+        if has_attribute_directives {
+            let mut attributes = starting_tag.attributes.to_owned();
+            let dynamic_model =
+                self.generate_ssr_directive_attributes(starting_tag, &mut attributes);
+            self.generate_ssr_attributes(&attributes, buffer, is_root);
+            if let Some(dynamic_model) = dynamic_model {
+                buffer.push_expr(dynamic_model);
+            }
+        } else {
+            self.generate_ssr_attributes(&starting_tag.attributes, buffer, is_root);
+        }
+
+        self.generate_ssr_scope_id(buffer);
+        buffer.push_str(">");
+
+        if VOID_TAGS.contains(&&*starting_tag.tag_name) {
+            return;
+        }
+
+        // `v-html`, `v-text` and `<textarea v-model>` replace the children
+        if let Some(v_html) = directives.and_then(|directives| directives.v_html.as_ref()) {
+            buffer.push_expr((**v_html).to_owned());
+        } else if let Some(v_text) = directives.and_then(|directives| directives.v_text.as_ref()) {
+            let ssr_interpolate = self.get_and_add_ssr_import_ident(SsrImports::SsrInterpolate);
+            buffer.push_expr(call(ssr_interpolate, vec![(**v_text).to_owned()]));
+        } else if let Some(textarea_value) = self.generate_ssr_textarea_v_model(starting_tag) {
+            buffer.push_expr(textarea_value);
+        } else {
+            self.generate_ssr_children(&element_node.children, buffer, false);
+        }
+
+        buffer.push_str("</");
+        buffer.push_str(&starting_tag.tag_name);
+        buffer.push_str(">");
+    }
+
+    /// Adds the scope IDs after the attributes of an element
+    fn generate_ssr_scope_id(&mut self, buffer: &mut SsrBuffer) {
+        // Scoped styles: the scope ID is known at compile time, e.g. `<div data-v-abcd1234>`
+        if let Some(ref scope_id) = self.scope_id {
+            buffer.push_str(" ");
+            buffer.push_str(scope_id);
+        }
+
+        // Elements inside a slot function get the scope ID of the slot, e.g. ` data-v-abcd1234-s`
+        if self.ssr_in_slot {
+            buffer.push_expr(ident("_scopeId"));
+        }
+    }
+
+    /// Generates `_push(_ssrRenderComponent(_component_foo, { /* props */ }, null, _parent))`
+    fn generate_ssr_component(
+        &mut self,
+        component_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let span = component_node.span;
+
+        let component_identifier =
+            self.get_component_identifier(&component_node.starting_tag.tag_name, span);
+
+        let (props_expr, slots_expr) =
+            self.generate_ssr_component_props_and_slots(component_node, is_root);
+
+        let mut args = vec![
+            component_identifier,
+            props_expr,
+            slots_expr,
+            ident("_parent"),
+        ];
+
+        // Components inside a slot function inherit the `_scopeId` of the slot
+        if self.ssr_in_slot {
+            args.push(ident("_scopeId"));
+        }
+
+        let ssr_render_component =
+            self.get_and_add_ssr_import_ident(SsrImports::SsrRenderComponent);
+        buffer.push_call(call(ssr_render_component, args));
+    }
+
+    /// Generates the props and the slots of a component, or `null` when there are none.
+    /// The root component also gets the fallthrough attributes
+    fn generate_ssr_component_props_and_slots(
+        &mut self,
+        component_node: &ElementNode,
+        is_root: bool,
+    ) -> (Expr, Expr) {
+        let attributes_obj = self.generate_component_attributes(component_node);
+        let attributes_expr = if !attributes_obj.props.is_empty() {
+            Some(self.generate_props_expr(attributes_obj.props, true, attributes_obj.span))
+        } else {
+            None
+        };
+
+        let props_expr = if is_root {
+            self.merge_with_fallthrough_attrs(attributes_expr)
+        } else {
+            attributes_expr.unwrap_or_else(null)
+        };
+
+        let slots_expr = self
+            .generate_ssr_component_slots(component_node)
+            .unwrap_or_else(null);
+
+        (props_expr, slots_expr)
+    }
+
+    /// Wraps the SSR render body in an arrow expression
+    ///
+    /// `(_ctx, _push, _parent, _attrs) => { /*...*/ }`
+    pub fn generate_ssr_render_arrow(&mut self, mut body: BlockStmt) -> ArrowExpr {
+        self.prepend_ssr_resolves(&mut body);
+
+        let ctx = &*self.bindings_helper.ident_prefixes.ctx;
+        let params = [ctx, "_push", "_parent", "_attrs"]
+            .into_iter()
+            .map(param)
+            .collect();
+
+        ArrowExpr {
+            span: DUMMY_SP,
+            params,
+            body: Box::new(BlockStmtOrExpr::BlockStmt(body)),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        }
+    }
+
+    /// Wraps the SSR render body in a `Function`
+    ///
+    /// `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) { /*...*/ }`
+    pub fn generate_ssr_render_fn(&mut self, mut body: BlockStmt) -> Function {
+        self.prepend_ssr_resolves(&mut body);
+
+        let ctx = &*self.bindings_helper.ident_prefixes.ctx;
+        let params = [
+            ctx, "_push", "_parent", "_attrs", "$props", "$setup", "$data", "$options",
+        ]
+        .into_iter()
+        .map(|name| Param {
+            span: DUMMY_SP,
+            decorators: vec![],
+            pat: param(name),
+        })
+        .collect();
+
+        Function {
+            params,
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(body),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }
+    }
+
+    /// Same as [`CodegenContext::generate_module`], but the template is attached
+    /// as the `ssrRender` function instead of `render`
+    pub fn generate_ssr_module(
+        &mut self,
+        ssr_body: Option<BlockStmt>,
+        script: Module,
+        mut sfc_export_obj: ObjectLit,
+        mut synthetic_setup_fn: Option<Box<Function>>,
+    ) -> Module {
+        if let Some(ssr_body) = ssr_body {
+            match self.bindings_helper.template_generation_mode {
+                // `return (_ctx, _push, _parent, _attrs) => { /*...*/ }` at the end of `setup`
+                TemplateGenerationMode::Inline => {
+                    let ssr_render_arrow = self.generate_ssr_render_arrow(ssr_body);
+                    return_from_setup(&mut synthetic_setup_fn, Expr::Arrow(ssr_render_arrow));
+                }
+
+                // `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) {}`
+                TemplateGenerationMode::RenderFn => {
+                    let ssr_render_fn = self.generate_ssr_render_fn(ssr_body);
+                    self.return_bindings_from_setup(&mut synthetic_setup_fn);
+                    add_method(&mut sfc_export_obj, "ssrRender", ssr_render_fn);
+                }
+            }
+        }
+
+        self.finish_module(script, sfc_export_obj, synthetic_setup_fn)
+    }
+
+    /// Adds `const _component_foo = _resolveComponent("foo")` in front of the body.
+    /// Must be called after the body was generated, so that all the components are known
+    fn prepend_ssr_resolves(&mut self, body: &mut BlockStmt) {
+        let component_resolves = self.generate_component_resolves();
+        if component_resolves.is_empty() {
+            return;
+        }
+
+        body.stmts.insert(
+            0,
+            Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Const,
+                declare: false,
+                decls: component_resolves,
+            }))),
+        );
+    }
+}
+
+/// Escapes the text and attribute values, same as `escapeHtml` in `@vue/shared`
+pub fn escape_html(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("&quot;"),
+            '&' => result.push_str("&amp;"),
+            '\'' => result.push_str("&#39;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn ident(sym: &str) -> Expr {
+    Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(sym),
+        optional: false,
+    })
+}
+
+fn str_lit(value: FervidAtom) -> Expr {
+    Expr::Lit(Lit::Str(Str {
+        span: DUMMY_SP,
+        value,
+        raw: None,
+    }))
+}
+
+fn null() -> Expr {
+    Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))
+}
+
+fn param(name: &str) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from(name),
+            optional: false,
+        },
+        type_ann: None,
+    })
+}
+
+fn array(elems: Vec<Expr>) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: elems
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect(),
+    })
+}
+
+fn call(callee: FervidAtom, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: callee,
+            optional: false,
+        }))),
+        args: args
+            .into_iter()
+            .map(|expr| ExprOrSpread {
+                spread: None,
+                expr: Box::new(expr),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        AttributeOrBinding, DiagnosticKind, StartingTag, VBindDirective, VModelDirective,
+        VSlotDirective, VueDirectives,
+    };
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+    use crate::test_utils::{js, js_module, to_str};
+
+    fn element(
+        tag_name: &str,
+        kind: ElementKind,
+        attributes: Vec<AttributeOrBinding>,
+        children: Vec<Node>,
+    ) -> Node {
+        Node::Element(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn with_directives(mut node: Node, directives: VueDirectives) -> Node {
+        if let Node::Element(ref mut element_node) = node {
+            element_node.starting_tag.directives = Some(Box::new(directives));
+        }
+        node
+    }
+
+    fn attr(name: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: name.into(),
+            value: value.into(),
+            span: Default::default(),
+        }
+    }
+
+    fn v_bind(name: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(name.into()),
+            value: js(value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: Default::default(),
+        })
+    }
+
+    fn v_model(value: &str) -> VueDirectives {
+        VueDirectives {
+            v_model: vec![VModelDirective {
+                argument: None,
+                value: *js(value),
+                modifiers: vec![],
+                update_handler: None,
+                span: DUMMY_SP,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn text(text: &str) -> Node {
+        Node::Text(text.into(), DUMMY_SP)
+    }
+
+    /// Wraps the node in a `<div>`, so that it does not get the fallthrough attributes
+    fn in_div(node: Node) -> Vec<Node> {
+        vec![element("div", ElementKind::Element, vec![], vec![node])]
+    }
+
+    fn test_out(ctx: &mut CodegenContext, roots: Vec<Node>) -> Vec<String> {
+        let sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots,
+            span: DUMMY_SP,
+        };
+
+        ctx.generate_ssr_template(&sfc_template)
+            .stmts
+            .into_iter()
+            .map(to_str)
+            .collect()
+    }
+
+    #[test]
+    fn it_generates_ssr_elements() {
+        // <div class="foo">Hello <b title="a &quot;b&quot;">world</b><br disabled><!--c--></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "div",
+                ElementKind::Element,
+                vec![attr("class", "foo")],
+                vec![
+                    text("Hello "),
+                    element(
+                        "b",
+                        ElementKind::Element,
+                        vec![attr("title", "a \"b\""), attr("ref", "bar")],
+                        vec![text("<world>")],
+                    ),
+                    element(
+                        "br",
+                        ElementKind::Element,
+                        vec![attr("disabled", "")],
+                        vec![],
+                    ),
+                    Node::Comment("c".into(), DUMMY_SP),
+                ],
+            )],
+        );
+
+        assert_eq!(
+            out,
+            vec![
+                "_push(`<div${_ssrRenderAttrs(_mergeProps({class:\"foo\"},_attrs))}>Hello <b title=\"a &quot;b&quot;\">&lt;world&gt;</b><br disabled><!--c--></div>`);"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_scope_id() {
+        // <div class="foo"><span>hi</span></div> with scoped styles
+        let mut ctx = CodegenContext::default();
+        ctx.scope_id = Some("data-v-abcd1234".into());
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "div",
+                ElementKind::Element,
+                vec![attr("class", "foo")],
+                vec![element(
+                    "span",
+                    ElementKind::Element,
+                    vec![],
+                    vec![text("hi")],
+                )],
+            )],
+        );
+
+        assert_eq!(
+            out,
+            vec![
+                "_push(`<div${_ssrRenderAttrs(_mergeProps({class:\"foo\"},_attrs))} data-v-abcd1234><span data-v-abcd1234>hi</span></div>`);"
+            ]
+        );
+
+        // Slot content also gets the `_scopeId` of the slot
+        let mut ctx = CodegenContext::default();
+        ctx.scope_id = Some("data-v-abcd1234".into());
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "my-comp",
+                ElementKind::Component,
+                vec![],
+                vec![element("span", ElementKind::Element, vec![], vec![])],
+            )],
+        );
+
+        assert_eq!(
+            out,
+            vec![
+                "_push(_ssrRenderComponent(_component_my_comp,_attrs,{\"default\":_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`<span data-v-abcd1234${_scopeId}></span>`)}else{return[_createElementVNode(\"span\")]}}),_:1},_parent));"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_fragment() {
+        // <div></div><span>`${hi}`</span>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "template",
+                ElementKind::Element,
+                vec![],
+                vec![
+                    element("div", ElementKind::Element, vec![], vec![]),
+                    element("span", ElementKind::Element, vec![], vec![text("`${hi}`")]),
+                ],
+            )],
+        );
+
+        assert_eq!(
+            out,
+            vec!["_push(`<!--[--><div></div><span>\\`$\\{hi}\\`</span><!--]-->`);"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_components() {
+        // <div><my-comp foo="bar" /></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "div",
+                ElementKind::Element,
+                vec![],
+                vec![element(
+                    "my-comp",
+                    ElementKind::Component,
+                    vec![attr("foo", "bar")],
+                    vec![],
+                )],
+            )],
+        );
+
+        assert_eq!(
+            out,
+            vec![
+                "_push(`<div${_ssrRenderAttrs(_attrs)}>`);",
+                "_push(_ssrRenderComponent(_component_my_comp,{foo:\"bar\"},null,_parent));",
+                "_push(`</div>`);"
+            ]
+        );
+
+        // <my-comp />
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element("my-comp", ElementKind::Component, vec![], vec![])],
+        );
+
+        assert_eq!(
+            out,
+            vec!["_push(_ssrRenderComponent(_component_my_comp,_attrs,null,_parent));"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_module() {
+        // <my-comp />
+        let mut ctx = CodegenContext::default();
+        let sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![element("my-comp", ElementKind::Component, vec![], vec![])],
+            span: DUMMY_SP,
+        };
+
+        let ssr_body = ctx.generate_ssr_template(&sfc_template);
+        let module = ctx.generate_ssr_module(
+            Some(ssr_body),
+            js_module(""),
+            ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            None,
+        );
+
+        let code = CodegenContext::stringify("", &module, true);
+        assert!(code.contains(
+            r#"import{resolveComponent as _resolveComponent}from"vue";import{ssrRenderComponent as _ssrRenderComponent}from"vue/server-renderer";"#
+        ));
+        assert!(code.contains(
+            r#"ssrRender(_ctx,_push,_parent,_attrs,$props,$setup,$data,$options){const _component_my_comp=_resolveComponent("my-comp");_push(_ssrRenderComponent(_component_my_comp,_attrs,null,_parent))}"#
+        ));
+    }
+
+    #[test]
+    fn it_generates_ssr_dynamic_component() {
+        // <div><component :is="foo" bar="baz" /></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(element(
+                "component",
+                ElementKind::Builtin(BuiltinType::Component),
+                vec![v_bind("is", "foo"), attr("bar", "baz")],
+                vec![],
+            )),
+        );
+        assert_eq!(
+            out,
+            vec![
+                "_push(`<div${_ssrRenderAttrs(_attrs)}>`);",
+                "_ssrRenderVNode(_push,_createVNode(_resolveDynamicComponent(foo),{bar:\"baz\"},null),_parent);",
+                "_push(`</div>`);"
+            ]
+        );
+
+        // <component is="div" />
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "component",
+                ElementKind::Builtin(BuiltinType::Component),
+                vec![attr("is", "div")],
+                vec![],
+            )],
+        );
+        assert_eq!(
+            out,
+            vec!["_ssrRenderVNode(_push,_createVNode(_resolveDynamicComponent(\"div\"),_attrs,null),_parent);"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_teleport() {
+        // <teleport to="#modal" :disabled="off"><div>hi</div></teleport>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "teleport",
+                ElementKind::Builtin(BuiltinType::Teleport),
+                vec![attr("to", "#modal"), v_bind("disabled", "off")],
+                vec![element(
+                    "div",
+                    ElementKind::Element,
+                    vec![],
+                    vec![text("hi")],
+                )],
+            )],
+        );
+        assert_eq!(
+            out,
+            vec![
+                "_ssrRenderTeleport(_push,_push=>{_push(`<div>hi</div>`)},\"#modal\",off,_parent);"
+            ]
+        );
+        assert!(ctx.bindings_helper.diagnostics.is_empty());
+
+        // <teleport></teleport> cannot be rendered without the target
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "teleport",
+                ElementKind::Builtin(BuiltinType::Teleport),
+                vec![],
+                vec![],
+            )],
+        );
+        assert_eq!(
+            out,
+            vec!["_ssrRenderTeleport(_push,_push=>{},null,false,_parent);"]
+        );
+        assert!(matches!(
+            ctx.bindings_helper.diagnostics[0].kind,
+            DiagnosticKind::TeleportMissingTarget
+        ));
+    }
+
+    #[test]
+    fn it_generates_ssr_suspense() {
+        // <suspense><div>a</div><template #fallback>b</template></suspense>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            vec![element(
+                "suspense",
+                ElementKind::Builtin(BuiltinType::Suspense),
+                vec![],
+                vec![
+                    element("div", ElementKind::Element, vec![], vec![text("a")]),
+                    with_directives(
+                        element("template", ElementKind::Element, vec![], vec![text("b")]),
+                        VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some("fallback".into()),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        },
+                    ),
+                ],
+            )],
+        );
+        assert_eq!(
+            out,
+            vec!["_ssrRenderSuspense(_push,{\"default\":()=>{_push(`<div>a</div>`)},fallback:()=>{_push(`b`)},_:1});"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_transition_group() {
+        // <transition-group tag="ul" class="list"><li>a</li></transition-group>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(element(
+                "transition-group",
+                ElementKind::Builtin(BuiltinType::TransitionGroup),
+                vec![attr("tag", "ul"), attr("class", "list")],
+                vec![element("li", ElementKind::Element, vec![], vec![text("a")])],
+            )),
+        );
+        assert_eq!(
+            out,
+            vec![
+                "_push(`<div${_ssrRenderAttrs(_attrs)}><ul class=\"list\"><li>a</li></ul></div>`);"
+            ]
+        );
+
+        // <transition-group :tag="tag"><li>a</li></transition-group>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(element(
+                "transition-group",
+                ElementKind::Builtin(BuiltinType::TransitionGroup),
+                vec![v_bind("tag", "tag")],
+                vec![element("li", ElementKind::Element, vec![], vec![text("a")])],
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><${tag}><li>a</li></${tag}></div>`);"]
+        );
+
+        // <transition-group><li>a</li></transition-group>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(element(
+                "transition-group",
+                ElementKind::Builtin(BuiltinType::TransitionGroup),
+                vec![],
+                vec![element("li", ElementKind::Element, vec![], vec![text("a")])],
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><!--[--><li>a</li><!--]--></div>`);"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_v_show() {
+        // <div><span v-show="ok" style="color: red"></span></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element(
+                    "span",
+                    ElementKind::Element,
+                    vec![attr("style", "color: red")],
+                    vec![],
+                ),
+                VueDirectives {
+                    v_show: Some(js("ok")),
+                    ..Default::default()
+                },
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><span style=\"${_ssrRenderStyle([{color:\"red\"},ok?null:{display:\"none\"}])}\"></span></div>`);"]
+        );
+
+        // <div><span v-show="ok" :style="s"></span></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element(
+                    "span",
+                    ElementKind::Element,
+                    vec![v_bind("style", "s")],
+                    vec![],
+                ),
+                VueDirectives {
+                    v_show: Some(js("ok")),
+                    ..Default::default()
+                },
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><span style=\"${_ssrRenderStyle([s,ok?null:{display:\"none\"}])}\"></span></div>`);"]
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_v_model() {
+        // <div><input v-model="msg"></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element("input", ElementKind::Element, vec![], vec![]),
+                v_model("msg"),
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><input${_ssrRenderAttr(\"value\",msg)}></div>`);"]
+        );
+
+        // <div><input type="checkbox" v-model="checked"></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element(
+                    "input",
+                    ElementKind::Element,
+                    vec![attr("type", "checkbox")],
+                    vec![],
+                ),
+                v_model("checked"),
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><input type=\"checkbox\"${_ssrIncludeBooleanAttr(Array.isArray(checked)?_ssrLooseContain(checked,null):checked)?\" checked\":\"\"}></div>`);"]
+        );
+
+        // <div><input type="radio" value="a" v-model="picked"></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element(
+                    "input",
+                    ElementKind::Element,
+                    vec![attr("type", "radio"), attr("value", "a")],
+                    vec![],
+                ),
+                v_model("picked"),
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><input type=\"radio\" value=\"a\"${_ssrIncludeBooleanAttr(_ssrLooseEqual(picked,\"a\"))?\" checked\":\"\"}></div>`);"]
+        );
+
+        // <div><input :type="t" v-model="msg"></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element(
+                    "input",
+                    ElementKind::Element,
+                    vec![v_bind("type", "t")],
+                    vec![],
+                ),
+                v_model("msg"),
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><input${_ssrRenderAttr(\"type\",t)}${_ssrRenderDynamicModel(t,msg,null)}></div>`);"]
+        );
+
+        // <div><textarea v-model="msg"></textarea></div>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            in_div(with_directives(
+                element("textarea", ElementKind::Element, vec![], vec![]),
+                v_model("msg"),
+            )),
+        );
+        assert_eq!(
+            out,
+            vec!["_push(`<div${_ssrRenderAttrs(_attrs)}><textarea>${_ssrInterpolate(msg)}</textarea></div>`);"]
+        );
+    }
+}
@@ -0,0 +1,557 @@
+use fervid_core::{
+    check_attribute_name, fervid_atom, is_from_default_slot, AttributeOrBinding, ElementNode,
+    FervidAtom, Node, SsrImports, StrOrExpr, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, ArrowExpr, BinExpr, BinaryOp, BlockStmt, BlockStmtOrExpr, Expr, ExprOrSpread,
+        ExprStmt, Ident, IfStmt, KeyValueProp, MemberExpr, MemberProp, ObjectLit, Pat, Prop,
+        PropOrSpread, ReturnStmt, Stmt,
+    },
+};
+
+use crate::{
+    builtins::camelize_slot_prop, components::generate_slots_flag, context::CodegenContext,
+    utils::str_or_expr_to_propname,
+};
+
+use super::{call, ident, null, param, str_lit, SsrBuffer};
+
+impl CodegenContext {
+    /// Generates the slots object of a component in SSR mode:
+    ///
+    /// ```js
+    /// {
+    ///   default: _withCtx((_, _push, _parent, _scopeId) => {
+    ///     if (_push) {
+    ///       _push(`<span${_scopeId}>hi</span>`)
+    ///     } else {
+    ///       return [_createVNode("span", null, "hi")]
+    ///     }
+    ///   }),
+    ///   _: 1
+    /// }
+    /// ```
+    ///
+    /// The VDOM branch is used when the slot is rendered by a component with a VDOM `render`.
+    ///
+    /// Conditional and looped slots, e.g. `<template v-if="foo" #foo>`,
+    /// are passed to `_createSlots`, same as in the VDOM path.
+    pub(crate) fn generate_ssr_component_slots(
+        &mut self,
+        component_node: &ElementNode,
+    ) -> Option<Expr> {
+        let mut slots = Vec::new();
+        let mut dynamic_slots = Vec::new();
+        let mut conditional_branch_index = 0;
+        let mut default_slot_children = Vec::new();
+
+        for child in component_node.children.iter() {
+            match child {
+                // `<template v-slot:foo="{ bar }">`
+                Node::Element(
+                    slot_node @ ElementNode {
+                        starting_tag,
+                        children,
+                        ..
+                    },
+                ) if starting_tag.tag_name == "template" => {
+                    let directives = starting_tag.directives.as_deref();
+                    let v_slot = directives.and_then(|directives| directives.v_slot.as_ref());
+
+                    let Some(v_slot) = v_slot else {
+                        default_slot_children.push(child.to_owned());
+                        continue;
+                    };
+
+                    // `<template v-for="item in list" #[item.name]>`
+                    if let Some(v_for) = directives.and_then(|directives| directives.v_for.as_ref())
+                    {
+                        let looped_slot = self.generate_looped_slot(v_for, slot_node, true);
+                        dynamic_slots.push(Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(looped_slot),
+                        }));
+                        continue;
+                    }
+
+                    let slot_name = v_slot
+                        .slot_name
+                        .to_owned()
+                        .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+                    let slot_fn = self.generate_ssr_slot_fn(children, v_slot.value.as_deref());
+
+                    slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: str_or_expr_to_propname(slot_name, DUMMY_SP),
+                        value: Box::new(slot_fn),
+                    }))));
+                }
+
+                // `<template v-if="foo" #foo>`
+                Node::ConditionalSeq(conditional_seq) if !is_from_default_slot(child) => {
+                    let conditional_slot = self.generate_conditional_slot(
+                        conditional_seq,
+                        &mut conditional_branch_index,
+                        true,
+                    );
+                    dynamic_slots.push(Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(conditional_slot),
+                    }));
+                }
+
+                _ => default_slot_children.push(child.to_owned()),
+            }
+        }
+
+        // Comments do not make a default slot on their own, same as in the VDOM path
+        let has_default_slot_content = default_slot_children
+            .iter()
+            .any(|child| !matches!(child, Node::Comment(_, _)));
+        if has_default_slot_content {
+            let slot_fn = self.generate_ssr_slot_fn(&default_slot_children, None);
+            slots.insert(
+                0,
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: str_or_expr_to_propname(StrOrExpr::Str(fervid_atom!("default")), DUMMY_SP),
+                    value: Box::new(slot_fn),
+                }))),
+            );
+        }
+
+        if slots.is_empty() && dynamic_slots.is_empty() {
+            return None;
+        }
+
+        slots.push(generate_slots_flag(component_node, DUMMY_SP));
+
+        let static_slots = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: slots,
+        });
+
+        if dynamic_slots.is_empty() {
+            return Some(static_slots);
+        }
+
+        Some(self.generate_create_slots(static_slots, dynamic_slots, DUMMY_SP))
+    }
+
+    /// Generates `_withCtx((binding, _push, _parent, _scopeId) => { /* SSR or VDOM */ })`.
+    /// The elements of the SSR branch get the `_scopeId` of the slot
+    pub(crate) fn generate_ssr_slot_fn(
+        &mut self,
+        children: &[Node],
+        slot_binding: Option<&Pat>,
+    ) -> Expr {
+        // SSR branch
+        let mut ssr_buffer = SsrBuffer::default();
+        let was_in_slot = std::mem::replace(&mut self.ssr_in_slot, true);
+        for child in children.iter() {
+            self.generate_ssr_node(child, &mut ssr_buffer, false);
+        }
+        self.ssr_in_slot = was_in_slot;
+
+        // VDOM branch
+        let vdom_children = self
+            .generate_slot_children(children)
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect();
+
+        // `if (_push) { /* SSR */ } else { return [/* VDOM */] }`
+        let if_stmt = Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(ident("_push")),
+            cons: Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                stmts: ssr_buffer.into_stmts(),
+            })),
+            alt: Some(Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(Expr::Array(ArrayLit {
+                        span: DUMMY_SP,
+                        elems: vdom_children,
+                    }))),
+                })],
+            }))),
+        });
+
+        let binding = slot_binding.cloned().unwrap_or_else(|| param("_"));
+        let params = vec![binding, param("_push"), param("_parent"), param("_scopeId")];
+
+        let with_ctx = self.get_and_add_import_ident(VueImports::WithCtx);
+        call(
+            with_ctx,
+            vec![Expr::Arrow(ArrowExpr {
+                span: DUMMY_SP,
+                params,
+                body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![if_stmt],
+                })),
+                is_async: false,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+            })],
+        )
+    }
+
+    /// Generates the `<slot>` outlet in SSR mode:
+    ///
+    /// ```js
+    /// _ssrRenderSlot(_ctx.$slots, "default", {}, () => {
+    ///   _push(`fallback`)
+    /// }, _push, _parent)
+    /// ```
+    ///
+    /// When the scoped styles use `:slotted()`, the slotted scope ID is passed as the last argument,
+    /// e.g. `"data-v-abcd1234-s"`. Inside a slot function the `_scopeId` of the slot is added to it.
+    pub(crate) fn generate_ssr_slot_outlet(
+        &mut self,
+        element_node: &ElementNode,
+        buffer: &mut SsrBuffer,
+    ) {
+        let attributes = &element_node.starting_tag.attributes;
+        let idx_of_name = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "name"));
+
+        // Slot name: `name="foo"`, `:name="foo"` or "default"
+        let name_expr = match idx_of_name.map(|idx| &attributes[idx]) {
+            Some(AttributeOrBinding::RegularAttribute { value, .. }) => str_lit(value.to_owned()),
+            Some(AttributeOrBinding::VBind(v_bind)) => (*v_bind.value).to_owned(),
+            _ => str_lit(fervid_atom!("default")),
+        };
+
+        // Slot props are camelized, the same as in the VDOM path
+        let slot_props: Vec<AttributeOrBinding> = attributes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| Some(*idx) != idx_of_name)
+            .map(|(_, attr)| camelize_slot_prop(attr))
+            .collect();
+        let props_expr = if !slot_props.is_empty() {
+            let mut props = Vec::with_capacity(slot_props.len());
+            self.generate_attributes(&slot_props, &mut props);
+            self.generate_props_expr(props, false, DUMMY_SP)
+        } else {
+            Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            })
+        };
+
+        // Fallback content: `() => { _push(`fallback`) }` or `null`
+        let fallback_expr = if !element_node.children.is_empty() {
+            let mut fallback_buffer = SsrBuffer::default();
+            for child in element_node.children.iter() {
+                self.generate_ssr_node(child, &mut fallback_buffer, false);
+            }
+
+            Expr::Arrow(ArrowExpr {
+                span: DUMMY_SP,
+                params: vec![],
+                body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: fallback_buffer.into_stmts(),
+                })),
+                is_async: false,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+            })
+        } else {
+            null()
+        };
+
+        let mut args = vec![
+            Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(ident(&self.bindings_helper.ident_prefixes.ctx)),
+                prop: MemberProp::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("$slots"),
+                    optional: false,
+                }),
+            }),
+            name_expr,
+            props_expr,
+            fallback_expr,
+            ident("_push"),
+            ident("_parent"),
+        ];
+
+        // `"data-v-abcd1234-s"`, `"data-v-abcd1234-s" + _scopeId` or `_scopeId`
+        let slotted_scope_id = match self.scope_id {
+            Some(ref scope_id) if !self.no_slotted => {
+                Some(str_lit(FervidAtom::from(format!("{}-s", scope_id))))
+            }
+            _ => None,
+        };
+        let slot_scope_id = match (slotted_scope_id, self.ssr_in_slot) {
+            (Some(slotted), true) => Some(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::Add,
+                left: Box::new(slotted),
+                right: Box::new(ident("_scopeId")),
+            })),
+            (Some(slotted), false) => Some(slotted),
+            (None, true) => Some(ident("_scopeId")),
+            (None, false) => None,
+        };
+        args.extend(slot_scope_id);
+
+        let ssr_render_slot = self.get_and_add_ssr_import_ident(SsrImports::SsrRenderSlot);
+        buffer.push_stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(call(ssr_render_slot, args)),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{
+        BuiltinType, Conditional, ConditionalNodeSequence, ElementKind, StartingTag, VForDirective,
+        VSlotDirective, VueDirectives,
+    };
+
+    use super::*;
+    use crate::test_utils::{js, to_str};
+
+    fn element(
+        tag_name: &str,
+        kind: ElementKind,
+        attributes: Vec<AttributeOrBinding>,
+        directives: Option<Box<VueDirectives>>,
+        children: Vec<Node>,
+    ) -> Node {
+        Node::Element(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes,
+                directives,
+            },
+            children,
+            template_scope: 0,
+            kind,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn attr(name: &str, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: name.into(),
+            value: value.into(),
+            span: Default::default(),
+        }
+    }
+
+    fn text(text: &str) -> Node {
+        Node::Text(text.into(), DUMMY_SP)
+    }
+
+    fn test_out(ctx: &mut CodegenContext, node: Node) -> String {
+        let mut buffer = SsrBuffer::default();
+        ctx.generate_ssr_node(&node, &mut buffer, false);
+
+        buffer.into_stmts().into_iter().map(to_str).collect()
+    }
+
+    #[test]
+    fn it_generates_ssr_component_slots() {
+        // <my-comp>hi<template v-slot:foo><b>x</b></template></my-comp>
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            element(
+                "my-comp",
+                ElementKind::Component,
+                vec![],
+                None,
+                vec![
+                    text("hi"),
+                    element(
+                        "template",
+                        ElementKind::Element,
+                        vec![],
+                        Some(Box::new(VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some("foo".into()),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                        vec![element(
+                            "b",
+                            ElementKind::Element,
+                            vec![],
+                            None,
+                            vec![text("x")],
+                        )],
+                    ),
+                ],
+            ),
+        );
+
+        assert_eq!(
+            out,
+            r#"_push(_ssrRenderComponent(_component_my_comp,null,{"default":_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`hi`)}else{return[_createTextVNode("hi")]}}),foo:_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`<b${_scopeId}>x</b>`)}else{return[_createElementVNode("b",null,"x")]}}),_:1},_parent));"#
+        );
+
+        // Comments alone do not make a slot
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            element(
+                "my-comp",
+                ElementKind::Component,
+                vec![],
+                None,
+                vec![Node::Comment("c".into(), DUMMY_SP)],
+            ),
+        );
+        assert_eq!(
+            out,
+            "_push(_ssrRenderComponent(_component_my_comp,null,null,_parent));"
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_dynamic_slots() {
+        // <my-comp><template v-if="ok" #foo>x</template></my-comp>
+        let slot_template = |slot_name: StrOrExpr, v_for: Option<VForDirective>| ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "template".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_slot: Some(VSlotDirective {
+                        slot_name: Some(slot_name),
+                        value: None,
+                    }),
+                    v_for,
+                    ..Default::default()
+                })),
+            },
+            children: vec![text("x")],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            element(
+                "my-comp",
+                ElementKind::Component,
+                vec![],
+                None,
+                vec![Node::ConditionalSeq(ConditionalNodeSequence {
+                    if_node: Box::new(Conditional {
+                        condition: *js("ok"),
+                        node: slot_template("foo".into(), None),
+                    }),
+                    else_if_nodes: vec![],
+                    else_node: None,
+                })],
+            ),
+        );
+        assert_eq!(
+            out,
+            r#"_push(_ssrRenderComponent(_component_my_comp,null,_createSlots({_:1},[ok?{name:"foo",fn:_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`x`)}else{return[_createTextVNode("x")]}}),key:"0"}:undefined]),_parent));"#
+        );
+
+        // <my-comp><template v-for="i in list" #[i]>x</template></my-comp>
+        let mut ctx = CodegenContext::default();
+        let looped_slot = slot_template(
+            StrOrExpr::Expr(js("i")),
+            Some(VForDirective {
+                iterable: js("list"),
+                itervar: js("i"),
+                patch_flags: Default::default(),
+            }),
+        );
+        let out = test_out(
+            &mut ctx,
+            element(
+                "my-comp",
+                ElementKind::Component,
+                vec![],
+                None,
+                vec![Node::Element(looped_slot)],
+            ),
+        );
+        assert_eq!(
+            out,
+            r#"_push(_ssrRenderComponent(_component_my_comp,null,_createSlots({_:1},[_renderList(list,i=>{return{name:i,fn:_withCtx((_,_push,_parent,_scopeId)=>{if(_push){_push(`x`)}else{return[_createTextVNode("x")]}})}})]),_parent));"#
+        );
+    }
+
+    #[test]
+    fn it_generates_ssr_slot_outlet() {
+        // <slot />
+        let mut ctx = CodegenContext::default();
+        let out = test_out(
+            &mut ctx,
+            element(
+                "slot",
+                ElementKind::Builtin(BuiltinType::Slot),
+                vec![],
+                None,
+                vec![],
+            ),
+        );
+        assert_eq!(
+            out,
+            r#"_ssrRenderSlot(_ctx.$slots,"default",{},null,_push,_parent);"#
+        );
+
+        // <slot name="foo" foo-bar="baz">fallback</slot> with `:slotted()` scoped styles
+        let mut ctx = CodegenContext::default();
+        ctx.scope_id = Some("data-v-abcd1234".into());
+        let out = test_out(
+            &mut ctx,
+            element(
+                "slot",
+                ElementKind::Builtin(BuiltinType::Slot),
+                vec![attr("name", "foo"), attr("foo-bar", "baz")],
+                None,
+                vec![text("fallback")],
+            ),
+        );
+        assert_eq!(
+            out,
+            r#"_ssrRenderSlot(_ctx.$slots,"foo",{fooBar:"baz"},()=>{_push(`fallback`)},_push,_parent,"data-v-abcd1234-s");"#
+        );
+
+        // Scoped styles without `:slotted()`
+        ctx.no_slotted = true;
+        let out = test_out(
+            &mut ctx,
+            element(
+                "slot",
+                ElementKind::Builtin(BuiltinType::Slot),
+                vec![],
+                None,
+                vec![],
+            ),
+        );
+        assert_eq!(
+            out,
+            r#"_ssrRenderSlot(_ctx.$slots,"default",{},null,_push,_parent);"#
+        );
+    }
+}
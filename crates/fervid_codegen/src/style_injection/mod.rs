@@ -0,0 +1,128 @@
+//! Generates the runtime injection of the compiled styles,
+//! for the environments without a CSS pipeline, e.g. embeddable widgets
+//! or custom elements rendering into a Shadow DOM.
+//!
+//! The CSS of all the `<style>` blocks is embedded in the module
+//! and passed to the injector as soon as the module is evaluated:
+//!
+//! ```js
+//! import { useStyleTag as _useStyleTag } from "@vueuse/core";
+//! _useStyleTag(".foo[data-v-abcd1234]{color:red}");
+//! ```
+
+use fervid_core::FervidAtom;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        CallExpr, Callee, Expr, ExprOrSpread, ExprStmt, Ident, Lit, ModuleItem, Stmt, Str,
+    },
+};
+
+use crate::{context::CodegenContext, imports::generate_import_specifier};
+
+/// Function which injects the CSS at runtime, called with the CSS string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleInjector {
+    /// Name of the function, e.g. `useStyleTag` or `__injectStyles`
+    pub name: FervidAtom,
+    /// Module to import the function from, e.g. `@vueuse/core`.
+    /// When `None`, the function is a global provided by the environment
+    pub source: Option<FervidAtom>,
+}
+
+/// Options of the style injection, see [`CodegenContext::generate_style_injection`]
+#[derive(Debug, Clone)]
+pub struct StyleInjectionOptions {
+    /// The compiled CSS of all the `<style>` blocks in order of appearance
+    pub css: FervidAtom,
+    pub injector: StyleInjector,
+}
+
+impl CodegenContext {
+    /// Generates the import of the injector (when it is not a global)
+    /// and the call injecting the styles, e.g. `_useStyleTag(".foo{color:red}")`
+    pub fn generate_style_injection(&self, options: &StyleInjectionOptions) -> Vec<ModuleItem> {
+        let mut items = Vec::with_capacity(2);
+
+        let injector = &options.injector;
+        let callee = match injector.source {
+            // `import { useStyleTag as _useStyleTag } from "@vueuse/core"`
+            Some(ref source) => {
+                let local_name = format!("_{}", injector.name);
+                items.push(
+                    self.generate_import_item(vec![generate_import_specifier(&local_name)], source),
+                );
+                FervidAtom::from(local_name)
+            }
+            None => injector.name.to_owned(),
+        };
+
+        items.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: callee,
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: options.css.to_owned(),
+                        raw: None,
+                    }))),
+                }],
+                type_args: None,
+            })),
+        })));
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{imports::ModuleFormat, test_utils::to_str};
+
+    fn test_out(ctx: &CodegenContext, name: &str, source: Option<&str>) -> String {
+        let options = StyleInjectionOptions {
+            css: FervidAtom::from(".foo[data-v-abcd1234]{color:red}"),
+            injector: StyleInjector {
+                name: FervidAtom::from(name),
+                source: source.map(FervidAtom::from),
+            },
+        };
+
+        ctx.generate_style_injection(&options)
+            .into_iter()
+            .map(to_str)
+            .collect()
+    }
+
+    #[test]
+    fn it_generates_imported_injector() {
+        let mut ctx = CodegenContext::default();
+        assert_eq!(
+            test_out(&ctx, "useStyleTag", Some("@vueuse/core")),
+            r#"import{useStyleTag as _useStyleTag}from"@vueuse/core";_useStyleTag(".foo[data-v-abcd1234]{color:red}");"#
+        );
+
+        ctx.module_format = ModuleFormat::Cjs;
+        assert_eq!(
+            test_out(&ctx, "useStyleTag", Some("@vueuse/core")),
+            r#"const{useStyleTag:_useStyleTag}=require("@vueuse/core");_useStyleTag(".foo[data-v-abcd1234]{color:red}");"#
+        );
+    }
+
+    #[test]
+    fn it_generates_global_injector() {
+        let ctx = CodegenContext::default();
+        assert_eq!(
+            test_out(&ctx, "__injectStyles", None),
+            r#"__injectStyles(".foo[data-v-abcd1234]{color:red}");"#
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use swc_core::{ecma::ast::Expr, common::BytePos};
+use swc_core::{ecma::ast::{Expr, Module}, common::BytePos};
 use swc_ecma_parser::{lexer::Lexer, Syntax, StringInput, Parser, PResult};
 
 /// Parses js as a temporary measure
@@ -16,3 +16,17 @@ pub fn parse_js(expr: &str) -> PResult<Box<Expr>> {
 
     parser.parse_expr()
 }
+
+/// Parses a js module as a temporary measure
+pub fn parse_js_module(module: &str) -> PResult<Module> {
+    let lexer = Lexer::new(
+        Syntax::Es(Default::default()),
+        Default::default(),
+        StringInput::new(module, BytePos(0), BytePos(0)),
+        None,
+    );
+
+    let mut parser = Parser::new_from(lexer);
+
+    parser.parse_module()
+}
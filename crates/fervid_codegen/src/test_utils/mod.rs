@@ -1,9 +1,21 @@
-use swc_core::{common::SourceMap, ecma::ast::Expr};
+use swc_core::{
+    common::{comments::Comments, SourceMap},
+    ecma::ast::{Expr, Module},
+};
 use swc_ecma_codegen::{Node, text_writer::JsWriter, Emitter};
 
 mod js_polyfill;
 
 pub fn to_str(swc_node: impl Node) -> String {
+    emit(swc_node, None)
+}
+
+/// Same as [`to_str`], but prints the comments, e.g. the `/*#__PURE__*/` annotations
+pub fn to_str_with_comments(swc_node: impl Node, comments: &dyn Comments) -> String {
+    emit(swc_node, Some(comments))
+}
+
+fn emit(swc_node: impl Node, comments: Option<&dyn Comments>) -> String {
     // Emitting the result requires some setup with SWC
     let cm: swc_core::common::sync::Lrc<SourceMap> = Default::default();
     let mut buff: Vec<u8> = Vec::with_capacity(128);
@@ -14,7 +26,7 @@ pub fn to_str(swc_node: impl Node) -> String {
 
     let mut emitter = Emitter {
         cfg: emitter_cfg,
-        comments: None,
+        comments,
         wr: writer,
         cm,
     };
@@ -27,3 +39,7 @@ pub fn to_str(swc_node: impl Node) -> String {
 pub fn js(raw: &str) -> Box<Expr> {
     js_polyfill::parse_js(raw).unwrap()
 }
+
+pub fn js_module(raw: &str) -> Module {
+    js_polyfill::parse_js_module(raw).unwrap()
+}
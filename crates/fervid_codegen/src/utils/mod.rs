@@ -3,7 +3,10 @@
 use std::fmt::{Write, Error};
 
 use fervid_core::{FervidAtom, StrOrExpr};
-use swc_core::{ecma::ast::{Ident, IdentExt, PropName, Str, ComputedPropName}, common::Span};
+use swc_core::{
+    ecma::ast::{ComputedPropName, Expr, Ident, IdentExt, Lit, MemberExpr, MemberProp, Number, PropName, Str},
+    common::Span,
+};
 
 /// Adapted from SWC Ident::verify_symbol
 #[inline]
@@ -57,6 +60,26 @@ pub fn str_or_expr_to_propname(str_or_expr: StrOrExpr, span: Span) -> PropName {
     }
 }
 
+/// Generates `_cache[cache_index]`, where `cache` is the name of the render cache
+pub fn cache_access(cache: &FervidAtom, cache_index: u32, span: Span) -> Expr {
+    Expr::Member(MemberExpr {
+        span,
+        obj: Box::new(Expr::Ident(Ident {
+            span,
+            sym: cache.to_owned(),
+            optional: false,
+        })),
+        prop: MemberProp::Computed(ComputedPropName {
+            span,
+            expr: Box::new(Expr::Lit(Lit::Num(Number {
+                span,
+                value: cache_index.into(),
+                raw: None,
+            }))),
+        }),
+    })
+}
+
 pub fn to_camelcase(s: &str, buf: &mut impl Write) -> Result<(), Error> {
     for (idx, word) in s.split('-').enumerate() {
         if idx == 0 {
@@ -95,3 +118,23 @@ pub fn to_pascalcase(s: &str, buf: &mut impl Write) -> Result<(), Error> {
 
     Ok(())
 }
+
+/// Converts the name of a runtime-resolved asset to a valid identifier with a prefix,
+/// e.g. `_component_my_comp` for `my-comp`, same as `toValidAssetId` in the official compiler.
+/// The asset itself is still resolved by its verbatim name
+pub fn to_valid_asset_id(name: &str, prefix: &str) -> FervidAtom {
+    let mut result = String::with_capacity(prefix.len() + name.len());
+    result.push_str(prefix);
+
+    for ch in name.chars() {
+        match ch {
+            '-' => result.push('_'),
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '$' => result.push(ch),
+            _ => {
+                let _ = write!(result, "{}", ch as u32); // ignore fault
+            }
+        }
+    }
+
+    FervidAtom::from(result)
+}
@@ -2,7 +2,10 @@ use fxhash::FxHashMap as HashMap;
 use smallvec::SmallVec;
 use swc_core::ecma::ast::{Expr, Id, Ident};
 
-use crate::{BuiltinType, FervidAtom, BindingTypes, TemplateGenerationMode, VueImportsSet};
+use crate::{
+    BindingTypes, BuiltinType, CancellationToken, Diagnostic, FervidAtom, SsrImportsSet,
+    TemplateGenerationMode, VueImportsSet,
+};
 
 #[derive(Debug, Default)]
 pub struct BindingsHelper {
@@ -10,12 +13,31 @@ pub struct BindingsHelper {
     pub components: HashMap<FervidAtom, ComponentBinding>,
     /// All custom directives present in the `<template>`
     pub custom_directives: HashMap<FervidAtom, CustomDirectiveBinding>,
+    /// Warnings and errors found during the transformation and code generation
+    pub diagnostics: Vec<Diagnostic>,
     /// Are we compiling for DEV or PROD
     pub is_prod: bool,
+    /// Whether the `<template>` comments are kept and rendered as comment vnodes
+    pub comments: bool,
+    /// Whether the script is TypeScript, then the component is wrapped in `defineComponent`
+    pub is_ts: bool,
+    /// PascalCase name of the component being compiled, inferred from the filename.
+    /// A component using its own tag is resolved as a self-reference
+    pub self_name: Option<FervidAtom>,
+    /// Tag names of the native custom elements, e.g. `<my-button>` from a web components library.
+    /// A name ending with `*` matches by prefix, e.g. `ion-*`.
+    /// Such tags are compiled as plain elements instead of being resolved as components
+    pub custom_elements: Vec<FervidAtom>,
     /// Scopes of the `<template>` for in-template variable resolutions
     pub template_scopes: Vec<TemplateScope>,
     /// Bindings in `<script setup>`
     pub setup_bindings: Vec<SetupBinding>,
+    /// Original keys of the destructured props aliases in `<script setup>`,
+    /// e.g. `bar` -> `foo` for `const { foo: bar } = defineProps()`
+    pub props_aliases: HashMap<FervidAtom, FervidAtom>,
+    /// `<script setup>` bindings referenced by `defineExpose`,
+    /// e.g. `focus` and `count` for `defineExpose({ focus, total: count })`
+    pub exposed_bindings: Vec<FervidAtom>,
     /// Bindings in `<script>`
     pub options_api_bindings: Option<Box<OptionsApiBindings>>,
     /// The mode with which `<template>` variables are resolved.
@@ -26,7 +48,54 @@ pub struct BindingsHelper {
     /// Identifiers used in the template and their respective binding types
     pub used_bindings: HashMap<FervidAtom, BindingTypes>,
     /// Internal Vue imports used by built-in components, directives and others
-    pub vue_imports: VueImportsSet
+    pub vue_imports: VueImportsSet,
+    /// Helpers from `vue/server-renderer` used by the SSR render function
+    pub ssr_imports: SsrImportsSet,
+    /// Checked during the transformation to abort a stale compilation
+    pub cancellation_token: CancellationToken,
+    /// Names and prefixes of the generated identifiers, e.g. `_ctx` or `_hoisted_`
+    pub ident_prefixes: IdentPrefixes,
+}
+
+impl BindingsHelper {
+    /// Whether a tag matches one of the [`BindingsHelper::custom_elements`]
+    pub fn is_custom_element(&self, tag_name: &str) -> bool {
+        self.custom_elements
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => tag_name.starts_with(prefix),
+                None => tag_name == pattern,
+            })
+    }
+}
+
+/// Names and prefixes of the identifiers generated by the compiler.
+/// The defaults are the same as in the official compiler,
+/// they can be changed to avoid collisions with the user code
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdentPrefixes {
+    /// Render context, e.g. `_ctx.foo`
+    pub ctx: FervidAtom,
+    /// Render cache, e.g. `_cache[0]`
+    pub cache: FervidAtom,
+    /// Prefix of the runtime-resolved components, e.g. `_component_my_comp`
+    pub component: FervidAtom,
+    /// Prefix of the runtime-resolved directives, e.g. `_directive_focus`
+    pub directive: FervidAtom,
+    /// Prefix of the hoisted values, e.g. `_hoisted_1`
+    pub hoisted: FervidAtom,
+}
+
+impl Default for IdentPrefixes {
+    fn default() -> Self {
+        Self {
+            ctx: FervidAtom::from("_ctx"),
+            cache: FervidAtom::from("_cache"),
+            component: FervidAtom::from("_component_"),
+            directive: FervidAtom::from("_directive_"),
+            hoisted: FervidAtom::from("_hoisted_"),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -0,0 +1,87 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A token for aborting a stale compilation, e.g. when an editor
+/// has already sent a newer version of the file.
+///
+/// The token is cheap to clone and all the clones share the same state,
+/// so one clone can be given to the compiler and another one kept for [`CancellationToken::cancel`].
+/// A deadline can also be set, after which the token is considered cancelled.
+///
+/// ## Example
+/// ```
+/// use fervid_core::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let compiler_token = token.clone();
+/// assert!(!compiler_token.is_cancelled());
+///
+/// token.cancel();
+/// assert!(compiler_token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token which gets cancelled at `deadline`
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            cancelled: Default::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Creates a token which gets cancelled after `timeout` from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Cancels this token and all of its clones
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Was the token cancelled or is its deadline reached
+    pub fn is_cancelled(&self) -> bool {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        matches!(self.deadline, Some(deadline) if Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_cancels_clones() {
+        let token = CancellationToken::new();
+        let cloned = token.clone();
+        assert!(!token.is_cancelled());
+        assert!(!cloned.is_cancelled());
+
+        cloned.cancel();
+        assert!(token.is_cancelled());
+        assert!(cloned.is_cancelled());
+    }
+
+    #[test]
+    fn it_respects_deadline() {
+        assert!(CancellationToken::with_deadline(Instant::now()).is_cancelled());
+        assert!(!CancellationToken::with_timeout(Duration::from_secs(3600)).is_cancelled());
+    }
+}
@@ -0,0 +1,92 @@
+/// A `v-bind()` usage in the `<style>` content, found by [`find_css_vars`].
+/// All the positions are byte offsets in the scanned content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CssVarMatch {
+    /// Start of `v-bind`
+    pub start: usize,
+    /// Right after the closing `)`
+    pub end: usize,
+    /// Start of the bound expression, without the whitespace and quotes
+    pub raw_start: usize,
+    /// End of the bound expression, without the whitespace and quotes
+    pub raw_end: usize,
+}
+
+/// Finds all the `v-bind()` usages in the `<style>` content.
+/// Empty usages such as `v-bind()` and the unclosed ones are skipped.
+///
+/// ## Example
+/// In `.foo { color: v-bind(color); width: v-bind( 'size.width' ) }`
+/// the raw expressions are `color` and `size.width`.
+pub fn find_css_vars(content: &str) -> Vec<CssVarMatch> {
+    const V_BIND: &str = "v-bind";
+
+    let mut css_vars = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(idx) = content[search_from..].find(V_BIND) {
+        let start = search_from + idx;
+        let after_name = start + V_BIND.len();
+        search_from = after_name;
+
+        // Whitespace is allowed between `v-bind` and `(`
+        let rest = &content[after_name..];
+        let rest_trimmed = rest.trim_start();
+        if !rest_trimmed.starts_with('(') {
+            continue;
+        }
+        let args_start = after_name + (rest.len() - rest_trimmed.len()) + '('.len_utf8();
+
+        // Unclosed `v-bind(` means there is nothing more to search for
+        let Some(args_len) = find_closing_paren(&content[args_start..]) else {
+            break;
+        };
+        let args_end = args_start + args_len;
+        search_from = args_end;
+
+        // Strip whitespace and quotes, e.g. `v-bind( 'foo.bar' )`
+        let raw_args = &content[args_start..args_end];
+        let mut raw_start = args_start + (raw_args.len() - raw_args.trim_start().len());
+        let mut raw = raw_args.trim();
+        let is_quoted = raw.len() >= 2
+            && ((raw.starts_with('\'') && raw.ends_with('\''))
+                || (raw.starts_with('"') && raw.ends_with('"')));
+        if is_quoted {
+            raw = &raw[1..raw.len() - 1];
+            raw_start += 1;
+        }
+
+        if raw.is_empty() {
+            continue;
+        }
+
+        css_vars.push(CssVarMatch {
+            start,
+            end: args_end + ')'.len_utf8(),
+            raw_start,
+            raw_end: raw_start + raw.len(),
+        });
+    }
+
+    css_vars
+}
+
+/// Finds the `)` closing an already opened `(`, ignoring the parens inside quotes
+fn find_closing_paren(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+
+    for (idx, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => return Some(idx),
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
@@ -0,0 +1,141 @@
+use swc_core::common::Span;
+
+use crate::FervidAtom;
+
+/// A warning or an error found during the SFC compilation.
+/// Unlike parse errors, diagnostics do not stop the compilation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticSeverity {
+    /// A hint in DEV which does not indicate a problem by itself
+    Note,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// Directives cannot be applied to `<Teleport>`, because it does not render an element
+    DirectiveOnTeleport,
+    /// `<Teleport>` without the `to` prop, which is required to render it on the server
+    TeleportMissingTarget,
+    /// `v-if` and `v-for` on the same element. `v-if` has a higher priority in Vue 3
+    /// and cannot access the `v-for` variables
+    VIfWithVFor,
+    /// `<Transition>` with multiple children or with a `v-for` child
+    TransitionInvalidChildren,
+    /// `<Transition mode="...">` with a mode other than `in-out`, `out-in` or `default`
+    TransitionInvalidMode(FervidAtom),
+    /// `v-html` on an element with children, which are overridden by the `innerHTML`
+    VHtmlWithChildren,
+    /// `v-slot` on an element which is neither a component nor a `<template>` inside a component
+    VSlotMisplaced,
+    /// `v-slot` on both the component and its nested `<template>`
+    VSlotMixedUsage,
+    /// Two slots with the same name, including the implicit default slot
+    /// alongside the `<template v-slot:default>`
+    VSlotDuplicateName(FervidAtom),
+    /// `v-text` on an element with children, which are overridden by the `textContent`
+    VTextWithChildren,
+    /// `>>>`, `/deep/` or `::v-deep` in a `<style scoped>`. They are still compiled.
+    /// Contains the replacement for the diagnostic span, e.g. `:deep(.bar)` for `>>> .bar`.
+    /// In a preprocessed style, the span is the whole block
+    DeprecatedDeepSelector(FervidAtom),
+    /// A `<style scoped>` selector with a class which is not used in the template.
+    /// The scope attribute is also added to the root elements of the child components,
+    /// so the selector can only match them. Contains the class name
+    ScopedSelectorChildRoot(FervidAtom),
+    /// `<style module>` in a language other than CSS without a style preprocessor.
+    /// Its class names are not hashed and `$style` is left for the runtime. Contains the `lang`
+    StyleModuleNotCompiled(FervidAtom),
+}
+
+impl DiagnosticKind {
+    pub fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            DiagnosticKind::ScopedSelectorChildRoot(_) => DiagnosticSeverity::Note,
+            DiagnosticKind::DirectiveOnTeleport
+            | DiagnosticKind::DeprecatedDeepSelector(_)
+            | DiagnosticKind::StyleModuleNotCompiled(_) => DiagnosticSeverity::Warning,
+            DiagnosticKind::VIfWithVFor
+            | DiagnosticKind::TransitionInvalidChildren
+            | DiagnosticKind::TransitionInvalidMode(_) => DiagnosticSeverity::Warning,
+            DiagnosticKind::VHtmlWithChildren
+            | DiagnosticKind::VSlotMisplaced
+            | DiagnosticKind::VSlotMixedUsage
+            | DiagnosticKind::VSlotDuplicateName(_)
+            | DiagnosticKind::VTextWithChildren
+            | DiagnosticKind::TeleportMissingTarget => DiagnosticSeverity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticKind::DirectiveOnTeleport => write!(
+                f,
+                "Directives on <Teleport> are ignored, because it does not render an element"
+            ),
+            DiagnosticKind::TeleportMissingTarget => {
+                write!(f, "<Teleport> is missing the `to` prop, which is required in SSR")
+            }
+            DiagnosticKind::VIfWithVFor => write!(
+                f,
+                "v-if and v-for on the same element: v-if is evaluated first and cannot access the v-for variables"
+            ),
+            DiagnosticKind::TransitionInvalidChildren => write!(
+                f,
+                "<Transition> expects exactly one child element or component"
+            ),
+            DiagnosticKind::TransitionInvalidMode(mode) => write!(
+                f,
+                "Invalid <Transition> mode `{}`, expected `in-out`, `out-in` or `default`",
+                mode
+            ),
+            DiagnosticKind::VHtmlWithChildren => {
+                write!(f, "v-html will override element children")
+            }
+            DiagnosticKind::VSlotMisplaced => write!(
+                f,
+                "v-slot can only be used on components or <template> tags inside components"
+            ),
+            DiagnosticKind::VSlotMixedUsage => write!(
+                f,
+                "Mixed v-slot usage on both the component and nested <template>: when there are multiple named slots, all slots should use <template> syntax"
+            ),
+            DiagnosticKind::VSlotDuplicateName(name) => {
+                write!(f, "Duplicate slot names found: `{}`", name)
+            }
+            DiagnosticKind::VTextWithChildren => {
+                write!(f, "v-text will override element children")
+            }
+            DiagnosticKind::DeprecatedDeepSelector(replacement) => write!(
+                f,
+                "`>>>`, `/deep/` and `::v-deep` are deprecated, use `{}` instead",
+                replacement
+            ),
+            DiagnosticKind::ScopedSelectorChildRoot(class_name) => write!(
+                f,
+                "`.{}` is not used in the template, so this scoped selector can only match the root element of a child component",
+                class_name
+            ),
+            DiagnosticKind::StyleModuleNotCompiled(lang) => write!(
+                f,
+                "<style module lang=\"{}\"> needs a style preprocessor, its class names are not hashed",
+                lang
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
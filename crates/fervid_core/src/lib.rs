@@ -1,7 +1,11 @@
 mod all_html_tags;
 mod bindings;
+mod cancellation;
+mod css_vars;
+mod diagnostics;
 mod sfc;
 mod structs;
+pub mod swc;
 mod template;
 mod vue_builtins;
 mod vue_imports;
@@ -9,9 +13,12 @@ mod utils;
 
 pub use all_html_tags::is_html_tag;
 pub use bindings::*;
+pub use cancellation::CancellationToken;
+pub use css_vars::{find_css_vars, CssVarMatch};
+pub use diagnostics::*;
 pub use sfc::*;
 pub use structs::*;
 pub use template::is_from_default_slot;
 pub use vue_builtins::VUE_BUILTINS;
-pub use vue_imports::{VueImports, VueImportsSet};
+pub use vue_imports::{SsrImports, SsrImportsSet, VueImports, VueImportsSet};
 pub use utils::*;
@@ -1,4 +1,4 @@
-use swc_core::{ecma::ast::Module, common::Span};
+use swc_core::{ecma::ast::{Expr, Module}, common::Span};
 
 use crate::{Node, StartingTag, FervidAtom};
 
@@ -30,6 +30,32 @@ pub struct SfcStyleBlock {
   pub lang: FervidAtom,
   pub content: FervidAtom,
   pub is_scoped: bool,
+  /// Name of the CSS module binding, `$style` for `<style module>`
+  /// and `classes` for `<style module="classes">`
+  pub module: Option<FervidAtom>,
+  /// `v-bind()` usages in the style, in order of appearance
+  pub css_vars: Vec<SfcCssVar>,
+  /// Span of the style content in the SFC
+  pub span: Span,
+}
+
+/// A `v-bind()` usage inside `<style>`, e.g. `v-bind('theme.color')`
+#[derive(Clone, Debug)]
+pub struct SfcCssVar {
+  /// Raw expression without quotes, e.g. `theme.color`
+  pub raw: FervidAtom,
+  /// Parsed expression
+  pub expr: Box<Expr>,
+}
+
+/// Class names of a compiled `<style module>`,
+/// e.g. `$style.red` in the template resolves to `"EgL3uq_red"`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SfcCssModule {
+  /// Name of the binding, e.g. `$style`
+  pub name: FervidAtom,
+  /// Original class names and their hashed counterparts, e.g. `("red", "EgL3uq_red")`
+  pub classes: Vec<(FervidAtom, FervidAtom)>,
 }
 
 #[derive(Clone, Debug)]
@@ -42,4 +68,20 @@ pub struct SfcCustomBlock {
 pub enum SfcScriptLang {
   Es,
   Typescript,
+  /// `lang="jsx"`. JSX is kept in the AST for a downstream transform to handle
+  Jsx,
+  /// `lang="tsx"`. JSX is kept in the AST for a downstream transform to handle
+  Tsx,
+}
+
+impl SfcScriptLang {
+  /// Whether the script is `lang="ts"` or `lang="tsx"`
+  pub fn is_typescript(&self) -> bool {
+    matches!(self, SfcScriptLang::Typescript | SfcScriptLang::Tsx)
+  }
+
+  /// Whether the script is `lang="jsx"` or `lang="tsx"`
+  pub fn is_jsx(&self) -> bool {
+    matches!(self, SfcScriptLang::Jsx | SfcScriptLang::Tsx)
+  }
 }
@@ -120,13 +120,38 @@ pub struct StartingTag {
 #[derive(Debug, Clone)]
 pub enum AttributeOrBinding {
     /// `RegularAttribute` is a plain HTML attribute without any associated logic
-    RegularAttribute { name: FervidAtom, value: FervidAtom },
+    RegularAttribute {
+        name: FervidAtom,
+        value: FervidAtom,
+        span: AttributeSpan,
+    },
     /// `v-bind` directive
     VBind(VBindDirective),
     /// `v-on` directive
     VOn(VOnDirective),
 }
 
+/// Source locations of an attribute, kept separately for its name and value.
+/// ## Example
+/// For `:foo="bar"`, `name` points at `:foo` and `value` points at `bar` (without quotes).
+/// A valueless attribute like `disabled` has an empty `value` span at the end of the name.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeSpan {
+    pub name: Span,
+    pub value: Span,
+}
+
+impl AttributeOrBinding {
+    /// Gets the source locations of an attribute regardless of its kind
+    pub fn span(&self) -> AttributeSpan {
+        match self {
+            AttributeOrBinding::RegularAttribute { span, .. } => *span,
+            AttributeOrBinding::VBind(v_bind) => v_bind.span,
+            AttributeOrBinding::VOn(v_on) => v_on.span,
+        }
+    }
+}
+
 /// Describes a type which can be either a static &str or a js Expr.
 /// This is mostly usable for dynamic binding scenarios.
 /// ## Example
@@ -149,7 +174,9 @@ pub struct PatchHints {
     /// Patch flags
     pub flags: PatchFlagsSet,
     /// Dynamic props
-    pub props: Vec<JsWord>
+    pub props: Vec<JsWord>,
+    /// Whether the slot content of a component has a `<slot>`, i.e. the parent slots are forwarded
+    pub has_forwarded_slots: bool
 }
 
 flagset::flags! {
@@ -300,6 +327,11 @@ pub struct VOnDirective {
     pub handler: Option<Box<Expr>>,
     /// A list of modifiers after the dot, e.g. `stop` and `prevent` in `@click.stop.prevent="handleClick"`
     pub modifiers: Vec<FervidAtom>,
+    /// Whether the handler may be cached, i.e. it does not use `v-for` or `v-slot` variables.
+    /// Determined during the transformation
+    pub is_cacheable: bool,
+    /// Where the directive name and handler are in the source
+    pub span: AttributeSpan,
 }
 
 #[derive(Clone, Debug)]
@@ -314,6 +346,8 @@ pub struct VBindDirective {
     pub is_prop: bool,
     /// .attr modifier
     pub is_attr: bool,
+    /// Where the directive name and value are in the source
+    pub span: AttributeSpan,
 }
 
 #[derive(Clone, Debug)]
@@ -324,6 +358,8 @@ pub struct VModelDirective {
     pub value: Expr,
     /// `lazy` and `trim` in `v-model.lazy.trim`
     pub modifiers: Vec<FervidAtom>,
+    /// `$event => (userInput = $event)`, generated during the transformation
+    pub update_handler: Option<Box<Expr>>,
     pub span: Span
 }
 
@@ -380,7 +416,7 @@ pub enum BindingTypes {
     Unresolved,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum TemplateGenerationMode {
     /// Applies the transformation as if the template is rendered inline
     /// and variables are directly accessible in the function scope.
@@ -0,0 +1,24 @@
+//! Re-exports of the SWC types used in the public API of `fervid`.
+//!
+//! Downstream crates are encouraged to use these instead of depending on `swc_core` directly.
+//! This way the types always come from the same `swc_core` version `fervid` was built with,
+//! and a `swc_core` bump in `fervid` does not break the transforms written against it.
+
+pub use swc_core::common::{BytePos, Span, Spanned, SyntaxContext, DUMMY_SP};
+pub use swc_core::ecma::ast::{
+    ArrayLit, ArrowExpr, BlockStmt, CallExpr, Callee, Decl, EsVersion, Expr, ExprOrSpread,
+    Function, Id, Ident, ImportDecl, Lit, MemberExpr, Module, ModuleDecl, ModuleItem, ObjectLit,
+    Pat, Prop, PropName, PropOrSpread, Stmt, Str,
+};
+pub use swc_core::ecma::atoms::{Atom, JsWord};
+
+/// The whole SWC AST, for the types not listed above
+pub use swc_core::ecma::ast;
+
+/// The `swc_core` crate itself. Prefer the re-exported types when possible.
+pub use swc_core as core;
+
+/// The `swc_core` version requirement `fervid` is built against.
+/// Crates which need `swc_core` directly should pin the same version,
+/// otherwise the types will not be compatible.
+pub const SWC_CORE_VERSION: &str = "0.86";
@@ -2,12 +2,22 @@ use crate::{ElementNode, Node, StrOrExpr};
 
 /// Checks whether a Node is from the component's default slot or not
 pub fn is_from_default_slot(node: &Node) -> bool {
-    let Node::Element(ElementNode { starting_tag, .. }) = node else {
-        // TODO: <template v-if="true" v-slot:foo>
-        // https://play.vuejs.org/#eNp9UT1PwzAQ/SvWzW0YukWABKgDDICA0UuUXlIXx7Z85xCpyn/HdvqpVp3sex+n93RbeHKu6ANCCfeMndMV46M0QsSJeF7bzuUxAxMt+rlqHiSwDyghTqQtl421O6EQ8b/z3J3tPF+CmtJz4V6rq+Y0HhOdkDADptqaRrXFhqyJVbbJICFplUb/4VhZQxJKkZnEVVrbv7eMpSKzPV6vsf69gm9oSJiET4+Evo/VDxxXvkWe6OX3Ow7xfyA7uwo6qm+QX0hWh5Rxkj0Hs4qxT3Q57WvnrGdl2h9aDoyG9qXyJaJyzHoJ8Z4vN6of4y6KRfZJM8L4D55mqXA=
-        // Node::ConditionalSeq(_) => true,
+    let starting_tag = match node {
+        Node::Element(ElementNode { starting_tag, .. }) => starting_tag,
 
-        return true;
+        // `<template v-if="foo" v-slot:foo>` is a conditional slot regardless of its name,
+        // the same way as in the official compiler
+        Node::ConditionalSeq(conditional_seq) => {
+            let if_tag = &conditional_seq.if_node.node.starting_tag;
+            let has_v_slot = if_tag
+                .directives
+                .as_ref()
+                .map_or(false, |directives| directives.v_slot.is_some());
+
+            return !(if_tag.tag_name.eq("template") && has_v_slot);
+        }
+
+        _ => return true,
     };
 
     if !starting_tag.tag_name.eq("template") {
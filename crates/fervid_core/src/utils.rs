@@ -1,4 +1,4 @@
-use crate::{AttributeOrBinding, VBindDirective, StrOrExpr};
+use crate::{AttributeOrBinding, FervidAtom, VBindDirective, StrOrExpr};
 
 /// Checks whether the attributes name is the same as `expected_name`
 #[inline]
@@ -9,3 +9,65 @@ pub fn check_attribute_name(attr: &AttributeOrBinding, expected_name: &str) -> b
         if name == expected_name
     )
 }
+
+/// Generates the name of a CSS variable, the same way as the official compiler does.
+/// In DEV it is `{scope_id}-{raw}`, e.g. `abcd1234-foo.bar`.
+/// In PROD it is a hash of the scope id and the raw expression.
+///
+/// This is the name `useCssVars` sets at runtime. The stylesheet refers to it
+/// through [`escape_css_var_name`], e.g. `var(--abcd1234-foo\.bar)`,
+/// which the CSS parser unescapes back to the same name.
+pub fn generate_css_var_name(scope_id: &str, raw: &str, is_prod: bool) -> FervidAtom {
+    if is_prod {
+        let hash = fxhash::hash32(&format!("{scope_id}{raw}"));
+        return FervidAtom::from(format!("{hash:08x}"));
+    }
+
+    FervidAtom::from(format!("{scope_id}-{raw}"))
+}
+
+/// Escapes the CSS special characters of a name from [`generate_css_var_name`],
+/// so that it can be used in the stylesheet, e.g. `abcd1234-foo\.bar`
+pub fn escape_css_var_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(
+            c,
+            ' ' | '!'
+                | '"'
+                | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '('
+                | ')'
+                | '*'
+                | '+'
+                | ','
+                | '.'
+                | '/'
+                | ':'
+                | ';'
+                | '<'
+                | '='
+                | '>'
+                | '?'
+                | '@'
+                | '['
+                | '\\'
+                | ']'
+                | '^'
+                | '`'
+                | '{'
+                | '|'
+                | '}'
+                | '~'
+        ) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+
+    result
+}
@@ -5,29 +5,45 @@ use crate::FervidAtom;
 flags! {
     // #[derive(Clone, Copy)]
     pub enum VueImports: u64 {
+        Camelize,
         CreateBlock,
         CreateCommentVNode,
         CreateElementBlock,
         CreateElementVNode,
+        CreatePropsRestProxy,
+        CreateSlots,
+        CreateStaticVNode,
         CreateTextVNode,
         CreateVNode,
+        DefineComponent,
         Fragment,
+        GuardReactiveProps,
+        IsMemoSame,
+        IsRef,
         KeepAlive,
         MergeModels,
+        MergeProps,
         NormalizeClass,
+        NormalizeProps,
         NormalizeStyle,
         OpenBlock,
+        PopScopeId,
+        PushScopeId,
         RenderList,
         RenderSlot,
         ResolveComponent,
         ResolveDirective,
         ResolveDynamicComponent,
+        SetBlockTracking,
         Suspense,
         Teleport,
         ToDisplayString,
+        ToHandlerKey,
+        ToHandlers,
         Transition,
         TransitionGroup,
         Unref,
+        UseCssVars,
         UseModel,
         UseSlots,
         VModelCheckbox,
@@ -38,6 +54,8 @@ flags! {
         VShow,
         WithCtx,
         WithDirectives,
+        WithKeys,
+        WithMemo,
         WithModifiers,
     }
 }
@@ -46,29 +64,45 @@ impl VueImports {
     #[inline]
     pub fn as_str(self) -> &'static str {
         match self {
+            VueImports::Camelize => "_camelize",
             VueImports::CreateBlock => "_createBlock",
             VueImports::CreateCommentVNode => "_createCommentVNode",
             VueImports::CreateElementBlock => "_createElementBlock",
             VueImports::CreateElementVNode => "_createElementVNode",
+            VueImports::CreatePropsRestProxy => "_createPropsRestProxy",
+            VueImports::CreateSlots => "_createSlots",
+            VueImports::CreateStaticVNode => "_createStaticVNode",
             VueImports::CreateTextVNode => "_createTextVNode",
             VueImports::CreateVNode => "_createVNode",
+            VueImports::DefineComponent => "_defineComponent",
             VueImports::Fragment => "_Fragment",
+            VueImports::GuardReactiveProps => "_guardReactiveProps",
+            VueImports::IsMemoSame => "_isMemoSame",
+            VueImports::IsRef => "_isRef",
             VueImports::KeepAlive => "_KeepAlive",
             VueImports::MergeModels => "_mergeModels",
+            VueImports::MergeProps => "_mergeProps",
             VueImports::NormalizeClass => "_normalizeClass",
+            VueImports::NormalizeProps => "_normalizeProps",
             VueImports::NormalizeStyle => "_normalizeStyle",
             VueImports::OpenBlock => "_openBlock",
+            VueImports::PopScopeId => "_popScopeId",
+            VueImports::PushScopeId => "_pushScopeId",
             VueImports::RenderList => "_renderList",
             VueImports::RenderSlot => "_renderSlot",
             VueImports::ResolveComponent => "_resolveComponent",
             VueImports::ResolveDirective => "_resolveDirective",
             VueImports::ResolveDynamicComponent => "_resolveDynamicComponent",
+            VueImports::SetBlockTracking => "_setBlockTracking",
             VueImports::Suspense => "_Suspense",
             VueImports::Teleport => "_Teleport",
             VueImports::ToDisplayString => "_toDisplayString",
+            VueImports::ToHandlerKey => "_toHandlerKey",
+            VueImports::ToHandlers => "_toHandlers",
             VueImports::Transition => "_Transition",
             VueImports::TransitionGroup => "_TransitionGroup",
             VueImports::Unref => "_unref",
+            VueImports::UseCssVars => "_useCssVars",
             VueImports::UseModel => "_useModel",
             VueImports::UseSlots => "_useSlots",
             VueImports::VModelCheckbox => "_vModelCheckbox",
@@ -79,6 +113,8 @@ impl VueImports {
             VueImports::VShow => "_vShow",
             VueImports::WithCtx => "_withCtx",
             VueImports::WithDirectives => "_withDirectives",
+            VueImports::WithKeys => "_withKeys",
+            VueImports::WithMemo => "_withMemo",
             VueImports::WithModifiers => "_withModifiers",
         }
     }
@@ -90,3 +126,56 @@ impl VueImports {
 }
 
 pub type VueImportsSet = FlagSet<VueImports>;
+
+flags! {
+    /// Helpers used by the SSR render function, imported from `vue/server-renderer`
+    pub enum SsrImports: u32 {
+        SsrIncludeBooleanAttr,
+        SsrInterpolate,
+        SsrLooseContain,
+        SsrLooseEqual,
+        SsrRenderAttr,
+        SsrRenderAttrs,
+        SsrRenderClass,
+        SsrRenderComponent,
+        SsrRenderDynamicAttr,
+        SsrRenderDynamicModel,
+        SsrRenderList,
+        SsrRenderSlot,
+        SsrRenderStyle,
+        SsrRenderSuspense,
+        SsrRenderTeleport,
+        SsrRenderVNode,
+    }
+}
+
+impl SsrImports {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SsrImports::SsrIncludeBooleanAttr => "_ssrIncludeBooleanAttr",
+            SsrImports::SsrInterpolate => "_ssrInterpolate",
+            SsrImports::SsrLooseContain => "_ssrLooseContain",
+            SsrImports::SsrLooseEqual => "_ssrLooseEqual",
+            SsrImports::SsrRenderAttr => "_ssrRenderAttr",
+            SsrImports::SsrRenderAttrs => "_ssrRenderAttrs",
+            SsrImports::SsrRenderClass => "_ssrRenderClass",
+            SsrImports::SsrRenderComponent => "_ssrRenderComponent",
+            SsrImports::SsrRenderDynamicAttr => "_ssrRenderDynamicAttr",
+            SsrImports::SsrRenderDynamicModel => "_ssrRenderDynamicModel",
+            SsrImports::SsrRenderList => "_ssrRenderList",
+            SsrImports::SsrRenderSlot => "_ssrRenderSlot",
+            SsrImports::SsrRenderStyle => "_ssrRenderStyle",
+            SsrImports::SsrRenderSuspense => "_ssrRenderSuspense",
+            SsrImports::SsrRenderTeleport => "_ssrRenderTeleport",
+            SsrImports::SsrRenderVNode => "_ssrRenderVNode",
+        }
+    }
+
+    #[inline]
+    pub fn as_atom(self) -> FervidAtom {
+        self.as_str().into()
+    }
+}
+
+pub type SsrImportsSet = FlagSet<SsrImports>;
@@ -0,0 +1,124 @@
+use fervid_core::{
+    swc::{BytePos, Span},
+    Diagnostic, DiagnosticKind, FervidAtom,
+};
+use lightningcss::{
+    selector::{Component, PseudoClass, Selector},
+    stylesheet::{ParserOptions, StyleSheet},
+};
+use parcel_selectors::parser::Combinator;
+
+use crate::transform_style_scoped::{collect_style_rules, scoped_pseudo_class};
+
+/// Finds the selectors of a `<style scoped>` which can only match the root element
+/// of a child component, because their last compound selector has a class
+/// which is not in `template_classes`, see `collect_template_classes` in `fervid_transform`.
+///
+/// The scope attribute is added both to the elements of the component template
+/// and to the root elements of its child components, thus `.child-root` still matches
+/// the root of a child component when `.child-root` is not used in the template.
+///
+/// Each such selector is reported as a [`DiagnosticKind::ScopedSelectorChildRoot`] note.
+/// The diagnostic span points at the selector list of the rule, offset by `span.lo`.
+/// A style which cannot be parsed has no notes.
+pub fn find_child_root_selectors(
+    input: &str,
+    span: Span,
+    template_classes: &[FervidAtom],
+) -> Vec<Diagnostic> {
+    let Ok(mut stylesheet) = StyleSheet::parse(input, ParserOptions::default()) else {
+        return Vec::new();
+    };
+
+    let mut style_rules = Vec::new();
+    collect_style_rules(&mut stylesheet.rules, &mut style_rules);
+
+    let mut diagnostics = Vec::new();
+    for style in style_rules {
+        let prelude_start = location_to_offset(input, style.loc.line, style.loc.column);
+        let prelude = input[prelude_start..]
+            .split('{')
+            .next()
+            .unwrap_or_default()
+            .trim_end();
+
+        for selector in style.selectors.0.iter() {
+            let Some(class_name) = find_child_root_class(selector, template_classes) else {
+                continue;
+            };
+
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::ScopedSelectorChildRoot(FervidAtom::from(class_name)),
+                span: Span {
+                    lo: BytePos(span.lo.0 + prelude_start as u32),
+                    hi: BytePos(span.lo.0 + (prelude_start + prelude.len()) as u32),
+                    ctxt: span.ctxt,
+                },
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Finds a class of the last compound selector which is not used in the template.
+/// The scope attribute is added to this compound selector,
+/// unless `:deep()`, `:slotted()` or `:global()` move or remove it
+fn find_child_root_class<'s>(
+    selector: &'s Selector,
+    template_classes: &[FervidAtom],
+) -> Option<&'s str> {
+    let has_scoped_pseudo_class = selector.iter_raw_match_order().any(|component| {
+        matches!(
+            component,
+            Component::NonTSPseudoClass(PseudoClass::CustomFunction { name, .. })
+                if scoped_pseudo_class(name).is_some()
+        )
+    });
+    if has_scoped_pseudo_class {
+        return None;
+    }
+
+    // Pseudo-elements are a part of the compound selector, e.g. `.foo::before`
+    selector
+        .iter_raw_match_order()
+        .take_while(|component| {
+            !matches!(
+                component,
+                Component::Combinator(
+                    Combinator::Child
+                        | Combinator::Descendant
+                        | Combinator::LaterSibling
+                        | Combinator::NextSibling
+                )
+            )
+        })
+        .find_map(|component| match component {
+            Component::Class(class_name) => {
+                let class_name: &str = &class_name.0;
+                let is_used = template_classes.iter().any(|known| known == class_name);
+                (!is_used).then_some(class_name)
+            }
+            _ => None,
+        })
+}
+
+/// Byte offset of a `lightningcss` location.
+/// Its line is zero-based and its column is one-based and counted in UTF-16 code units
+fn location_to_offset(input: &str, line: u32, column: u32) -> usize {
+    let line_start: usize = input
+        .split_inclusive('\n')
+        .take(line as usize)
+        .map(str::len)
+        .sum();
+
+    let mut utf16_column = 1;
+    for (idx, c) in input[line_start..].char_indices() {
+        if utf16_column >= column {
+            return line_start + idx;
+        }
+        utf16_column += c.len_utf16() as u32;
+    }
+
+    input.len()
+}
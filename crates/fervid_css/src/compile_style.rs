@@ -0,0 +1,433 @@
+use fervid_core::{swc::Span, Diagnostic};
+use lightningcss::{
+    css_modules::CssModuleExports,
+    stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet},
+};
+use parcel_sourcemap::{OriginalLocation, SourceMap, SourceMapError};
+
+use crate::{
+    css_vars::rewrite_css_vars_with_replacements,
+    deprecated_deep::rewrite_deprecated_deep_with_replacements, needs_preprocessor,
+    StylePreprocessor, TransformError, TransformOptions, Transformer,
+};
+
+/// Options for [`compile_style`]
+#[derive(Default)]
+pub struct CompileStyleOptions<'o> {
+    /// Scope ID of the component without `data-v-`, e.g. `abcd1234`
+    pub scope_id: &'o str,
+    /// Language of the block, e.g. `scss` for `<style lang="scss">`. Empty means `css`
+    pub lang: &'o str,
+    /// Compiles the languages other than CSS, see [`StylePreprocessor`]
+    pub preprocessor: Option<&'o dyn StylePreprocessor>,
+    /// Whether the block is `<style scoped>`
+    pub is_scoped: bool,
+    /// Whether the block is `<style module>`, then its class names are hashed and renamed
+    pub is_module: bool,
+    /// Are we compiling for DEV or PROD, it changes the names of the `v-bind()` CSS variables
+    pub is_prod: bool,
+    /// Minify the compiled CSS, i.e. strip the whitespace and comments
+    /// and merge the longhand properties and rules where it is safe
+    pub minify: bool,
+    /// Checked in between the phases and style rules to abort a stale compilation
+    pub is_cancelled: Option<&'o dyn Fn() -> bool>,
+    /// Generate a source map pointing to the `<style>` block in the SFC
+    pub source_map: Option<StyleSourceMapOptions<'o>>,
+    /// Span of the `<style>` content in the SFC, the diagnostics are reported relative to it
+    pub span: Span,
+}
+
+/// Location of the `<style>` block in the SFC, see [`CompileStyleOptions::source_map`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StyleSourceMapOptions<'o> {
+    /// Path to the SFC, e.g. `src/App.vue`
+    pub filename: &'o str,
+    /// Zero-based line where the `<style>` content starts in the SFC
+    pub line: u32,
+    /// Zero-based column where the `<style>` content starts in the SFC
+    pub column: u32,
+}
+
+/// A compiled `<style>` block
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompiledStyle {
+    /// The compiled CSS
+    pub code: String,
+    /// The attribute added to the selectors, e.g. `data-v-abcd1234`.
+    /// It is `None` when the block is not scoped
+    pub scope_id: Option<String>,
+    /// Original class names and their hashed counterparts sorted by the original name,
+    /// e.g. `("red", "EgL3uq_red")`. It is `None` when the block is not a CSS module
+    pub module_mapping: Option<Vec<(String, String)>>,
+    /// Source map in JSON format, present when [`CompileStyleOptions::source_map`] was set.
+    /// When the preprocessor returned a map, the source map is chained with it
+    pub map: Option<String>,
+    /// Warnings found during the compilation, e.g. the deprecated deep selectors
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Error of [`compile_style`], e.g. when the style cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleError {
+    pub message: String,
+}
+
+/// Compiles the content of a single `<style>` block.
+///
+/// Selectors of a `<style scoped>` get the `[data-v-abcd1234]` attribute
+/// on their last compound selector, e.g. `.foo .bar` becomes `.foo .bar[data-v-abcd1234]`.
+///
+/// Class names of a `<style module>` are hashed using the `scope_id`,
+/// so that they are unique per component, but stable between the compilations.
+///
+/// `v-bind()` usages are replaced with the CSS variables, see [`crate::rewrite_css_vars`].
+///
+/// A block in other language than CSS is compiled by the `preprocessor` first,
+/// and it is an error when the preprocessor is not provided.
+///
+/// The deprecated `>>>`, `/deep/` and `::v-deep` are compiled as `:deep()` in a `<style scoped>`
+/// and reported in [`CompiledStyle::diagnostics`], see [`crate::rewrite_deprecated_deep`].
+/// In other languages, they are rewritten in the preprocessed CSS
+/// and reported with the span of the whole block.
+pub fn compile_style(
+    input: &str,
+    options: CompileStyleOptions,
+) -> Result<CompiledStyle, StyleError> {
+    let CompileStyleOptions {
+        scope_id,
+        lang,
+        preprocessor,
+        is_scoped,
+        is_module,
+        is_prod,
+        minify,
+        is_cancelled,
+        source_map: source_map_options,
+        span,
+    } = options;
+
+    let mut diagnostics = Vec::new();
+    let original_input = input;
+    let is_preprocessed = needs_preprocessor(lang);
+
+    // Deprecated deep selectors are only supported in the scoped styles.
+    // Other languages are rewritten after the preprocessor, see below
+    let mut deep_replacements = Vec::new();
+    let rewritten_input;
+    let input = if is_scoped && !is_preprocessed {
+        rewritten_input = rewrite_deprecated_deep_with_replacements(
+            input,
+            span,
+            &mut diagnostics,
+            &mut deep_replacements,
+        );
+        &*rewritten_input
+    } else {
+        input
+    };
+
+    // The content is padded with whitespace to start at the same position as in the SFC.
+    // This way the source map points to the SFC lines and columns without remapping
+    let padded_input;
+    let input = match source_map_options {
+        Some(StyleSourceMapOptions { line, column, .. }) => {
+            padded_input = pad_to_position(input, line, column);
+            padded_input.as_str()
+        }
+        None => input,
+    };
+
+    // Other languages are compiled to CSS before the transformations
+    let preprocessed;
+    let mut preprocessor_map = None;
+    let input = if is_preprocessed {
+        let Some(preprocessor) = preprocessor else {
+            return Err(StyleError {
+                message: format!(
+                    "<style lang=\"{}\"> needs a style preprocessor, but none was provided",
+                    lang
+                ),
+            });
+        };
+
+        preprocessed = preprocessor.preprocess(lang, input)?;
+        preprocessor_map = preprocessed.map.as_deref();
+        preprocessed.code.as_str()
+    } else {
+        input
+    };
+
+    // The source map points to the code before the rewrites.
+    // A preprocessed style is chained with the preprocessor map instead
+    let map_source = source_map_options.map(|options| {
+        if is_preprocessed {
+            input.to_owned()
+        } else {
+            pad_to_position(original_input, options.line, options.column)
+        }
+    });
+    let mut source_map = source_map_options.map(|options| {
+        let mut source_map = SourceMap::new("/");
+        source_map.add_source(options.filename);
+        source_map
+    });
+
+    // The preprocessor strips the `//` comments and expands the mixins,
+    // so the deep selectors are only found reliably in its output.
+    // Their positions do not match the SFC anymore, and the whole block is reported instead
+    let rewritten_preprocessed;
+    let input = if is_scoped && is_preprocessed {
+        rewritten_preprocessed = rewrite_deprecated_deep_with_replacements(
+            input,
+            span,
+            &mut diagnostics,
+            &mut deep_replacements,
+        );
+        for diagnostic in diagnostics.iter_mut() {
+            diagnostic.span = span;
+        }
+        &*rewritten_preprocessed
+    } else {
+        input
+    };
+
+    let mut css_vars_replacements = Vec::new();
+    let input =
+        rewrite_css_vars_with_replacements(input, scope_id, is_prod, &mut css_vars_replacements);
+    let input: &str = &input;
+
+    let parser_options = || ParserOptions {
+        filename: scope_id.to_owned(),
+        css_modules: is_module.then(Default::default),
+        ..Default::default()
+    };
+    let printer_options = PrinterOptions {
+        minify,
+        source_map: source_map.as_mut(),
+        ..Default::default()
+    };
+
+    let (to_css_result, scope_attr) = if is_scoped {
+        let scope_attr = format!("data-v-{}", scope_id);
+        let to_css_result = {
+            let mut transformer = Transformer::new(input, &scope_attr);
+            transformer
+                .transform_style_scoped(TransformOptions {
+                    parse: parser_options(),
+                    minify: minify.then(MinifyOptions::default),
+                    to_css: printer_options,
+                    is_cancelled,
+                    ..Default::default()
+                })
+                .map_err(StyleError::from)?
+        };
+
+        (to_css_result, Some(scope_attr))
+    } else {
+        let mut stylesheet =
+            StyleSheet::parse(input, parser_options()).map_err(TransformError::from)?;
+        if minify {
+            stylesheet
+                .minify(MinifyOptions::default())
+                .map_err(TransformError::from)?;
+        }
+        let to_css_result = stylesheet
+            .to_css(printer_options)
+            .map_err(TransformError::from)?;
+
+        (to_css_result, None)
+    };
+
+    let map = match (source_map, source_map_options, map_source) {
+        (Some(source_map), Some(options), Some(map_source)) => {
+            // CSS is padded after the deep selectors are rewritten, the preprocessed styles before
+            let padding = if is_preprocessed {
+                0
+            } else {
+                (options.line + options.column) as usize
+            };
+            let to_source_offset = |offset: usize| {
+                let offset = to_input_offset(&css_vars_replacements, offset);
+                match offset.checked_sub(padding) {
+                    Some(unpadded) => to_input_offset(&deep_replacements, unpadded) + padding,
+                    None => offset,
+                }
+            };
+
+            let mut source_map = remap_to_source(
+                &source_map,
+                options.filename,
+                input,
+                &map_source,
+                to_source_offset,
+            )?;
+            Some(source_map_to_json(&mut source_map, preprocessor_map)?)
+        }
+        _ => None,
+    };
+
+    Ok(CompiledStyle {
+        code: to_css_result.code,
+        scope_id: scope_attr,
+        module_mapping: to_module_mapping(to_css_result.exports),
+        map,
+        diagnostics,
+    })
+}
+
+/// Prepends `line` newlines and `column` spaces to the input
+fn pad_to_position(input: &str, line: u32, column: u32) -> String {
+    let mut result = String::with_capacity(line as usize + column as usize + input.len());
+    result.extend(std::iter::repeat('\n').take(line as usize));
+    result.extend(std::iter::repeat(' ').take(column as usize));
+    result.push_str(input);
+    result
+}
+
+/// A range of the input replaced by a rewrite, e.g. `v-bind(color)` by `var(--abcd1234-color)`.
+/// `start` and `end` are byte offsets in the input, `len` is the length of the replacement
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Replacement {
+    pub start: usize,
+    pub end: usize,
+    pub len: usize,
+}
+
+/// Maps a byte offset in the rewritten text back to the input of the rewrite.
+/// An offset inside of a replacement maps to the start of the replaced range
+fn to_input_offset(replacements: &[Replacement], offset: usize) -> usize {
+    // Rewritten offset minus the input offset
+    let mut shift = 0isize;
+
+    for replacement in replacements {
+        let rewritten_start = (replacement.start as isize + shift) as usize;
+        if offset < rewritten_start {
+            break;
+        }
+        if offset < rewritten_start + replacement.len {
+            return replacement.start;
+        }
+
+        shift += replacement.len as isize - (replacement.end - replacement.start) as isize;
+    }
+
+    (offset as isize - shift) as usize
+}
+
+/// Moves the original positions of the `source_map` from the `rewritten` style to the `source`,
+/// because the `v-bind()` and deep selector rewrites change the length of the code
+fn remap_to_source(
+    source_map: &SourceMap,
+    filename: &str,
+    rewritten: &str,
+    source: &str,
+    to_source_offset: impl Fn(usize) -> usize,
+) -> Result<SourceMap, StyleError> {
+    let rewritten_lines = LineIndex::new(rewritten);
+    let source_lines = LineIndex::new(source);
+
+    let mut result = SourceMap::new("/");
+    let source_index = result.add_source(filename);
+    result.set_source_content(source_index as usize, source)?;
+
+    for mapping in source_map.get_mappings() {
+        let original = mapping.original.map(|original| {
+            let offset =
+                rewritten_lines.to_offset(original.original_line, original.original_column);
+            let (line, column) = source_lines.to_line_column(to_source_offset(offset));
+            OriginalLocation::new(line, column, source_index, None)
+        });
+        result.add_mapping(mapping.generated_line, mapping.generated_column, original);
+    }
+
+    Ok(result)
+}
+
+/// Converts between the byte offsets and the zero-based lines and UTF-16 columns of the source maps
+struct LineIndex<'t> {
+    text: &'t str,
+    line_starts: Vec<usize>,
+}
+
+impl<'t> LineIndex<'t> {
+    fn new(text: &'t str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        LineIndex { text, line_starts }
+    }
+
+    fn to_offset(&self, line: u32, column: u32) -> usize {
+        let Some(&line_start) = self.line_starts.get(line as usize) else {
+            return self.text.len();
+        };
+
+        let mut current_column = 0;
+        for (idx, c) in self.text[line_start..].char_indices() {
+            if current_column >= column as usize || c == '\n' {
+                return line_start + idx;
+            }
+            current_column += c.len_utf16();
+        }
+
+        self.text.len()
+    }
+
+    fn to_line_column(&self, offset: usize) -> (u32, u32) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column: usize = self
+            .text
+            .get(self.line_starts[line]..offset)
+            .map_or(0, |line_text| line_text.chars().map(char::len_utf16).sum());
+
+        (line as u32, column as u32)
+    }
+}
+
+/// Serializes the source map. When the CSS was preprocessed,
+/// the map is chained with the preprocessor map to point to the original sources
+fn source_map_to_json(
+    source_map: &mut SourceMap,
+    preprocessor_map: Option<&str>,
+) -> Result<String, StyleError> {
+    if let Some(preprocessor_map) = preprocessor_map {
+        let mut original_map = SourceMap::from_json("/", preprocessor_map)?;
+        source_map.extends(&mut original_map)?;
+    }
+
+    Ok(source_map.to_json(None)?)
+}
+
+/// Converts the CSS module exports to the class name mapping, which is sorted for stable output
+fn to_module_mapping(exports: Option<CssModuleExports>) -> Option<Vec<(String, String)>> {
+    let mut module_mapping: Vec<(String, String)> = exports?
+        .into_iter()
+        .map(|(local_name, export)| (local_name, export.name))
+        .collect();
+
+    module_mapping.sort();
+    Some(module_mapping)
+}
+
+impl From<TransformError<'_>> for StyleError {
+    fn from(value: TransformError<'_>) -> Self {
+        let message = match value {
+            TransformError::ParserError(err) => err.to_string(),
+            TransformError::MinifyError(err) => err.to_string(),
+            TransformError::PrinterError(err) => err.to_string(),
+            TransformError::Cancelled => String::from("Style compilation was cancelled"),
+        };
+
+        StyleError { message }
+    }
+}
+
+impl From<SourceMapError> for StyleError {
+    fn from(value: SourceMapError) -> Self {
+        StyleError {
+            message: value.to_string(),
+        }
+    }
+}
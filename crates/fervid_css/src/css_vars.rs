@@ -0,0 +1,55 @@
+use std::borrow::Cow;
+
+use fervid_core::{escape_css_var_name, find_css_vars, generate_css_var_name};
+
+use crate::compile_style::Replacement;
+
+/// Replaces the `v-bind()` usages in the style with the CSS custom properties
+/// which are bound by `useCssVars` in the script.
+///
+/// ## Example
+/// `.foo { color: v-bind(color); width: v-bind('size.width') }` in DEV becomes
+/// `.foo { color: var(--abcd1234-color); width: var(--abcd1234-size\.width) }`.
+///
+/// The input is borrowed as-is when it has no `v-bind()`.
+pub fn rewrite_css_vars<'i>(input: &'i str, scope_id: &str, is_prod: bool) -> Cow<'i, str> {
+    rewrite_css_vars_with_replacements(input, scope_id, is_prod, &mut Vec::new())
+}
+
+/// Same as [`rewrite_css_vars`], but also records the replaced ranges of the input
+pub(crate) fn rewrite_css_vars_with_replacements<'i>(
+    input: &'i str,
+    scope_id: &str,
+    is_prod: bool,
+    replacements: &mut Vec<Replacement>,
+) -> Cow<'i, str> {
+    let css_vars = find_css_vars(input);
+    if css_vars.is_empty() {
+        return Cow::Borrowed(input);
+    }
+
+    let mut result = String::with_capacity(input.len());
+    let mut copied_until = 0;
+
+    for css_var in css_vars {
+        let raw = &input[css_var.raw_start..css_var.raw_end];
+
+        result.push_str(&input[copied_until..css_var.start]);
+        let replacement_start = result.len();
+        result.push_str("var(--");
+        result.push_str(&escape_css_var_name(&generate_css_var_name(
+            scope_id, raw, is_prod,
+        )));
+        result.push(')');
+        copied_until = css_var.end;
+
+        replacements.push(Replacement {
+            start: css_var.start,
+            end: css_var.end,
+            len: result.len() - replacement_start,
+        });
+    }
+
+    result.push_str(&input[copied_until..]);
+    Cow::Owned(result)
+}
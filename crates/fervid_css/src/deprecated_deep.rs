@@ -0,0 +1,165 @@
+use std::borrow::Cow;
+
+use fervid_core::{
+    swc::{BytePos, Span},
+    Diagnostic, DiagnosticKind, FervidAtom,
+};
+
+use crate::compile_style::Replacement;
+
+/// Rewrites the deprecated deep selectors to `:deep()`, so that they are still compiled:
+/// - `.foo >>> .bar` and `.foo /deep/ .bar` become `.foo :deep(.bar)`;
+/// - `.foo ::v-deep .bar` becomes `.foo :deep(.bar)`;
+/// - `::v-deep(.bar)` becomes `:deep(.bar)`.
+///
+/// Each usage is reported as a [`DiagnosticKind::DeprecatedDeepSelector`].
+/// The diagnostic span is the range to replace, offset by `span.lo` of the style content.
+///
+/// The input is borrowed as-is when it has no deprecated selectors.
+pub fn rewrite_deprecated_deep<'i>(
+    input: &'i str,
+    span: Span,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Cow<'i, str> {
+    rewrite_deprecated_deep_with_replacements(input, span, diagnostics, &mut Vec::new())
+}
+
+/// Same as [`rewrite_deprecated_deep`], but also records the replaced ranges of the input
+pub(crate) fn rewrite_deprecated_deep_with_replacements<'i>(
+    input: &'i str,
+    span: Span,
+    diagnostics: &mut Vec<Diagnostic>,
+    replacements: &mut Vec<Replacement>,
+) -> Cow<'i, str> {
+    let bytes = input.as_bytes();
+
+    let mut result = String::new();
+    let mut copied_until = 0;
+    let mut prelude_start = 0;
+    let mut paren_depth = 0;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            // Comments and strings cannot contain selectors
+            b'/' if bytes.get(idx + 1) == Some(&b'*') => {
+                let comment_end = input[idx + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| idx + 2 + end + "*/".len());
+
+                // A comment before the selector is not a part of it
+                if input[prelude_start..idx].trim().is_empty() {
+                    prelude_start = comment_end;
+                }
+
+                idx = comment_end;
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                idx = input[idx + 1..]
+                    .find(quote as char)
+                    .map_or(bytes.len(), |end| idx + 1 + end + 1);
+                continue;
+            }
+            b'(' => paren_depth += 1,
+            b')' => paren_depth -= 1,
+
+            // Selectors are what comes before `{`, declarations end with `;` or `}`
+            b'{' if paren_depth == 0 => {
+                let is_at_rule = input[prelude_start..idx].trim_start().starts_with('@');
+                if !is_at_rule {
+                    for (start, end, replacement) in find_deprecated_deep(input, prelude_start, idx)
+                    {
+                        result.push_str(&input[copied_until..start]);
+                        result.push_str(&replacement);
+                        copied_until = end;
+
+                        replacements.push(Replacement {
+                            start,
+                            end,
+                            len: replacement.len(),
+                        });
+
+                        diagnostics.push(Diagnostic {
+                            kind: DiagnosticKind::DeprecatedDeepSelector(FervidAtom::from(
+                                replacement,
+                            )),
+                            span: Span {
+                                lo: BytePos(span.lo.0 + start as u32),
+                                hi: BytePos(span.lo.0 + end as u32),
+                                ctxt: span.ctxt,
+                            },
+                        });
+                    }
+                }
+
+                prelude_start = idx + 1;
+            }
+            b'}' | b';' if paren_depth == 0 => prelude_start = idx + 1,
+            _ => {}
+        }
+
+        idx += 1;
+    }
+
+    if copied_until == 0 {
+        return Cow::Borrowed(input);
+    }
+
+    result.push_str(&input[copied_until..]);
+    Cow::Owned(result)
+}
+
+const V_DEEP: &str = "::v-deep";
+
+/// Finds the deprecated deep selectors in the selector list `input[start..end]`.
+/// Returns the ranges to replace and their replacements, one per selector at most
+fn find_deprecated_deep(input: &str, start: usize, end: usize) -> Vec<(usize, usize, String)> {
+    let mut result = Vec::new();
+    let mut selector_start = start;
+    let mut paren_depth = 0;
+
+    for (idx, c) in input[start..end].char_indices() {
+        let idx = start + idx;
+        match c {
+            '(' => paren_depth += 1,
+            ')' => paren_depth -= 1,
+            ',' if paren_depth == 0 => {
+                result.extend(find_in_selector(input, selector_start, idx));
+                selector_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    result.extend(find_in_selector(input, selector_start, end));
+
+    result
+}
+
+/// Finds the first deprecated deep selector in the selector `input[start..end]`
+fn find_in_selector(input: &str, start: usize, end: usize) -> Option<(usize, usize, String)> {
+    let selector = &input[start..end];
+
+    let (token_idx, token) = [">>>", "/deep/", V_DEEP]
+        .into_iter()
+        .filter_map(|token| selector.find(token).map(|idx| (idx, token)))
+        .min_by_key(|(idx, _)| *idx)?;
+
+    let token_start = start + token_idx;
+    let token_end = token_start + token.len();
+
+    // `::v-deep(.bar)` only needs a rename
+    if token == V_DEEP && input[token_end..end].starts_with('(') {
+        return Some((token_start, token_end, String::from(":deep")));
+    }
+
+    // Everything after the combinator goes inside `:deep()`
+    let rest = input[token_end..end].trim();
+    let rest_end = if rest.is_empty() {
+        token_end
+    } else {
+        token_end + input[token_end..end].find(rest).unwrap_or(0) + rest.len()
+    };
+
+    Some((token_start, rest_end, format!(":deep({})", rest)))
+}
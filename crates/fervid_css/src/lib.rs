@@ -27,6 +27,7 @@
 //!         minify: true,
 //!         ..Default::default()
 //!     },
+//!     is_cancelled: None,
 //! };
 //!
 //! let result = transformer.transform_style_scoped(options);
@@ -36,8 +37,20 @@
 //! }
 //! ```
 
+mod child_root;
+mod compile_style;
+mod css_vars;
+mod deprecated_deep;
+mod merge_styles;
+mod preprocessor;
 mod transform_style_scoped;
 
+pub use child_root::*;
+pub use compile_style::*;
+pub use css_vars::*;
+pub use deprecated_deep::*;
+pub use merge_styles::*;
+pub use preprocessor::*;
 pub use transform_style_scoped::*;
 
 #[cfg(test)]
@@ -45,7 +58,16 @@ pub use transform_style_scoped::*;
 mod tests {
     use lightningcss::{targets::Browsers, stylesheet::{MinifyOptions, PrinterOptions}};
 
-    use crate::{Transformer, TransformOptions};
+    use fervid_core::{
+        swc::{BytePos, Span},
+        Diagnostic, DiagnosticKind, FervidAtom,
+    };
+
+    use crate::{
+        compile_style, find_child_root_selectors, merge_styles, rewrite_css_vars,
+        rewrite_deprecated_deep, CompileStyleOptions, MergedStyleOffset, PreprocessedStyle,
+        StyleError, StyleSourceMapOptions, TransformOptions, Transformer,
+    };
 
     macro_rules! test_output {
         ($input: expr, $expected: expr, $options: expr) => {
@@ -84,6 +106,7 @@ mod tests {
                     minify: true,
                     ..Default::default()
                 },
+                is_cancelled: None,
             }
         };
     }
@@ -184,4 +207,654 @@ mod tests {
             minify_yes!()
         );
     }
+
+    #[test]
+    fn transform_style_scoped_nested_in_at_rules() {
+        test_ok!(
+            "@media (min-width: 100px) { .foo .bar { background: yellow } }",
+            "@media (min-width:100px){.foo .bar[data-v-abcd1234]{background:#ff0}}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "@supports (display: grid) { .foo { background: yellow } } .bar { background: yellow }",
+            "@supports (display:grid){.foo[data-v-abcd1234]{background:#ff0}}.bar[data-v-abcd1234]{background:#ff0}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_scoped_slotted_and_global() {
+        test_ok!(
+            ":slotted(.bar) { background: yellow }",
+            ".bar[data-v-abcd1234-s]{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo :slotted(.bar .baz) { background: yellow }",
+            ".foo .bar .baz[data-v-abcd1234-s]{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ":global(.foo) { background: yellow }",
+            ".foo{background:#ff0}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo :global(.bar .baz), .qux { background: yellow }",
+            ".bar .baz,.qux[data-v-abcd1234]{background:#ff0}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn compile_style_scoped_and_not_scoped() {
+        let scoped = compile_style(
+            ".foo .bar { color: red }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                lang: "css",
+                preprocessor: None,
+                is_scoped: true,
+                is_module: false,
+                is_prod: false,
+                minify: false,
+                is_cancelled: None,
+                source_map: None,
+                span: Default::default(),
+            },
+        )
+        .expect("Style should compile");
+        assert!(scoped.code.contains(".foo .bar[data-v-abcd1234]"));
+        assert_eq!(scoped.scope_id.as_deref(), Some("data-v-abcd1234"));
+
+        let not_scoped = compile_style(
+            ".foo .bar { color: red }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                lang: "css",
+                preprocessor: None,
+                is_scoped: false,
+                is_module: false,
+                is_prod: false,
+                minify: false,
+                is_cancelled: None,
+                source_map: None,
+                span: Default::default(),
+            },
+        )
+        .expect("Style should compile");
+        assert!(not_scoped.code.contains(".foo .bar"));
+        assert!(!not_scoped.code.contains("data-v-abcd1234"));
+        assert_eq!(not_scoped.scope_id, None);
+        assert_eq!(not_scoped.module_mapping, None);
+    }
+
+    #[test]
+    fn compile_style_module() {
+        let compiled = compile_style(
+            ".red { color: red } .foo .red { color: blue }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                lang: "css",
+                preprocessor: None,
+                is_scoped: false,
+                is_module: true,
+                is_prod: false,
+                minify: false,
+                is_cancelled: None,
+                source_map: None,
+                span: Default::default(),
+            },
+        )
+        .expect("Style should compile");
+
+        let module_mapping = compiled.module_mapping.expect("Mapping should be present");
+        let local_names: Vec<&str> = module_mapping
+            .iter()
+            .map(|(local_name, _)| local_name.as_str())
+            .collect();
+        assert_eq!(local_names, vec!["foo", "red"]);
+
+        // Class names are renamed in the code
+        for (local_name, hashed_name) in module_mapping.iter() {
+            assert_ne!(local_name, hashed_name);
+            assert!(compiled.code.contains(&format!(".{}", hashed_name)));
+        }
+    }
+
+    #[test]
+    fn rewrite_css_vars_dev_and_prod() {
+        assert_eq!(
+            rewrite_css_vars(
+                ".foo { color: v-bind(color); width: v-bind( 'size.width' ) }",
+                "abcd1234",
+                false
+            ),
+            ".foo { color: var(--abcd1234-color); width: var(--abcd1234-size\\.width) }"
+        );
+
+        // Nested parens and quotes
+        assert_eq!(
+            rewrite_css_vars(".foo { height: v-bind(\"calc(h)\") }", "abcd1234", false),
+            ".foo { height: var(--abcd1234-calc\\(h\\)) }"
+        );
+
+        // Name is hashed in PROD
+        let prod = rewrite_css_vars(".foo { color: v-bind(color) }", "abcd1234", true);
+        assert!(prod.starts_with(".foo { color: var(--"));
+        assert!(!prod.contains("v-bind"));
+        assert!(!prod.contains("abcd1234-color"));
+
+        // Not a `v-bind()` call, empty or unclosed
+        for input in [
+            ".v-bind { color: red }",
+            ".foo { color: v-bind() }",
+            ".foo { color: v-bind(color }",
+        ] {
+            assert_eq!(rewrite_css_vars(input, "abcd1234", false), input);
+        }
+    }
+
+    #[test]
+    fn compile_style_css_vars() {
+        let compiled = compile_style(
+            ".foo { color: v-bind(color) }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                lang: "css",
+                preprocessor: None,
+                is_scoped: true,
+                is_module: false,
+                is_prod: false,
+                minify: false,
+                is_cancelled: None,
+                source_map: None,
+                span: Default::default(),
+            },
+        )
+        .expect("Style should compile");
+
+        assert!(compiled.code.contains("var(--abcd1234-color)"));
+    }
+
+    #[test]
+    fn compile_style_preprocessed() {
+        // Pretend that `scss` nesting is compiled
+        let preprocessor = |lang: &str, input: &str| {
+            if lang != "scss" {
+                return Err(StyleError {
+                    message: format!("Unsupported lang {}", lang),
+                });
+            }
+
+            Ok(PreprocessedStyle {
+                code: input
+                    .replace(".foo { .bar", ".foo .bar")
+                    .replacen('}', "", 1),
+                map: None,
+            })
+        };
+
+        let compile = |lang| {
+            compile_style(
+                ".foo { .bar { color: red } }",
+                CompileStyleOptions {
+                    scope_id: "abcd1234",
+                    lang,
+                    preprocessor: Some(&preprocessor),
+                    is_scoped: true,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let compiled = compile("scss").expect("Style should compile");
+        assert!(compiled.code.contains(".foo .bar[data-v-abcd1234]"));
+
+        let err = compile("less").expect_err("Unsupported lang is an error");
+        assert_eq!(err.message, "Unsupported lang less");
+
+        // No preprocessor
+        let err = compile_style(
+            ".foo { .bar { color: red } }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                lang: "scss",
+                ..Default::default()
+            },
+        )
+        .expect_err("Preprocessor is required");
+        assert!(err.message.contains("<style lang=\"scss\">"));
+    }
+
+    #[test]
+    fn compile_style_preprocessed_deprecated_deep() {
+        // Pretend that `scss` line comments are stripped and `@include` is expanded
+        let preprocessor = |_: &str, input: &str| {
+            Ok(PreprocessedStyle {
+                code: input
+                    .replace("// a >>> b\n", "")
+                    .replace("@include deep;", ".foo >>> .bar { color: red }"),
+                map: None,
+            })
+        };
+
+        let span = Span {
+            lo: BytePos(10),
+            hi: BytePos(100),
+            ctxt: Default::default(),
+        };
+        let compile = |input| {
+            compile_style(
+                input,
+                CompileStyleOptions {
+                    scope_id: "abcd1234",
+                    lang: "scss",
+                    preprocessor: Some(&preprocessor),
+                    is_scoped: true,
+                    minify: true,
+                    span,
+                    ..Default::default()
+                },
+            )
+            .expect("Style should compile")
+        };
+
+        // A comment is not a selector
+        let compiled = compile("// a >>> b\n.foo { color: red }");
+        assert_eq!(compiled.code, ".foo[data-v-abcd1234]{color:red}");
+        assert!(compiled.diagnostics.is_empty());
+
+        // A selector from a mixin is rewritten and reported for the whole block
+        let compiled = compile("@include deep;");
+        assert_eq!(compiled.code, ".foo[data-v-abcd1234] .bar{color:red}");
+        assert!(matches!(
+            compiled.diagnostics[..],
+            [Diagnostic {
+                kind: DiagnosticKind::DeprecatedDeepSelector(_),
+                span: diagnostic_span,
+            }] if diagnostic_span == span
+        ));
+    }
+
+    #[test]
+    fn compile_style_minify() {
+        let compile = |is_scoped, minify| {
+            compile_style(
+                "/* comment */\n.foo {\n  margin-top: 0;\n  margin-right: 0;\n  margin-bottom: 0;\n  margin-left: 0;\n}\n",
+                CompileStyleOptions {
+                    scope_id: "abcd1234",
+                    is_scoped,
+                    minify,
+                    ..Default::default()
+                },
+            )
+            .expect("Style should compile")
+            .code
+        };
+
+        assert_eq!(compile(false, true), ".foo{margin:0}");
+        assert_eq!(compile(true, true), ".foo[data-v-abcd1234]{margin:0}");
+
+        let not_minified = compile(false, false);
+        assert!(not_minified.contains("margin-top: 0;"));
+    }
+
+    #[test]
+    fn compile_style_source_map() {
+        let compile = |source_map| {
+            compile_style(
+                ".foo { color: red }",
+                CompileStyleOptions {
+                    scope_id: "abcd1234",
+                    is_scoped: true,
+                    source_map,
+                    ..Default::default()
+                },
+            )
+            .expect("Style should compile")
+        };
+
+        let compiled = compile(Some(StyleSourceMapOptions {
+            filename: "src/App.vue",
+            line: 4,
+            column: 7,
+        }));
+        let map = compiled.map.expect("Source map should be present");
+        assert!(map.contains("src/App.vue"));
+        assert!(map.contains("\"mappings\""));
+        assert!(compiled.code.contains(".foo[data-v-abcd1234]"));
+
+        assert_eq!(compile(None).map, None);
+    }
+
+    #[test]
+    fn compile_style_source_map_after_rewrites() {
+        let input = ".a >>> .b { color: v-bind(color) } .c { color: red }";
+        let compiled = compile_style(
+            input,
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                is_scoped: true,
+                source_map: Some(StyleSourceMapOptions {
+                    filename: "src/App.vue",
+                    line: 1,
+                    column: 7,
+                }),
+                ..Default::default()
+            },
+        )
+        .expect("Style should compile");
+
+        let map = compiled.map.expect("Source map should be present");
+        let map =
+            parcel_sourcemap::SourceMap::from_json("/", &map).expect("Source map should be valid");
+        let original_positions: Vec<(u32, u32)> = map
+            .get_mappings()
+            .into_iter()
+            .filter_map(|mapping| mapping.original)
+            .map(|original| (original.original_line, original.original_column))
+            .collect();
+
+        // `.c` is after both rewrites on the same line, but still points to the original code
+        let c_column = 7 + input.find(".c").unwrap() as u32;
+        assert!(original_positions.contains(&(1, 7)));
+        assert!(original_positions.contains(&(1, c_column)));
+        assert!(map
+            .get_source_content(0)
+            .expect("Source content should be present")
+            .ends_with(input));
+    }
+
+    #[test]
+    fn rewrite_deprecated_deep_selectors() {
+        macro_rules! test {
+            ($input: expr, $expected: expr, $expected_fixes: expr) => {
+                let mut diagnostics = Vec::new();
+                let span = Span {
+                    lo: BytePos(100),
+                    hi: BytePos(100 + $input.len() as u32),
+                    ctxt: Default::default(),
+                };
+
+                let rewritten = rewrite_deprecated_deep($input, span, &mut diagnostics);
+                assert_eq!(rewritten, $expected);
+
+                // The span is the range to replace
+                let fixes: Vec<(u32, u32, &str)> = diagnostics
+                    .iter()
+                    .map(|diagnostic| {
+                        let DiagnosticKind::DeprecatedDeepSelector(ref replacement) =
+                            diagnostic.kind
+                        else {
+                            panic!("Unexpected diagnostic {:?}", diagnostic.kind)
+                        };
+                        (
+                            diagnostic.span.lo.0,
+                            diagnostic.span.hi.0,
+                            replacement.as_ref(),
+                        )
+                    })
+                    .collect();
+                assert_eq!(fixes, $expected_fixes);
+            };
+        }
+
+        test!(
+            ".foo >>> .bar { color: red }",
+            ".foo :deep(.bar) { color: red }",
+            vec![(105, 113, ":deep(.bar)")]
+        );
+        test!(
+            ".foo /deep/ .bar .baz, .qux >>> a { color: red }",
+            ".foo :deep(.bar .baz), .qux :deep(a) { color: red }",
+            vec![(105, 121, ":deep(.bar .baz)"), (128, 133, ":deep(a)")]
+        );
+        test!(
+            ".foo ::v-deep .bar {} ::v-deep(.baz) {} .qux::v-deep {}",
+            ".foo :deep(.bar) {} :deep(.baz) {} .qux:deep() {}",
+            vec![
+                (105, 118, ":deep(.bar)"),
+                (122, 130, ":deep"),
+                (144, 152, ":deep()")
+            ]
+        );
+
+        // Nested rules, at-rules, declarations and comments are not selectors
+        test!(
+            "@media (x) { .a >>> .b { background: url('/deep/a.png') } } /* >>> */ .c {}",
+            "@media (x) { .a :deep(.b) { background: url('/deep/a.png') } } /* >>> */ .c {}",
+            vec![(116, 122, ":deep(.b)")]
+        );
+        test!(".foo { color: red }", ".foo { color: red }", vec![]);
+    }
+
+    #[test]
+    fn compile_style_deprecated_deep() {
+        let compiled = compile_style(
+            ".foo >>> .bar { color: red }",
+            CompileStyleOptions {
+                scope_id: "abcd1234",
+                is_scoped: true,
+                minify: true,
+                ..Default::default()
+            },
+        )
+        .expect("Style should compile");
+
+        assert_eq!(compiled.code, ".foo[data-v-abcd1234] .bar{color:red}");
+        assert_eq!(compiled.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn find_child_root_selectors_unused_classes() {
+        let input = ".foo .bar {}\n.baz::before, .child-root {}\n.foo :deep(.qux) {}\n@media (x) { div.nested {} }";
+        let span = Span {
+            lo: BytePos(100),
+            hi: BytePos(100 + input.len() as u32),
+            ctxt: Default::default(),
+        };
+        let template_classes = vec![FervidAtom::from("foo"), FervidAtom::from("baz")];
+
+        let diagnostics = find_child_root_selectors(input, span, &template_classes);
+        let notes: Vec<(u32, u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let DiagnosticKind::ScopedSelectorChildRoot(ref class_name) = diagnostic.kind
+                else {
+                    panic!("Unexpected diagnostic {:?}", diagnostic.kind)
+                };
+                (
+                    diagnostic.span.lo.0,
+                    diagnostic.span.hi.0,
+                    class_name.as_ref(),
+                )
+            })
+            .collect();
+
+        // `.baz::before` is used in the template, `:deep(.qux)` is not scoped
+        assert_eq!(
+            notes,
+            vec![
+                (100, 109, "bar"),
+                (113, 138, "child-root"),
+                (175, 185, "nested")
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_styles_dedupe() {
+        let merged = merge_styles(
+            [
+                (".foo { color: red } .bar { padding: 0 }", None),
+                (".foo { color: red } .baz { margin: 0 }", None),
+                (".baz { margin: 0 }", None),
+            ],
+            true,
+        )
+        .expect("Styles should merge");
+
+        // Only the last occurrence of an identical rule is kept
+        assert_eq!(
+            merged.code,
+            ".bar{padding:0}\n.foo{color:red}\n.baz{margin:0}\n"
+        );
+        assert_eq!(
+            merged.offsets,
+            vec![
+                MergedStyleOffset { offset: 0, line: 0 },
+                MergedStyleOffset {
+                    offset: 16,
+                    line: 1
+                },
+                MergedStyleOffset {
+                    offset: 32,
+                    line: 2
+                },
+            ]
+        );
+        assert_eq!(merged.map, None);
+    }
+
+    #[test]
+    fn merge_styles_hoists_statement_rules() {
+        let merged = merge_styles(
+            [
+                ("@import \"a.css\"; @layer a, b; .foo { color: red }", None),
+                (
+                    "@import \"b.css\"; @import \"a.css\"; @layer a, b; .bar { padding: 0 }",
+                    None,
+                ),
+            ],
+            true,
+        )
+        .expect("Styles should merge");
+
+        // Statement at-rules go first, and only the first occurrence is kept
+        assert_eq!(
+            merged.code,
+            "@import \"a.css\";\n@import \"b.css\";\n@layer a,b;\n.foo{color:red}\n.bar{padding:0}\n"
+        );
+        assert_eq!(
+            merged.offsets,
+            vec![
+                MergedStyleOffset {
+                    offset: 46,
+                    line: 3
+                },
+                MergedStyleOffset {
+                    offset: 62,
+                    line: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_styles_hoists_imports_before_namespaces() {
+        let merged = merge_styles(
+            [
+                (
+                    "@namespace svg \"http://www.w3.org/2000/svg\"; .foo { color: red }",
+                    None,
+                ),
+                ("@import \"a.css\"; .bar { padding: 0 }", None),
+            ],
+            true,
+        )
+        .expect("Styles should merge");
+
+        // `@import` after `@namespace` would be ignored
+        assert_eq!(
+            merged.code,
+            "@import \"a.css\";\n@namespace svg \"http://www.w3.org/2000/svg\";\n.foo{color:red}\n.bar{padding:0}\n"
+        );
+    }
+
+    #[test]
+    fn merge_styles_keeps_layer_order() {
+        let merged = merge_styles(
+            [
+                ("@layer b { .foo { color: red } }", None),
+                ("@layer a, b; .bar { padding: 0 }", None),
+            ],
+            true,
+        )
+        .expect("Styles should merge");
+
+        // Hoisting `@layer a, b;` would put `a` before `b`
+        assert_eq!(
+            merged.code,
+            "@layer b{.foo{color:red}}\n@layer a,b;\n.bar{padding:0}\n"
+        );
+        assert_eq!(
+            merged.offsets,
+            vec![
+                MergedStyleOffset { offset: 0, line: 0 },
+                MergedStyleOffset {
+                    offset: 26,
+                    line: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_styles_source_map() {
+        let compile = |input: &str, line: u32| {
+            compile_style(
+                input,
+                CompileStyleOptions {
+                    scope_id: "abcd1234",
+                    is_scoped: true,
+                    minify: true,
+                    source_map: Some(StyleSourceMapOptions {
+                        filename: "src/App.vue",
+                        line,
+                        column: 0,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .expect("Style should compile")
+        };
+        let first = compile(".foo { color: red }", 3);
+        let second = compile(".bar { padding: 0 }", 7);
+
+        let merged = merge_styles(
+            [
+                (first.code.as_str(), first.map.as_deref()),
+                (second.code.as_str(), second.map.as_deref()),
+            ],
+            true,
+        )
+        .expect("Styles should merge");
+
+        assert_eq!(
+            merged.code,
+            ".foo[data-v-abcd1234]{color:red}\n.bar[data-v-abcd1234]{padding:0}\n"
+        );
+        assert_eq!(merged.offsets[1].line, 1);
+        let map = merged.map.expect("Source map should be present");
+        assert!(map.contains("src/App.vue"));
+    }
+
+    #[test]
+    fn transform_style_scoped_cancelled() {
+        let is_cancelled = || true;
+
+        test_output!(
+            ".foo :deep(.bar) { background: yellow }",
+            Err(()),
+            TransformOptions {
+                is_cancelled: Some(&is_cancelled),
+                ..minify_yes!()
+            }
+        );
+    }
 }
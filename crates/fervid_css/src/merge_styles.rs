@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+
+use lightningcss::{
+    rules::{
+        layer::{LayerBlockRule, LayerName},
+        CssRule,
+    },
+    stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+    traits::ToCss,
+};
+use parcel_sourcemap::SourceMap;
+
+use crate::{StyleError, TransformError};
+
+/// Compiled styles of multiple `<style>` blocks merged by [`merge_styles`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MergedStyle {
+    /// The merged CSS
+    pub code: String,
+    /// Combined source map in JSON format, present when every block had a source map
+    pub map: Option<String>,
+    /// Where each block starts in the merged CSS, in order of the blocks
+    pub offsets: Vec<MergedStyleOffset>,
+}
+
+/// Start of a block in the [`MergedStyle`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MergedStyleOffset {
+    /// Byte offset in the merged CSS
+    pub offset: usize,
+    /// Zero-based line in the merged CSS and in the combined source map
+    pub line: u32,
+}
+
+/// Merges the compiled `<style>` blocks, given as their code and source map,
+/// into a single stylesheet. The blocks are kept in order and each starts on a new line.
+///
+/// Identical rules are deduplicated, and only the last occurrence is kept,
+/// because the earlier ones cannot win the cascade against it anyway.
+/// A block which only had duplicate rules is empty.
+///
+/// Statement at-rules (`@import`, `@namespace` and `@layer a, b;`) are only valid
+/// or meaningful before the other rules, so they are hoisted to the start of the merged CSS,
+/// `@import` first, then `@namespace`, then `@layer`, each in order of appearance.
+/// Only the first occurrence of a duplicate is kept.
+/// A `@layer a, b;` after a `@layer b { ... }` block stays in place,
+/// because hoisting it would change the order of the layers.
+/// `@charset` is dropped when parsing, the merged CSS is always UTF-8.
+///
+/// The source maps of the blocks are shifted by their line offsets and combined,
+/// so that the merged CSS still points to the `<style>` blocks in the SFC.
+pub fn merge_styles<'s>(
+    styles: impl IntoIterator<Item = (&'s str, Option<&'s str>)>,
+    minify: bool,
+) -> Result<MergedStyle, StyleError> {
+    let mut stylesheets = Vec::new();
+    for (code, map) in styles {
+        let stylesheet =
+            StyleSheet::parse(code, ParserOptions::default()).map_err(TransformError::from)?;
+        stylesheets.push((stylesheet, code, map));
+    }
+
+    // Rules are compared by their minified code
+    let rule_keys: Vec<Vec<Option<String>>> = stylesheets
+        .iter()
+        .map(|(stylesheet, _, _)| {
+            stylesheet
+                .rules
+                .0
+                .iter()
+                .map(|rule| {
+                    rule.to_css_string(PrinterOptions {
+                        minify: true,
+                        ..Default::default()
+                    })
+                    .ok()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut last_occurrences: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (block_idx, block_keys) in rule_keys.iter().enumerate() {
+        for (rule_idx, key) in block_keys.iter().enumerate() {
+            if let Some(key) = key {
+                last_occurrences.insert(key, (block_idx, rule_idx));
+            }
+        }
+    }
+
+    let mut hoisted_imports = Vec::new();
+    let mut hoisted_namespaces = Vec::new();
+    let mut hoisted_layers = Vec::new();
+    let mut hoisted_keys: HashSet<&str> = HashSet::new();
+    let mut declared_layers: HashSet<String> = HashSet::new();
+    for (block_idx, (stylesheet, _, _)) in stylesheets.iter_mut().enumerate() {
+        let rules = std::mem::take(&mut stylesheet.rules.0);
+        for (rule_idx, rule) in rules.into_iter().enumerate() {
+            let key = rule_keys[block_idx][rule_idx].as_deref();
+            if is_statement_rule(&rule) {
+                if key.map_or(false, |key| hoisted_keys.contains(key)) {
+                    continue;
+                }
+
+                match rule {
+                    // The layers are already ordered by an earlier `@layer b { ... }`
+                    CssRule::LayerStatement(ref layer_statement)
+                        if layer_statement
+                            .names
+                            .iter()
+                            .any(|name| declared_layers.contains(&layer_name_key(name))) =>
+                    {
+                        stylesheet.rules.0.push(rule);
+                        continue;
+                    }
+                    CssRule::Import(_) => hoisted_imports.push(rule),
+                    CssRule::Namespace(_) => hoisted_namespaces.push(rule),
+                    _ => hoisted_layers.push(rule),
+                }
+
+                if let Some(key) = key {
+                    hoisted_keys.insert(key);
+                }
+            } else {
+                if let CssRule::LayerBlock(LayerBlockRule {
+                    name: Some(ref name),
+                    ..
+                }) = rule
+                {
+                    declared_layers.insert(layer_name_key(name));
+                }
+
+                if key.map_or(true, |key| last_occurrences[key] == (block_idx, rule_idx)) {
+                    stylesheet.rules.0.push(rule);
+                }
+            }
+        }
+    }
+
+    let mut result = MergedStyle::default();
+    let mut merged_map = Some(SourceMap::new("/"));
+
+    // Hoisted rules are not mapped, they may come from different blocks
+    for rule in hoisted_imports
+        .into_iter()
+        .chain(hoisted_namespaces)
+        .chain(hoisted_layers)
+    {
+        let rule_code = rule
+            .to_css_string(PrinterOptions {
+                minify,
+                ..Default::default()
+            })
+            .map_err(TransformError::from)?;
+        result.code.push_str(&rule_code);
+        result.code.push('\n');
+    }
+    let mut line = result.code.matches('\n').count() as u32;
+
+    for (block_idx, (stylesheet, code, map)) in stylesheets.into_iter().enumerate() {
+        // The block is mapped to its compiled code, and then chained to the block map
+        let mut source_map = match (merged_map.is_some(), map) {
+            (true, Some(_)) => {
+                let mut source_map = SourceMap::new("/");
+                let source_index = source_map.add_source(&format!("style{}.css", block_idx));
+                // The source was just added, setting its content cannot fail
+                let _ = source_map.set_source_content(source_index as usize, code);
+                Some(source_map)
+            }
+            _ => None,
+        };
+
+        let to_css_result = stylesheet
+            .to_css(PrinterOptions {
+                minify,
+                source_map: source_map.as_mut(),
+                ..Default::default()
+            })
+            .map_err(TransformError::from)?;
+
+        // A block without a source map leaves the merged CSS without one
+        match (source_map, map) {
+            (Some(mut source_map), Some(map)) => {
+                let mut block_map = SourceMap::from_json("/", map)?;
+                source_map.extends(&mut block_map)?;
+                if let Some(ref mut merged_map) = merged_map {
+                    merged_map.add_sourcemap(&mut source_map, line as i64)?;
+                }
+            }
+            _ => merged_map = None,
+        }
+
+        result.offsets.push(MergedStyleOffset {
+            offset: result.code.len(),
+            line,
+        });
+
+        let mut block_code = to_css_result.code;
+        if !block_code.is_empty() && !block_code.ends_with('\n') {
+            block_code.push('\n');
+        }
+        line += block_code.matches('\n').count() as u32;
+        result.code.push_str(&block_code);
+    }
+
+    result.map = match merged_map {
+        Some(ref mut merged_map) if !result.offsets.is_empty() => Some(merged_map.to_json(None)?),
+        _ => None,
+    };
+
+    Ok(result)
+}
+
+/// Whether the rule is a statement at-rule, which must precede the other rules
+fn is_statement_rule(rule: &CssRule) -> bool {
+    matches!(
+        rule,
+        CssRule::Import(_) | CssRule::Namespace(_) | CssRule::LayerStatement(_)
+    )
+}
+
+/// Dotted name of a layer, e.g. `a.b`
+fn layer_name_key(name: &LayerName) -> String {
+    name.to_css_string(PrinterOptions::default())
+        .unwrap_or_default()
+}
@@ -0,0 +1,43 @@
+use std::fmt::Debug;
+
+use crate::StyleError;
+
+/// Output of a [`StylePreprocessor`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PreprocessedStyle {
+    /// The compiled CSS
+    pub code: String,
+    /// Source map of the compiled CSS in JSON format
+    pub map: Option<String>,
+}
+
+/// Compiles the `<style>` blocks written in other languages than CSS,
+/// e.g. `<style lang="scss">`, before they are scoped or turned into CSS modules.
+///
+/// Build tools implement it to plug in their preprocessors, e.g. `grass` or a Node.js `sass`.
+/// A closure `Fn(lang, input) -> Result<PreprocessedStyle, StyleError>` is a preprocessor as well.
+pub trait StylePreprocessor {
+    /// Compiles `input` written in `lang` (e.g. `scss`, `less` or `styl`) to CSS.
+    /// An unsupported `lang` is an error
+    fn preprocess(&self, lang: &str, input: &str) -> Result<PreprocessedStyle, StyleError>;
+}
+
+impl<F> StylePreprocessor for F
+where
+    F: Fn(&str, &str) -> Result<PreprocessedStyle, StyleError>,
+{
+    fn preprocess(&self, lang: &str, input: &str) -> Result<PreprocessedStyle, StyleError> {
+        self(lang, input)
+    }
+}
+
+impl Debug for dyn StylePreprocessor + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StylePreprocessor")
+    }
+}
+
+/// Whether the `<style>` block needs a [`StylePreprocessor`], i.e. it is not written in CSS
+pub fn needs_preprocessor(lang: &str) -> bool {
+    !lang.is_empty() && lang != "css"
+}
@@ -3,7 +3,7 @@ use lightningcss::{
     error::{Error, MinifyErrorKind, ParserError, PrinterErrorKind},
     printer::Printer,
     properties::custom::TokenOrValue,
-    rules::CssRule,
+    rules::{style::StyleRule, CssRule, CssRuleList},
     selector::{Component, PseudoClass, Selector},
     stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet, ToCssResult},
     traits::ParseWithOptions,
@@ -15,6 +15,8 @@ pub struct TransformOptions<'a> {
     pub parse: ParserOptions<'a, 'a>,
     pub minify: Option<MinifyOptions>,
     pub to_css: PrinterOptions<'a>,
+    /// Checked in between the phases and style rules to abort a stale transformation
+    pub is_cancelled: Option<&'a dyn Fn() -> bool>,
 }
 
 #[derive(Debug)]
@@ -22,6 +24,8 @@ pub enum TransformError<'i> {
     ParserError(Error<ParserError<'i>>),
     MinifyError(Error<MinifyErrorKind>),
     PrinterError(Error<PrinterErrorKind>),
+    /// Transformation was aborted using [`TransformOptions::is_cancelled`]
+    Cancelled,
 }
 
 pub struct Transformer<'i> {
@@ -70,18 +74,30 @@ impl<'i> Transformer<'i> {
             parse,
             minify,
             to_css,
+            is_cancelled,
         } = options;
 
+        let is_cancelled = || is_cancelled.map_or(false, |is_cancelled| is_cancelled());
+
         let mut stylesheet = StyleSheet::parse(self.input, parse)?;
 
         let suffix = self.scope.into();
 
-        transform_cached_strategy(&mut stylesheet, &suffix, &mut self.cache);
+        if !transform_cached_strategy(&mut stylesheet, &suffix, &mut self.cache, &is_cancelled) {
+            return Err(TransformError::Cancelled);
+        }
 
         if let Some(minify_options) = minify {
+            if is_cancelled() {
+                return Err(TransformError::Cancelled);
+            }
             stylesheet.minify(minify_options)?;
         }
 
+        if is_cancelled() {
+            return Err(TransformError::Cancelled);
+        }
+
         Ok(stylesheet.to_css(to_css)?)
     }
 }
@@ -90,21 +106,33 @@ impl<'i> Transformer<'i> {
 /// that `Visitor` has at least the same lifetime as its input.
 /// And we need this guarantee to satisfy Rust's lifetime checks,
 /// simply because we are creating new `String`s to parse and attach
-/// the contents of `:deep()`.
+/// the contents of `:deep()`, `:slotted()` and `:global()`.
+///
+/// - `:deep(.bar)` moves the scope attribute before it: `.foo :deep(.bar)` -> `.foo[data-v-abcd1234] .bar`;
+/// - `:slotted(.bar)` is scoped by the slotted attribute instead: `.bar[data-v-abcd1234-s]`;
+/// - `:global(.bar)` replaces the whole selector and is not scoped: `.foo :global(.bar)` -> `.bar`.
 ///
 /// Another method could have been using a static set,
 /// but this sounds like even more effort, and potentially dangerous in WASM.
+///
+/// Returns `false` when `is_cancelled` reported cancellation during the collect phase.
+/// The write phase is not interrupted, because it relies on the collect phase being complete.
 fn transform_cached_strategy<'i>(
     stylesheet: &mut StyleSheet<'i, '_>,
     suffix: &CowRcStr<'i>,
     cache: &'i mut Vec<String>,
-) {
+    is_cancelled: &impl Fn() -> bool,
+) -> bool {
+    // Style rules nested in `@media` and other at-rules are scoped as well
+    let mut style_rules = Vec::new();
+    collect_style_rules(&mut stylesheet.rules, &mut style_rules);
+
     // Collect phase, because we cannot write to cache and reference it at the same time
     // Both phases must be identical in items they visit, otherwise `cache` will get out-of-bounds
-    for rule in stylesheet.rules.0.iter_mut() {
-        let CssRule::Style(style) = rule else {
-            continue;
-        };
+    for style in style_rules.iter_mut() {
+        if is_cancelled() {
+            return false;
+        }
 
         for selector in style.selectors.0.iter_mut() {
             let mut iter = selector.iter_raw_match_order();
@@ -114,7 +142,7 @@ fn transform_cached_strategy<'i>(
                     continue;
                 };
 
-                if *name != "deep" {
+                if scoped_pseudo_class(name).is_none() {
                     continue;
                 }
 
@@ -134,6 +162,11 @@ fn transform_cached_strategy<'i>(
         }
     }
 
+    // The last item of cache is the attribute for `:slotted()`
+    cache.push(format!("{}-s", suffix));
+    let cache: &'i Vec<String> = cache;
+    let slotted_suffix: CowRcStr<'i> = cache[cache.len() - 1].as_str().into();
+
     let mut ptr: usize = 0;
 
     macro_rules! to_append {
@@ -145,6 +178,15 @@ fn transform_cached_strategy<'i>(
         };
     }
 
+    macro_rules! to_append_slotted {
+        () => {
+            Component::AttributeInNoNamespaceExists {
+                local_name: slotted_suffix.clone().into(),
+                local_name_lower: slotted_suffix.clone().into(),
+            }
+        };
+    }
+
     macro_rules! is_combinator {
         ($what: ident) => {
             matches!(
@@ -160,11 +202,7 @@ fn transform_cached_strategy<'i>(
     }
 
     // Write phase, cached contents of `:deep`s will be parsed as selectors
-    for rule in stylesheet.rules.0.iter_mut() {
-        let CssRule::Style(style) = rule else {
-            continue;
-        };
-
+    for style in style_rules.iter_mut() {
         for selector in style.selectors.0.iter_mut() {
             let selector_len = selector.len();
             let iter = &mut selector.iter_mut_raw_match_order().enumerate();
@@ -174,6 +212,7 @@ fn transform_cached_strategy<'i>(
             let mut deep_without_selector = false;
             let mut sequence_start = 0;
             let mut previous_combinator = None;
+            let mut slotted_or_global = None;
 
             while let Some((idx, part)) = iter.next() {
                 // Find the start of the sequence
@@ -188,8 +227,18 @@ fn transform_cached_strategy<'i>(
                     continue;
                 };
 
-                if *name != "deep" {
+                let Some(pseudo_class) = scoped_pseudo_class(name) else {
                     continue;
+                };
+
+                // Get the contents of the pseudo-class from cache
+                let contents = &cache[ptr];
+                ptr += 1;
+
+                // `:slotted()` and `:global()` are applied when the iteration is over
+                if !matches!(pseudo_class, ScopedPseudoClass::Deep) {
+                    slotted_or_global = Some((pseudo_class, idx, contents));
+                    break;
                 }
 
                 // The algorithm:
@@ -256,9 +305,7 @@ fn transform_cached_strategy<'i>(
                 // 3. Insert the parsed contents of `:deep` into the vec at `sequence_start`.
                 //    Point 2 explains why it works.
 
-                // Get the contents of `:deep` from cache
-                let deep_contents = &cache[ptr];
-                ptr += 1;
+                let deep_contents = contents;
 
                 // Step 1. Replace the contents
                 *part = to_append!();
@@ -321,6 +368,43 @@ fn transform_cached_strategy<'i>(
                 break;
             }
 
+            match slotted_or_global {
+                // `.foo :global(.bar)` -> `.bar`
+                Some((ScopedPseudoClass::Global, _, contents)) => {
+                    if let Ok(parsed) =
+                        Selector::parse_string_with_options(contents, Default::default())
+                    {
+                        *selector = parsed;
+                    }
+                    continue;
+                }
+
+                // `.foo :slotted(.bar .baz)` -> `.foo .bar .baz[data-v-smth-s]`.
+                // `:slotted` is replaced by the first component of its contents,
+                // and the rest of the components are inserted right after it
+                Some((ScopedPseudoClass::Slotted, idx, contents)) => {
+                    let Ok(mut parsed) =
+                        Selector::parse_string_with_options(contents, Default::default())
+                    else {
+                        continue;
+                    };
+                    parsed.append(to_append_slotted!());
+
+                    let mut slotted_components = parsed.iter_raw_match_order().cloned();
+                    let (Some(first), Some(slotted)) = (
+                        slotted_components.next(),
+                        selector.iter_mut_raw_match_order().nth(idx),
+                    ) else {
+                        continue;
+                    };
+                    *slotted = first;
+                    selector.insert_raw_multiple(idx + 1, slotted_components.collect());
+                    continue;
+                }
+
+                _ => {}
+            }
+
             // Deep without selector already did its job
             if deep_without_selector {
                 continue;
@@ -341,6 +425,43 @@ fn transform_cached_strategy<'i>(
             }
         }
     }
+
+    true
+}
+
+/// Vue pseudo-classes which change how a selector is scoped
+#[derive(Clone, Copy)]
+pub(crate) enum ScopedPseudoClass {
+    Deep,
+    Slotted,
+    Global,
+}
+
+pub(crate) fn scoped_pseudo_class(name: &str) -> Option<ScopedPseudoClass> {
+    match name {
+        "deep" => Some(ScopedPseudoClass::Deep),
+        "slotted" => Some(ScopedPseudoClass::Slotted),
+        "global" => Some(ScopedPseudoClass::Global),
+        _ => None,
+    }
+}
+
+/// Collects the style rules in the order of appearance,
+/// including the ones nested in `@media`, `@supports`, `@container` and `@layer`
+pub(crate) fn collect_style_rules<'a, 'i>(
+    rules: &'a mut CssRuleList<'i>,
+    out: &mut Vec<&'a mut StyleRule<'i>>,
+) {
+    for rule in rules.0.iter_mut() {
+        match rule {
+            CssRule::Style(style) => out.push(style),
+            CssRule::Media(media) => collect_style_rules(&mut media.rules, out),
+            CssRule::Supports(supports) => collect_style_rules(&mut supports.rules, out),
+            CssRule::Container(container) => collect_style_rules(&mut container.rules, out),
+            CssRule::LayerBlock(layer) => collect_style_rules(&mut layer.rules, out),
+            _ => {}
+        }
+    }
 }
 
 impl<'i> From<Error<ParserError<'i>>> for TransformError<'i> {
@@ -17,7 +17,7 @@ const compilerName = ref('fervid')
 `
 
 test('should work', () => {
-  expect(compileSync(HELLO_WORLD)).toMatchInlineSnapshot(`
+  expect(compileSync(HELLO_WORLD).code).toMatchInlineSnapshot(`
     "import { ref } from 'vue';
     import { createElementBlock as _createElementBlock, openBlock as _openBlock, toDisplayString as _toDisplayString } from \\"vue\\";
     export default {
@@ -36,7 +36,7 @@ test('should work', () => {
     "
   `)
 
-  expect(compileSync(HELLO_WORLD, { isProd: true })).toMatchInlineSnapshot(`
+  expect(compileSync(HELLO_WORLD, { isProd: true }).code).toMatchInlineSnapshot(`
     "import { ref } from 'vue';
     import { createElementBlock as _createElementBlock, openBlock as _openBlock, toDisplayString as _toDisplayString } from \\"vue\\";
     export default {
@@ -50,3 +50,19 @@ test('should work', () => {
     "
   `)
 })
+
+test('should return the compiled styles', () => {
+  const input = `${HELLO_WORLD}<style scoped>.simple { color: red }</style>`
+
+  expect(compileSync(input).styles).toEqual([])
+  expect(compileSync(input, { isProd: true, scopeId: 'abcd1234', compileStyles: true, minifyStyles: true }).styles).toEqual([
+    { code: '.simple[data-v-abcd1234]{color:red}', scoped: true },
+  ])
+})
+
+test('should preserve JSX when asked', () => {
+  const input = `<script setup lang="tsx">const Foo = () => <div>{42}</div></script>`
+
+  expect(() => compileSync(input)).toThrow()
+  expect(compileSync(input, { isProd: false, preserveJsx: true }).code).toContain('<div>{42}</div>')
+})
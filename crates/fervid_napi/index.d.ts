@@ -5,5 +5,116 @@
 
 export interface CompileSyncOptions {
   isProd: boolean
+  /** Path to the SFC, used for `__file` and `__name` in DEV */
+  filename?: string
+  /** Scope ID without `data-v-`, used for naming CSS variables */
+  scopeId?: string
+  /** Cache the event handlers in the render function */
+  cacheHandlers?: boolean
+  /** Hoist the static nodes and constant props out of the render function */
+  hoistStatic?: boolean
+  /** Keep a static `style` attribute as a string instead of parsing it into an object */
+  keepStyleString?: boolean
+  /** Generate the `ssrRender` function for server-side rendering instead of `render` */
+  ssr?: boolean
+  /** Emit the hot module replacement code in DEV, either `"vite"` or `"webpack"` */
+  hmr?: string
+  /** Format of the generated module, either `"esm"` (default) or `"cjs"` */
+  moduleFormat?: string
+  /** Module to import the Vue helpers from, defaults to `"vue"` */
+  runtimeModule?: string
+  /** Keep the template comments, defaults to `true` in DEV and `false` in PROD */
+  comments?: boolean
+  /**
+   * Tag names of the native custom elements, which are not resolved as components.
+   * A name ending with `*` matches by prefix, e.g. `"ion-*"`
+   */
+  customElements?: Array<string>
+  /**
+   * Render the template inline in `setup` instead of a separate `render` function,
+   * defaults to `true` in PROD when `<script setup>` is present
+   */
+  inlineTemplate?: boolean
+  /** Emit the minified code instead of the pretty-printed one */
+  minify?: boolean
+  /** Escape the non-ASCII characters in strings */
+  asciiOnly?: boolean
+  /**
+   * ES version of the output, e.g. `"es2015"` or `"esnext"` (default).
+   * The newer syntax is downleveled, e.g. `a?.b` for the targets below `"es2020"`
+   */
+  target?: string
+  /** Omit the semicolon after the last statement of a block */
+  omitLastSemi?: boolean
+  /** Generate the TypeScript declaration of the component, see [`CompileSyncResult::declaration`] */
+  declaration?: boolean
+  /** Names and prefixes of the generated identifiers, to avoid collisions with the user code */
+  identPrefixes?: IdentPrefixesOptions
+  /** Compile the `<style>` blocks and return them in [`CompileSyncResult::styles`] */
+  compileStyles?: boolean
+  /** Minify the compiled styles, independently from `minify` */
+  minifyStyles?: boolean
+  /** Embed the compiled styles in the module and inject them at runtime with this function */
+  styleInjector?: StyleInjectorOptions
+  /**
+   * Merge the compiled styles into [`CompileSyncResult::merged_style`]
+   * and deduplicate the identical rules
+   */
+  mergeStyles?: boolean
+  /**
+   * Keep the JSX of `lang="jsx"` and `lang="tsx"` scripts in the output,
+   * e.g. for `@vue/babel-plugin-jsx`. Without it, JSX in a script is an error
+   */
+  preserveJsx?: boolean
 }
-export function compileSync(source: string, options?: CompileSyncOptions | undefined | null): string
+/** Function which injects the CSS at runtime, called with the CSS string */
+export interface StyleInjectorOptions {
+  /** Name of the function, e.g. `"useStyleTag"` */
+  name: string
+  /**
+   * Module to import the function from, e.g. `"@vueuse/core"`.
+   * Without it, the function is a global provided by the environment
+   */
+  source?: string
+}
+/** The defaults are the same as in the official compiler */
+export interface IdentPrefixesOptions {
+  /** Render context, defaults to `"_ctx"` */
+  ctx?: string
+  /** Render cache, defaults to `"_cache"` */
+  cache?: string
+  /** Prefix of the runtime-resolved components, defaults to `"_component_"` */
+  component?: string
+  /** Prefix of the runtime-resolved directives, defaults to `"_directive_"` */
+  directive?: string
+  /** Prefix of the hoisted values, defaults to `"_hoisted_"` */
+  hoisted?: string
+}
+export interface CompileSyncResult {
+  /** The compiled module */
+  code: string
+  /**
+   * TypeScript declaration of the component (the `.d.ts` content),
+   * present when the `declaration` option was set
+   */
+  declaration?: string
+  /**
+   * The compiled `<style>` blocks in order of appearance,
+   * empty unless the `compile_styles` option was set
+   */
+  styles: Array<CompiledStyleBlock>
+  /**
+   * All the compiled styles merged into one stylesheet,
+   * present when the `merge_styles` option was set
+   */
+  mergedStyle?: string
+}
+export interface CompiledStyleBlock {
+  /** The compiled CSS */
+  code: string
+  /** Whether the block is `<style scoped>` */
+  scoped: boolean
+  /** Name of the CSS module binding, e.g. `$style` for `<style module>` */
+  module?: string
+}
+export function compileSync(source: string, options?: CompileSyncOptions | undefined | null): CompileSyncResult
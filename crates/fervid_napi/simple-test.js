@@ -6,6 +6,6 @@ const input = fs.readFileSync(path.join(process.cwd(), '../crates/fervid/benches
   encoding: 'utf-8',
 })
 
-const compiledCode = compileSync(input)
+const compiledCode = compileSync(input).code
 
 console.log(compiledCode)
@@ -7,14 +7,253 @@ static ALLOC: mimalloc_rust::GlobalMiMalloc = mimalloc_rust::GlobalMiMalloc;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use fervid::compile_sync_naive;
+use fervid::{
+    compile_sync_naive, parse_es_version, CompileOptions, EmitOptions, FervidAtom, HmrTarget,
+    IdentPrefixes, ModuleFormat, StyleInjector, TemplateGenerationMode,
+};
 
 #[napi(object)]
 pub struct CompileSyncOptions {
-    pub is_prod: bool
+    pub is_prod: bool,
+    /// Path to the SFC, used for `__file` and `__name` in DEV
+    pub filename: Option<String>,
+    /// Scope ID without `data-v-`, used for naming CSS variables
+    pub scope_id: Option<String>,
+    /// Cache the event handlers in the render function
+    pub cache_handlers: Option<bool>,
+    /// Hoist the static nodes and constant props out of the render function
+    pub hoist_static: Option<bool>,
+    /// Keep a static `style` attribute as a string instead of parsing it into an object
+    pub keep_style_string: Option<bool>,
+    /// Generate the `ssrRender` function for server-side rendering instead of `render`
+    pub ssr: Option<bool>,
+    /// Emit the hot module replacement code in DEV, either `"vite"` or `"webpack"`
+    pub hmr: Option<String>,
+    /// Format of the generated module, either `"esm"` (default) or `"cjs"`
+    pub module_format: Option<String>,
+    /// Module to import the Vue helpers from, defaults to `"vue"`
+    pub runtime_module: Option<String>,
+    /// Keep the template comments, defaults to `true` in DEV and `false` in PROD
+    pub comments: Option<bool>,
+    /// Tag names of the native custom elements, which are not resolved as components.
+    /// A name ending with `*` matches by prefix, e.g. `"ion-*"`
+    pub custom_elements: Option<Vec<String>>,
+    /// Render the template inline in `setup` instead of a separate `render` function,
+    /// defaults to `true` in PROD when `<script setup>` is present
+    pub inline_template: Option<bool>,
+    /// Emit the minified code instead of the pretty-printed one
+    pub minify: Option<bool>,
+    /// Escape the non-ASCII characters in strings
+    pub ascii_only: Option<bool>,
+    /// ES version of the output, e.g. `"es2015"` or `"esnext"` (default).
+    /// The newer syntax is downleveled, e.g. `a?.b` for the targets below `"es2020"`
+    pub target: Option<String>,
+    /// Omit the semicolon after the last statement of a block
+    pub omit_last_semi: Option<bool>,
+    /// Generate the TypeScript declaration of the component, see [`CompileSyncResult::declaration`]
+    pub declaration: Option<bool>,
+    /// Names and prefixes of the generated identifiers, to avoid collisions with the user code
+    pub ident_prefixes: Option<IdentPrefixesOptions>,
+    /// Compile the `<style>` blocks and return them in [`CompileSyncResult::styles`]
+    pub compile_styles: Option<bool>,
+    /// Minify the compiled styles, independently from `minify`
+    pub minify_styles: Option<bool>,
+    /// Embed the compiled styles in the module and inject them at runtime with this function
+    pub style_injector: Option<StyleInjectorOptions>,
+    /// Merge the compiled styles into [`CompileSyncResult::merged_style`]
+    /// and deduplicate the identical rules
+    pub merge_styles: Option<bool>,
+    /// Keep the JSX of `lang="jsx"` and `lang="tsx"` scripts in the output,
+    /// e.g. for `@vue/babel-plugin-jsx`. Without it, JSX in a script is an error
+    pub preserve_jsx: Option<bool>,
+}
+
+/// Function which injects the CSS at runtime, called with the CSS string
+#[napi(object)]
+pub struct StyleInjectorOptions {
+    /// Name of the function, e.g. `"useStyleTag"`
+    pub name: String,
+    /// Module to import the function from, e.g. `"@vueuse/core"`.
+    /// Without it, the function is a global provided by the environment
+    pub source: Option<String>,
+}
+
+/// The defaults are the same as in the official compiler
+#[napi(object)]
+pub struct IdentPrefixesOptions {
+    /// Render context, defaults to `"_ctx"`
+    pub ctx: Option<String>,
+    /// Render cache, defaults to `"_cache"`
+    pub cache: Option<String>,
+    /// Prefix of the runtime-resolved components, defaults to `"_component_"`
+    pub component: Option<String>,
+    /// Prefix of the runtime-resolved directives, defaults to `"_directive_"`
+    pub directive: Option<String>,
+    /// Prefix of the hoisted values, defaults to `"_hoisted_"`
+    pub hoisted: Option<String>,
+}
+
+#[napi(object)]
+pub struct CompileSyncResult {
+    /// The compiled module
+    pub code: String,
+    /// TypeScript declaration of the component (the `.d.ts` content),
+    /// present when the `declaration` option was set
+    pub declaration: Option<String>,
+    /// The compiled `<style>` blocks in order of appearance,
+    /// empty unless the `compile_styles` option was set
+    pub styles: Vec<CompiledStyleBlock>,
+    /// All the compiled styles merged into one stylesheet,
+    /// present when the `merge_styles` option was set
+    pub merged_style: Option<String>,
+}
+
+#[napi(object)]
+pub struct CompiledStyleBlock {
+    /// The compiled CSS
+    pub code: String,
+    /// Whether the block is `<style scoped>`
+    pub scoped: bool,
+    /// Name of the CSS module binding, e.g. `$style` for `<style module>`
+    pub module: Option<String>,
 }
 
 #[napi]
-pub fn compile_sync(source: String, options: Option<CompileSyncOptions>) -> Result<String> {
-    compile_sync_naive(&source, options.map_or(false, |v| v.is_prod)).map_err(|e| Error::from_reason(e))
+pub fn compile_sync(
+    source: String,
+    options: Option<CompileSyncOptions>,
+) -> Result<CompileSyncResult> {
+    let compile_options = CompileOptions {
+        is_prod: options.as_ref().map_or(false, |v| v.is_prod),
+        filename: options.as_ref().and_then(|v| v.filename.as_deref()),
+        scope_id: options
+            .as_ref()
+            .and_then(|v| v.scope_id.as_deref())
+            .unwrap_or_default(),
+        source_map: false,
+        cancellation_token: Default::default(),
+        cache_handlers: options
+            .as_ref()
+            .and_then(|v| v.cache_handlers)
+            .unwrap_or_default(),
+        hoist_static: options
+            .as_ref()
+            .and_then(|v| v.hoist_static)
+            .unwrap_or_default(),
+        keep_style_string: options
+            .as_ref()
+            .and_then(|v| v.keep_style_string)
+            .unwrap_or_default(),
+        ssr: options.as_ref().and_then(|v| v.ssr).unwrap_or_default(),
+        hmr: match options.as_ref().and_then(|v| v.hmr.as_deref()) {
+            Some("vite") => Some(HmrTarget::Vite),
+            Some("webpack") => Some(HmrTarget::Webpack),
+            Some(other) => return Err(Error::from_reason(format!("Unknown HMR target: {other}"))),
+            None => None,
+        },
+        module_format: match options.as_ref().and_then(|v| v.module_format.as_deref()) {
+            Some("esm") | None => ModuleFormat::Esm,
+            Some("cjs") => ModuleFormat::Cjs,
+            Some(other) => {
+                return Err(Error::from_reason(format!(
+                    "Unknown module format: {other}"
+                )))
+            }
+        },
+        runtime_module: options.as_ref().and_then(|v| v.runtime_module.as_deref()),
+        comments: options.as_ref().and_then(|v| v.comments),
+        custom_elements: options
+            .as_ref()
+            .and_then(|v| v.custom_elements.as_ref())
+            .map_or_else(Vec::new, |custom_elements| {
+                custom_elements
+                    .iter()
+                    .map(|tag| FervidAtom::from(tag.as_str()))
+                    .collect()
+            }),
+        declaration: options
+            .as_ref()
+            .and_then(|v| v.declaration)
+            .unwrap_or_default(),
+        template_generation_mode: options
+            .as_ref()
+            .and_then(|v| v.inline_template)
+            .map(|inline| {
+                if inline {
+                    TemplateGenerationMode::Inline
+                } else {
+                    TemplateGenerationMode::RenderFn
+                }
+            }),
+        emit_options: EmitOptions {
+            minify: options.as_ref().and_then(|v| v.minify).unwrap_or(false),
+            ascii_only: options.as_ref().and_then(|v| v.ascii_only).unwrap_or(false),
+            target: match options.as_ref().and_then(|v| v.target.as_deref()) {
+                Some(target) => parse_es_version(target)
+                    .ok_or_else(|| Error::from_reason(format!("Unknown target: {target}")))?,
+                None => EmitOptions::default().target,
+            },
+            omit_last_semi: options
+                .as_ref()
+                .and_then(|v| v.omit_last_semi)
+                .unwrap_or(false),
+        },
+        ident_prefixes: options
+            .as_ref()
+            .and_then(|v| v.ident_prefixes.as_ref())
+            .map_or_else(IdentPrefixes::default, |prefixes| {
+                let defaults = IdentPrefixes::default();
+                let prefix = |value: &Option<String>, default: FervidAtom| {
+                    value.as_deref().map_or(default, FervidAtom::from)
+                };
+                IdentPrefixes {
+                    ctx: prefix(&prefixes.ctx, defaults.ctx),
+                    cache: prefix(&prefixes.cache, defaults.cache),
+                    component: prefix(&prefixes.component, defaults.component),
+                    directive: prefix(&prefixes.directive, defaults.directive),
+                    hoisted: prefix(&prefixes.hoisted, defaults.hoisted),
+                }
+            }),
+        style_preprocessor: None,
+        compile_styles: options
+            .as_ref()
+            .and_then(|v| v.compile_styles)
+            .unwrap_or_default(),
+        style_injector: options
+            .as_ref()
+            .and_then(|v| v.style_injector.as_ref())
+            .map(|style_injector| StyleInjector {
+                name: FervidAtom::from(style_injector.name.as_str()),
+                source: style_injector.source.as_deref().map(FervidAtom::from),
+            }),
+        merge_styles: options
+            .as_ref()
+            .and_then(|v| v.merge_styles)
+            .unwrap_or_default(),
+        minify_styles: options
+            .as_ref()
+            .and_then(|v| v.minify_styles)
+            .unwrap_or_default(),
+        preserve_jsx: options
+            .as_ref()
+            .and_then(|v| v.preserve_jsx)
+            .unwrap_or_default(),
+    };
+
+    compile_sync_naive(&source, compile_options)
+        .map(|result| CompileSyncResult {
+            code: result.code,
+            declaration: result.declaration,
+            styles: result
+                .styles
+                .into_iter()
+                .map(|style| CompiledStyleBlock {
+                    code: style.code,
+                    scoped: style.scoped,
+                    module: style.module.map(|module| module.to_string()),
+                })
+                .collect(),
+            merged_style: result.merged_style.map(|merged_style| merged_style.code),
+        })
+        .map_err(|e| Error::from_reason(e))
 }
@@ -1,7 +1,9 @@
 use fervid_core::{
-    AttributeOrBinding, FervidAtom, StrOrExpr, VBindDirective, VCustomDirective, VForDirective,
+    AttributeOrBinding, AttributeSpan, FervidAtom, StrOrExpr, VBindDirective, VCustomDirective, VForDirective,
     VModelDirective, VOnDirective, VSlotDirective, VueDirectives, fervid_atom,
 };
+use std::borrow::Cow;
+
 use swc_core::common::{BytePos, Span};
 use swc_ecma_parser::Syntax;
 use swc_html_ast::Attribute;
@@ -34,9 +36,11 @@ pub fn parse_raw_attribute(
         }
         Err(raw_attribute) => {
             // parse as a raw attribute
+            let span = get_attribute_span(&raw_attribute);
             attrs_or_bindings.push(AttributeOrBinding::RegularAttribute {
                 name: raw_attribute.name,
                 value: raw_attribute.value.unwrap_or_else(|| fervid_atom!("")),
+                span,
             })
         }
     }
@@ -76,8 +80,9 @@ pub fn try_parse_directive(
 
     // TODO Fix and test parsing of directives
 
-    // TODO Should the span be narrower? (It can be narrowed with lo = lo + name.len() + 1 and hi = hi - 1)
-    let span = raw_attribute.span;
+    // Expressions are parsed using the value span, so that their positions match the source
+    let attribute_span = get_attribute_span(&raw_attribute);
+    let span = attribute_span.value;
     let raw_name: &str = &raw_attribute.name;
     let mut chars_iter = raw_name.chars().enumerate().peekable();
 
@@ -167,7 +172,7 @@ pub fn try_parse_directive(
 
             if is_dynamic_argument {
                 // TODO Narrower span?
-                let parsed_argument = match parse_expr(raw_argument, ts!(), span) {
+                let parsed_argument = match parse_expr(raw_argument, ts!(), attribute_span.name) {
                     Ok(parsed) => parsed,
                     Err(expr_err) => {
                         bail!(js, expr_err.into());
@@ -260,6 +265,7 @@ pub fn try_parse_directive(
                 is_camel,
                 is_prop,
                 is_attr,
+                span: attribute_span,
             }));
         }
 
@@ -278,6 +284,8 @@ pub fn try_parse_directive(
                 event: argument,
                 handler,
                 modifiers,
+                is_cacheable: false,
+                span: attribute_span,
             }));
         }
 
@@ -304,7 +312,10 @@ pub fn try_parse_directive(
                 bail!(ParseErrorKind::DirectiveSyntax);
             };
 
-            match parse_expr(itervar, ts!(), itervar_span) {
+            // `(, key, index)` skips the value alias
+            let itervar = fill_skipped_v_for_aliases(itervar);
+
+            match parse_expr(&itervar, ts!(), itervar_span) {
                 Ok(itervar) => match parse_expr(iterable, ts!(), iterable_span) {
                     Ok(iterable) => {
                         push_directive!(
@@ -332,7 +343,8 @@ pub fn try_parse_directive(
                         argument,
                         value: *model_binding,
                         modifiers,
-                        span,
+                        update_handler: None,
+                        span: raw_attribute.span,
                     });
                 }
                 Result::Err(_) => {}
@@ -420,15 +432,68 @@ pub fn try_parse_directive(
     Ok(())
 }
 
+/// Splits the attribute span into the name and value spans.
+/// Quotes around the value are not included into the value span.
+fn get_attribute_span(raw_attribute: &Attribute) -> AttributeSpan {
+    let span = raw_attribute.span;
+
+    let name_len = raw_attribute
+        .raw_name
+        .as_ref()
+        .map_or(raw_attribute.name.len(), |raw_name| raw_name.len());
+    let name_hi = BytePos(span.lo.0 + name_len as u32);
+    let name = Span {
+        lo: span.lo,
+        hi: name_hi,
+        ctxt: span.ctxt,
+    };
+
+    // `raw_value` includes quotes and is not unescaped, prefer it
+    let (lo, hi) = match (&raw_attribute.raw_value, &raw_attribute.value) {
+        (Some(raw_value), _) => {
+            let is_quoted = raw_value.len() >= 2
+                && (raw_value.starts_with('"') || raw_value.starts_with('\''));
+            let quote_len = if is_quoted { 1 } else { 0 };
+
+            (
+                span.hi.0 - raw_value.len() as u32 + quote_len,
+                span.hi.0 - quote_len,
+            )
+        }
+        (None, Some(value)) => (span.hi.0 - value.len() as u32, span.hi.0),
+        (None, None) => (name_hi.0, name_hi.0),
+    };
+
+    let value = Span {
+        lo: BytePos(lo),
+        hi: BytePos(hi),
+        ctxt: span.ctxt,
+    };
+
+    AttributeSpan { name, value }
+}
+
 fn split_itervar_and_iterable<'a>(
     raw: &'a str,
     original_span: Span,
 ) -> Option<((&'a str, Span), (&'a str, Span))> {
-    // `item in iterable` or `item of iterable`
-    let Some(split_idx) = raw.find(" in ").or_else(|| raw.find(" of ")) else {
-        return None;
-    };
-    const SPLIT_LEN: usize = " in ".len();
+    // `item in iterable` or `item of iterable`, whichever keyword comes first.
+    // The keyword may be surrounded by any whitespace
+    let split_idx = raw.char_indices().find_map(|(idx, ch)| {
+        if !ch.is_whitespace() {
+            return None;
+        }
+
+        let keyword_idx = idx + ch.len_utf8();
+        let after_keyword = raw[keyword_idx..]
+            .strip_prefix("in")
+            .or_else(|| raw[keyword_idx..].strip_prefix("of"))?;
+
+        after_keyword
+            .starts_with(char::is_whitespace)
+            .then_some(keyword_idx)
+    })?;
+    const SPLIT_LEN: usize = "in".len();
 
     // Get the trimmed itervar and its span
     let mut offset = original_span.lo.0;
@@ -469,6 +534,57 @@ fn split_itervar_and_iterable<'a>(
     Some(((itervar, new_span_itervar), (iterable, new_span_iterable)))
 }
 
+/// Fills in the skipped `v-for` aliases the same way the official compiler does,
+/// e.g. `(, key, index)` becomes `(_, key, index)` and `(, , index)` becomes `(_, __, index)`.
+/// Trailing skipped aliases are dropped
+fn fill_skipped_v_for_aliases(itervar: &str) -> Cow<'_, str> {
+    let Some(inner) = itervar
+        .strip_prefix('(')
+        .and_then(|itervar| itervar.strip_suffix(')'))
+    else {
+        return Cow::Borrowed(itervar);
+    };
+
+    // Split by the top-level commas, destructured aliases may contain commas as well
+    let mut aliases = Vec::with_capacity(3);
+    let mut depth = 0;
+    let mut alias_start = 0;
+    for (idx, ch) in inner.char_indices() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                aliases.push(&inner[alias_start..idx]);
+                alias_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    aliases.push(&inner[alias_start..]);
+
+    if aliases.iter().all(|alias| !alias.trim().is_empty()) {
+        return Cow::Borrowed(itervar);
+    }
+
+    while matches!(aliases.last(), Some(alias) if alias.trim().is_empty()) {
+        aliases.pop();
+    }
+
+    let filled: Vec<Cow<str>> = aliases
+        .into_iter()
+        .enumerate()
+        .map(|(idx, alias)| {
+            if alias.trim().is_empty() {
+                Cow::Owned("_".repeat(idx + 1))
+            } else {
+                Cow::Borrowed(alias)
+            }
+        })
+        .collect();
+
+    Cow::Owned(format!("({})", filled.join(",")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,5 +621,23 @@ mod tests {
 
         // A bit harder
         check!("   item   in \n \t  list   ", "item", 4, 8, "list", 19, 23);
+        check!("item\nin\tlist", "item", 1, 5, "list", 9, 13);
+
+        // The first keyword wins
+        check!("x of xs(y in z)", "x", 1, 2, "xs(y in z)", 6, 16);
+        check!("index in indexes", "index", 1, 6, "indexes", 10, 17);
+    }
+
+    #[test]
+    fn it_fills_skipped_v_for_aliases() {
+        assert_eq!(fill_skipped_v_for_aliases("item"), "item");
+        assert_eq!(fill_skipped_v_for_aliases("(item, index)"), "(item, index)");
+        assert_eq!(fill_skipped_v_for_aliases("(, key)"), "(_, key)");
+        assert_eq!(fill_skipped_v_for_aliases("(, , index)"), "(_,__, index)");
+        assert_eq!(fill_skipped_v_for_aliases("(value, , )"), "(value)");
+        assert_eq!(
+            fill_skipped_v_for_aliases("({ a, b }, , index)"),
+            "({ a, b },__, index)"
+        );
     }
 }
@@ -3,12 +3,17 @@ mod common;
 mod error;
 mod script;
 mod sfc;
+mod style;
 mod template;
 
+pub use error::{ParseError, ParseErrorKind};
 pub use sfc::{parse_sfc, parse_html_document_fragment};
 
 #[cfg(test)]
 mod tests {
+    use fervid_core::Node;
+    use swc_core::common::Span;
+
     use crate::sfc::parse_sfc;
 
     #[test]
@@ -41,4 +46,75 @@ mod tests {
 
         // assert_eq!(errors.len(), 0);
     }
+
+    #[test]
+    fn it_parses_jsx_scripts() {
+        let mut errors = Vec::new();
+
+        for lang in ["jsx", "tsx"] {
+            let document = format!(
+                r#"<script setup lang="{lang}">const foo = <div class="bar">{{ 42 }}</div></script>"#
+            );
+
+            let parsed = parse_sfc(&document, &mut errors).expect("SFC should parse");
+            let script_setup = parsed.script_setup.expect("script setup should be present");
+            assert!(script_setup.lang.is_jsx());
+            assert_eq!(script_setup.content.body.len(), 1);
+        }
+    }
+
+    #[test]
+    fn it_parses_style_modules() {
+        let document = r#"
+            <style>.foo {}</style>
+            <style module>.bar {}</style>
+            <style module="classes" scoped>.baz {}</style>
+        "#;
+        let mut errors = Vec::new();
+
+        let parsed = parse_sfc(document, &mut errors).expect("SFC should parse");
+        let modules: Vec<Option<&str>> = parsed
+            .styles
+            .iter()
+            .map(|style| style.module.as_deref())
+            .collect();
+
+        assert_eq!(modules, vec![None, Some("$style"), Some("classes")]);
+        assert!(parsed.styles[2].is_scoped);
+    }
+
+    #[test]
+    fn it_retains_attribute_spans() {
+        let document = r#"<template><div foo="bar" :baz='qux' @click=handle disabled></div></template>"#;
+        let mut errors = Vec::new();
+
+        let parsed = parse_sfc(document, &mut errors).expect("SFC should parse");
+        let template = parsed.template.expect("template should be present");
+        let Some(Node::Element(div)) = template.roots.first() else {
+            panic!("Root is not an element")
+        };
+
+        // Spans are 1-based
+        let source = |span: Span| &document[span.lo.0 as usize - 1..span.hi.0 as usize - 1];
+
+        let spans: Vec<(&str, &str)> = div
+            .starting_tag
+            .attributes
+            .iter()
+            .map(|attr| {
+                let span = attr.span();
+                (source(span.name), source(span.value))
+            })
+            .collect();
+
+        assert_eq!(
+            spans,
+            vec![
+                ("foo", "bar"),
+                (":baz", "qux"),
+                ("@click", "handle"),
+                ("disabled", "")
+            ]
+        );
+    }
 }
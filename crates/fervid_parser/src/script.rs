@@ -14,6 +14,8 @@ pub fn parse_sfc_script_element(element: Element) -> Option<SfcScriptBlock> {
         } else if attr_name.eq("lang") {
             lang = match attr.value.as_ref() {
                 Some(v) if v.eq("ts") => SfcScriptLang::Typescript,
+                Some(v) if v.eq("tsx") => SfcScriptLang::Tsx,
+                Some(v) if v.eq("jsx") => SfcScriptLang::Jsx,
                 _ => SfcScriptLang::Es
             };
         }
@@ -24,10 +26,17 @@ pub fn parse_sfc_script_element(element: Element) -> Option<SfcScriptBlock> {
         return None;
     };
 
-    let syntax = if matches!(lang, SfcScriptLang::Typescript) {
-        Syntax::Typescript(TsConfig::default())
+    // JSX is only parsed here, transforming it is up to the downstream tooling
+    let syntax = if lang.is_typescript() {
+        Syntax::Typescript(TsConfig {
+            tsx: lang.is_jsx(),
+            ..Default::default()
+        })
     } else {
-        Syntax::Es(EsConfig::default())
+        Syntax::Es(EsConfig {
+            jsx: lang.is_jsx(),
+            ..Default::default()
+        })
     };
 
     let Ok(content) = parse_module(&script_content.data, syntax, script_content.span) else {
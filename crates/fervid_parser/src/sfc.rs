@@ -11,6 +11,7 @@ use crate::{
     common::process_element_starting_tag,
     error::{ParseError, ParseErrorKind},
     script::parse_sfc_script_element,
+    style::parse_css_vars,
     template::parse_template_to_ir,
 };
 
@@ -100,6 +101,7 @@ pub fn parse_sfc(input: &str, errors: &mut Vec<ParseError>) -> Result<SfcDescrip
 
             let mut lang = FervidAtom::from("css");
             let mut is_scoped = false;
+            let mut module = None;
 
             for attr in root_element.attributes.into_iter() {
                 if attr.name.eq("lang") {
@@ -110,13 +112,25 @@ pub fn parse_sfc(input: &str, errors: &mut Vec<ParseError>) -> Result<SfcDescrip
                     lang = attr_val;
                 } else if attr.name.eq("scoped") {
                     is_scoped = true;
+                } else if attr.name.eq("module") {
+                    // `<style module>` is bound as `$style`
+                    module = Some(
+                        attr.value
+                            .filter(|name| !name.is_empty())
+                            .unwrap_or_else(|| fervid_atom!("$style")),
+                    );
                 }
             }
 
+            let css_vars = parse_css_vars(&style_content.data, style_content.span, errors);
+
             sfc_descriptor.styles.push(SfcStyleBlock {
                 lang,
                 content: style_content.data.to_owned(),
                 is_scoped,
+                module,
+                css_vars,
+                span: style_content.span,
             })
         } else {
             let starting_tag = process_element_starting_tag(
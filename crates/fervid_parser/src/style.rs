@@ -0,0 +1,72 @@
+use fervid_core::{find_css_vars, FervidAtom, SfcCssVar};
+use swc_core::common::{BytePos, Span};
+use swc_ecma_parser::Syntax;
+
+use crate::{error::ParseError, script::parse_expr};
+
+/// Finds and parses all the `v-bind()` usages in the `<style>` content.
+///
+/// ## Example
+/// `.foo { color: v-bind(color); width: v-bind('size.width') }`
+/// yields `color` and `size.width`.
+pub fn parse_css_vars(content: &str, span: Span, errors: &mut Vec<ParseError>) -> Vec<SfcCssVar> {
+    let mut css_vars = Vec::new();
+
+    for css_var in find_css_vars(content) {
+        let raw = &content[css_var.raw_start..css_var.raw_end];
+        let expr_span = Span {
+            lo: BytePos(span.lo.0 + css_var.raw_start as u32),
+            hi: BytePos(span.lo.0 + css_var.raw_end as u32),
+            ctxt: span.ctxt,
+        };
+
+        match parse_expr(raw, Syntax::Typescript(Default::default()), expr_span) {
+            Ok(expr) => css_vars.push(SfcCssVar {
+                raw: FervidAtom::from(raw),
+                expr,
+            }),
+            Err(expr_err) => errors.push(expr_err.into()),
+        }
+    }
+
+    css_vars
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_css_vars() {
+        let mut errors = Vec::new();
+        let css_vars = parse_css_vars(
+            ".foo { color: v-bind(color); width: v-bind( 'size.width' ) }\n\
+            .bar { height: v-bind(\"calc(size.height)\"); background: v-bind (bg) }",
+            DUMMY_SP,
+            &mut errors,
+        );
+
+        let raws: Vec<&str> = css_vars.iter().map(|v| v.raw.as_ref()).collect();
+        assert_eq!(raws, vec!["color", "size.width", "calc(size.height)", "bg"]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn it_ignores_invalid_css_vars() {
+        let mut errors = Vec::new();
+
+        // Not a call
+        assert!(parse_css_vars(".v-bind { color: red }", DUMMY_SP, &mut errors).is_empty());
+
+        // Empty and unclosed
+        assert!(parse_css_vars(".foo { color: v-bind() }", DUMMY_SP, &mut errors).is_empty());
+        assert!(parse_css_vars(".foo { color: v-bind(color }", DUMMY_SP, &mut errors).is_empty());
+        assert!(errors.is_empty());
+
+        // Bad expression is reported
+        assert!(parse_css_vars(".foo { color: v-bind(1 +) }", DUMMY_SP, &mut errors).is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+}
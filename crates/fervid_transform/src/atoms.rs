@@ -20,6 +20,7 @@ lazy_static! {
 
     // Composition API atoms
     // pub static ref COMPUTED: FervidAtom = fervid_atom!("computed");
+    pub static ref DEFINE_ASYNC_COMPONENT: FervidAtom = fervid_atom!("defineAsyncComponent");
     pub static ref DEFINE_EMITS: FervidAtom = fervid_atom!("defineEmits");
     pub static ref DEFINE_EXPOSE: FervidAtom = fervid_atom!("defineExpose");
     pub static ref DEFINE_MODEL: FervidAtom = fervid_atom!("defineModel");
@@ -33,8 +34,11 @@ lazy_static! {
     pub static ref EMIT: FervidAtom = fervid_atom!("emit");
     pub static ref EMIT_HELPER: FervidAtom = fervid_atom!("__emit");
     pub static ref EXPOSE_HELPER: FervidAtom = fervid_atom!("__expose");
+    pub static ref FILE_HELPER: FervidAtom = fervid_atom!("__file");
     pub static ref MERGE_MODELS_HELPER: FervidAtom = fervid_atom!("_mergeModels");
     pub static ref MODEL_VALUE: FervidAtom = fervid_atom!("modelValue");
+    pub static ref NAME_HELPER: FervidAtom = fervid_atom!("__name");
     pub static ref PROPS_HELPER: FervidAtom = fervid_atom!("__props");
+    pub static ref USE_CSS_VARS_HELPER: FervidAtom = fervid_atom!("_useCssVars");
     pub static ref USE_MODEL_HELPER: FervidAtom = fervid_atom!("_useModel");
 }
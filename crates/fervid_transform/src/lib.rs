@@ -1,5 +1,8 @@
-use fervid_core::{BindingsHelper, SfcDescriptor, SfcTemplateBlock};
-use script::transform_and_record_scripts;
+use fervid_core::{
+    BindingsHelper, FervidAtom, SfcCssVar, SfcDescriptor, SfcTemplateBlock, TemplateGenerationMode,
+};
+use script::{add_dev_component_meta, infer_component_name, transform_and_record_scripts};
+use structs::{TemplateAnalysis, TransformSfcOptions};
 use swc_core::ecma::ast::{Function, Module, ObjectLit};
 use template::transform_and_record_template;
 
@@ -25,24 +28,72 @@ pub struct TransformSfcResult {
     pub setup_fn: Option<Box<Function>>,
     /// Transformed template block
     pub template_block: Option<SfcTemplateBlock>,
+    /// Template information, e.g. whether it is fully static
+    pub template_analysis: TemplateAnalysis,
+    /// TypeScript declaration of the component, see [`TransformSfcOptions::declaration`]
+    pub declaration: Option<Module>,
 }
 
 /// Applies all the necessary transformations to the SFC.
 ///
 /// The transformations can be fine-tuned by using individual `transform_` functions.
-pub fn transform_sfc(sfc_descriptor: SfcDescriptor, is_prod: bool) -> TransformSfcResult {
+pub fn transform_sfc(
+    sfc_descriptor: SfcDescriptor,
+    options: TransformSfcOptions,
+) -> TransformSfcResult {
     let mut template_block = None;
+    let mut template_analysis = TemplateAnalysis::default();
 
     let mut bindings_helper = BindingsHelper::default();
-    bindings_helper.is_prod = is_prod;
-    let transform_result = transform_and_record_scripts(
+    bindings_helper.is_prod = options.is_prod;
+    bindings_helper.comments = options.comments;
+    bindings_helper.custom_elements = options.custom_elements;
+    bindings_helper.ident_prefixes = options.ident_prefixes;
+    bindings_helper.template_generation_mode = match options.template_generation_mode {
+        _ if sfc_descriptor.script_setup.is_none() => TemplateGenerationMode::RenderFn,
+        Some(template_generation_mode) => template_generation_mode,
+        None if options.is_prod => TemplateGenerationMode::Inline,
+        None => TemplateGenerationMode::RenderFn,
+    };
+    bindings_helper.is_ts = [&sfc_descriptor.script_setup, &sfc_descriptor.script_legacy]
+        .into_iter()
+        .flatten()
+        .any(|script| script.lang.is_typescript());
+    bindings_helper.cancellation_token = options.cancellation_token;
+    bindings_helper.self_name = options
+        .filename
+        .map(infer_component_name)
+        .filter(|name| !name.is_empty())
+        .map(FervidAtom::from);
+    // `v-bind()` from all the `<style>` blocks
+    let css_vars: Vec<SfcCssVar> = sfc_descriptor
+        .styles
+        .into_iter()
+        .flat_map(|style| style.css_vars)
+        .collect();
+
+    let mut transform_result = transform_and_record_scripts(
         sfc_descriptor.script_setup,
         sfc_descriptor.script_legacy,
+        css_vars,
+        options.css_modules,
+        options.scope_id,
+        options.declaration,
         &mut bindings_helper,
     );
 
-    if let Some(mut template) = sfc_descriptor.template {
-        transform_and_record_template(&mut template, &mut bindings_helper);
+    // Devtools and HMR rely on `__file` and `__name`
+    if let (false, Some(filename)) = (options.is_prod, options.filename) {
+        add_dev_component_meta(&mut transform_result.export_obj, filename);
+    }
+
+    // A cancelled compilation does not need the template
+    let template = sfc_descriptor
+        .template
+        .filter(|_| !bindings_helper.cancellation_token.is_cancelled());
+
+    if let Some(mut template) = template {
+        template_analysis = transform_and_record_template(&mut template, &mut bindings_helper);
         if !template.roots.is_empty() {
             template_block = Some(template);
         }
@@ -54,5 +105,7 @@ pub fn transform_sfc(sfc_descriptor: SfcDescriptor, is_prod: bool) -> TransformS
         module: transform_result.module,
         setup_fn: transform_result.setup_fn,
         template_block,
+        template_analysis,
+        declaration: transform_result.declaration,
     }
 }
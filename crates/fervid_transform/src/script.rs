@@ -1,21 +1,30 @@
 //! Responsible for `<script>` and `<script setup>` transformations and analysis.
 
-use fervid_core::{SfcScriptBlock, TemplateGenerationMode, BindingsHelper};
+use fervid_core::{BindingsHelper, FervidAtom, SfcCssModule, SfcCssVar, SfcScriptBlock};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{
-        Function, Module, ObjectLit, ModuleItem,
+        Expr, Function, Ident, KeyValueProp, Lit, Module, ObjectLit, ModuleItem, Prop, PropName,
+        PropOrSpread, Str,
     },
 };
 
-use crate::structs::TransformScriptsResult;
+use crate::{
+    atoms::{FILE_HELPER, NAME_HELPER},
+    structs::TransformScriptsResult,
+};
 
 use self::{
+    declaration::generate_declaration,
     options_api::{transform_and_record_script_options_api, AnalyzeOptions},
-    setup::{transform_and_record_script_setup, merge_sfc_helper},
+    setup::{
+        generate_css_modules, generate_use_css_vars, inject_use_css_vars, merge_sfc_helper,
+        transform_and_record_script_setup,
+    },
 };
 
 mod common;
+mod declaration;
 mod options_api;
 mod setup;
 pub mod utils;
@@ -29,16 +38,26 @@ pub mod utils;
 /// - Variable bindings (from `<script setup>` and from Options API);
 /// - Import bindings;
 /// - (TODO) Imported `.vue` component bindings;
+///
+/// The `template_generation_mode` of the [`BindingsHelper`] is expected to be already set,
+/// because the `<script setup>` bindings are accessed differently in each mode.
+///
+/// `css_vars` are the `v-bind()` usages from `<style>` blocks,
+/// they are bound inside `setup` using `useCssVars`.
+///
+/// `css_modules` are the class names of the compiled `<style module>` blocks,
+/// they are declared as module-level constants, e.g. `const $style = { "red": "EgL3uq_red" }`.
+///
+/// When `with_declaration` is set, the TypeScript declaration of the component is generated as well.
 pub fn transform_and_record_scripts(
     script_setup: Option<SfcScriptBlock>,
     script_legacy: Option<SfcScriptBlock>,
+    css_vars: Vec<SfcCssVar>,
+    css_modules: Vec<SfcCssModule>,
+    scope_id: &str,
+    with_declaration: bool,
     bindings_helper: &mut BindingsHelper,
 ) -> TransformScriptsResult {
-    // Set inline flag in `BindingsHelper`
-    if bindings_helper.is_prod && script_setup.is_some() {
-        bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
-    }
-
     //
     // STEP 1: Transform Options API `<script>`.
     //
@@ -74,6 +93,7 @@ pub fn transform_and_record_scripts(
     //
 
     let mut setup_fn: Option<Box<Function>> = None;
+    let mut declaration: Option<Module> = None;
     if let Some(script_setup) = script_setup {
         let setup_transform_result = transform_and_record_script_setup(script_setup, bindings_helper);
 
@@ -84,17 +104,331 @@ pub fn transform_and_record_scripts(
             module.body.push(ModuleItem::ModuleDecl(module_decl));
         }
 
+        // Hoisted statements go after imports, they do not reference anything
+        for hoisted_stmt in setup_transform_result.hoisted_stmts.into_iter() {
+            module.body.push(ModuleItem::Stmt(hoisted_stmt));
+        }
+
+        // The macros are analyzed before they are merged into the exported object
+        if with_declaration {
+            declaration = Some(generate_declaration(
+                &module,
+                &export_obj,
+                Some(&setup_transform_result.sfc_object_helper),
+            ));
+        }
+
         // Merge fields into an SFC exported object
-        merge_sfc_helper(setup_transform_result.sfc_object_helper, &mut export_obj.props);
+        merge_sfc_helper(
+            setup_transform_result.sfc_object_helper,
+            &mut export_obj.props,
+            bindings_helper,
+        );
 
         // TODO Adding bindings to `setup()` in Options API will get overwritten in `<script setup>`
         // https://play.vuejs.org/#eNp9U01v2zAM/SuELm6BNFmTm5F22IYetsM2bMUudTEYNp2okyVDklMPQf77SNpunS7txTQfH/n4Ye/Vh6aZ71pUqVpHrBuTR7zOLAB5IV4Urm7EFaAPw+5CV1eZir7FTA1RgMq5gbg4KnScGYyLKVGf0rb6ZBa7z/pDQ//rB2qA7cvs7ZJYaAL21CqnV6KKXS+2y4G1GljX/CB8NWqVekehynlK/g3awipTBBRtiK7mMbbucVJ3vaCEMZdHBJvXSAQ2pRAYPTFJL3F2pwm7nAGb5T1ZW2J3zsJGh0gF9nuJXcLhcDQr16OYa6J2NlB0kNC2aSPVr12JhhTE/soNnwzS+Lfh7qR9eA9JxC4mkEJSUtVERp3ujetg7Qi4o9PdC+BswfovmlmHwusmQsDY8uF03TgfgW/5iU4Jlaf1JXM5Ln92CScV1HmE25FzBQnBtDEpNS1L79hJwRKrvDUR9jysiJ2d9w6AJ9fb0YNxNynIBysgbUkesq1ePifddxNZNVMxUKjSm/lDcJZ+EKmYKf4mtUH/ra+bqXTUylRujHv8IhirzUa82GLx5wT+EDrGMvXdY0C/o2U/xWLuN0i35/DNz690okmQ7tkaYr8R/IHBmZZ77GkfW1tS2xOedPtZTqTt5jbcdBFtGIca13UQfqboXHyf10Z/bnc1X437VYd/HFh0XQ==
         setup_fn = setup_transform_result.setup_fn;
     }
 
+    // `<style module>` bindings, they are not overwritten if already declared in `<script setup>`
+    for css_module_stmt in generate_css_modules(css_modules, bindings_helper) {
+        module.body.push(ModuleItem::Stmt(css_module_stmt));
+    }
+
+    // `useCssVars` needs to be the first thing in `setup`
+    if let Some(use_css_vars) = generate_use_css_vars(css_vars, scope_id, bindings_helper) {
+        match setup_fn.as_mut().and_then(|f| f.body.as_mut()) {
+            Some(setup_body) => setup_body.stmts.insert(0, use_css_vars),
+            None => inject_use_css_vars(&mut export_obj, use_css_vars),
+        }
+    }
+
+    if with_declaration && declaration.is_none() {
+        declaration = Some(generate_declaration(&module, &export_obj, None));
+    }
+
     TransformScriptsResult {
         module,
         export_obj,
         setup_fn,
+        declaration,
+    }
+}
+
+/// Adds the `__name` and `__file` properties used by devtools and HMR to the exported object.
+/// `__name` is inferred from the file name, e.g. for `src/components/my-button.vue`:
+/// ```js
+/// export default {
+///     __name: "MyButton",
+///     /* other fields */
+///     __file: "src/components/my-button.vue"
+/// }
+/// ```
+pub fn add_dev_component_meta(export_obj: &mut ObjectLit, filename: &str) {
+    macro_rules! str_prop {
+        ($sym: expr, $value: expr) => {
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: $sym,
+                    optional: false,
+                }),
+                value: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: FervidAtom::from($value),
+                    raw: None,
+                }))),
+            })))
+        };
+    }
+
+    // An explicit `name`, e.g. from `defineOptions({ name: 'Foo' })`, is kept as is
+    let has_name = export_obj.props.iter().any(is_name_prop);
+
+    let component_name = infer_component_name(filename);
+    if !has_name && !component_name.is_empty() {
+        export_obj
+            .props
+            .insert(0, str_prop!(NAME_HELPER.to_owned(), component_name));
+    }
+
+    export_obj
+        .props
+        .push(str_prop!(FILE_HELPER.to_owned(), filename));
+}
+
+/// Whether the property is `name`, i.e. `name: 'Foo'`, `'name': 'Foo'` or `name`
+fn is_name_prop(prop: &PropOrSpread) -> bool {
+    let PropOrSpread::Prop(prop) = prop else {
+        return false;
+    };
+
+    match **prop {
+        Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident { ref sym, .. }),
+            ..
+        })
+        | Prop::Shorthand(Ident { ref sym, .. }) => sym == "name",
+        Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str { ref value, .. }),
+            ..
+        }) => value == "name",
+        _ => false,
+    }
+}
+
+/// Infers the PascalCase component name from the file path,
+/// e.g. `src/components/my-button.vue` becomes `MyButton`
+pub(crate) fn infer_component_name(filename: &str) -> String {
+    // `src/components/my-button.vue` -> `my-button`
+    let file_name = filename
+        .rsplit(|c: char| c == '/' || c == '\\')
+        .next()
+        .unwrap_or(filename);
+    let file_stem = file_name.split('.').next().unwrap_or(file_name);
+
+    // `my-button` -> `MyButton`
+    let mut result = String::with_capacity(file_stem.len());
+    let mut capitalize_next = true;
+    for c in file_stem.chars() {
+        if matches!(c, '-' | '_' | ' ') {
+            capitalize_next = true;
+            continue;
+        }
+
+        if capitalize_next {
+            result.extend(c.to_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{common::DUMMY_SP, ecma::ast::ObjectLit};
+
+    use fervid_core::{
+        BindingsHelper, FervidAtom, SfcCssVar, SfcScriptBlock, SfcScriptLang, VueImports,
+    };
+
+    use super::{add_dev_component_meta, infer_component_name, transform_and_record_scripts};
+    use crate::test_utils::{
+        parser::{parse_javascript_expr, parse_javascript_module, parse_typescript_module},
+        to_str,
+    };
+
+    #[test]
+    fn it_infers_component_name() {
+        assert_eq!(infer_component_name("App.vue"), "App");
+        assert_eq!(infer_component_name("src/components/my-button.vue"), "MyButton");
+        assert_eq!(infer_component_name("C:\\project\\foo_bar.vue"), "FooBar");
+        assert_eq!(infer_component_name("src/Foo.test.vue"), "Foo");
+    }
+
+    #[test]
+    fn it_adds_dev_component_meta() {
+        let mut export_obj = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+
+        add_dev_component_meta(&mut export_obj, "src/components/my-button.vue");
+
+        assert_eq!(
+            to_str(&export_obj),
+            r#"{__name:"MyButton",__file:"src/components/my-button.vue"}"#
+        );
+
+        // `name` from `defineOptions` is not overridden
+        let mut result = transform_and_record_scripts(
+            Some(ts_script("defineOptions({ name: 'FancyButton' })", true)),
+            None,
+            vec![],
+            vec![],
+            "",
+            false,
+            &mut BindingsHelper::default(),
+        );
+
+        add_dev_component_meta(&mut result.export_obj, "src/components/my-button.vue");
+
+        let code = to_str(&result.export_obj);
+        assert!(code.contains("FancyButton"));
+        assert!(!code.contains("__name"));
+        assert!(code.contains(r#"__file:"src/components/my-button.vue""#));
+    }
+
+    #[test]
+    fn it_preserves_side_effect_imports() {
+        macro_rules! script {
+            ($input: expr, $is_setup: expr) => {
+                SfcScriptBlock {
+                    content: Box::new(
+                        parse_javascript_module($input, 0, Default::default())
+                            .expect("script expects the input to be parseable")
+                            .0,
+                    ),
+                    lang: SfcScriptLang::Es,
+                    is_setup: $is_setup,
+                }
+            };
+        }
+
+        let result = transform_and_record_scripts(
+            Some(script!("import 'uno.css'\nimport './setup-polyfill'\nconst foo = 42", true)),
+            Some(script!("import './polyfill'\nconst bar = 1", false)),
+            vec![],
+            vec![],
+            "",
+            false,
+            &mut BindingsHelper::default(),
+        );
+
+        assert!(to_str(&result.module).starts_with(
+            r#"import"./polyfill";const bar=1;import"uno.css";import"./setup-polyfill";"#
+        ));
+    }
+
+    #[test]
+    fn it_merges_options_api_props_and_emits() {
+        let mut bindings_helper = BindingsHelper::default();
+        let result = transform_and_record_scripts(
+            Some(ts_script(
+                "defineProps({ bar: Number })\ndefineEmits(['update'])",
+                true,
+            )),
+            Some(ts_script(
+                "export default { props: ['foo'], emits: ['change'] }",
+                false,
+            )),
+            vec![],
+            vec![],
+            "",
+            false,
+            &mut bindings_helper,
+        );
+
+        assert_eq!(
+            to_str(&result.export_obj),
+            r#"{props:_mergeModels(["foo"],{bar:Number}),emits:["change","update"]}"#
+        );
+        assert!(bindings_helper
+            .vue_imports
+            .contains(VueImports::MergeModels));
+    }
+
+    #[test]
+    fn it_generates_runtime_props_and_emits_from_types() {
+        let result = transform_and_record_scripts(
+            Some(ts_script(
+                "
+                interface Props { foo: string; bar?: number | boolean; baz: Foo }
+                defineProps<Props>()
+                defineEmits<{ (e: 'change', id: number): void; (e: 'update'): void }>()
+                const model = defineModel<string>()
+                ",
+                true,
+            )),
+            None,
+            vec![],
+            vec![],
+            "",
+            false,
+            &mut BindingsHelper::default(),
+        );
+
+        let export_obj = to_str(&result.export_obj);
+        assert!(export_obj.contains(r#"emits:["change","update","update:modelValue"]"#));
+        assert!(export_obj.contains(concat!(
+            r#"props:{"foo":{type:String,required:true},"#,
+            r#""bar":{type:[Number,Boolean],required:false},"#,
+            r#""baz":{type:null,required:true},"#,
+            r#""modelValue":{}}"#
+        )));
+    }
+
+    #[test]
+    fn it_binds_css_vars_without_script_setup() {
+        let css_var = SfcCssVar {
+            raw: FervidAtom::from("color"),
+            expr: parse_javascript_expr("color", 0, Default::default())
+                .expect("css var should be parseable")
+                .0,
+        };
+
+        let result = transform_and_record_scripts(
+            None,
+            Some(ts_script(
+                "export default { data: () => ({ color: 'red' }) }",
+                false,
+            )),
+            vec![css_var],
+            vec![],
+            "abcd1234",
+            false,
+            &mut BindingsHelper::default(),
+        );
+
+        assert!(result.setup_fn.is_none());
+        assert_eq!(
+            to_str(&result.export_obj),
+            r#"{data:()=>({color:"red"}),setup(){_useCssVars(_ctx=>({"abcd1234-color":_ctx.color}))}}"#
+        );
+    }
+
+    fn ts_script(input: &str, is_setup: bool) -> SfcScriptBlock {
+        SfcScriptBlock {
+            content: Box::new(
+                parse_typescript_module(input, 0, Default::default())
+                    .expect("script expects the input to be parseable")
+                    .0,
+            ),
+            lang: SfcScriptLang::Typescript,
+            is_setup,
+        }
     }
 }
@@ -1,5 +1,8 @@
 use fervid_core::{BindingTypes, SetupBinding};
-use swc_core::ecma::ast::{ClassDecl, FnDecl, VarDeclarator, Pat, Expr, Callee, ObjectPatProp, RestPat};
+use swc_core::ecma::ast::{
+    ClassDecl, FnDecl, VarDeclarator, Pat, Expr, Callee, ObjectPatProp, RestPat, Lit, MemberProp,
+    Prop, PropName, PropOrSpread, UnaryOp,
+};
 
 use crate::{structs::VueResolvedImports, script::utils::unroll_paren_seq};
 
@@ -36,9 +39,12 @@ pub fn categorize_fn_decl(fn_decl: &FnDecl) -> SetupBinding {
 /// let foo = ref(1)                    // BindingTypes::SetupLet
 /// const
 ///     pi = 3.14,                      // BindingTypes::LiteralConst
+///     greeting = `Hello ${'world'}`,  // BindingTypes::LiteralConst
+///     opts = Object.freeze({ a: 1 }), // BindingTypes::LiteralConst
 ///     bar = ref(2),                   // BindingTypes::SetupRef
 ///     baz = computed(() => 3),        // BindingTypes::SetupRef
 ///     qux = reactive({ x: 4 }),       // BindingTypes::SetupReactiveConst
+///     Foo = defineAsyncComponent(() => import('./Foo.vue')), // BindingTypes::SetupConst
 /// ```
 pub fn categorize_var_declarator(
     var_decl: &VarDeclarator,
@@ -78,6 +84,13 @@ pub fn categorize_var_declarator(
         return;
     };
 
+    // Constants which can never change, e.g. `const title = 'Hello'`.
+    // This is checked before unrolling, as `(sideEffect(), 'a')` is not a constant
+    if is_static_literal(init_expr) {
+        push!(BindingTypes::LiteralConst);
+        return;
+    }
+
     let init_expr = unroll_paren_seq(init_expr);
 
     match init_expr {
@@ -100,6 +113,9 @@ pub fn categorize_var_declarator(
                         push!(BindingTypes::SetupRef)
                     } else if callee_ident_option == vue_imports.reactive {
                         push!(BindingTypes::SetupReactiveConst)
+                    } else if callee_ident_option == vue_imports.define_async_component {
+                        // Async component is never reassigned, thus usable as a component directly
+                        push!(BindingTypes::SetupConst)
                     } else {
                         push!(BindingTypes::SetupMaybeRef)
                     }
@@ -134,8 +150,98 @@ pub fn categorize_var_declarator(
     }
 }
 
+/// Checks whether an expression is a compile-time constant.
+/// Such expressions can never be a ref and are safe to hoist out of `setup`.
+///
+/// Supported are:
+/// - primitive literals: `'foo'`, `42`, `true`, `null`, `42n`;
+/// - unary operations on them: `-1`, `!0`;
+/// - template literals consisting of literals: `` `foo ${'bar'}` ``;
+/// - frozen objects and arrays of literals: `Object.freeze({ foo: 'bar' })`;
+/// - sequences of any of the above: `('foo', 42)`;
+/// - `as const` assertions of any of the above.
+pub fn is_static_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(lit) => matches!(
+            lit,
+            Lit::Str(_) | Lit::Num(_) | Lit::Bool(_) | Lit::Null(_) | Lit::BigInt(_)
+        ),
+
+        Expr::Tpl(tpl) => tpl.exprs.iter().all(|expr| is_static_literal(expr)),
+
+        Expr::Unary(unary_expr) => {
+            matches!(
+                unary_expr.op,
+                UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Bang | UnaryOp::Tilde
+            ) && is_static_literal(&unary_expr.arg)
+        }
+
+        Expr::Paren(paren_expr) => is_static_literal(&paren_expr.expr),
+
+        // Only the last expression is the value, but the others may have side effects,
+        // e.g. `(sideEffect(), 'a')`
+        Expr::Seq(seq_expr) => {
+            !seq_expr.exprs.is_empty() && seq_expr.exprs.iter().all(|expr| is_static_literal(expr))
+        }
+
+        Expr::TsConstAssertion(const_assertion) => is_static_literal(&const_assertion.expr),
+
+        // `Object.freeze({ foo: 'bar' })`
+        Expr::Call(call_expr) => {
+            let Callee::Expr(ref callee_expr) = call_expr.callee else {
+                return false;
+            };
+
+            let Expr::Member(ref member_expr) = **callee_expr else {
+                return false;
+            };
+
+            let is_object_freeze = matches!(
+                (&*member_expr.obj, &member_expr.prop),
+                (Expr::Ident(obj), MemberProp::Ident(prop))
+                    if &*obj.sym == "Object" && &*prop.sym == "freeze"
+            );
+
+            if !is_object_freeze || call_expr.args.len() != 1 {
+                return false;
+            }
+
+            let arg = &call_expr.args[0];
+            arg.spread.is_none() && is_static_frozen_value(&arg.expr)
+        }
+
+        _ => false,
+    }
+}
+
+/// Checks the insides of `Object.freeze()` in [`is_static_literal`].
+/// Nested objects and arrays are allowed as long as they only consist of literals.
+fn is_static_frozen_value(expr: &Expr) -> bool {
+    match expr {
+        Expr::Object(obj_lit) => obj_lit.props.iter().all(|prop| {
+            let PropOrSpread::Prop(prop) = prop else {
+                return false;
+            };
+
+            let Prop::KeyValue(ref key_value) = **prop else {
+                return false;
+            };
+
+            !matches!(key_value.key, PropName::Computed(_))
+                && is_static_frozen_value(&key_value.value)
+        }),
+
+        Expr::Array(arr_lit) => arr_lit.elems.iter().all(|elem| match elem {
+            Some(elem) => elem.spread.is_none() && is_static_frozen_value(&elem.expr),
+            None => true,
+        }),
+
+        _ => is_static_literal(expr),
+    }
+}
+
 /// Collects the destructures, e.g. `foo` in `const { foo = 123 } = {}` or `bar` in `let [bar] = [123]`
-fn collect_destructure(dest: &Pat, out: &mut Vec<SetupBinding>, is_const: bool) {
+pub fn collect_destructure(dest: &Pat, out: &mut Vec<SetupBinding>, is_const: bool) {
     match dest {
         // Base case for recursion
         Pat::Ident(ident) => out.push(SetupBinding(
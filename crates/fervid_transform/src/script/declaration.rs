@@ -0,0 +1,632 @@
+//! Generates the TypeScript declaration (`.d.ts`) of a compiled component,
+//! so that a prebuilt component can be shipped with types without running `vue-tsc`.
+//!
+//! The public interface is taken from the macros of `<script setup>`
+//! or from the `props` and `emits` of the Options API:
+//!
+//! ```ts
+//! declare const _default: new () => {
+//!     $props: { msg: string; count?: number; onChange?: (...args: any[]) => any };
+//!     $emit: (event: "change", ...args: any[]) => void;
+//!     $slots: { default(): any };
+//! } & { focus: any };
+//! export default _default;
+//! ```
+
+use fervid_core::FervidAtom;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        BindingIdent, Bool, Decl, ExportDefaultExpr, Expr, Ident, Lit, Module, ModuleDecl,
+        ModuleItem, ObjectLit, Pat, Prop, PropName, PropOrSpread, RestPat, Stmt, Str, TsArrayType,
+        TsConstructorType, TsEntityName, TsFnOrConstructorType, TsFnParam, TsFnType,
+        TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit, TsLitType,
+        TsPropertySignature, TsType, TsTypeAnn, TsTypeElement, TsTypeLit, TsTypeParamInstantiation,
+        TsTypeRef, TsUnionOrIntersectionType, TsUnionType, VarDecl, VarDeclKind, VarDeclarator,
+    },
+};
+
+use crate::{
+    atoms::{EMITS, PROPS},
+    structs::SfcExportedObjectHelper,
+};
+
+/// Generates the declaration module of a component.
+///
+/// `module` is the transformed script, its imports and type declarations are kept,
+/// because the macro types may refer to them.
+/// `sfc_object_helper` is present for `<script setup>`, otherwise `export_obj` is analyzed.
+pub fn generate_declaration(
+    module: &Module,
+    export_obj: &ObjectLit,
+    sfc_object_helper: Option<&SfcExportedObjectHelper>,
+) -> Module {
+    let mut body = Vec::<ModuleItem>::new();
+
+    // Imports with bindings and type declarations, side-effect imports are not needed
+    for module_item in module.body.iter() {
+        let is_needed = match module_item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)) => {
+                !import_decl.specifiers.is_empty()
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                is_type_decl(&export_decl.decl)
+            }
+            ModuleItem::Stmt(Stmt::Decl(decl)) => is_type_decl(decl),
+            _ => false,
+        };
+
+        if is_needed {
+            body.push(module_item.to_owned());
+        }
+    }
+    if let Some(sfc_object_helper) = sfc_object_helper {
+        body.extend(
+            sfc_object_helper
+                .type_decls
+                .iter()
+                .map(|decl| ModuleItem::Stmt(Stmt::Decl(decl.to_owned()))),
+        );
+    }
+
+    // Runtime declarations, `<script setup>` macros take precedence
+    let runtime_props = sfc_object_helper
+        .and_then(|helper| helper.props.as_deref())
+        .or_else(|| find_field(export_obj, &PROPS));
+    let runtime_emits = sfc_object_helper
+        .and_then(|helper| helper.emits.as_deref())
+        .or_else(|| find_field(export_obj, &EMITS));
+
+    let mut props_members = Vec::<TsTypeElement>::new();
+    if let Some(runtime_props) = runtime_props {
+        collect_runtime_props(runtime_props, &mut props_members);
+    }
+
+    // Listeners are also accepted as props, e.g. `onChange`
+    let event_names = runtime_emits.map_or_else(Vec::new, collect_event_names);
+    for event_name in event_names.iter() {
+        props_members.push(property(
+            &event_name_to_handler(event_name),
+            true,
+            any_fn_type(keyword(TsKeywordTypeKind::TsAnyKeyword)),
+        ));
+    }
+
+    // `$props`
+    let props_type = match sfc_object_helper.and_then(|helper| helper.props_type.as_ref()) {
+        Some(props_type) if props_members.is_empty() => props_type.to_owned(),
+        Some(props_type) => intersection(vec![props_type.to_owned(), type_lit(props_members)]),
+        None => type_lit(props_members),
+    };
+    let mut instance_members = vec![property("$props", false, props_type)];
+
+    // `$emit`
+    let emits_type = sfc_object_helper.and_then(|helper| helper.emits_type.as_ref());
+    if let Some(emits_type) = emits_type {
+        instance_members.push(property("$emit", false, emits_type.to_owned()));
+    } else if !event_names.is_empty() {
+        // `(event: "change" | "input", ...args: any[]) => void`
+        let event_type = union(
+            event_names
+                .iter()
+                .map(|event_name| {
+                    Box::new(TsType::TsLitType(TsLitType {
+                        span: DUMMY_SP,
+                        lit: TsLit::Str(Str {
+                            span: DUMMY_SP,
+                            value: event_name.to_owned(),
+                            raw: None,
+                        }),
+                    }))
+                })
+                .collect(),
+        );
+        let event_param = TsFnParam::Ident(BindingIdent {
+            id: ident("event"),
+            type_ann: Some(type_ann(event_type)),
+        });
+        instance_members.push(property(
+            "$emit",
+            false,
+            fn_type(
+                vec![event_param, rest_args_param()],
+                keyword(TsKeywordTypeKind::TsVoidKeyword),
+            ),
+        ));
+    }
+
+    // `$slots`
+    if let Some(slots_type) = sfc_object_helper.and_then(|helper| helper.slots_type.as_ref()) {
+        instance_members.push(property("$slots", false, slots_type.to_owned()));
+    }
+
+    // Exposed bindings are available on the instance, their types are not inferred
+    let exposed_members: Vec<TsTypeElement> = sfc_object_helper
+        .and_then(|helper| helper.exposed.as_deref())
+        .map_or_else(Vec::new, collect_object_keys)
+        .iter()
+        .map(|key| property(key, false, keyword(TsKeywordTypeKind::TsAnyKeyword)))
+        .collect();
+
+    let mut instance_type = type_lit(instance_members);
+    if !exposed_members.is_empty() {
+        instance_type = intersection(vec![instance_type, type_lit(exposed_members)]);
+    }
+
+    // `declare const _default: new () => { /*...*/ }`
+    let constructor_type = TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsConstructorType(
+        TsConstructorType {
+            span: DUMMY_SP,
+            params: vec![],
+            type_params: None,
+            type_ann: type_ann(instance_type),
+            is_abstract: false,
+        },
+    ));
+    body.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: true,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: ident("_default"),
+                type_ann: Some(type_ann(Box::new(constructor_type))),
+            }),
+            init: None,
+            definite: false,
+        }],
+    })))));
+
+    // `export default _default`
+    body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+        ExportDefaultExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Ident(ident("_default"))),
+        },
+    )));
+
+    Module {
+        span: DUMMY_SP,
+        body,
+        shebang: None,
+    }
+}
+
+/// Converts the runtime props declaration to the property signatures.
+/// Supports an array of names, an object of options and `mergeModels()`
+fn collect_runtime_props(runtime_props: &Expr, out: &mut Vec<TsTypeElement>) {
+    match runtime_props {
+        // `['foo', 'bar']`
+        Expr::Array(array_lit) => {
+            for elem in array_lit.elems.iter().flatten() {
+                if let Expr::Lit(Lit::Str(ref prop_name)) = *elem.expr {
+                    out.push(property(
+                        &prop_name.value,
+                        true,
+                        keyword(TsKeywordTypeKind::TsAnyKeyword),
+                    ));
+                }
+            }
+        }
+
+        // `{ foo: String, bar: { type: Number, required: true } }`
+        Expr::Object(object_lit) => {
+            for prop in object_lit.props.iter() {
+                let PropOrSpread::Prop(ref prop) = prop else {
+                    continue;
+                };
+
+                match **prop {
+                    Prop::KeyValue(ref key_value) => {
+                        let Some(prop_name) = prop_name_str(&key_value.key) else {
+                            continue;
+                        };
+                        let (prop_type, is_required) = runtime_prop_type(&key_value.value);
+                        out.push(property(prop_name, !is_required, prop_type));
+                    }
+
+                    Prop::Shorthand(ref ident) => out.push(property(
+                        &ident.sym,
+                        true,
+                        keyword(TsKeywordTypeKind::TsAnyKeyword),
+                    )),
+
+                    _ => {}
+                }
+            }
+        }
+
+        // `_mergeModels(props, { modelValue: {} })`
+        Expr::Call(call_expr) => {
+            for arg in call_expr.args.iter() {
+                collect_runtime_props(&arg.expr, out);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Type of the prop and whether it is required
+fn runtime_prop_type(prop_options: &Expr) -> (Box<TsType>, bool) {
+    let Expr::Object(ref options_obj) = prop_options else {
+        return (constructor_to_type(prop_options), false);
+    };
+
+    let mut prop_type = keyword(TsKeywordTypeKind::TsAnyKeyword);
+    let mut is_required = false;
+    for prop in options_obj.props.iter() {
+        let PropOrSpread::Prop(ref prop) = prop else {
+            continue;
+        };
+        let Prop::KeyValue(ref key_value) = **prop else {
+            continue;
+        };
+
+        match prop_name_str(&key_value.key) {
+            Some("type") => prop_type = constructor_to_type(&key_value.value),
+            Some("required") => {
+                is_required = matches!(
+                    *key_value.value,
+                    Expr::Lit(Lit::Bool(Bool { value: true, .. }))
+                )
+            }
+            _ => {}
+        }
+    }
+
+    (prop_type, is_required)
+}
+
+/// `String` -> `string`, `[String, Number]` -> `string | number`,
+/// `Object as PropType<Foo>` -> `Foo`
+fn constructor_to_type(constructor: &Expr) -> Box<TsType> {
+    match constructor {
+        Expr::Ident(constructor_ident) => match &*constructor_ident.sym {
+            "String" => keyword(TsKeywordTypeKind::TsStringKeyword),
+            "Number" => keyword(TsKeywordTypeKind::TsNumberKeyword),
+            "Boolean" => keyword(TsKeywordTypeKind::TsBooleanKeyword),
+            "Symbol" => keyword(TsKeywordTypeKind::TsSymbolKeyword),
+            "BigInt" => keyword(TsKeywordTypeKind::TsBigIntKeyword),
+            "Array" => Box::new(TsType::TsArrayType(TsArrayType {
+                span: DUMMY_SP,
+                elem_type: keyword(TsKeywordTypeKind::TsAnyKeyword),
+            })),
+            "Object" => type_ref(
+                "Record",
+                vec![
+                    keyword(TsKeywordTypeKind::TsStringKeyword),
+                    keyword(TsKeywordTypeKind::TsAnyKeyword),
+                ],
+            ),
+            "Function" => any_fn_type(keyword(TsKeywordTypeKind::TsAnyKeyword)),
+
+            // Classes, e.g. `Date` or a user class
+            _ => type_ref(&constructor_ident.sym, vec![]),
+        },
+
+        Expr::Array(array_lit) => {
+            let types: Vec<Box<TsType>> = array_lit
+                .elems
+                .iter()
+                .flatten()
+                .map(|elem| constructor_to_type(&elem.expr))
+                .collect();
+
+            match types.len() {
+                0 => keyword(TsKeywordTypeKind::TsAnyKeyword),
+                1 => types.into_iter().next().unwrap(),
+                _ => union(types),
+            }
+        }
+
+        Expr::TsAs(ts_as_expr) => match *ts_as_expr.type_ann {
+            TsType::TsTypeRef(TsTypeRef {
+                type_name: TsEntityName::Ident(ref type_name),
+                type_params: Some(ref type_params),
+                ..
+            }) if &*type_name.sym == "PropType" => type_params
+                .params
+                .get(0)
+                .cloned()
+                .unwrap_or_else(|| keyword(TsKeywordTypeKind::TsAnyKeyword)),
+            _ => keyword(TsKeywordTypeKind::TsAnyKeyword),
+        },
+
+        Expr::Paren(paren_expr) => constructor_to_type(&paren_expr.expr),
+
+        _ => keyword(TsKeywordTypeKind::TsAnyKeyword),
+    }
+}
+
+/// Event names from the runtime `emits`, either an array or an object
+fn collect_event_names(runtime_emits: &Expr) -> Vec<FervidAtom> {
+    match runtime_emits {
+        Expr::Array(array_lit) => array_lit
+            .elems
+            .iter()
+            .flatten()
+            .filter_map(|elem| match *elem.expr {
+                Expr::Lit(Lit::Str(ref event_name)) => Some(event_name.value.to_owned()),
+                _ => None,
+            })
+            .collect(),
+
+        Expr::Object(_) => collect_object_keys(runtime_emits),
+
+        // `_mergeModels(emits, ["update:modelValue"])`
+        Expr::Call(call_expr) => call_expr
+            .args
+            .iter()
+            .flat_map(|arg| collect_event_names(&arg.expr))
+            .collect(),
+
+        _ => vec![],
+    }
+}
+
+/// Static keys of an object literal, e.g. `defineExpose({ focus, count: 1 })`
+fn collect_object_keys(expr: &Expr) -> Vec<FervidAtom> {
+    let Expr::Object(ref object_lit) = expr else {
+        return vec![];
+    };
+
+    object_lit
+        .props
+        .iter()
+        .filter_map(|prop| {
+            let PropOrSpread::Prop(ref prop) = prop else {
+                return None;
+            };
+
+            match **prop {
+                Prop::Shorthand(ref ident) => Some(ident.sym.to_owned()),
+                Prop::KeyValue(ref key_value) => {
+                    prop_name_str(&key_value.key).map(FervidAtom::from)
+                }
+                Prop::Method(ref method) => prop_name_str(&method.key).map(FervidAtom::from),
+                Prop::Getter(ref getter) => prop_name_str(&getter.key).map(FervidAtom::from),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn find_field<'o>(export_obj: &'o ObjectLit, field: &str) -> Option<&'o Expr> {
+    export_obj.props.iter().find_map(|prop| {
+        let PropOrSpread::Prop(ref prop) = prop else {
+            return None;
+        };
+        let Prop::KeyValue(ref key_value) = **prop else {
+            return None;
+        };
+
+        (prop_name_str(&key_value.key) == Some(field)).then_some(&*key_value.value)
+    })
+}
+
+fn prop_name_str(prop_name: &PropName) -> Option<&str> {
+    match prop_name {
+        PropName::Ident(ref ident) => Some(&ident.sym),
+        PropName::Str(ref s) => Some(&s.value),
+        _ => None,
+    }
+}
+
+fn is_type_decl(decl: &Decl) -> bool {
+    matches!(decl, Decl::TsInterface(_) | Decl::TsTypeAlias(_))
+}
+
+/// `change` -> `onChange`, `update:modelValue` -> `onUpdate:modelValue`
+fn event_name_to_handler(event_name: &str) -> String {
+    let mut chars = event_name.chars();
+    let mut handler = String::with_capacity(event_name.len() + 2);
+    handler.push_str("on");
+    if let Some(first) = chars.next() {
+        handler.extend(first.to_uppercase());
+        handler.push_str(chars.as_str());
+    }
+    handler
+}
+
+/// `key: type` or `key?: type`, the key is quoted when it is not an identifier
+fn property(key: &str, optional: bool, prop_type: Box<TsType>) -> TsTypeElement {
+    let is_ident = !key.starts_with(|c: char| c.is_ascii_digit())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    let key = if is_ident {
+        Expr::Ident(ident(key))
+    } else {
+        Expr::Lit(Lit::Str(Str {
+            span: DUMMY_SP,
+            value: FervidAtom::from(key),
+            raw: None,
+        }))
+    };
+
+    TsTypeElement::TsPropertySignature(TsPropertySignature {
+        span: DUMMY_SP,
+        readonly: false,
+        key: Box::new(key),
+        computed: false,
+        optional,
+        init: None,
+        params: vec![],
+        type_ann: Some(type_ann(prop_type)),
+        type_params: None,
+    })
+}
+
+/// `(...args: any[]) => return_type`
+fn any_fn_type(return_type: Box<TsType>) -> Box<TsType> {
+    fn_type(vec![rest_args_param()], return_type)
+}
+
+fn fn_type(params: Vec<TsFnParam>, return_type: Box<TsType>) -> Box<TsType> {
+    Box::new(TsType::TsFnOrConstructorType(
+        TsFnOrConstructorType::TsFnType(TsFnType {
+            span: DUMMY_SP,
+            params,
+            type_params: None,
+            type_ann: type_ann(return_type),
+        }),
+    ))
+}
+
+/// `...args: any[]`
+fn rest_args_param() -> TsFnParam {
+    TsFnParam::Rest(RestPat {
+        span: DUMMY_SP,
+        dot3_token: DUMMY_SP,
+        arg: Box::new(Pat::Ident(BindingIdent {
+            id: ident("args"),
+            type_ann: None,
+        })),
+        type_ann: Some(type_ann(Box::new(TsType::TsArrayType(TsArrayType {
+            span: DUMMY_SP,
+            elem_type: keyword(TsKeywordTypeKind::TsAnyKeyword),
+        })))),
+    })
+}
+
+fn type_lit(members: Vec<TsTypeElement>) -> Box<TsType> {
+    Box::new(TsType::TsTypeLit(TsTypeLit {
+        span: DUMMY_SP,
+        members,
+    }))
+}
+
+fn type_ref(name: &str, params: Vec<Box<TsType>>) -> Box<TsType> {
+    Box::new(TsType::TsTypeRef(TsTypeRef {
+        span: DUMMY_SP,
+        type_name: TsEntityName::Ident(ident(name)),
+        type_params: (!params.is_empty()).then(|| {
+            Box::new(TsTypeParamInstantiation {
+                span: DUMMY_SP,
+                params,
+            })
+        }),
+    }))
+}
+
+fn union(types: Vec<Box<TsType>>) -> Box<TsType> {
+    Box::new(TsType::TsUnionOrIntersectionType(
+        TsUnionOrIntersectionType::TsUnionType(TsUnionType {
+            span: DUMMY_SP,
+            types,
+        }),
+    ))
+}
+
+fn intersection(types: Vec<Box<TsType>>) -> Box<TsType> {
+    Box::new(TsType::TsUnionOrIntersectionType(
+        TsUnionOrIntersectionType::TsIntersectionType(TsIntersectionType {
+            span: DUMMY_SP,
+            types,
+        }),
+    ))
+}
+
+fn keyword(kind: TsKeywordTypeKind) -> Box<TsType> {
+    Box::new(TsType::TsKeywordType(TsKeywordType {
+        span: DUMMY_SP,
+        kind,
+    }))
+}
+
+fn type_ann(type_ann: Box<TsType>) -> Box<TsTypeAnn> {
+    Box::new(TsTypeAnn {
+        span: DUMMY_SP,
+        type_ann,
+    })
+}
+
+fn ident(sym: &str) -> Ident {
+    Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(sym),
+        optional: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{BindingsHelper, SfcScriptBlock, SfcScriptLang};
+
+    use crate::{
+        script::transform_and_record_scripts,
+        test_utils::{parser::parse_typescript_module, to_str},
+    };
+
+    fn test_out(script_setup: &str, script_legacy: Option<&str>) -> String {
+        let script = |input: &str, is_setup: bool| SfcScriptBlock {
+            content: Box::new(
+                parse_typescript_module(input, 0, Default::default())
+                    .expect("script expects the input to be parseable")
+                    .0,
+            ),
+            lang: SfcScriptLang::Typescript,
+            is_setup,
+        };
+
+        let result = transform_and_record_scripts(
+            (!script_setup.is_empty()).then(|| script(script_setup, true)),
+            script_legacy.map(|input| script(input, false)),
+            vec![],
+            vec![],
+            "",
+            true,
+            &mut BindingsHelper::default(),
+        );
+
+        to_str(&result.declaration.expect("declaration was requested"))
+    }
+
+    #[test]
+    fn it_declares_typed_macros() {
+        assert_eq!(
+            test_out(
+                r"
+                import type { Item } from './types'
+                import 'uno.css'
+                interface Props { items: Item[] }
+                defineProps<Props>()
+                defineEmits<{ (e: 'select', item: Item): void }>()
+                defineSlots<{ default(props: { item: Item }): any }>()
+                const focus = () => {}
+                defineExpose({ focus })
+                ",
+                None
+            ),
+            r#"import type{Item}from"./types";interface Props{items:Item[];}declare const _default:new()=>{$props:Props;$emit:{(e:"select",item:Item):void;};$slots:{default(props:{item:Item;}):any;};}&{focus:any;};export default _default;"#
+        );
+    }
+
+    #[test]
+    fn it_declares_runtime_props_and_emits() {
+        assert_eq!(
+            test_out(
+                r"
+                defineProps({
+                    msg: { type: String, required: true },
+                    count: [Number, String],
+                    user: Object as PropType<User>,
+                })
+                defineEmits(['change'])
+                defineModel()
+                ",
+                None
+            ),
+            r#"declare const _default:new()=>{$props:{msg:string;count?:number|string;user?:User;modelValue?:any;onChange?:(...args:any[])=>any;"onUpdate:modelValue"?:(...args:any[])=>any;};$emit:(event:"change"|"update:modelValue",...args:any[])=>void;};export default _default;"#
+        );
+
+        // Options API
+        assert_eq!(
+            test_out("", Some("export default { props: ['title'] }")),
+            r#"declare const _default:new()=>{$props:{title?:any;};};export default _default;"#
+        );
+    }
+}
@@ -86,6 +86,15 @@ fn find_default_export_obj(module: &mut Module) -> Option<ObjectLit> {
         return None;
     };
 
+    // `export default Foo` is left as-is, e.g. for class components
+    if let ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ref export_default_expr)) =
+        module.body[idx]
+    {
+        if export_default_expr.expr.is_ident() {
+            return None;
+        }
+    }
+
     let item = module.body.remove(idx);
     // TODO What to do with weird default exports?
     let ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(expr)) = item else {
@@ -130,6 +130,11 @@ pub fn analyze_decl(
     vue_imports: &mut VueResolvedImports,
 ) {
     match decl {
+        // Ambient declarations, e.g. `declare const foo: string`, do not exist in runtime
+        Decl::Class(class) if class.declare => {}
+        Decl::Fn(fn_decl) if fn_decl.declare => {}
+        Decl::Var(var_decl) if var_decl.declare => {}
+
         Decl::Class(class) => out.push(categorize_class(class)),
 
         Decl::Fn(fn_decl) => out.push(categorize_fn_decl(fn_decl)),
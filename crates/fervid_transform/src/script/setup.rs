@@ -1,9 +1,10 @@
-use fervid_core::{BindingsHelper, SfcScriptBlock};
+use fervid_core::{BindingsHelper, FervidAtom, SfcScriptBlock};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{
-        BindingIdent, BlockStmt, Function, Id, Ident, KeyValuePatProp, KeyValueProp, ModuleDecl,
-        ModuleItem, ObjectPat, ObjectPatProp, Param, Pat, Prop, PropName, PropOrSpread, Stmt,
+        BindingIdent, BlockStmt, Expr, Function, Id, Ident, Invalid, KeyValuePatProp, KeyValueProp,
+        ModuleDecl, ModuleItem, ObjectPat, ObjectPatProp, Param, Pat, Prop, PropName, PropOrSpread,
+        Stmt,
     },
 };
 
@@ -12,18 +13,27 @@ use crate::{
     structs::{SfcExportedObjectHelper, VueResolvedImports},
 };
 
+mod css_modules;
+mod css_vars;
 mod imports;
 mod macros;
 mod statements;
 
+pub use css_modules::*;
+pub use css_vars::*;
 pub use imports::*;
 pub use statements::*;
 
-use self::macros::postprocess_macros;
+use self::macros::{
+    generate_runtime_emits, generate_runtime_props, merge_runtime_declarations, postprocess_macros,
+    record_props_bindings,
+};
 
 pub struct TransformScriptSetupResult {
     /// All the imports (and maybe exports) of the `<script setup>`
     pub module_decls: Vec<ModuleDecl>,
+    /// Statements hoisted out of `setup`, e.g. literal constants
+    pub hoisted_stmts: Vec<Stmt>,
     /// SFC object produced in a form of helper
     pub sfc_object_helper: SfcExportedObjectHelper,
     /// `setup` function produced
@@ -42,6 +52,7 @@ pub fn transform_and_record_script_setup(
     let mut vue_user_imports = VueResolvedImports::default();
     let mut imports = Vec::<Id>::new();
     let mut setup_body_stmts = Vec::<Stmt>::new();
+    let mut hoisted_stmts = Vec::<Stmt>::new();
 
     // Go over the whole script setup: process all the statements and declarations
     for module_item in script_setup.content.body {
@@ -51,6 +62,7 @@ pub fn transform_and_record_script_setup(
                 // TODO And maybe non-Vue as well?
                 if let ModuleDecl::Import(ref import_decl) = decl {
                     collect_imports(import_decl, &mut imports, &mut vue_user_imports);
+                    collect_namespace_imports(import_decl, &mut bindings_helper.setup_bindings);
                 }
 
                 module_decls.push(decl);
@@ -63,7 +75,13 @@ pub fn transform_and_record_script_setup(
                     &vue_user_imports,
                     &mut sfc_object_helper,
                 ) {
-                    setup_body_stmts.push(transformed_stmt);
+                    // Literal constants, e.g. `const title = 'Hello'`, can live outside `setup`,
+                    // and ambient declarations, e.g. `declare const foo: number`, must
+                    if is_hoistable_stmt(&transformed_stmt) || is_ambient_stmt(&transformed_stmt) {
+                        hoisted_stmts.push(transformed_stmt);
+                    } else {
+                        setup_body_stmts.push(transformed_stmt);
+                    }
                 }
             }
         }
@@ -71,6 +89,7 @@ pub fn transform_and_record_script_setup(
 
     // Post-process macros, e.g. merge models to `props` and `emits`
     postprocess_macros(bindings_helper, &mut sfc_object_helper);
+    record_props_bindings(bindings_helper, &mut sfc_object_helper);
 
     // Should we check that this function was not assigned anywhere else?
     let setup_fn = Some(Box::new(Function {
@@ -89,33 +108,97 @@ pub fn transform_and_record_script_setup(
 
     TransformScriptSetupResult {
         module_decls,
+        hoisted_stmts,
         sfc_object_helper,
         setup_fn,
     }
 }
 
-pub fn merge_sfc_helper(sfc_helper: SfcExportedObjectHelper, dest: &mut Vec<PropOrSpread>) {
-    macro_rules! merge {
-        ($field: ident, $span: expr, $sym: expr) => {
-            if let Some(value) = sfc_helper.$field {
-                dest.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                    key: PropName::Ident(Ident {
-                        span: $span,
-                        sym: $sym,
-                        optional: false,
-                    }),
-                    value,
-                }))));
-            }
-        };
+/// Merges the fields collected from `<script setup>` into the exported object.
+///
+/// The runtime `props` and `emits` are generated from the macro type arguments when needed,
+/// and are merged with the `props` and `emits` already declared in the Options API `<script>`.
+pub fn merge_sfc_helper(
+    sfc_helper: SfcExportedObjectHelper,
+    dest: &mut Vec<PropOrSpread>,
+    bindings_helper: &mut BindingsHelper,
+) {
+    // `defineProps<T>()` and `defineEmits<T>()` go before the models
+    let mut props = sfc_helper.props;
+    if let Some(ref props_type) = sfc_helper.props_type {
+        if let Some(type_props) = generate_runtime_props(props_type, &sfc_helper.type_decls) {
+            let type_props = Box::new(type_props);
+            props = Some(match props {
+                Some(props) => merge_runtime_declarations(type_props, props, bindings_helper),
+                None => type_props,
+            });
+        }
     }
 
-    merge!(emits, DUMMY_SP, EMITS.to_owned());
-    merge!(props, DUMMY_SP, PROPS.to_owned());
+    let mut emits = sfc_helper.emits;
+    if let Some(ref emits_type) = sfc_helper.emits_type {
+        if let Some(type_emits) = generate_runtime_emits(emits_type, &sfc_helper.type_decls) {
+            let type_emits = Box::new(type_emits);
+            emits = Some(match emits {
+                Some(emits) => merge_runtime_declarations(type_emits, emits, bindings_helper),
+                None => type_emits,
+            });
+        }
+    }
+
+    if let Some(emits) = emits {
+        merge_runtime_field(dest, EMITS.to_owned(), emits, bindings_helper);
+    }
+    if let Some(props) = props {
+        merge_runtime_field(dest, PROPS.to_owned(), props, bindings_helper);
+    }
 
     dest.extend(sfc_helper.untyped_fields);
 }
 
+/// Adds `props` or `emits` to the exported object.
+/// When the Options API already declares the field, both declarations are merged
+fn merge_runtime_field(
+    dest: &mut Vec<PropOrSpread>,
+    sym: FervidAtom,
+    value: Box<Expr>,
+    bindings_helper: &mut BindingsHelper,
+) {
+    let existing = dest.iter_mut().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+        let Prop::KeyValue(ref mut key_value) = **prop else {
+            return None;
+        };
+
+        let is_same_field = match key_value.key {
+            PropName::Ident(ref ident) => ident.sym == sym,
+            PropName::Str(ref s) => s.value == sym,
+            _ => false,
+        };
+        is_same_field.then_some(&mut key_value.value)
+    });
+
+    match existing {
+        Some(existing) => {
+            let span = DUMMY_SP;
+            let options_value =
+                std::mem::replace(existing, Box::new(Expr::Invalid(Invalid { span })));
+            *existing = merge_runtime_declarations(options_value, value, bindings_helper);
+        }
+
+        None => dest.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident {
+                span: DUMMY_SP,
+                sym,
+                optional: false,
+            }),
+            value,
+        })))),
+    }
+}
+
 /// Used to populate the params to `setup()`, such as `__props`, `emit`, etc.
 fn get_setup_fn_params(sfc_object_helper: &SfcExportedObjectHelper) -> Vec<Param> {
     let has_ctx_param =
@@ -187,8 +270,14 @@ fn get_setup_fn_params(sfc_object_helper: &SfcExportedObjectHelper) -> Vec<Param
 
 #[cfg(test)]
 mod tests {
-    use crate::test_utils::parser::*;
-    use fervid_core::{BindingTypes, SfcScriptBlock, SetupBinding, BindingsHelper, FervidAtom};
+    use crate::{
+        template::expr_transform::BindingsHelperTransform,
+        test_utils::{parser::*, to_str},
+    };
+    use fervid_core::{
+        BindingTypes, BindingsHelper, FervidAtom, SetupBinding, SfcScriptBlock,
+        TemplateGenerationMode, VueImports,
+    };
 
     use super::transform_and_record_script_setup;
 
@@ -254,6 +343,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_records_props_bindings() {
+        // Setup variables shadow the props
+        test_js_and_ts!(
+            r"
+            defineProps(['foo', 'bar'])
+            const bar = 42
+            defineModel('baz')
+            ",
+            vec![
+                SetupBinding(FervidAtom::from("bar"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("foo"), BindingTypes::Props),
+                SetupBinding(FervidAtom::from("baz"), BindingTypes::Props),
+            ]
+        );
+
+        assert_eq!(
+            analyze_ts_bindings(
+                r"
+                interface Props { msg: string; 'data-id'?: number }
+                defineProps<Props & { size: number }>()
+                "
+            ),
+            vec![
+                SetupBinding(FervidAtom::from("msg"), BindingTypes::Props),
+                SetupBinding(FervidAtom::from("data-id"), BindingTypes::Props),
+                SetupBinding(FervidAtom::from("size"), BindingTypes::Props),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_transforms_props_destructure_rest() {
+        let parsed = parse_javascript_module(
+            r"
+            const { foo, bar: baz, qux = 1, ...rest } = defineProps(['foo', 'bar', 'qux', 'other'])
+            ",
+            0,
+            Default::default(),
+        )
+        .expect("it_transforms_props_destructure_rest expects the input to be parseable")
+        .0;
+
+        let mut bindings_helper = BindingsHelper::default();
+        let result = transform_and_record_script_setup(
+            SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Es,
+                is_setup: true,
+            },
+            &mut bindings_helper,
+        );
+
+        assert_eq!(
+            bindings_helper.setup_bindings,
+            vec![
+                SetupBinding(FervidAtom::from("foo"), BindingTypes::Props),
+                SetupBinding(FervidAtom::from("baz"), BindingTypes::PropsAliased),
+                SetupBinding(FervidAtom::from("qux"), BindingTypes::SetupMaybeRef),
+                SetupBinding(FervidAtom::from("rest"), BindingTypes::SetupReactiveConst),
+                SetupBinding(FervidAtom::from("bar"), BindingTypes::Props),
+                SetupBinding(FervidAtom::from("other"), BindingTypes::Props),
+            ]
+        );
+        assert!(bindings_helper
+            .vue_imports
+            .contains(VueImports::CreatePropsRestProxy));
+
+        let setup_body = to_str(&result.setup_fn.and_then(|setup_fn| setup_fn.body).unwrap());
+        assert!(setup_body.contains(r#"bar:baz,qux=1}=__props,"#));
+        assert!(setup_body.contains(r#"rest=_createPropsRestProxy(__props,["foo","bar","qux"])"#));
+
+        // Only the rest is destructured
+        let parsed = parse_javascript_module(
+            r"const { ...rest } = defineProps(['foo'])",
+            0,
+            Default::default(),
+        )
+        .expect("it_transforms_props_destructure_rest expects the input to be parseable")
+        .0;
+
+        let mut bindings_helper = BindingsHelper::default();
+        let result = transform_and_record_script_setup(
+            SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Es,
+                is_setup: true,
+            },
+            &mut bindings_helper,
+        );
+        let setup_body = to_str(&result.setup_fn.and_then(|setup_fn| setup_fn.body).unwrap());
+        assert!(setup_body.contains("const rest=_createPropsRestProxy(__props,[])"));
+    }
+
+    #[test]
+    fn it_resolves_props_aliases() {
+        let parsed = parse_javascript_module(
+            r"const { foo: bar } = defineProps(['foo'])",
+            0,
+            Default::default(),
+        )
+        .expect("it_resolves_props_aliases expects the input to be parseable")
+        .0;
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        transform_and_record_script_setup(
+            SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Es,
+                is_setup: true,
+            },
+            &mut bindings_helper,
+        );
+
+        assert_eq!(
+            bindings_helper.setup_bindings[0],
+            SetupBinding(FervidAtom::from("bar"), BindingTypes::PropsAliased)
+        );
+        assert_eq!(
+            bindings_helper.props_aliases.get("bar"),
+            Some(&FervidAtom::from("foo"))
+        );
+
+        // Template access reads the original prop
+        let mut expr = parse_javascript_expr("bar", 0, Default::default())
+            .expect("it_resolves_props_aliases expects the input to be parseable")
+            .0;
+        bindings_helper.transform_expr(&mut expr, 0);
+        assert_eq!(to_str(&*expr), r#"__props["foo"]"#);
+    }
+
     #[test]
     fn it_recognizes_non_vue_refs() {
         test_js_and_ts!(
@@ -285,6 +506,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_recognizes_async_components() {
+        test_js_and_ts!(
+            r"
+            import { defineAsyncComponent as asyncComponent } from 'vue'
+            import { defineAsyncComponent } from './not-vue'
+
+            const Foo = asyncComponent(() => import('./Foo.vue'))
+            const Bar = defineAsyncComponent(() => import('./Bar.vue'))
+            ",
+            vec![
+                SetupBinding(FervidAtom::from("Foo"), BindingTypes::SetupConst),
+                SetupBinding(FervidAtom::from("Bar"), BindingTypes::SetupMaybeRef),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_ignores_ambient_and_type_decls() {
+        let parsed = parse_typescript_module(
+            r"
+            declare const foo: number
+            declare function bar(): void
+            declare class Baz {}
+            declare global { interface Window { qux: string } }
+            interface Props { qux: string }
+            type Emits = { (e: 'change'): void }
+            const answer = 42
+            ",
+            0,
+            Default::default(),
+        )
+        .expect("the input should be parseable")
+        .0;
+
+        let mut bindings_helper = BindingsHelper::default();
+        let result = transform_and_record_script_setup(
+            SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Typescript,
+                is_setup: true,
+            },
+            &mut bindings_helper,
+        );
+
+        assert_eq!(
+            bindings_helper.setup_bindings,
+            vec![SetupBinding(FervidAtom::from("answer"), BindingTypes::LiteralConst)]
+        );
+
+        // Types are kept for the macros
+        assert_eq!(result.sfc_object_helper.type_decls.len(), 2);
+
+        // Ambient declarations are not allowed in `setup`, thus they are hoisted with `answer`
+        assert_eq!(result.hoisted_stmts.len(), 5);
+        assert_eq!(
+            result
+                .setup_fn
+                .and_then(|setup_fn| setup_fn.body)
+                .map(|body| body.stmts.len()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn it_collects_namespace_imports() {
+        test_js_and_ts!(
+            r"
+            import * as Form from './form'
+            import { Input } from './input'
+            ",
+            vec![SetupBinding(FervidAtom::from("Form"), BindingTypes::SetupConst)]
+        );
+    }
+
     #[test]
     fn it_supports_ts_enums() {
         assert_eq!(
@@ -354,4 +650,77 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn it_recognizes_literal_consts() {
+        test_js_and_ts!(
+            r"
+            import { ref } from 'vue'
+
+            const str = 'Hello'
+            const num = -42
+            const tpl = `Hello ${'world'}`
+            const frozen = Object.freeze({ foo: 'bar', baz: [1, 2, 3] })
+            const notFrozen = Object.freeze({ foo: ref() })
+            const tplWithExpr = `Hello ${str}`
+            const seq = ('Hello', 42)
+            const seqEffect = (sideEffect(), 'Hello')
+            let letStr = 'Hello'
+            ",
+            vec![
+                SetupBinding(FervidAtom::from("str"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("num"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("tpl"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("frozen"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("notFrozen"), BindingTypes::SetupMaybeRef),
+                SetupBinding(FervidAtom::from("tplWithExpr"), BindingTypes::SetupConst),
+                SetupBinding(FervidAtom::from("seq"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("seqEffect"), BindingTypes::SetupConst),
+                SetupBinding(FervidAtom::from("letStr"), BindingTypes::SetupLet),
+            ]
+        );
+
+        assert_eq!(
+            analyze_ts_bindings("const foo = 'foo' as const"),
+            vec![SetupBinding(FervidAtom::from("foo"), BindingTypes::LiteralConst)]
+        );
+    }
+
+    #[test]
+    fn it_hoists_literal_consts() {
+        let parsed = parse_javascript_module(
+            r"
+            import { ref } from 'vue'
+
+            const title = 'Hello', answer = 42
+            const mixed = 'foo', count = ref(0)
+            const withSideEffect = (sideEffect(), 'baz')
+            let notConst = 'bar'
+            ",
+            0,
+            Default::default(),
+        )
+        .expect("it_hoists_literal_consts expects the input to be parseable")
+        .0;
+
+        let mut bindings_helper = BindingsHelper::default();
+        let result = transform_and_record_script_setup(
+            SfcScriptBlock {
+                content: Box::new(parsed),
+                lang: fervid_core::SfcScriptLang::Es,
+                is_setup: true,
+            },
+            &mut bindings_helper,
+        );
+
+        // Only `const title = 'Hello', answer = 42` is hoisted
+        assert_eq!(result.hoisted_stmts.len(), 1);
+        assert_eq!(
+            result
+                .setup_fn
+                .and_then(|setup_fn| setup_fn.body)
+                .map(|body| body.stmts.len()),
+            Some(3)
+        );
+    }
 }
@@ -0,0 +1,149 @@
+use fervid_core::{BindingTypes, BindingsHelper, SetupBinding, SfcCssModule};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        BindingIdent, Decl, Expr, Ident, KeyValueProp, Lit, ObjectLit, Pat, Prop, PropName,
+        PropOrSpread, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+    },
+};
+
+/// Generates the module-level constants for the `<style module>` blocks
+/// and registers them as literal constants, so that the template accesses them directly.
+/// A CSS module is skipped when `<script setup>` already declares a binding with the same name.
+///
+/// ## Example
+/// For `<style module>.red { color: red }</style>`:
+/// ```js
+/// const $style = {
+///     "red": "EgL3uq_red"
+/// }
+/// ```
+pub fn generate_css_modules(
+    css_modules: Vec<SfcCssModule>,
+    bindings_helper: &mut BindingsHelper,
+) -> Vec<Stmt> {
+    let mut result = Vec::with_capacity(css_modules.len());
+
+    for SfcCssModule { name, classes } in css_modules {
+        let is_declared = bindings_helper
+            .setup_bindings
+            .iter()
+            .any(|binding| binding.0 == name);
+        if is_declared {
+            continue;
+        }
+
+        let props = classes
+            .into_iter()
+            .map(|(local_name, hashed_name)| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(Str {
+                        span: DUMMY_SP,
+                        value: local_name,
+                        raw: None,
+                    }),
+                    value: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: hashed_name,
+                        raw: None,
+                    }))),
+                })))
+            })
+            .collect();
+
+        result.push(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                    id: Ident {
+                        span: DUMMY_SP,
+                        sym: name.to_owned(),
+                        optional: false,
+                    },
+                    type_ann: None,
+                }),
+                init: Some(Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                }))),
+                definite: false,
+            }],
+        }))));
+
+        bindings_helper
+            .setup_bindings
+            .push(SetupBinding(name, BindingTypes::LiteralConst));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::FervidAtom;
+
+    use super::*;
+    use crate::test_utils::to_str;
+
+    fn css_module(name: &str, classes: &[(&str, &str)]) -> SfcCssModule {
+        SfcCssModule {
+            name: FervidAtom::from(name),
+            classes: classes
+                .iter()
+                .map(|(local_name, hashed_name)| {
+                    (
+                        FervidAtom::from(*local_name),
+                        FervidAtom::from(*hashed_name),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn it_generates_css_modules() {
+        let mut bindings_helper = BindingsHelper::default();
+
+        let stmts = generate_css_modules(
+            vec![
+                css_module("$style", &[("red", "EgL3uq_red")]),
+                css_module("classes", &[("big-text", "EgL3uq_big-text")]),
+            ],
+            &mut bindings_helper,
+        );
+
+        assert_eq!(stmts.len(), 2);
+        assert_eq!(to_str(&stmts[0]), r#"const $style={"red":"EgL3uq_red"};"#);
+        assert_eq!(
+            to_str(&stmts[1]),
+            r#"const classes={"big-text":"EgL3uq_big-text"};"#
+        );
+        assert_eq!(
+            bindings_helper.setup_bindings,
+            vec![
+                SetupBinding(FervidAtom::from("$style"), BindingTypes::LiteralConst),
+                SetupBinding(FervidAtom::from("classes"), BindingTypes::LiteralConst),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_skips_css_modules_declared_in_setup() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings = vec![SetupBinding(
+            FervidAtom::from("$style"),
+            BindingTypes::SetupConst,
+        )];
+
+        let stmts = generate_css_modules(
+            vec![css_module("$style", &[("red", "EgL3uq_red")])],
+            &mut bindings_helper,
+        );
+
+        assert!(stmts.is_empty());
+        assert_eq!(bindings_helper.setup_bindings.len(), 1);
+    }
+}
@@ -0,0 +1,425 @@
+use fervid_core::{
+    fervid_atom, generate_css_var_name, BindingsHelper, FervidAtom, SfcCssVar,
+    TemplateGenerationMode, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread,
+        ExprStmt, FnExpr, Function, Ident, Invalid, KeyValueProp, MethodProp, ObjectLit, ParenExpr,
+        Pat, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Str,
+    },
+};
+
+use crate::{
+    atoms::{SETUP, USE_CSS_VARS_HELPER},
+    template::expr_transform::BindingsHelperTransform,
+};
+
+/// Generates the `useCssVars` call for the `v-bind()` usages in `<style>`.
+/// Duplicate usages are bound once.
+///
+/// ## Example
+/// For `.foo { color: v-bind(color) }` in DEV:
+/// ```js
+/// _useCssVars(_ctx => ({
+///     "abcd1234-color": color.value
+/// }))
+/// ```
+pub fn generate_use_css_vars(
+    css_vars: Vec<SfcCssVar>,
+    scope_id: &str,
+    bindings_helper: &mut BindingsHelper,
+) -> Option<Stmt> {
+    if css_vars.is_empty() {
+        return None;
+    }
+
+    // The call is a part of `setup`, so the bindings are accessed the same way as in inline mode
+    let old_generation_mode = std::mem::replace(
+        &mut bindings_helper.template_generation_mode,
+        TemplateGenerationMode::Inline,
+    );
+
+    let mut seen_vars = Vec::<FervidAtom>::with_capacity(css_vars.len());
+    let mut props = Vec::<PropOrSpread>::with_capacity(css_vars.len());
+
+    for SfcCssVar { raw, mut expr } in css_vars {
+        if seen_vars.contains(&raw) {
+            continue;
+        }
+
+        bindings_helper.transform_expr(&mut expr, 0);
+
+        props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str {
+                span: DUMMY_SP,
+                value: generate_css_var_name(scope_id, &raw, bindings_helper.is_prod),
+                raw: None,
+            }),
+            value: expr,
+        }))));
+
+        seen_vars.push(raw);
+    }
+
+    bindings_helper.template_generation_mode = old_generation_mode;
+    bindings_helper.vue_imports |= VueImports::UseCssVars;
+
+    // `_ctx => ({ ... })`
+    let css_vars_fn = Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![Pat::Ident(BindingIdent {
+            id: Ident {
+                span: DUMMY_SP,
+                sym: bindings_helper.ident_prefixes.ctx.to_owned(),
+                optional: false,
+            },
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            })),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    });
+
+    Some(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: USE_CSS_VARS_HELPER.to_owned(),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(css_vars_fn),
+            }],
+            type_args: None,
+        })),
+    }))
+}
+
+/// Calls `useCssVars` in a component without `<script setup>`.
+/// The call goes first in the Options API `setup`, which is added when missing:
+/// ```js
+/// export default {
+///     data: () => ({ color: "red" }),
+///     setup() {
+///         _useCssVars(_ctx => ({ "abcd1234-color": _ctx.color }))
+///     }
+/// }
+/// ```
+pub fn inject_use_css_vars(export_obj: &mut ObjectLit, use_css_vars: Stmt) {
+    let span = DUMMY_SP;
+
+    let setup_prop = export_obj.props.iter_mut().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+
+        let is_setup = match **prop {
+            Prop::Method(MethodProp { ref key, .. })
+            | Prop::KeyValue(KeyValueProp { ref key, .. }) => match key {
+                PropName::Ident(ident) => ident.sym == *SETUP,
+                PropName::Str(s) => s.value == *SETUP,
+                _ => false,
+            },
+            Prop::Shorthand(ref ident) => ident.sym == *SETUP,
+            _ => false,
+        };
+        is_setup.then_some(prop)
+    });
+
+    let Some(setup_prop) = setup_prop else {
+        export_obj
+            .props
+            .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+                key: PropName::Ident(Ident {
+                    span,
+                    sym: SETUP.to_owned(),
+                    optional: false,
+                }),
+                function: Box::new(Function {
+                    params: vec![],
+                    decorators: vec![],
+                    span,
+                    body: Some(BlockStmt {
+                        span,
+                        stmts: vec![use_css_vars],
+                    }),
+                    is_generator: false,
+                    is_async: false,
+                    type_params: None,
+                    return_type: None,
+                }),
+            }))));
+        return;
+    };
+
+    // `{ setup }` is the same as `{ setup: setup }`
+    if let Prop::Shorthand(ref ident) = **setup_prop {
+        let value = Box::new(Expr::Ident(ident.to_owned()));
+        **setup_prop = Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(ident.to_owned()),
+            value,
+        });
+    }
+
+    macro_rules! ident {
+        ($sym: expr) => {
+            Ident {
+                span,
+                sym: fervid_atom!($sym),
+                optional: false,
+            }
+        };
+    }
+
+    match **setup_prop {
+        // `setup() {}`
+        Prop::Method(ref mut method) => {
+            if let Some(ref mut body) = method.function.body {
+                body.stmts.insert(0, use_css_vars);
+            }
+        }
+
+        Prop::KeyValue(ref mut key_value) => match *key_value.value {
+            // `setup: function () {}`
+            Expr::Fn(FnExpr {
+                ref mut function, ..
+            }) => {
+                if let Some(ref mut body) = function.body {
+                    body.stmts.insert(0, use_css_vars);
+                }
+            }
+
+            Expr::Arrow(ref mut arrow) => match *arrow.body {
+                // `setup: () => {}`
+                BlockStmtOrExpr::BlockStmt(ref mut body) => body.stmts.insert(0, use_css_vars),
+
+                // `setup: () => ({ foo })` becomes `setup: () => { _useCssVars(/*...*/); return { foo } }`
+                BlockStmtOrExpr::Expr(ref mut returned) => {
+                    let returned =
+                        std::mem::replace(returned, Box::new(Expr::Invalid(Invalid { span })));
+                    *arrow.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                        span,
+                        stmts: vec![
+                            use_css_vars,
+                            Stmt::Return(ReturnStmt {
+                                span,
+                                arg: Some(returned),
+                            }),
+                        ],
+                    });
+                }
+            },
+
+            // `setup: useSetup` becomes
+            // `setup: (props, ctx) => { _useCssVars(/*...*/); return useSetup(props, ctx) }`
+            _ => {
+                let user_setup = std::mem::replace(
+                    &mut key_value.value,
+                    Box::new(Expr::Invalid(Invalid { span })),
+                );
+
+                let user_setup_call = Expr::Call(CallExpr {
+                    span,
+                    callee: Callee::Expr(user_setup),
+                    args: vec![
+                        ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(Expr::Ident(ident!("props"))),
+                        },
+                        ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(Expr::Ident(ident!("ctx"))),
+                        },
+                    ],
+                    type_args: None,
+                });
+
+                key_value.value = Box::new(Expr::Arrow(ArrowExpr {
+                    span,
+                    params: vec![
+                        Pat::Ident(BindingIdent {
+                            id: ident!("props"),
+                            type_ann: None,
+                        }),
+                        Pat::Ident(BindingIdent {
+                            id: ident!("ctx"),
+                            type_ann: None,
+                        }),
+                    ],
+                    body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                        span,
+                        stmts: vec![
+                            use_css_vars,
+                            Stmt::Return(ReturnStmt {
+                                span,
+                                arg: Some(Box::new(user_setup_call)),
+                            }),
+                        ],
+                    })),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                }));
+            }
+        },
+
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{escape_css_var_name, BindingTypes, SetupBinding};
+
+    use super::*;
+    use crate::test_utils::{parser::parse_javascript_expr, to_str};
+
+    fn css_var(raw: &str) -> SfcCssVar {
+        SfcCssVar {
+            raw: FervidAtom::from(raw),
+            expr: parse_javascript_expr(raw, 0, Default::default())
+                .expect("css_var expects the input to be parseable")
+                .0,
+        }
+    }
+
+    #[test]
+    fn it_generates_use_css_vars() {
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("color"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("theme"), BindingTypes::SetupReactiveConst),
+        ];
+
+        let stmt = generate_use_css_vars(
+            vec![css_var("color"), css_var("theme.width"), css_var("color")],
+            "abcd1234",
+            &mut bindings_helper,
+        )
+        .expect("useCssVars should be generated");
+
+        assert_eq!(
+            to_str(&stmt),
+            r#"_useCssVars(_ctx=>({"abcd1234-color":color.value,"abcd1234-theme.width":theme.width}));"#
+        );
+
+        // The runtime keys are the unescaped names, the same as `var()` in the stylesheet after parsing
+        let Stmt::Expr(ExprStmt { expr, .. }) = stmt else {
+            panic!("useCssVars is not an expression statement")
+        };
+        let Expr::Call(CallExpr { args, .. }) = *expr else {
+            panic!("useCssVars is not a call")
+        };
+        let Expr::Arrow(ArrowExpr { body, .. }) = *args[0].expr.to_owned() else {
+            panic!("useCssVars argument is not an arrow function")
+        };
+        let BlockStmtOrExpr::Expr(ref body) = *body else {
+            panic!("useCssVars arrow function has a block body")
+        };
+        let Expr::Paren(ref paren) = **body else {
+            panic!("useCssVars arrow function does not return an object")
+        };
+        let Expr::Object(ref object) = *paren.expr else {
+            panic!("useCssVars arrow function does not return an object")
+        };
+        let keys: Vec<&str> = object
+            .props
+            .iter()
+            .filter_map(|prop| match prop.as_prop()?.as_key_value()?.key {
+                PropName::Str(ref s) => Some(&*s.value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keys, vec!["abcd1234-color", "abcd1234-theme.width"]);
+        assert!(bindings_helper.vue_imports.contains(VueImports::UseCssVars));
+        assert!(matches!(
+            bindings_helper.template_generation_mode,
+            TemplateGenerationMode::RenderFn
+        ));
+    }
+
+    #[test]
+    fn it_skips_use_css_vars_without_vars() {
+        let mut bindings_helper = BindingsHelper::default();
+        assert!(generate_use_css_vars(vec![], "abcd1234", &mut bindings_helper).is_none());
+        assert!(bindings_helper.vue_imports.is_empty());
+    }
+
+    #[test]
+    fn it_injects_use_css_vars_into_options_api_setup() {
+        macro_rules! test {
+            ($input: expr, $expected: expr) => {
+                let Expr::Object(mut export_obj) =
+                    *parse_javascript_expr($input, 0, Default::default())
+                        .expect("export object should be parseable")
+                        .0
+                else {
+                    panic!("export object should be an object");
+                };
+
+                let use_css_vars = Stmt::Expr(ExprStmt {
+                    span: DUMMY_SP,
+                    expr: parse_javascript_expr("_useCssVars()", 0, Default::default())
+                        .expect("useCssVars should be parseable")
+                        .0,
+                });
+
+                inject_use_css_vars(&mut export_obj, use_css_vars);
+                assert_eq!(to_str(&export_obj), $expected);
+            };
+        }
+
+        test!(
+            "{ data: () => ({}) }",
+            "{data:()=>({}),setup(){_useCssVars()}}"
+        );
+        test!(
+            "{ setup(props) { return {} } }",
+            "{setup(props){_useCssVars();return{}}}"
+        );
+        test!(
+            "{ setup: () => ({ foo }) }",
+            "{setup:()=>{_useCssVars();return{foo}}}"
+        );
+        test!(
+            "{ setup }",
+            "{setup:(props,ctx)=>{_useCssVars();return setup(props,ctx)}}"
+        );
+    }
+
+    #[test]
+    fn it_generates_css_var_names() {
+        assert_eq!(
+            &*generate_css_var_name("abcd1234", "color", false),
+            "abcd1234-color"
+        );
+        assert_eq!(
+            &*generate_css_var_name("abcd1234", "foo.bar[0]", false),
+            "abcd1234-foo.bar[0]"
+        );
+        assert_eq!(
+            escape_css_var_name("abcd1234-foo.bar[0]"),
+            r"abcd1234-foo\.bar\[0\]"
+        );
+
+        // PROD names are hashes
+        let prod_name = generate_css_var_name("abcd1234", "color", true);
+        assert_eq!(prod_name.len(), 8);
+        assert_eq!(prod_name, generate_css_var_name("abcd1234", "color", true));
+        assert_ne!(prod_name, generate_css_var_name("abcd1234", "size", true));
+    }
+}
@@ -1,10 +1,11 @@
+use fervid_core::{BindingTypes, SetupBinding};
 use swc_core::ecma::{
     ast::{Id, ImportDecl, ImportSpecifier, ModuleExportName},
     atoms::JsWord,
 };
 
 use crate::{
-    atoms::{COMPUTED, REACTIVE, REF, VUE},
+    atoms::{COMPUTED, DEFINE_ASYNC_COMPONENT, REACTIVE, REF, VUE},
     structs::VueResolvedImports,
 };
 
@@ -58,6 +59,24 @@ pub fn collect_imports(
     }
 }
 
+/// Namespace imports are bindings, because they are used as component namespaces,
+/// e.g. `import * as Form from './form'` is used as `<Form.Input>`.
+/// Such imports are never reassigned, thus `BindingTypes::SetupConst`.
+pub fn collect_namespace_imports(import_decl: &ImportDecl, out: &mut Vec<SetupBinding>) {
+    if import_decl.type_only {
+        return;
+    }
+
+    for specifier in import_decl.specifiers.iter() {
+        if let ImportSpecifier::Namespace(ns_spec) = specifier {
+            out.push(SetupBinding(
+                ns_spec.local.sym.to_owned(),
+                BindingTypes::SetupConst,
+            ));
+        }
+    }
+}
+
 fn collect_vue_import(imported_word: &JsWord, used_as: Id, vue_imports: &mut VueResolvedImports) {
     if *imported_word == *REF {
         vue_imports.ref_import = Some(used_as)
@@ -65,6 +84,8 @@ fn collect_vue_import(imported_word: &JsWord, used_as: Id, vue_imports: &mut Vue
         vue_imports.computed = Some(used_as)
     } else if *imported_word == *REACTIVE {
         vue_imports.reactive = Some(used_as)
+    } else if *imported_word == *DEFINE_ASYNC_COMPONENT {
+        vue_imports.define_async_component = Some(used_as)
     }
 }
 
@@ -139,7 +160,8 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("ref"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("computed"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default())),
+                    ..Default::default()
                 },
                 ..Default::default()
             }
@@ -154,7 +176,8 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("foo"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("bar"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("baz"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("baz"), SyntaxContext::default())),
+                    ..Default::default()
                 },
                 ..Default::default()
             }
@@ -208,7 +231,8 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("ref"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("computed"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default())),
+                    ..Default::default()
                 },
                 ..Default::default()
             }
@@ -1,9 +1,14 @@
-use fervid_core::{fervid_atom, BindingsHelper, FervidAtom, VueImports};
+use fervid_core::{
+    fervid_atom, BindingTypes, BindingsHelper, FervidAtom, SetupBinding, VueImports,
+};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{
-        ArrayLit, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, ObjectLit,
-        Prop, PropName, PropOrSpread, Str,
+        ArrayLit, BindingIdent, Bool, CallExpr, Callee, Decl, Expr, ExprOrSpread, Ident,
+        KeyValueProp, Lit, Null, ObjectLit, ObjectPatProp, Pat, Prop, PropName, PropOrSpread, Str,
+        TsEntityName, TsFnOrConstructorType, TsFnParam, TsKeywordTypeKind, TsLit, TsLitType,
+        TsType, TsTypeElement, TsTypeOperatorOp, TsTypeRef, TsUnionOrIntersectionType,
+        VarDeclarator,
     },
 };
 
@@ -13,6 +18,7 @@ use crate::{
         EMIT_HELPER, EXPOSE_HELPER, MERGE_MODELS_HELPER, MODEL_VALUE, PROPS_HELPER,
         USE_MODEL_HELPER,
     },
+    script::common::collect_destructure,
     structs::{SfcDefineModel, SfcExportedObjectHelper},
 };
 
@@ -62,6 +68,9 @@ pub fn transform_script_setup_macro_expr(
             // TODO Check if this was re-assigned before
             sfc_object_helper.props = Some(arg0.expr.to_owned());
         }
+        if let Some(props_type) = first_type_arg(call_expr) {
+            sfc_object_helper.props_type = Some(props_type);
+        }
 
         // Return `__props` when in var mode
         if is_var_decl {
@@ -79,6 +88,9 @@ pub fn transform_script_setup_macro_expr(
         if let Some(arg0) = &call_expr.args.get(0) {
             sfc_object_helper.emits = Some(arg0.expr.to_owned())
         }
+        if let Some(emits_type) = first_type_arg(call_expr) {
+            sfc_object_helper.emits_type = Some(emits_type);
+        }
 
         // Return `__emits` when in var mode
         if is_var_decl {
@@ -94,6 +106,10 @@ pub fn transform_script_setup_macro_expr(
         }
     } else if DEFINE_EXPOSE.eq(sym) {
         sfc_object_helper.is_setup_expose_referenced = true;
+        sfc_object_helper.exposed = call_expr.args.get(0).map(|arg0| arg0.expr.to_owned());
+        if let Some(arg0) = call_expr.args.get(0) {
+            collect_exposed_bindings(&arg0.expr, &mut bindings_helper.exposed_bindings);
+        }
 
         // __expose
         let new_callee_ident = Ident {
@@ -175,6 +191,11 @@ pub fn transform_script_setup_macro_expr(
             type_args: None,
         }))
     } else if DEFINE_SLOTS.eq(sym) {
+        // The slot types are a part of the component declaration even without a variable
+        if let Some(slots_type) = first_type_arg(call_expr) {
+            sfc_object_helper.slots_type = Some(slots_type);
+        }
+
         // Without a variable to bind to this macro means nothing
         if !is_var_decl {
             bail!();
@@ -230,6 +251,178 @@ pub fn transform_script_setup_macro_expr(
     }
 }
 
+/// Whether this is a destructure of `defineProps`, e.g. `const { foo, ...rest } = defineProps()`
+pub fn is_props_destructure(var_declarator: &VarDeclarator) -> bool {
+    let (Pat::Object(_), Some(init_expr)) = (&var_declarator.name, &var_declarator.init) else {
+        return false;
+    };
+
+    let Expr::Call(CallExpr {
+        callee: Callee::Expr(ref callee_expr),
+        ..
+    }) = **init_expr
+    else {
+        return false;
+    };
+
+    matches!(**callee_expr, Expr::Ident(ref callee_ident) if DEFINE_PROPS.eq(&callee_ident.sym))
+}
+
+/// Transforms the destructure of `defineProps` and records its bindings, e.g.
+/// `const { foo, bar: baz, ...rest } = defineProps(/*...*/)` becomes
+/// `const { foo, bar: baz } = __props, rest = _createPropsRestProxy(__props, ["foo", "bar"])`.
+///
+/// The props destructured as-is are accessed through `__props` and `$props` in the template.
+/// The rest proxy is reactive and never reassigned, thus it is `SetupReactiveConst`
+/// and is returned from `setup` when the template is not inlined.
+pub fn transform_props_destructure(
+    var_declarator: VarDeclarator,
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+) -> Vec<VarDeclarator> {
+    let VarDeclarator {
+        span,
+        name: Pat::Object(mut object_pat),
+        init: Some(init_expr),
+        definite,
+    } = var_declarator
+    else {
+        unreachable!("Props destructure must be checked with `is_props_destructure`")
+    };
+
+    // `defineProps()` -> `__props`
+    let init_expr =
+        transform_script_setup_macro_expr(*init_expr, bindings_helper, sfc_object_helper, true)
+            .map(Box::new);
+
+    let mut destructured_keys = Vec::<FervidAtom>::with_capacity(object_pat.props.len());
+    let mut rest_ident: Option<BindingIdent> = None;
+    let setup_bindings = &mut bindings_helper.setup_bindings;
+    let props_aliases = &mut bindings_helper.props_aliases;
+
+    object_pat.props.retain(|prop| match prop {
+        // `foo` in `{ foo }`, but `{ foo = 'default' }` does not equal to the prop
+        ObjectPatProp::Assign(assign_prop) => {
+            let sym = assign_prop.key.sym.to_owned();
+            let binding_type = if assign_prop.value.is_some() {
+                BindingTypes::SetupMaybeRef
+            } else {
+                BindingTypes::Props
+            };
+            setup_bindings.push(SetupBinding(sym.to_owned(), binding_type));
+            destructured_keys.push(sym);
+            true
+        }
+
+        // `baz` in `{ bar: baz }`
+        ObjectPatProp::KeyValue(key_value_prop) => {
+            let key = match key_value_prop.key {
+                PropName::Ident(ref ident) => Some(ident.sym.to_owned()),
+                PropName::Str(ref s) => Some(s.value.to_owned()),
+                _ => None,
+            };
+
+            match (key, key_value_prop.value.as_ref()) {
+                // `baz` is an alias of the prop `bar` and is accessed as `__props["bar"]`
+                (Some(key), Pat::Ident(alias)) => {
+                    setup_bindings.push(SetupBinding(
+                        alias.sym.to_owned(),
+                        BindingTypes::PropsAliased,
+                    ));
+                    props_aliases.insert(alias.sym.to_owned(), key.to_owned());
+                    destructured_keys.push(key);
+                }
+
+                // `{ bar: baz = 1 }` or `{ bar: { baz } }` are not the props themselves
+                (key, value) => {
+                    destructured_keys.extend(key);
+                    collect_destructure(value, setup_bindings, true);
+                }
+            }
+            true
+        }
+
+        // `...rest`, only an identifier is supported
+        ObjectPatProp::Rest(rest_pat) => {
+            let Pat::Ident(ref ident) = *rest_pat.arg else {
+                return true;
+            };
+            setup_bindings.push(SetupBinding(
+                ident.sym.to_owned(),
+                BindingTypes::SetupReactiveConst,
+            ));
+            rest_ident = Some(ident.to_owned());
+            false
+        }
+    });
+
+    // The destructure is dropped when only the rest was destructured
+    let mut result = Vec::with_capacity(2);
+    if rest_ident.is_none() || !object_pat.props.is_empty() {
+        result.push(VarDeclarator {
+            span,
+            name: Pat::Object(object_pat),
+            init: init_expr,
+            definite,
+        });
+    }
+
+    let Some(rest_ident) = rest_ident else {
+        return result;
+    };
+
+    // _createPropsRestProxy(__props, ["foo", "bar"])
+    bindings_helper.vue_imports |= VueImports::CreatePropsRestProxy;
+    sfc_object_helper.is_setup_props_referenced = true;
+
+    let keys = destructured_keys
+        .into_iter()
+        .map(|key| {
+            Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span,
+                    value: key,
+                    raw: None,
+                }))),
+            })
+        })
+        .collect();
+
+    let rest_proxy = Expr::Call(CallExpr {
+        span,
+        callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+            span,
+            sym: VueImports::CreatePropsRestProxy.as_atom(),
+            optional: false,
+        }))),
+        args: vec![
+            ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: PROPS_HELPER.to_owned(),
+                    optional: false,
+                })),
+            },
+            ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Array(ArrayLit { span, elems: keys })),
+            },
+        ],
+        type_args: None,
+    });
+
+    result.push(VarDeclarator {
+        span,
+        name: Pat::Ident(rest_ident),
+        init: Some(Box::new(rest_proxy)),
+        definite: false,
+    });
+
+    result
+}
+
 /// Mainly used to process `models` by adding them to `props` and `emits`
 pub fn postprocess_macros(
     bindings_helper: &mut BindingsHelper,
@@ -277,99 +470,526 @@ pub fn postprocess_macros(
         }))));
     }
 
-    match sfc_object_helper.props.take() {
-        Some(mut existing_props) => {
-            // Try merging into an object if previous props is an object
-            if let Expr::Object(ref mut existing_props_obj) = *existing_props {
-                existing_props_obj.props.extend(new_props);
+    let new_props = Box::new(Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props: new_props,
+    }));
+    sfc_object_helper.props = Some(match sfc_object_helper.props.take() {
+        Some(existing_props) => {
+            merge_runtime_declarations(existing_props, new_props, bindings_helper)
+        }
+        None => new_props,
+    });
 
-                sfc_object_helper.props = Some(existing_props);
-            } else {
-                // Use `mergeModels` otherwise
-                bindings_helper.vue_imports |= VueImports::MergeModels;
-                let merge_models_ident = MERGE_MODELS_HELPER.to_owned();
+    let new_emits = Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: new_emits,
+    }));
+    sfc_object_helper.emits = Some(match sfc_object_helper.emits.take() {
+        Some(existing_emits) => {
+            merge_runtime_declarations(existing_emits, new_emits, bindings_helper)
+        }
+        None => new_emits,
+    });
+}
 
-                let new_props = Expr::Call(CallExpr {
-                    span: DUMMY_SP,
-                    callee: Callee::Expr(Box::new(Expr::Ident(Ident {
-                        span: DUMMY_SP,
-                        sym: merge_models_ident,
-                        optional: false,
-                    }))),
-                    args: vec![
-                        ExprOrSpread {
-                            spread: None,
-                            expr: existing_props,
-                        },
-                        ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(Expr::Object(ObjectLit {
-                                span: DUMMY_SP,
-                                props: new_props,
-                            })),
-                        },
-                    ],
-                    type_args: None,
-                });
-
-                sfc_object_helper.props = Some(Box::new(new_props));
-            };
+/// Merges two runtime declarations of `props` or `emits`.
+/// Two objects or two arrays are concatenated, otherwise `_mergeModels(existing, new)` is used
+pub fn merge_runtime_declarations(
+    mut existing: Box<Expr>,
+    new: Box<Expr>,
+    bindings_helper: &mut BindingsHelper,
+) -> Box<Expr> {
+    match (existing.as_mut(), *new) {
+        (Expr::Object(existing_obj), Expr::Object(new_obj)) => {
+            existing_obj.props.extend(new_obj.props);
+            existing
+        }
+
+        (Expr::Array(existing_arr), Expr::Array(new_arr)) => {
+            existing_arr.elems.extend(new_arr.elems);
+            existing
         }
-        None => {
-            sfc_object_helper.props = Some(Box::new(Expr::Object(ObjectLit {
+
+        (_, new) => {
+            bindings_helper.vue_imports |= VueImports::MergeModels;
+
+            Box::new(Expr::Call(CallExpr {
                 span: DUMMY_SP,
-                props: new_props,
-            })))
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: MERGE_MODELS_HELPER.to_owned(),
+                    optional: false,
+                }))),
+                args: vec![
+                    ExprOrSpread {
+                        spread: None,
+                        expr: existing,
+                    },
+                    ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(new),
+                    },
+                ],
+                type_args: None,
+            }))
         }
     }
+}
 
-    match sfc_object_helper.emits.take() {
-        Some(mut existing_emits) => {
-            // Try merging into an array if previous emits is an array
-            if let Expr::Array(ref mut existing_emits_arr) = *existing_emits {
-                existing_emits_arr.elems.extend(new_emits);
+/// Records the props declared by `defineProps` and `defineModel` as bindings,
+/// so that the template accesses them as `$props.foo` in the render function
+/// or as `__props.foo` when it is inlined in `setup`
+pub fn record_props_bindings(
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+) {
+    let mut prop_names = Vec::<FervidAtom>::new();
+    if let Some(ref props) = sfc_object_helper.props {
+        collect_runtime_prop_names(props, &mut prop_names);
+    }
+    if let Some(ref props_type) = sfc_object_helper.props_type {
+        collect_type_prop_names(props_type, &sfc_object_helper.type_decls, &mut prop_names);
+    }
 
-                sfc_object_helper.emits = Some(existing_emits);
-            } else {
-                // Use `mergeModels` otherwise
-                bindings_helper.vue_imports |= VueImports::MergeModels;
-                let merge_models_ident = MERGE_MODELS_HELPER.to_owned();
+    if prop_names.is_empty() {
+        return;
+    }
 
-                let new_emits = Expr::Call(CallExpr {
-                    span: DUMMY_SP,
-                    callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+    // `__props` is needed to access the props
+    sfc_object_helper.is_setup_props_referenced = true;
+
+    for prop_name in prop_names {
+        // Setup variables shadow the props
+        let is_declared = bindings_helper
+            .setup_bindings
+            .iter()
+            .any(|binding| binding.0 == prop_name);
+
+        if !is_declared {
+            bindings_helper
+                .setup_bindings
+                .push(SetupBinding(prop_name, BindingTypes::Props));
+        }
+    }
+}
+
+/// Prop names from `['foo', 'bar']`, `{ foo: String }` or `_mergeModels(/*...*/)`
+fn collect_runtime_prop_names(props: &Expr, out: &mut Vec<FervidAtom>) {
+    match props {
+        Expr::Array(array_lit) => {
+            for elem in array_lit.elems.iter().flatten() {
+                if let Expr::Lit(Lit::Str(ref prop_name)) = *elem.expr {
+                    out.push(prop_name.value.to_owned());
+                }
+            }
+        }
+
+        Expr::Object(object_lit) => {
+            for prop in object_lit.props.iter() {
+                let PropOrSpread::Prop(ref prop) = prop else {
+                    continue;
+                };
+
+                match **prop {
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(ref ident),
+                        ..
+                    })
+                    | Prop::Shorthand(ref ident) => out.push(ident.sym.to_owned()),
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(ref s),
+                        ..
+                    }) => out.push(s.value.to_owned()),
+                    _ => {}
+                }
+            }
+        }
+
+        Expr::Call(call_expr) => {
+            for arg in call_expr.args.iter() {
+                collect_runtime_prop_names(&arg.expr, out);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Prop names from `{ foo: string }`, an intersection of types,
+/// or a reference to an `interface` or a `type` declared in `<script setup>`
+fn collect_type_prop_names(props_type: &TsType, type_decls: &[Decl], out: &mut Vec<FervidAtom>) {
+    let mut members = Vec::<&TsTypeElement>::new();
+    collect_type_members(props_type, type_decls, &mut members);
+
+    for member in members {
+        if let Some(name) = type_member_name(member) {
+            out.push(name);
+        }
+    }
+}
+
+/// Members of `{ foo: string }`, an intersection of types,
+/// or a reference to an `interface` or a `type` declared in `<script setup>`
+fn collect_type_members<'t>(
+    ts_type: &'t TsType,
+    type_decls: &'t [Decl],
+    out: &mut Vec<&'t TsTypeElement>,
+) {
+    match ts_type {
+        TsType::TsTypeLit(type_lit) => out.extend(type_lit.members.iter()),
+
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+            intersection,
+        )) => {
+            for ts_type in intersection.types.iter() {
+                collect_type_members(ts_type, type_decls, out);
+            }
+        }
+
+        TsType::TsParenthesizedType(parenthesized) => {
+            collect_type_members(&parenthesized.type_ann, type_decls, out)
+        }
+
+        TsType::TsTypeRef(TsTypeRef {
+            type_name: TsEntityName::Ident(ref type_name),
+            ..
+        }) => {
+            for decl in type_decls.iter() {
+                match decl {
+                    Decl::TsInterface(interface) if interface.id.sym == type_name.sym => {
+                        out.extend(interface.body.body.iter())
+                    }
+                    Decl::TsTypeAlias(alias) if alias.id.sym == type_name.sym => {
+                        collect_type_members(&alias.type_ann, type_decls, out)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+/// Name of a property or a method signature, e.g. `foo` in `foo: string` or `'foo'(): void`
+fn type_member_name(member: &TsTypeElement) -> Option<FervidAtom> {
+    let key = match member {
+        TsTypeElement::TsPropertySignature(property) => &property.key,
+        TsTypeElement::TsMethodSignature(method) => &method.key,
+        _ => return None,
+    };
+
+    match **key {
+        Expr::Ident(ref ident) => Some(ident.sym.to_owned()),
+        Expr::Lit(Lit::Str(ref s)) => Some(s.value.to_owned()),
+        _ => None,
+    }
+}
+
+/// Generates the runtime `props` from the type argument of `defineProps<T>()`, e.g.
+/// `{ foo: string, bar?: number | boolean }` becomes
+/// `{ foo: { type: String, required: true }, bar: { type: [Number, Boolean], required: false } }`.
+///
+/// Types which cannot be checked at runtime get `type: null`
+pub fn generate_runtime_props(props_type: &TsType, type_decls: &[Decl]) -> Option<Expr> {
+    let mut members = Vec::<&TsTypeElement>::new();
+    collect_type_members(props_type, type_decls, &mut members);
+    if members.is_empty() {
+        return None;
+    }
+
+    macro_rules! key_value {
+        ($key: expr, $value: expr) => {
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: $key,
+                value: Box::new($value),
+            })))
+        };
+    }
+
+    let mut props = Vec::<PropOrSpread>::with_capacity(members.len());
+    for member in members {
+        let Some(name) = type_member_name(member) else {
+            continue;
+        };
+
+        // Methods are functions, properties are checked by their type
+        let mut runtime_types = Vec::<&'static str>::new();
+        let (is_optional, is_known) = match member {
+            TsTypeElement::TsPropertySignature(property) => (
+                property.optional,
+                property.type_ann.as_ref().map_or(false, |type_ann| {
+                    infer_runtime_types(&type_ann.type_ann, type_decls, &mut runtime_types)
+                }),
+            ),
+            TsTypeElement::TsMethodSignature(method) => {
+                runtime_types.push("Function");
+                (method.optional, true)
+            }
+            _ => continue,
+        };
+
+        let runtime_type = match runtime_types.as_slice() {
+            [single] if is_known => runtime_type_expr(single),
+            [_, ..] if is_known => Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: runtime_types
+                    .iter()
+                    .map(|runtime_type| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(runtime_type_expr(runtime_type)),
+                        })
+                    })
+                    .collect(),
+            }),
+            _ => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
+        };
+
+        let prop_options = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                key_value!(ident_prop_name("type"), runtime_type),
+                key_value!(
+                    ident_prop_name("required"),
+                    Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: !is_optional,
+                    }))
+                ),
+            ],
+        });
+
+        props.push(key_value!(
+            PropName::Str(Str {
+                span: DUMMY_SP,
+                value: name,
+                raw: None,
+            }),
+            prop_options
+        ));
+    }
+
+    Some(Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props,
+    }))
+}
+
+/// Generates the runtime `emits` from the type argument of `defineEmits<T>()`.
+/// Both `{ (e: 'change', id: number): void }` and `{ change: [id: number] }` become `["change"]`
+pub fn generate_runtime_emits(emits_type: &TsType, type_decls: &[Decl]) -> Option<Expr> {
+    let mut event_names = Vec::<FervidAtom>::new();
+
+    match emits_type {
+        // `defineEmits<(e: 'change') => void>()`
+        TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(fn_type)) => {
+            collect_event_param_names(&fn_type.params, &mut event_names)
+        }
+
+        _ => {
+            let mut members = Vec::<&TsTypeElement>::new();
+            collect_type_members(emits_type, type_decls, &mut members);
+
+            for member in members {
+                match member {
+                    TsTypeElement::TsCallSignatureDecl(call_signature) => {
+                        collect_event_param_names(&call_signature.params, &mut event_names)
+                    }
+                    _ => event_names.extend(type_member_name(member)),
+                }
+            }
+        }
+    }
+
+    if event_names.is_empty() {
+        return None;
+    }
+
+    Some(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: event_names
+            .into_iter()
+            .map(|event_name| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
                         span: DUMMY_SP,
-                        sym: merge_models_ident,
-                        optional: false,
+                        value: event_name,
+                        raw: None,
                     }))),
-                    args: vec![
-                        ExprOrSpread {
-                            spread: None,
-                            expr: existing_emits,
-                        },
-                        ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(Expr::Array(ArrayLit {
-                                span: DUMMY_SP,
-                                elems: new_emits,
-                            })),
-                        },
-                    ],
-                    type_args: None,
-                });
-
-                sfc_object_helper.emits = Some(Box::new(new_emits));
-            };
+                })
+            })
+            .collect(),
+    }))
+}
+
+/// Event names from the first parameter of a call signature, e.g. `(e: 'change', id: number)`
+fn collect_event_param_names(params: &[TsFnParam], out: &mut Vec<FervidAtom>) {
+    let Some(TsFnParam::Ident(event_param)) = params.first() else {
+        return;
+    };
+    if let Some(ref type_ann) = event_param.type_ann {
+        collect_string_literals(&type_ann.type_ann, out);
+    }
+}
+
+/// String literal types, e.g. `'change'` or `'change' | 'update'`
+fn collect_string_literals(ts_type: &TsType, out: &mut Vec<FervidAtom>) {
+    match ts_type {
+        TsType::TsLitType(TsLitType {
+            lit: TsLit::Str(ref s),
+            ..
+        }) => {
+            if !out.contains(&s.value) {
+                out.push(s.value.to_owned());
+            }
         }
-        None => {
-            sfc_object_helper.emits = Some(Box::new(Expr::Array(ArrayLit {
-                span: DUMMY_SP,
-                elems: new_emits,
-            })))
+
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => {
+            for ts_type in union.types.iter() {
+                collect_string_literals(ts_type, out);
+            }
+        }
+
+        TsType::TsParenthesizedType(parenthesized) => {
+            collect_string_literals(&parenthesized.type_ann, out)
+        }
+
+        _ => {}
+    }
+}
+
+/// Infers the runtime constructors of a type, e.g. `String` for `string`.
+/// `null` stands for the `null` type itself.
+/// Returns `false` when the type cannot be checked at runtime, e.g. `any` or an imported type
+fn infer_runtime_types(ts_type: &TsType, type_decls: &[Decl], out: &mut Vec<&'static str>) -> bool {
+    macro_rules! add {
+        ($runtime_type: expr) => {{
+            if !out.contains(&$runtime_type) {
+                out.push($runtime_type);
+            }
+            true
+        }};
+    }
+
+    match ts_type {
+        TsType::TsKeywordType(keyword) => match keyword.kind {
+            TsKeywordTypeKind::TsStringKeyword => add!("String"),
+            TsKeywordTypeKind::TsNumberKeyword => add!("Number"),
+            TsKeywordTypeKind::TsBooleanKeyword => add!("Boolean"),
+            TsKeywordTypeKind::TsObjectKeyword => add!("Object"),
+            TsKeywordTypeKind::TsSymbolKeyword => add!("Symbol"),
+            TsKeywordTypeKind::TsNullKeyword => add!("null"),
+            TsKeywordTypeKind::TsUndefinedKeyword | TsKeywordTypeKind::TsVoidKeyword => true,
+            _ => false,
+        },
+
+        TsType::TsLitType(lit_type) => match lit_type.lit {
+            TsLit::Str(_) | TsLit::Tpl(_) => add!("String"),
+            TsLit::Number(_) => add!("Number"),
+            TsLit::Bool(_) => add!("Boolean"),
+            TsLit::BigInt(_) => false,
+        },
+
+        TsType::TsTypeLit(_) | TsType::TsMappedType(_) => add!("Object"),
+        TsType::TsFnOrConstructorType(_) => add!("Function"),
+        TsType::TsArrayType(_) | TsType::TsTupleType(_) => add!("Array"),
+
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => union
+            .types
+            .iter()
+            .all(|ts_type| infer_runtime_types(ts_type, type_decls, out)),
+
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(_)) => {
+            add!("Object")
+        }
+
+        TsType::TsParenthesizedType(parenthesized) => {
+            infer_runtime_types(&parenthesized.type_ann, type_decls, out)
+        }
+
+        TsType::TsOptionalType(optional) => {
+            infer_runtime_types(&optional.type_ann, type_decls, out)
+        }
+
+        TsType::TsTypeOperator(type_operator) if type_operator.op == TsTypeOperatorOp::ReadOnly => {
+            infer_runtime_types(&type_operator.type_ann, type_decls, out)
         }
+
+        TsType::TsTypeRef(TsTypeRef {
+            type_name: TsEntityName::Ident(ref type_name),
+            ..
+        }) => {
+            // Types declared in `<script setup>`
+            for decl in type_decls.iter() {
+                match decl {
+                    Decl::TsInterface(interface) if interface.id.sym == type_name.sym => {
+                        return add!("Object");
+                    }
+                    Decl::TsTypeAlias(alias) if alias.id.sym == type_name.sym => {
+                        return infer_runtime_types(&alias.type_ann, type_decls, out);
+                    }
+                    _ => {}
+                }
+            }
+
+            // Global types
+            match &*type_name.sym {
+                "Array" | "ReadonlyArray" => add!("Array"),
+                "Function" => add!("Function"),
+                "Object" | "Record" | "Partial" | "Required" | "Readonly" | "Pick" | "Omit" => {
+                    add!("Object")
+                }
+                "String" => add!("String"),
+                "Number" => add!("Number"),
+                "Boolean" => add!("Boolean"),
+                "Symbol" => add!("Symbol"),
+                "Date" => add!("Date"),
+                "Promise" => add!("Promise"),
+                "Map" => add!("Map"),
+                "Set" => add!("Set"),
+                "WeakMap" => add!("WeakMap"),
+                "WeakSet" => add!("WeakSet"),
+                _ => false,
+            }
+        }
+
+        _ => false,
     }
 }
 
+/// `String` for the `String` constructor, `null` for the `null` type
+fn runtime_type_expr(runtime_type: &str) -> Expr {
+    if runtime_type == "null" {
+        return Expr::Lit(Lit::Null(Null { span: DUMMY_SP }));
+    }
+
+    Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(runtime_type),
+        optional: false,
+    })
+}
+
+fn ident_prop_name(sym: &'static str) -> PropName {
+    PropName::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(sym),
+        optional: false,
+    })
+}
+
+/// Type argument of a macro, e.g. `Props` in `defineProps<Props>()`
+fn first_type_arg(call_expr: &CallExpr) -> Option<Box<TsType>> {
+    call_expr
+        .type_args
+        .as_ref()
+        .and_then(|type_args| type_args.params.get(0))
+        .cloned()
+}
+
 /// Processes `defineModel`
 fn read_define_model(macro_args: &[ExprOrSpread]) -> SfcDefineModel {
     // 1st arg - model name (string) or model options (object)
@@ -444,3 +1064,27 @@ fn is_local(options: Option<&ExprOrSpread>) -> bool {
 
     local_bool.value
 }
+
+/// Collects the identifiers referenced by the `defineExpose` object,
+/// e.g. `focus` and `count` for `defineExpose({ focus, total: count })`
+fn collect_exposed_bindings(expr: &Expr, out: &mut Vec<FervidAtom>) {
+    let Expr::Object(ref object_lit) = expr else {
+        return;
+    };
+
+    for prop in object_lit.props.iter() {
+        let PropOrSpread::Prop(ref prop) = prop else {
+            continue;
+        };
+
+        match **prop {
+            Prop::Shorthand(ref ident) => out.push(ident.sym.to_owned()),
+            Prop::KeyValue(KeyValueProp { ref value, .. }) => {
+                if let Expr::Ident(ref ident) = **value {
+                    out.push(ident.sym.to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+}
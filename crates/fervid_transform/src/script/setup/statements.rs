@@ -1,12 +1,16 @@
 use fervid_core::{BindingTypes, BindingsHelper, SetupBinding};
-use swc_core::ecma::ast::{Decl, ExprStmt, Stmt, VarDeclKind};
+use swc_core::ecma::ast::{Decl, ExprStmt, Pat, Stmt, VarDeclKind};
 
 use crate::{
-    script::common::{categorize_class, categorize_fn_decl, categorize_var_declarator},
+    script::common::{
+        categorize_class, categorize_fn_decl, categorize_var_declarator, is_static_literal,
+    },
     structs::{SfcExportedObjectHelper, VueResolvedImports},
 };
 
-use super::macros::transform_script_setup_macro_expr;
+use super::macros::{
+    is_props_destructure, transform_props_destructure, transform_script_setup_macro_expr,
+};
 
 /// Analyzes the statement in `script setup` context.
 /// This can either be:
@@ -46,6 +50,45 @@ pub fn transform_and_record_stmt(
     }
 }
 
+/// Checks whether a statement only declares literal constants,
+/// e.g. `const title = 'Hello', answer = 42`.
+/// Such statements do not depend on `setup` and can be hoisted to the module level.
+pub fn is_hoistable_stmt(stmt: &Stmt) -> bool {
+    let Stmt::Decl(Decl::Var(ref var_decl)) = stmt else {
+        return false;
+    };
+
+    if !matches!(var_decl.kind, VarDeclKind::Const) || var_decl.declare {
+        return false;
+    }
+
+    var_decl.decls.iter().all(|var_declarator| {
+        let (Pat::Ident(_), Some(init_expr)) = (&var_declarator.name, &var_declarator.init) else {
+            return false;
+        };
+
+        is_static_literal(init_expr)
+    })
+}
+
+/// Checks whether a statement is an ambient TypeScript declaration,
+/// e.g. `declare const foo: number` or `declare global {}`.
+/// Such statements are not allowed inside `setup` and are hoisted to the module level.
+pub fn is_ambient_stmt(stmt: &Stmt) -> bool {
+    let Stmt::Decl(ref decl) = stmt else {
+        return false;
+    };
+
+    match decl {
+        Decl::Class(class) => class.declare,
+        Decl::Fn(fn_decl) => fn_decl.declare,
+        Decl::Var(var_decl) => var_decl.declare,
+        Decl::TsEnum(ts_enum) => ts_enum.declare,
+        Decl::TsModule(ts_module) => ts_module.declare,
+        _ => false,
+    }
+}
+
 /// Analyzes the declaration in `script setup` context.
 /// These are typically `var`/`let`/`const` declarations, function declarations, etc.
 fn transform_decl_stmt(
@@ -64,6 +107,11 @@ fn transform_decl_stmt(
     }
 
     match decl {
+        // Ambient declarations, e.g. `declare function foo(): void`, do not exist in runtime
+        Decl::Class(ref class) if class.declare => Some(decl),
+        Decl::Fn(ref fn_decl) if fn_decl.declare => Some(decl),
+        Decl::Var(ref var_decl) if var_decl.declare => Some(decl),
+
         Decl::Class(ref class) => {
             push_return!(categorize_class(class));
         }
@@ -74,8 +122,19 @@ fn transform_decl_stmt(
 
         Decl::Var(mut var_decl) => {
             let is_const = matches!(var_decl.kind, VarDeclKind::Const);
+            let var_declarators = std::mem::take(&mut var_decl.decls);
+
+            for mut var_declarator in var_declarators {
+                // `const { foo, ...rest } = defineProps()`
+                if is_const && is_props_destructure(&var_declarator) {
+                    var_decl.decls.extend(transform_props_destructure(
+                        var_declarator,
+                        bindings_helper,
+                        sfc_object_helper,
+                    ));
+                    continue;
+                }
 
-            for var_declarator in var_decl.as_mut().decls.iter_mut() {
                 categorize_var_declarator(
                     &var_declarator,
                     &mut bindings_helper.setup_bindings,
@@ -94,6 +153,8 @@ fn transform_decl_stmt(
                     );
                     var_declarator.init = transformed.map(Box::new);
                 }
+
+                var_decl.decls.push(var_declarator);
             }
 
             Some(Decl::Var(var_decl))
@@ -108,9 +169,14 @@ fn transform_decl_stmt(
             ));
         }
 
+        // Types are not runtime bindings, but macros may refer to them,
+        // e.g. `interface Props {}` in `defineProps<Props>()`
+        Decl::TsInterface(_) | Decl::TsTypeAlias(_) => {
+            sfc_object_helper.type_decls.push(decl.to_owned());
+            Some(decl)
+        }
+
         // TODO: What?
-        // Decl::TsInterface(_) => todo!(),
-        // Decl::TsTypeAlias(_) => todo!(),
         // Decl::TsModule(_) => todo!(),
         _ => Some(decl),
     }
@@ -1,9 +1,13 @@
 //! A collection of utils for working with SWC structs
 
 use fervid_core::{fervid_atom, FervidAtom};
-use swc_core::ecma::ast::{
-    ArrayLit, BlockStmt, Callee, Expr, ExprOrSpread, Function, Lit, Module, ModuleDecl, ModuleItem,
-    ObjectLit, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Tpl,
+use swc_core::ecma::{
+    ast::{
+        ArrayLit, BlockStmt, Callee, Expr, ExprOrSpread, Function, JSXElement, JSXFragment, Lit,
+        Module, ModuleDecl, ModuleItem, ObjectLit, Prop, PropName, PropOrSpread, ReturnStmt, Stmt,
+        Tpl,
+    },
+    visit::{Visit, VisitWith},
 };
 
 #[deprecated]
@@ -268,3 +272,25 @@ pub fn unroll_paren_seq(expr: &Expr) -> &Expr {
         _ => expr,
     }
 }
+
+/// Checks whether the module has JSX, e.g. `<div />` or `<></>`.
+/// JSX is only parsed in `<script lang="jsx">` and `<script lang="tsx">`, but not compiled
+pub fn contains_jsx(module: &Module) -> bool {
+    let mut jsx_finder = JsxFinder { found: false };
+    module.visit_with(&mut jsx_finder);
+    jsx_finder.found
+}
+
+struct JsxFinder {
+    found: bool,
+}
+
+impl Visit for JsxFinder {
+    fn visit_jsx_element(&mut self, _n: &JSXElement) {
+        self.found = true;
+    }
+
+    fn visit_jsx_fragment(&mut self, _n: &JSXFragment) {
+        self.found = true;
+    }
+}
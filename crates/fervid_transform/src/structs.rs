@@ -1,13 +1,17 @@
 //! Exports data structs used by the crate
 
-use swc_core::ecma::{atoms::JsWord, ast::{Id, Expr, PropOrSpread, Module, ObjectLit, Function, ExprOrSpread}};
+use fervid_core::{
+    CancellationToken, FervidAtom, IdentPrefixes, SfcCssModule, TemplateGenerationMode,
+};
+use swc_core::ecma::{atoms::JsWord, ast::{Decl, Id, Expr, PropOrSpread, Module, ObjectLit, Function, ExprOrSpread, TsType}};
 
 /// Imports from "vue" package
 #[derive(Debug, Default, PartialEq)]
 pub struct VueResolvedImports {
     pub ref_import: Option<Id>,
     pub computed: Option<Id>,
-    pub reactive: Option<Id>
+    pub reactive: Option<Id>,
+    pub define_async_component: Option<Id>,
 }
 
 /// https://github.com/vuejs/rfcs/discussions/503
@@ -21,6 +25,10 @@ pub struct SfcDefineModel {
 pub struct SfcExportedObjectHelper {
     /// `emits` property
     pub emits: Option<Box<Expr>>,
+    /// Type argument of `defineEmits<T>()`
+    pub emits_type: Option<Box<TsType>>,
+    /// Argument of `defineExpose()`, used for the component declaration
+    pub exposed: Option<Box<Expr>>,
     /// Whether `__emit` was referenced (e.g. as a result of `const foo = defineEmits()`)
     pub is_setup_emit_referenced: bool,
     /// Whether `__expose` was referenced (e.g. as a result of `defineExpose()`)
@@ -31,10 +39,65 @@ pub struct SfcExportedObjectHelper {
     pub models: Vec<SfcDefineModel>,
     /// `props` property
     pub props: Option<Box<Expr>>,
+    /// Type argument of `defineProps<T>()`
+    pub props_type: Option<Box<TsType>>,
+    /// Type argument of `defineSlots<T>()`
+    pub slots_type: Option<Box<TsType>>,
+    /// Types declared in `<script setup>`, i.e. `interface`s and `type` aliases.
+    /// They are not runtime bindings, but are needed to resolve macro type arguments
+    pub type_decls: Vec<Decl>,
     /// Other fields of the object
     pub untyped_fields: Vec<PropOrSpread>,
 }
 
+/// Options for [`crate::transform_sfc`]
+#[derive(Debug, Default)]
+pub struct TransformSfcOptions<'o> {
+    /// Are we compiling for DEV or PROD
+    pub is_prod: bool,
+    /// Path to the SFC, usually relative to the project root.
+    /// In DEV it is used for the `__file` and `__name` component properties
+    pub filename: Option<&'o str>,
+    /// Scope ID of the component without `data-v-`, e.g. `abcd1234`.
+    /// It is used to name CSS variables produced by `v-bind()` in `<style>`
+    pub scope_id: &'o str,
+    /// Whether to keep the `<template>` comments, usually only in DEV
+    pub comments: bool,
+    /// Whether to generate the TypeScript declaration of the component
+    pub declaration: bool,
+    /// Whether the template is rendered inline in `setup` or by a separate render function.
+    /// By default it is inlined in PROD when `<script setup>` is present.
+    /// Without `<script setup>` the render function is always used
+    pub template_generation_mode: Option<TemplateGenerationMode>,
+    /// Tag names of the native custom elements, which are then not resolved as components.
+    /// A name ending with `*` matches by prefix, e.g. `ion-*`
+    pub custom_elements: Vec<FervidAtom>,
+    /// Names and prefixes of the generated identifiers, e.g. `_ctx`
+    pub ident_prefixes: IdentPrefixes,
+    /// Aborts the transformation when cancelled, see [`CancellationToken`]
+    pub cancellation_token: CancellationToken,
+    /// Class names of the compiled `<style module>` blocks.
+    /// Each of them becomes a constant accessible in the template, e.g. `$style.red`
+    pub css_modules: Vec<SfcCssModule>,
+}
+
+/// Information about the `<template>` gathered during the transformation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TemplateAnalysis {
+    /// Number of nodes with dynamic bindings:
+    /// components and built-ins, elements with bound attributes, event listeners or directives,
+    /// conditional sequences and interpolations referencing Js variables
+    pub dynamic_nodes: u32,
+}
+
+impl TemplateAnalysis {
+    /// Whether the template has zero dynamic bindings.
+    /// Such a template renders the same output every time and can be aggressively cached
+    pub fn is_fully_static(&self) -> bool {
+        self.dynamic_nodes == 0
+    }
+}
+
 pub struct TransformScriptsResult {
     /// EcmaScript module
     pub module: Module,
@@ -42,4 +105,6 @@ pub struct TransformScriptsResult {
     pub export_obj: ObjectLit,
     /// Setup function (not linked to default export yet)
     pub setup_fn: Option<Box<Function>>,
+    /// TypeScript declaration of the component, when requested
+    pub declaration: Option<Module>,
 }
@@ -1,8 +1,12 @@
 //! Handles template AST transformations.
 
 mod ast_transform;
+mod class_analysis;
 mod collect_vars;
-mod expr_transform;
+mod const_eval;
+pub(crate) mod expr_transform;
 mod js_builtins;
+mod slots_analysis;
 
 pub use ast_transform::transform_and_record_template;
+pub use class_analysis::collect_template_classes;
@@ -1,16 +1,36 @@
 use fervid_core::{
-    fervid_atom, is_from_default_slot, is_html_tag, AttributeOrBinding, BindingsHelper,
-    Conditional, ConditionalNodeSequence, ElementKind, ElementNode, Interpolation, Node,
-    PatchFlags, SfcTemplateBlock, StartingTag, StrOrExpr, TemplateScope, VOnDirective,
-    VSlotDirective, VUE_BUILTINS, check_attribute_name,
+    check_attribute_name, fervid_atom, is_from_default_slot, is_html_tag, AttributeOrBinding,
+    BindingTypes, BindingsHelper, BuiltinType, ComponentBinding, Conditional,
+    ConditionalNodeSequence, Diagnostic, DiagnosticKind, ElementKind, ElementNode, FervidAtom,
+    Interpolation, Node, PatchFlags, PatchHints, SetupBinding, SfcTemplateBlock, StartingTag,
+    StrOrExpr, TemplateGenerationMode, TemplateScope, VBindDirective, VOnDirective, VSlotDirective,
+    VUE_BUILTINS,
 };
 use smallvec::SmallVec;
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::ast::{
+        ArrowExpr, AssignExpr, AssignOp, BindingIdent, BlockStmtOrExpr, Bool, Expr, Ident, Lit,
+        MemberExpr, MemberProp, ParenExpr, Pat, PatOrExpr,
+    },
+};
 
-use super::{collect_vars::collect_variables, expr_transform::BindingsHelperTransform};
+use super::{
+    collect_vars::collect_variables,
+    const_eval::fold_const_expr,
+    expr_transform::BindingsHelperTransform,
+    slots_analysis::{has_dynamic_slots, has_forwarded_slots},
+};
+use crate::structs::TemplateAnalysis;
 
 struct TemplateVisitor<'s> {
     scope_helper: &'s mut BindingsHelper,
     current_scope: u32,
+    dynamic_nodes: u32,
+    /// Whether the visitor is inside a `v-once` subtree, which never gets patched
+    is_in_v_once: bool,
+    /// Whether the visitor is inside a `v-for`, where template refs are collected into arrays
+    is_in_v_for: bool,
 }
 
 /// Transforms the AST template by using information from [`BindingsHelper`].
@@ -19,20 +39,36 @@ struct TemplateVisitor<'s> {
 /// - Optimizing the tree by removing white-space nodes;
 /// - Folding the conditional nodes (`v-if`, etc.) into a single `ConditionalNode`;
 /// - Transforming Js expressions by resolving variables inside them.
+///
+/// Returns the [`TemplateAnalysis`], e.g. whether the template is fully static.
 pub fn transform_and_record_template(
     template: &mut SfcTemplateBlock,
     bindings_helper: &mut BindingsHelper,
-) {
-    // Only retain `ElementNode`s as template roots
-    template
-        .roots
-        .retain(|root| matches!(root, Node::Element(_)));
+) -> TemplateAnalysis {
+    let keep_comments = bindings_helper.comments;
+
+    // Only retain `ElementNode`s as template roots, and the comments if they are kept
+    template.roots.retain(|root| match root {
+        Node::Element(_) => true,
+        Node::Comment(_, _) => keep_comments,
+        _ => false,
+    });
 
     // Optimize conditional sequences within template root
-    optimize_children(&mut template.roots, ElementKind::Element);
+    optimize_children(&mut template.roots, ElementKind::Element, keep_comments);
 
     // Merge more than 1 child into a separate `<template>` element so that Fragment gets generated
     if template.roots.len() > 1 {
+        // Root comments produce a Fragment, which is only there in DEV
+        let mut patch_hints = PatchHints::default();
+        if template
+            .roots
+            .iter()
+            .any(|root| matches!(root, Node::Comment(_, _)))
+        {
+            patch_hints.flags = PatchFlags::StableFragment | PatchFlags::DevRootFragment;
+        }
+
         let all_roots = std::mem::replace(&mut template.roots, Vec::with_capacity(1));
         let new_root = Node::Element(ElementNode {
             kind: ElementKind::Element,
@@ -43,15 +79,27 @@ pub fn transform_and_record_template(
             },
             children: all_roots,
             template_scope: 0,
-            patch_hints: Default::default(),
+            patch_hints,
             span: template.span,
         });
         template.roots.push(new_root);
     }
 
+    // The root scope has no variables and is its own parent,
+    // so that the `v-for` and `v-slot` scopes never get the ID of `0`
+    if bindings_helper.template_scopes.is_empty() {
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: SmallVec::new(),
+            parent: 0,
+        });
+    }
+
     let mut template_visitor = TemplateVisitor {
         scope_helper: bindings_helper,
         current_scope: 0,
+        dynamic_nodes: 0,
+        is_in_v_once: false,
+        is_in_v_for: false,
     };
 
     // Optimize each root node separately
@@ -60,11 +108,18 @@ pub fn transform_and_record_template(
     while let Some(ref mut node) = iter.next() {
         node.visit_mut_with(&mut template_visitor);
     }
+
+    TemplateAnalysis {
+        dynamic_nodes: template_visitor.dynamic_nodes,
+    }
 }
 
-/// Optimizes the children by removing whitespace in between `ElementNode`s,
-/// as well as folding `v-if`/`v-else-if`/`v-else` sequences into a `ConditionalNodeSequence`
-fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
+/// Optimizes the children by removing the insignificant whitespace in between `ElementNode`s,
+/// as well as folding `v-if`/`v-else-if`/`v-else` sequences into a `ConditionalNodeSequence`.
+///
+/// The comments are removed unless `keep_comments` is set.
+/// Kept comments in between the conditional branches are moved into the next branch
+fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind, keep_comments: bool) {
     let children_len = children.len();
 
     // Discard children mask, limited to 128 children. 0 means to preserve the node, 1 to discard
@@ -84,18 +139,48 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
         _ => {}
     }
 
-    // For removing the middle whitespace text nodes, we need sliding windows of three nodes
+    // For removing the middle whitespace text nodes, we need sliding windows of three nodes.
+    // Same as in the official compiler, the whitespace is only removed if it is next to a comment
+    // or in between the elements on separate lines. Otherwise it stays and is condensed to a space,
+    // e.g. `<span>a</span> <span>b</span>` is rendered as `a b`.
     for (index, window) in children.windows(3).enumerate() {
         match window {
-            [Node::Element(_) | Node::Comment(_, _), Node::Text(middle, _), Node::Element(_) | Node::Comment(_, _)]
+            [prev @ (Node::Element(_) | Node::Comment(_, _)), Node::Text(middle, _), next @ (Node::Element(_) | Node::Comment(_, _))]
                 if middle.trim().len() == 0 =>
             {
-                discard_mask |= 1 << (index + 1);
+                let is_between_elements =
+                    matches!((prev, next), (Node::Element(_), Node::Element(_)));
+                let has_newline = middle.contains(|c| c == '\n' || c == '\r');
+
+                // Whitespace in front of `v-else-if` and `v-else` would break the conditional sequence
+                let is_before_else = match next {
+                    Node::Element(next_element) => next_element
+                        .starting_tag
+                        .directives
+                        .as_ref()
+                        .map_or(false, |directives| {
+                            directives.v_else_if.is_some() || directives.v_else.is_some()
+                        }),
+                    _ => false,
+                };
+
+                if !is_between_elements || has_newline || is_before_else {
+                    discard_mask |= 1 << (index + 1);
+                }
             }
             _ => {}
         }
     }
 
+    // The whitespace around the comments is already discarded
+    if !keep_comments {
+        for (index, child) in children.iter().enumerate() {
+            if let Node::Comment(_, _) = child {
+                discard_mask |= 1 << index;
+            }
+        }
+    }
+
     // Retain based on discard_mask. If a discard bit at `index` is set to 1, the node will be dropped
     let mut index = 0;
     children.retain(|_| {
@@ -119,12 +204,16 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
         let mut seq: Option<ConditionalNodeSequence> = None;
         let mut new_children = Vec::with_capacity(children.len());
 
+        // Comments after a conditional branch, which may belong to the next branch
+        let mut pending_comments: Vec<Node> = Vec::new();
+
         /// Finishes the sequence. Pass `child` to also push the current child
         macro_rules! finish_seq {
             () => {
                 if let Some(seq) = seq.take() {
                     new_children.push(Node::ConditionalSeq(seq))
                 }
+                new_children.append(&mut pending_comments);
             };
             ($child: expr) => {
                 finish_seq!();
@@ -144,9 +233,10 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
 
         for mut child in children.drain(..) {
             // Only process `ElementNode`s.
-            // Otherwise, when we have an `if` node, ignore `Comment`s and finish sequence.
+            // Otherwise, when we have an `if` node, postpone `Comment`s and finish sequence.
             let Node::Element(child_element) = &mut child else {
                 if let (Node::Comment(_, _), Some(_)) = (&child, seq.as_ref()) {
+                    pending_comments.push(child);
                     continue;
                 } else {
                     finish_seq!(child);
@@ -184,7 +274,7 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
 
                 seq.else_if_nodes.push(Conditional {
                     condition: *v_else_if,
-                    node: deref_element!(child),
+                    node: prepend_comments(&mut pending_comments, deref_element!(child)),
                 });
                 continue;
             }
@@ -197,7 +287,10 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
                     continue;
                 };
 
-                cond_seq.else_node = Some(Box::new(deref_element!(child)));
+                cond_seq.else_node = Some(Box::new(prepend_comments(
+                    &mut pending_comments,
+                    deref_element!(child),
+                )));
 
                 // `else` node always finishes the sequence
                 finish_seq!();
@@ -214,6 +307,42 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
     }
 }
 
+/// Moves the comments in front of a `v-else-if`/`v-else` branch into the branch.
+/// An element branch is wrapped in a `<template>` to render a Fragment with the comments
+fn prepend_comments(comments: &mut Vec<Node>, mut branch: ElementNode) -> ElementNode {
+    if comments.is_empty() {
+        return branch;
+    }
+
+    if matches!(branch.kind, ElementKind::Element) && branch.starting_tag.tag_name == "template" {
+        branch.children.splice(0..0, comments.drain(..));
+        return branch;
+    }
+
+    // The branch is already a part of the sequence
+    if let Some(ref mut directives) = branch.starting_tag.directives {
+        directives.v_else = None;
+    }
+
+    let template_scope = branch.template_scope;
+    let span = branch.span;
+    let mut children = std::mem::take(comments);
+    children.push(Node::Element(branch));
+
+    ElementNode {
+        kind: ElementKind::Element,
+        starting_tag: StartingTag {
+            tag_name: fervid_atom!("template"),
+            attributes: vec![],
+            directives: None,
+        },
+        children,
+        template_scope,
+        patch_hints: Default::default(),
+        span,
+    }
+}
+
 trait Visitor {
     fn visit_element_node(&mut self, element_node: &mut ElementNode);
     fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence);
@@ -226,20 +355,55 @@ trait VisitMut {
 
 impl<'a> Visitor for TemplateVisitor<'_> {
     fn visit_element_node(&mut self, element_node: &mut ElementNode) {
+        // Stop visiting a stale template as soon as possible
+        if self.scope_helper.cancellation_token.is_cancelled() {
+            return;
+        }
+
         let parent_scope = self.current_scope;
         let mut scope_to_use = parent_scope;
 
+        // `v-once` subtree is rendered once and then cached, so patch flags do not apply to it
+        let was_in_v_once = self.is_in_v_once;
+        self.is_in_v_once |= element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_once.is_some());
+
+        // Template refs inside `v-for` (including the `v-for` element itself) need `ref_for`
+        let was_in_v_for = self.is_in_v_for;
+        self.is_in_v_for |= element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_for.is_some());
+
+        // `v-is` and `is="vue:..."` turn a native element into a component
+        normalize_is_attribute(&mut element_node.starting_tag);
+
         // Mark the node with a correct type (element, component or built-in)
         let element_kind = self.recognize_element_kind(&element_node.starting_tag);
         let is_component = matches!(element_kind, ElementKind::Component);
         element_node.kind = element_kind;
 
+        // `v-html` and `v-text` override the children
+        self.remove_overridden_children(element_node);
+
+        // Components declared in `<script setup>` are used directly
+        if is_component {
+            self.resolve_component(&element_node.starting_tag.tag_name);
+        }
+
+        // Components and built-ins are never static, elements depend on their bindings
+        let mut is_dynamic = !matches!(element_kind, ElementKind::Element);
+
         // Check if there is a scoping directive
         // Finds a `v-for` or `v-slot` directive when in ElementNode
         // and collects their variables into the new template scope
         if let Some(ref mut directives) = element_node.starting_tag.directives {
             let v_for = directives.v_for.as_mut();
-            let v_slot = directives.v_slot.as_ref();
+            let v_slot = directives.v_slot.as_mut();
 
             // Create a new scope
             if v_for.is_some() || v_slot.is_some() {
@@ -257,12 +421,15 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 collect_variables(&v_for.itervar, &mut scope);
 
                 // Transform the iterable
-                let is_dynamic = self
+                let is_iterable_dynamic = self
                     .scope_helper
                     .transform_expr(&mut v_for.iterable, scope_to_use);
 
+                // A list is dynamic even with a static iterable, because it renders a fragment
+                is_dynamic = true;
+
                 // Add patch flags
-                if !is_dynamic {
+                if !is_iterable_dynamic {
                     // This is `64 /* STABLE_FRAGMENT */))`
                     // when iterable is non-dynamic (number, string) (`v-for="i in 3"`)
                     v_for.patch_flags |= PatchFlags::StableFragment;
@@ -282,14 +449,17 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                 }
             }
 
-            if let Some(VSlotDirective {
-                value: Some(v_slot_value),
-                ..
-            }) = v_slot
-            {
+            if let Some(v_slot) = v_slot {
+                // Dynamic slot name, e.g. `#[name]`, cannot reference the slot bindings
+                if let Some(StrOrExpr::Expr(ref mut slot_name)) = v_slot.slot_name {
+                    self.scope_helper.transform_expr(slot_name, scope_to_use);
+                }
+
                 // Collect slot bindings
-                let mut scope = &mut self.scope_helper.template_scopes[scope_to_use as usize];
-                collect_variables(v_slot_value, &mut scope);
+                if let Some(ref v_slot_value) = v_slot.value {
+                    let mut scope = &mut self.scope_helper.template_scopes[scope_to_use as usize];
+                    collect_variables(v_slot_value, &mut scope);
+                }
                 // TODO transform slot?
             }
         }
@@ -298,6 +468,34 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         element_node.template_scope = scope_to_use;
         self.current_scope = scope_to_use;
 
+        // Slots are analyzed before their content gets transformed
+        let has_slots = is_component
+            || matches!(
+                element_kind,
+                ElementKind::Builtin(
+                    BuiltinType::Component
+                        | BuiltinType::Suspense
+                        | BuiltinType::Transition
+                        | BuiltinType::TransitionGroup
+                )
+            );
+        let has_dynamic_slots = has_slots && {
+            // `v-for` on the component itself makes its variables outer to the slots
+            let has_v_for = element_node
+                .starting_tag
+                .directives
+                .as_ref()
+                .map_or(false, |directives| directives.v_for.is_some());
+            let outer_variables = self.collect_scope_variables(if has_v_for {
+                scope_to_use
+            } else {
+                parent_scope
+            });
+            has_dynamic_slots(element_node, &outer_variables)
+        };
+        element_node.patch_hints.has_forwarded_slots =
+            has_slots && has_forwarded_slots(&element_node.children);
+
         // Transform the VBind and VOn attributes
         let patch_hints = &mut element_node.patch_hints;
         for attr in element_node.starting_tag.attributes.iter_mut() {
@@ -313,6 +511,15 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                         .scope_helper
                         .transform_expr(&mut v_bind.value, scope_to_use);
 
+                    // Fold constant values, e.g. `:width="2 * 8"` becomes `:width="16"`
+                    if !has_bindings {
+                        fold_const_expr(&mut v_bind.value);
+                    }
+
+                    // Dynamic argument is a binding, e.g. `:[foo]="bar"`, same as `v-bind="obj"`
+                    let is_static_argument = matches!(v_bind.argument, Some(StrOrExpr::Str(_)));
+                    is_dynamic |= has_bindings || !is_static_argument;
+
                     let Some(StrOrExpr::Str(ref argument)) = v_bind.argument else {
                         // This is dynamic
                         // From docs: [FULL_PROPS is] exclusive with CLASS, STYLE and PROPS.
@@ -329,34 +536,98 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                         continue;
                     }
 
-                    // Skip `key` prop
-                    if argument == "key" {
+                    // Skip `key` prop, and `ref` is handled by `NEED_PATCH`
+                    if argument == "key" || argument == "ref" {
+                        continue;
+                    }
+
+                    // `<component :is="foo">` is resolved and not passed as a prop
+                    if argument == "is"
+                        && matches!(element_kind, ElementKind::Builtin(BuiltinType::Component))
+                    {
                         continue;
                     }
 
+                    // The prop name as generated, e.g. `:foo-bar.camel.prop` is `.fooBar`
+                    let is_forced = v_bind.is_prop || v_bind.is_attr;
+                    let prop_name = if v_bind.is_camel || is_forced {
+                        let prefix = if v_bind.is_prop {
+                            "."
+                        } else if v_bind.is_attr {
+                            "^"
+                        } else {
+                            ""
+                        };
+                        let name = if v_bind.is_camel {
+                            camelize(argument)
+                        } else {
+                            argument.to_string()
+                        };
+                        FervidAtom::from(format!("{}{}", prefix, name))
+                    } else {
+                        argument.to_owned()
+                    };
+
                     // Adding `class` and `style` bindings depends on `is_component`
                     // They are added to PROPS for the components.
                     if is_component {
                         patch_hints.flags |= PatchFlags::Props;
-                        patch_hints.props.push(argument.to_owned());
+                        patch_hints.props.push(prop_name);
                         continue;
                     }
 
-                    if argument == "class" {
+                    if argument == "class" && !is_forced {
                         patch_hints.flags |= PatchFlags::Class;
-                    } else if argument == "style" {
+                    } else if argument == "style" && !is_forced {
                         patch_hints.flags |= PatchFlags::Style;
                     } else {
                         patch_hints.flags |= PatchFlags::Props;
-                        patch_hints.props.push(argument.to_owned());
+                        patch_hints.props.push(prop_name);
                     }
                 }
 
+                // `v-on="handlers"` is an object of handlers, not a handler itself.
+                // Its keys are dynamic, same as with `v-bind="obj"`
                 AttributeOrBinding::VOn(VOnDirective {
-                    handler: Some(ref mut handler),
+                    event: None,
+                    handler: Some(ref mut handlers),
                     ..
                 }) => {
-                    self.scope_helper.transform_expr(handler, scope_to_use);
+                    self.scope_helper.transform_expr(handlers, scope_to_use);
+
+                    patch_hints.flags &=
+                        !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
+                    patch_hints.flags |= PatchFlags::FullProps;
+                    patch_hints.props.clear();
+                    is_dynamic = true;
+                }
+
+                AttributeOrBinding::VOn(v_on) => {
+                    // Dynamic event name is a dynamic key, same as `v-on="handlers"`
+                    if let Some(StrOrExpr::Expr(ref mut event)) = v_on.event {
+                        self.scope_helper.transform_expr(event, scope_to_use);
+
+                        patch_hints.flags &=
+                            !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
+                        patch_hints.flags |= PatchFlags::FullProps;
+                        patch_hints.props.clear();
+                    }
+
+                    // Event listeners without a handler still get attached.
+                    // Handlers inside `v-once` are not cached, the whole subtree already is
+                    v_on.is_cacheable = match v_on.handler {
+                        Some(ref mut handler) => {
+                            let uses_template_scope = self
+                                .scope_helper
+                                .transform_handler_expr(handler, scope_to_use);
+
+                            // Component method handlers are not cached, same as in the official compiler
+                            let is_method = matches!(**handler, Expr::Ident(_) | Expr::Member(_));
+                            !uses_template_scope && !(is_method && is_component)
+                        }
+                        None => true,
+                    } && !self.is_in_v_once;
+                    is_dynamic = true;
                 }
 
                 _ => {}
@@ -373,14 +644,121 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                     }
                 };
             }
-            maybe_transform!(v_html);
-            maybe_transform!(v_memo);
-            maybe_transform!(v_show);
-            maybe_transform!(v_text);
+            let is_v_html_dynamic = maybe_transform!(v_html);
+            let is_v_text_dynamic = maybe_transform!(v_text);
+            is_dynamic |= is_v_html_dynamic || is_v_text_dynamic;
+            is_dynamic |= maybe_transform!(v_memo);
+            is_dynamic |= maybe_transform!(v_show);
+
+            // `v-html` and `v-text` are the `innerHTML` and `textContent` props
+            if !patch_hints.flags.contains(PatchFlags::FullProps) {
+                if is_v_html_dynamic {
+                    patch_hints.flags |= PatchFlags::Props;
+                    patch_hints.props.push(fervid_atom!("innerHTML"));
+                }
+                if is_v_text_dynamic {
+                    patch_hints.flags |= PatchFlags::Props;
+                    patch_hints.props.push(fervid_atom!("textContent"));
+                }
+            }
+
+            // `v-model:title="title"` -> `title: title` and `"onUpdate:title": $event => (title = $event)`
+            for v_model in directives.v_model.iter_mut() {
+                let mut update_handler = generate_v_model_update_handler(&v_model.value);
+                self.scope_helper
+                    .transform_handler_expr(&mut update_handler, scope_to_use);
+                v_model.update_handler = Some(Box::new(update_handler));
+
+                self.scope_helper
+                    .transform_expr(&mut v_model.value, scope_to_use);
+
+                if let Some(StrOrExpr::Expr(ref mut argument)) = v_model.argument {
+                    self.scope_helper.transform_expr(argument, scope_to_use);
+                }
+
+                // Element `v-model` is applied by a runtime directive, e.g. `vModelText`
+                if !is_component {
+                    patch_hints.flags |= PatchFlags::NeedPatch;
+                }
+
+                // The model value (components only) and its update handler are dynamic props
+                if patch_hints.flags.contains(PatchFlags::FullProps) {
+                    continue;
+                }
+                let model_name = match v_model.argument {
+                    None => fervid_atom!("modelValue"),
+                    Some(StrOrExpr::Str(ref argument)) => argument.to_owned(),
+                    Some(StrOrExpr::Expr(_)) => {
+                        patch_hints.flags &=
+                            !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
+                        patch_hints.flags |= PatchFlags::FullProps;
+                        patch_hints.props.clear();
+                        continue;
+                    }
+                };
+                patch_hints.flags |= PatchFlags::Props;
+                if is_component {
+                    patch_hints.props.push(model_name.to_owned());
+                }
+                patch_hints
+                    .props
+                    .push(FervidAtom::from(format!("onUpdate:{}", camelize(&model_name))));
+            }
+
+            // `v-my-dir:[arg].mod="value"`, the modifiers are static
+            for custom_directive in directives.custom.iter_mut() {
+                if let Some(ref mut value) = custom_directive.value {
+                    self.scope_helper.transform_expr(value, scope_to_use);
+                }
+                if let Some(StrOrExpr::Expr(ref mut argument)) = custom_directive.argument {
+                    self.scope_helper.transform_expr(argument, scope_to_use);
+                }
+            }
+
+            // Custom directives need the node to be patched for their hooks to be called
+            if !directives.custom.is_empty() && patch_hints.flags.is_empty() {
+                patch_hints.flags |= PatchFlags::NeedPatch;
+            }
+
+            // `v-model` and custom directives are applied in runtime
+            is_dynamic |= !directives.v_model.is_empty() || !directives.custom.is_empty();
+        }
+
+        // Template refs are set in runtime and need the node to be patched
+        if self.transform_template_ref(&mut element_node.starting_tag.attributes) {
+            if patch_hints.flags.is_empty() {
+                patch_hints.flags |= PatchFlags::NeedPatch;
+            }
+            is_dynamic = true;
+        }
+
+        // Dynamic slot names make the slots object dynamic
+        if has_dynamic_slots {
+            patch_hints.flags |= PatchFlags::DynamicSlots;
+        }
+
+        if is_dynamic {
+            self.dynamic_nodes += 1;
         }
 
         // Merge conditional nodes and clean up whitespace
-        optimize_children(&mut element_node.children, element_kind);
+        optimize_children(
+            &mut element_node.children,
+            element_kind,
+            self.scope_helper.comments,
+        );
+
+        // Slots are validated once the conditional `<template v-slot>`s are folded
+        let accepts_slots = !matches!(
+            element_kind,
+            ElementKind::Element | ElementKind::Builtin(BuiltinType::Slot)
+        );
+        self.report_v_slot_misuse(
+            &element_node.starting_tag,
+            &element_node.children,
+            element_node.span,
+            accepts_slots,
+        );
 
         // Patch flag for HTML elements which only contain interpolation and text,
         // e.g. `<p>{{ msg }}</p>`.
@@ -394,8 +772,9 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             child.visit_mut_with(self);
 
             match child {
-                // When Elements are present, TEXT patch flag does not apply
-                Node::Element(_) | Node::ConditionalSeq(_) => {
+                // When Elements or Comments are present, TEXT patch flag does not apply,
+                // because the children are generated as an array of vnodes
+                Node::Element(_) | Node::ConditionalSeq(_) | Node::Comment(_, _) => {
                     is_children_text_only = false;
                 }
 
@@ -404,7 +783,7 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                     has_dynamic_interpolation |= interpolation.patch_flag;
                 }
 
-                Node::Text(_, _) | Node::Comment(_, _) => {}
+                Node::Text(_, _) => {}
             }
         }
 
@@ -413,22 +792,44 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             patch_hints.flags |= PatchFlags::Text;
         }
 
-        // Restore the parent scope
+        if self.is_in_v_once {
+            *patch_hints = Default::default();
+        }
+
+        // Restore the parent scope, `v-once` and `v-for` state
         self.current_scope = parent_scope;
+        self.is_in_v_once = was_in_v_once;
+        self.is_in_v_for = was_in_v_for;
     }
 
     fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence) {
+        // The branch being rendered depends on the conditions
+        self.dynamic_nodes += 1;
+
         // In this function, conditions are transformed first
         // without updating the template scope and collecting its variables.
         // I believe this is a correct way of doing it, because in VDOM the condition
         // wraps around the node (`condition ? if_node : else_node`).
         // However, I am not too sure about the `v-if` & `v-slot` combined usage.
-
+        // This is also why `v-if` on a `v-for` element cannot access the iteration variables.
+
+        // `v-once` on the `v-if` node caches the whole conditional, including the other branches
+        let was_in_v_once = self.is_in_v_once;
+        self.is_in_v_once |= conditional_node
+            .if_node
+            .node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_once.is_some());
+
+        self.report_v_if_with_v_for(&conditional_node.if_node.node);
         self.scope_helper
             .transform_expr(&mut conditional_node.if_node.condition, self.current_scope);
         self.visit_element_node(&mut conditional_node.if_node.node);
 
         for else_if_node in conditional_node.else_if_nodes.iter_mut() {
+            self.report_v_if_with_v_for(&else_if_node.node);
             self.scope_helper
                 .transform_expr(&mut else_if_node.condition, self.current_scope);
             self.visit_element_node(&mut else_if_node.node);
@@ -437,6 +838,8 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         if let Some(ref mut else_node) = conditional_node.else_node {
             self.visit_element_node(else_node);
         }
+
+        self.is_in_v_once = was_in_v_once;
     }
 
     fn visit_interpolation(&mut self, interpolation: &mut Interpolation) {
@@ -447,10 +850,180 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             .transform_expr(&mut interpolation.value, self.current_scope);
 
         interpolation.patch_flag = has_js;
+
+        if has_js {
+            self.dynamic_nodes += 1;
+        }
     }
 }
 
 impl TemplateVisitor<'_> {
+    /// Warns about `v-if` and `v-for` on the same element, same as the official compiler
+    fn report_v_if_with_v_for(&mut self, element_node: &ElementNode) {
+        let has_v_for = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| directives.v_for.is_some());
+
+        if has_v_for {
+            self.scope_helper.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::VIfWithVFor,
+                span: element_node.span,
+            });
+        }
+    }
+
+    /// Reports and removes the children of an element with `v-html` or `v-text`,
+    /// because they are replaced in runtime, same as the official compiler.
+    /// Whitespace-only children are removed silently
+    fn remove_overridden_children(&mut self, element_node: &mut ElementNode) {
+        let Some(ref directives) = element_node.starting_tag.directives else {
+            return;
+        };
+        let kind = if directives.v_html.is_some() {
+            DiagnosticKind::VHtmlWithChildren
+        } else if directives.v_text.is_some() {
+            DiagnosticKind::VTextWithChildren
+        } else {
+            return;
+        };
+
+        let has_children = element_node
+            .children
+            .iter()
+            .any(|child| !matches!(child, Node::Text(text, _) if text.trim().is_empty()));
+        if has_children {
+            self.scope_helper.diagnostics.push(Diagnostic {
+                kind,
+                span: element_node.span,
+            });
+        }
+
+        element_node.children.clear();
+    }
+
+    /// Reports the `v-slot` usages which cannot be compiled unambiguously:
+    /// - `v-slot` on an element which is neither a component nor a `<template>` inside a component;
+    /// - `v-slot` on both the component and its nested `<template>`;
+    /// - duplicate slot names, including the default slot content
+    ///   alongside the `<template v-slot:default>`.
+    ///
+    /// Conditional and looped slots, as well as the dynamic slot names, are not checked for duplicates
+    fn report_v_slot_misuse(
+        &mut self,
+        starting_tag: &StartingTag,
+        children: &[Node],
+        span: Span,
+        accepts_slots: bool,
+    ) {
+        fn get_v_slot(starting_tag: &StartingTag) -> Option<&VSlotDirective> {
+            starting_tag
+                .directives
+                .as_ref()
+                .and_then(|directives| directives.v_slot.as_ref())
+        }
+
+        let diagnostics = &mut self.scope_helper.diagnostics;
+        macro_rules! report {
+            ($kind: expr, $span: expr) => {
+                diagnostics.push(Diagnostic {
+                    kind: $kind,
+                    span: $span,
+                })
+            };
+        }
+
+        // `<template v-slot>` is checked by its parent
+        let has_own_v_slot = get_v_slot(starting_tag).is_some();
+        if has_own_v_slot && !accepts_slots && starting_tag.tag_name != "template" {
+            report!(DiagnosticKind::VSlotMisplaced, span);
+        }
+
+        let mut seen_slot_names: Vec<FervidAtom> = Vec::new();
+        let mut explicit_default_span = None;
+        let mut has_default_slot_content = false;
+
+        for child in children.iter() {
+            let (slot_candidates, is_conditional): (Vec<&ElementNode>, bool) = match child {
+                Node::Element(element_node) => (
+                    vec![element_node],
+                    element_node
+                        .starting_tag
+                        .directives
+                        .as_ref()
+                        .map_or(false, |directives| directives.v_for.is_some()),
+                ),
+                Node::ConditionalSeq(conditional_seq) => (
+                    std::iter::once(&conditional_seq.if_node.node)
+                        .chain(conditional_seq.else_if_nodes.iter().map(|c| &c.node))
+                        .chain(conditional_seq.else_node.as_deref())
+                        .collect(),
+                    true,
+                ),
+                Node::Text(text, _) if text.trim().is_empty() => continue,
+                Node::Comment(_, _) => continue,
+                Node::Text(_, _) | Node::Interpolation(_) => {
+                    has_default_slot_content = true;
+                    continue;
+                }
+            };
+
+            for slot_candidate in slot_candidates {
+                let v_slot = get_v_slot(&slot_candidate.starting_tag)
+                    .filter(|_| slot_candidate.starting_tag.tag_name == "template");
+                let Some(v_slot) = v_slot else {
+                    has_default_slot_content = true;
+                    continue;
+                };
+
+                if !accepts_slots {
+                    report!(DiagnosticKind::VSlotMisplaced, slot_candidate.span);
+                    continue;
+                }
+
+                if has_own_v_slot {
+                    report!(DiagnosticKind::VSlotMixedUsage, slot_candidate.span);
+                    continue;
+                }
+
+                if is_conditional {
+                    continue;
+                }
+
+                let slot_name = match v_slot.slot_name {
+                    None => fervid_atom!("default"),
+                    Some(StrOrExpr::Str(ref slot_name)) => slot_name.to_owned(),
+                    Some(StrOrExpr::Expr(_)) => continue,
+                };
+
+                if seen_slot_names.contains(&slot_name) {
+                    report!(
+                        DiagnosticKind::VSlotDuplicateName(slot_name),
+                        slot_candidate.span
+                    );
+                    continue;
+                }
+
+                if slot_name == "default" {
+                    explicit_default_span = Some(slot_candidate.span);
+                }
+                seen_slot_names.push(slot_name);
+            }
+        }
+
+        // `<comp><template #default>foo</template>bar</comp>`
+        if let (true, Some(explicit_default_span)) = (
+            accepts_slots && has_default_slot_content && !has_own_v_slot,
+            explicit_default_span,
+        ) {
+            report!(
+                DiagnosticKind::VSlotDuplicateName(fervid_atom!("default")),
+                explicit_default_span
+            );
+        }
+    }
+
     fn recognize_element_kind(&self, starting_tag: &StartingTag) -> ElementKind {
         let tag_name = &starting_tag.tag_name;
 
@@ -471,13 +1044,316 @@ impl TemplateVisitor<'_> {
             return ElementKind::Builtin(*builtin_type);
         }
 
-        // Then check if this is an HTML tag
-        if is_html_tag(&starting_tag.tag_name) {
+        // Then check if this is an HTML tag or a custom element
+        if self.scope_helper.is_custom_element(tag_name) || is_html_tag(tag_name) {
             ElementKind::Element
         } else {
             ElementKind::Component
         }
     }
+
+    /// Collects the variables of the template scope and all of its parents
+    fn collect_scope_variables(&self, starting_scope: u32) -> Vec<FervidAtom> {
+        let mut variables = Vec::new();
+        let mut current_scope_index = starting_scope;
+
+        while let Some(current_scope) = self
+            .scope_helper
+            .template_scopes
+            .get(current_scope_index as usize)
+        {
+            variables.extend(current_scope.variables.iter().cloned());
+
+            // The root scope has itself as a parent
+            if current_scope.parent == current_scope_index {
+                break;
+            }
+            current_scope_index = current_scope.parent;
+        }
+
+        variables
+    }
+
+    /// Processes the `ref` attribute of an element or a component:
+    /// - under `v-for`, `ref_for: true` is added so that the refs are collected into an array;
+    /// - in the inline mode, `ref="el"` referencing a `<script setup>` ref is passed as the ref itself,
+    ///   together with `ref_key: "el"`, because there is no `setupState` to set the value by key.
+    ///
+    /// Returns whether the `ref` is present.
+    fn transform_template_ref(&mut self, attributes: &mut Vec<AttributeOrBinding>) -> bool {
+        let Some(mut ref_idx) = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "ref"))
+        else {
+            return false;
+        };
+
+        if self.is_in_v_for {
+            attributes.insert(
+                ref_idx,
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(fervid_atom!("ref_for"))),
+                    value: Box::new(Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    }))),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: Default::default(),
+                }),
+            );
+            ref_idx += 1;
+        }
+
+        if !matches!(
+            self.scope_helper.template_generation_mode,
+            TemplateGenerationMode::Inline
+        ) {
+            return true;
+        }
+
+        let (value, span) = match attributes[ref_idx] {
+            AttributeOrBinding::RegularAttribute {
+                ref value, span, ..
+            } => (value.to_owned(), span),
+            _ => return true,
+        };
+
+        let binding_type = self
+            .scope_helper
+            .setup_bindings
+            .iter()
+            .find(|binding| binding.0 == *value)
+            .map(|binding| binding.1);
+
+        let ref_ident = Ident {
+            span: DUMMY_SP,
+            sym: value.to_owned(),
+            optional: false,
+        };
+        let ref_value = match binding_type {
+            Some(BindingTypes::SetupRef | BindingTypes::SetupMaybeRef) => Expr::Ident(ref_ident),
+
+            // `let el` may be reassigned, so the element is assigned to it
+            Some(BindingTypes::SetupLet) => generate_ref_setter(ref_ident),
+
+            _ => return true,
+        };
+
+        // `ref_key: "el", ref: el`
+        attributes[ref_idx] = AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(fervid_atom!("ref"))),
+            value: Box::new(ref_value),
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span,
+        });
+        attributes.insert(
+            ref_idx,
+            AttributeOrBinding::RegularAttribute {
+                name: fervid_atom!("ref_key"),
+                value,
+                span,
+            },
+        );
+
+        true
+    }
+
+    /// Resolves a component to a `<script setup>` binding,
+    /// e.g. `<async-foo>` to `const AsyncFoo = defineAsyncComponent(() => import('./Foo.vue'))`
+    /// or `<Form.Input>` to `import * as Form from './form'`.
+    /// Resolved components do not need `resolveComponent` in runtime.
+    fn resolve_component(&mut self, tag_name: &FervidAtom) {
+        if self.scope_helper.components.contains_key(tag_name) {
+            return;
+        }
+
+        // `<Form.Input>` resolves through the namespace `Form`
+        let mut segments = tag_name.split('.');
+        let Some(binding_tag) = segments.next() else {
+            return;
+        };
+
+        // `<foo-bar>` can refer to `foo-bar`, `fooBar` or `FooBar`
+        let camelized = camelize(binding_tag);
+        let capitalized = capitalize(&camelized);
+        let candidates = [binding_tag, camelized.as_str(), capitalized.as_str()];
+
+        let found = candidates.iter().find_map(|candidate| {
+            self.scope_helper
+                .setup_bindings
+                .iter()
+                .find(|binding| &*binding.0 == *candidate)
+        });
+        let Some(SetupBinding(binding_name, _)) = found else {
+            return;
+        };
+
+        // `$setup.Foo`, or `Foo` in the inline mode
+        let mut resolved = Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: binding_name.to_owned(),
+            optional: false,
+        });
+        self.scope_helper.transform_expr(&mut resolved, 0);
+
+        // `Form.Input`
+        for member in segments {
+            resolved = Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(resolved),
+                prop: MemberProp::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: FervidAtom::from(member),
+                    optional: false,
+                }),
+            });
+        }
+
+        self.scope_helper.components.insert(
+            tag_name.to_owned(),
+            ComponentBinding::Resolved(Box::new(resolved)),
+        );
+    }
+}
+
+/// Generates `$event => (value = $event)` for the `v-model` value.
+/// The value is not transformed yet, so that the assignment target is transformed as such.
+fn generate_v_model_update_handler(value: &Expr) -> Expr {
+    let event_ident = Ident {
+        span: DUMMY_SP,
+        sym: fervid_atom!("$event"),
+        optional: false,
+    };
+
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![Pat::Ident(BindingIdent {
+            id: event_ident.to_owned(),
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: PatOrExpr::Expr(Box::new(value.to_owned())),
+                right: Box::new(Expr::Ident(event_ident)),
+            })),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+/// Generates `_value => el = _value` for the `ref` of a `let el` binding
+fn generate_ref_setter(ref_ident: Ident) -> Expr {
+    let value_ident = Ident {
+        span: DUMMY_SP,
+        sym: fervid_atom!("_value"),
+        optional: false,
+    };
+
+    Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![Pat::Ident(BindingIdent {
+            id: value_ident.to_owned(),
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: PatOrExpr::Pat(Box::new(Pat::Ident(BindingIdent {
+                id: ref_ident,
+                type_ann: None,
+            }))),
+            right: Box::new(Expr::Ident(value_ident)),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    })
+}
+
+/// Handles the `is` semantics of the native elements:
+/// - `<tr v-is="foo">` is the same as `<component :is="foo">`;
+/// - `<tr is="vue:my-row">` is the same as `<my-row>`;
+/// - `<button is="plastic-button">` is a customized built-in element and is kept as is.
+fn normalize_is_attribute(starting_tag: &mut StartingTag) {
+    if starting_tag.tag_name == "component" {
+        return;
+    }
+
+    // `v-is` is a custom directive for the parser
+    let v_is = starting_tag.directives.as_mut().and_then(|directives| {
+        let idx = directives
+            .custom
+            .iter()
+            .position(|directive| directive.name == "is" && directive.value.is_some())?;
+        directives.custom.remove(idx).value
+    });
+
+    if let Some(value) = v_is {
+        starting_tag.tag_name = fervid_atom!("component");
+        starting_tag
+            .attributes
+            .push(AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(fervid_atom!("is"))),
+                value,
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: Default::default(),
+            }));
+        return;
+    }
+
+    let vue_is_idx = starting_tag.attributes.iter().position(|attr| {
+        matches!(attr, AttributeOrBinding::RegularAttribute { name, value, .. }
+            if name == "is" && value.starts_with("vue:"))
+    });
+
+    if let Some(idx) = vue_is_idx {
+        if let AttributeOrBinding::RegularAttribute { value, .. } =
+            starting_tag.attributes.remove(idx)
+        {
+            starting_tag.tag_name = FervidAtom::from(&value[4..]);
+        }
+    }
+}
+
+/// `foo-bar` -> `fooBar`
+fn camelize(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut uppercase_next = false;
+
+    for c in s.chars() {
+        if c == '-' {
+            uppercase_next = true;
+        } else if uppercase_next {
+            result.extend(c.to_uppercase());
+            uppercase_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// `fooBar` -> `FooBar`
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 impl VisitMut for Node {
@@ -493,8 +1369,11 @@ impl VisitMut for Node {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementKind, Node, VueDirectives};
-    use swc_core::{common::DUMMY_SP, ecma::ast::Expr};
+    use fervid_core::{ElementKind, Node, VModelDirective, VueDirectives};
+    use swc_core::{
+        common::{BytePos, SyntaxContext, DUMMY_SP},
+        ecma::ast::Expr,
+    };
 
     use crate::test_utils::{parser::parse_javascript_expr, to_str};
 
@@ -513,6 +1392,9 @@ mod tests {
         let template_visitor = TemplateVisitor {
             scope_helper: &mut scope_helper,
             current_scope: 0,
+            dynamic_nodes: 0,
+            is_in_v_once: false,
+            is_in_v_for: false,
         };
         assert!(matches!(
             template_visitor.recognize_element_kind(&starting_tag),
@@ -521,22 +1403,21 @@ mod tests {
     }
 
     #[test]
-    fn it_folds_basic_seq() {
-        // <template><div>
-        //   text
-        //   <h1 v-if="true">if</h1>
-        //   <h2 v-else-if="foo">else-if</h2>
-        //   <h3 v-else>else</h3>
-        // </div></template>
+    fn it_recognizes_custom_elements() {
+        // <ion-button label="Hi">Click</ion-button>
         let mut sfc_template = SfcTemplateBlock {
             lang: "html".into(),
             roots: vec![Node::Element(ElementNode {
                 starting_tag: StartingTag {
-                    tag_name: "div".into(),
-                    attributes: vec![],
+                    tag_name: "ion-button".into(),
+                    attributes: vec![AttributeOrBinding::RegularAttribute {
+                        name: "label".into(),
+                        value: "Hi".into(),
+                        span: DUMMY_SP,
+                    }],
                     directives: None,
                 },
-                children: vec![text_node(), if_node(), else_if_node(), else_node()],
+                children: vec![Node::Text("Click".into(), DUMMY_SP)],
                 template_scope: 0,
                 kind: ElementKind::Element,
                 patch_hints: Default::default(),
@@ -545,15 +1426,123 @@ mod tests {
             span: DUMMY_SP,
         };
 
-        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        let mut bindings_helper = BindingsHelper {
+            custom_elements: vec![FervidAtom::from("ion-*")],
+            ..Default::default()
+        };
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
 
-        // Template roots: one div
-        assert_eq!(1, sfc_template.roots.len());
-        let Node::Element(ref div) = sfc_template.roots[0] else {
+        let Node::Element(ref element) = sfc_template.roots[0] else {
             panic!("Root is not an element")
         };
+        assert!(matches!(element.kind, ElementKind::Element));
+        assert!(bindings_helper.components.is_empty());
+        assert!(matches!(
+            element.starting_tag.attributes[..],
+            [AttributeOrBinding::RegularAttribute { ref name, ref value, .. }]
+                if name == "label" && value == "Hi"
+        ));
+        assert!(matches!(element.children[..], [Node::Text(ref text, _)] if text == "Click"));
 
-        // Text and conditional seq
+        // Without the pattern, the same tag is a component
+        let starting_tag = element.starting_tag.to_owned();
+        let mut scope_helper = Default::default();
+        let template_visitor = TemplateVisitor {
+            scope_helper: &mut scope_helper,
+            current_scope: 0,
+            dynamic_nodes: 0,
+            is_in_v_once: false,
+            is_in_v_for: false,
+        };
+        assert!(matches!(
+            template_visitor.recognize_element_kind(&starting_tag),
+            ElementKind::Component
+        ));
+    }
+
+    #[test]
+    fn it_normalizes_native_is() {
+        // <tr v-is="foo">
+        let mut starting_tag = StartingTag {
+            tag_name: "tr".into(),
+            attributes: vec![],
+            directives: Some(Box::new(VueDirectives {
+                custom: vec![fervid_core::VCustomDirective {
+                    name: "is".into(),
+                    value: Some(js("foo")),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })),
+        };
+        normalize_is_attribute(&mut starting_tag);
+        assert_eq!(starting_tag.tag_name, "component");
+        assert!(check_attribute_name(&starting_tag.attributes[0], "is"));
+        assert!(starting_tag.directives.unwrap().custom.is_empty());
+
+        // <tr is="vue:my-row">
+        let mut starting_tag = StartingTag {
+            tag_name: "tr".into(),
+            attributes: vec![AttributeOrBinding::RegularAttribute {
+                name: "is".into(),
+                value: "vue:my-row".into(),
+                span: Default::default(),
+            }],
+            directives: None,
+        };
+        normalize_is_attribute(&mut starting_tag);
+        assert_eq!(starting_tag.tag_name, "my-row");
+        assert!(starting_tag.attributes.is_empty());
+
+        // <button is="plastic-button">
+        let mut starting_tag = StartingTag {
+            tag_name: "button".into(),
+            attributes: vec![AttributeOrBinding::RegularAttribute {
+                name: "is".into(),
+                value: "plastic-button".into(),
+                span: Default::default(),
+            }],
+            directives: None,
+        };
+        normalize_is_attribute(&mut starting_tag);
+        assert_eq!(starting_tag.tag_name, "button");
+        assert_eq!(starting_tag.attributes.len(), 1);
+    }
+
+    #[test]
+    fn it_folds_basic_seq() {
+        // <template><div>
+        //   text
+        //   <h1 v-if="true">if</h1>
+        //   <h2 v-else-if="foo">else-if</h2>
+        //   <h3 v-else>else</h3>
+        // </div></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![text_node(), if_node(), else_if_node(), else_node()],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        // Template roots: one div
+        assert_eq!(1, sfc_template.roots.len());
+        let Node::Element(ref div) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+
+        // Text and conditional seq
         assert_eq!(2, div.children.len());
         check_text_node(&div.children[0]);
         let Node::ConditionalSeq(seq) = &div.children[1] else {
@@ -748,6 +1737,207 @@ mod tests {
         assert!(matches!(&div.children[5], Node::ConditionalSeq(_)));
     }
 
+    #[test]
+    fn it_adds_text_patch_flag() {
+        // <p>{{ foo }} bar</p> and <p>{{ foo }}<!-- comment --></p>
+        let paragraph = |children: Vec<Node>| {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "p".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children,
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        let interpolation = || {
+            Node::Interpolation(Interpolation {
+                value: js("foo"),
+                template_scope: 0,
+                patch_flag: false,
+            })
+        };
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                paragraph(vec![interpolation(), Node::Text(" bar".into(), DUMMY_SP)]),
+                paragraph(vec![
+                    interpolation(),
+                    Node::Comment(" comment ".into(), DUMMY_SP),
+                ]),
+            ],
+            span: DUMMY_SP,
+        };
+        let mut bindings_helper = BindingsHelper {
+            comments: true,
+            ..Default::default()
+        };
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Node::Element(ref text_only) = root.children[0] else {
+            panic!("Child is not an element")
+        };
+        assert_eq!(text_only.patch_hints.flags, PatchFlags::Text);
+        let Node::Element(ref with_comment) = root.children[1] else {
+            panic!("Child is not an element")
+        };
+        assert!(with_comment.patch_hints.flags.is_empty());
+    }
+
+    #[test]
+    fn it_condenses_whitespace() {
+        // <div><span/> <span/>
+        //   <span/> <!-- comment --> <span/></div>
+        let mut children = vec![
+            span_node(vec![]),
+            Node::Text(" ".into(), DUMMY_SP),
+            span_node(vec![]),
+            Node::Text("\n  ".into(), DUMMY_SP),
+            span_node(vec![]),
+            Node::Text(" ".into(), DUMMY_SP),
+            Node::Comment(" comment ".into(), DUMMY_SP),
+            Node::Text(" ".into(), DUMMY_SP),
+            span_node(vec![]),
+        ];
+        optimize_children(&mut children, ElementKind::Element, true);
+
+        assert_eq!(6, children.len());
+        assert!(matches!(children[1], Node::Text(ref text, _) if text == " "));
+        assert!(matches!(children[2], Node::Element(_)));
+        assert!(matches!(children[3], Node::Element(_)));
+        assert!(matches!(children[4], Node::Comment(_, _)));
+
+        // <h1 v-if="true">if</h1> <h3 v-else>else</h3>
+        let mut children = vec![if_node(), Node::Text(" ".into(), DUMMY_SP), else_node()];
+        optimize_children(&mut children, ElementKind::Element, true);
+
+        assert_eq!(1, children.len());
+        let Node::ConditionalSeq(ref seq) = children[0] else {
+            panic!("Not a conditional sequence")
+        };
+        assert!(seq.else_node.is_some());
+    }
+
+    #[test]
+    fn it_removes_comments() {
+        // <div><span/> <!-- comment --> <span/></div>
+        let mut children = vec![
+            span_node(vec![]),
+            Node::Text(" ".into(), DUMMY_SP),
+            Node::Comment(" comment ".into(), DUMMY_SP),
+            Node::Text(" ".into(), DUMMY_SP),
+            span_node(vec![]),
+        ];
+        optimize_children(&mut children, ElementKind::Element, false);
+
+        assert_eq!(2, children.len());
+        assert!(matches!(children[0], Node::Element(_)));
+        assert!(matches!(children[1], Node::Element(_)));
+    }
+
+    #[test]
+    fn it_moves_comments_into_conditional_branches() {
+        // <h1 v-if="true">if</h1> <!-- comment --> <h3 v-else>else</h3>
+        let children = || {
+            vec![
+                if_node(),
+                Node::Text(" ".into(), DUMMY_SP),
+                Node::Comment(" comment ".into(), DUMMY_SP),
+                Node::Text(" ".into(), DUMMY_SP),
+                else_node(),
+            ]
+        };
+
+        let mut kept = children();
+        optimize_children(&mut kept, ElementKind::Element, true);
+        assert_eq!(1, kept.len());
+        let Node::ConditionalSeq(ref seq) = kept[0] else {
+            panic!("Not a conditional sequence")
+        };
+        let else_node = seq.else_node.as_ref().expect("Must have else node");
+        assert_eq!(else_node.starting_tag.tag_name, "template");
+        assert_eq!(2, else_node.children.len());
+        assert!(matches!(else_node.children[0], Node::Comment(_, _)));
+        assert!(matches!(
+            else_node.children[1],
+            Node::Element(ElementNode { ref starting_tag, .. }) if starting_tag.tag_name == "h3"
+        ));
+
+        let mut removed = children();
+        optimize_children(&mut removed, ElementKind::Element, false);
+        assert_eq!(1, removed.len());
+        check_else_node(match removed[0] {
+            Node::ConditionalSeq(ref seq) => seq.else_node.as_ref(),
+            _ => None,
+        });
+
+        // <h1 v-if="true">if</h1> <!-- comment --> <span/>
+        let mut children = vec![
+            if_node(),
+            Node::Comment(" comment ".into(), DUMMY_SP),
+            span_node(vec![]),
+        ];
+        optimize_children(&mut children, ElementKind::Element, true);
+        assert_eq!(3, children.len());
+        assert!(matches!(children[0], Node::ConditionalSeq(_)));
+        assert!(matches!(children[1], Node::Comment(_, _)));
+        assert!(matches!(children[2], Node::Element(_)));
+    }
+
+    #[test]
+    fn it_keeps_root_comments() {
+        // <!-- comment --><div></div>
+        let roots = || {
+            vec![
+                Node::Comment(" comment ".into(), DUMMY_SP),
+                span_node(vec![]),
+            ]
+        };
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: roots(),
+            span: DUMMY_SP,
+        };
+        let mut bindings_helper = BindingsHelper {
+            comments: true,
+            ..Default::default()
+        };
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        assert_eq!(1, sfc_template.roots.len());
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(root.starting_tag.tag_name, "template");
+        assert_eq!(2, root.children.len());
+        assert_eq!(
+            root.patch_hints.flags,
+            PatchFlags::StableFragment | PatchFlags::DevRootFragment
+        );
+
+        // Comments are not kept
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: roots(),
+            span: DUMMY_SP,
+        };
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+        assert_eq!(1, sfc_template.roots.len());
+        assert!(matches!(
+            sfc_template.roots[0],
+            Node::Element(ElementNode { ref starting_tag, .. }) if starting_tag.tag_name == "span"
+        ));
+    }
+
     #[test]
     fn it_ignores_node_without_conditional_directives() {
         let no_directives1 = Node::Element(ElementNode {
@@ -796,6 +1986,1165 @@ mod tests {
         assert_eq!(2, root.children.len());
     }
 
+    #[test]
+    fn it_analyzes_static_template() {
+        // <template><div class="foo">text<span>hello</span></div></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![AttributeOrBinding::RegularAttribute {
+                        name: "class".into(),
+                        value: "foo".into(),
+                        span: Default::default(),
+                    }],
+                    directives: None,
+                },
+                children: vec![text_node(), span_node(vec![])],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let analysis = transform_and_record_template(&mut sfc_template, &mut Default::default());
+        assert_eq!(0, analysis.dynamic_nodes);
+        assert!(analysis.is_fully_static());
+    }
+
+    #[test]
+    fn it_counts_dynamic_nodes() {
+        // <template><div>
+        //   <h1 v-if="true">if</h1>
+        //   <span :title="foo">{{ bar }}</span>
+        //   <span :title="1 + 2">{{ 'static' }}</span>
+        // </div></template>
+        let mut dynamic_span = span_node(vec![Node::Interpolation(Interpolation {
+            value: js("bar"),
+            template_scope: 0,
+            patch_flag: false,
+        })]);
+        let mut static_span = span_node(vec![Node::Interpolation(Interpolation {
+            value: js("'static'"),
+            template_scope: 0,
+            patch_flag: false,
+        })]);
+        for (node, value) in [(&mut dynamic_span, "foo"), (&mut static_span, "1 + 2")] {
+            let Node::Element(element) = node else {
+                unreachable!()
+            };
+            element
+                .starting_tag
+                .attributes
+                .push(AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                    argument: Some("title".into()),
+                    value: js(value),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: Default::default(),
+                }));
+        }
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![if_node(), dynamic_span, static_span],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let analysis = transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        // Conditional sequence, `<span :title="foo">` and `{{ bar }}`
+        assert_eq!(3, analysis.dynamic_nodes);
+        assert!(!analysis.is_fully_static());
+    }
+
+    #[test]
+    fn it_stops_when_cancelled() {
+        // <template><span>{{ bar }}</span></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![span_node(vec![Node::Interpolation(Interpolation {
+                value: js("bar"),
+                template_scope: 0,
+                patch_flag: false,
+            })])],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.cancellation_token.cancel();
+
+        let analysis = transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        // Interpolation was not visited
+        assert_eq!(0, analysis.dynamic_nodes);
+        let Node::Element(ref span) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Node::Interpolation(ref interpolation) = span.children[0] else {
+            panic!("Child is not an interpolation")
+        };
+        assert!(!interpolation.patch_flag);
+    }
+
+    #[test]
+    fn it_resolves_setup_components() {
+        // <template><async-foo /><unknown-bar /></template>
+        fn component_node(tag_name: &str) -> Node {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        }
+
+        macro_rules! resolve {
+            ($mode: expr) => {{
+                let mut sfc_template = SfcTemplateBlock {
+                    lang: "html".into(),
+                    roots: vec![component_node("async-foo"), component_node("unknown-bar")],
+                    span: DUMMY_SP,
+                };
+
+                let mut bindings_helper = BindingsHelper::default();
+                bindings_helper.template_generation_mode = $mode;
+                bindings_helper.setup_bindings = vec![SetupBinding(
+                    FervidAtom::from("AsyncFoo"),
+                    fervid_core::BindingTypes::SetupConst,
+                )];
+
+                transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+                // Unknown components are left for `resolveComponent`
+                assert!(!bindings_helper.components.contains_key(&FervidAtom::from("unknown-bar")));
+
+                let Some(ComponentBinding::Resolved(resolved)) =
+                    bindings_helper.components.get(&FervidAtom::from("async-foo"))
+                else {
+                    panic!("Component was not resolved")
+                };
+                to_str(&**resolved)
+            }};
+        }
+
+        assert_eq!(resolve!(fervid_core::TemplateGenerationMode::Inline), "AsyncFoo");
+        assert_eq!(
+            resolve!(fervid_core::TemplateGenerationMode::RenderFn),
+            "$setup.AsyncFoo"
+        );
+    }
+
+    #[test]
+    fn it_resolves_namespaced_components() {
+        // <template><Form.Input /></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "Form.Input".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = fervid_core::TemplateGenerationMode::Inline;
+        bindings_helper.setup_bindings = vec![SetupBinding(
+            FervidAtom::from("Form"),
+            fervid_core::BindingTypes::SetupConst,
+        )];
+
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Some(ComponentBinding::Resolved(resolved)) =
+            bindings_helper.components.get(&FervidAtom::from("Form.Input"))
+        else {
+            panic!("Component was not resolved")
+        };
+        assert_eq!(to_str(&**resolved), "Form.Input");
+    }
+
+    #[test]
+    fn it_transforms_component_v_model() {
+        // <MyComp v-model:title.trim="t" v-model:[key]="count" />
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "MyComp".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_model: vec![
+                            VModelDirective {
+                                argument: Some("title".into()),
+                                value: *js("t"),
+                                modifiers: vec!["trim".into()],
+                                update_handler: None,
+                                span: DUMMY_SP,
+                            },
+                            VModelDirective {
+                                argument: Some(StrOrExpr::Expr(js("key"))),
+                                value: *js("count"),
+                                modifiers: vec![],
+                                update_handler: None,
+                                span: DUMMY_SP,
+                            },
+                        ],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = fervid_core::TemplateGenerationMode::Inline;
+        bindings_helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("t"), fervid_core::BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("count"), fervid_core::BindingTypes::SetupLet),
+            SetupBinding(FervidAtom::from("key"), fervid_core::BindingTypes::SetupConst),
+        ];
+
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let directives = element
+            .starting_tag
+            .directives
+            .as_ref()
+            .expect("Directives must be present");
+
+        let title_model = &directives.v_model[0];
+        assert_eq!(to_str(&title_model.value), "t.value");
+        assert_eq!(
+            to_str(title_model.update_handler.as_ref().expect("Handler must be generated")),
+            "$event=>(t.value=$event)"
+        );
+
+        let dynamic_model = &directives.v_model[1];
+        assert_eq!(to_str(&dynamic_model.value), "_unref(count)");
+        assert_eq!(
+            to_str(dynamic_model.update_handler.as_ref().expect("Handler must be generated")),
+            "$event=>(count=$event)"
+        );
+        let Some(StrOrExpr::Expr(ref argument)) = dynamic_model.argument else {
+            panic!("Argument must stay dynamic")
+        };
+        assert_eq!(to_str(&**argument), "key");
+    }
+
+    #[test]
+    fn it_adds_v_model_patch_hints() {
+        // <input v-model="msg">
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "input".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_model: vec![VModelDirective {
+                            argument: None,
+                            value: *js("msg"),
+                            modifiers: vec![],
+                            update_handler: None,
+                            span: DUMMY_SP,
+                        }],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(
+            element.patch_hints.flags,
+            PatchFlags::NeedPatch | PatchFlags::Props
+        );
+        assert_eq!(
+            element.patch_hints.props,
+            vec![FervidAtom::from("onUpdate:modelValue")]
+        );
+    }
+
+    #[test]
+    fn it_skips_is_in_component_builtin_patch_hints() {
+        // <component :is="foo" :bar="baz"></component>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "component".into(),
+                    attributes: vec![
+                        AttributeOrBinding::VBind(VBindDirective {
+                            argument: Some("is".into()),
+                            value: js("foo"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: false,
+                            span: DUMMY_SP,
+                        }),
+                        AttributeOrBinding::VBind(VBindDirective {
+                            argument: Some("bar".into()),
+                            value: js("baz"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: false,
+                            span: DUMMY_SP,
+                        }),
+                    ],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::Props);
+        assert_eq!(element.patch_hints.props, vec![FervidAtom::from("bar")]);
+    }
+
+    #[test]
+    fn it_skips_patch_hints_in_v_once() {
+        // <div v-once><span :title="foo">{{ bar }}</span></div>
+        let mut span = span_node(vec![Node::Interpolation(Interpolation {
+            value: js("bar"),
+            template_scope: 0,
+            patch_flag: false,
+        })]);
+        let Node::Element(ref mut span_element) = span else {
+            unreachable!()
+        };
+        span_element
+            .starting_tag
+            .attributes
+            .push(AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                argument: Some("title".into()),
+                value: js("foo"),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: Default::default(),
+            }));
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_once: Some(()),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![span],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Node::Element(ref child) = root.children[0] else {
+            panic!("Child is not an element")
+        };
+        assert!(child.patch_hints.flags.is_empty());
+        assert!(child.patch_hints.props.is_empty());
+
+        // Bindings are still transformed
+        let Some(AttributeOrBinding::VBind(ref v_bind)) = child.starting_tag.attributes.first()
+        else {
+            panic!("Binding is missing")
+        };
+        assert_eq!(to_str(&*v_bind.value), "_ctx.foo");
+    }
+
+    #[test]
+    fn it_does_not_cache_handlers_in_v_once() {
+        // <div v-once><button @click="count++"></button></div>
+        let button = Node::Element(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "button".into(),
+                attributes: vec![AttributeOrBinding::VOn(VOnDirective {
+                    event: Some("click".into()),
+                    handler: Some(js("count++")),
+                    modifiers: vec![],
+                    is_cacheable: false,
+                    span: DUMMY_SP,
+                })],
+                directives: None,
+            },
+            children: vec![],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        });
+
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_once: Some(()),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![button],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref root) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let Node::Element(ref button) = root.children[0] else {
+            panic!("Child is not an element")
+        };
+        let Some(AttributeOrBinding::VOn(ref v_on)) = button.starting_tag.attributes.first() else {
+            panic!("Handler is missing")
+        };
+        assert!(!v_on.is_cacheable);
+    }
+
+    #[test]
+    fn it_adds_full_props_for_v_on_object() {
+        // <div :id="id" v-on="{ click: onClick }"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![
+                        AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                            argument: Some("id".into()),
+                            value: js("id"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: false,
+                            span: Default::default(),
+                        }),
+                        AttributeOrBinding::VOn(VOnDirective {
+                            event: None,
+                            handler: Some(js("{ click: onClick }")),
+                            modifiers: vec![],
+                            is_cacheable: false,
+                            span: Default::default(),
+                        }),
+                    ],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::FullProps);
+        assert!(element.patch_hints.props.is_empty());
+
+        // The object of handlers is not wrapped as an inline handler
+        let Some(AttributeOrBinding::VOn(ref v_on)) = element.starting_tag.attributes.get(1) else {
+            panic!("v-on is missing")
+        };
+        let handlers = v_on.handler.as_ref().expect("v-on should have handlers");
+        assert_eq!(to_str(&**handlers), "{click:_ctx.onClick}");
+    }
+
+    #[test]
+    fn it_adds_full_props_for_dynamic_events() {
+        // <div :id="id" @[event]="onEvent"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![
+                        AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                            argument: Some("id".into()),
+                            value: js("id"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: false,
+                            span: Default::default(),
+                        }),
+                        AttributeOrBinding::VOn(VOnDirective {
+                            event: Some(StrOrExpr::Expr(js("event"))),
+                            handler: Some(js("onEvent")),
+                            modifiers: vec![],
+                            is_cacheable: false,
+                            span: Default::default(),
+                        }),
+                    ],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::FullProps);
+        assert!(element.patch_hints.props.is_empty());
+
+        let Some(AttributeOrBinding::VOn(ref v_on)) = element.starting_tag.attributes.get(1) else {
+            panic!("v-on is missing")
+        };
+        let Some(StrOrExpr::Expr(ref event)) = v_on.event else {
+            panic!("Event is not dynamic")
+        };
+        assert_eq!(to_str(&**event), "_ctx.event");
+        assert!(v_on.is_cacheable);
+    }
+
+    #[test]
+    fn it_adds_dynamic_slots_for_dynamic_slot_names() {
+        // <my-comp><template #[name]>hi</template></my-comp>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "my-comp".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "template".into(),
+                        attributes: vec![],
+                        directives: Some(Box::new(VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some(StrOrExpr::Expr(js("name"))),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })),
+                    },
+                    children: vec![Node::Text("hi".into(), DUMMY_SP)],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref component) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(component.patch_hints.flags, PatchFlags::DynamicSlots);
+
+        // Slot name is transformed
+        let Some(Node::Element(ref template)) = component.children.first() else {
+            panic!("Template is missing")
+        };
+        let Some(VSlotDirective {
+            slot_name: Some(StrOrExpr::Expr(ref slot_name)),
+            ..
+        }) = template.starting_tag.directives.as_ref().unwrap().v_slot
+        else {
+            panic!("Slot name is not dynamic")
+        };
+        assert_eq!(to_str(&**slot_name), "_ctx.name");
+    }
+
+    #[test]
+    fn it_adds_modified_v_bind_props() {
+        // <div :inner-html.camel.prop="html" :class.attr="cls"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![
+                        AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                            argument: Some("inner-html".into()),
+                            value: js("html"),
+                            is_camel: true,
+                            is_prop: true,
+                            is_attr: false,
+                            span: Default::default(),
+                        }),
+                        AttributeOrBinding::VBind(fervid_core::VBindDirective {
+                            argument: Some("class".into()),
+                            value: js("cls"),
+                            is_camel: false,
+                            is_prop: false,
+                            is_attr: true,
+                            span: Default::default(),
+                        }),
+                    ],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::Props);
+        assert_eq!(
+            element.patch_hints.props,
+            vec![FervidAtom::from(".innerHtml"), FervidAtom::from("^class")]
+        );
+    }
+
+    #[test]
+    fn it_transforms_template_refs() {
+        // <div v-for="item in items" ref="el"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![AttributeOrBinding::RegularAttribute {
+                        name: "ref".into(),
+                        value: "el".into(),
+                        span: Default::default(),
+                    }],
+                    directives: Some(Box::new(VueDirectives {
+                        v_for: Some(fervid_core::VForDirective {
+                            iterable: js("items"),
+                            itervar: js("item"),
+                            patch_flags: Default::default(),
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        bindings_helper.setup_bindings =
+            vec![SetupBinding(FervidAtom::from("el"), BindingTypes::SetupRef)];
+
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::NeedPatch);
+
+        // `ref_for: true, ref_key: "el", ref: el`
+        let attributes = &element.starting_tag.attributes;
+        assert_eq!(attributes.len(), 3);
+        let AttributeOrBinding::VBind(ref ref_for) = attributes[0] else {
+            panic!("ref_for is missing")
+        };
+        assert!(check_attribute_name(&attributes[0], "ref_for"));
+        assert_eq!(to_str(&*ref_for.value), "true");
+        assert!(matches!(
+            attributes[1],
+            AttributeOrBinding::RegularAttribute { ref name, ref value, .. }
+                if name == "ref_key" && value == "el"
+        ));
+        let AttributeOrBinding::VBind(ref ref_binding) = attributes[2] else {
+            panic!("ref is not bound")
+        };
+        assert_eq!(to_str(&*ref_binding.value), "el");
+
+        // `let el` is assigned by a function ref
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![AttributeOrBinding::RegularAttribute {
+                        name: "ref".into(),
+                        value: "el".into(),
+                        span: Default::default(),
+                    }],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        bindings_helper.setup_bindings =
+            vec![SetupBinding(FervidAtom::from("el"), BindingTypes::SetupLet)];
+
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        let attributes = &element.starting_tag.attributes;
+        assert_eq!(attributes.len(), 2);
+        let AttributeOrBinding::VBind(ref ref_binding) = attributes[1] else {
+            panic!("ref is not bound")
+        };
+        assert_eq!(to_str(&*ref_binding.value), "_value=>el=_value");
+    }
+
+    #[test]
+    fn it_transforms_function_refs() {
+        // <div :ref="el => refs.push(el)"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str("ref".into())),
+                        value: js("el => refs.push(el)"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: DUMMY_SP,
+                    })],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
+        bindings_helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("refs"), BindingTypes::SetupConst),
+            SetupBinding(FervidAtom::from("el"), BindingTypes::SetupRef),
+        ];
+
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::NeedPatch);
+        assert!(element.patch_hints.props.is_empty());
+
+        // Function ref is passed as-is, without `ref_key`
+        let attributes = &element.starting_tag.attributes;
+        assert_eq!(attributes.len(), 1);
+        let AttributeOrBinding::VBind(ref ref_binding) = attributes[0] else {
+            panic!("ref is not bound")
+        };
+        assert_eq!(to_str(&*ref_binding.value), "el=>refs.push(el)");
+    }
+
+    #[test]
+    fn it_transforms_custom_directives() {
+        // <div v-my-dir:[arg].mod="value"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        custom: vec![fervid_core::VCustomDirective {
+                            name: "my-dir".into(),
+                            argument: Some(StrOrExpr::Expr(js("arg"))),
+                            modifiers: vec!["mod".into()],
+                            value: Some(js("value")),
+                        }],
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        let Node::Element(ref element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(element.patch_hints.flags, PatchFlags::NeedPatch);
+
+        let custom_directive = &element.starting_tag.directives.as_ref().unwrap().custom[0];
+        assert_eq!(
+            to_str(&**custom_directive.value.as_ref().unwrap()),
+            "_ctx.value"
+        );
+        let Some(StrOrExpr::Expr(ref argument)) = custom_directive.argument else {
+            panic!("Argument is not dynamic")
+        };
+        assert_eq!(to_str(&**argument), "_ctx.arg");
+    }
+
+    #[test]
+    fn it_warns_about_v_if_with_v_for() {
+        // <div v-if="item.visible" v-for="item in items"></div>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_if: Some(js("item.visible")),
+                        v_for: Some(fervid_core::VForDirective {
+                            iterable: js("items"),
+                            itervar: js("item"),
+                            patch_flags: Default::default(),
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        assert_eq!(
+            bindings_helper.diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::VIfWithVFor,
+                span: DUMMY_SP,
+            }]
+        );
+
+        // `v-if` is evaluated first, thus `item` is not the `v-for` variable
+        let Node::ConditionalSeq(ref seq) = sfc_template.roots[0] else {
+            panic!("Root is not a conditional sequence")
+        };
+        assert_eq!(to_str(&seq.if_node.condition), "_ctx.item.visible");
+    }
+
+    #[test]
+    fn it_transforms_v_html_and_v_text() {
+        // <div v-html="html">hi</div><span v-text="msg"> </span>
+        let element = |directives: VueDirectives, text: &str| {
+            Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "div".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(directives)),
+                },
+                children: vec![Node::Text(text.into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![
+                element(
+                    VueDirectives {
+                        v_html: Some(js("html")),
+                        ..Default::default()
+                    },
+                    "hi",
+                ),
+                element(
+                    VueDirectives {
+                        v_text: Some(js("msg")),
+                        ..Default::default()
+                    },
+                    " ",
+                ),
+            ],
+            span: DUMMY_SP,
+        };
+
+        let mut bindings_helper = BindingsHelper::default();
+        transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+        // Only the meaningful children are reported
+        assert_eq!(
+            bindings_helper.diagnostics,
+            vec![Diagnostic {
+                kind: DiagnosticKind::VHtmlWithChildren,
+                span: DUMMY_SP,
+            }]
+        );
+
+        let Node::Element(ref v_html_element) = sfc_template.roots[0] else {
+            panic!("Root is not an element")
+        };
+        assert!(v_html_element.children.is_empty());
+        assert_eq!(v_html_element.patch_hints.flags, PatchFlags::Props);
+        assert_eq!(
+            v_html_element.patch_hints.props,
+            vec![FervidAtom::from("innerHTML")]
+        );
+
+        let Node::Element(ref v_text_element) = sfc_template.roots[1] else {
+            panic!("Root is not an element")
+        };
+        assert!(v_text_element.children.is_empty());
+        assert_eq!(v_text_element.patch_hints.flags, PatchFlags::Props);
+        assert_eq!(
+            v_text_element.patch_hints.props,
+            vec![FervidAtom::from("textContent")]
+        );
+    }
+
+    #[test]
+    fn it_reports_v_slot_misuse() {
+        fn span(lo: u32) -> Span {
+            Span::new(BytePos(lo), BytePos(lo + 1), SyntaxContext::empty())
+        }
+
+        fn element(
+            tag_name: &str,
+            v_slot: Option<&str>,
+            children: Vec<Node>,
+            lo: u32,
+        ) -> ElementNode {
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: v_slot.map(|slot_name| {
+                        Box::new(VueDirectives {
+                            v_slot: Some(VSlotDirective {
+                                slot_name: Some(slot_name.into()),
+                                value: None,
+                            }),
+                            ..Default::default()
+                        })
+                    }),
+                },
+                children,
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: span(lo),
+            }
+        }
+
+        fn diagnostics_of(root: ElementNode) -> Vec<Diagnostic> {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(root)],
+                span: DUMMY_SP,
+            };
+            let mut bindings_helper = BindingsHelper::default();
+            transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+            bindings_helper.diagnostics
+        }
+
+        // <div v-slot:foo><template v-slot:bar></template></div>
+        assert_eq!(
+            diagnostics_of(element(
+                "div",
+                Some("foo"),
+                vec![Node::Element(element("template", Some("bar"), vec![], 2))],
+                1
+            )),
+            vec![
+                Diagnostic {
+                    kind: DiagnosticKind::VSlotMisplaced,
+                    span: span(1)
+                },
+                Diagnostic {
+                    kind: DiagnosticKind::VSlotMisplaced,
+                    span: span(2)
+                }
+            ]
+        );
+
+        // <my-comp v-slot:default><template v-slot:foo></template></my-comp>
+        assert_eq!(
+            diagnostics_of(element(
+                "my-comp",
+                Some("default"),
+                vec![Node::Element(element("template", Some("foo"), vec![], 2))],
+                1
+            )),
+            vec![Diagnostic {
+                kind: DiagnosticKind::VSlotMixedUsage,
+                span: span(2)
+            }]
+        );
+
+        // <my-comp>
+        //   <template v-slot:foo></template>
+        //   <template v-slot:foo></template>
+        //   <template v-slot:default></template>
+        //   hello
+        // </my-comp>
+        assert_eq!(
+            diagnostics_of(element(
+                "my-comp",
+                None,
+                vec![
+                    Node::Element(element("template", Some("foo"), vec![], 2)),
+                    Node::Element(element("template", Some("foo"), vec![], 3)),
+                    Node::Element(element("template", Some("default"), vec![], 4)),
+                    Node::Text("hello".into(), DUMMY_SP),
+                ],
+                1
+            )),
+            vec![
+                Diagnostic {
+                    kind: DiagnosticKind::VSlotDuplicateName("foo".into()),
+                    span: span(3)
+                },
+                Diagnostic {
+                    kind: DiagnosticKind::VSlotDuplicateName("default".into()),
+                    span: span(4)
+                }
+            ]
+        );
+
+        // <my-comp><template v-slot:foo></template><template v-slot:bar></template></my-comp>
+        assert!(diagnostics_of(element(
+            "my-comp",
+            None,
+            vec![
+                Node::Element(element("template", Some("foo"), vec![], 2)),
+                Node::Element(element("template", Some("bar"), vec![], 3)),
+            ],
+            1
+        ))
+        .is_empty());
+    }
+
+    fn span_node(children: Vec<Node>) -> Node {
+        Node::Element(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "span".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
     // text
     fn text_node() -> Node {
         Node::Text("text".into(), DUMMY_SP)
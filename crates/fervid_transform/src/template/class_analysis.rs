@@ -0,0 +1,221 @@
+//! Analysis of the class names rendered by the template,
+//! used to find the `<style scoped>` selectors which can only match the child component roots.
+
+use fervid_core::{AttributeOrBinding, ElementNode, FervidAtom, Node, StrOrExpr};
+use swc_core::ecma::ast::{BinaryOp, Expr, Lit, Prop, PropName, PropOrSpread};
+
+/// Collects the class names which the template may render, both from `class` and `:class`.
+///
+/// Returns `None` when the class names cannot be known statically,
+/// e.g. `:class="classes"` or `v-bind="attrs"`, because then any class may be rendered.
+pub fn collect_template_classes(nodes: &[Node]) -> Option<Vec<FervidAtom>> {
+    let mut classes = Vec::new();
+
+    for node in nodes {
+        if !collect_node_classes(node, &mut classes) {
+            return None;
+        }
+    }
+
+    Some(classes)
+}
+
+/// Returns `false` when the class names of the node are not statically known
+fn collect_node_classes(node: &Node, out: &mut Vec<FervidAtom>) -> bool {
+    match node {
+        Node::Element(element_node) => collect_element_classes(element_node, out),
+        Node::ConditionalSeq(conditional_seq) => {
+            collect_element_classes(&conditional_seq.if_node.node, out)
+                && conditional_seq
+                    .else_if_nodes
+                    .iter()
+                    .all(|else_if| collect_element_classes(&else_if.node, out))
+                && conditional_seq
+                    .else_node
+                    .as_ref()
+                    .map_or(true, |else_node| collect_element_classes(else_node, out))
+        }
+        Node::Text(_, _) | Node::Comment(_, _) | Node::Interpolation(_) => true,
+    }
+}
+
+fn collect_element_classes(element_node: &ElementNode, out: &mut Vec<FervidAtom>) -> bool {
+    for attr in element_node.starting_tag.attributes.iter() {
+        let is_known = match attr {
+            AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                if name == "class" {
+                    add_classes(value, out);
+                }
+                true
+            }
+            AttributeOrBinding::VBind(v_bind) => match v_bind.argument {
+                Some(StrOrExpr::Str(ref name)) if name != "class" => true,
+                Some(StrOrExpr::Str(_)) => collect_expr_classes(&v_bind.value, out),
+                // `:[name]="value"` and `v-bind="attrs"` may bind `class` as well
+                Some(StrOrExpr::Expr(_)) | None => false,
+            },
+            AttributeOrBinding::VOn(_) => true,
+        };
+
+        if !is_known {
+            return false;
+        }
+    }
+
+    element_node
+        .children
+        .iter()
+        .all(|child| collect_node_classes(child, out))
+}
+
+/// Collects the class names from a `:class` value, which is a string, an array or an object.
+/// Returns `false` when a class name is only known at runtime
+fn collect_expr_classes(expr: &Expr, out: &mut Vec<FervidAtom>) -> bool {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => {
+            add_classes(&str.value, out);
+            true
+        }
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            for quasi in tpl.quasis.iter() {
+                add_classes(&quasi.raw, out);
+            }
+            true
+        }
+        Expr::Array(array_lit) => array_lit
+            .elems
+            .iter()
+            .flatten()
+            .all(|elem| elem.spread.is_none() && collect_expr_classes(&elem.expr, out)),
+        // `{ active: isActive, 'text-danger': hasError }`, the values are conditions
+        Expr::Object(object_lit) => object_lit.props.iter().all(|prop| {
+            let PropOrSpread::Prop(prop) = prop else {
+                return false;
+            };
+
+            match prop.as_ref() {
+                Prop::KeyValue(key_value) => match key_value.key {
+                    PropName::Ident(ref ident) => add_classes(&ident.sym, out),
+                    PropName::Str(ref str) => add_classes(&str.value, out),
+                    _ => return false,
+                },
+                Prop::Shorthand(ident) => add_classes(&ident.sym, out),
+                _ => return false,
+            }
+
+            true
+        }),
+        // `isActive ? 'active' : ''`
+        Expr::Cond(cond_expr) => {
+            collect_expr_classes(&cond_expr.cons, out) && collect_expr_classes(&cond_expr.alt, out)
+        }
+        // `isActive && 'active'`
+        Expr::Bin(bin_expr) if bin_expr.op == BinaryOp::LogicalAnd => {
+            collect_expr_classes(&bin_expr.right, out)
+        }
+        Expr::Paren(paren_expr) => collect_expr_classes(&paren_expr.expr, out),
+        _ => false,
+    }
+}
+
+fn add_classes(value: &str, out: &mut Vec<FervidAtom>) {
+    for class_name in value.split_ascii_whitespace() {
+        if !out.iter().any(|known| known == class_name) {
+            out.push(FervidAtom::from(class_name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{AttributeSpan, ElementKind, StartingTag, VBindDirective};
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+    use crate::test_utils::parser::parse_javascript_expr;
+
+    fn element(attributes: Vec<AttributeOrBinding>, children: Vec<Node>) -> Node {
+        Node::Element(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes,
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        })
+    }
+
+    fn class(value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::RegularAttribute {
+            name: "class".into(),
+            value: value.into(),
+            span: AttributeSpan::default(),
+        }
+    }
+
+    fn v_bind(argument: Option<&str>, value: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: argument.map(StrOrExpr::from),
+            value: parse_javascript_expr(value, 0, Default::default())
+                .unwrap()
+                .0,
+            is_camel: false,
+            is_prop: false,
+            is_attr: false,
+            span: AttributeSpan::default(),
+        })
+    }
+
+    #[test]
+    fn it_collects_static_and_bound_classes() {
+        // <div class="foo bar">
+        //   <div :class="{ active: isActive, 'text-red': hasError }" :title="foo" />
+        //   <div :class="[isBig ? 'big' : 'small', isBold && 'bold']" class="foo" />
+        // </div>
+        let nodes = vec![element(
+            vec![class("foo bar")],
+            vec![
+                element(
+                    vec![
+                        v_bind(Some("class"), "{ active: isActive, 'text-red': hasError }"),
+                        v_bind(Some("title"), "foo"),
+                    ],
+                    vec![],
+                ),
+                element(
+                    vec![
+                        v_bind(Some("class"), "[isBig ? 'big' : 'small', isBold && 'bold']"),
+                        class("foo"),
+                    ],
+                    vec![],
+                ),
+            ],
+        )];
+
+        let classes = collect_template_classes(&nodes).expect("Classes should be known");
+        let expected: Vec<FervidAtom> =
+            ["foo", "bar", "active", "text-red", "big", "small", "bold"]
+                .into_iter()
+                .map(FervidAtom::from)
+                .collect();
+        assert_eq!(classes, expected);
+    }
+
+    #[test]
+    fn it_bails_on_runtime_classes() {
+        // <div :class="classes" />
+        let nodes = vec![element(vec![v_bind(Some("class"), "classes")], vec![])];
+        assert!(collect_template_classes(&nodes).is_none());
+
+        // <div class="foo"><div v-bind="attrs" /></div>
+        let nodes = vec![element(
+            vec![class("foo")],
+            vec![element(vec![v_bind(None, "attrs")], vec![])],
+        )];
+        assert!(collect_template_classes(&nodes).is_none());
+    }
+}
@@ -1,7 +1,12 @@
 use fervid_core::TemplateScope;
-use swc_core::ecma::{ast::Ident, visit::{Visit, VisitWith}};
+use swc_core::ecma::{
+    ast::{AssignExpr, AssignPat, AssignPatProp, Expr, Ident, KeyValuePatProp, Prop, TsTypeAnn},
+    visit::{Visit, VisitWith},
+};
 
-/// Polyfill for variable collection before the 
+/// Collects the variables introduced by a `v-for` alias or a `v-slot` value into the scope.
+/// Both patterns (`v-slot="{ item }"`) and expressions in place of patterns
+/// (`v-for="({ id, name: alias = 'x' }, index) in items"`) are supported
 pub fn collect_variables(root: &impl VisitWith<IdentifierVisitor>, scope: &mut TemplateScope) {
     let mut visitor = IdentifierVisitor { collected: vec![] };
 
@@ -13,36 +18,94 @@ pub fn collect_variables(root: &impl VisitWith<IdentifierVisitor>, scope: &mut T
     }
 }
 
+/// Only visits the binding positions, i.e. default values,
+/// property keys and type annotations are skipped
 pub struct IdentifierVisitor {
     collected: Vec<Ident>,
 }
 
 impl Visit for IdentifierVisitor {
-    fn visit_ident(&mut self, n: &swc_core::ecma::ast::Ident) {
+    fn visit_ident(&mut self, n: &Ident) {
         self.collected.push(n.to_owned());
     }
 
-    fn visit_object_lit(&mut self, n: &swc_core::ecma::ast::ObjectLit) {
-        self.collected.reserve(n.props.len());
+    // Only the expressions which can be in place of a pattern introduce variables
+    fn visit_expr(&mut self, n: &Expr) {
+        match n {
+            Expr::Ident(_)
+            | Expr::Paren(_)
+            | Expr::Seq(_)
+            | Expr::Array(_)
+            | Expr::Object(_)
+            | Expr::Assign(_) => n.visit_children_with(self),
+            _ => {}
+        }
+    }
 
-        for prop in n.props.iter() {
-            let swc_core::ecma::ast::PropOrSpread::Prop(prop) = prop else {
-                continue;
-            };
+    // `a` in `a = 1`
+    fn visit_assign_expr(&mut self, n: &AssignExpr) {
+        n.left.visit_with(self);
+    }
 
+    fn visit_prop(&mut self, n: &Prop) {
+        match n {
             // This is shorthand `a` in `{ a }`
-            let shorthand = prop.as_shorthand();
-            if let Some(ident) = shorthand {
-                self.collected.push(ident.to_owned());
-                continue;
-            }
-
-            // This is key-value `a: b` in `{ a: b }`
-            let Some(keyvalue) = prop.as_key_value() else { continue };
-
-            // We only support renaming things (therefore value must be an identifier)
-            let Some(value) = keyvalue.value.as_ident() else { continue };
-            self.collected.push(value.to_owned());
+            Prop::Shorthand(ident) => self.visit_ident(ident),
+            // This is key-value `a: b` in `{ a: b }`, where `b` may also be a nested pattern
+            Prop::KeyValue(key_value) => key_value.value.visit_with(self),
+            // This is `a` in `{ a = 1 }`
+            Prop::Assign(assign) => self.visit_ident(&assign.key),
+            _ => {}
         }
     }
+
+    // `a` in `a = 1`
+    fn visit_assign_pat(&mut self, n: &AssignPat) {
+        n.left.visit_with(self);
+    }
+
+    // `a` in `{ a = 1 }`
+    fn visit_assign_pat_prop(&mut self, n: &AssignPatProp) {
+        n.key.visit_with(self);
+    }
+
+    // `b` in `{ a: b }`
+    fn visit_key_value_pat_prop(&mut self, n: &KeyValuePatProp) {
+        n.value.visit_with(self);
+    }
+
+    fn visit_ts_type_ann(&mut self, _n: &TsTypeAnn) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::SmallVec;
+
+    use super::*;
+    use crate::test_utils::parser::parse_javascript_expr;
+
+    fn collect(raw: &str) -> Vec<String> {
+        let expr = parse_javascript_expr(raw, 0, Default::default()).unwrap().0;
+        let mut scope = TemplateScope {
+            variables: SmallVec::new(),
+            parent: 0,
+        };
+        collect_variables(&expr, &mut scope);
+        scope.variables.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn it_collects_destructured_variables() {
+        assert_eq!(collect("item"), vec!["item"]);
+        assert_eq!(collect("(item, index)"), vec!["item", "index"]);
+        assert_eq!(
+            collect("({ id, name: alias = defaultName }, index)"),
+            vec!["id", "alias", "index"]
+        );
+        assert_eq!(
+            collect("({ a: [b, , { c }], ...rest }, [first = other, ...others])"),
+            vec!["b", "c", "rest", "first", "others"]
+        );
+        assert_eq!(collect("({ [key]: value })"), vec!["value"]);
+    }
 }
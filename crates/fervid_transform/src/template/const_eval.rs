@@ -0,0 +1,282 @@
+//! A tiny evaluator for constant template expressions.
+//!
+//! It is used to fold expressions like `:width="2 * 8"` into literals,
+//! so that the generated code contains `width: 16` instead of `width: 2 * 8`.
+//! Only literals, arithmetic, string concatenation, comparisons,
+//! logical operators and ternaries over them are supported.
+
+use std::cmp::Ordering;
+
+use fervid_core::FervidAtom;
+use swc_core::{
+    common::{Span, Spanned},
+    ecma::ast::{BinaryOp, Bool, Expr, Lit, Null, Number, Str, Tpl, UnaryOp},
+};
+
+/// Result of evaluating a constant expression
+#[derive(Debug, PartialEq)]
+enum ConstValue {
+    Bool(bool),
+    Null,
+    Num(f64),
+    Str(String),
+}
+
+/// Tries to fold the expression into a single literal.
+/// Returns `true` when the expression was replaced.
+///
+/// ## Example
+/// `2 * 8` becomes `16` and `'foo' + 1` becomes `'foo1'`,
+/// while `foo + 1` is left as-is.
+pub fn fold_const_expr(expr: &mut Expr) -> bool {
+    // Literals do not need folding
+    if let Expr::Lit(_) = expr {
+        return false;
+    }
+
+    let Some(value) = evaluate(expr) else {
+        return false;
+    };
+
+    *expr = value.into_expr(expr.span());
+    true
+}
+
+fn evaluate(expr: &Expr) -> Option<ConstValue> {
+    match expr {
+        Expr::Lit(Lit::Bool(b)) => Some(ConstValue::Bool(b.value)),
+        Expr::Lit(Lit::Null(_)) => Some(ConstValue::Null),
+        Expr::Lit(Lit::Num(n)) => Some(ConstValue::Num(n.value)),
+        Expr::Lit(Lit::Str(s)) => Some(ConstValue::Str(s.value.to_string())),
+
+        Expr::Paren(paren_expr) => evaluate(&paren_expr.expr),
+
+        Expr::Tpl(tpl) => evaluate_tpl(tpl),
+
+        Expr::Unary(unary_expr) => {
+            let arg = evaluate(&unary_expr.arg)?;
+            match unary_expr.op {
+                UnaryOp::Bang => Some(ConstValue::Bool(!arg.is_truthy())),
+                UnaryOp::Minus => Some(ConstValue::Num(-arg.as_num()?)),
+                UnaryOp::Plus => Some(ConstValue::Num(arg.as_num()?)),
+                _ => None,
+            }
+        }
+
+        Expr::Cond(cond_expr) => {
+            if evaluate(&cond_expr.test)?.is_truthy() {
+                evaluate(&cond_expr.cons)
+            } else {
+                evaluate(&cond_expr.alt)
+            }
+        }
+
+        Expr::Bin(bin_expr) => {
+            let left = evaluate(&bin_expr.left)?;
+
+            // Short-circuiting operators
+            match bin_expr.op {
+                BinaryOp::LogicalAnd if !left.is_truthy() => return Some(left),
+                BinaryOp::LogicalAnd => return evaluate(&bin_expr.right),
+                BinaryOp::LogicalOr if left.is_truthy() => return Some(left),
+                BinaryOp::LogicalOr => return evaluate(&bin_expr.right),
+                BinaryOp::NullishCoalescing if left != ConstValue::Null => return Some(left),
+                BinaryOp::NullishCoalescing => return evaluate(&bin_expr.right),
+                _ => {}
+            }
+
+            let right = evaluate(&bin_expr.right)?;
+            evaluate_binary(bin_expr.op, left, right)
+        }
+
+        _ => None,
+    }
+}
+
+fn evaluate_binary(op: BinaryOp, left: ConstValue, right: ConstValue) -> Option<ConstValue> {
+    macro_rules! arithmetic {
+        ($op: tt) => {
+            ConstValue::Num(left.as_num()? $op right.as_num()?)
+        };
+    }
+
+    let result = match op {
+        // `+` either concatenates strings or adds numbers
+        BinaryOp::Add => {
+            let is_concat =
+                matches!(left, ConstValue::Str(_)) || matches!(right, ConstValue::Str(_));
+
+            if is_concat {
+                let mut result = left.into_string()?;
+                result.push_str(&right.into_string()?);
+                ConstValue::Str(result)
+            } else {
+                arithmetic!(+)
+            }
+        }
+        BinaryOp::Sub => arithmetic!(-),
+        BinaryOp::Mul => arithmetic!(*),
+        BinaryOp::Div => arithmetic!(/),
+        BinaryOp::Mod => arithmetic!(%),
+        BinaryOp::Exp => ConstValue::Num(left.as_num()?.powf(right.as_num()?)),
+
+        // Equality is only supported for the same types, `==` and `===` are the same here
+        BinaryOp::EqEq | BinaryOp::EqEqEq => ConstValue::Bool(left.strict_eq(&right)?),
+        BinaryOp::NotEq | BinaryOp::NotEqEq => ConstValue::Bool(!left.strict_eq(&right)?),
+
+        BinaryOp::Lt => ConstValue::Bool(left.compare(&right)? == Ordering::Less),
+        BinaryOp::LtEq => ConstValue::Bool(left.compare(&right)? != Ordering::Greater),
+        BinaryOp::Gt => ConstValue::Bool(left.compare(&right)? == Ordering::Greater),
+        BinaryOp::GtEq => ConstValue::Bool(left.compare(&right)? != Ordering::Less),
+
+        _ => return None,
+    };
+
+    // `Infinity` and `NaN` cannot be represented as literals
+    if let ConstValue::Num(n) = result {
+        if !n.is_finite() {
+            return None;
+        }
+    }
+
+    Some(result)
+}
+
+/// Evaluates `` `foo ${1 + 2}` `` into `'foo 3'`
+fn evaluate_tpl(tpl: &Tpl) -> Option<ConstValue> {
+    let mut result = String::new();
+
+    for (idx, quasi) in tpl.quasis.iter().enumerate() {
+        result.push_str(quasi.cooked.as_ref()?);
+
+        if let Some(expr) = tpl.exprs.get(idx) {
+            result.push_str(&evaluate(expr)?.into_string()?);
+        }
+    }
+
+    Some(ConstValue::Str(result))
+}
+
+impl ConstValue {
+    fn is_truthy(&self) -> bool {
+        match self {
+            ConstValue::Bool(b) => *b,
+            ConstValue::Null => false,
+            ConstValue::Num(n) => *n != 0.0 && !n.is_nan(),
+            ConstValue::Str(s) => !s.is_empty(),
+        }
+    }
+
+    /// Numeric coercion is only supported for numbers.
+    /// Coercing strings and booleans is valid Js, but almost certainly a mistake in a template.
+    fn as_num(&self) -> Option<f64> {
+        match self {
+            ConstValue::Num(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// String coercion. Non-integer numbers are not supported,
+    /// because Js number formatting is different from Rust's.
+    fn into_string(self) -> Option<String> {
+        match self {
+            ConstValue::Bool(b) => Some(b.to_string()),
+            ConstValue::Null => Some("null".to_string()),
+            ConstValue::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                Some((n as i64).to_string())
+            }
+            ConstValue::Num(_) => None,
+            ConstValue::Str(s) => Some(s),
+        }
+    }
+
+    fn strict_eq(&self, other: &ConstValue) -> Option<bool> {
+        match (self, other) {
+            (ConstValue::Bool(a), ConstValue::Bool(b)) => Some(a == b),
+            (ConstValue::Null, ConstValue::Null) => Some(true),
+            (ConstValue::Num(a), ConstValue::Num(b)) => Some(a == b),
+            (ConstValue::Str(a), ConstValue::Str(b)) => Some(a == b),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, other: &ConstValue) -> Option<Ordering> {
+        match (self, other) {
+            (ConstValue::Num(a), ConstValue::Num(b)) => a.partial_cmp(b),
+            // Js compares strings by UTF-16 code units
+            (ConstValue::Str(a), ConstValue::Str(b)) => {
+                Some(a.encode_utf16().cmp(b.encode_utf16()))
+            }
+            _ => None,
+        }
+    }
+
+    fn into_expr(self, span: Span) -> Expr {
+        Expr::Lit(match self {
+            ConstValue::Bool(value) => Lit::Bool(Bool { span, value }),
+            ConstValue::Null => Lit::Null(Null { span }),
+            ConstValue::Num(value) => Lit::Num(Number {
+                span,
+                value,
+                raw: None,
+            }),
+            ConstValue::Str(value) => Lit::Str(Str {
+                span,
+                value: FervidAtom::from(value),
+                raw: None,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fold_const_expr;
+    use crate::test_utils::{parser::parse_javascript_expr, to_str};
+
+    fn fold(raw: &str) -> Option<String> {
+        let mut expr = parse_javascript_expr(raw, 0, Default::default())
+            .expect("fold expects the input to be parseable")
+            .0;
+
+        if fold_const_expr(&mut expr) {
+            Some(to_str(&*expr))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn it_folds_arithmetic() {
+        assert_eq!(fold("2 * 8"), Some("16".to_string()));
+        assert_eq!(fold("(1 + 2) * 3 - 4 / 2"), Some("7".to_string()));
+        assert_eq!(fold("10 % 4"), Some("2".to_string()));
+        assert_eq!(fold("2 ** 10"), Some("1024".to_string()));
+        assert_eq!(fold("-(3 - 5)"), Some("2".to_string()));
+    }
+
+    #[test]
+    fn it_folds_strings() {
+        assert_eq!(fold("'foo' + 'bar'"), Some(r#""foobar""#.to_string()));
+        assert_eq!(fold("'width: ' + 2 * 8"), Some(r#""width: 16""#.to_string()));
+        assert_eq!(fold("`${1 + 1}px`"), Some(r#""2px""#.to_string()));
+    }
+
+    #[test]
+    fn it_folds_logic_and_ternaries() {
+        assert_eq!(fold("1 > 2 ? 'yes' : 'no'"), Some(r#""no""#.to_string()));
+        assert_eq!(fold("!0 && 'foo'"), Some(r#""foo""#.to_string()));
+        assert_eq!(fold("null ?? 42"), Some("42".to_string()));
+        assert_eq!(fold("'a' === 'a'"), Some("true".to_string()));
+    }
+
+    #[test]
+    fn it_leaves_non_constant_expressions() {
+        assert_eq!(fold("42"), None);
+        assert_eq!(fold("foo * 2"), None);
+        assert_eq!(fold("1 / 0"), None);
+        assert_eq!(fold("'foo' + 0.1"), None);
+        assert_eq!(fold("true + 1"), None);
+        assert_eq!(fold("`${foo}px`"), None);
+    }
+}
@@ -1,17 +1,23 @@
-use fervid_core::{BindingTypes, BindingsHelper, FervidAtom, TemplateGenerationMode, VueImports};
+use fervid_core::{
+    BindingTypes, BindingsHelper, FervidAtom, TemplateGenerationMode, TemplateScope, VueImports,
+};
 use swc_core::{
-    common::{Span, DUMMY_SP},
+    common::{Span, Spanned, DUMMY_SP},
     ecma::{
         ast::{
-            CallExpr, Callee, Expr, ExprOrSpread, Ident, KeyValueProp, MemberExpr, MemberProp,
-            PatOrExpr, Prop, PropName, PropOrSpread,
+            ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, ComputedPropName, CondExpr,
+            Expr, ExprOrSpread, Ident, Invalid, KeyValueProp, Lit, MemberExpr, MemberProp,
+            ParenExpr, Pat, PatOrExpr, Prop, PropName, PropOrSpread, Str, UpdateExpr,
         },
         atoms::JsWord,
         visit::{VisitMut, VisitMutWith},
     },
 };
 
-use crate::template::js_builtins::JS_BUILTINS;
+use crate::{
+    atoms::EMIT,
+    template::{collect_vars::collect_variables, js_builtins::JS_BUILTINS},
+};
 
 struct TransformVisitor<'s> {
     current_scope: u32,
@@ -19,10 +25,20 @@ struct TransformVisitor<'s> {
     has_js_bindings: bool,
     is_inline: bool,
     is_write: bool,
+    /// `v-on` handlers have `$event` as the event argument
+    is_handler: bool,
+    /// Parameters of the arrow functions being visited, e.g. `el` in `el => refs.push(el)`
+    local_vars: Vec<FervidAtom>,
+    /// Whether `v-for` or `v-slot` variables were referenced
+    uses_template_scope: bool,
 }
 
 pub trait BindingsHelperTransform {
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool;
+    /// Same as [`BindingsHelperTransform::transform_expr`], but for the `v-on` handlers,
+    /// where `$event` refers to the event argument.
+    /// Returns `true` when the handler uses `v-for` or `v-slot` variables and thus cannot be cached.
+    fn transform_handler_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool;
     fn get_var_binding_type(&mut self, starting_scope: u32, variable: &str) -> BindingTypes;
 }
 
@@ -30,20 +46,14 @@ impl BindingsHelperTransform for BindingsHelper {
     // TODO This function needs to be invoked when an AST is being optimized
     // TODO Support transformation modes (e.g. `inline`, `renderFn`)
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool {
-        let is_inline = matches!(
-            self.template_generation_mode,
-            TemplateGenerationMode::Inline
-        );
-        let mut visitor = TransformVisitor {
-            current_scope: scope_to_use,
-            scope_helper: self,
-            has_js_bindings: false,
-            is_inline,
-            is_write: false,
-        };
-        expr.visit_mut_with(&mut visitor);
+        let (has_js_bindings, _) = transform_expr_with(self, expr, scope_to_use, false);
+        has_js_bindings
+    }
 
-        visitor.has_js_bindings
+    fn transform_handler_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool {
+        wrap_inline_handler(expr);
+        let (_, uses_template_scope) = transform_expr_with(self, expr, scope_to_use, true);
+        uses_template_scope
     }
 
     fn get_var_binding_type(&mut self, starting_scope: u32, variable: &str) -> BindingTypes {
@@ -123,29 +133,266 @@ impl BindingsHelperTransform for BindingsHelper {
     }
 }
 
+/// Returns whether the expression has js bindings and whether it uses the template scope variables
+fn transform_expr_with(
+    bindings_helper: &mut BindingsHelper,
+    expr: &mut Expr,
+    scope_to_use: u32,
+    is_handler: bool,
+) -> (bool, bool) {
+    let is_inline = matches!(
+        bindings_helper.template_generation_mode,
+        TemplateGenerationMode::Inline
+    );
+    let mut visitor = TransformVisitor {
+        current_scope: scope_to_use,
+        scope_helper: bindings_helper,
+        has_js_bindings: false,
+        is_inline,
+        is_write: false,
+        is_handler,
+        uses_template_scope: false,
+        local_vars: vec![],
+    };
+    expr.visit_mut_with(&mut visitor);
+
+    (visitor.has_js_bindings, visitor.uses_template_scope)
+}
+
+/// Wraps the inline statement handlers into an arrow function,
+/// so that they are evaluated when the event fires and not during render.
+///
+/// ## Examples
+/// - `count++` becomes `$event => (count++)`;
+/// - `onInput($event, id)` becomes `$event => (onInput($event, id))`;
+/// - `handleClick`, `foo.bar` and `() => foo()` are left as-is.
+fn wrap_inline_handler(expr: &mut Expr) {
+    let is_method_or_fn = match expr {
+        Expr::Ident(_) | Expr::Member(_) | Expr::Arrow(_) | Expr::Fn(_) => true,
+        Expr::Paren(paren) => matches!(*paren.expr, Expr::Arrow(_) | Expr::Fn(_)),
+        _ => false,
+    };
+    if is_method_or_fn {
+        return;
+    }
+
+    let span = expr.span();
+    let statement = std::mem::replace(expr, Expr::Invalid(Invalid { span }));
+
+    // `$event => (statement)`
+    *expr = Expr::Arrow(ArrowExpr {
+        span,
+        params: vec![Pat::Ident(BindingIdent {
+            id: Ident {
+                span: DUMMY_SP,
+                sym: FervidAtom::from("$event"),
+                optional: false,
+            },
+            type_ann: None,
+        })],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span,
+            expr: Box::new(statement),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    });
+}
+
+impl TransformVisitor<'_> {
+    /// Resolves the component instance properties, e.g. `$attrs`, `$slots` or `$emit`.
+    /// Returns `None` when the identifier is not an instance property.
+    ///
+    /// ## Examples
+    /// - `$attrs` becomes `_ctx.$attrs`;
+    /// - `$emit` becomes `emit` in the inline mode when there is an `emit` binding,
+    ///   otherwise `_ctx.$emit`;
+    /// - `$event` inside a `v-on` handler is left as-is.
+    fn resolve_instance_property(&mut self, ident: &Ident) -> Option<Expr> {
+        let symbol = &*ident.sym;
+        if !symbol.starts_with('$') {
+            return None;
+        }
+
+        let span = ident.span;
+
+        match symbol {
+            "$event" if self.is_handler => return Some(Expr::Ident(ident.to_owned())),
+
+            "$emit" if self.is_inline => {
+                let has_emit_binding = self
+                    .scope_helper
+                    .setup_bindings
+                    .iter()
+                    .any(|binding| binding.0 == *EMIT);
+
+                if has_emit_binding {
+                    return Some(Expr::Ident(Ident {
+                        span,
+                        sym: EMIT.to_owned(),
+                        optional: false,
+                    }));
+                }
+            }
+
+            "$attrs" | "$data" | "$el" | "$emit" | "$event" | "$options" | "$parent" | "$props"
+            | "$refs" | "$root" | "$slots" | "$watch" | "$forceUpdate" | "$nextTick" => {}
+
+            _ => return None,
+        }
+
+        // `_ctx.$attrs`
+        Some(Expr::Member(MemberExpr {
+            span,
+            obj: Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.scope_helper.ident_prefixes.ctx.to_owned(),
+                optional: false,
+            })),
+            prop: MemberProp::Ident(ident.to_owned()),
+        }))
+    }
+
+    /// Transforms an assignment or an update of a `let` binding in the inline mode.
+    /// Such a binding may or may not hold a ref, thus it needs a runtime check.
+    /// Returns `false` when the expression is not a write to a `let` binding.
+    ///
+    /// ## Examples
+    /// - `foo = bar` becomes `_isRef(foo) ? foo.value = bar : foo = bar`;
+    /// - `foo++` becomes `_isRef(foo) ? foo.value++ : foo++`.
+    fn transform_setup_let_write(&mut self, n: &mut Expr) -> bool {
+        let target = match n {
+            Expr::Assign(assign_expr) => get_assign_target(&assign_expr.left),
+            Expr::Update(update_expr) => update_expr.arg.as_ident(),
+            _ => None,
+        };
+        let Some(target) = target.cloned() else {
+            return false;
+        };
+        if self.local_vars.contains(&target.sym) {
+            return false;
+        }
+
+        let binding_type = self
+            .scope_helper
+            .get_var_binding_type(self.current_scope, &target.sym);
+        if !matches!(binding_type, BindingTypes::SetupLet) {
+            return false;
+        }
+
+        self.has_js_bindings = true;
+
+        // The assigned value is transformed once and shared by both branches
+        if let Expr::Assign(assign_expr) = n {
+            assign_expr.right.visit_mut_with(self);
+        }
+
+        // `foo.value = bar` or `foo.value++`
+        let span = n.span();
+        let mut ref_write = n.to_owned();
+        let ref_value = Box::new(Expr::Member(MemberExpr {
+            span: target.span,
+            obj: Box::new(Expr::Ident(target.to_owned())),
+            prop: MemberProp::Ident(Ident {
+                span: DUMMY_SP,
+                sym: "value".into(),
+                optional: false,
+            }),
+        }));
+        match ref_write {
+            Expr::Assign(ref mut assign_expr) => assign_expr.left = PatOrExpr::Expr(ref_value),
+            Expr::Update(ref mut update_expr) => update_expr.arg = ref_value,
+            _ => unreachable!(),
+        }
+
+        self.scope_helper.vue_imports |= VueImports::IsRef;
+        let non_ref_write = std::mem::replace(n, Expr::Invalid(Invalid { span }));
+        *n = Expr::Cond(CondExpr {
+            span,
+            test: Box::new(Expr::Call(CallExpr {
+                span,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: VueImports::IsRef.as_atom(),
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Ident(target)),
+                }],
+                type_args: None,
+            })),
+            cons: Box::new(ref_write),
+            alt: Box::new(non_ref_write),
+        });
+
+        true
+    }
+}
+
+/// Gets the identifier being assigned to, e.g. `foo` in `foo = bar`
+fn get_assign_target(left: &PatOrExpr) -> Option<&Ident> {
+    match left {
+        PatOrExpr::Expr(expr) => expr.as_ident(),
+        PatOrExpr::Pat(pat) => match **pat {
+            Pat::Ident(ref binding_ident) => Some(&binding_ident.id),
+            Pat::Expr(ref expr) => expr.as_ident(),
+            _ => None,
+        },
+    }
+}
+
 impl<'s> VisitMut for TransformVisitor<'s> {
     fn visit_mut_assign_expr(&mut self, n: &mut swc_core::ecma::ast::AssignExpr) {
-        match n.left {
-            // Assignments must have their LHS correctly handled
-            // Especially `SetupLet`
-            PatOrExpr::Expr(ref e) if matches!(**e, Expr::Ident(_)) => {
-                let old_is_write = self.is_write;
-                self.is_write = true;
-                n.left.visit_mut_with(self);
-                self.is_write = old_is_write;
-                n.right.visit_mut_with(self);
-            }
+        // Assignments must have their LHS correctly handled
+        // Especially `SetupLet`.
+        // Identifier target is normalized to an expression, so that it can be transformed
+        if let Some(target) = get_assign_target(&n.left).cloned() {
+            n.left = PatOrExpr::Expr(Box::new(Expr::Ident(target)));
 
-            _ => n.visit_mut_children_with(self),
+            let old_is_write = self.is_write;
+            self.is_write = true;
+            n.left.visit_mut_with(self);
+            self.is_write = old_is_write;
+            n.right.visit_mut_with(self);
+            return;
         }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_update_expr(&mut self, n: &mut UpdateExpr) {
+        // Update target is written to the same way as the assignment target, e.g. `foo++`
+        let old_is_write = self.is_write;
+        self.is_write = n.arg.is_ident();
+        n.arg.visit_mut_with(self);
+        self.is_write = old_is_write;
     }
 
     fn visit_mut_expr(&mut self, n: &mut Expr) {
+        if self.is_inline && self.transform_setup_let_write(n) {
+            return;
+        }
+
         let Expr::Ident(ident_expr) = n else {
             n.visit_mut_children_with(self);
             return;
         };
 
+        // Arrow parameters shadow any binding with the same name
+        if self.local_vars.contains(&ident_expr.sym) {
+            return;
+        }
+
+        // Instance properties are never prefixed, because they are not bindings
+        if let Some(instance_property) = self.resolve_instance_property(ident_expr) {
+            *n = instance_property;
+            self.has_js_bindings = true;
+            return;
+        }
+
         let symbol = &ident_expr.sym;
         let span = ident_expr.span;
 
@@ -156,11 +403,28 @@ impl<'s> VisitMut for TransformVisitor<'s> {
         // Template local binding doesn't need any processing
         if let BindingTypes::TemplateLocal = binding_type {
             self.has_js_bindings = true;
+            self.uses_template_scope = true;
             return;
         }
 
         // Get the prefix which fits the scope (e.g. `_ctx.` for unknown scopes, `$setup.` for setup scope)
-        if let Some(prefix) = get_prefix(&binding_type, self.is_inline) {
+        let ctx = &self.scope_helper.ident_prefixes.ctx;
+        if let Some(prefix) = get_prefix(&binding_type, self.is_inline, ctx) {
+            // Props aliases are accessed by the original key, e.g. `__props["foo"]`
+            let prop = match self.scope_helper.props_aliases.get(symbol) {
+                Some(original_key) if matches!(binding_type, BindingTypes::PropsAliased) => {
+                    MemberProp::Computed(ComputedPropName {
+                        span,
+                        expr: Box::new(Expr::Lit(Lit::Str(Str {
+                            span,
+                            value: original_key.to_owned(),
+                            raw: None,
+                        }))),
+                    })
+                }
+                _ => MemberProp::Ident(ident_expr.to_owned()),
+            };
+
             *n = Expr::Member(MemberExpr {
                 span,
                 obj: Box::new(Expr::Ident(Ident {
@@ -168,7 +432,7 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                     sym: prefix,
                     optional: false,
                 })),
-                prop: MemberProp::Ident(ident_expr.to_owned()),
+                prop,
             });
             self.has_js_bindings = true;
         }
@@ -178,7 +442,6 @@ impl<'s> VisitMut for TransformVisitor<'s> {
             return;
         }
 
-        // TODO The logic for setup variables actually differs quite significantly
         // https://play.vuejs.org/#eNp9UU1rwzAM/SvCl25QEkZvIRTa0cN22Mq6oy8hUVJ3iW380QWC//tkh2Y7jN6k956kJ2liO62zq0dWsNLWRmgHFp3XWy7FoJVxMMEOArRGDbDK8v2KywZbIfFolLYPE5cArVIFnJwRsuMyPHJZ5nMv6kKJw0H3lUPKAMrz03aaYgmEQAE1D2VOYKxalGzNnK2VbEWXXaySZC9N4qxWgxY9mnfthJKWswISE7mq79X3a8Kc8bi+4fUZ669/8IsdI8bZ0aBFc0XOFs5VpkM304fTG44UL+SgGt+T+g75gVb1PnqcZXsvG7L9R5fcvqQj0+E+7WF0KO1tqWg0KkPSc0Y/er6z+q/dTbZJdfQJFn4A+DKelw==
 
         let dot_value = |expr: &mut Expr, span: Span| {
@@ -222,12 +485,18 @@ impl<'s> VisitMut for TransformVisitor<'s> {
             self.has_js_bindings = true;
         }
 
-        // Inline logic is pretty complex
-        // TODO Actual logic
+        // Inline logic is pretty complex, same as in the official compiler:
+        // - constants are never refs and are accessed as-is;
+        // - refs are always accessed with `.value`;
+        // - maybe-refs are read with `unref` and written with `.value`;
+        // - `let`s are read with `unref`, writes are handled by `transform_setup_let_write`.
         match binding_type {
+            // Assignment target stays as-is, e.g. `[foo] = $event`
+            BindingTypes::SetupLet if self.is_write => {}
             BindingTypes::SetupLet => unref(n, span),
             BindingTypes::SetupConst => {}
             BindingTypes::SetupReactiveConst => {}
+            BindingTypes::SetupMaybeRef if self.is_write => dot_value(n, span),
             BindingTypes::SetupMaybeRef => unref(n, span),
             BindingTypes::SetupRef => dot_value(n, span),
             BindingTypes::LiteralConst => {}
@@ -248,6 +517,20 @@ impl<'s> VisitMut for TransformVisitor<'s> {
     //     }
     // }
 
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        // Parameters are local to the body, e.g. in the function refs `:ref="el => refs.push(el)"`
+        let mut params_scope = TemplateScope {
+            variables: Default::default(),
+            parent: self.current_scope,
+        };
+        collect_variables(&n.params, &mut params_scope);
+
+        let locals_len = self.local_vars.len();
+        self.local_vars.extend(params_scope.variables);
+        n.body.visit_mut_with(self);
+        self.local_vars.truncate(locals_len);
+    }
+
     fn visit_mut_member_expr(&mut self, n: &mut swc_core::ecma::ast::MemberExpr) {
         if n.obj.is_ident() {
             n.obj.visit_mut_with(self)
@@ -292,22 +575,28 @@ impl<'s> VisitMut for TransformVisitor<'s> {
 /// `data()` variable `foo` in non-inline compilation becomes `$data.foo`.\
 /// `setup()` ref variable `bar` in non-inline compilation becomes `$setup.bar`,
 /// but in the inline compilation it remains the same.
-pub fn get_prefix(binding_type: &BindingTypes, is_inline: bool) -> Option<JsWord> {
+///
+/// `ctx` is the render context identifier, `_ctx` unless changed by [`fervid_core::IdentPrefixes`].
+pub fn get_prefix(
+    binding_type: &BindingTypes,
+    is_inline: bool,
+    ctx: &FervidAtom,
+) -> Option<JsWord> {
     // For inline mode, options API variables become prefixed
     if is_inline {
         return match binding_type {
             BindingTypes::Data | BindingTypes::Options | BindingTypes::Unresolved => {
-                Some(FervidAtom::from("_ctx"))
+                Some(ctx.to_owned())
             }
-            BindingTypes::Props => Some(FervidAtom::from("__props")),
-            // TODO This is not correct. The transform implementation must handle `unref`
+            BindingTypes::Props | BindingTypes::PropsAliased => Some(FervidAtom::from("__props")),
+            // Setup bindings are accessible directly, `unref` and `.value` are added by the transform
             _ => None,
         };
     }
 
     match binding_type {
         BindingTypes::Data => Some(FervidAtom::from("$data")),
-        BindingTypes::Props => Some(FervidAtom::from("$props")),
+        BindingTypes::Props | BindingTypes::PropsAliased => Some(FervidAtom::from("$props")),
         BindingTypes::Options => Some(FervidAtom::from("$options")),
         BindingTypes::TemplateLocal | BindingTypes::JsGlobal | BindingTypes::LiteralConst => None,
         BindingTypes::SetupConst
@@ -315,15 +604,20 @@ pub fn get_prefix(binding_type: &BindingTypes, is_inline: bool) -> Option<JsWord
         | BindingTypes::SetupMaybeRef
         | BindingTypes::SetupReactiveConst
         | BindingTypes::SetupRef => Some(FervidAtom::from("$setup")),
-        BindingTypes::Unresolved => Some(FervidAtom::from("_ctx")),
-        BindingTypes::PropsAliased => unimplemented!(),
+        BindingTypes::Unresolved => Some(ctx.to_owned()),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::template::{expr_transform::BindingsHelperTransform, js_builtins::JS_BUILTINS};
-    use fervid_core::{BindingTypes, BindingsHelper, TemplateGenerationMode, TemplateScope, FervidAtom};
+    use crate::{
+        template::{expr_transform::BindingsHelperTransform, js_builtins::JS_BUILTINS},
+        test_utils::{parser::parse_javascript_expr, to_str},
+    };
+    use fervid_core::{
+        BindingTypes, BindingsHelper, FervidAtom, OptionsApiBindings, SetupBinding,
+        TemplateGenerationMode, TemplateScope, VueImports,
+    };
     use smallvec::SmallVec;
 
     #[test]
@@ -517,4 +811,257 @@ mod tests {
 
         println!("Elapsed total: {:?}", st0.elapsed())
     }
+
+    #[test]
+    fn it_resolves_instance_properties() {
+        fn transform(helper: &mut BindingsHelper, raw: &str, is_handler: bool) -> String {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("transform expects the input to be parseable")
+                .0;
+
+            if is_handler {
+                helper.transform_handler_expr(&mut expr, 0);
+            } else {
+                helper.transform_expr(&mut expr, 0);
+            }
+
+            to_str(&*expr)
+        }
+
+        let mut helper = BindingsHelper::default();
+        helper.template_generation_mode = TemplateGenerationMode::Inline;
+
+        assert_eq!(
+            transform(&mut helper, "$attrs.foo", false),
+            "_ctx.$attrs.foo"
+        );
+        assert_eq!(
+            transform(&mut helper, "$slots.default", false),
+            "_ctx.$slots.default"
+        );
+        assert_eq!(
+            transform(&mut helper, "$refs.input", false),
+            "_ctx.$refs.input"
+        );
+        assert_eq!(
+            transform(&mut helper, "$emit('change')", false),
+            r#"_ctx.$emit("change")"#
+        );
+
+        // `$event` is only an argument inside the handlers
+        assert_eq!(
+            transform(&mut helper, "foo($event)", true),
+            "$event=>(_ctx.foo($event))"
+        );
+        assert_eq!(transform(&mut helper, "$event", false), "_ctx.$event");
+
+        // `$emit` maps to the setup `emit`
+        helper.setup_bindings.push(SetupBinding(
+            FervidAtom::from("emit"),
+            BindingTypes::SetupConst,
+        ));
+        assert_eq!(
+            transform(&mut helper, "$emit('change')", true),
+            r#"$event=>(emit("change"))"#
+        );
+
+        // But not in the render function
+        helper.template_generation_mode = TemplateGenerationMode::RenderFn;
+        assert_eq!(
+            transform(&mut helper, "$emit('change')", true),
+            r#"$event=>(_ctx.$emit("change"))"#
+        );
+    }
+
+    #[test]
+    fn it_transforms_bindings_in_render_fn() {
+        fn transform(helper: &mut BindingsHelper, raw: &str) -> String {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("transform expects the input to be parseable")
+                .0;
+            helper.transform_expr(&mut expr, 0);
+            to_str(&*expr)
+        }
+
+        let mut helper = BindingsHelper::default();
+        helper.template_generation_mode = TemplateGenerationMode::RenderFn;
+        helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("count"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("msg"), BindingTypes::Props),
+            SetupBinding(FervidAtom::from("label"), BindingTypes::PropsAliased),
+        ];
+        helper
+            .props_aliases
+            .insert(FervidAtom::from("label"), FervidAtom::from("title"));
+        helper.options_api_bindings = Some(Box::new(OptionsApiBindings {
+            data: vec![FervidAtom::from("foo")],
+            computed: vec![FervidAtom::from("total")],
+            ..Default::default()
+        }));
+
+        assert_eq!(
+            transform(&mut helper, "count + msg"),
+            "$setup.count+$props.msg"
+        );
+        assert_eq!(transform(&mut helper, "label"), r#"$props["title"]"#);
+        assert_eq!(transform(&mut helper, "foo"), "$data.foo");
+        assert_eq!(transform(&mut helper, "total"), "$options.total");
+        assert_eq!(transform(&mut helper, "unknown"), "_ctx.unknown");
+    }
+
+    #[test]
+    fn it_transforms_setup_bindings_inline() {
+        fn transform(helper: &mut BindingsHelper, raw: &str) -> String {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("transform expects the input to be parseable")
+                .0;
+            helper.transform_expr(&mut expr, 0);
+            to_str(&*expr)
+        }
+
+        let mut helper = BindingsHelper::default();
+        helper.template_generation_mode = TemplateGenerationMode::Inline;
+        helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("count"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("maybe"), BindingTypes::SetupMaybeRef),
+            SetupBinding(FervidAtom::from("state"), BindingTypes::SetupReactiveConst),
+            SetupBinding(FervidAtom::from("fn"), BindingTypes::SetupConst),
+            SetupBinding(FervidAtom::from("answer"), BindingTypes::LiteralConst),
+            SetupBinding(FervidAtom::from("value"), BindingTypes::SetupLet),
+        ];
+        helper.options_api_bindings = Some(Box::new(OptionsApiBindings {
+            props: vec![FervidAtom::from("msg")],
+            ..Default::default()
+        }));
+
+        // Reads
+        assert_eq!(transform(&mut helper, "count + 1"), "count.value+1");
+        assert_eq!(transform(&mut helper, "maybe.foo"), "_unref(maybe).foo");
+        assert_eq!(transform(&mut helper, "state.foo"), "state.foo");
+        assert_eq!(transform(&mut helper, "fn(answer)"), "fn(answer)");
+        assert_eq!(transform(&mut helper, "value"), "_unref(value)");
+        assert_eq!(transform(&mut helper, "msg"), "__props.msg");
+        assert_eq!(transform(&mut helper, "unknown"), "_ctx.unknown");
+
+        // Writes
+        assert_eq!(transform(&mut helper, "count = 1"), "count.value=1");
+        assert_eq!(
+            transform(&mut helper, "maybe = count"),
+            "maybe.value=count.value"
+        );
+        assert_eq!(transform(&mut helper, "maybe++"), "maybe.value++");
+        assert_eq!(
+            transform(&mut helper, "value = count"),
+            "_isRef(value)?value.value=count.value:value=count.value"
+        );
+        assert_eq!(
+            transform(&mut helper, "value++"),
+            "_isRef(value)?value.value++:value++"
+        );
+        assert!(helper.vue_imports.contains(VueImports::IsRef));
+    }
+
+    #[test]
+    fn it_keeps_arrow_params_local() {
+        fn transform(helper: &mut BindingsHelper, raw: &str) -> String {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("transform expects the input to be parseable")
+                .0;
+            helper.transform_expr(&mut expr, 0);
+            to_str(&*expr)
+        }
+
+        let mut helper = BindingsHelper::default();
+        helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("refs"), BindingTypes::SetupConst),
+            SetupBinding(FervidAtom::from("el"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("value"), BindingTypes::SetupLet),
+        ];
+
+        // Function refs
+        assert_eq!(
+            transform(&mut helper, "el => refs.push(el)"),
+            "el=>$setup.refs.push(el)"
+        );
+        assert_eq!(
+            transform(&mut helper, "({ $el: node }, i) => refs[i] = node"),
+            "({$el:node},i)=>$setup.refs[i]=node"
+        );
+
+        // Parameters are only local to their arrow
+        assert_eq!(
+            transform(&mut helper, "[(el) => el, el]"),
+            "[el=>el,$setup.el]"
+        );
+
+        helper.template_generation_mode = TemplateGenerationMode::Inline;
+        assert_eq!(
+            transform(&mut helper, "(node) => el = node"),
+            "node=>el.value=node"
+        );
+        assert_eq!(
+            transform(&mut helper, "(value) => value = 1"),
+            "value=>value=1"
+        );
+    }
+
+    #[test]
+    fn it_wraps_inline_handlers() {
+        fn transform(helper: &mut BindingsHelper, raw: &str) -> String {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("transform expects the input to be parseable")
+                .0;
+            helper.transform_handler_expr(&mut expr, 0);
+            to_str(&*expr)
+        }
+
+        let mut helper = BindingsHelper::default();
+        helper.setup_bindings = vec![
+            SetupBinding(FervidAtom::from("count"), BindingTypes::SetupRef),
+            SetupBinding(FervidAtom::from("onInput"), BindingTypes::SetupConst),
+            SetupBinding(FervidAtom::from("id"), BindingTypes::SetupConst),
+        ];
+
+        // Inline statements
+        assert_eq!(
+            transform(&mut helper, "count++"),
+            "$event=>($setup.count++)"
+        );
+        assert_eq!(
+            transform(&mut helper, "onInput($event, id)"),
+            "$event=>($setup.onInput($event,$setup.id))"
+        );
+
+        // Method handlers and functions
+        assert_eq!(transform(&mut helper, "onInput"), "$setup.onInput");
+        assert_eq!(transform(&mut helper, "foo.bar"), "_ctx.foo.bar");
+        assert_eq!(
+            transform(&mut helper, "() => onInput()"),
+            "()=>$setup.onInput()"
+        );
+
+        // Inline mode
+        helper.template_generation_mode = TemplateGenerationMode::Inline;
+        assert_eq!(transform(&mut helper, "count++"), "$event=>(count.value++)");
+    }
+
+    #[test]
+    fn it_detects_template_scope_in_handlers() {
+        let mut helper = BindingsHelper::default();
+        helper.template_scopes.push(TemplateScope {
+            parent: 0,
+            variables: SmallVec::from([FervidAtom::from("item")]),
+        });
+
+        let mut uses_template_scope = |raw: &str| {
+            let mut expr = parse_javascript_expr(raw, 0, Default::default())
+                .expect("expects the input to be parseable")
+                .0;
+            helper.transform_handler_expr(&mut expr, 0)
+        };
+
+        assert!(uses_template_scope("select(item)"));
+        assert!(!uses_template_scope("select($event)"));
+        assert!(!uses_template_scope("handleClick"));
+    }
 }
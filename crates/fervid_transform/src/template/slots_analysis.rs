@@ -0,0 +1,291 @@
+//! Analysis of the component slots, used to mark the slots object with `_: 1` (stable),
+//! `_: 2` (dynamic) or `_: 3` (forwarded), and the component with `DYNAMIC_SLOTS`.
+
+use fervid_core::{
+    AttributeOrBinding, ElementNode, FervidAtom, Node, StartingTag, StrOrExpr, VOnDirective,
+    VSlotDirective,
+};
+use swc_core::ecma::{
+    ast::{Expr, Prop},
+    visit::{Visit, VisitWith},
+};
+
+/// Whether the slots of a component change between renders:
+/// - a slot name is dynamic, e.g. `<template #[name]>`;
+/// - a slot is conditional or looped, e.g. `<template v-if="foo" #foo>`;
+/// - the slot content references `outer_variables`, i.e. the `v-for` or `v-slot` variables
+///   from outside the component.
+pub fn has_dynamic_slots(element_node: &ElementNode, outer_variables: &[FervidAtom]) -> bool {
+    let is_dynamic_v_slot = |starting_tag: &StartingTag, is_template: bool| {
+        starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| {
+                let Some(VSlotDirective { ref slot_name, .. }) = directives.v_slot else {
+                    return false;
+                };
+
+                matches!(slot_name, Some(StrOrExpr::Expr(_)))
+                    || (is_template
+                        && (directives.v_if.is_some()
+                            || directives.v_else_if.is_some()
+                            || directives.v_else.is_some()
+                            || directives.v_for.is_some()))
+            })
+    };
+
+    let has_dynamic_v_slot = is_dynamic_v_slot(&element_node.starting_tag, false)
+        || element_node.children.iter().any(|child| match child {
+            Node::Element(child) => {
+                child.starting_tag.tag_name == "template"
+                    && is_dynamic_v_slot(&child.starting_tag, true)
+            }
+            // Already folded `<template v-if="foo" #foo>`
+            Node::ConditionalSeq(conditional_seq) => {
+                let if_node = &conditional_seq.if_node.node;
+                if_node.starting_tag.tag_name == "template"
+                    && if_node
+                        .starting_tag
+                        .directives
+                        .as_ref()
+                        .map_or(false, |directives| directives.v_slot.is_some())
+            }
+            _ => false,
+        });
+
+    has_dynamic_v_slot
+        || (!outer_variables.is_empty() && has_scope_ref(&element_node.children, outer_variables))
+}
+
+/// Whether the slot content renders a `<slot>`, thus forwarding the slots of the parent
+pub fn has_forwarded_slots(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(element_node) => is_or_has_forwarded_slot(element_node),
+        Node::ConditionalSeq(conditional_seq) => {
+            is_or_has_forwarded_slot(&conditional_seq.if_node.node)
+                || conditional_seq
+                    .else_if_nodes
+                    .iter()
+                    .any(|else_if| is_or_has_forwarded_slot(&else_if.node))
+                || conditional_seq
+                    .else_node
+                    .as_ref()
+                    .map_or(false, |else_node| is_or_has_forwarded_slot(else_node))
+        }
+        _ => false,
+    })
+}
+
+fn is_or_has_forwarded_slot(element_node: &ElementNode) -> bool {
+    element_node.starting_tag.tag_name == "slot" || has_forwarded_slots(&element_node.children)
+}
+
+/// Whether any expression in the nodes references one of the `variables`.
+/// Same as in the official compiler, only the names are compared and shadowing is not considered.
+fn has_scope_ref(nodes: &[Node], variables: &[FervidAtom]) -> bool {
+    let mut finder = VariableFinder {
+        variables,
+        found: false,
+    };
+
+    for node in nodes {
+        finder.visit_node(node);
+        if finder.found {
+            return true;
+        }
+    }
+
+    false
+}
+
+struct VariableFinder<'v> {
+    variables: &'v [FervidAtom],
+    found: bool,
+}
+
+impl VariableFinder<'_> {
+    fn visit_node(&mut self, node: &Node) {
+        match node {
+            Node::Element(element_node) => self.visit_element_node(element_node),
+            Node::Interpolation(interpolation) => interpolation.value.visit_with(self),
+            Node::ConditionalSeq(conditional_seq) => {
+                conditional_seq.if_node.condition.visit_with(self);
+                self.visit_element_node(&conditional_seq.if_node.node);
+                for else_if in conditional_seq.else_if_nodes.iter() {
+                    else_if.condition.visit_with(self);
+                    self.visit_element_node(&else_if.node);
+                }
+                if let Some(ref else_node) = conditional_seq.else_node {
+                    self.visit_element_node(else_node);
+                }
+            }
+            Node::Text(_, _) | Node::Comment(_, _) => {}
+        }
+    }
+
+    fn visit_element_node(&mut self, element_node: &ElementNode) {
+        let starting_tag = &element_node.starting_tag;
+
+        macro_rules! visit_str_or_expr {
+            ($str_or_expr: expr) => {
+                if let Some(StrOrExpr::Expr(expr)) = &$str_or_expr {
+                    expr.visit_with(self);
+                }
+            };
+        }
+
+        for attr in starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::VBind(v_bind) => {
+                    visit_str_or_expr!(v_bind.argument);
+                    v_bind.value.visit_with(self);
+                }
+                AttributeOrBinding::VOn(VOnDirective { event, handler, .. }) => {
+                    visit_str_or_expr!(event);
+                    if let Some(handler) = handler {
+                        handler.visit_with(self);
+                    }
+                }
+                AttributeOrBinding::RegularAttribute { .. } => {}
+            }
+        }
+
+        if let Some(ref directives) = starting_tag.directives {
+            let exprs = [
+                &directives.v_else_if,
+                &directives.v_html,
+                &directives.v_if,
+                &directives.v_memo,
+                &directives.v_show,
+                &directives.v_text,
+            ];
+            for expr in exprs.into_iter().flatten() {
+                expr.visit_with(self);
+            }
+
+            if let Some(ref v_for) = directives.v_for {
+                v_for.iterable.visit_with(self);
+            }
+
+            for v_model in directives.v_model.iter() {
+                visit_str_or_expr!(v_model.argument);
+                v_model.value.visit_with(self);
+            }
+
+            for custom in directives.custom.iter() {
+                visit_str_or_expr!(custom.argument);
+                if let Some(ref value) = custom.value {
+                    value.visit_with(self);
+                }
+            }
+
+            if let Some(ref v_slot) = directives.v_slot {
+                visit_str_or_expr!(v_slot.slot_name);
+            }
+        }
+
+        for child in element_node.children.iter() {
+            self.visit_node(child);
+        }
+    }
+}
+
+impl Visit for VariableFinder<'_> {
+    fn visit_expr(&mut self, n: &Expr) {
+        if let Expr::Ident(ident) = n {
+            self.found |= self.variables.contains(&ident.sym);
+            return;
+        }
+
+        n.visit_children_with(self);
+    }
+
+    // `{ item }`
+    fn visit_prop(&mut self, n: &Prop) {
+        if let Prop::Shorthand(ident) = n {
+            self.found |= self.variables.contains(&ident.sym);
+            return;
+        }
+
+        n.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{ElementKind, Interpolation, VueDirectives};
+    use swc_core::common::DUMMY_SP;
+
+    use super::*;
+    use crate::test_utils::parser::parse_javascript_expr;
+
+    fn element(tag_name: &str, children: Vec<Node>) -> ElementNode {
+        ElementNode {
+            starting_tag: StartingTag {
+                tag_name: tag_name.into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children,
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        }
+    }
+
+    fn interpolation(raw: &str) -> Node {
+        Node::Interpolation(Interpolation {
+            value: parse_javascript_expr(raw, 0, Default::default()).unwrap().0,
+            template_scope: 0,
+            patch_flag: false,
+        })
+    }
+
+    #[test]
+    fn it_detects_scope_refs() {
+        // <my-comp>{{ item.name }}</my-comp>
+        let component = element("my-comp", vec![interpolation("item.name")]);
+        assert!(has_dynamic_slots(&component, &["item".into()]));
+        assert!(!has_dynamic_slots(&component, &["name".into()]));
+        assert!(!has_dynamic_slots(&component, &[]));
+
+        // <my-comp>{{ { item } }}</my-comp>
+        let component = element("my-comp", vec![interpolation("{ item }")]);
+        assert!(has_dynamic_slots(&component, &["item".into()]));
+    }
+
+    #[test]
+    fn it_detects_conditional_slots() {
+        // <my-comp><template v-if="foo" #foo>hi</template></my-comp>
+        let mut template = element("template", vec![Node::Text("hi".into(), DUMMY_SP)]);
+        template.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_if: Some(
+                parse_javascript_expr("foo", 0, Default::default())
+                    .unwrap()
+                    .0,
+            ),
+            v_slot: Some(VSlotDirective {
+                slot_name: Some("foo".into()),
+                value: None,
+            }),
+            ..Default::default()
+        }));
+        let component = element("my-comp", vec![Node::Element(template)]);
+        assert!(has_dynamic_slots(&component, &[]));
+    }
+
+    #[test]
+    fn it_detects_forwarded_slots() {
+        // <my-comp><div><slot /></div></my-comp>
+        let children = vec![Node::Element(element(
+            "div",
+            vec![Node::Element(element("slot", vec![]))],
+        ))];
+        assert!(has_forwarded_slots(&children));
+        assert!(!has_forwarded_slots(&[Node::Element(element(
+            "div",
+            vec![]
+        ))]));
+    }
+}
@@ -5,9 +5,213 @@ extern crate wee_alloc;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 use wasm_bindgen::prelude::*;
-use fervid::compile_sync_naive;
+use fervid::{
+    compile_sync_naive, parse_es_version, CompileOptions, EmitOptions, FervidAtom, IdentPrefixes,
+    StyleInjector,
+};
+
+/// Result of [`compile_sync`]
+#[wasm_bindgen(getter_with_clone)]
+pub struct CompileResult {
+    /// The compiled module
+    pub code: String,
+    /// TypeScript declaration of the component (the `.d.ts` content),
+    /// present when `declaration` was set
+    pub declaration: Option<String>,
+    /// The compiled `<style>` blocks in order of appearance as [`CompiledStyleBlock`]s,
+    /// empty unless `compile_styles` was set
+    pub styles: Box<[JsValue]>,
+    /// All the compiled styles merged into one stylesheet, present when `merge_styles` was set
+    pub merged_style: Option<String>,
+}
+
+/// A compiled `<style>` block, see [`CompileResult::styles`]
+#[wasm_bindgen(getter_with_clone)]
+pub struct CompiledStyleBlock {
+    /// The compiled CSS
+    pub code: String,
+    /// Whether the block is `<style scoped>`
+    pub scoped: bool,
+    /// Name of the CSS module binding, e.g. `$style` for `<style module>`
+    pub module: Option<String>,
+}
+
+/// Names and prefixes of the generated identifiers,
+/// they can be changed to avoid collisions with the user code
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct IdentPrefixesOptions {
+    /// Render context, e.g. `_ctx.foo`
+    pub ctx: String,
+    /// Render cache, e.g. `_cache[0]`
+    pub cache: String,
+    /// Prefix of the runtime-resolved components, e.g. `_component_my_comp`
+    pub component: String,
+    /// Prefix of the runtime-resolved directives, e.g. `_directive_focus`
+    pub directive: String,
+    /// Prefix of the hoisted values, e.g. `_hoisted_1`
+    pub hoisted: String,
+}
+
+#[wasm_bindgen]
+impl IdentPrefixesOptions {
+    /// Creates the prefixes with the same defaults as in the official compiler
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> IdentPrefixesOptions {
+        IdentPrefixesOptions::default()
+    }
+}
+
+impl Default for IdentPrefixesOptions {
+    fn default() -> Self {
+        let defaults = IdentPrefixes::default();
+        Self {
+            ctx: defaults.ctx.to_string(),
+            cache: defaults.cache.to_string(),
+            component: defaults.component.to_string(),
+            directive: defaults.directive.to_string(),
+            hoisted: defaults.hoisted.to_string(),
+        }
+    }
+}
+
+/// Function which injects the CSS at runtime, called with the CSS string
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct StyleInjectorOptions {
+    /// Name of the function, e.g. `useStyleTag`
+    pub name: String,
+    /// Module to import the function from, e.g. `@vueuse/core`.
+    /// Without it, the function is a global provided by the environment
+    pub source: Option<String>,
+}
 
 #[wasm_bindgen]
-pub fn compile_sync(source: &str, is_prod: Option<bool>) -> Result<String, String> {
-    compile_sync_naive(source, is_prod.unwrap_or(false))
+impl StyleInjectorOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: String, source: Option<String>) -> StyleInjectorOptions {
+        StyleInjectorOptions { name, source }
+    }
+}
+
+/// Options of [`compile_sync`], all of them are off by default
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Default)]
+pub struct CompileSyncOptions {
+    /// Generate the production code
+    pub is_prod: bool,
+    /// Name of the compiled file
+    pub filename: Option<String>,
+    /// Scope id used for `<style scoped>` and the CSS variables
+    pub scope_id: Option<String>,
+    /// Tag names of the native custom elements,
+    /// a name ending with `*` matches by prefix, e.g. `"ion-*"`
+    pub custom_elements: Option<Box<[JsValue]>>,
+    /// Generate the TypeScript declaration of the component
+    pub declaration: bool,
+    /// Names and prefixes of the generated identifiers
+    pub ident_prefixes: Option<IdentPrefixesOptions>,
+    /// Compile the `<style>` blocks
+    pub compile_styles: bool,
+    /// Minify the compiled styles
+    pub minify_styles: bool,
+    /// Inject the compiled styles at runtime using this function
+    pub style_injector: Option<StyleInjectorOptions>,
+    /// Merge all the compiled styles into one stylesheet
+    pub merge_styles: bool,
+    /// Keep the JSX of `lang="jsx"` and `lang="tsx"` scripts in the output,
+    /// without it JSX in a script is an error
+    pub preserve_jsx: bool,
+    /// ES version of the output, e.g. `"es2015"` or `"esnext"` (default).
+    /// The newer syntax is downleveled, e.g. `a?.b` for the targets below `"es2020"`
+    pub target: Option<String>,
+}
+
+#[wasm_bindgen]
+impl CompileSyncOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CompileSyncOptions {
+        CompileSyncOptions::default()
+    }
+}
+
+#[wasm_bindgen]
+pub fn compile_sync(
+    source: &str,
+    options: Option<CompileSyncOptions>,
+) -> Result<CompileResult, String> {
+    let options = options.unwrap_or_default();
+    let target = match options.target.as_deref() {
+        Some(target) => {
+            parse_es_version(target).ok_or_else(|| format!("Unknown target: {target}"))?
+        }
+        None => EmitOptions::default().target,
+    };
+
+    compile_sync_naive(
+        source,
+        CompileOptions {
+            is_prod: options.is_prod,
+            filename: options.filename.as_deref(),
+            scope_id: options.scope_id.as_deref().unwrap_or_default(),
+            source_map: false,
+            cancellation_token: Default::default(),
+            cache_handlers: false,
+            hoist_static: false,
+            keep_style_string: false,
+            ssr: false,
+            hmr: None,
+            module_format: Default::default(),
+            runtime_module: None,
+            comments: None,
+            custom_elements: options
+                .custom_elements
+                .iter()
+                .flat_map(|custom_elements| custom_elements.iter())
+                .filter_map(|tag| tag.as_string())
+                .map(FervidAtom::from)
+                .collect(),
+            declaration: options.declaration,
+            template_generation_mode: None,
+            emit_options: EmitOptions {
+                target,
+                ..Default::default()
+            },
+            ident_prefixes: options.ident_prefixes.map_or_else(
+                IdentPrefixes::default,
+                |prefixes| IdentPrefixes {
+                    ctx: FervidAtom::from(prefixes.ctx),
+                    cache: FervidAtom::from(prefixes.cache),
+                    component: FervidAtom::from(prefixes.component),
+                    directive: FervidAtom::from(prefixes.directive),
+                    hoisted: FervidAtom::from(prefixes.hoisted),
+                },
+            ),
+            style_preprocessor: None,
+            compile_styles: options.compile_styles,
+            style_injector: options.style_injector.map(|style_injector| StyleInjector {
+                name: FervidAtom::from(style_injector.name),
+                source: style_injector.source.map(FervidAtom::from),
+            }),
+            merge_styles: options.merge_styles,
+            minify_styles: options.minify_styles,
+            preserve_jsx: options.preserve_jsx,
+        },
+    )
+    .map(|result| CompileResult {
+        code: result.code,
+        declaration: result.declaration,
+        styles: result
+            .styles
+            .into_iter()
+            .map(|style| {
+                JsValue::from(CompiledStyleBlock {
+                    code: style.code,
+                    scoped: style.scoped,
+                    module: style.module.map(|module| module.to_string()),
+                })
+            })
+            .collect(),
+        merged_style: result.merged_style.map(|merged_style| merged_style.code),
+    })
 }
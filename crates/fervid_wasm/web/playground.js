@@ -1,4 +1,4 @@
-import init, { compile_sync } from '../pkg/fervid_wasm.js'
+import init, { compile_sync, CompileSyncOptions } from '../pkg/fervid_wasm.js'
 import * as monaco from 'https://cdn.jsdelivr.net/npm/monaco-editor@0.41.0/esm/vs/editor/editor.main.js/+esm'
 
 const INITIAL =
@@ -64,7 +64,9 @@ function mountEditor () {
 
 function compileAndTime () {
     const start = performance.now()
-    const result = compile_sync(value, is_prod)
+    const options = new CompileSyncOptions()
+    options.is_prod = is_prod
+    const result = compile_sync(value, options).code
     const end = performance.now()
 
     outputTimeElement.textContent = `${((end - start) * 1000).toFixed(0)}µs ${isTimeInitial ? '(cold)' : ''}`